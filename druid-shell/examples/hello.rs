@@ -14,17 +14,19 @@
 
 use std::any::Any;
 
-use druid_shell::kurbo::{Line, Rect, Vec2};
+use druid_shell::kurbo::{Line, Rect};
 use druid_shell::piet::{Color, RenderContext};
 
 use druid_shell::application::Application;
 use druid_shell::dialog::{FileDialogOptions, FileSpec};
 use druid_shell::hotkey::{HotKey, SysMods};
-use druid_shell::keyboard::{KeyEvent, KeyModifiers};
+use druid_shell::keyboard::KeyEvent;
 use druid_shell::menu::Menu;
 use druid_shell::platform::WindowBuilder;
 use druid_shell::runloop;
-use druid_shell::window::{Cursor, MouseEvent, TimerToken, WinCtx, WinHandler, WindowHandle};
+use druid_shell::window::{
+    Cursor, MouseEvent, TimerToken, WheelEvent, WinCtx, WinHandler, WindowHandle,
+};
 
 const BG_COLOR: Color = Color::rgb8(0x27, 0x28, 0x22);
 const FG_COLOR: Color = Color::rgb8(0xf0, 0xf0, 0xea);
@@ -74,8 +76,8 @@ impl WinHandler for HelloState {
         false
     }
 
-    fn wheel(&mut self, delta: Vec2, mods: KeyModifiers, _ctx: &mut dyn WinCtx) {
-        println!("mouse_wheel {:?} {:?}", delta, mods);
+    fn wheel(&mut self, event: &WheelEvent, _ctx: &mut dyn WinCtx) {
+        println!("mouse_wheel {:?}", event);
     }
 
     fn mouse_move(&mut self, event: &MouseEvent, ctx: &mut dyn WinCtx) {
@@ -0,0 +1,76 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Android implementation of features at the application scope.
+
+use std::cell::RefCell;
+use std::path::Path;
+
+use log::warn;
+
+use crate::clipboard::ClipboardItem;
+
+thread_local! {
+    /// The clipboard used for copying and pasting within the application.
+    ///
+    /// The system clipboard is only reachable through Java, which native
+    /// activities can't call without JNI.
+    static CLIPBOARD: RefCell<Option<ClipboardItem>> = RefCell::new(None);
+}
+
+pub struct Application;
+
+impl Application {
+    /// Finish the activity.
+    pub fn quit() {
+        crate::runloop::request_quit();
+    }
+
+    //TODO: both of these need an `Intent`, which means going through JNI.
+    pub fn open_url(url: &str) {
+        warn!("cannot open url '{}': not yet supported on Android", url);
+    }
+
+    pub fn reveal_in_file_manager(path: &Path) {
+        warn!(
+            "cannot reveal '{}': no file manager on Android",
+            path.display()
+        );
+    }
+
+    //TODO: this is in `AccessibilityManager`, which needs JNI.
+    pub fn is_high_contrast() -> bool {
+        false
+    }
+
+    //TODO: this is the animator duration scale, which needs JNI.
+    pub fn prefers_reduced_motion() -> bool {
+        false
+    }
+
+    /// Application fonts are not yet supported; this does nothing.
+    pub fn register_font(_data: &[u8]) {
+        warn!("registering fonts is not yet supported on Android");
+    }
+
+    /// Returns the contents of the clipboard, if any.
+    pub fn get_clipboard_contents() -> Option<ClipboardItem> {
+        CLIPBOARD.with(|clipboard| clipboard.borrow().clone())
+    }
+
+    /// Sets the contents of the clipboard.
+    pub fn set_clipboard_contents(item: ClipboardItem) {
+        CLIPBOARD.with(|clipboard| *clipboard.borrow_mut() = Some(item));
+    }
+}
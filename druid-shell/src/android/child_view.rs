@@ -0,0 +1,51 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Native child views, which are not supported on Android.
+
+use raw_window_handle::android::AndroidHandle;
+use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+
+use crate::kurbo::Rect;
+use crate::Error;
+
+use super::WindowHandle;
+
+/// A native view shown inside a druid window.
+///
+/// There is no way to create one on this platform; the type exists so
+/// that code using child views builds everywhere.
+pub struct ChildView {
+    _private: (),
+}
+
+impl ChildView {
+    pub fn new_surface() -> Result<ChildView, Error> {
+        Err(Error::Null)
+    }
+
+    pub fn attach(&self, _parent: &WindowHandle) {}
+
+    pub fn set_frame(&self, _frame: Rect) {}
+
+    pub fn set_visible(&self, _visible: bool) {}
+
+    pub fn detach(&self) {}
+}
+
+unsafe impl HasRawWindowHandle for ChildView {
+    fn raw_window_handle(&self) -> RawWindowHandle {
+        RawWindowHandle::Android(AndroidHandle::empty())
+    }
+}
@@ -0,0 +1,53 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Android implementation of menus.
+//!
+//! Android activities have no menu bar; menus are accepted so that applications
+//! build unchanged, but are never shown.
+
+use crate::hotkey::HotKey;
+use crate::image::RgbaImage;
+
+#[derive(Default)]
+pub struct Menu;
+
+impl Menu {
+    pub fn new() -> Menu {
+        Menu
+    }
+
+    pub fn new_for_popup() -> Menu {
+        Menu
+    }
+
+    pub fn add_dropdown(&mut self, _menu: Menu, _text: &str, _enabled: bool) {}
+
+    pub fn add_item(
+        &mut self,
+        _id: u32,
+        _text: &str,
+        _key: Option<&HotKey>,
+        _enabled: bool,
+        _selected: bool,
+    ) {
+    }
+
+    pub fn add_separator(&mut self) {}
+
+    /// Set the icon shown beside an item already added to this menu.
+    ///
+    /// Menu items have no icons on this platform, so this does nothing.
+    pub fn set_item_icon(&mut self, _id: u32, _icon: &RgbaImage) {}
+}
@@ -0,0 +1,878 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Android implementation of window creation.
+//!
+//! This is built on [`ndk-glue`], which runs the application's `main` on the
+//! main thread of a `NativeActivity`; applications should mark their entry
+//! point with `#[cfg_attr(target_os = "android", ndk_glue::main)]`.
+//!
+//! An activity has a single surface, so only one window is visible at a
+//! time: the one most recently shown. Touches are delivered as left mouse
+//! button events, for the first finger only. Hardware and soft keyboard key
+//! presses are delivered as key events, using a US layout to produce text;
+//! composed input from an input method needs an `InputConnection`, which is
+//! only available through Java.
+//!
+//! [`ndk-glue`]: https://docs.rs/ndk-glue
+
+pub mod application;
+mod child_view;
+pub mod menu;
+pub mod util;
+#[cfg(feature = "video")]
+mod video;
+#[cfg(feature = "webview")]
+mod webview;
+pub mod win_main;
+
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::ffi::{c_void, OsString};
+use std::mem;
+use std::ptr::null_mut;
+use std::rc::{Rc, Weak};
+use std::sync::{Arc, Mutex, Weak as ArcWeak};
+use std::time::{Duration, Instant};
+
+use cairo::{Format, ImageSurface};
+use log::{error, warn};
+use ndk::looper::ForeignLooper;
+use ndk_glue::Event as ActivityEvent;
+use ndk_sys::{
+    AInputEvent, AInputEvent_getType, AInputQueue_finishEvent, AInputQueue_getEvent,
+    AInputQueue_preDispatchEvent, AKeyEvent_getAction, AKeyEvent_getKeyCode,
+    AKeyEvent_getMetaState, AKeyEvent_getRepeatCount, AMotionEvent_getAction,
+    AMotionEvent_getMetaState, AMotionEvent_getX, AMotionEvent_getY, ANativeActivity_hideSoftInput,
+    ANativeActivity_showSoftInput, ANativeWindow, ANativeWindow_Buffer, ANativeWindow_getHeight,
+    ANativeWindow_getWidth, ANativeWindow_lock, ANativeWindow_setBuffersGeometry,
+    ANativeWindow_unlockAndPost,
+};
+
+use piet_common::{Piet, RenderContext};
+use raw_window_handle::android::AndroidHandle;
+use raw_window_handle::RawWindowHandle;
+
+use crate::kurbo::{Point, Rect};
+
+use crate::clipboard::ClipboardItem;
+use crate::dialog::{FileDialogOptions, FileDialogType};
+use crate::dnd::DragContents;
+use crate::keyboard::{KeyCode, KeyEvent, KeyModifiers};
+use crate::platform::application::Application;
+use crate::print::{PageSetup, Printable};
+use crate::window::{
+    Cursor, FileInfo, IdleToken, MouseButton, MouseEvent, Text, TimerToken, WinCtx, WinHandler,
+};
+use crate::Error;
+
+pub use child_view::ChildView;
+pub use menu::Menu;
+#[cfg(feature = "video")]
+pub use video::VideoDecoder;
+#[cfg(feature = "webview")]
+pub use webview::WebView;
+
+use util::assert_main_thread;
+
+// TODO: remove these when they get added to ndk-sys
+const AINPUT_EVENT_TYPE_KEY: i32 = 1;
+const AINPUT_EVENT_TYPE_MOTION: i32 = 2;
+const AKEY_EVENT_ACTION_DOWN: i32 = 0;
+const AKEY_EVENT_ACTION_UP: i32 = 1;
+const AMOTION_EVENT_ACTION_MASK: i32 = 0xff;
+const AMOTION_EVENT_ACTION_DOWN: i32 = 0;
+const AMOTION_EVENT_ACTION_UP: i32 = 1;
+const AMOTION_EVENT_ACTION_MOVE: i32 = 2;
+const AMOTION_EVENT_ACTION_CANCEL: i32 = 3;
+const AMETA_SHIFT_ON: i32 = 0x01;
+const AMETA_ALT_ON: i32 = 0x02;
+const AMETA_CTRL_ON: i32 = 0x1000;
+const AMETA_META_ON: i32 = 0x10000;
+const ANATIVEACTIVITY_SHOW_SOFT_INPUT_IMPLICIT: u32 = 0x01;
+const WINDOW_FORMAT_RGBX_8888: i32 = 2;
+
+/// How long to wait before retrying idle callbacks that couldn't run
+/// because the window handler was in use.
+const IDLE_RETRY: Duration = Duration::from_millis(10);
+
+thread_local! {
+    /// The state of every open window, with the visible one last.
+    ///
+    /// This is what keeps the state alive; everything else holds weak
+    /// references.
+    static WINDOWS: RefCell<Vec<Rc<WindowState>>> = RefCell::new(Vec::new());
+
+    /// Pending timers, in no particular order.
+    static TIMERS: RefCell<Vec<Timer>> = RefCell::new(Vec::new());
+}
+
+#[derive(Clone, Default)]
+pub struct WindowHandle {
+    state: Weak<WindowState>,
+}
+
+/// Builder abstraction for creating new windows.
+pub struct WindowBuilder {
+    handler: Option<Box<dyn WinHandler>>,
+}
+
+#[derive(Clone)]
+pub struct IdleHandle {
+    idle_queue: ArcWeak<Mutex<Vec<IdleKind>>>,
+    looper: ForeignLooper,
+}
+
+// TODO: move this out of platform-dependent section.
+trait IdleCallback: Send {
+    fn call(self: Box<Self>, a: &dyn Any);
+}
+
+impl<F: FnOnce(&dyn Any) + Send> IdleCallback for F {
+    fn call(self: Box<F>, a: &dyn Any) {
+        (*self)(a)
+    }
+}
+
+/// An entry in a window's idle queue.
+enum IdleKind {
+    Callback(Box<dyn IdleCallback>),
+    Token(IdleToken),
+}
+
+/// The state associated with each window.
+struct WindowState {
+    handler: RefCell<Box<dyn WinHandler>>,
+    idle_queue: Arc<Mutex<Vec<IdleKind>>>,
+    needs_paint: Cell<bool>,
+}
+
+struct Timer {
+    deadline: Instant,
+    window: WindowHandle,
+    token: TimerToken,
+}
+
+struct WinCtxImpl<'a> {
+    handle: &'a WindowHandle,
+    text: Text<'static>,
+}
+
+impl WindowBuilder {
+    pub fn new() -> WindowBuilder {
+        WindowBuilder { handler: None }
+    }
+
+    pub fn set_handler(&mut self, handler: Box<dyn WinHandler>) {
+        self.handler = Some(handler);
+    }
+
+    /// The activity's title comes from its manifest.
+    pub fn set_title(&mut self, _title: impl Into<String>) {}
+
+    /// Menus are not shown on Android.
+    pub fn set_menu(&mut self, _menu: Menu) {}
+
+    /// There is no pointer to move without touching the screen.
+    pub fn set_enable_mouse_move_events(&mut self, _to: bool) {}
+
+    /// The activity has a single surface, so windows can't be embedded.
+    pub fn set_parent(&mut self, parent: RawWindowHandle) {
+        warn!("unsupported parent window handle: {:?}", parent);
+    }
+
+    pub fn build(self) -> Result<WindowHandle, Error> {
+        assert_main_thread();
+        let handler = self
+            .handler
+            .expect("Tried to build a window without setting the handler");
+
+        let state = Rc::new(WindowState {
+            handler: RefCell::new(handler),
+            idle_queue: Arc::new(Mutex::new(Vec::new())),
+            needs_paint: Cell::new(false),
+        });
+        let handle = WindowHandle {
+            state: Rc::downgrade(&state),
+        };
+        WINDOWS.with(|windows| windows.borrow_mut().insert(0, state.clone()));
+
+        state
+            .handler
+            .borrow_mut()
+            .connect(&crate::window::WindowHandle {
+                inner: handle.clone(),
+            });
+
+        Ok(handle)
+    }
+}
+
+/// The handle of the visible window, if any.
+fn front_window() -> Option<WindowHandle> {
+    WINDOWS.with(|windows| {
+        windows.borrow().last().map(|state| WindowHandle {
+            state: Rc::downgrade(state),
+        })
+    })
+}
+
+fn all_windows() -> Vec<WindowHandle> {
+    WINDOWS.with(|windows| {
+        windows
+            .borrow()
+            .iter()
+            .map(|state| WindowHandle {
+                state: Rc::downgrade(state),
+            })
+            .collect()
+    })
+}
+
+/// Call a closure with the activity's native window, if it has one.
+fn with_native_window<R>(f: impl FnOnce(*mut ANativeWindow) -> R) -> Option<R> {
+    let window = ndk_glue::native_window();
+    window.as_ref().map(|window| f(window.ptr().as_ptr()))
+}
+
+pub(crate) fn handle_activity_event(event: ActivityEvent) {
+    match event {
+        ActivityEvent::WindowCreated => {
+            with_native_window(|window| unsafe {
+                ANativeWindow_setBuffersGeometry(window, 0, 0, WINDOW_FORMAT_RGBX_8888);
+            });
+            if let Some(handle) = front_window() {
+                handle.send_size();
+            }
+        }
+        ActivityEvent::WindowResized
+        | ActivityEvent::ContentRectChanged
+        | ActivityEvent::ConfigChanged => {
+            if let Some(handle) = front_window() {
+                handle.send_size();
+            }
+        }
+        ActivityEvent::WindowRedrawNeeded => {
+            if let Some(handle) = front_window() {
+                handle.invalidate();
+            }
+        }
+        ActivityEvent::WindowHasFocus => {
+            if let Some(handle) = front_window() {
+                handle.with_handler(|handler, ctx| handler.got_focus(ctx));
+            }
+        }
+        ActivityEvent::Pause => {
+            for handle in all_windows() {
+                handle.with_handler(|handler, ctx| handler.suspended(ctx));
+            }
+        }
+        ActivityEvent::Resume => {
+            for handle in all_windows() {
+                handle.with_handler(|handler, ctx| handler.resumed(ctx));
+            }
+        }
+        ActivityEvent::Destroy => {
+            for handle in all_windows() {
+                handle.with_handler(|handler, ctx| handler.destroy(ctx));
+            }
+            WINDOWS.with(|windows| windows.borrow_mut().clear());
+            win_main::stop();
+        }
+        _ => (),
+    }
+}
+
+pub(crate) fn process_input() {
+    let queue = ndk_glue::input_queue();
+    let queue = match queue.as_ref() {
+        Some(queue) => queue.ptr().as_ptr(),
+        None => return,
+    };
+    unsafe {
+        let mut event: *mut AInputEvent = null_mut();
+        while AInputQueue_getEvent(queue, &mut event) >= 0 {
+            // This gives the soft keyboard a chance to consume the event.
+            if AInputQueue_preDispatchEvent(queue, event) != 0 {
+                continue;
+            }
+            let handled = match AInputEvent_getType(event) {
+                AINPUT_EVENT_TYPE_MOTION => dispatch_touch(event),
+                AINPUT_EVENT_TYPE_KEY => dispatch_key(event),
+                _ => false,
+            };
+            AInputQueue_finishEvent(queue, event, handled as i32);
+        }
+    }
+}
+
+unsafe fn dispatch_touch(event: *const AInputEvent) -> bool {
+    let handle = match front_window() {
+        Some(handle) => handle,
+        None => return false,
+    };
+    let scale = util::scale_factor();
+    let pos = Point::new(
+        AMotionEvent_getX(event, 0) as f64 / scale,
+        AMotionEvent_getY(event, 0) as f64 / scale,
+    );
+    let mods = get_modifiers(AMotionEvent_getMetaState(event));
+    let mut mouse = MouseEvent {
+        pos,
+        mods,
+        count: 0,
+        button: MouseButton::Left,
+    };
+    // Fingers after the first have their own actions, which we ignore.
+    match AMotionEvent_getAction(event) & AMOTION_EVENT_ACTION_MASK {
+        AMOTION_EVENT_ACTION_DOWN => {
+            // Widgets expect the pointer to hover before it clicks.
+            handle.with_handler(|handler, ctx| handler.mouse_move(&mouse, ctx));
+            mouse.count = 1;
+            handle.with_handler(|handler, ctx| handler.mouse_down(&mouse, ctx));
+        }
+        AMOTION_EVENT_ACTION_MOVE => {
+            handle.with_handler(|handler, ctx| handler.mouse_move(&mouse, ctx));
+        }
+        AMOTION_EVENT_ACTION_UP | AMOTION_EVENT_ACTION_CANCEL => {
+            handle.with_handler(|handler, ctx| handler.mouse_up(&mouse, ctx));
+            // Nothing should stay hot once the finger is lifted.
+            mouse.pos = Point::new(-1.0, -1.0);
+            handle.with_handler(|handler, ctx| handler.mouse_move(&mouse, ctx));
+        }
+        _ => (),
+    }
+    true
+}
+
+unsafe fn dispatch_key(event: *const AInputEvent) -> bool {
+    let handle = match front_window() {
+        Some(handle) => handle,
+        None => return false,
+    };
+    let key_code = KeyCode::from(AKeyEvent_getKeyCode(event));
+    let is_repeat = AKeyEvent_getRepeatCount(event) > 0;
+    let mods = get_modifiers(AKeyEvent_getMetaState(event));
+    let text = key_text(key_code, mods.shift);
+    let unmodified_text = key_text(key_code, false);
+    let key_event = KeyEvent::new(key_code, is_repeat, mods, text, unmodified_text);
+    match AKeyEvent_getAction(event) {
+        AKEY_EVENT_ACTION_DOWN => handle
+            .with_handler(|handler, ctx| handler.key_down(key_event, ctx))
+            .unwrap_or(false),
+        AKEY_EVENT_ACTION_UP => {
+            handle.with_handler(|handler, ctx| handler.key_up(key_event, ctx));
+            true
+        }
+        _ => false,
+    }
+}
+
+fn get_modifiers(meta: i32) -> KeyModifiers {
+    KeyModifiers {
+        shift: meta & AMETA_SHIFT_ON != 0,
+        alt: meta & AMETA_ALT_ON != 0,
+        ctrl: meta & AMETA_CTRL_ON != 0,
+        meta: meta & AMETA_META_ON != 0,
+    }
+}
+
+/// The text produced by a key, assuming a US layout.
+///
+/// The real key character map is only available through Java.
+fn key_text(key_code: KeyCode, shift: bool) -> Option<char> {
+    use KeyCode::*;
+    let (plain, shifted) = match key_code {
+        KeyA => ('a', 'A'),
+        KeyB => ('b', 'B'),
+        KeyC => ('c', 'C'),
+        KeyD => ('d', 'D'),
+        KeyE => ('e', 'E'),
+        KeyF => ('f', 'F'),
+        KeyG => ('g', 'G'),
+        KeyH => ('h', 'H'),
+        KeyI => ('i', 'I'),
+        KeyJ => ('j', 'J'),
+        KeyK => ('k', 'K'),
+        KeyL => ('l', 'L'),
+        KeyM => ('m', 'M'),
+        KeyN => ('n', 'N'),
+        KeyO => ('o', 'O'),
+        KeyP => ('p', 'P'),
+        KeyQ => ('q', 'Q'),
+        KeyR => ('r', 'R'),
+        KeyS => ('s', 'S'),
+        KeyT => ('t', 'T'),
+        KeyU => ('u', 'U'),
+        KeyV => ('v', 'V'),
+        KeyW => ('w', 'W'),
+        KeyX => ('x', 'X'),
+        KeyY => ('y', 'Y'),
+        KeyZ => ('z', 'Z'),
+        Key0 => ('0', ')'),
+        Key1 => ('1', '!'),
+        Key2 => ('2', '@'),
+        Key3 => ('3', '#'),
+        Key4 => ('4', '$'),
+        Key5 => ('5', '%'),
+        Key6 => ('6', '^'),
+        Key7 => ('7', '&'),
+        Key8 => ('8', '*'),
+        Key9 => ('9', '('),
+        Backtick => ('`', '~'),
+        Minus => ('-', '_'),
+        Equals => ('=', '+'),
+        LeftBracket => ('[', '{'),
+        RightBracket => (']', '}'),
+        Backslash => ('\\', '|'),
+        Semicolon => (';', ':'),
+        Quote => ('\'', '"'),
+        Comma => (',', '<'),
+        Period => ('.', '>'),
+        Slash => ('/', '?'),
+        Space => (' ', ' '),
+        Tab => ('\t', '\t'),
+        Return | NumpadEnter => ('\r', '\r'),
+        _ => return None,
+    };
+    Some(if shift { shifted } else { plain })
+}
+
+/// How long the runloop may sleep before it has work to do.
+///
+/// `None` means there is nothing to do until the next event.
+pub(crate) fn next_wake_delay() -> Option<Duration> {
+    let needs_paint = front_window()
+        .and_then(|handle| handle.state.upgrade())
+        .map(|state| state.needs_paint.get())
+        .unwrap_or(false);
+    if needs_paint {
+        return Some(Duration::from_millis(0));
+    }
+    let idle_pending = all_windows().iter().any(|handle| {
+        handle
+            .state
+            .upgrade()
+            .map(|state| !state.idle_queue.lock().expect("queue").is_empty())
+            .unwrap_or(false)
+    });
+    if idle_pending {
+        return Some(IDLE_RETRY);
+    }
+    let now = Instant::now();
+    TIMERS.with(|timers| {
+        timers
+            .borrow()
+            .iter()
+            .map(|timer| {
+                timer
+                    .deadline
+                    .checked_duration_since(now)
+                    .unwrap_or_default()
+            })
+            .min()
+    })
+}
+
+pub(crate) fn run_timers() {
+    let now = Instant::now();
+    let expired: Vec<Timer> = TIMERS.with(|timers| {
+        let mut timers = timers.borrow_mut();
+        let (expired, pending): (Vec<_>, Vec<_>) =
+            timers.drain(..).partition(|timer| timer.deadline <= now);
+        *timers = pending;
+        expired
+    });
+    for timer in expired {
+        let token = timer.token;
+        timer
+            .window
+            .with_handler(|handler, ctx| handler.timer(token, ctx));
+    }
+}
+
+pub(crate) fn run_idle() {
+    for handle in all_windows() {
+        let state = match handle.state.upgrade() {
+            Some(state) => state,
+            None => continue,
+        };
+        // The callbacks stay queued while the handler is in use, and are
+        // retried after `IDLE_RETRY`.
+        let mut handler = match state.handler.try_borrow_mut() {
+            Ok(handler) => handler,
+            Err(_) => continue,
+        };
+        let queue: Vec<_> = mem::replace(&mut state.idle_queue.lock().expect("queue"), Vec::new());
+        if queue.is_empty() {
+            continue;
+        }
+        let mut ctx = WinCtxImpl {
+            handle: &handle,
+            text: Text::new(),
+        };
+        for item in queue {
+            match item {
+                IdleKind::Callback(callback) => callback.call(handler.as_any()),
+                IdleKind::Token(token) => handler.idle(token, &mut ctx),
+            }
+        }
+    }
+}
+
+pub(crate) fn paint_if_needed() {
+    if let Some(handle) = front_window() {
+        let needs_paint = handle
+            .state
+            .upgrade()
+            .map(|state| state.needs_paint.replace(false))
+            .unwrap_or(false);
+        if needs_paint {
+            with_native_window(|window| handle.paint(window));
+        }
+    }
+}
+
+/// Copy pixels from a cairo surface into a locked window buffer.
+///
+/// Cairo's `Rgb24` format is BGRX in memory, where the window is RGBX.
+unsafe fn copy_pixels(src: &[u8], src_stride: usize, buffer: &ANativeWindow_Buffer) {
+    let dest = buffer.bits as *mut u8;
+    let dest_stride = buffer.stride as usize * 4;
+    for y in 0..buffer.height as usize {
+        for x in 0..buffer.width as usize {
+            let pixel = &src[y * src_stride + x * 4..][..4];
+            let out = dest.add(y * dest_stride + x * 4);
+            *out = pixel[2];
+            *out.add(1) = pixel[1];
+            *out.add(2) = pixel[0];
+            *out.add(3) = 0xff;
+        }
+    }
+}
+
+impl WindowHandle {
+    /// Bring the window to the front, replacing the visible one.
+    pub fn show(&self) {
+        if let Some(state) = self.state.upgrade() {
+            WINDOWS.with(|windows| {
+                let mut windows = windows.borrow_mut();
+                windows.retain(|other| !Rc::ptr_eq(other, &state));
+                windows.push(state.clone());
+            });
+        }
+        self.send_size();
+    }
+
+    /// Close the window.
+    pub fn close(&self) {
+        if let Some(state) = self.state.upgrade() {
+            self.with_handler(|handler, ctx| handler.destroy(ctx));
+            WINDOWS.with(|windows| {
+                windows
+                    .borrow_mut()
+                    .retain(|other| !Rc::ptr_eq(other, &state))
+            });
+        }
+        // Whatever is now in front needs to be drawn.
+        if let Some(handle) = front_window() {
+            handle.send_size();
+        }
+    }
+
+    /// Bring this window to the front of the window stack and give it focus.
+    pub fn bring_to_front_and_focus(&self) {
+        self.show();
+    }
+
+    /// Minimize the window.
+    ///
+    /// Windows fill the screen on this platform, so this does nothing.
+    pub fn minimize(&self) {}
+
+    /// Toggle the window between maximized and its normal size.
+    ///
+    /// Windows fill the screen on this platform, so this does nothing.
+    pub fn zoom(&self) {}
+
+    /// The window's frame, in screen coordinates.
+    ///
+    /// Windows fill the screen on this platform, so this is always empty.
+    pub fn get_frame(&self) -> Rect {
+        Rect::ZERO
+    }
+
+    /// Move the window's top left corner.
+    ///
+    /// Windows fill the screen on this platform, so this does nothing.
+    pub fn set_position(&self, _position: Point) {}
+
+    // Request invalidation of the entire window contents.
+    pub fn invalidate(&self) {
+        if let Some(state) = self.state.upgrade() {
+            state.needs_paint.set(true);
+        }
+    }
+
+    /// The activity's title comes from its manifest.
+    pub fn set_title(&self, _title: &str) {}
+
+    /// Menus are not shown on Android.
+    pub fn set_menu(&self, _menu: Menu) {}
+
+    /// Menus are not shown on Android.
+    pub fn show_context_menu(&self, _menu: Menu, _x: f64, _y: f64) {}
+
+    /// Set whether animation frames are drawn in step with the display's
+    /// refresh.
+    ///
+    /// Posting a frame to the native window always waits for the display,
+    /// so this does nothing.
+    pub fn set_vsync(&self, _vsync: bool) {}
+
+    /// Print a document, after showing the system's print dialog.
+    ///
+    /// Printing is not supported on this platform, so this only logs a
+    /// warning.
+    pub fn print(&self, _setup: PageSetup, _doc: Box<dyn Printable>) {
+        warn!("printing is not supported on this platform");
+    }
+
+    /// Get a handle that can be used to schedule an idle task.
+    pub fn get_idle_handle(&self) -> Option<IdleHandle> {
+        let state = self.state.upgrade()?;
+        Some(IdleHandle {
+            idle_queue: Arc::downgrade(&state.idle_queue),
+            looper: ForeignLooper::for_thread()?,
+        })
+    }
+
+    /// Get the raw handle of the window, for use with other libraries.
+    ///
+    /// The native window is shared by all windows, and only exists while
+    /// the activity is visible.
+    pub fn raw_window_handle(&self) -> RawWindowHandle {
+        let a_native_window =
+            with_native_window(|window| window as *mut c_void).unwrap_or(null_mut());
+        RawWindowHandle::Android(AndroidHandle {
+            a_native_window,
+            ..AndroidHandle::empty()
+        })
+    }
+
+    /// Get the dpi of the window.
+    ///
+    /// This is derived from the screen density, so that a px is one of
+    /// Android's density-independent pixels.
+    pub fn get_dpi(&self) -> f32 {
+        (96.0 * util::scale_factor()) as f32
+    }
+
+    // TODO: the following methods are cut'n'paste code. A good way to DRY
+    // would be to have a platform-independent trait with these as methods with
+    // default implementations.
+
+    /// Convert a dimension in px units to physical pixels (rounding).
+    pub fn px_to_pixels(&self, x: f32) -> i32 {
+        (x * self.get_dpi() * (1.0 / 96.0)).round() as i32
+    }
+
+    /// Convert a point in px units to physical pixels (rounding).
+    pub fn px_to_pixels_xy(&self, x: f32, y: f32) -> (i32, i32) {
+        let scale = self.get_dpi() * (1.0 / 96.0);
+        ((x * scale).round() as i32, (y * scale).round() as i32)
+    }
+
+    /// Convert a dimension in physical pixels to px units.
+    pub fn pixels_to_px<T: Into<f64>>(&self, x: T) -> f32 {
+        (x.into() as f32) * 96.0 / self.get_dpi()
+    }
+
+    /// Convert a point in physical pixels to px units.
+    pub fn pixels_to_px_xy<T: Into<f64>>(&self, x: T, y: T) -> (f32, f32) {
+        let scale = 96.0 / self.get_dpi();
+        ((x.into() as f32) * scale, (y.into() as f32) * scale)
+    }
+
+    #[deprecated(since = "0.3", note = "use methods on WinCtx instead")]
+    pub fn file_dialog(
+        &self,
+        _ty: FileDialogType,
+        _options: FileDialogOptions,
+    ) -> Result<OsString, Error> {
+        Err(Error::Null)
+    }
+
+    /// Tell the handler the size of the activity's surface, in physical
+    /// pixels, and schedule a repaint.
+    fn send_size(&self) {
+        let size = with_native_window(|window| unsafe {
+            (
+                ANativeWindow_getWidth(window),
+                ANativeWindow_getHeight(window),
+            )
+        });
+        if let Some((width, height)) = size {
+            self.with_handler(|handler, ctx| handler.size(width as u32, height as u32, ctx));
+            self.invalidate();
+        }
+    }
+
+    fn paint(&self, window: *mut ANativeWindow) {
+        let mut buffer: ANativeWindow_Buffer = unsafe { mem::zeroed() };
+        if unsafe { ANativeWindow_lock(window, &mut buffer, null_mut()) } != 0 {
+            error!("failed to lock the native window");
+            return;
+        }
+        let mut surface = match ImageSurface::create(Format::Rgb24, buffer.width, buffer.height) {
+            Ok(surface) => surface,
+            Err(e) => {
+                error!("failed to create surface: {:?}", e);
+                unsafe { ANativeWindow_unlockAndPost(window) };
+                return;
+            }
+        };
+        let anim = {
+            let mut cairo_ctx = cairo::Context::new(&surface);
+            // The handler paints in px units.
+            let scale = util::scale_factor();
+            cairo_ctx.scale(scale, scale);
+            self.with_handler(|handler, ctx| {
+                let mut piet_ctx = Piet::new(&mut cairo_ctx);
+                let anim = handler.paint(&mut piet_ctx, ctx);
+                if let Err(e) = piet_ctx.finish() {
+                    error!("{}", e)
+                }
+                anim
+            })
+            .unwrap_or(false)
+        };
+        surface.flush();
+        let stride = surface.get_stride() as usize;
+        match surface.get_data() {
+            Ok(data) => unsafe { copy_pixels(&data, stride, &buffer) },
+            Err(e) => error!("failed to read surface: {:?}", e),
+        }
+        unsafe { ANativeWindow_unlockAndPost(window) };
+        if anim {
+            self.invalidate();
+        }
+    }
+
+    /// Call a closure with the window's handler and a context, if the window
+    /// is still alive and the handler isn't already in use.
+    pub(crate) fn with_handler<F, R>(&self, f: F) -> Option<R>
+    where
+        F: FnOnce(&mut dyn WinHandler, &mut dyn WinCtx) -> R,
+    {
+        let state = self.state.upgrade()?;
+        let mut handler = match state.handler.try_borrow_mut() {
+            Ok(handler) => handler,
+            Err(_) => {
+                warn!("dropping event, window handler is already in use");
+                return None;
+            }
+        };
+        let mut ctx = WinCtxImpl {
+            handle: self,
+            text: Text::new(),
+        };
+        Some(f(&mut **handler, &mut ctx))
+    }
+}
+
+impl IdleHandle {
+    /// Add an idle handler, which is called (once) when the message loop
+    /// is empty. The idle handler will be run from the main UI thread, and
+    /// won't be scheduled if the associated view has been dropped.
+    ///
+    /// Note: the name "idle" suggests that it will be scheduled with a lower
+    /// priority than other UI events, but that's not necessarily the case.
+    pub fn add_idle<F>(&self, callback: F)
+    where
+        F: FnOnce(&dyn Any) + Send + 'static,
+    {
+        self.add_idle_kind(IdleKind::Callback(Box::new(callback)));
+    }
+
+    /// Request a call to the window handler's [`idle`] method, with the
+    /// given token.
+    ///
+    /// [`idle`]: ../trait.WinHandler.html#method.idle
+    pub fn schedule_idle(&self, token: IdleToken) {
+        self.add_idle_kind(IdleKind::Token(token));
+    }
+
+    fn add_idle_kind(&self, kind: IdleKind) {
+        if let Some(queue) = self.idle_queue.upgrade() {
+            queue.lock().expect("queue lock").push(kind);
+            self.looper.wake();
+        }
+    }
+}
+
+impl<'a> WinCtx<'a> for WinCtxImpl<'a> {
+    fn invalidate(&mut self) {
+        self.handle.invalidate();
+    }
+
+    fn text_factory(&mut self) -> &mut Text<'a> {
+        &mut self.text
+    }
+
+    /// There is no cursor on a touch screen.
+    fn set_cursor(&mut self, _cursor: &Cursor) {}
+
+    fn request_timer(&mut self, deadline: Instant) -> TimerToken {
+        let token = TimerToken::new(next_timer_id());
+        let timer = Timer {
+            deadline,
+            window: self.handle.clone(),
+            token,
+        };
+        TIMERS.with(|timers| timers.borrow_mut().push(timer));
+        token
+    }
+
+    fn open_file_sync(&mut self, _options: FileDialogOptions) -> Option<FileInfo> {
+        //TODO: this needs the storage access framework, through JNI.
+        warn!("file dialogs are not yet supported on Android");
+        None
+    }
+
+    fn set_clipboard_contents(&mut self, contents: ClipboardItem) {
+        Application::set_clipboard_contents(contents);
+    }
+
+    fn start_drag(&mut self, _contents: DragContents) {
+        warn!("dragging out of the window is not supported on Android");
+    }
+
+    fn set_soft_keyboard_visible(&mut self, visible: bool) {
+        let activity = ndk_glue::native_activity().ptr().as_ptr();
+        unsafe {
+            if visible {
+                ANativeActivity_showSoftInput(activity, ANATIVEACTIVITY_SHOW_SOFT_INPUT_IMPLICIT);
+            } else {
+                ANativeActivity_hideSoftInput(activity, 0);
+            }
+        }
+    }
+}
+
+fn next_timer_id() -> usize {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static TIMER_ID: AtomicUsize = AtomicUsize::new(1);
+    TIMER_ID.fetch_add(1, Ordering::Relaxed)
+}
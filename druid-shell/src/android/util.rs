@@ -0,0 +1,85 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Android implementation of utilities.
+
+use std::os::raw::c_char;
+
+use ndk_sys::{
+    AConfiguration, AConfiguration_delete, AConfiguration_fromAssetManager,
+    AConfiguration_getCountry, AConfiguration_getDensity, AConfiguration_getLanguage,
+    AConfiguration_new,
+};
+
+/// The density, in dots per inch, that Android uses as its baseline.
+const BASELINE_DENSITY: f64 = 160.0;
+
+pub fn init() {}
+
+pub fn assert_main_thread() {
+    assert!(
+        ndk::looper::ThreadLooper::for_thread().is_some(),
+        "must be called on the activity's main thread"
+    );
+}
+
+/// Returns the current locale string.
+///
+/// This should a [Unicode language identifier].
+///
+/// [Unicode language identifier]: https://unicode.org/reports/tr35/#Unicode_language_identifier
+pub fn get_locale() -> String {
+    let (language, country) = with_configuration(|config| unsafe {
+        let mut language = [0 as c_char; 2];
+        let mut country = [0 as c_char; 2];
+        AConfiguration_getLanguage(config, language.as_mut_ptr());
+        AConfiguration_getCountry(config, country.as_mut_ptr());
+        (to_string(&language), to_string(&country))
+    });
+    match (language.is_empty(), country.is_empty()) {
+        (true, _) => "en-US".to_string(),
+        (false, true) => language,
+        (false, false) => format!("{}-{}", language, country),
+    }
+}
+
+/// The ratio of the screen's density to the baseline density.
+pub(crate) fn scale_factor() -> f64 {
+    let density = with_configuration(|config| unsafe { AConfiguration_getDensity(config) });
+    // `ACONFIGURATION_DENSITY_DEFAULT` and `ACONFIGURATION_DENSITY_NONE`.
+    match density {
+        0 | 0xffff => 1.0,
+        density => density as f64 / BASELINE_DENSITY,
+    }
+}
+
+/// Call a closure with the activity's current configuration.
+fn with_configuration<R>(f: impl FnOnce(*mut AConfiguration) -> R) -> R {
+    let activity = ndk_glue::native_activity().ptr().as_ptr();
+    unsafe {
+        let config = AConfiguration_new();
+        AConfiguration_fromAssetManager(config, (*activity).assetManager);
+        let result = f(config);
+        AConfiguration_delete(config);
+        result
+    }
+}
+
+fn to_string(chars: &[c_char]) -> String {
+    chars
+        .iter()
+        .take_while(|c| **c != 0)
+        .map(|c| *c as u8 as char)
+        .collect()
+}
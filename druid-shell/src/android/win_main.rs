@@ -0,0 +1,79 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Android implementation of the runloop.
+
+use std::cell::Cell;
+
+use log::error;
+use ndk::looper::{Poll, ThreadLooper};
+use ndk_glue::{NDK_GLUE_LOOPER_EVENT_PIPE_IDENT, NDK_GLUE_LOOPER_INPUT_QUEUE_IDENT};
+use ndk_sys::ANativeActivity_finish;
+
+thread_local! {
+    static QUIT_REQUESTED: Cell<bool> = Cell::new(false);
+}
+
+pub struct RunLoop {
+    looper: ThreadLooper,
+}
+
+impl RunLoop {
+    pub fn new() -> RunLoop {
+        let looper = ThreadLooper::for_thread()
+            .expect("the runloop must be created on the activity's main thread");
+        RunLoop { looper }
+    }
+
+    pub fn run(&mut self) {
+        while !QUIT_REQUESTED.with(|quit| quit.get()) {
+            let poll = match super::next_wake_delay() {
+                Some(delay) => self.looper.poll_all_timeout(delay),
+                None => self.looper.poll_all(),
+            };
+            match poll {
+                Ok(Poll::Event { ident, .. }) => match ident {
+                    NDK_GLUE_LOOPER_EVENT_PIPE_IDENT => {
+                        if let Some(event) = ndk_glue::poll_events() {
+                            super::handle_activity_event(event);
+                        }
+                    }
+                    NDK_GLUE_LOOPER_INPUT_QUEUE_IDENT => super::process_input(),
+                    _ => (),
+                },
+                Ok(_) => (),
+                Err(e) => error!("looper error: {:?}", e),
+            }
+            super::run_timers();
+            super::run_idle();
+            super::paint_if_needed();
+        }
+    }
+}
+
+/// Embedding is not supported, so there is nothing to pump.
+pub fn pump_events() {}
+
+/// Finish the activity, and stop the runloop.
+pub fn request_quit() {
+    stop();
+    unsafe {
+        ANativeActivity_finish(ndk_glue::native_activity().ptr().as_ptr());
+    }
+}
+
+/// Stop the runloop, as when the activity is destroyed by the system.
+pub(crate) fn stop() {
+    QUIT_REQUESTED.with(|quit| quit.set(true));
+}
@@ -0,0 +1,107 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Helpers shared between platform implementations.
+
+use std::time::{Duration, Instant};
+
+use crate::kurbo::Point;
+use crate::window::MouseButton;
+
+/// The double-click time used when the platform doesn't tell us.
+pub(crate) const DEFAULT_MULTI_CLICK_INTERVAL: Duration = Duration::from_millis(500);
+/// The double-click distance, in px, used when the platform doesn't tell us.
+pub(crate) const DEFAULT_MULTI_CLICK_DISTANCE: f64 = 4.0;
+
+/// Counts consecutive clicks, for platforms that only report single presses.
+///
+/// A press continues a run of clicks if it uses the same button as the
+/// previous one, comes within the double-click time of it, and lands within
+/// the double-click distance of it.
+#[derive(Debug, Clone)]
+pub(crate) struct ClickCounter {
+    max_interval: Duration,
+    max_distance: f64,
+    last: Option<(Instant, Point, MouseButton)>,
+    count: u32,
+}
+
+impl ClickCounter {
+    pub(crate) fn new(max_interval: Duration, max_distance: f64) -> ClickCounter {
+        ClickCounter {
+            max_interval,
+            max_distance,
+            last: None,
+            count: 0,
+        }
+    }
+
+    /// Update the double-click time and distance, if the user changes them.
+    pub(crate) fn set_limits(&mut self, max_interval: Duration, max_distance: f64) {
+        self.max_interval = max_interval;
+        self.max_distance = max_distance;
+    }
+
+    /// Record a button press, returning its click count.
+    pub(crate) fn count_for_click(
+        &mut self,
+        time: Instant,
+        pos: Point,
+        button: MouseButton,
+    ) -> u32 {
+        let continues = match self.last {
+            Some((last_time, last_pos, last_button)) => {
+                last_button == button
+                    && time.duration_since(last_time) <= self.max_interval
+                    && (pos - last_pos).hypot() <= self.max_distance
+            }
+            None => false,
+        };
+        self.count = if continues { self.count + 1 } else { 1 };
+        self.last = Some((time, pos, button));
+        self.count
+    }
+}
+
+impl Default for ClickCounter {
+    fn default() -> Self {
+        ClickCounter::new(DEFAULT_MULTI_CLICK_INTERVAL, DEFAULT_MULTI_CLICK_DISTANCE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn click_counting() {
+        let mut counter = ClickCounter::default();
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let pos = Point::new(10.0, 10.0);
+        assert_eq!(counter.count_for_click(at(0), pos, MouseButton::Left), 1);
+        assert_eq!(counter.count_for_click(at(200), pos, MouseButton::Left), 2);
+        assert_eq!(counter.count_for_click(at(400), pos, MouseButton::Left), 3);
+        // too slow
+        assert_eq!(counter.count_for_click(at(1000), pos, MouseButton::Left), 1);
+        // too far
+        let far = Point::new(30.0, 10.0);
+        assert_eq!(counter.count_for_click(at(1100), far, MouseButton::Left), 1);
+        // another button
+        assert_eq!(
+            counter.count_for_click(at(1200), far, MouseButton::Right),
+            1
+        );
+    }
+}
@@ -0,0 +1,141 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Drag and drop between the application and the rest of the system.
+
+use std::path::PathBuf;
+
+use crate::keyboard::KeyModifiers;
+use crate::kurbo::Point;
+
+/// The payload of a drag operation.
+///
+/// A single drag can carry several representations at once; for instance
+/// dragging a file out of a file manager usually provides both the path and
+/// a textual version of it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DragData {
+    /// Paths of any files being dragged.
+    pub files: Vec<PathBuf>,
+    /// Plain text being dragged, if any.
+    pub text: Option<String>,
+}
+
+/// Something to be dragged out of the application.
+///
+/// This is passed to the platform to begin a drag, by way of
+/// [`WinCtx::start_drag`].
+///
+/// [`WinCtx::start_drag`]: ../window/trait.WinCtx.html#tymethod.start_drag
+#[derive(Debug, Clone, Default)]
+pub struct DragContents {
+    /// The standard representations of the dragged item.
+    pub data: DragData,
+    /// Application-specific representations, as pairs of a platform type
+    /// identifier and the raw bytes.
+    pub custom: Vec<(String, Vec<u8>)>,
+    /// An image shown under the cursor for the duration of the drag.
+    pub image: Option<DragImage>,
+    /// Whether the receiver is allowed to move the data, as opposed to only
+    /// copying it.
+    pub allow_move: bool,
+}
+
+/// An image displayed during a drag.
+#[derive(Debug, Clone)]
+pub struct DragImage {
+    pub width: usize,
+    pub height: usize,
+    /// Premultiplied RGBA pixels, 8 bits per channel, in row-major order.
+    pub pixels: Vec<u8>,
+}
+
+/// A drag event, delivered while something is being dragged over a window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DragEvent {
+    /// The location of the drag in the current window.
+    ///
+    /// This is in px units, that is, adjusted for hi-dpi.
+    pub pos: Point,
+    /// Keyboard modifiers at the time of the event.
+    pub mods: KeyModifiers,
+    /// The data being dragged.
+    pub data: DragData,
+}
+
+/// The operation that will be performed if the dragged item is dropped.
+///
+/// This is returned by the drop target, and is used by the platform to
+/// give the user feedback, typically by changing the cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DropEffect {
+    /// The drop is rejected.
+    None,
+    /// The data will be copied.
+    Copy,
+    /// The data will be moved.
+    Move,
+    /// A link to the original data will be created.
+    Link,
+}
+
+impl Default for DropEffect {
+    fn default() -> Self {
+        DropEffect::None
+    }
+}
+
+impl DragData {
+    /// Returns `true` if the drag carries no data we understand.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty() && self.text.is_none()
+    }
+}
+
+impl DragContents {
+    /// Create a new, empty drag.
+    pub fn new() -> Self {
+        DragContents::default()
+    }
+
+    /// Add a file to the drag.
+    pub fn file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.data.files.push(path.into());
+        self
+    }
+
+    /// Set the text carried by the drag.
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.data.text = Some(text.into());
+        self
+    }
+
+    /// Add data in an application-specific format.
+    pub fn custom(mut self, type_id: impl Into<String>, bytes: Vec<u8>) -> Self {
+        self.custom.push((type_id.into(), bytes));
+        self
+    }
+
+    /// Set the image displayed during the drag.
+    pub fn image(mut self, image: DragImage) -> Self {
+        self.image = Some(image);
+        self
+    }
+
+    /// Allow the receiver to move, rather than copy, the data.
+    pub fn allow_move(mut self) -> Self {
+        self.allow_move = true;
+        self
+    }
+}
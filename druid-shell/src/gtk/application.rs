@@ -0,0 +1,147 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! GTK implementation of features at the application scope.
+
+use std::ffi::CString;
+use std::os::raw::{c_int, c_void};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use gio::FileExt;
+use gtk::{Clipboard, SettingsExt};
+use log::warn;
+
+use crate::clipboard::ClipboardItem;
+
+#[link(name = "fontconfig")]
+extern "C" {
+    fn FcConfigAppFontAddFile(config: *mut c_void, file: *const u8) -> c_int;
+}
+
+/// Used to give each registered font its own file name.
+static FONT_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+pub struct Application;
+
+impl Application {
+    pub fn quit() {
+        crate::runloop::request_quit();
+    }
+
+    /// Open a URL with the default handler for its scheme.
+    ///
+    /// For `http` and `https` URLs this is the user's web browser.
+    pub fn open_url(url: &str) {
+        if let Err(e) = gtk::show_uri(None::<&gdk::Screen>, url, gtk::get_current_event_time()) {
+            warn!("failed to open url '{}': {}", url, e);
+        }
+    }
+
+    /// Show a file or directory in the file manager.
+    ///
+    /// There is no portable way to ask a Linux file manager to select a
+    /// file, so this opens the directory containing it.
+    pub fn reveal_in_file_manager(path: &Path) {
+        let dir = if path.is_dir() {
+            path
+        } else {
+            path.parent().unwrap_or(path)
+        };
+        let uri = gio::File::new_for_path(dir).get_uri();
+        Application::open_url(&uri);
+    }
+
+    /// Whether the user has chosen a high-contrast theme.
+    ///
+    /// GTK has no separate setting for this, so the high-contrast themes
+    /// are recognized by name, such as GNOME's "HighContrast" and
+    /// "HighContrastInverse".
+    pub fn is_high_contrast() -> bool {
+        gtk::Settings::get_default()
+            .and_then(|settings| settings.get_property_gtk_theme_name())
+            .map(|name| name.to_lowercase().contains("highcontrast"))
+            .unwrap_or(false)
+    }
+
+    /// Whether the user has turned animations off.
+    pub fn prefers_reduced_motion() -> bool {
+        gtk::Settings::get_default()
+            .map(|settings| !settings.get_property_gtk_enable_animations())
+            .unwrap_or(false)
+    }
+
+    /// Make a font available to the application, from the contents of a
+    /// TrueType or OpenType file.
+    ///
+    /// The font can then be selected by its family name.
+    pub fn register_font(data: &[u8]) {
+        // fontconfig can only load application fonts from files, so the
+        // data is written to a temporary one, which must outlive the process.
+        let count = FONT_COUNT.fetch_add(1, Ordering::Relaxed);
+        let name = format!("druid-font-{}-{}", std::process::id(), count);
+        let path = std::env::temp_dir().join(name);
+        if let Err(e) = std::fs::write(&path, data) {
+            warn!("failed to register font: {}", e);
+            return;
+        }
+        let c_path = match CString::new(path.to_string_lossy().into_owned()) {
+            Ok(c_path) => c_path,
+            Err(_) => return,
+        };
+        let added =
+            unsafe { FcConfigAppFontAddFile(std::ptr::null_mut(), c_path.as_ptr() as *const u8) };
+        if added == 0 {
+            warn!("failed to register font: invalid font data");
+        }
+    }
+
+    /// Returns the contents of the clipboard, if any.
+    pub fn get_clipboard_contents() -> Option<ClipboardItem> {
+        let clipboard = Clipboard::get(&gdk::SELECTION_CLIPBOARD);
+        //TODO: handle other data types
+        clipboard
+            .wait_for_text()
+            .map(|text| ClipboardItem::from(text.to_string()))
+    }
+
+    /// Returns the contents of the primary selection, if any.
+    ///
+    /// This is the text most recently selected in any application, which
+    /// is conventionally pasted with the middle mouse button.
+    pub fn get_primary_selection_contents() -> Option<ClipboardItem> {
+        let clipboard = Clipboard::get(&gdk::SELECTION_PRIMARY);
+        clipboard
+            .wait_for_text()
+            .map(|text| ClipboardItem::from(text.to_string()))
+    }
+
+    /// Sets the contents of the primary selection.
+    pub fn set_primary_selection_contents(item: ClipboardItem) {
+        let clipboard = Clipboard::get(&gdk::SELECTION_PRIMARY);
+        match item {
+            ClipboardItem::Text(string) => clipboard.set_text(&string),
+            other => warn!("unhandled primary selection data {:?}", other),
+        }
+    }
+
+    /// Sets the contents of the system clipboard.
+    pub fn set_clipboard_contents(item: ClipboardItem) {
+        let clipboard = Clipboard::get(&gdk::SELECTION_CLIPBOARD);
+        match item {
+            ClipboardItem::Text(string) => clipboard.set_text(&string),
+            other => warn!("unhandled clipboard data {:?}", other),
+        }
+    }
+}
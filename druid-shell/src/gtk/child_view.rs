@@ -0,0 +1,117 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Native child widgets, GTK implementation.
+
+use std::cell::RefCell;
+use std::ffi::c_void;
+use std::os::raw::c_int;
+
+use gdk::WindowExt;
+use glib::object::ObjectType;
+use glib::{Cast, IsA};
+use gtk::{ContainerExt, FixedExt, WidgetExt};
+use raw_window_handle::unix::XlibHandle;
+use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+
+use crate::kurbo::Rect;
+use crate::Error;
+
+use super::{gdk_window_handle, WindowHandle};
+
+// Deprecated, so not wrapped by gtk-rs, but still needed under X11 to keep
+// GTK from painting over the surface.
+extern "C" {
+    fn gtk_widget_set_double_buffered(widget: *mut c_void, double_buffered: c_int);
+}
+
+/// A widget shown inside a druid window, either created by other code or
+/// created as a surface for GPU rendering.
+///
+/// Child widgets are placed in a layer above the druid drawing area.
+pub struct ChildView {
+    widget: gtk::Widget,
+    container: RefCell<Option<gtk::Fixed>>,
+}
+
+impl ChildView {
+    /// Wrap a widget, which will be added to the druid window when attached.
+    pub fn from_widget(widget: impl IsA<gtk::Widget>) -> ChildView {
+        ChildView {
+            widget: widget.upcast(),
+            container: RefCell::new(None),
+        }
+    }
+
+    /// Create an empty widget with a native window of its own, for
+    /// rendering into with a graphics API such as Vulkan or OpenGL.
+    ///
+    /// The native window exists once the widget has been attached and
+    /// shown; until then, the raw window handle is empty.
+    pub fn new_surface() -> Result<ChildView, Error> {
+        let area = gtk::DrawingArea::new();
+        unsafe { gtk_widget_set_double_buffered(area.as_ptr() as *mut c_void, 0) };
+        area.connect_realize(|area| {
+            if let Some(window) = area.get_window() {
+                window.ensure_native();
+            }
+        });
+        Ok(ChildView::from_widget(area))
+    }
+
+    /// The wrapped widget.
+    pub fn widget(&self) -> &gtk::Widget {
+        &self.widget
+    }
+
+    /// Add the widget to the window, initially hidden.
+    pub fn attach(&self, parent: &WindowHandle) {
+        if let Some(state) = parent.state.upgrade() {
+            self.widget.set_no_show_all(true);
+            self.widget.hide();
+            state.children.put(&self.widget, 0, 0);
+            *self.container.borrow_mut() = Some(state.children.clone());
+        }
+    }
+
+    /// Set the area covered by the widget, in px relative to the window.
+    pub fn set_frame(&self, frame: Rect) {
+        if let Some(container) = self.container.borrow().as_ref() {
+            let (x, y) = (frame.x0.round() as i32, frame.y0.round() as i32);
+            container.move_(&self.widget, x, y);
+            self.widget
+                .set_size_request(frame.x1.round() as i32 - x, frame.y1.round() as i32 - y);
+        }
+    }
+
+    pub fn set_visible(&self, visible: bool) {
+        self.widget.set_visible(visible);
+    }
+
+    /// Remove the widget from its window.
+    pub fn detach(&self) {
+        if let Some(container) = self.container.borrow_mut().take() {
+            container.remove(&self.widget);
+        }
+    }
+}
+
+unsafe impl HasRawWindowHandle for ChildView {
+    fn raw_window_handle(&self) -> RawWindowHandle {
+        match self.widget.get_window() {
+            Some(window) => gdk_window_handle(&window),
+            None => RawWindowHandle::Xlib(XlibHandle::empty()),
+        }
+    }
+}
@@ -0,0 +1,71 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! File open/save dialogs, GTK implementation.
+
+use std::ffi::OsString;
+
+use gtk::{FileChooserAction, FileChooserExt, FileFilter, NativeDialogExt};
+use gtk::{ResponseType, Window};
+
+use crate::dialog::{FileDialogOptions, FileDialogType};
+use crate::Error;
+
+pub(crate) fn get_file_dialog_path(
+    window: &Window,
+    ty: FileDialogType,
+    options: FileDialogOptions,
+) -> Result<OsString, Error> {
+    let (title, action, accept) = match ty {
+        FileDialogType::Open => ("Open File", FileChooserAction::Open, "_Open"),
+        FileDialogType::Save => ("Save File", FileChooserAction::Save, "_Save"),
+    };
+    let dialog = gtk::FileChooserNative::new(
+        Some(title),
+        Some(window),
+        action,
+        Some(accept),
+        Some("_Cancel"),
+    );
+
+    dialog.set_show_hidden(options.show_hidden);
+    if let FileDialogType::Save = ty {
+        dialog.set_do_overwrite_confirmation(true);
+    }
+
+    if let Some(allowed_types) = options.allowed_types {
+        for spec in allowed_types {
+            let filter = FileFilter::new();
+            let patterns = spec
+                .extensions
+                .iter()
+                .map(|ext| format!("*.{}", ext))
+                .collect::<Vec<_>>();
+            let name = format!("{} ({})", spec.name, patterns.join("; "));
+            filter.set_name(Some(name.as_str()));
+            for pattern in &patterns {
+                filter.add_pattern(pattern);
+            }
+            dialog.add_filter(&filter);
+        }
+    }
+
+    let result = dialog.run();
+    let path = dialog.get_filename();
+    dialog.destroy();
+    match ResponseType::from(result) {
+        ResponseType::Accept => path.map(|p| p.into_os_string()).ok_or(Error::Null),
+        _ => Err(Error::Null),
+    }
+}
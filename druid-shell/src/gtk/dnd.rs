@@ -0,0 +1,211 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Dragging out of a window, GTK implementation.
+//!
+//! The contents are kept in the window state for the duration of the drag,
+//! and handed to GTK in whichever representation the receiver asks for.
+
+use gdk::{DragAction, ModifierType};
+use gdk_pixbuf::{Colorspace, Pixbuf};
+use gtk::{DragContextExtManual, Inhibit, TargetList, WidgetExt};
+use log::warn;
+
+use crate::dnd::{DragContents, DragImage, DropEffect};
+
+use super::{WindowHandle, WindowState};
+
+/// The target info for plain text.
+const TEXT_INFO: u32 = 0;
+/// The target info for a list of files.
+const URI_INFO: u32 = 1;
+/// The target info of the first custom representation; the others follow.
+const CUSTOM_INFO: u32 = 2;
+
+pub(super) fn connect_signals(state: &WindowState, handle: &WindowHandle) {
+    let area = &state.drawing_area;
+
+    let h = handle.clone();
+    area.connect_drag_begin(move |_, context| {
+        let state = match h.state.upgrade() {
+            Some(state) => state,
+            None => return,
+        };
+        let contents = state.drag_contents.borrow();
+        if let Some(image) = contents.as_ref().and_then(|c| c.image.as_ref()) {
+            if image.width > 0 && image.height > 0 {
+                let (hot_x, hot_y) = (image.width as i32 / 2, image.height as i32 / 2);
+                context.drag_set_icon_pixbuf(&make_pixbuf(image), hot_x, hot_y);
+            }
+        }
+    });
+
+    let h = handle.clone();
+    area.connect_drag_data_get(move |_, _, selection, info, _| {
+        let state = match h.state.upgrade() {
+            Some(state) => state,
+            None => return,
+        };
+        let contents = state.drag_contents.borrow();
+        let contents = match contents.as_ref() {
+            Some(contents) => contents,
+            None => return,
+        };
+        match info {
+            TEXT_INFO => {
+                if let Some(text) = contents.data.text.as_ref() {
+                    selection.set_text(text);
+                }
+            }
+            URI_INFO => {
+                let uris: Vec<_> = contents
+                    .data
+                    .files
+                    .iter()
+                    .filter_map(|path| glib::filename_to_uri(path, None).ok())
+                    .collect();
+                let uris: Vec<&str> = uris.iter().map(|uri| uri.as_str()).collect();
+                selection.set_uris(&uris);
+            }
+            _ => {
+                let index = (info - CUSTOM_INFO) as usize;
+                if let Some((type_id, bytes)) = contents.custom.get(index) {
+                    selection.set(&gdk::Atom::intern(type_id), 8, bytes);
+                }
+            }
+        }
+    });
+
+    // A failed drag (cancelled, or rejected by the receiver) is reported
+    // before the drag ends, and has no meaningful selected action.
+    let h = handle.clone();
+    area.connect_drag_failed(move |_, _, _| {
+        if let Some(state) = h.state.upgrade() {
+            if state.drag_contents.borrow_mut().take().is_some() {
+                h.with_handler(|handler, ctx| handler.drag_end(DropEffect::None, ctx));
+            }
+        }
+        Inhibit(false)
+    });
+
+    let h = handle.clone();
+    area.connect_drag_end(move |_, context| {
+        if let Some(state) = h.state.upgrade() {
+            if state.drag_contents.borrow_mut().take().is_some() {
+                let effect = drop_effect(context.get_selected_action());
+                h.with_handler(|handler, ctx| handler.drag_end(effect, ctx));
+            }
+        }
+    });
+}
+
+/// Begin a drag from the window's drawing area, in response to the current
+/// event.
+pub(super) fn begin_drag(state: &WindowState, contents: DragContents) {
+    if contents.data.is_empty() && contents.custom.is_empty() {
+        warn!("start_drag called with empty contents");
+        return;
+    }
+    let targets = TargetList::new(&[]);
+    if contents.data.text.is_some() {
+        targets.add_text_targets(TEXT_INFO);
+    }
+    if !contents.data.files.is_empty() {
+        targets.add_uri_targets(URI_INFO);
+    }
+    for (i, (type_id, _)) in contents.custom.iter().enumerate() {
+        targets.add(&gdk::Atom::intern(type_id), 0, CUSTOM_INFO + i as u32);
+    }
+
+    // Drags usually start from a motion event, which only reports the
+    // button in its modifier state.
+    let event = gtk::get_current_event();
+    let button = event
+        .as_ref()
+        .and_then(|event| {
+            event
+                .get_button()
+                .or_else(|| event.get_state().and_then(pressed_button))
+        })
+        .unwrap_or(1);
+    let actions = if contents.allow_move {
+        DragAction::COPY | DragAction::MOVE
+    } else {
+        DragAction::COPY
+    };
+
+    state.drag_contents.replace(Some(contents));
+    // With no coordinates, GTK takes the position from the event.
+    let context = state.drawing_area.drag_begin_with_coordinates(
+        &targets,
+        actions,
+        button as i32,
+        event.as_ref(),
+        -1,
+        -1,
+    );
+    if context.is_none() {
+        warn!("failed to begin drag");
+        state.drag_contents.replace(None);
+    }
+}
+
+/// The number of the first mouse button held down, if any.
+fn pressed_button(modifiers: ModifierType) -> Option<u32> {
+    [
+        ModifierType::BUTTON1_MASK,
+        ModifierType::BUTTON2_MASK,
+        ModifierType::BUTTON3_MASK,
+    ]
+    .iter()
+    .position(|&mask| modifiers.contains(mask))
+    .map(|i| i as u32 + 1)
+}
+
+/// The effect of a finished drag, from the action the receiver chose.
+fn drop_effect(action: DragAction) -> DropEffect {
+    if action.contains(DragAction::MOVE) {
+        DropEffect::Move
+    } else if action.contains(DragAction::COPY) {
+        DropEffect::Copy
+    } else if action.contains(DragAction::LINK) {
+        DropEffect::Link
+    } else {
+        DropEffect::None
+    }
+}
+
+/// Convert a drag image to a pixbuf, which isn't premultiplied.
+fn make_pixbuf(image: &DragImage) -> Pixbuf {
+    let len = image.width * image.height * 4;
+    let mut pixels = image.pixels.clone();
+    pixels.resize(len, 0);
+    for pixel in pixels.chunks_mut(4) {
+        let alpha = pixel[3] as u32;
+        if alpha > 0 && alpha < 255 {
+            for channel in &mut pixel[..3] {
+                *channel = (*channel as u32 * 255 / alpha).min(255) as u8;
+            }
+        }
+    }
+    Pixbuf::new_from_mut_slice(
+        pixels,
+        Colorspace::Rgb,
+        true,
+        8,
+        image.width as i32,
+        image.height as i32,
+        image.width as i32 * 4,
+    )
+}
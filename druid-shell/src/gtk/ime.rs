@@ -0,0 +1,108 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Input method support, GTK implementation.
+//!
+//! Key presses are offered to a `GtkIMMulticontext` before they are
+//! delivered, while the input method is enabled. As on other platforms,
+//! text committed by a single key press outside of a composition is
+//! delivered as that key press.
+
+use gtk::{IMContextExt, WidgetExt};
+
+use crate::ime::ImeEvent;
+use crate::keyboard::KeyEvent;
+use crate::kurbo::Rect;
+
+use super::{WindowHandle, WindowState};
+
+pub(super) fn connect_signals(state: &WindowState, handle: &WindowHandle) {
+    let im_context = &state.im_context;
+
+    let h = handle.clone();
+    im_context.connect_commit(move |_, text| {
+        let state = match h.state.upgrade() {
+            Some(state) => state,
+            None => return,
+        };
+        let was_composing = state.ime_composing.replace(false);
+        match state.pending_key.take() {
+            Some(key) if !was_composing && key.text() == Some(text) => {
+                h.with_handler(|handler, ctx| handler.key_down(key, ctx));
+            }
+            _ => {
+                let event = ImeEvent::Commit(text.into());
+                h.with_handler(|handler, ctx| handler.ime(event, ctx));
+            }
+        }
+    });
+
+    let h = handle.clone();
+    im_context.connect_preedit_changed(move |im_context| {
+        let (text, _, cursor) = im_context.get_preedit_string();
+        let text = text.to_string();
+        if let Some(state) = h.state.upgrade() {
+            state.ime_composing.set(!text.is_empty());
+        }
+        // GTK gives the cursor in characters.
+        let cursor = text
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain(Some(text.len()))
+            .nth(cursor.max(0) as usize);
+        let event = ImeEvent::Preedit { text, cursor };
+        h.with_handler(|handler, ctx| handler.ime(event, ctx));
+    });
+
+    let im_context = im_context.clone();
+    state.drawing_area.connect_realize(move |area| {
+        im_context.set_client_window(area.get_window().as_ref());
+    });
+}
+
+/// Offer a key press to the input method, returning `true` if it was
+/// consumed, in which case it must not be delivered again.
+pub(super) fn filter_key(state: &WindowState, key: &gdk::EventKey, event: KeyEvent) -> bool {
+    if !state.ime_enabled.get() {
+        return false;
+    }
+    state.pending_key.set(Some(event));
+    let consumed = state.im_context.filter_keypress(key);
+    state.pending_key.set(None);
+    consumed
+}
+
+pub(super) fn set_enabled(state: &WindowState, enabled: bool) {
+    if state.ime_enabled.replace(enabled) == enabled {
+        return;
+    }
+    if enabled {
+        state.im_context.focus_in();
+    } else {
+        state.im_context.reset();
+        state.im_context.focus_out();
+        state.ime_composing.set(false);
+    }
+}
+
+pub(super) fn set_cursor_rect(state: &WindowState, rect: Rect) {
+    // Our px units are the same as GTK's.
+    let area = gdk::Rectangle {
+        x: rect.x0 as i32,
+        y: rect.y0 as i32,
+        width: rect.width() as i32,
+        height: rect.height() as i32,
+    };
+    state.im_context.set_cursor_location(&area);
+}
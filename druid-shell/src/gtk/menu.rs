@@ -0,0 +1,318 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! GTK implementation of menus.
+
+use gdk::enums::key as keys;
+use gdk_pixbuf::{Colorspace, InterpType, Pixbuf};
+use glib::{Cast, IsA};
+use gtk::{
+    AccelFlags, AccelGroup, BinExt, BoxExt, CheckMenuItem, CheckMenuItemExt, ContainerExt,
+    GtkMenuExt, GtkMenuItemExt, Image, Menu as GtkMenu, MenuBar, MenuItem as GtkMenuItem,
+    MenuShell, MenuShellExt, Orientation, SeparatorMenuItem, WidgetExt,
+};
+use log::warn;
+
+use super::WindowHandle;
+use crate::hotkey::HotKey;
+use crate::image::RgbaImage;
+use crate::keyboard::{KbKey, KeyModifiers};
+
+/// A menu, which is turned into GTK widgets once it is attached to a window.
+///
+/// GTK menu items need a way of reaching the window's handler, which doesn't
+/// exist until the window is built, so we keep a description of the menu
+/// instead of building widgets straight away.
+#[derive(Default, Debug)]
+pub struct Menu {
+    items: Vec<MenuItem>,
+}
+
+#[derive(Debug)]
+enum MenuItem {
+    Entry {
+        name: String,
+        id: u32,
+        key: Option<HotKey>,
+        enabled: bool,
+        selected: bool,
+        icon: Option<RgbaImage>,
+    },
+    SubMenu(String, Menu, bool),
+    Separator,
+}
+
+impl Menu {
+    pub fn new() -> Menu {
+        Menu { items: Vec::new() }
+    }
+
+    pub fn new_for_popup() -> Menu {
+        // GTK doesn't distinguish between application and context menu types.
+        Menu::new()
+    }
+
+    pub fn add_dropdown(&mut self, menu: Menu, text: &str, enabled: bool) {
+        self.items
+            .push(MenuItem::SubMenu(translate_access_key(text), menu, enabled));
+    }
+
+    pub fn add_item(
+        &mut self,
+        id: u32,
+        text: &str,
+        key: Option<&HotKey>,
+        enabled: bool,
+        selected: bool,
+    ) {
+        self.items.push(MenuItem::Entry {
+            name: translate_access_key(text),
+            id,
+            key: key.cloned(),
+            enabled,
+            selected,
+            icon: None,
+        });
+    }
+
+    pub fn add_separator(&mut self) {
+        self.items.push(MenuItem::Separator);
+    }
+
+    /// Set the icon shown beside an item already added to this menu.
+    ///
+    /// The icon is scaled to 16 pixels high.
+    pub fn set_item_icon(&mut self, id: u32, icon: &RgbaImage) {
+        for item in &mut self.items {
+            if let MenuItem::Entry {
+                id: item_id,
+                icon: item_icon,
+                ..
+            } = item
+            {
+                if *item_id == id {
+                    *item_icon = Some(icon.clone());
+                }
+            }
+        }
+    }
+
+    pub(crate) fn into_gtk_menubar(
+        self,
+        handle: &WindowHandle,
+        accel_group: &AccelGroup,
+    ) -> MenuBar {
+        let menu = MenuBar::new();
+        self.append_items_to_menu(&menu, handle, accel_group);
+        menu
+    }
+
+    pub(crate) fn into_gtk_menu(self, handle: &WindowHandle, accel_group: &AccelGroup) -> GtkMenu {
+        let menu = GtkMenu::new();
+        menu.set_accel_group(Some(accel_group));
+        self.append_items_to_menu(&menu, handle, accel_group);
+        menu
+    }
+
+    fn append_items_to_menu<M: IsA<MenuShell>>(
+        self,
+        menu: &M,
+        handle: &WindowHandle,
+        accel_group: &AccelGroup,
+    ) {
+        for item in self.items {
+            match item {
+                MenuItem::Entry {
+                    name,
+                    id,
+                    key,
+                    enabled,
+                    selected,
+                    icon,
+                } => {
+                    let item = if selected {
+                        let item = CheckMenuItem::new_with_mnemonic(&name);
+                        item.set_active(true);
+                        item.upcast::<GtkMenuItem>()
+                    } else {
+                        GtkMenuItem::new_with_mnemonic(&name)
+                    };
+                    item.set_sensitive(enabled);
+                    if let Some(icon) = icon {
+                        add_icon(&item, &icon);
+                    }
+                    if let Some(key) = key {
+                        register_accelerator(&item, accel_group, &key);
+                    }
+
+                    let handle = handle.clone();
+                    item.connect_activate(move |_| {
+                        handle.with_handler(|handler, ctx| handler.command(id, ctx));
+                    });
+                    menu.append(&item);
+                }
+                MenuItem::SubMenu(name, submenu, enabled) => {
+                    let item = GtkMenuItem::new_with_mnemonic(&name);
+                    item.set_submenu(Some(&submenu.into_gtk_menu(handle, accel_group)));
+                    item.set_sensitive(enabled);
+                    menu.append(&item);
+                }
+                MenuItem::Separator => menu.append(&SeparatorMenuItem::new()),
+            }
+        }
+    }
+}
+
+/// The height at which menu item icons are shown, in pixels.
+const ICON_HEIGHT: i32 = 16;
+
+/// Put an icon before the label of a menu item.
+fn add_icon(item: &GtkMenuItem, icon: &RgbaImage) {
+    if icon.width == 0 || icon.height == 0 {
+        return;
+    }
+    let pixbuf = Pixbuf::new_from_mut_slice(
+        icon.pixels.clone(),
+        Colorspace::Rgb,
+        true,
+        8,
+        icon.width as i32,
+        icon.height as i32,
+        icon.width as i32 * 4,
+    );
+    let width = (ICON_HEIGHT as usize * icon.width / icon.height).max(1) as i32;
+    let pixbuf = pixbuf
+        .scale_simple(width, ICON_HEIGHT, InterpType::Bilinear)
+        .unwrap_or(pixbuf);
+    let image = Image::new_from_pixbuf(Some(&pixbuf));
+    let hbox = gtk::Box::new(Orientation::Horizontal, 6);
+    hbox.pack_start(&image, false, false, 0);
+    if let Some(label) = item.get_child() {
+        item.remove(&label);
+        hbox.pack_start(&label, true, true, 0);
+    }
+    item.add(&hbox);
+}
+
+fn register_accelerator(item: &GtkMenuItem, accel_group: &AccelGroup, key: &HotKey) {
+    let keyval = match key.kb_key().and_then(keyval_for_key) {
+        Some(keyval) => keyval,
+        None => {
+            warn!("no accelerator for {:?}", key);
+            return;
+        }
+    };
+    let mods: KeyModifiers = key.mods.into();
+    item.add_accelerator(
+        "activate",
+        accel_group,
+        keyval,
+        modifier_type(mods),
+        AccelFlags::VISIBLE,
+    );
+}
+
+/// The GDK keyval for a logical key, if it can be used as an accelerator.
+fn keyval_for_key(key: KbKey) -> Option<u32> {
+    let keyval = match key {
+        KbKey::Character(c) => gdk::unicode_to_keyval(c as u32),
+        KbKey::Enter => keys::Return,
+        KbKey::Tab => keys::Tab,
+        KbKey::Backspace => keys::BackSpace,
+        KbKey::Delete => keys::Delete,
+        KbKey::Escape => keys::Escape,
+        KbKey::Insert => keys::Insert,
+        KbKey::Home => keys::Home,
+        KbKey::End => keys::End,
+        KbKey::PageUp => keys::Page_Up,
+        KbKey::PageDown => keys::Page_Down,
+        KbKey::ArrowUp => keys::Up,
+        KbKey::ArrowDown => keys::Down,
+        KbKey::ArrowLeft => keys::Left,
+        KbKey::ArrowRight => keys::Right,
+        KbKey::PrintScreen => keys::Print,
+        KbKey::Pause => keys::Pause,
+        KbKey::F1 => keys::F1,
+        KbKey::F2 => keys::F2,
+        KbKey::F3 => keys::F3,
+        KbKey::F4 => keys::F4,
+        KbKey::F5 => keys::F5,
+        KbKey::F6 => keys::F6,
+        KbKey::F7 => keys::F7,
+        KbKey::F8 => keys::F8,
+        KbKey::F9 => keys::F9,
+        KbKey::F10 => keys::F10,
+        KbKey::F11 => keys::F11,
+        KbKey::F12 => keys::F12,
+        // modifiers and lock keys can't be accelerators on their own
+        _ => return None,
+    };
+    Some(keyval)
+}
+
+fn modifier_type(mods: KeyModifiers) -> gdk::ModifierType {
+    let mut result = gdk::ModifierType::empty();
+    if mods.shift {
+        result |= gdk::ModifierType::SHIFT_MASK;
+    }
+    if mods.ctrl {
+        result |= gdk::ModifierType::CONTROL_MASK;
+    }
+    if mods.alt {
+        result |= gdk::ModifierType::MOD1_MASK;
+    }
+    if mods.meta {
+        result |= gdk::ModifierType::META_MASK;
+    }
+    result
+}
+
+/// Convert our access keys to GTK's mnemonics.
+///
+/// Changes "E&xit" to "E_xit". Actual ampersands are escaped as "&&",
+/// and actual underscores are escaped for GTK.
+fn translate_access_key(raw_menu_text: &str) -> String {
+    let mut saw_ampersand = false;
+    let mut result = String::new();
+    for c in raw_menu_text.chars() {
+        if c == '&' {
+            if saw_ampersand {
+                result.push(c);
+            }
+            saw_ampersand = !saw_ampersand;
+        } else {
+            if saw_ampersand {
+                result.push('_');
+            }
+            if c == '_' {
+                result.push('_');
+            }
+            result.push(c);
+            saw_ampersand = false;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn access_keys() {
+        assert_eq!(translate_access_key("E&xit"), "E_xit");
+        assert_eq!(translate_access_key("Save && Quit"), "Save & Quit");
+        assert_eq!(translate_access_key("snake_case"), "snake__case");
+    }
+}
@@ -0,0 +1,1003 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! GTK implementation of window creation.
+
+pub mod application;
+mod child_view;
+pub mod dialog;
+mod dnd;
+mod ime;
+pub mod menu;
+mod print;
+pub mod util;
+#[cfg(feature = "video")]
+mod video;
+#[cfg(feature = "webview")]
+mod webview;
+pub mod win_main;
+
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::ffi::{c_void, OsString};
+use std::mem;
+use std::os::raw::{c_int, c_ulong};
+use std::rc::{Rc, Weak};
+use std::sync::{Arc, Mutex, Weak as ArcWeak};
+use std::time::{Duration, Instant};
+
+use gdk::{EventMask, ModifierType, ScrollDirection, WindowExt};
+use glib::object::{Cast, ObjectType};
+use gtk::{
+    AccelGroup, BoxExt, ContainerExt, GtkMenuExt, GtkWindowExt, GtkWindowExtManual, Inhibit,
+    OverlayExt, SettingsExt, WidgetExt, WidgetExtManual,
+};
+use log::{error, warn};
+
+use piet_common::{Piet, RenderContext};
+use raw_window_handle::unix::{WaylandHandle, XlibHandle};
+use raw_window_handle::RawWindowHandle;
+
+use crate::kurbo::{Point, Rect, Vec2};
+
+use crate::clipboard::ClipboardItem;
+use crate::common_util::ClickCounter;
+use crate::dialog::{FileDialogOptions, FileDialogType};
+use crate::dnd::DragContents;
+use crate::keyboard::{KeyEvent, KeyModifiers};
+use crate::platform::application::Application;
+use crate::print::{PageSetup, Printable};
+use crate::window::{
+    Cursor, FileInfo, IdleToken, MouseButton, MouseEvent, ScrollDevice, ScrollPhase, Text,
+    TimerToken, WheelEvent, WinCtx, WinHandler,
+};
+use crate::Error;
+
+pub use child_view::ChildView;
+pub use menu::Menu;
+#[cfg(feature = "video")]
+pub use video::VideoDecoder;
+#[cfg(feature = "webview")]
+pub use webview::WebView;
+
+use util::assert_main_thread;
+
+// These are part of GDK, but not wrapped by gtk-rs.
+extern "C" {
+    fn gdk_x11_window_get_xid(window: *mut c_void) -> c_ulong;
+    fn gdk_x11_display_get_xdisplay(display: *mut c_void) -> *mut c_void;
+    fn gdk_wayland_window_get_wl_surface(window: *mut c_void) -> *mut c_void;
+    fn gdk_wayland_display_get_wl_display(display: *mut c_void) -> *mut c_void;
+    fn gdk_x11_window_foreign_new_for_display(display: *mut c_void, window: c_ulong)
+        -> *mut c_void;
+    fn gdk_window_reparent(window: *mut c_void, new_parent: *mut c_void, x: c_int, y: c_int);
+    fn gdk_window_get_width(window: *mut c_void) -> c_int;
+    fn gdk_window_get_height(window: *mut c_void) -> c_int;
+}
+
+/// The distance scrolled by one notch of a mouse wheel.
+const SCROLL_LINE_DELTA: f64 = 120.0;
+
+/// How long to wait, in milliseconds, before retrying idle callbacks that
+/// couldn't run because the window handler was in use.
+const IDLE_RETRY_MS: u32 = 10;
+
+thread_local! {
+    /// The state of every open window, keyed by window id.
+    ///
+    /// This is what keeps the state alive; everything else holds weak
+    /// references. It also lets idle callbacks, which are scheduled from
+    /// other threads, find their window.
+    static WINDOWS: RefCell<HashMap<usize, Rc<WindowState>>> = RefCell::new(HashMap::new());
+}
+
+#[derive(Clone, Default)]
+pub struct WindowHandle {
+    state: Weak<WindowState>,
+}
+
+/// Builder abstraction for creating new windows.
+pub struct WindowBuilder {
+    handler: Option<Box<dyn WinHandler>>,
+    title: String,
+    enable_mouse_move_events: bool,
+    menu: Option<Menu>,
+    /// The X11 window to embed this window in, if any.
+    parent: Option<c_ulong>,
+}
+
+#[derive(Clone)]
+pub struct IdleHandle {
+    window_id: usize,
+    idle_queue: ArcWeak<Mutex<Vec<IdleKind>>>,
+}
+
+// TODO: move this out of platform-dependent section.
+trait IdleCallback: Send {
+    fn call(self: Box<Self>, a: &dyn Any);
+}
+
+impl<F: FnOnce(&dyn Any) + Send> IdleCallback for F {
+    fn call(self: Box<F>, a: &dyn Any) {
+        (*self)(a)
+    }
+}
+
+/// An entry in a window's idle queue.
+enum IdleKind {
+    Callback(Box<dyn IdleCallback>),
+    Token(IdleToken),
+}
+
+/// The state associated with each window.
+struct WindowState {
+    id: usize,
+    window: gtk::Window,
+    vbox: gtk::Box,
+    drawing_area: gtk::DrawingArea,
+    /// The layer holding native child widgets, above the drawing area.
+    children: gtk::Fixed,
+    menu_bar: RefCell<Option<gtk::MenuBar>>,
+    accel_group: AccelGroup,
+    handler: RefCell<Box<dyn WinHandler>>,
+    idle_queue: Arc<Mutex<Vec<IdleKind>>>,
+    /// The hardware keycode of the last key pressed, used to detect repeats,
+    /// which GDK doesn't report.
+    last_key: Cell<Option<u16>>,
+    im_context: gtk::IMMulticontext,
+    ime_enabled: Cell<bool>,
+    /// Whether the input method has shown a preedit since the last commit.
+    ime_composing: Cell<bool>,
+    /// The key press being offered to the input method.
+    pending_key: Cell<Option<KeyEvent>>,
+    /// The time the frame being drawn is expected to be shown, if known.
+    presentation_time: Cell<Option<Instant>>,
+    /// What is being dragged out of the window, while a drag is in progress.
+    drag_contents: RefCell<Option<DragContents>>,
+}
+
+struct WinCtxImpl<'a> {
+    handle: &'a WindowHandle,
+    text: Text<'static>,
+}
+
+impl WindowBuilder {
+    pub fn new() -> WindowBuilder {
+        WindowBuilder {
+            handler: None,
+            title: String::new(),
+            enable_mouse_move_events: true,
+            menu: None,
+            parent: None,
+        }
+    }
+
+    pub fn set_handler(&mut self, handler: Box<dyn WinHandler>) {
+        self.handler = Some(handler);
+    }
+
+    pub fn set_title(&mut self, title: impl Into<String>) {
+        self.title = title.into();
+    }
+
+    pub fn set_menu(&mut self, menu: Menu) {
+        self.menu = Some(menu);
+    }
+
+    pub fn set_enable_mouse_move_events(&mut self, to: bool) {
+        self.enable_mouse_move_events = to;
+    }
+
+    /// Embed the window in a window owned by another toolkit, filling it.
+    ///
+    /// Only X11 windows can be used as parents; under Wayland, where a
+    /// client can't access another client's surfaces, this is ignored.
+    pub fn set_parent(&mut self, parent: RawWindowHandle) {
+        match parent {
+            RawWindowHandle::Xlib(handle) if !util::is_wayland() => {
+                self.parent = Some(handle.window)
+            }
+            _ => warn!("unsupported parent window handle: {:?}", parent),
+        }
+    }
+
+    pub fn build(self) -> Result<WindowHandle, Error> {
+        assert_main_thread();
+        let handler = self
+            .handler
+            .expect("Tried to build a window without setting the handler");
+
+        let window = gtk::Window::new(gtk::WindowType::Toplevel);
+        window.set_title(&self.title);
+        window.set_default_size(500, 400);
+
+        let accel_group = AccelGroup::new();
+        window.add_accel_group(&accel_group);
+
+        let vbox = gtk::Box::new(gtk::Orientation::Vertical, 0);
+        window.add(&vbox);
+
+        let drawing_area = gtk::DrawingArea::new();
+        drawing_area.set_can_focus(true);
+        let mut events = EventMask::EXPOSURE_MASK
+            | EventMask::BUTTON_PRESS_MASK
+            | EventMask::BUTTON_RELEASE_MASK
+            | EventMask::KEY_PRESS_MASK
+            | EventMask::KEY_RELEASE_MASK
+            | EventMask::SCROLL_MASK
+            | EventMask::SMOOTH_SCROLL_MASK
+            | EventMask::FOCUS_CHANGE_MASK;
+        if self.enable_mouse_move_events {
+            events |= EventMask::POINTER_MOTION_MASK;
+        }
+        drawing_area.add_events(events);
+        let overlay = gtk::Overlay::new();
+        overlay.add(&drawing_area);
+        let children = gtk::Fixed::new();
+        overlay.add_overlay(&children);
+        overlay.set_overlay_pass_through(&children, true);
+        vbox.pack_end(&overlay, true, true, 0);
+
+        let window_id = next_window_id();
+        let state = Rc::new(WindowState {
+            id: window_id,
+            window,
+            vbox,
+            drawing_area,
+            children,
+            menu_bar: RefCell::new(None),
+            accel_group,
+            handler: RefCell::new(handler),
+            idle_queue: Arc::new(Mutex::new(Vec::new())),
+            last_key: Cell::new(None),
+            im_context: gtk::IMMulticontext::new(),
+            ime_enabled: Cell::new(false),
+            ime_composing: Cell::new(false),
+            pending_key: Cell::new(None),
+            presentation_time: Cell::new(None),
+            drag_contents: RefCell::new(None),
+        });
+        let handle = WindowHandle {
+            state: Rc::downgrade(&state),
+        };
+        WINDOWS.with(|windows| windows.borrow_mut().insert(window_id, state.clone()));
+
+        if let Some(menu) = self.menu {
+            handle.set_menu(menu);
+        }
+        connect_signals(&state, &handle);
+        ime::connect_signals(&state, &handle);
+        dnd::connect_signals(&state, &handle);
+        if let Some(parent) = self.parent {
+            embed_in(&state.window, parent);
+        }
+
+        state
+            .handler
+            .borrow_mut()
+            .connect(&crate::window::WindowHandle {
+                inner: handle.clone(),
+            });
+
+        Ok(handle)
+    }
+}
+
+/// The time the frame being drawn is expected to be shown, according to
+/// the widget's frame clock.
+fn predicted_presentation_time(widget: &gtk::DrawingArea) -> Option<Instant> {
+    let timings = widget.get_frame_clock()?.get_current_timings()?;
+    // Times are in microseconds, on the clock of `g_get_monotonic_time`.
+    let predicted = match timings.get_predicted_presentation_time() {
+        Some(predicted) => predicted.get() as i64,
+        None => {
+            let interval = timings.get_refresh_interval().map_or(0, |i| i.get());
+            timings.get_frame_time() + interval as i64
+        }
+    };
+    let ahead = (predicted - glib::get_monotonic_time()).max(0);
+    Some(Instant::now() + Duration::from_micros(ahead as u64))
+}
+
+fn connect_signals(state: &WindowState, handle: &WindowHandle) {
+    let area = &state.drawing_area;
+
+    let h = handle.clone();
+    area.connect_draw(move |widget, context| {
+        if let Some(state) = h.state.upgrade() {
+            state
+                .presentation_time
+                .set(predicted_presentation_time(widget));
+        }
+        let anim = h
+            .with_handler(|handler, ctx| {
+                let mut cairo_ctx = context.clone();
+                let mut piet_ctx = Piet::new(&mut cairo_ctx);
+                let anim = handler.paint(&mut piet_ctx, ctx);
+                if let Err(e) = piet_ctx.finish() {
+                    error!("{}", e)
+                }
+                anim
+            })
+            .unwrap_or(false);
+        if let Some(state) = h.state.upgrade() {
+            state.presentation_time.set(None);
+        }
+        if anim {
+            // Drawing is driven by the frame clock, so this waits for the
+            // next refresh of the display.
+            widget.queue_draw();
+        }
+        Inhibit(false)
+    });
+
+    // Sizes are reported in physical pixels, like on other platforms;
+    // GTK's own units are scaled by the window's (integer) scale factor.
+    let h = handle.clone();
+    area.connect_size_allocate(move |widget, allocation| {
+        let scale = widget.get_scale_factor() as u32;
+        h.with_handler(|handler, ctx| {
+            let width = allocation.width as u32 * scale;
+            let height = allocation.height as u32 * scale;
+            handler.size(width, height, ctx)
+        });
+    });
+
+    // The scale factor changes when the window is moved to a monitor with a
+    // different resolution.
+    let h = handle.clone();
+    area.connect_property_scale_factor_notify(move |widget| {
+        let scale = widget.get_scale_factor() as u32;
+        let width = widget.get_allocated_width() as u32 * scale;
+        let height = widget.get_allocated_height() as u32 * scale;
+        h.with_handler(|handler, ctx| handler.size(width, height, ctx));
+        widget.queue_draw();
+    });
+
+    let h = handle.clone();
+    let click_counter = RefCell::new(ClickCounter::default());
+    area.connect_button_press_event(move |widget, event| {
+        widget.grab_focus();
+        // GDK sends a regular press event for each click, followed by a
+        // synthetic double or triple press; we only want the former, and
+        // count clicks ourselves so that counts go beyond three.
+        if event.get_event_type() != gdk::EventType::ButtonPress {
+            return Inhibit(true);
+        }
+        if let Some(button) = get_mouse_button(event.get_button()) {
+            let (x, y) = event.get_position();
+            let pos = Point::new(x, y);
+            let mut counter = click_counter.borrow_mut();
+            if let Some(settings) = widget.get_settings() {
+                let interval = settings.get_property_gtk_double_click_time().max(0) as u64;
+                let distance = settings.get_property_gtk_double_click_distance() as f64;
+                counter.set_limits(Duration::from_millis(interval), distance);
+            }
+            let event = MouseEvent {
+                pos,
+                mods: get_modifiers(event.get_state()),
+                count: counter.count_for_click(Instant::now(), pos, button),
+                button,
+            };
+            h.with_handler(|handler, ctx| handler.mouse_down(&event, ctx));
+        }
+        Inhibit(true)
+    });
+
+    let h = handle.clone();
+    area.connect_button_release_event(move |_, event| {
+        if let Some(button) = get_mouse_button(event.get_button()) {
+            let (x, y) = event.get_position();
+            let event = MouseEvent {
+                pos: Point::new(x, y),
+                mods: get_modifiers(event.get_state()),
+                count: 0,
+                button,
+            };
+            h.with_handler(|handler, ctx| handler.mouse_up(&event, ctx));
+        }
+        Inhibit(true)
+    });
+
+    let h = handle.clone();
+    area.connect_motion_notify_event(move |_, event| {
+        let (x, y) = event.get_position();
+        let state = event.get_state();
+        let event = MouseEvent {
+            pos: Point::new(x, y),
+            mods: get_modifiers(state),
+            count: 0,
+            button: get_mouse_button_from_modifiers(state),
+        };
+        h.with_handler(|handler, ctx| handler.mouse_move(&event, ctx));
+        Inhibit(true)
+    });
+
+    let h = handle.clone();
+    area.connect_scroll_event(move |_, event| {
+        let delta = match event.get_direction() {
+            ScrollDirection::Up => Vec2::new(0.0, -SCROLL_LINE_DELTA),
+            ScrollDirection::Down => Vec2::new(0.0, SCROLL_LINE_DELTA),
+            ScrollDirection::Left => Vec2::new(-SCROLL_LINE_DELTA, 0.0),
+            ScrollDirection::Right => Vec2::new(SCROLL_LINE_DELTA, 0.0),
+            ScrollDirection::Smooth => {
+                let (dx, dy) = event.get_delta();
+                Vec2::new(dx * SCROLL_LINE_DELTA, dy * SCROLL_LINE_DELTA)
+            }
+            _ => return Inhibit(false),
+        };
+        let is_touchpad = event
+            .get_source_device()
+            .map(|device| device.get_source() == gdk::InputSource::Touchpad)
+            .unwrap_or(false);
+        // GTK leaves kinetic scrolling to the application, so a touchpad gesture
+        // has no momentum phase: it ends with a zero-delta "stop" event.
+        let (phase, device) = if is_touchpad {
+            let phase = if event.get_is_stop() {
+                ScrollPhase::Ended
+            } else {
+                ScrollPhase::Changed
+            };
+            (phase, ScrollDevice::Trackpad)
+        } else {
+            (ScrollPhase::None, ScrollDevice::Wheel)
+        };
+        let event = WheelEvent {
+            delta,
+            mods: get_modifiers(event.get_state()),
+            phase,
+            device,
+        };
+        h.with_handler(|handler, ctx| handler.wheel(&event, ctx));
+        Inhibit(true)
+    });
+
+    let h = handle.clone();
+    area.connect_key_press_event(move |_, key| {
+        let code = key.get_hardware_keycode();
+        let is_repeat = h
+            .state
+            .upgrade()
+            .map(|state| state.last_key.replace(Some(code)) == Some(code))
+            .unwrap_or(false);
+        let event = make_key_event(key, is_repeat);
+        if let Some(state) = h.state.upgrade() {
+            if ime::filter_key(&state, key, event) {
+                return Inhibit(true);
+            }
+        }
+        let handled = h
+            .with_handler(|handler, ctx| handler.key_down(event, ctx))
+            .unwrap_or(false);
+        Inhibit(handled)
+    });
+
+    let h = handle.clone();
+    area.connect_key_release_event(move |_, key| {
+        let code = key.get_hardware_keycode();
+        if let Some(state) = h.state.upgrade() {
+            if state.last_key.get() == Some(code) {
+                state.last_key.set(None);
+            }
+        }
+        let event = make_key_event(key, false);
+        h.with_handler(|handler, ctx| handler.key_up(event, ctx));
+        Inhibit(true)
+    });
+
+    let h = handle.clone();
+    area.connect_focus_in_event(move |_, _| {
+        h.with_handler(|handler, ctx| handler.got_focus(ctx));
+        Inhibit(false)
+    });
+
+    if let Some(settings) = gtk::Settings::get_default() {
+        let h = handle.clone();
+        settings.connect_property_gtk_theme_name_notify(move |_| {
+            h.with_handler(|handler, ctx| handler.accessibility_changed(ctx));
+        });
+        let h = handle.clone();
+        settings.connect_property_gtk_enable_animations_notify(move |_| {
+            h.with_handler(|handler, ctx| handler.accessibility_changed(ctx));
+        });
+    }
+
+    let h = handle.clone();
+    let window_id = state.id;
+    state.window.connect_destroy(move |_| {
+        h.with_handler(|handler, ctx| handler.destroy(ctx));
+        WINDOWS.with(|windows| windows.borrow_mut().remove(&window_id));
+    });
+}
+
+fn get_mouse_button(button: u32) -> Option<MouseButton> {
+    match button {
+        1 => Some(MouseButton::Left),
+        2 => Some(MouseButton::Middle),
+        3 => Some(MouseButton::Right),
+        8 => Some(MouseButton::X1),
+        9 => Some(MouseButton::X2),
+        _ => None,
+    }
+}
+
+fn get_mouse_button_from_modifiers(modifiers: ModifierType) -> MouseButton {
+    //TODO: this doesn't correctly handle multiple buttons being pressed.
+    match modifiers {
+        m if m.contains(ModifierType::BUTTON1_MASK) => MouseButton::Left,
+        m if m.contains(ModifierType::BUTTON2_MASK) => MouseButton::Middle,
+        m if m.contains(ModifierType::BUTTON3_MASK) => MouseButton::Right,
+        m if m.contains(ModifierType::BUTTON4_MASK) => MouseButton::X1,
+        m if m.contains(ModifierType::BUTTON5_MASK) => MouseButton::X2,
+        _ => {
+            //FIXME: this gets called when the mouse moves, where there
+            //may be no buttons down. This is mostly a problem with our API?
+            MouseButton::Left
+        }
+    }
+}
+
+/// Move a window into a foreign X11 window, resizing it to fill its parent.
+fn embed_in(window: &gtk::Window, parent: c_ulong) {
+    window.set_decorated(false);
+    window.realize();
+    let gdk_window = match window.get_window() {
+        Some(gdk_window) => gdk_window,
+        None => {
+            error!("failed to realize window for embedding");
+            return;
+        }
+    };
+    unsafe {
+        let display = gdk_window.get_display();
+        // The foreign window is owned by GDK's window table, so we don't
+        // take a reference to it.
+        let foreign =
+            gdk_x11_window_foreign_new_for_display(display.as_ptr() as *mut c_void, parent);
+        if foreign.is_null() {
+            error!("parent window {:#x} does not exist", parent);
+            return;
+        }
+        gdk_window_reparent(gdk_window.as_ptr() as *mut c_void, foreign, 0, 0);
+        window.resize(
+            gdk_window_get_width(foreign),
+            gdk_window_get_height(foreign),
+        );
+    }
+}
+
+fn get_modifiers(modifiers: ModifierType) -> KeyModifiers {
+    KeyModifiers {
+        shift: modifiers.contains(ModifierType::SHIFT_MASK),
+        alt: modifiers.contains(ModifierType::MOD1_MASK),
+        ctrl: modifiers.contains(ModifierType::CONTROL_MASK),
+        meta: modifiers.intersects(ModifierType::SUPER_MASK | ModifierType::META_MASK),
+    }
+}
+
+fn make_key_event(key: &gdk::EventKey, is_repeat: bool) -> KeyEvent {
+    let keyval = key.get_keyval();
+    let hardware_keycode = key.get_hardware_keycode() as u32;
+    let text = gdk::keyval_to_unicode(keyval);
+    let unmodified_text = gdk::keyval_to_unicode(gdk::keyval_to_lower(keyval));
+    let mods = get_modifiers(key.get_state());
+    KeyEvent::new(hardware_keycode, is_repeat, mods, text, unmodified_text)
+}
+
+impl WindowHandle {
+    pub fn show(&self) {
+        if let Some(state) = self.state.upgrade() {
+            state.window.show_all();
+            state.drawing_area.grab_focus();
+        }
+    }
+
+    /// Close the window.
+    pub fn close(&self) {
+        if let Some(state) = self.state.upgrade() {
+            state.window.close();
+        }
+    }
+
+    /// Bring this window to the front of the window stack and give it focus.
+    pub fn bring_to_front_and_focus(&self) {
+        if let Some(state) = self.state.upgrade() {
+            state.window.present();
+        }
+    }
+
+    /// Minimize the window.
+    pub fn minimize(&self) {
+        if let Some(state) = self.state.upgrade() {
+            state.window.iconify();
+        }
+    }
+
+    /// Toggle the window between maximized and its normal size.
+    pub fn zoom(&self) {
+        if let Some(state) = self.state.upgrade() {
+            if state.window.is_maximized() {
+                state.window.unmaximize();
+            } else {
+                state.window.maximize();
+            }
+        }
+    }
+
+    /// The window's frame, in screen coordinates.
+    pub fn get_frame(&self) -> Rect {
+        if let Some(state) = self.state.upgrade() {
+            let (x, y) = state.window.get_position();
+            let (width, height) = state.window.get_size();
+            Rect::from_origin_size((x as f64, y as f64), (width as f64, height as f64))
+        } else {
+            Rect::ZERO
+        }
+    }
+
+    /// Move the window's top left corner to `position`, in screen
+    /// coordinates.
+    pub fn set_position(&self, position: Point) {
+        if let Some(state) = self.state.upgrade() {
+            state
+                .window
+                .move_(position.x.round() as i32, position.y.round() as i32);
+        }
+    }
+
+    // Request invalidation of the entire window contents.
+    pub fn invalidate(&self) {
+        if let Some(state) = self.state.upgrade() {
+            state.drawing_area.queue_draw();
+        }
+    }
+
+    /// Set the title for this menu.
+    pub fn set_title(&self, title: &str) {
+        if let Some(state) = self.state.upgrade() {
+            state.window.set_title(title);
+        }
+    }
+
+    pub fn set_menu(&self, menu: Menu) {
+        if let Some(state) = self.state.upgrade() {
+            let menu_bar = menu.into_gtk_menubar(self, &state.accel_group);
+            if let Some(old) = state.menu_bar.replace(Some(menu_bar.clone())) {
+                state.vbox.remove(&old);
+            }
+            state.vbox.pack_start(&menu_bar, false, false, 0);
+            menu_bar.show_all();
+        }
+    }
+
+    pub fn show_context_menu(&self, menu: Menu, x: f64, y: f64) {
+        if let Some(state) = self.state.upgrade() {
+            if let Some(window) = state.drawing_area.get_window() {
+                let menu = menu.into_gtk_menu(self, &state.accel_group);
+                menu.set_property_attach_widget(Some(state.drawing_area.upcast_ref()));
+                menu.show_all();
+                let rect = gdk::Rectangle {
+                    x: x as i32,
+                    y: y as i32,
+                    width: 0,
+                    height: 0,
+                };
+                menu.popup_at_rect(
+                    &window,
+                    &rect,
+                    gdk::Gravity::NorthWest,
+                    gdk::Gravity::NorthWest,
+                    None,
+                );
+            }
+        }
+    }
+
+    /// Set whether animation frames are drawn in step with the display's
+    /// refresh.
+    ///
+    /// GTK always draws in step with its frame clock, so this does nothing.
+    pub fn set_vsync(&self, _vsync: bool) {}
+
+    /// Print a document, after showing the system's print dialog.
+    ///
+    /// This returns once the document has been sent to the printer, or the
+    /// dialog has been cancelled.
+    pub fn print(&self, setup: PageSetup, doc: Box<dyn Printable>) {
+        if let Some(state) = self.state.upgrade() {
+            print::print(&state.window, setup, doc);
+        }
+    }
+
+    /// Get a handle that can be used to schedule an idle task.
+    pub fn get_idle_handle(&self) -> Option<IdleHandle> {
+        let state = self.state.upgrade()?;
+        Some(IdleHandle {
+            window_id: state.id,
+            idle_queue: Arc::downgrade(&state.idle_queue),
+        })
+    }
+
+    /// Get the raw handle of the window, for use with other libraries.
+    ///
+    /// This is an Xlib handle under X11, and a Wayland handle under Wayland.
+    pub fn raw_window_handle(&self) -> RawWindowHandle {
+        let window = self
+            .state
+            .upgrade()
+            .and_then(|state| state.window.get_window());
+        match window {
+            Some(window) => gdk_window_handle(&window),
+            None => RawWindowHandle::Xlib(XlibHandle::empty()),
+        }
+    }
+
+    /// Get the dpi of the window.
+    ///
+    /// TODO: we want to migrate this from dpi (with 96 as nominal) to a scale
+    /// factor (with 1 as nominal).
+    pub fn get_dpi(&self) -> f32 {
+        self.state
+            .upgrade()
+            .map(|state| 96.0 * state.drawing_area.get_scale_factor() as f32)
+            .unwrap_or(96.0)
+    }
+
+    // TODO: the following methods are cut'n'paste code. A good way to DRY
+    // would be to have a platform-independent trait with these as methods with
+    // default implementations.
+
+    /// Convert a dimension in px units to physical pixels (rounding).
+    pub fn px_to_pixels(&self, x: f32) -> i32 {
+        (x * self.get_dpi() * (1.0 / 96.0)).round() as i32
+    }
+
+    /// Convert a point in px units to physical pixels (rounding).
+    pub fn px_to_pixels_xy(&self, x: f32, y: f32) -> (i32, i32) {
+        let scale = self.get_dpi() * (1.0 / 96.0);
+        ((x * scale).round() as i32, (y * scale).round() as i32)
+    }
+
+    /// Convert a dimension in physical pixels to px units.
+    pub fn pixels_to_px<T: Into<f64>>(&self, x: T) -> f32 {
+        (x.into() as f32) * 96.0 / self.get_dpi()
+    }
+
+    /// Convert a point in physical pixels to px units.
+    pub fn pixels_to_px_xy<T: Into<f64>>(&self, x: T, y: T) -> (f32, f32) {
+        let scale = 96.0 / self.get_dpi();
+        ((x.into() as f32) * scale, (y.into() as f32) * scale)
+    }
+
+    #[deprecated(since = "0.3", note = "use methods on WinCtx instead")]
+    pub fn file_dialog(
+        &self,
+        ty: FileDialogType,
+        options: FileDialogOptions,
+    ) -> Result<OsString, Error> {
+        let state = self.state.upgrade().ok_or(Error::Null)?;
+        dialog::get_file_dialog_path(&state.window, ty, options)
+    }
+
+    /// Call a closure with the window's handler and a context, if the window
+    /// is still alive and the handler isn't already in use.
+    ///
+    /// Events delivered while the handler is borrowed (for instance, while a
+    /// modal dialog runs its own loop) are dropped.
+    pub(crate) fn with_handler<F, R>(&self, f: F) -> Option<R>
+    where
+        F: FnOnce(&mut dyn WinHandler, &mut dyn WinCtx) -> R,
+    {
+        let state = self.state.upgrade()?;
+        let mut handler = match state.handler.try_borrow_mut() {
+            Ok(handler) => handler,
+            Err(_) => {
+                warn!("dropping event, window handler is already in use");
+                return None;
+            }
+        };
+        let mut ctx = WinCtxImpl {
+            handle: self,
+            text: Text::new(),
+        };
+        Some(f(&mut **handler, &mut ctx))
+    }
+}
+
+/// The platform handle of a GDK window.
+pub(crate) fn gdk_window_handle(window: &gdk::Window) -> RawWindowHandle {
+    let display = window.get_display();
+    unsafe {
+        let window_ptr = window.as_ptr() as *mut c_void;
+        let display_ptr = display.as_ptr() as *mut c_void;
+        if util::is_wayland() {
+            RawWindowHandle::Wayland(WaylandHandle {
+                surface: gdk_wayland_window_get_wl_surface(window_ptr),
+                display: gdk_wayland_display_get_wl_display(display_ptr),
+                ..WaylandHandle::empty()
+            })
+        } else {
+            RawWindowHandle::Xlib(XlibHandle {
+                window: gdk_x11_window_get_xid(window_ptr),
+                display: gdk_x11_display_get_xdisplay(display_ptr),
+                ..XlibHandle::empty()
+            })
+        }
+    }
+}
+
+// The queue is only accessed through its mutex, and the window state is
+// only looked up on the main thread.
+unsafe impl Send for IdleHandle {}
+
+impl IdleHandle {
+    /// Add an idle handler, which is called (once) when the message loop
+    /// is empty. The idle handler will be run from the main UI thread, and
+    /// won't be scheduled if the associated view has been dropped.
+    ///
+    /// Note: the name "idle" suggests that it will be scheduled with a lower
+    /// priority than other UI events, but that's not necessarily the case.
+    pub fn add_idle<F>(&self, callback: F)
+    where
+        F: FnOnce(&dyn Any) + Send + 'static,
+    {
+        self.add_idle_kind(IdleKind::Callback(Box::new(callback)));
+    }
+
+    /// Request a call to the window handler's [`idle`] method, with the
+    /// given token.
+    ///
+    /// [`idle`]: ../trait.WinHandler.html#method.idle
+    pub fn schedule_idle(&self, token: IdleToken) {
+        self.add_idle_kind(IdleKind::Token(token));
+    }
+
+    fn add_idle_kind(&self, kind: IdleKind) {
+        if let Some(queue) = self.idle_queue.upgrade() {
+            let mut queue = queue.lock().expect("queue lock");
+            if queue.is_empty() {
+                let window_id = self.window_id;
+                glib::idle_add(move || {
+                    run_idle(window_id);
+                    glib::Continue(false)
+                });
+            }
+            queue.push(kind);
+        }
+    }
+}
+
+fn run_idle(window_id: usize) {
+    let state = match WINDOWS.with(|windows| windows.borrow().get(&window_id).cloned()) {
+        Some(state) => state,
+        None => return,
+    };
+    // The handler is in use while a nested main loop, such as a modal
+    // dialog's, runs from one of its methods. The callbacks stay queued,
+    // since nothing else schedules them again while the queue isn't empty.
+    let mut handler = match state.handler.try_borrow_mut() {
+        Ok(handler) => handler,
+        Err(_) => {
+            gtk::timeout_add(IDLE_RETRY_MS, move || {
+                run_idle(window_id);
+                glib::Continue(false)
+            });
+            return;
+        }
+    };
+    let queue: Vec<_> = mem::replace(&mut state.idle_queue.lock().expect("queue"), Vec::new());
+    let handle = WindowHandle {
+        state: Rc::downgrade(&state),
+    };
+    let mut ctx = WinCtxImpl {
+        handle: &handle,
+        text: Text::new(),
+    };
+    for item in queue {
+        match item {
+            IdleKind::Callback(callback) => callback.call(handler.as_any()),
+            IdleKind::Token(token) => handler.idle(token, &mut ctx),
+        }
+    }
+}
+
+impl<'a> WinCtx<'a> for WinCtxImpl<'a> {
+    fn invalidate(&mut self) {
+        self.handle.invalidate();
+    }
+
+    fn presentation_time(&self) -> Option<Instant> {
+        self.handle.state.upgrade()?.presentation_time.get()
+    }
+
+    fn text_factory(&mut self) -> &mut Text<'a> {
+        &mut self.text
+    }
+
+    fn set_cursor(&mut self, cursor: &Cursor) {
+        let state = match self.handle.state.upgrade() {
+            Some(state) => state,
+            None => return,
+        };
+        if let Some(gdk_window) = state.drawing_area.get_window() {
+            let cursor_type = match cursor {
+                Cursor::Arrow => gdk::CursorType::LeftPtr,
+                Cursor::IBeam => gdk::CursorType::Xterm,
+                Cursor::Crosshair => gdk::CursorType::Crosshair,
+                Cursor::OpenHand => gdk::CursorType::Hand1,
+                Cursor::NotAllowed => gdk::CursorType::XCursor,
+                Cursor::ResizeLeftRight => gdk::CursorType::SbHDoubleArrow,
+                Cursor::ResizeUpDown => gdk::CursorType::SbVDoubleArrow,
+            };
+            let cursor = gdk::Cursor::new_for_display(&gdk_window.get_display(), cursor_type);
+            gdk_window.set_cursor(Some(&cursor));
+        }
+    }
+
+    fn request_timer(&mut self, deadline: Instant) -> TimerToken {
+        let interval = deadline
+            .checked_duration_since(Instant::now())
+            .map(|d| d.as_millis() as u32)
+            .unwrap_or(0);
+        let token = TimerToken::new(next_timer_id());
+        let handle = self.handle.clone();
+        gtk::timeout_add(interval, move || {
+            handle.with_handler(|handler, ctx| handler.timer(token, ctx));
+            glib::Continue(false)
+        });
+        token
+    }
+
+    fn open_file_sync(&mut self, options: FileDialogOptions) -> Option<FileInfo> {
+        let state = self.handle.state.upgrade()?;
+        dialog::get_file_dialog_path(&state.window, FileDialogType::Open, options)
+            .ok()
+            .map(|s| FileInfo { path: s.into() })
+    }
+
+    fn set_clipboard_contents(&mut self, contents: ClipboardItem) {
+        Application::set_clipboard_contents(contents);
+    }
+
+    fn start_drag(&mut self, contents: DragContents) {
+        if let Some(state) = self.handle.state.upgrade() {
+            dnd::begin_drag(&state, contents);
+        }
+    }
+
+    fn set_ime_enabled(&mut self, enabled: bool) {
+        if let Some(state) = self.handle.state.upgrade() {
+            ime::set_enabled(&state, enabled);
+        }
+    }
+
+    fn set_ime_cursor_rect(&mut self, rect: Rect) {
+        if let Some(state) = self.handle.state.upgrade() {
+            ime::set_cursor_rect(&state, rect);
+        }
+    }
+}
+
+fn next_window_id() -> usize {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static WINDOW_ID: AtomicUsize = AtomicUsize::new(1);
+    WINDOW_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+fn next_timer_id() -> usize {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static TIMER_ID: AtomicUsize = AtomicUsize::new(1);
+    TIMER_ID.fetch_add(1, Ordering::Relaxed)
+}
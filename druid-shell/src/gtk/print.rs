@@ -0,0 +1,85 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Printing, GTK implementation.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gtk::{
+    PageSetup as GtkPageSetup, PaperSize, PrintContext, PrintOperation, PrintOperationAction,
+    PrintOperationExt, Unit, Window,
+};
+use log::error;
+use piet_common::{Piet, RenderContext};
+
+use crate::kurbo::Size;
+use crate::print::{PageSetup, Printable};
+
+pub(crate) fn print(window: &Window, setup: PageSetup, doc: Box<dyn Printable>) {
+    let page_setup = GtkPageSetup::new();
+    let paper = PaperSize::new_custom(
+        "druid-custom",
+        "Custom",
+        setup.paper.width,
+        setup.paper.height,
+        Unit::Points,
+    );
+    page_setup.set_paper_size(&paper);
+    page_setup.set_top_margin(setup.margin, Unit::Points);
+    page_setup.set_bottom_margin(setup.margin, Unit::Points);
+    page_setup.set_left_margin(setup.margin, Unit::Points);
+    page_setup.set_right_margin(setup.margin, Unit::Points);
+
+    let op = PrintOperation::new();
+    op.set_default_page_setup(Some(&page_setup));
+    op.set_unit(Unit::Points);
+
+    let doc = Rc::new(RefCell::new(doc));
+    let begin_doc = doc.clone();
+    op.connect_begin_print(move |op, ctx| {
+        // The user may have chosen different paper in the dialog.
+        let page_size = page_size(ctx).unwrap_or_else(|| setup.printable_size());
+        let pages = with_piet(ctx, |piet| begin_doc.borrow_mut().paginate(page_size, piet));
+        op.set_n_pages(pages.unwrap_or(1).max(1) as i32);
+    });
+    op.connect_draw_page(move |_op, ctx, page| {
+        let page_size = page_size(ctx).unwrap_or_else(|| setup.printable_size());
+        with_piet(ctx, |piet| {
+            doc.borrow_mut().paint_page(page as usize, page_size, piet)
+        });
+    });
+    if let Err(e) = op.run(PrintOperationAction::PrintDialog, Some(window)) {
+        error!("printing failed: {}", e);
+    }
+}
+
+/// The size of the printable area, in points.
+fn page_size(ctx: &PrintContext) -> Option<Size> {
+    let setup = ctx.get_page_setup()?;
+    Some(Size::new(
+        setup.get_page_width(Unit::Points),
+        setup.get_page_height(Unit::Points),
+    ))
+}
+
+fn with_piet<R>(ctx: &PrintContext, f: impl FnOnce(&mut Piet) -> R) -> Option<R> {
+    let mut cairo_ctx = ctx.get_cairo_context()?;
+    let mut piet = Piet::new(&mut cairo_ctx);
+    let result = f(&mut piet);
+    if let Err(e) = piet.finish() {
+        error!("{}", e);
+    }
+    Some(result)
+}
@@ -0,0 +1,60 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Utilities, GTK specific.
+
+use std::env;
+
+use glib::ObjectExt;
+
+pub fn init() {
+    gtk::init().expect("failed to initialize GTK");
+}
+
+/// Panic if not on the main thread.
+///
+/// GTK is not thread safe, and the bindings check this on most calls;
+/// this lets us fail early with a clearer message.
+pub fn assert_main_thread() {
+    assert!(gtk::is_initialized_main_thread());
+}
+
+/// Returns `true` if we are running as a native Wayland client, as opposed
+/// to on X11 (including through XWayland).
+///
+/// GDK handles most of the differences itself, including drawing
+/// client-side window decorations when the compositor doesn't provide them.
+pub fn is_wayland() -> bool {
+    gdk::Display::get_default()
+        .map(|display| display.get_type().name() == "GdkWaylandDisplay")
+        .unwrap_or(false)
+}
+
+/// Returns the current locale string.
+///
+/// This should a [Unicode language identifier].
+///
+/// [Unicode language identifier]: https://unicode.org/reports/tr35/#Unicode_language_identifier
+pub fn get_locale() -> String {
+    // POSIX locales look like "en_US.UTF-8@euro"; we want "en_US".
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|val| !val.is_empty() && val != "C" && val != "POSIX")
+        .unwrap_or_else(|| "en_US".to_string());
+    let end = locale
+        .find(|c| c == '.' || c == '@')
+        .unwrap_or(locale.len());
+    locale[..end].to_string()
+}
@@ -0,0 +1,119 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Video decoding, GTK implementation, using GStreamer.
+
+use std::path::Path;
+use std::time::Duration;
+
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_app::AppSink;
+use gstreamer_video::VideoInfo;
+
+use log::warn;
+
+use crate::video::VideoFrame;
+use crate::Error;
+
+/// The number of decoded frames to keep ready.
+const QUEUED_FRAMES: u32 = 4;
+
+/// Reads the frames of a video file.
+pub struct VideoDecoder {
+    pipeline: gst::Pipeline,
+    sink: AppSink,
+}
+
+impl VideoDecoder {
+    /// Open a video file, ready to decode its first frame.
+    pub fn open(path: &Path) -> Result<VideoDecoder, Error> {
+        gst::init().map_err(|e| {
+            warn!("failed to initialize gstreamer: {}", e);
+            Error::Null
+        })?;
+        let uri = glib::filename_to_uri(path, None).map_err(|_| Error::Null)?;
+        // The sink doesn't sync to the clock, because the caller decides
+        // when frames are shown; it just keeps a few decoded frames ready.
+        let description = format!(
+            "uridecodebin uri=\"{}\" ! videoconvert ! \
+             appsink name=sink caps=video/x-raw,format=RGBA sync=false max-buffers={}",
+            uri, QUEUED_FRAMES
+        );
+        let pipeline = gst::parse_launch(&description)
+            .map_err(|e| {
+                warn!("failed to create video pipeline: {}", e);
+                Error::Null
+            })?
+            .downcast::<gst::Pipeline>()
+            .map_err(|_| Error::Null)?;
+        let sink = pipeline
+            .get_by_name("sink")
+            .and_then(|sink| sink.dynamic_cast::<AppSink>().ok())
+            .ok_or(Error::Null)?;
+        pipeline
+            .set_state(gst::State::Playing)
+            .map_err(|_| Error::Null)?;
+        Ok(VideoDecoder { pipeline, sink })
+    }
+
+    /// The length of the video, if known.
+    pub fn duration(&self) -> Option<Duration> {
+        self.pipeline
+            .query_duration::<gst::ClockTime>()
+            .and_then(|time| time.nseconds())
+            .map(Duration::from_nanos)
+    }
+
+    /// Move to the given time. The next frame is the one shown at that time.
+    pub fn seek(&mut self, time: Duration) {
+        let nanos = time.as_secs() * 1_000_000_000 + u64::from(time.subsec_nanos());
+        let flags = gst::SeekFlags::FLUSH | gst::SeekFlags::ACCURATE;
+        if let Err(e) = self
+            .pipeline
+            .seek_simple(flags, gst::ClockTime::from_nseconds(nanos))
+        {
+            warn!("failed to seek video: {}", e);
+        }
+    }
+
+    /// Decode the next frame, or return `None` at the end of the video.
+    ///
+    /// This blocks until the frame has been decoded.
+    pub fn next_frame(&mut self) -> Option<VideoFrame> {
+        let sample = self.sink.pull_sample().ok()?;
+        let info = VideoInfo::from_caps(sample.get_caps()?).ok()?;
+        let buffer = sample.get_buffer()?;
+        let map = buffer.map_readable().ok()?;
+        let (width, height) = (info.width() as usize, info.height() as usize);
+        let stride = info.stride()[0] as usize;
+        let mut pixels = Vec::with_capacity(width * height * 4);
+        for row in map.as_slice().chunks(stride).take(height) {
+            pixels.extend_from_slice(&row[..width * 4]);
+        }
+        let time = buffer.get_pts().nseconds().unwrap_or(0);
+        Some(VideoFrame {
+            time: Duration::from_nanos(time),
+            width,
+            height,
+            pixels,
+        })
+    }
+}
+
+impl Drop for VideoDecoder {
+    fn drop(&mut self) {
+        let _ = self.pipeline.set_state(gst::State::Null);
+    }
+}
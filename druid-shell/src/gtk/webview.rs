@@ -0,0 +1,130 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Embedded web content, GTK implementation, using WebKitGTK.
+
+use std::cell::RefCell;
+
+use gio::Cancellable;
+use glib::{ObjectExt, SignalHandlerId};
+use webkit2gtk::{
+    UserContentInjectedFrames, UserContentManager, UserContentManagerExt, UserScript,
+    UserScriptInjectionTime, WebViewExt,
+};
+
+use log::warn;
+
+use crate::webview::{BRIDGE_SCRIPT, MESSAGE_HANDLER};
+use crate::Error;
+
+use super::ChildView;
+
+/// A view showing web content.
+pub struct WebView {
+    view: ChildView,
+    webview: webkit2gtk::WebView,
+    manager: UserContentManager,
+    message_handler: Option<SignalHandlerId>,
+}
+
+impl WebView {
+    /// Create a new web view, initially empty.
+    ///
+    /// The callback is called with each message posted by the page.
+    pub fn new(on_message: impl FnMut(String) + 'static) -> Result<WebView, Error> {
+        let manager = UserContentManager::new();
+        if !manager.register_script_message_handler(MESSAGE_HANDLER) {
+            return Err(Error::Null);
+        }
+        let on_message = RefCell::new(on_message);
+        let message_handler = manager.connect_script_message_received(move |_, result| {
+            let message = match (result.get_global_context(), result.get_value()) {
+                (Some(context), Some(value)) => value.to_string(&context),
+                _ => None,
+            };
+            match message {
+                Some(message) => (&mut *on_message.borrow_mut())(message),
+                None => warn!("dropping script message that isn't a string"),
+            }
+        });
+        let script = UserScript::new(
+            BRIDGE_SCRIPT,
+            UserContentInjectedFrames::TopFrame,
+            UserScriptInjectionTime::Start,
+            &[],
+            &[],
+        );
+        manager.add_script(&script);
+
+        let webview = webkit2gtk::WebView::new_with_user_content_manager(&manager);
+        Ok(WebView {
+            view: ChildView::from_widget(webview.clone()),
+            webview,
+            manager,
+            message_handler: Some(message_handler),
+        })
+    }
+
+    /// The view, for placing in a window.
+    pub fn view(&self) -> &ChildView {
+        &self.view
+    }
+
+    /// Start loading the given URL.
+    pub fn load_url(&self, url: &str) {
+        self.webview.load_uri(url);
+    }
+
+    /// Show the given HTML. Relative links are resolved against `base_url`.
+    pub fn load_html(&self, html: &str, base_url: Option<&str>) {
+        self.webview.load_html(html, base_url);
+    }
+
+    pub fn go_back(&self) {
+        self.webview.go_back();
+    }
+
+    pub fn go_forward(&self) {
+        self.webview.go_forward();
+    }
+
+    pub fn reload(&self) {
+        self.webview.reload();
+    }
+
+    pub fn stop(&self) {
+        self.webview.stop_loading();
+    }
+
+    /// Run a script in the context of the current page. The result is
+    /// discarded.
+    pub fn eval_script(&self, script: &str) {
+        self.webview
+            .run_javascript(script, None::<&Cancellable>, |result| {
+                if let Err(err) = result {
+                    warn!("script failed: {}", err);
+                }
+            });
+    }
+}
+
+impl Drop for WebView {
+    fn drop(&mut self) {
+        if let Some(id) = self.message_handler.take() {
+            self.manager.disconnect(id);
+        }
+        self.manager
+            .unregister_script_message_handler(MESSAGE_HANDLER);
+    }
+}
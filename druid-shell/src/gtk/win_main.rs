@@ -0,0 +1,45 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! GTK implementation of runloop.
+
+use crate::util::assert_main_thread;
+
+pub struct RunLoop {}
+
+impl RunLoop {
+    pub fn new() -> RunLoop {
+        assert_main_thread();
+        RunLoop {}
+    }
+
+    pub fn run(&mut self) {
+        gtk::main();
+    }
+}
+
+/// Process any pending events, without blocking.
+///
+/// This is for applications embedded in a host that doesn't itself run
+/// the GTK main loop.
+pub fn pump_events() {
+    while gtk::events_pending() {
+        gtk::main_iteration_do(false);
+    }
+}
+
+/// Request to quit the application, exiting the runloop.
+pub fn request_quit() {
+    gtk::main_quit();
+}
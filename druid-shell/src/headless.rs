@@ -0,0 +1,349 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A window without a display, for tests and servers.
+//!
+//! A [`HeadlessWindow`] owns a [`WinHandler`] and drives it directly; events
+//! are injected by calling methods on the window, and painting renders into
+//! an in-memory image. Nothing here touches the windowing system, so it works
+//! on machines without a display server.
+//!
+//! The handler is connected to a default [`WindowHandle`], on which
+//! operations such as setting the title or showing a menu do nothing.
+//!
+//! This is only available on platforms where piet draws with cairo.
+//!
+//! [`HeadlessWindow`]: struct.HeadlessWindow.html
+//! [`WinHandler`]: ../window/trait.WinHandler.html
+//! [`WindowHandle`]: ../window/struct.WindowHandle.html
+
+use std::any::Any;
+use std::time::{Duration, Instant};
+
+use cairo::{Context, Format, ImageSurface};
+use log::{error, warn};
+use piet_common::{Piet, RenderContext};
+
+use crate::clipboard::ClipboardItem;
+use crate::dialog::FileDialogOptions;
+use crate::dnd::DragContents;
+pub use crate::image::RgbaImage;
+use crate::keyboard::KeyEvent;
+use crate::kurbo::Rect;
+use crate::window::{
+    Cursor, FileInfo, MouseEvent, Text, TimerToken, WheelEvent, WinCtx, WinHandler, WindowHandle,
+};
+use crate::Error;
+
+/// A window that renders offscreen and is driven programmatically.
+pub struct HeadlessWindow {
+    handler: Box<dyn WinHandler>,
+    state: HeadlessState,
+}
+
+/// Everything the window records about the handler's requests.
+struct HeadlessState {
+    width: u32,
+    height: u32,
+    /// The window's notion of the current time, for firing timers.
+    now: Instant,
+    timers: Vec<(Instant, TimerToken)>,
+    cursor: Option<Cursor>,
+    clipboard: Option<ClipboardItem>,
+    invalidated: bool,
+}
+
+struct HeadlessCtx<'a> {
+    state: &'a mut HeadlessState,
+    text: Text<'static>,
+}
+
+impl HeadlessWindow {
+    /// Create a window of the given size, in pixels, and connect the handler.
+    pub fn new(mut handler: Box<dyn WinHandler>, width: u32, height: u32) -> HeadlessWindow {
+        handler.connect(&WindowHandle::default());
+        let mut window = HeadlessWindow {
+            handler,
+            state: HeadlessState {
+                width,
+                height,
+                now: Instant::now(),
+                timers: Vec::new(),
+                cursor: None,
+                clipboard: None,
+                invalidated: true,
+            },
+        };
+        window.with_handler(|handler, ctx| handler.size(width, height, ctx));
+        window
+    }
+
+    /// Call a closure with the window's handler and a context.
+    ///
+    /// This is the way to invoke handler methods not covered by the
+    /// methods on this type.
+    pub fn with_handler<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut dyn WinHandler, &mut dyn WinCtx) -> R,
+    {
+        let mut ctx = HeadlessCtx {
+            state: &mut self.state,
+            text: Text::new(),
+        };
+        f(&mut *self.handler, &mut ctx)
+    }
+
+    /// Get the handler, for downcasting to its concrete type.
+    pub fn handler_as_any(&mut self) -> &mut dyn Any {
+        self.handler.as_any()
+    }
+
+    /// Change the size of the window, in pixels.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.state.width = width;
+        self.state.height = height;
+        self.state.invalidated = true;
+        self.with_handler(|handler, ctx| handler.size(width, height, ctx));
+    }
+
+    /// The size of the window, in pixels.
+    pub fn size(&self) -> (u32, u32) {
+        (self.state.width, self.state.height)
+    }
+
+    /// Set the safe area of the window, as a mobile platform would.
+    pub fn set_safe_area(&mut self, area: Rect) {
+        self.with_handler(|handler, ctx| handler.safe_area(area, ctx));
+    }
+
+    pub fn mouse_move(&mut self, event: &MouseEvent) {
+        self.with_handler(|handler, ctx| handler.mouse_move(event, ctx));
+    }
+
+    pub fn mouse_down(&mut self, event: &MouseEvent) {
+        self.with_handler(|handler, ctx| handler.mouse_down(event, ctx));
+    }
+
+    pub fn mouse_up(&mut self, event: &MouseEvent) {
+        self.with_handler(|handler, ctx| handler.mouse_up(event, ctx));
+    }
+
+    pub fn wheel(&mut self, event: &WheelEvent) {
+        self.with_handler(|handler, ctx| handler.wheel(event, ctx));
+    }
+
+    /// Send a key down event, returning `true` if it was handled.
+    pub fn key_down(&mut self, event: KeyEvent) -> bool {
+        self.with_handler(|handler, ctx| handler.key_down(event, ctx))
+    }
+
+    pub fn key_up(&mut self, event: KeyEvent) {
+        self.with_handler(|handler, ctx| handler.key_up(event, ctx));
+    }
+
+    /// Send a menu command, as if the item with this id had been chosen.
+    pub fn command(&mut self, id: u32) {
+        self.with_handler(|handler, ctx| handler.command(id, ctx));
+    }
+
+    pub fn got_focus(&mut self) {
+        self.with_handler(|handler, ctx| handler.got_focus(ctx));
+    }
+
+    /// Move the window's clock forward, firing any timers that come due.
+    ///
+    /// Timers fire in deadline order. The clock starts at the time the
+    /// window was created, and only moves when this is called.
+    pub fn advance_time(&mut self, duration: Duration) {
+        self.state.now += duration;
+        loop {
+            let now = self.state.now;
+            let due = self
+                .state
+                .timers
+                .iter()
+                .enumerate()
+                .filter(|(_, (deadline, _))| *deadline <= now)
+                .min_by_key(|(_, (deadline, _))| *deadline)
+                .map(|(i, _)| i);
+            match due {
+                Some(i) => {
+                    let (_, token) = self.state.timers.remove(i);
+                    self.with_handler(|handler, ctx| handler.timer(token, ctx));
+                }
+                None => break,
+            }
+        }
+    }
+
+    /// The number of timers that have been requested but not yet fired.
+    pub fn pending_timers(&self) -> usize {
+        self.state.timers.len()
+    }
+
+    /// Returns `true` if the handler has requested a repaint since the last
+    /// call to [`paint`].
+    ///
+    /// [`paint`]: #method.paint
+    pub fn is_invalidated(&self) -> bool {
+        self.state.invalidated
+    }
+
+    /// The cursor most recently set by the handler.
+    pub fn cursor(&self) -> Option<&Cursor> {
+        self.state.cursor.as_ref()
+    }
+
+    /// The contents most recently put on the clipboard by the handler.
+    pub fn clipboard(&self) -> Option<&ClipboardItem> {
+        self.state.clipboard.as_ref()
+    }
+
+    /// Paint the window into a new image.
+    ///
+    /// If the handler requests an animation frame, the window remains
+    /// invalidated.
+    pub fn paint(&mut self) -> Result<RgbaImage, Error> {
+        let (width, height) = (self.state.width, self.state.height);
+        self.state.invalidated = false;
+        let (image, anim) = render_image(width, height, |piet_ctx| {
+            self.with_handler(|handler, ctx| handler.paint(piet_ctx, ctx))
+        })?;
+        if anim {
+            self.state.invalidated = true;
+        }
+        Ok(image)
+    }
+}
+
+/// Render into a new image of the given size, in pixels, returning the
+/// image and the result of the paint function.
+///
+/// Unlike the rest of this module, this is usable alongside real windows,
+/// for painting content to be drawn again later.
+pub fn render_image<R>(
+    width: u32,
+    height: u32,
+    paint: impl FnOnce(&mut Piet) -> R,
+) -> Result<(RgbaImage, R), Error> {
+    let mut surface =
+        ImageSurface::create(Format::ARgb32, width as i32, height as i32).map_err(|e| {
+            error!("failed to create surface: {:?}", e);
+            Error::Null
+        })?;
+    let result = {
+        let mut cairo_ctx = Context::new(&surface);
+        let mut piet_ctx = Piet::new(&mut cairo_ctx);
+        let result = paint(&mut piet_ctx);
+        if let Err(e) = piet_ctx.finish() {
+            error!("{}", e)
+        }
+        result
+    };
+    surface.flush();
+    let stride = surface.get_stride() as usize;
+    let data = surface.get_data().map_err(|e| {
+        error!("failed to read surface: {:?}", e);
+        Error::Null
+    })?;
+    let image = RgbaImage::from_cairo(&data, stride, width as usize, height as usize);
+    Ok((image, result))
+}
+
+impl RgbaImage {
+    /// Convert premultiplied native-endian ARGB32 pixels, as used by cairo.
+    fn from_cairo(data: &[u8], stride: usize, width: usize, height: usize) -> RgbaImage {
+        let mut pixels = Vec::with_capacity(width * height * 4);
+        for y in 0..height {
+            for x in 0..width {
+                let start = y * stride + x * 4;
+                let argb = u32::from_ne_bytes([
+                    data[start],
+                    data[start + 1],
+                    data[start + 2],
+                    data[start + 3],
+                ]);
+                let a = (argb >> 24) as u8;
+                let unpremultiply = |c: u32| {
+                    if a == 0 {
+                        0
+                    } else {
+                        ((c & 0xff) * 255 / a as u32) as u8
+                    }
+                };
+                pixels.push(unpremultiply(argb >> 16));
+                pixels.push(unpremultiply(argb >> 8));
+                pixels.push(unpremultiply(argb));
+                pixels.push(a);
+            }
+        }
+        RgbaImage {
+            width,
+            height,
+            pixels,
+        }
+    }
+}
+
+impl<'a> WinCtx<'a> for HeadlessCtx<'a> {
+    fn invalidate(&mut self) {
+        self.state.invalidated = true;
+    }
+
+    fn text_factory(&mut self) -> &mut Text<'a> {
+        &mut self.text
+    }
+
+    fn set_cursor(&mut self, cursor: &Cursor) {
+        self.state.cursor = Some(cursor.clone());
+    }
+
+    fn request_timer(&mut self, deadline: Instant) -> TimerToken {
+        let token = next_timer_id();
+        self.state.timers.push((deadline, token));
+        token
+    }
+
+    fn open_file_sync(&mut self, _options: FileDialogOptions) -> Option<FileInfo> {
+        warn!("file dialogs are not available in a headless window");
+        None
+    }
+
+    fn set_clipboard_contents(&mut self, contents: ClipboardItem) {
+        self.state.clipboard = Some(contents);
+    }
+
+    fn start_drag(&mut self, _contents: DragContents) {
+        warn!("dragging out of a headless window is not supported");
+    }
+}
+
+fn next_timer_id() -> TimerToken {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static TIMER_ID: AtomicUsize = AtomicUsize::new(1);
+    TimerToken::new(TIMER_ID.fetch_add(1, Ordering::Relaxed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unpremultiply() {
+        // Half-transparent pure red, premultiplied.
+        let data = 0x8080_0000u32.to_ne_bytes();
+        let image = RgbaImage::from_cairo(&data, 4, 1, 1);
+        assert_eq!(image.pixel(0, 0), [255, 0, 0, 128]);
+    }
+}
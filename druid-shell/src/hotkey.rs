@@ -18,7 +18,7 @@ use std::borrow::Borrow;
 
 use log::warn;
 
-use crate::keyboard::{KeyCode, KeyEvent, KeyModifiers};
+use crate::keyboard::{KbKey, KeyCode, KeyEvent, KeyModifiers};
 
 /// A description of a keyboard shortcut.
 ///
@@ -65,6 +65,7 @@ pub struct HotKey {
 #[derive(Debug, Clone, PartialEq)]
 pub enum KeyCompare {
     Code(KeyCode),
+    Key(KbKey),
     Text(&'static str),
 }
 
@@ -75,13 +76,16 @@ impl HotKey {
     /// or an instance of either [`SysMods`], or [`RawMods`]. [`SysMods`] unify the
     /// 'Command' key on macOS with the 'Ctrl' key on other platforms.
     ///
-    /// The second argument describes the non-modifier key. This can be either
-    /// a `&'static str` or a [`KeyCode`]. If it is a `&str`, it will be compared
-    /// against the [`unmodified text`] for a given key event; if it is a [`KeyCode`]
-    /// it will be compared against the event's key code.
+    /// The second argument describes the non-modifier key. This can be
+    /// a `&'static str`, a [`KbKey`] or a [`KeyCode`]. If it is a `&str`, it will
+    /// be compared against the logical key and the text of a given key event,
+    /// so that shortcuts follow the user's keyboard layout; a [`KbKey`] is
+    /// compared against the event's [`key`], and a [`KeyCode`] against the
+    /// event's physical key code.
     ///
-    /// In general, [`KeyCode`] should be preferred for non-printing keys, like
-    /// the arrows or backspace.
+    /// In general, [`KbKey`] should be preferred for non-printing keys, like
+    /// the arrows or backspace, and [`KeyCode`] kept for shortcuts that are
+    /// about the position of a key rather than its meaning.
     ///
     /// # Examples
     /// ```
@@ -93,10 +97,11 @@ impl HotKey {
     /// let macos_fullscreen = HotKey::new(RawMods::CtrlMeta, "f");
     /// ```
     ///
-    /// [`KeyCode`]: enum.KeyCode.html
+    /// [`KeyCode`]: ../keyboard/enum.KeyCode.html
+    /// [`KbKey`]: ../keyboard/enum.KbKey.html
+    /// [`key`]: ../keyboard/struct.KeyEvent.html#structfield.key
     /// [`SysMods`]: enum.SysMods.html
     /// [`RawMods`]: enum.RawMods.html
-    /// [`unmodified text`]: struct.KeyEvent.html#method.unmod_text
     pub fn new(mods: impl Into<Option<RawMods>>, key: impl Into<KeyCompare>) -> Self {
         HotKey {
             mods: mods.into().unwrap_or(RawMods::None),
@@ -129,9 +134,73 @@ impl HotKey {
         self.mods == event.mods
             && match self.key {
                 KeyCompare::Code(code) => code == event.key_code,
-                KeyCompare::Text(text) => Some(text) == event.text(),
+                KeyCompare::Key(key) => key == event.key,
+                KeyCompare::Text(text) => {
+                    Some(text) == event.text() || is_single_char(text, event.key)
+                }
             }
     }
+
+    /// The logical key of this hotkey, for platforms that describe
+    /// shortcuts by key rather than by key code.
+    ///
+    /// Key codes are resolved using a US layout.
+    pub(crate) fn kb_key(&self) -> Option<KbKey> {
+        match self.key {
+            KeyCompare::Text(text) => text.chars().next().map(KbKey::Character),
+            KeyCompare::Key(key) => Some(key),
+            KeyCompare::Code(code) => Some(KbKey::new(code, "")),
+        }
+        .filter(|key| *key != KbKey::Unidentified)
+    }
+
+    /// A human-readable description of this hotkey, such as "Ctrl+Shift+S",
+    /// for platforms where menus do not display shortcuts themselves.
+    #[cfg(any(target_os = "windows", test))]
+    pub(crate) fn label(&self) -> String {
+        let mods: KeyModifiers = self.mods.into();
+        let mut label = String::new();
+        for (on, name) in &[
+            (mods.ctrl, "Ctrl"),
+            (mods.alt, "Alt"),
+            (mods.shift, "Shift"),
+            (mods.meta, "Win"),
+        ] {
+            if *on {
+                label.push_str(name);
+                label.push('+');
+            }
+        }
+        match self.kb_key() {
+            Some(KbKey::Character(' ')) => label.push_str("Space"),
+            Some(KbKey::Character(c)) => label.extend(c.to_uppercase()),
+            Some(KbKey::Escape) => label.push_str("Esc"),
+            Some(KbKey::Delete) => label.push_str("Del"),
+            Some(KbKey::Insert) => label.push_str("Ins"),
+            Some(KbKey::PageUp) => label.push_str("PgUp"),
+            Some(KbKey::PageDown) => label.push_str("PgDn"),
+            Some(KbKey::ArrowUp) => label.push_str("Up"),
+            Some(KbKey::ArrowDown) => label.push_str("Down"),
+            Some(KbKey::ArrowLeft) => label.push_str("Left"),
+            Some(KbKey::ArrowRight) => label.push_str("Right"),
+            Some(key) => label.push_str(&format!("{:?}", key)),
+            None => {
+                label.pop();
+            }
+        }
+        label
+    }
+}
+
+/// Returns `true` if `text` is exactly the character of a `KbKey::Character`.
+fn is_single_char(text: &str, key: KbKey) -> bool {
+    match key {
+        KbKey::Character(c) => {
+            let mut chars = text.chars();
+            chars.next() == Some(c) && chars.next().is_none()
+        }
+        _ => false,
+    }
 }
 
 /// A platform-agnostic representation of keyboard modifiers, for command handling.
@@ -265,8 +334,30 @@ impl From<KeyCode> for KeyCompare {
     }
 }
 
+impl From<KbKey> for KeyCompare {
+    fn from(src: KbKey) -> KeyCompare {
+        KeyCompare::Key(src)
+    }
+}
+
 impl From<&'static str> for KeyCompare {
     fn from(src: &'static str) -> KeyCompare {
         KeyCompare::Text(src)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels() {
+        assert_eq!(HotKey::new(RawMods::CtrlShift, "s").label(), "Ctrl+Shift+S");
+        assert_eq!(HotKey::new(None, KbKey::F5).label(), "F5");
+        assert_eq!(HotKey::new(RawMods::Alt, KeyCode::KeyQ).label(), "Alt+Q");
+        assert_eq!(
+            HotKey::new(RawMods::Ctrl, KbKey::PageDown).label(),
+            "Ctrl+PgDn"
+        );
+    }
+}
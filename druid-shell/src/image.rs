@@ -0,0 +1,136 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Images in memory.
+
+use std::fmt;
+
+/// An image in non-premultiplied RGBA order, four bytes per pixel.
+#[derive(Clone)]
+pub struct RgbaImage {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    /// The color of the pixel at the given location, as `[r, g, b, a]`.
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        let start = (y * self.width + x) * 4;
+        let mut pixel = [0; 4];
+        pixel.copy_from_slice(&self.pixels[start..start + 4]);
+        pixel
+    }
+
+    /// Encode the image in the [PAM] format, which is simple enough to need
+    /// no dependencies, and is read by most image tools.
+    ///
+    /// [PAM]: http://netpbm.sourceforge.net/doc/pam.html
+    pub fn to_pam(&self) -> Vec<u8> {
+        let header = format!(
+            "P7\nWIDTH {}\nHEIGHT {}\nDEPTH 4\nMAXVAL 255\nTUPLTYPE RGB_ALPHA\nENDHDR\n",
+            self.width, self.height
+        );
+        let mut data = header.into_bytes();
+        data.extend_from_slice(&self.pixels);
+        data
+    }
+
+    /// Decode an image written by [`to_pam`].
+    ///
+    /// Returns `None` if the data is not an RGBA PAM image.
+    ///
+    /// [`to_pam`]: #method.to_pam
+    pub fn from_pam(data: &[u8]) -> Option<RgbaImage> {
+        const END: &[u8] = b"ENDHDR\n";
+        let header_len = data.windows(END.len()).position(|w| w == END)? + END.len();
+        let header = std::str::from_utf8(&data[..header_len]).ok()?;
+        let mut lines = header.lines();
+        if lines.next()? != "P7" {
+            return None;
+        }
+        let (mut width, mut height, mut depth) = (None, None, None);
+        for line in lines {
+            let mut parts = line.split_whitespace();
+            match (
+                parts.next(),
+                parts.next().and_then(|v| v.parse::<usize>().ok()),
+            ) {
+                (Some("WIDTH"), value) => width = value,
+                (Some("HEIGHT"), value) => height = value,
+                (Some("DEPTH"), value) => depth = value,
+                _ => (),
+            }
+        }
+        let (width, height) = (width?, height?);
+        let pixels = &data[header_len..];
+        if depth != Some(4) || pixels.len() != width * height * 4 {
+            return None;
+        }
+        Some(RgbaImage {
+            width,
+            height,
+            pixels: pixels.to_vec(),
+        })
+    }
+
+    /// The number of pixels with a channel differing from `other` by more
+    /// than `tolerance`, or `None` if the images have different sizes.
+    pub fn count_differing(&self, other: &RgbaImage, tolerance: u8) -> Option<usize> {
+        if self.width != other.width || self.height != other.height {
+            return None;
+        }
+        let count = self
+            .pixels
+            .chunks(4)
+            .zip(other.pixels.chunks(4))
+            .filter(|(a, b)| {
+                a.iter()
+                    .zip(b.iter())
+                    .any(|(a, b)| (*a as i16 - *b as i16).abs() > tolerance as i16)
+            })
+            .count();
+        Some(count)
+    }
+}
+
+impl fmt::Debug for RgbaImage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // The pixels are left out, as there are usually far too many.
+        f.debug_struct("RgbaImage")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pam_round_trip() {
+        let image = RgbaImage {
+            width: 2,
+            height: 1,
+            pixels: vec![255, 0, 0, 255, 0, 0, 250, 128],
+        };
+        let decoded = RgbaImage::from_pam(&image.to_pam()).unwrap();
+        assert_eq!(decoded.pixels, image.pixels);
+        let mut changed = decoded.clone();
+        changed.pixels[6] = 240;
+        assert_eq!(image.count_differing(&changed, 8), Some(1));
+        assert_eq!(image.count_differing(&changed, 10), Some(0));
+    }
+}
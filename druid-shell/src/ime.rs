@@ -0,0 +1,71 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Input method (IME) composition.
+//!
+//! Input methods compose text from several key presses, as when typing
+//! Chinese or Japanese, or an accented letter with a dead key. While a
+//! composition is in progress, the text being composed (the "preedit") is
+//! shown inline by the focused text widget, and the keys involved are not
+//! delivered as key events.
+//!
+//! Input methods are only active in windows that ask for them, with
+//! [`WinCtx::set_ime_enabled`].
+//!
+//! [`WinCtx::set_ime_enabled`]: ../window/trait.WinCtx.html#method.set_ime_enabled
+
+/// An event from the platform's input method.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImeEvent {
+    /// The composition text changed.
+    ///
+    /// The cursor is a byte offset in the text, if the input method places
+    /// one. An empty text ends the composition without inserting anything.
+    Preedit { text: String, cursor: Option<usize> },
+    /// Text was committed, ending any composition.
+    ///
+    /// The text replaces the preedit, if there was one.
+    Commit(String),
+}
+
+/// Convert an offset in UTF-16 code units, as used by the platform APIs,
+/// to a byte offset in `text`.
+///
+/// Offsets past the end of the text are clamped to its length.
+#[allow(dead_code)]
+pub(crate) fn utf16_to_utf8_offset(text: &str, offset: usize) -> usize {
+    let mut utf16 = 0;
+    for (i, c) in text.char_indices() {
+        if utf16 >= offset {
+            return i;
+        }
+        utf16 += c.len_utf16();
+    }
+    text.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utf16_offsets() {
+        let text = "a\u{1F600}b日";
+        assert_eq!(utf16_to_utf8_offset(text, 0), 0);
+        assert_eq!(utf16_to_utf8_offset(text, 1), 1);
+        assert_eq!(utf16_to_utf8_offset(text, 3), 5);
+        assert_eq!(utf16_to_utf8_offset(text, 4), 6);
+        assert_eq!(utf16_to_utf8_offset(text, 10), text.len());
+    }
+}
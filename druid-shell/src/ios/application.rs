@@ -0,0 +1,100 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! iOS implementation of features at the application scope.
+
+use std::path::Path;
+
+use log::warn;
+use objc::runtime::Object;
+
+use crate::clipboard::ClipboardItem;
+use crate::util::{from_nsstring, id, make_nsstring};
+
+#[link(name = "UIKit", kind = "framework")]
+extern "C" {
+    fn UIAccessibilityDarkerSystemColorsEnabled() -> u8;
+    fn UIAccessibilityIsReduceMotionEnabled() -> u8;
+}
+
+pub struct Application;
+
+impl Application {
+    /// iOS applications don't quit themselves; this does nothing.
+    pub fn quit() {
+        crate::runloop::request_quit();
+    }
+
+    /// Open a URL with the default handler for its scheme.
+    pub fn open_url(url: &str) {
+        unsafe {
+            let nsurl: id = msg_send![class!(NSURL), URLWithString: make_nsstring(url)];
+            if nsurl.is_null() {
+                warn!("failed to open url '{}': invalid url", url);
+                return;
+            }
+            let app: id = msg_send![class!(UIApplication), sharedApplication];
+            let options: id = msg_send![class!(NSDictionary), dictionary];
+            let no_handler: *mut Object = std::ptr::null_mut();
+            let () = msg_send![app, openURL: nsurl options: options completionHandler: no_handler];
+        }
+    }
+
+    /// There is no file manager that can be asked to show a file.
+    pub fn reveal_in_file_manager(path: &Path) {
+        warn!("cannot reveal '{}': not supported on iOS", path.display());
+    }
+
+    /// Whether "Increase Contrast" is turned on in the accessibility
+    /// settings.
+    pub fn is_high_contrast() -> bool {
+        unsafe { UIAccessibilityDarkerSystemColorsEnabled() != 0 }
+    }
+
+    /// Whether "Reduce Motion" is turned on in the accessibility settings.
+    pub fn prefers_reduced_motion() -> bool {
+        unsafe { UIAccessibilityIsReduceMotionEnabled() != 0 }
+    }
+
+    /// Application fonts are not yet supported; this does nothing.
+    pub fn register_font(_data: &[u8]) {
+        warn!("registering fonts is not yet supported on iOS");
+    }
+
+    /// Returns the contents of the clipboard, if any.
+    pub fn get_clipboard_contents() -> Option<ClipboardItem> {
+        unsafe {
+            let pasteboard: id = msg_send![class!(UIPasteboard), generalPasteboard];
+            let contents: id = msg_send![pasteboard, string];
+            if contents.is_null() {
+                None
+            } else {
+                Some(ClipboardItem::from(from_nsstring(contents)))
+            }
+        }
+    }
+
+    /// Sets the contents of the system clipboard.
+    pub fn set_clipboard_contents(item: ClipboardItem) {
+        unsafe {
+            let pasteboard: id = msg_send![class!(UIPasteboard), generalPasteboard];
+            match item {
+                ClipboardItem::Text(string) => {
+                    let () = msg_send![pasteboard, setString: make_nsstring(&string)];
+                }
+                other => warn!("unhandled clipboard data {:?}", other),
+            }
+        }
+    }
+}
@@ -0,0 +1,127 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Native child views, iOS implementation.
+
+use std::ffi::c_void;
+
+use objc::rc::StrongPtr;
+use objc::runtime::{NO, YES};
+use raw_window_handle::ios::IOSHandle;
+use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+
+use crate::kurbo::Rect;
+use crate::Error;
+
+use super::util::id;
+use super::{CGPoint, CGRect, CGSize, WindowHandle};
+
+/// A view shown inside a druid window, either created by other code or
+/// created as a surface for GPU rendering.
+pub struct ChildView {
+    uiview: StrongPtr,
+}
+
+impl ChildView {
+    /// Wrap a view, which will become a subview of the druid view when
+    /// attached. The view is retained.
+    ///
+    /// # Safety
+    ///
+    /// The pointer must be a valid `UIView`.
+    pub unsafe fn from_uiview(uiview: id) -> ChildView {
+        ChildView {
+            uiview: StrongPtr::retain(uiview),
+        }
+    }
+
+    /// Create an empty view, for rendering into with a graphics API such
+    /// as Metal.
+    pub fn new_surface() -> Result<ChildView, Error> {
+        let frame = CGRect {
+            origin: CGPoint { x: 0.0, y: 0.0 },
+            size: CGSize {
+                width: 1.0,
+                height: 1.0,
+            },
+        };
+        unsafe {
+            let uiview: id = msg_send![class!(UIView), alloc];
+            let uiview: id = msg_send![uiview, initWithFrame: frame];
+            if uiview.is_null() {
+                return Err(Error::Null);
+            }
+            Ok(ChildView {
+                uiview: StrongPtr::new(uiview),
+            })
+        }
+    }
+
+    /// The wrapped view.
+    pub fn uiview(&self) -> id {
+        *self.uiview
+    }
+
+    /// Add the view to the window, initially hidden.
+    pub fn attach(&self, parent: &WindowHandle) {
+        if let Some(parent) = parent.uiview.as_ref() {
+            unsafe {
+                let () = msg_send![*self.uiview, setHidden: YES];
+                let () = msg_send![*parent.load(), addSubview: *self.uiview];
+            }
+        }
+    }
+
+    /// Set the area covered by the view, in px relative to the window.
+    pub fn set_frame(&self, frame: Rect) {
+        let frame = CGRect {
+            origin: CGPoint {
+                x: frame.x0,
+                y: frame.y0,
+            },
+            size: CGSize {
+                width: frame.width(),
+                height: frame.height(),
+            },
+        };
+        unsafe {
+            let () = msg_send![*self.uiview, setFrame: frame];
+        }
+    }
+
+    pub fn set_visible(&self, visible: bool) {
+        let hidden = if visible { NO } else { YES };
+        unsafe {
+            let () = msg_send![*self.uiview, setHidden: hidden];
+        }
+    }
+
+    /// Remove the view from its window.
+    pub fn detach(&self) {
+        unsafe {
+            let () = msg_send![*self.uiview, removeFromSuperview];
+        }
+    }
+}
+
+unsafe impl HasRawWindowHandle for ChildView {
+    fn raw_window_handle(&self) -> RawWindowHandle {
+        let ui_window: id = unsafe { msg_send![*self.uiview, window] };
+        RawWindowHandle::IOS(IOSHandle {
+            ui_window: ui_window as *mut c_void,
+            ui_view: *self.uiview as *mut c_void,
+            ..IOSHandle::empty()
+        })
+    }
+}
@@ -0,0 +1,932 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! iOS implementation of window creation.
+//!
+//! A window is a `UIView` filling a `UIWindow` of its own. Windows can only
+//! be put on screen once UIKit has finished launching the application, so
+//! windows shown before then are presented by the application delegate.
+//!
+//! Touches are delivered as left mouse button events, for the first finger
+//! only. Text typed on the on-screen keyboard is delivered as key events;
+//! characters without a matching key are reported with an unknown key code.
+#![allow(non_snake_case)]
+
+pub mod application;
+mod child_view;
+pub mod menu;
+pub mod util;
+#[cfg(feature = "video")]
+mod video;
+#[cfg(feature = "webview")]
+mod webview;
+pub mod win_main;
+
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::ffi::c_void;
+use std::ffi::OsString;
+use std::mem;
+use std::ptr::null_mut;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Instant;
+
+use cairo::{Context, QuartzSurface};
+use log::{error, warn};
+use objc::declare::ClassDecl;
+use objc::rc::WeakPtr;
+use objc::runtime::{Class, Object, Protocol, Sel, BOOL, NO, YES};
+use objc::{Encode, Encoding};
+
+use crate::kurbo::{Point, Rect};
+use piet_common::{Piet, RenderContext};
+use raw_window_handle::ios::IOSHandle;
+use raw_window_handle::RawWindowHandle;
+
+use crate::clipboard::ClipboardItem;
+use crate::dialog::{FileDialogOptions, FileDialogType};
+use crate::dnd::DragContents;
+use crate::keyboard::{KeyCode, KeyEvent, KeyModifiers, RawKeyCode};
+use crate::platform::application::Application;
+use crate::print::{PageSetup, Printable};
+use crate::window::{
+    Cursor, FileInfo, IdleToken, MouseButton, MouseEvent, Text, TimerToken, WinCtx, WinHandler,
+};
+use crate::Error;
+
+pub use child_view::ChildView;
+pub use menu::Menu;
+#[cfg(feature = "video")]
+pub use video::VideoDecoder;
+#[cfg(feature = "webview")]
+pub use webview::WebView;
+
+use crate::util::{assert_main_thread, from_nsstring, id};
+
+#[link(name = "UIKit", kind = "framework")]
+extern "C" {
+    fn UIGraphicsGetCurrentContext() -> *mut c_void;
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+struct CGPoint {
+    x: f64,
+    y: f64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+struct CGSize {
+    width: f64,
+    height: f64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+struct CGRect {
+    origin: CGPoint,
+    size: CGSize,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+struct UIEdgeInsets {
+    top: f64,
+    left: f64,
+    bottom: f64,
+    right: f64,
+}
+
+unsafe impl Encode for CGRect {
+    fn encode() -> Encoding {
+        unsafe { Encoding::from_str("{CGRect={CGPoint=dd}{CGSize=dd}}") }
+    }
+}
+
+thread_local! {
+    /// Whether UIKit has finished launching the application.
+    static LAUNCHED: Cell<bool> = Cell::new(false);
+
+    /// Views shown before launch, to be presented when it finishes.
+    static PENDING: RefCell<Vec<WeakPtr>> = RefCell::new(Vec::new());
+
+    /// Every view we have created, for broadcasting lifecycle events.
+    static VIEWS: RefCell<Vec<WeakPtr>> = RefCell::new(Vec::new());
+}
+
+#[derive(Clone, Default)]
+pub struct WindowHandle {
+    /// This is a UIView; the UIWindow holding it is an implementation detail.
+    ///
+    /// TODO: remove option (issue has been filed against objc, or we could manually impl default with nil)
+    /// https://github.com/SSheldon/rust-objc/issues/77
+    uiview: Option<WeakPtr>,
+    idle_queue: Weak<Mutex<Vec<IdleKind>>>,
+}
+
+/// Builder abstraction for creating new windows.
+pub struct WindowBuilder {
+    handler: Option<Box<dyn WinHandler>>,
+}
+
+#[derive(Clone)]
+pub struct IdleHandle {
+    uiview: WeakPtr,
+    idle_queue: Weak<Mutex<Vec<IdleKind>>>,
+}
+
+// TODO: move this out of platform-dependent section.
+trait IdleCallback: Send {
+    fn call(self: Box<Self>, a: &dyn Any);
+}
+
+impl<F: FnOnce(&dyn Any) + Send> IdleCallback for F {
+    fn call(self: Box<F>, a: &dyn Any) {
+        (*self)(a)
+    }
+}
+
+/// An entry in a window's idle queue.
+enum IdleKind {
+    Callback(Box<dyn IdleCallback>),
+    Token(IdleToken),
+}
+
+/// This is the state associated with our custom UIView.
+struct ViewState {
+    uiview: WeakPtr,
+    handler: Box<dyn WinHandler>,
+    idle_queue: Arc<Mutex<Vec<IdleKind>>>,
+    /// The UIWindow presenting the view, retained; nil until it is shown.
+    uiwindow: id,
+    /// The touch being delivered as mouse events, if any. This is only
+    /// used for identity, and isn't retained.
+    touch: id,
+}
+
+struct WinCtxImpl<'a> {
+    uiview: &'a WeakPtr,
+    text: Text<'static>,
+}
+
+impl WindowBuilder {
+    pub fn new() -> WindowBuilder {
+        WindowBuilder { handler: None }
+    }
+
+    pub fn set_handler(&mut self, handler: Box<dyn WinHandler>) {
+        self.handler = Some(handler);
+    }
+
+    /// iOS windows have no title bar.
+    pub fn set_title(&mut self, _title: impl Into<String>) {}
+
+    /// Menus are not shown on iOS.
+    pub fn set_menu(&mut self, _menu: Menu) {}
+
+    /// There is no pointer to move without touching the screen.
+    pub fn set_enable_mouse_move_events(&mut self, _to: bool) {}
+
+    /// Embedding in views owned by another framework is not yet supported.
+    pub fn set_parent(&mut self, parent: RawWindowHandle) {
+        warn!("unsupported parent window handle: {:?}", parent);
+    }
+
+    pub fn build(self) -> Result<WindowHandle, Error> {
+        assert_main_thread();
+        let handler = self
+            .handler
+            .expect("Tried to build a window without setting the handler");
+        let idle_queue = Arc::new(Mutex::new(Vec::new()));
+        let queue_handle = Arc::downgrade(&idle_queue);
+        unsafe {
+            let view: id = msg_send![VIEW_CLASS.0, new];
+            let uiview = WeakPtr::new(view);
+            let state = ViewState {
+                uiview: uiview.clone(),
+                handler,
+                idle_queue,
+                uiwindow: null_mut(),
+                touch: null_mut(),
+            };
+            let state_ptr = Box::into_raw(Box::new(state));
+            (*view).set_ivar("viewState", state_ptr as *mut c_void);
+            let () = msg_send![view, setMultipleTouchEnabled: NO];
+            VIEWS.with(|views| views.borrow_mut().push(uiview.clone()));
+
+            let handle = WindowHandle {
+                uiview: Some(uiview),
+                idle_queue: queue_handle,
+            };
+            (*state_ptr).handler.connect(&crate::window::WindowHandle {
+                inner: handle.clone(),
+            });
+            Ok(handle)
+        }
+    }
+}
+
+// Wrap pointer because lazy_static requires Sync.
+struct ViewClass(*const Class);
+unsafe impl Sync for ViewClass {}
+
+lazy_static! {
+    static ref VIEW_CLASS: ViewClass = unsafe {
+        let mut decl = ClassDecl::new("DruidUIView", class!(UIView)).expect("View class defined");
+        decl.add_ivar::<*mut c_void>("viewState");
+        if let Some(protocol) = Protocol::get("UIKeyInput") {
+            decl.add_protocol(protocol);
+        }
+
+        decl.add_method(
+            sel!(canBecomeFirstResponder),
+            yes as extern "C" fn(&Object, Sel) -> BOOL,
+        );
+        // This has to be true for the keyboard to send `deleteBackward`.
+        decl.add_method(sel!(hasText), yes as extern "C" fn(&Object, Sel) -> BOOL);
+        extern "C" fn yes(_this: &Object, _sel: Sel) -> BOOL {
+            YES
+        }
+        decl.add_method(sel!(dealloc), dealloc as extern "C" fn(&Object, Sel));
+        extern "C" fn dealloc(this: &Object, _sel: Sel) {
+            unsafe {
+                let view_state: *mut c_void = *this.get_ivar("viewState");
+                Box::from_raw(view_state as *mut ViewState);
+            }
+        }
+
+        decl.add_method(
+            sel!(layoutSubviews),
+            layout_subviews as extern "C" fn(&mut Object, Sel),
+        );
+        decl.add_method(
+            sel!(safeAreaInsetsDidChange),
+            safe_area_insets_did_change as extern "C" fn(&mut Object, Sel),
+        );
+        decl.add_method(
+            sel!(drawRect:),
+            draw_rect as extern "C" fn(&mut Object, Sel, CGRect),
+        );
+        decl.add_method(
+            sel!(touchesBegan:withEvent:),
+            touches_began as extern "C" fn(&mut Object, Sel, id, id),
+        );
+        decl.add_method(
+            sel!(touchesMoved:withEvent:),
+            touches_moved as extern "C" fn(&mut Object, Sel, id, id),
+        );
+        decl.add_method(
+            sel!(touchesEnded:withEvent:),
+            touches_ended as extern "C" fn(&mut Object, Sel, id, id),
+        );
+        decl.add_method(
+            sel!(touchesCancelled:withEvent:),
+            touches_ended as extern "C" fn(&mut Object, Sel, id, id),
+        );
+        decl.add_method(
+            sel!(insertText:),
+            insert_text as extern "C" fn(&mut Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(deleteBackward),
+            delete_backward as extern "C" fn(&mut Object, Sel),
+        );
+        decl.add_method(sel!(runIdle), run_idle as extern "C" fn(&mut Object, Sel));
+        decl.add_method(sel!(redraw), redraw as extern "C" fn(&mut Object, Sel));
+        decl.add_method(
+            sel!(handleTimer:),
+            handle_timer as extern "C" fn(&mut Object, Sel, id),
+        );
+        ViewClass(decl.register())
+    };
+}
+
+lazy_static! {
+    static ref APP_DELEGATE_CLASS: ViewClass = unsafe {
+        let mut decl = ClassDecl::new("DruidAppDelegate", class!(UIResponder))
+            .expect("App delegate class defined");
+        decl.add_method(
+            sel!(application:didFinishLaunchingWithOptions:),
+            did_finish_launching as extern "C" fn(&mut Object, Sel, id, id) -> BOOL,
+        );
+        decl.add_method(
+            sel!(applicationDidEnterBackground:),
+            did_enter_background as extern "C" fn(&mut Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(applicationWillEnterForeground:),
+            will_enter_foreground as extern "C" fn(&mut Object, Sel, id),
+        );
+        ViewClass(decl.register())
+    };
+}
+
+/// The name of the application delegate class, registering it if needed.
+pub(crate) fn app_delegate_class_name() -> &'static str {
+    lazy_static::initialize(&APP_DELEGATE_CLASS);
+    "DruidAppDelegate"
+}
+
+extern "C" fn did_finish_launching(_this: &mut Object, _: Sel, _app: id, _options: id) -> BOOL {
+    LAUNCHED.with(|launched| launched.set(true));
+    let pending = PENDING.with(|pending| mem::replace(&mut *pending.borrow_mut(), Vec::new()));
+    for view in pending {
+        unsafe { present(*view.load()) };
+    }
+    YES
+}
+
+extern "C" fn did_enter_background(_this: &mut Object, _: Sel, _app: id) {
+    for_each_view(|view_state, ctx| view_state.handler.suspended(ctx));
+}
+
+extern "C" fn will_enter_foreground(_this: &mut Object, _: Sel, _app: id) {
+    for_each_view(|view_state, ctx| view_state.handler.resumed(ctx));
+}
+
+fn for_each_view(mut f: impl FnMut(&mut ViewState, &mut WinCtxImpl)) {
+    let views = VIEWS.with(|views| {
+        let mut views = views.borrow_mut();
+        views.retain(|view| unsafe { !(*view.load()).is_null() });
+        views.clone()
+    });
+    for view in views {
+        unsafe {
+            let view = view.load();
+            let view_state = view_state(&**view);
+            let mut ctx = WinCtxImpl {
+                uiview: &view_state.uiview.clone(),
+                text: Text::new(),
+            };
+            f(view_state, &mut ctx);
+        }
+    }
+}
+
+unsafe fn view_state<'a>(this: &Object) -> &'a mut ViewState {
+    let view_state: *mut c_void = *this.get_ivar("viewState");
+    &mut *(view_state as *mut ViewState)
+}
+
+/// Put a view on screen, in a new window.
+unsafe fn present(view: id) {
+    if view.is_null() {
+        return;
+    }
+    let screen: id = msg_send![class!(UIScreen), mainScreen];
+    let bounds: CGRect = msg_send![screen, bounds];
+    let window: id = msg_send![class!(UIWindow), alloc];
+    let window: id = msg_send![window, initWithFrame: bounds];
+    let controller: id = msg_send![class!(UIViewController), new];
+    let () = msg_send![controller, setView: view];
+    let () = msg_send![window, setRootViewController: controller];
+    let () = msg_send![controller, release];
+    let () = msg_send![window, makeKeyAndVisible];
+    let old = mem::replace(&mut view_state(&*view).uiwindow, window);
+    if !old.is_null() {
+        let () = msg_send![old, release];
+    }
+}
+
+extern "C" fn layout_subviews(this: &mut Object, _: Sel) {
+    unsafe {
+        let superclass = msg_send![this, superclass];
+        let () = msg_send![super(this, superclass), layoutSubviews];
+        let bounds: CGRect = msg_send![this, bounds];
+        let insets: UIEdgeInsets = msg_send![this, safeAreaInsets];
+        let view_state = view_state(this);
+        let mut ctx = WinCtxImpl {
+            uiview: &view_state.uiview.clone(),
+            text: Text::new(),
+        };
+        let (width, height) = (bounds.size.width, bounds.size.height);
+        view_state
+            .handler
+            .size(width as u32, height as u32, &mut ctx);
+        let area = Rect::new(
+            insets.left,
+            insets.top,
+            width - insets.right,
+            height - insets.bottom,
+        );
+        view_state.handler.safe_area(area, &mut ctx);
+    }
+}
+
+extern "C" fn safe_area_insets_did_change(this: &mut Object, _: Sel) {
+    unsafe {
+        let () = msg_send![this, setNeedsLayout];
+    }
+}
+
+extern "C" fn draw_rect(this: &mut Object, _: Sel, _dirty_rect: CGRect) {
+    unsafe {
+        let cgcontext = UIGraphicsGetCurrentContext();
+        let bounds: CGRect = msg_send![this, bounds];
+        let width = bounds.size.width as u32;
+        let height = bounds.size.height as u32;
+        let cairo_surface =
+            QuartzSurface::create_for_cg_context(cgcontext, width, height).expect("cairo surface");
+        let mut cairo_ctx = Context::new(&cairo_surface);
+        let mut piet_ctx = Piet::new(&mut cairo_ctx);
+        let view_state = view_state(this);
+        let mut ctx = WinCtxImpl {
+            uiview: &view_state.uiview.clone(),
+            text: Text::new(),
+        };
+        let anim = view_state.handler.paint(&mut piet_ctx, &mut ctx);
+        if let Err(e) = piet_ctx.finish() {
+            error!("{}", e)
+        }
+
+        if anim {
+            // TODO: synchronize with screen refresh rate using CADisplayLink instead.
+            let () = msg_send!(this as *const _, performSelectorOnMainThread: sel!(redraw)
+                withObject: null_mut::<Object>() waitUntilDone: NO);
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum TouchPhase {
+    Began,
+    Moved,
+    Ended,
+}
+
+extern "C" fn touches_began(this: &mut Object, _: Sel, touches: id, _event: id) {
+    handle_touches(this, touches, TouchPhase::Began);
+}
+
+extern "C" fn touches_moved(this: &mut Object, _: Sel, touches: id, _event: id) {
+    handle_touches(this, touches, TouchPhase::Moved);
+}
+
+extern "C" fn touches_ended(this: &mut Object, _: Sel, touches: id, _event: id) {
+    handle_touches(this, touches, TouchPhase::Ended);
+}
+
+fn handle_touches(this: &mut Object, touches: id, phase: TouchPhase) {
+    unsafe {
+        let view: id = this;
+        let view_state = view_state(this);
+        let touch = if phase == TouchPhase::Began {
+            if !view_state.touch.is_null() {
+                return;
+            }
+            let touch: id = msg_send![touches, anyObject];
+            view_state.touch = touch;
+            touch
+        } else {
+            let contains: BOOL = msg_send![touches, containsObject: view_state.touch];
+            if contains == NO {
+                return;
+            }
+            view_state.touch
+        };
+        let location: CGPoint = msg_send![touch, locationInView: view];
+        let tap_count: usize = msg_send![touch, tapCount];
+        let mut mouse = MouseEvent {
+            pos: Point::new(location.x, location.y),
+            mods: KeyModifiers::default(),
+            count: 0,
+            button: MouseButton::Left,
+        };
+        let mut ctx = WinCtxImpl {
+            uiview: &view_state.uiview.clone(),
+            text: Text::new(),
+        };
+        match phase {
+            TouchPhase::Began => {
+                // Widgets expect the pointer to hover before it clicks.
+                view_state.handler.mouse_move(&mouse, &mut ctx);
+                mouse.count = tap_count.max(1) as u32;
+                view_state.handler.mouse_down(&mouse, &mut ctx);
+            }
+            TouchPhase::Moved => view_state.handler.mouse_move(&mouse, &mut ctx),
+            TouchPhase::Ended => {
+                view_state.touch = null_mut();
+                view_state.handler.mouse_up(&mouse, &mut ctx);
+                // Nothing should stay hot once the finger is lifted.
+                mouse.pos = Point::new(-1.0, -1.0);
+                view_state.handler.mouse_move(&mouse, &mut ctx);
+            }
+        }
+    }
+}
+
+extern "C" fn insert_text(this: &mut Object, _: Sel, text: id) {
+    let text = from_nsstring(text);
+    unsafe {
+        let view_state = view_state(this);
+        let mut ctx = WinCtxImpl {
+            uiview: &view_state.uiview.clone(),
+            text: Text::new(),
+        };
+        for c in text.chars() {
+            let (key_code, shift) = key_code_for_char(c);
+            let mods = KeyModifiers {
+                shift,
+                ..KeyModifiers::default()
+            };
+            let event = KeyEvent::new(key_code, false, mods, c, c);
+            view_state.handler.key_down(event, &mut ctx);
+            view_state.handler.key_up(event, &mut ctx);
+        }
+    }
+}
+
+extern "C" fn delete_backward(this: &mut Object, _: Sel) {
+    unsafe {
+        let view_state = view_state(this);
+        let mut ctx = WinCtxImpl {
+            uiview: &view_state.uiview.clone(),
+            text: Text::new(),
+        };
+        let event = KeyEvent::new(KeyCode::Backspace, false, KeyModifiers::default(), "", "");
+        view_state.handler.key_down(event, &mut ctx);
+        view_state.handler.key_up(event, &mut ctx);
+    }
+}
+
+/// The key that produces a character on a US keyboard, and whether it
+/// needs shift.
+///
+/// The on-screen keyboard only tells us about text, but widgets expect
+/// the key code of a printable key to accompany it.
+fn key_code_for_char(c: char) -> (KeyCode, bool) {
+    use KeyCode::*;
+    let letters = [
+        KeyA, KeyB, KeyC, KeyD, KeyE, KeyF, KeyG, KeyH, KeyI, KeyJ, KeyK, KeyL, KeyM, KeyN, KeyO,
+        KeyP, KeyQ, KeyR, KeyS, KeyT, KeyU, KeyV, KeyW, KeyX, KeyY, KeyZ,
+    ];
+    let digits = [Key0, Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9];
+    match c {
+        'a'..='z' => (letters[c as usize - 'a' as usize], false),
+        'A'..='Z' => (letters[c as usize - 'A' as usize], true),
+        '0'..='9' => (digits[c as usize - '0' as usize], false),
+        ' ' => (Space, false),
+        '\n' | '\r' => (Return, false),
+        '\t' => (Tab, false),
+        '`' => (Backtick, false),
+        '~' => (Backtick, true),
+        '-' => (Minus, false),
+        '_' => (Minus, true),
+        '=' => (Equals, false),
+        '+' => (Equals, true),
+        '[' => (LeftBracket, false),
+        '{' => (LeftBracket, true),
+        ']' => (RightBracket, false),
+        '}' => (RightBracket, true),
+        '\\' => (Backslash, false),
+        '|' => (Backslash, true),
+        ';' => (Semicolon, false),
+        ':' => (Semicolon, true),
+        '\'' => (Quote, false),
+        '"' => (Quote, true),
+        ',' => (Comma, false),
+        '<' => (Comma, true),
+        '.' => (Period, false),
+        '>' => (Period, true),
+        '/' => (Slash, false),
+        '?' => (Slash, true),
+        '!' => (Key1, true),
+        '@' => (Key2, true),
+        '#' => (Key3, true),
+        '$' => (Key4, true),
+        '%' => (Key5, true),
+        '^' => (Key6, true),
+        '&' => (Key7, true),
+        '*' => (Key8, true),
+        '(' => (Key9, true),
+        ')' => (Key0, true),
+        other => (Unknown(RawKeyCode::Ios(other as u32)), false),
+    }
+}
+
+extern "C" fn run_idle(this: &mut Object, _: Sel) {
+    let view_state = unsafe { view_state(this) };
+    let queue: Vec<_> = mem::replace(
+        &mut view_state.idle_queue.lock().expect("queue"),
+        Vec::new(),
+    );
+    let mut ctx = WinCtxImpl {
+        uiview: &view_state.uiview.clone(),
+        text: Text::new(),
+    };
+    for item in queue {
+        match item {
+            IdleKind::Callback(callback) => callback.call(view_state.handler.as_any()),
+            IdleKind::Token(token) => view_state.handler.idle(token, &mut ctx),
+        }
+    }
+}
+
+extern "C" fn redraw(this: &mut Object, _: Sel) {
+    unsafe {
+        let () = msg_send![this as *const _, setNeedsDisplay];
+    }
+}
+
+extern "C" fn handle_timer(this: &mut Object, _: Sel, timer: id) {
+    let view_state = unsafe { view_state(this) };
+    let mut ctx = WinCtxImpl {
+        uiview: &view_state.uiview.clone(),
+        text: Text::new(),
+    };
+    let token = unsafe {
+        let user_info: id = msg_send![timer, userInfo];
+        msg_send![user_info, unsignedIntegerValue]
+    };
+
+    view_state.handler.timer(TimerToken::new(token), &mut ctx);
+}
+
+impl WindowHandle {
+    pub fn show(&self) {
+        if let Some(ref uiview) = self.uiview {
+            if LAUNCHED.with(|launched| launched.get()) {
+                unsafe { present(*uiview.load()) };
+            } else {
+                PENDING.with(|pending| pending.borrow_mut().push(uiview.clone()));
+            }
+        }
+    }
+
+    /// Close the window.
+    pub fn close(&self) {
+        if let Some(ref uiview) = self.uiview {
+            unsafe {
+                let view = uiview.load();
+                if view.is_null() {
+                    return;
+                }
+                let view_state = view_state(&**view);
+                let mut ctx = WinCtxImpl {
+                    uiview,
+                    text: Text::new(),
+                };
+                view_state.handler.destroy(&mut ctx);
+                let window = mem::replace(&mut view_state.uiwindow, null_mut());
+                if !window.is_null() {
+                    let () = msg_send![window, setHidden: YES];
+                    let () = msg_send![window, release];
+                }
+            }
+        }
+    }
+
+    /// Bring this window to the front of the window stack and give it focus.
+    pub fn bring_to_front_and_focus(&self) {
+        self.show();
+    }
+
+    /// Minimize the window.
+    ///
+    /// Windows fill the screen on this platform, so this does nothing.
+    pub fn minimize(&self) {}
+
+    /// Toggle the window between maximized and its normal size.
+    ///
+    /// Windows fill the screen on this platform, so this does nothing.
+    pub fn zoom(&self) {}
+
+    /// The window's frame, in screen coordinates.
+    ///
+    /// Windows fill the screen on this platform, so this is always empty.
+    pub fn get_frame(&self) -> Rect {
+        Rect::ZERO
+    }
+
+    /// Move the window's top left corner.
+    ///
+    /// Windows fill the screen on this platform, so this does nothing.
+    pub fn set_position(&self, _position: Point) {}
+
+    // Request invalidation of the entire window contents.
+    pub fn invalidate(&self) {
+        if let Some(ref uiview) = self.uiview {
+            unsafe {
+                let () = msg_send![*uiview.load(), setNeedsDisplay];
+            }
+        }
+    }
+
+    /// iOS windows have no title bar.
+    pub fn set_title(&self, _title: &str) {}
+
+    /// Menus are not shown on iOS.
+    pub fn set_menu(&self, _menu: Menu) {}
+
+    /// Menus are not shown on iOS.
+    pub fn show_context_menu(&self, _menu: Menu, _x: f64, _y: f64) {}
+
+    /// Set whether animation frames are drawn in step with the display's
+    /// refresh.
+    ///
+    /// Frames are not yet paced by the display on iOS, so this does nothing.
+    pub fn set_vsync(&self, _vsync: bool) {}
+
+    /// Print a document, after showing the system's print dialog.
+    ///
+    /// Printing is not supported on this platform, so this only logs a
+    /// warning.
+    pub fn print(&self, _setup: PageSetup, _doc: Box<dyn Printable>) {
+        warn!("printing is not supported on this platform");
+    }
+
+    /// Get a handle that can be used to schedule an idle task.
+    pub fn get_idle_handle(&self) -> Option<IdleHandle> {
+        self.uiview.as_ref().map(|uiview| IdleHandle {
+            uiview: uiview.clone(),
+            idle_queue: self.idle_queue.clone(),
+        })
+    }
+
+    /// Get the raw handle of the window, for use with other libraries.
+    pub fn raw_window_handle(&self) -> RawWindowHandle {
+        let (ui_window, ui_view) = match self.uiview.as_ref() {
+            Some(uiview) => unsafe {
+                let view = *uiview.load();
+                let window = if view.is_null() {
+                    null_mut()
+                } else {
+                    view_state(&*view).uiwindow
+                };
+                (window as *mut c_void, view as *mut c_void)
+            },
+            None => (null_mut(), null_mut()),
+        };
+        RawWindowHandle::IOS(IOSHandle {
+            ui_window,
+            ui_view,
+            ..IOSHandle::empty()
+        })
+    }
+
+    /// Get the dpi of the window.
+    ///
+    /// UIKit works in points and scales to the screen itself, so this is
+    /// always the nominal dpi.
+    pub fn get_dpi(&self) -> f32 {
+        96.0
+    }
+
+    // TODO: the following methods are cut'n'paste code. A good way to DRY
+    // would be to have a platform-independent trait with these as methods with
+    // default implementations.
+
+    /// Convert a dimension in px units to physical pixels (rounding).
+    pub fn px_to_pixels(&self, x: f32) -> i32 {
+        (x * self.get_dpi() * (1.0 / 96.0)).round() as i32
+    }
+
+    /// Convert a point in px units to physical pixels (rounding).
+    pub fn px_to_pixels_xy(&self, x: f32, y: f32) -> (i32, i32) {
+        let scale = self.get_dpi() * (1.0 / 96.0);
+        ((x * scale).round() as i32, (y * scale).round() as i32)
+    }
+
+    /// Convert a dimension in physical pixels to px units.
+    pub fn pixels_to_px<T: Into<f64>>(&self, x: T) -> f32 {
+        (x.into() as f32) * 96.0 / self.get_dpi()
+    }
+
+    /// Convert a point in physical pixels to px units.
+    pub fn pixels_to_px_xy<T: Into<f64>>(&self, x: T, y: T) -> (f32, f32) {
+        let scale = 96.0 / self.get_dpi();
+        ((x.into() as f32) * scale, (y.into() as f32) * scale)
+    }
+
+    #[deprecated(since = "0.3", note = "use methods on WinCtx instead")]
+    pub fn file_dialog(
+        &self,
+        _ty: FileDialogType,
+        _options: FileDialogOptions,
+    ) -> Result<OsString, Error> {
+        Err(Error::Null)
+    }
+}
+
+unsafe impl Send for IdleHandle {}
+
+impl IdleHandle {
+    /// Add an idle handler, which is called (once) when the message loop
+    /// is empty. The idle handler will be run from the main UI thread, and
+    /// won't be scheduled if the associated view has been dropped.
+    ///
+    /// Note: the name "idle" suggests that it will be scheduled with a lower
+    /// priority than other UI events, but that's not necessarily the case.
+    pub fn add_idle<F>(&self, callback: F)
+    where
+        F: FnOnce(&dyn Any) + Send + 'static,
+    {
+        self.add_idle_kind(IdleKind::Callback(Box::new(callback)));
+    }
+
+    /// Request a call to the window handler's [`idle`] method, with the
+    /// given token.
+    ///
+    /// [`idle`]: ../trait.WinHandler.html#method.idle
+    pub fn schedule_idle(&self, token: IdleToken) {
+        self.add_idle_kind(IdleKind::Token(token));
+    }
+
+    fn add_idle_kind(&self, kind: IdleKind) {
+        if let Some(queue) = self.idle_queue.upgrade() {
+            let mut queue = queue.lock().expect("queue lock");
+            if queue.is_empty() {
+                unsafe {
+                    let uiview = self.uiview.load();
+                    // Note: the view might be nil here if the window has been dropped, but that's ok.
+                    let () = msg_send!(*uiview, performSelectorOnMainThread: sel!(runIdle)
+                        withObject: null_mut::<Object>() waitUntilDone: NO);
+                }
+            }
+            queue.push(kind);
+        }
+    }
+}
+
+impl<'a> WinCtx<'a> for WinCtxImpl<'a> {
+    fn invalidate(&mut self) {
+        unsafe {
+            let () = msg_send![*self.uiview.load(), setNeedsDisplay];
+        }
+    }
+
+    fn text_factory(&mut self) -> &mut Text<'a> {
+        &mut self.text
+    }
+
+    /// There is no cursor on a touch screen.
+    fn set_cursor(&mut self, _cursor: &Cursor) {}
+
+    fn request_timer(&mut self, deadline: Instant) -> TimerToken {
+        let ti = time_interval_from_deadline(deadline);
+        let token = next_timer_id();
+        unsafe {
+            let user_info: id = msg_send![class!(NSNumber), numberWithUnsignedInteger: token];
+            let selector = sel!(handleTimer:);
+            let view = *self.uiview.load();
+            let _: id = msg_send![class!(NSTimer), scheduledTimerWithTimeInterval: ti target: view selector: selector userInfo: user_info repeats: NO];
+        }
+        TimerToken::new(token)
+    }
+
+    fn open_file_sync(&mut self, _options: FileDialogOptions) -> Option<FileInfo> {
+        //TODO: this needs UIDocumentPickerViewController, which is asynchronous.
+        warn!("synchronous file dialogs are not supported on iOS");
+        None
+    }
+
+    fn set_clipboard_contents(&mut self, contents: ClipboardItem) {
+        Application::set_clipboard_contents(contents);
+    }
+
+    fn start_drag(&mut self, _contents: DragContents) {
+        //TODO: implement with UIDragInteraction
+        warn!("dragging out of the window is not yet supported on iOS");
+    }
+
+    fn set_soft_keyboard_visible(&mut self, visible: bool) {
+        unsafe {
+            let view = *self.uiview.load();
+            let _: BOOL = if visible {
+                msg_send![view, becomeFirstResponder]
+            } else {
+                msg_send![view, resignFirstResponder]
+            };
+        }
+    }
+}
+
+/// Convert an `Instant` into an NSTimeInterval, i.e. a fractional number
+/// of seconds from now.
+fn time_interval_from_deadline(deadline: Instant) -> f64 {
+    let now = Instant::now();
+    if now >= deadline {
+        0.0
+    } else {
+        let duration = deadline - now;
+        duration.as_secs() as f64 + duration.subsec_nanos() as f64 * 1e-9
+    }
+}
+
+fn next_timer_id() -> usize {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static TIMER_ID: AtomicUsize = AtomicUsize::new(1);
+    TIMER_ID.fetch_add(1, Ordering::Relaxed)
+}
@@ -0,0 +1,74 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Utilities, iOS specific.
+
+use std::os::raw::c_char;
+
+use objc::runtime::{Object, BOOL, YES};
+
+/// An Objective-C object pointer.
+#[allow(non_camel_case_types)]
+pub(crate) type id = *mut Object;
+
+/// `NSUTF8StringEncoding`.
+const UTF8_ENCODING: usize = 4;
+
+pub fn init() {}
+
+/// Panic if not on the main thread.
+///
+/// UIKit may only be used from the main thread.
+pub fn assert_main_thread() {
+    unsafe {
+        let is_main_thread: BOOL = msg_send!(class!(NSThread), isMainThread);
+        assert_eq!(is_main_thread, YES);
+    }
+}
+
+/// Create a new NSString from a &str.
+pub(crate) fn make_nsstring(s: &str) -> id {
+    unsafe {
+        let string: id = msg_send![class!(NSString), alloc];
+        let string: id =
+            msg_send![string, initWithBytes: s.as_ptr() length: s.len() encoding: UTF8_ENCODING];
+        msg_send![string, autorelease]
+    }
+}
+
+pub(crate) fn from_nsstring(s: id) -> String {
+    unsafe {
+        let bytes: *const c_char = msg_send![s, UTF8String];
+        let len: usize = msg_send![s, lengthOfBytesUsingEncoding: UTF8_ENCODING];
+        let slice = std::slice::from_raw_parts(bytes as *const u8, len);
+        std::str::from_utf8_unchecked(slice).into()
+    }
+}
+
+/// Returns the current locale string.
+///
+/// This should a [Unicode language identifier].
+///
+/// [Unicode language identifier]: https://unicode.org/reports/tr35/#Unicode_language_identifier
+pub fn get_locale() -> String {
+    unsafe {
+        let locale: id = msg_send![class!(NSLocale), currentLocale];
+        let ident: id = msg_send![locale, localeIdentifier];
+        let mut locale = from_nsstring(ident);
+        if let Some(idx) = locale.chars().position(|c| c == '@') {
+            locale.truncate(idx);
+        }
+        locale
+    }
+}
@@ -0,0 +1,208 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Embedded web content, iOS implementation, using `WKWebView`.
+
+use std::ffi::c_void;
+use std::ptr::null_mut;
+
+use objc::declare::ClassDecl;
+use objc::rc::StrongPtr;
+use objc::runtime::{Class, Object, Protocol, Sel, YES};
+
+use log::warn;
+
+use crate::webview::{BRIDGE_SCRIPT, MESSAGE_HANDLER};
+use crate::Error;
+
+use super::util::{from_nsstring, id, make_nsstring};
+use super::{CGPoint, CGRect, CGSize, ChildView};
+
+#[link(name = "WebKit", kind = "framework")]
+extern "C" {}
+
+/// `WKUserScriptInjectionTimeAtDocumentStart`
+const INJECT_AT_DOCUMENT_START: isize = 0;
+
+type MessageCallback = Box<dyn FnMut(String)>;
+
+/// A view showing web content.
+pub struct WebView {
+    view: ChildView,
+    webview: id,
+    handler: StrongPtr,
+}
+
+struct HandlerClass(*const Class);
+unsafe impl Sync for HandlerClass {}
+
+lazy_static! {
+    static ref HANDLER_CLASS: HandlerClass = unsafe {
+        let mut decl = ClassDecl::new("DruidScriptMessageHandler", class!(NSObject))
+            .expect("Script message handler class defined");
+        decl.add_ivar::<*mut c_void>("callback");
+        if let Some(protocol) = Protocol::get("WKScriptMessageHandler") {
+            decl.add_protocol(protocol);
+        }
+        decl.add_method(
+            sel!(userContentController:didReceiveScriptMessage:),
+            did_receive_message as extern "C" fn(&Object, Sel, id, id),
+        );
+        HandlerClass(decl.register())
+    };
+}
+
+extern "C" fn did_receive_message(this: &Object, _: Sel, _controller: id, message: id) {
+    unsafe {
+        let callback: *mut c_void = *this.get_ivar("callback");
+        if callback.is_null() {
+            return;
+        }
+        let callback = &mut *(callback as *mut MessageCallback);
+        let body: id = msg_send![message, body];
+        let body: id = msg_send![body, description];
+        callback(from_nsstring(body));
+    }
+}
+
+impl WebView {
+    /// Create a new web view, initially empty.
+    ///
+    /// The callback is called with each message posted by the page.
+    pub fn new(on_message: impl FnMut(String) + 'static) -> Result<WebView, Error> {
+        unsafe {
+            let config: id = msg_send![class!(WKWebViewConfiguration), new];
+            let controller: id = msg_send![config, userContentController];
+
+            let handler: id = msg_send![HANDLER_CLASS.0, new];
+            let callback: Box<MessageCallback> = Box::new(Box::new(on_message));
+            (*handler).set_ivar("callback", Box::into_raw(callback) as *mut c_void);
+            let () = msg_send![controller, addScriptMessageHandler: handler
+                name: make_nsstring(MESSAGE_HANDLER)];
+
+            let script: id = msg_send![class!(WKUserScript), alloc];
+            let script: id = msg_send![script, initWithSource: make_nsstring(BRIDGE_SCRIPT)
+                injectionTime: INJECT_AT_DOCUMENT_START forMainFrameOnly: YES];
+            let () = msg_send![controller, addUserScript: script];
+            let () = msg_send![script, release];
+
+            let frame = CGRect {
+                origin: CGPoint { x: 0.0, y: 0.0 },
+                size: CGSize {
+                    width: 100.0,
+                    height: 100.0,
+                },
+            };
+            let webview: id = msg_send![class!(WKWebView), alloc];
+            let webview: id = msg_send![webview, initWithFrame: frame configuration: config];
+            let () = msg_send![config, release];
+            let handler = StrongPtr::new(handler);
+            if webview.is_null() {
+                free_callback(*handler);
+                return Err(Error::Null);
+            }
+            let view = ChildView::from_uiview(webview);
+            // The child view holds the only reference we need.
+            let () = msg_send![webview, release];
+            Ok(WebView {
+                view,
+                webview,
+                handler,
+            })
+        }
+    }
+
+    /// The view, for placing in a window.
+    pub fn view(&self) -> &ChildView {
+        &self.view
+    }
+
+    /// Start loading the given URL.
+    pub fn load_url(&self, url: &str) {
+        unsafe {
+            let nsurl: id = msg_send![class!(NSURL), URLWithString: make_nsstring(url)];
+            if nsurl.is_null() {
+                warn!("invalid url {:?}", url);
+                return;
+            }
+            let request: id = msg_send![class!(NSURLRequest), requestWithURL: nsurl];
+            let _: id = msg_send![self.webview, loadRequest: request];
+        }
+    }
+
+    /// Show the given HTML. Relative links are resolved against `base_url`.
+    pub fn load_html(&self, html: &str, base_url: Option<&str>) {
+        unsafe {
+            let base: id = match base_url {
+                Some(url) => msg_send![class!(NSURL), URLWithString: make_nsstring(url)],
+                None => null_mut(),
+            };
+            let _: id = msg_send![self.webview, loadHTMLString: make_nsstring(html) baseURL: base];
+        }
+    }
+
+    pub fn go_back(&self) {
+        unsafe {
+            let _: id = msg_send![self.webview, goBack];
+        }
+    }
+
+    pub fn go_forward(&self) {
+        unsafe {
+            let _: id = msg_send![self.webview, goForward];
+        }
+    }
+
+    pub fn reload(&self) {
+        unsafe {
+            let _: id = msg_send![self.webview, reload];
+        }
+    }
+
+    pub fn stop(&self) {
+        unsafe {
+            let () = msg_send![self.webview, stopLoading];
+        }
+    }
+
+    /// Run a script in the context of the current page. The result is
+    /// discarded.
+    pub fn eval_script(&self, script: &str) {
+        unsafe {
+            let () = msg_send![self.webview, evaluateJavaScript: make_nsstring(script)
+                completionHandler: null_mut::<Object>()];
+        }
+    }
+}
+
+impl Drop for WebView {
+    fn drop(&mut self) {
+        unsafe {
+            let config: id = msg_send![self.webview, configuration];
+            let controller: id = msg_send![config, userContentController];
+            let () = msg_send![controller,
+                removeScriptMessageHandlerForName: make_nsstring(MESSAGE_HANDLER)];
+            free_callback(*self.handler);
+        }
+    }
+}
+
+/// Drop the message callback of a handler object.
+unsafe fn free_callback(handler: id) {
+    let callback: *mut c_void = *(*handler).get_ivar("callback");
+    (*handler).set_ivar("callback", std::ptr::null_mut::<c_void>());
+    if !callback.is_null() {
+        drop(Box::from_raw(callback as *mut MessageCallback));
+    }
+}
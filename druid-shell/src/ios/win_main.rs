@@ -0,0 +1,57 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! iOS implementation of the runloop.
+
+use std::os::raw::{c_char, c_int};
+use std::ptr::null_mut;
+
+use log::warn;
+
+use crate::util::{assert_main_thread, id, make_nsstring};
+
+#[link(name = "UIKit", kind = "framework")]
+extern "C" {
+    fn UIApplicationMain(
+        argc: c_int,
+        argv: *mut *mut c_char,
+        principal_class_name: id,
+        delegate_class_name: id,
+    ) -> c_int;
+}
+
+pub struct RunLoop {}
+
+impl RunLoop {
+    pub fn new() -> RunLoop {
+        assert_main_thread();
+        RunLoop {}
+    }
+
+    /// Run the application. On iOS, this never returns.
+    pub fn run(&mut self) {
+        // Make sure the delegate class is registered before UIKit looks it up.
+        let class_name = super::app_delegate_class_name();
+        unsafe {
+            UIApplicationMain(0, null_mut(), null_mut(), make_nsstring(class_name));
+        }
+    }
+}
+
+/// Embedding is not supported, so there is nothing to pump.
+pub fn pump_events() {}
+
+pub fn request_quit() {
+    warn!("iOS applications cannot quit themselves");
+}
@@ -20,7 +20,14 @@ use std::fmt;
 #[derive(Debug, Clone, Copy)]
 pub struct KeyEvent {
     /// The platform independent keycode.
+    ///
+    /// This identifies the physical key, regardless of the keyboard layout.
     pub key_code: KeyCode,
+    /// The logical key, taking the keyboard layout into account.
+    pub key: KbKey,
+    /// Which of several keys with the same meaning was pressed, such as the
+    /// left or right shift key.
+    pub location: KeyLocation,
     /// Whether or not this event is a repeat (the key was held down)
     pub is_repeat: bool,
     /// The modifiers for this event.
@@ -54,9 +61,38 @@ impl KeyEvent {
             StrOrChar::Char(c) => c.into(),
             StrOrChar::Str(s) => TinyStr::new(s),
         };
+        KeyEvent::from_tiny_strs(key_code.into(), is_repeat, mods, text, unmodified_text)
+    }
 
+    /// Create a `KeyEvent` from its parts, such as when replaying recorded
+    /// input.
+    ///
+    /// As for events from the platform, the logical key and its location are
+    /// derived from the key code and the unmodified text.
+    pub fn from_parts(
+        key_code: KeyCode,
+        is_repeat: bool,
+        mods: KeyModifiers,
+        text: &str,
+        unmodified_text: &str,
+    ) -> Self {
+        let text = TinyStr::new(text);
+        let unmodified_text = TinyStr::new(unmodified_text);
+        KeyEvent::from_tiny_strs(key_code, is_repeat, mods, text, unmodified_text)
+    }
+
+    fn from_tiny_strs(
+        key_code: KeyCode,
+        is_repeat: bool,
+        mods: KeyModifiers,
+        text: TinyStr,
+        unmodified_text: TinyStr,
+    ) -> Self {
+        let key = KbKey::new(key_code, unmodified_text.as_str());
         KeyEvent {
-            key_code: key_code.into(),
+            key_code,
+            key,
+            location: key_code.location(),
             is_repeat,
             mods,
             text,
@@ -90,6 +126,132 @@ impl KeyEvent {
     }
 }
 
+/// The logical meaning of a key, as described by the current keyboard layout.
+///
+/// Where [`KeyCode`] names the physical key (the key labeled 'Q' on a US
+/// layout is `KeyCode::KeyQ` everywhere), `KbKey` is what that key means:
+/// on a French layout it is `KbKey::Character('a')`. The variants follow the
+/// names of the [key values] used by DOM keyboard events.
+///
+/// Characters are those produced without modifiers other than shift; when
+/// the platform reports no text for a printable key, as happens with some
+/// ctrl shortcuts, the character of the key on a US layout is used.
+///
+/// [`KeyCode`]: enum.KeyCode.html
+/// [key values]: https://developer.mozilla.org/en-US/docs/Web/API/KeyboardEvent/key/Key_Values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KbKey {
+    /// A key that produces a character, including the space bar.
+    Character(char),
+    Enter,
+    Tab,
+    Backspace,
+    Delete,
+    Escape,
+    Insert,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    ArrowUp,
+    ArrowDown,
+    ArrowLeft,
+    ArrowRight,
+    Shift,
+    Control,
+    Alt,
+    /// Command on macOS, the Windows key elsewhere.
+    Meta,
+    CapsLock,
+    NumLock,
+    ScrollLock,
+    PrintScreen,
+    Pause,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+    /// A key we don't know how to name.
+    Unidentified,
+}
+
+/// The location of a key on the keyboard, for keys that appear more than once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyLocation {
+    /// The key is not distinguished by location.
+    Standard,
+    /// The left-hand version of a key, such as the left shift.
+    Left,
+    /// The right-hand version of a key, such as the right alt (AltGr).
+    Right,
+    /// A key on the numeric keypad.
+    Numpad,
+}
+
+impl KbKey {
+    pub(crate) fn new(code: KeyCode, unmodified_text: &str) -> KbKey {
+        use KeyCode::*;
+        match code {
+            Return | NumpadEnter => KbKey::Enter,
+            Tab => KbKey::Tab,
+            Backspace => KbKey::Backspace,
+            Delete => KbKey::Delete,
+            Escape => KbKey::Escape,
+            Insert => KbKey::Insert,
+            Home => KbKey::Home,
+            End => KbKey::End,
+            PageUp => KbKey::PageUp,
+            PageDown => KbKey::PageDown,
+            ArrowUp => KbKey::ArrowUp,
+            ArrowDown => KbKey::ArrowDown,
+            ArrowLeft => KbKey::ArrowLeft,
+            ArrowRight => KbKey::ArrowRight,
+            LeftShift | RightShift => KbKey::Shift,
+            LeftControl | RightControl => KbKey::Control,
+            LeftAlt | RightAlt => KbKey::Alt,
+            LeftMeta | RightMeta => KbKey::Meta,
+            CapsLock => KbKey::CapsLock,
+            NumLock => KbKey::NumLock,
+            ScrollLock => KbKey::ScrollLock,
+            PrintScreen => KbKey::PrintScreen,
+            Pause => KbKey::Pause,
+            F1 => KbKey::F1,
+            F2 => KbKey::F2,
+            F3 => KbKey::F3,
+            F4 => KbKey::F4,
+            F5 => KbKey::F5,
+            F6 => KbKey::F6,
+            F7 => KbKey::F7,
+            F8 => KbKey::F8,
+            F9 => KbKey::F9,
+            F10 => KbKey::F10,
+            F11 => KbKey::F11,
+            F12 => KbKey::F12,
+            other => unmodified_text
+                .chars()
+                .next()
+                .filter(|c| !c.is_control())
+                .or_else(|| other.us_char())
+                .map(KbKey::Character)
+                .unwrap_or(KbKey::Unidentified),
+        }
+    }
+}
+
+impl Default for KeyLocation {
+    fn default() -> Self {
+        KeyLocation::Standard
+    }
+}
+
 /// Keyboard modifier state, provided for events.
 #[derive(Clone, Copy, Default, PartialEq)]
 pub struct KeyModifiers {
@@ -239,9 +401,85 @@ pub enum KeyCode {
 pub enum RawKeyCode {
     Windows(i32),
     Mac(u16),
+    Linux(u32),
+    Web(u32),
+    Android(i32),
+    Ios(u32),
 }
 
 impl KeyCode {
+    /// The location of this key, for keys that appear more than once.
+    pub fn location(self) -> KeyLocation {
+        use KeyCode::*;
+        match self {
+            LeftControl | LeftAlt | LeftShift | LeftMeta => KeyLocation::Left,
+            RightControl | RightAlt | RightShift | RightMeta => KeyLocation::Right,
+            Numpad0 | Numpad1 | Numpad2 | Numpad3 | Numpad4 | Numpad5 | Numpad6 | Numpad7
+            | Numpad8 | Numpad9 | NumpadEquals | NumpadSubtract | NumpadAdd | NumpadDecimal
+            | NumpadMultiply | NumpadDivide | NumLock | NumpadEnter => KeyLocation::Numpad,
+            _ => KeyLocation::Standard,
+        }
+    }
+
+    /// The character this key produces on a US layout, without modifiers.
+    fn us_char(self) -> Option<char> {
+        use KeyCode::*;
+        let c = match self {
+            Backtick => '`',
+            Key0 | Numpad0 => '0',
+            Key1 | Numpad1 => '1',
+            Key2 | Numpad2 => '2',
+            Key3 | Numpad3 => '3',
+            Key4 | Numpad4 => '4',
+            Key5 | Numpad5 => '5',
+            Key6 | Numpad6 => '6',
+            Key7 | Numpad7 => '7',
+            Key8 | Numpad8 => '8',
+            Key9 | Numpad9 => '9',
+            Minus | NumpadSubtract => '-',
+            Equals | NumpadEquals => '=',
+            KeyQ => 'q',
+            KeyW => 'w',
+            KeyE => 'e',
+            KeyR => 'r',
+            KeyT => 't',
+            KeyY => 'y',
+            KeyU => 'u',
+            KeyI => 'i',
+            KeyO => 'o',
+            KeyP => 'p',
+            LeftBracket => '[',
+            RightBracket => ']',
+            KeyA => 'a',
+            KeyS => 's',
+            KeyD => 'd',
+            KeyF => 'f',
+            KeyG => 'g',
+            KeyH => 'h',
+            KeyJ => 'j',
+            KeyK => 'k',
+            KeyL => 'l',
+            Semicolon => ';',
+            Quote => '\'',
+            Backslash => '\\',
+            KeyZ => 'z',
+            KeyX => 'x',
+            KeyC => 'c',
+            KeyV => 'v',
+            KeyB => 'b',
+            KeyN => 'n',
+            KeyM => 'm',
+            Comma => ',',
+            Period | NumpadDecimal => '.',
+            Slash | NumpadDivide => '/',
+            NumpadAdd => '+',
+            NumpadMultiply => '*',
+            Space => ' ',
+            _ => return None,
+        };
+        Some(c)
+    }
+
     pub fn is_printable(self) -> bool {
         use KeyCode::*;
         match self {
@@ -411,6 +649,405 @@ impl From<u16> for RawKeyCode {
     }
 }
 
+#[cfg(target_os = "linux")]
+impl From<u32> for RawKeyCode {
+    fn from(src: u32) -> RawKeyCode {
+        RawKeyCode::Linux(src)
+    }
+}
+
+/// Convert an X11 hardware keycode, as reported by GDK.
+///
+/// These are evdev scancodes offset by 8.
+#[cfg(target_os = "linux")]
+impl From<u32> for KeyCode {
+    fn from(raw: u32) -> KeyCode {
+        match raw {
+            9 => KeyCode::Escape,
+            10 => KeyCode::Key1,
+            11 => KeyCode::Key2,
+            12 => KeyCode::Key3,
+            13 => KeyCode::Key4,
+            14 => KeyCode::Key5,
+            15 => KeyCode::Key6,
+            16 => KeyCode::Key7,
+            17 => KeyCode::Key8,
+            18 => KeyCode::Key9,
+            19 => KeyCode::Key0,
+            20 => KeyCode::Minus,
+            21 => KeyCode::Equals,
+            22 => KeyCode::Backspace,
+            23 => KeyCode::Tab,
+            24 => KeyCode::KeyQ,
+            25 => KeyCode::KeyW,
+            26 => KeyCode::KeyE,
+            27 => KeyCode::KeyR,
+            28 => KeyCode::KeyT,
+            29 => KeyCode::KeyY,
+            30 => KeyCode::KeyU,
+            31 => KeyCode::KeyI,
+            32 => KeyCode::KeyO,
+            33 => KeyCode::KeyP,
+            34 => KeyCode::LeftBracket,
+            35 => KeyCode::RightBracket,
+            36 => KeyCode::Return,
+            37 => KeyCode::LeftControl,
+            38 => KeyCode::KeyA,
+            39 => KeyCode::KeyS,
+            40 => KeyCode::KeyD,
+            41 => KeyCode::KeyF,
+            42 => KeyCode::KeyG,
+            43 => KeyCode::KeyH,
+            44 => KeyCode::KeyJ,
+            45 => KeyCode::KeyK,
+            46 => KeyCode::KeyL,
+            47 => KeyCode::Semicolon,
+            48 => KeyCode::Quote,
+            49 => KeyCode::Backtick,
+            50 => KeyCode::LeftShift,
+            51 => KeyCode::Backslash,
+            52 => KeyCode::KeyZ,
+            53 => KeyCode::KeyX,
+            54 => KeyCode::KeyC,
+            55 => KeyCode::KeyV,
+            56 => KeyCode::KeyB,
+            57 => KeyCode::KeyN,
+            58 => KeyCode::KeyM,
+            59 => KeyCode::Comma,
+            60 => KeyCode::Period,
+            61 => KeyCode::Slash,
+            62 => KeyCode::RightShift,
+            63 => KeyCode::NumpadMultiply,
+            64 => KeyCode::LeftAlt,
+            65 => KeyCode::Space,
+            66 => KeyCode::CapsLock,
+            67 => KeyCode::F1,
+            68 => KeyCode::F2,
+            69 => KeyCode::F3,
+            70 => KeyCode::F4,
+            71 => KeyCode::F5,
+            72 => KeyCode::F6,
+            73 => KeyCode::F7,
+            74 => KeyCode::F8,
+            75 => KeyCode::F9,
+            76 => KeyCode::F10,
+            77 => KeyCode::NumLock,
+            78 => KeyCode::ScrollLock,
+            79 => KeyCode::Numpad7,
+            80 => KeyCode::Numpad8,
+            81 => KeyCode::Numpad9,
+            82 => KeyCode::NumpadSubtract,
+            83 => KeyCode::Numpad4,
+            84 => KeyCode::Numpad5,
+            85 => KeyCode::Numpad6,
+            86 => KeyCode::NumpadAdd,
+            87 => KeyCode::Numpad1,
+            88 => KeyCode::Numpad2,
+            89 => KeyCode::Numpad3,
+            90 => KeyCode::Numpad0,
+            91 => KeyCode::NumpadDecimal,
+            95 => KeyCode::F11,
+            96 => KeyCode::F12,
+            104 => KeyCode::NumpadEnter,
+            105 => KeyCode::RightControl,
+            106 => KeyCode::NumpadDivide,
+            107 => KeyCode::PrintScreen,
+            108 => KeyCode::RightAlt,
+            110 => KeyCode::Home,
+            111 => KeyCode::ArrowUp,
+            112 => KeyCode::PageUp,
+            113 => KeyCode::ArrowLeft,
+            114 => KeyCode::ArrowRight,
+            115 => KeyCode::End,
+            116 => KeyCode::ArrowDown,
+            117 => KeyCode::PageDown,
+            118 => KeyCode::Insert,
+            119 => KeyCode::Delete,
+            125 => KeyCode::NumpadEquals,
+            127 => KeyCode::Pause,
+            133 => KeyCode::LeftMeta,
+            134 => KeyCode::RightMeta,
+            other => KeyCode::Unknown(other.into()),
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl From<u32> for RawKeyCode {
+    fn from(src: u32) -> RawKeyCode {
+        RawKeyCode::Web(src)
+    }
+}
+
+/// Generate the conversions between `KeyCode`s and the names of the physical
+/// keys used by the `code` of DOM keyboard events.
+macro_rules! code_names {
+    ($($name:literal => $code:ident,)*) => {
+        impl KeyCode {
+            /// The key with the given [DOM `code`] name, such as `"KeyA"` or
+            /// `"ArrowUp"`.
+            ///
+            /// [DOM `code`]: https://developer.mozilla.org/en-US/docs/Web/API/KeyboardEvent/code
+            pub fn from_code_name(name: &str) -> Option<KeyCode> {
+                match name {
+                    $($name => Some(KeyCode::$code),)*
+                    _ => None,
+                }
+            }
+
+            /// The DOM `code` name of this key, or `None` for unknown keys.
+            ///
+            /// This is a stable, platform independent name, suitable for
+            /// storing; it can be converted back with [`from_code_name`].
+            ///
+            /// [`from_code_name`]: #method.from_code_name
+            pub fn code_name(self) -> Option<&'static str> {
+                match self {
+                    $(KeyCode::$code => Some($name),)*
+                    KeyCode::Unknown(_) => None,
+                }
+            }
+        }
+    };
+}
+
+code_names! {
+    "Escape" => Escape,
+    "Backquote" => Backtick,
+    "Digit0" => Key0,
+    "Digit1" => Key1,
+    "Digit2" => Key2,
+    "Digit3" => Key3,
+    "Digit4" => Key4,
+    "Digit5" => Key5,
+    "Digit6" => Key6,
+    "Digit7" => Key7,
+    "Digit8" => Key8,
+    "Digit9" => Key9,
+    "Minus" => Minus,
+    "Equal" => Equals,
+    "Backspace" => Backspace,
+    "Tab" => Tab,
+    "KeyQ" => KeyQ,
+    "KeyW" => KeyW,
+    "KeyE" => KeyE,
+    "KeyR" => KeyR,
+    "KeyT" => KeyT,
+    "KeyY" => KeyY,
+    "KeyU" => KeyU,
+    "KeyI" => KeyI,
+    "KeyO" => KeyO,
+    "KeyP" => KeyP,
+    "BracketLeft" => LeftBracket,
+    "BracketRight" => RightBracket,
+    "Enter" => Return,
+    "KeyA" => KeyA,
+    "KeyS" => KeyS,
+    "KeyD" => KeyD,
+    "KeyF" => KeyF,
+    "KeyG" => KeyG,
+    "KeyH" => KeyH,
+    "KeyJ" => KeyJ,
+    "KeyK" => KeyK,
+    "KeyL" => KeyL,
+    "Semicolon" => Semicolon,
+    "Quote" => Quote,
+    "Backslash" => Backslash,
+    "KeyZ" => KeyZ,
+    "KeyX" => KeyX,
+    "KeyC" => KeyC,
+    "KeyV" => KeyV,
+    "KeyB" => KeyB,
+    "KeyN" => KeyN,
+    "KeyM" => KeyM,
+    "Comma" => Comma,
+    "Period" => Period,
+    "Slash" => Slash,
+    "ControlLeft" => LeftControl,
+    "ControlRight" => RightControl,
+    "AltLeft" => LeftAlt,
+    "AltRight" => RightAlt,
+    "ShiftLeft" => LeftShift,
+    "ShiftRight" => RightShift,
+    "MetaLeft" => LeftMeta,
+    "MetaRight" => RightMeta,
+    "Space" => Space,
+    "CapsLock" => CapsLock,
+    "F1" => F1,
+    "F2" => F2,
+    "F3" => F3,
+    "F4" => F4,
+    "F5" => F5,
+    "F6" => F6,
+    "F7" => F7,
+    "F8" => F8,
+    "F9" => F9,
+    "F10" => F10,
+    "F11" => F11,
+    "F12" => F12,
+    "PrintScreen" => PrintScreen,
+    "ScrollLock" => ScrollLock,
+    "Pause" => Pause,
+    "Insert" => Insert,
+    "Delete" => Delete,
+    "Home" => Home,
+    "End" => End,
+    "PageUp" => PageUp,
+    "PageDown" => PageDown,
+    "Numpad0" => Numpad0,
+    "Numpad1" => Numpad1,
+    "Numpad2" => Numpad2,
+    "Numpad3" => Numpad3,
+    "Numpad4" => Numpad4,
+    "Numpad5" => Numpad5,
+    "Numpad6" => Numpad6,
+    "Numpad7" => Numpad7,
+    "Numpad8" => Numpad8,
+    "Numpad9" => Numpad9,
+    "NumpadEqual" => NumpadEquals,
+    "NumpadSubtract" => NumpadSubtract,
+    "NumpadAdd" => NumpadAdd,
+    "NumpadDecimal" => NumpadDecimal,
+    "NumpadMultiply" => NumpadMultiply,
+    "NumpadDivide" => NumpadDivide,
+    "NumLock" => NumLock,
+    "NumpadEnter" => NumpadEnter,
+    "ArrowUp" => ArrowUp,
+    "ArrowDown" => ArrowDown,
+    "ArrowLeft" => ArrowLeft,
+    "ArrowRight" => ArrowRight,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl KeyCode {
+    /// Convert the `code` of a DOM `KeyboardEvent`, which names the physical
+    /// key. The legacy numeric `keyCode` is kept for keys we don't know.
+    pub(crate) fn from_dom_code(code: &str, raw: u32) -> KeyCode {
+        KeyCode::from_code_name(code).unwrap_or_else(|| KeyCode::Unknown(raw.into()))
+    }
+}
+
+#[cfg(target_os = "android")]
+impl From<i32> for RawKeyCode {
+    fn from(src: i32) -> RawKeyCode {
+        RawKeyCode::Android(src)
+    }
+}
+
+/// Convert an Android key code (`AKEYCODE_*`).
+#[cfg(target_os = "android")]
+impl From<i32> for KeyCode {
+    fn from(raw: i32) -> KeyCode {
+        match raw {
+            7 => KeyCode::Key0,
+            8 => KeyCode::Key1,
+            9 => KeyCode::Key2,
+            10 => KeyCode::Key3,
+            11 => KeyCode::Key4,
+            12 => KeyCode::Key5,
+            13 => KeyCode::Key6,
+            14 => KeyCode::Key7,
+            15 => KeyCode::Key8,
+            16 => KeyCode::Key9,
+            19 => KeyCode::ArrowUp,
+            20 => KeyCode::ArrowDown,
+            21 => KeyCode::ArrowLeft,
+            22 => KeyCode::ArrowRight,
+            29 => KeyCode::KeyA,
+            30 => KeyCode::KeyB,
+            31 => KeyCode::KeyC,
+            32 => KeyCode::KeyD,
+            33 => KeyCode::KeyE,
+            34 => KeyCode::KeyF,
+            35 => KeyCode::KeyG,
+            36 => KeyCode::KeyH,
+            37 => KeyCode::KeyI,
+            38 => KeyCode::KeyJ,
+            39 => KeyCode::KeyK,
+            40 => KeyCode::KeyL,
+            41 => KeyCode::KeyM,
+            42 => KeyCode::KeyN,
+            43 => KeyCode::KeyO,
+            44 => KeyCode::KeyP,
+            45 => KeyCode::KeyQ,
+            46 => KeyCode::KeyR,
+            47 => KeyCode::KeyS,
+            48 => KeyCode::KeyT,
+            49 => KeyCode::KeyU,
+            50 => KeyCode::KeyV,
+            51 => KeyCode::KeyW,
+            52 => KeyCode::KeyX,
+            53 => KeyCode::KeyY,
+            54 => KeyCode::KeyZ,
+            55 => KeyCode::Comma,
+            56 => KeyCode::Period,
+            57 => KeyCode::LeftAlt,
+            58 => KeyCode::RightAlt,
+            59 => KeyCode::LeftShift,
+            60 => KeyCode::RightShift,
+            61 => KeyCode::Tab,
+            62 => KeyCode::Space,
+            66 => KeyCode::Return,
+            67 => KeyCode::Backspace,
+            68 => KeyCode::Backtick,
+            69 => KeyCode::Minus,
+            70 => KeyCode::Equals,
+            71 => KeyCode::LeftBracket,
+            72 => KeyCode::RightBracket,
+            73 => KeyCode::Backslash,
+            74 => KeyCode::Semicolon,
+            75 => KeyCode::Quote,
+            76 => KeyCode::Slash,
+            92 => KeyCode::PageUp,
+            93 => KeyCode::PageDown,
+            111 => KeyCode::Escape,
+            112 => KeyCode::Delete,
+            113 => KeyCode::LeftControl,
+            114 => KeyCode::RightControl,
+            115 => KeyCode::CapsLock,
+            116 => KeyCode::ScrollLock,
+            117 => KeyCode::LeftMeta,
+            118 => KeyCode::RightMeta,
+            120 => KeyCode::PrintScreen,
+            121 => KeyCode::Pause,
+            122 => KeyCode::Home,
+            123 => KeyCode::End,
+            124 => KeyCode::Insert,
+            131 => KeyCode::F1,
+            132 => KeyCode::F2,
+            133 => KeyCode::F3,
+            134 => KeyCode::F4,
+            135 => KeyCode::F5,
+            136 => KeyCode::F6,
+            137 => KeyCode::F7,
+            138 => KeyCode::F8,
+            139 => KeyCode::F9,
+            140 => KeyCode::F10,
+            141 => KeyCode::F11,
+            142 => KeyCode::F12,
+            143 => KeyCode::NumLock,
+            144 => KeyCode::Numpad0,
+            145 => KeyCode::Numpad1,
+            146 => KeyCode::Numpad2,
+            147 => KeyCode::Numpad3,
+            148 => KeyCode::Numpad4,
+            149 => KeyCode::Numpad5,
+            150 => KeyCode::Numpad6,
+            151 => KeyCode::Numpad7,
+            152 => KeyCode::Numpad8,
+            153 => KeyCode::Numpad9,
+            154 => KeyCode::NumpadDivide,
+            155 => KeyCode::NumpadMultiply,
+            156 => KeyCode::NumpadSubtract,
+            157 => KeyCode::NumpadAdd,
+            158 => KeyCode::NumpadDecimal,
+            160 => KeyCode::NumpadEnter,
+            161 => KeyCode::NumpadEquals,
+            other => KeyCode::Unknown(other.into()),
+        }
+    }
+}
+
 #[cfg(target_os = "windows")]
 impl From<i32> for KeyCode {
     fn from(src: i32) -> KeyCode {
@@ -705,6 +1342,27 @@ mod tests {
         let _too_big = TinyStr::new("😍🥰😘😗");
     }
 
+    #[test]
+    fn logical_key() {
+        // An AZERTY layout, where the physical 'Q' key types 'a'.
+        let event = KeyEvent::new(KeyCode::KeyQ, false, KeyModifiers::default(), "a", "a");
+        assert_eq!(event.key, KbKey::Character('a'));
+        // ctrl shortcuts may have control characters or nothing as their text
+        let event = KeyEvent::new(KeyCode::KeyC, false, KeyModifiers::default(), '\u{3}', "");
+        assert_eq!(event.key, KbKey::Character('c'));
+        let event = KeyEvent::for_test(KeyModifiers::default(), "", KeyCode::RightShift);
+        assert_eq!(event.key, KbKey::Shift);
+        assert_eq!(event.location, KeyLocation::Right);
+    }
+
+    #[test]
+    fn code_names() {
+        assert_eq!(KeyCode::from_code_name("KeyA"), Some(KeyCode::KeyA));
+        assert_eq!(KeyCode::NumpadEnter.code_name(), Some("NumpadEnter"));
+        assert_eq!(KeyCode::from_code_name("Hyper"), None);
+        assert_eq!(KeyCode::Unknown(RawKeyCode::Mac(64)).code_name(), None);
+    }
+
     #[test]
     fn vk_mac() {
         assert_eq!(KeyCode::from(0x30_u16), KeyCode::Tab);
@@ -715,6 +1373,16 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn x11_keycode() {
+        assert_eq!(KeyCode::from(38_u32), KeyCode::KeyA);
+        assert_eq!(
+            KeyCode::from(191_u32),
+            KeyCode::Unknown(RawKeyCode::Linux(191))
+        );
+    }
+
     #[test]
     #[cfg(target_os = "windows")]
     fn win_vk() {
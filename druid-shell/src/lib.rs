@@ -18,12 +18,13 @@
 
 pub use piet_common as piet;
 pub use piet_common::kurbo;
+pub use raw_window_handle;
 
 #[cfg(target_os = "windows")]
 #[macro_use]
 extern crate winapi;
 
-#[cfg(target_os = "macos")]
+#[cfg(any(target_os = "macos", target_os = "ios"))]
 #[macro_use]
 extern crate objc;
 
@@ -31,11 +32,26 @@ extern crate objc;
 extern crate lazy_static;
 
 pub mod clipboard;
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+mod common_util;
 pub mod dialog;
+pub mod dnd;
 pub mod error;
+#[cfg(not(any(target_os = "windows", target_arch = "wasm32")))]
+pub mod headless;
 pub mod hotkey;
+pub mod image;
+pub mod ime;
 pub mod keyboard;
 pub mod keycodes;
+#[cfg(not(any(target_os = "windows", target_arch = "wasm32")))]
+pub mod pdf;
+pub mod print;
+pub mod taskbar;
+#[cfg(feature = "video")]
+pub mod video;
+#[cfg(feature = "webview")]
+pub mod webview;
 pub mod window;
 
 #[cfg(target_os = "windows")]
@@ -50,11 +66,32 @@ pub mod mac;
 #[cfg(target_os = "macos")]
 pub use mac as platform;
 
+#[cfg(target_os = "ios")]
+pub mod ios;
+#[cfg(target_os = "ios")]
+pub use ios as platform;
+
+#[cfg(target_os = "linux")]
+pub mod gtk;
+#[cfg(target_os = "linux")]
+pub use crate::gtk as platform;
+
+#[cfg(target_os = "android")]
+pub mod android;
+#[cfg(target_os = "android")]
+pub use android as platform;
+
+#[cfg(target_arch = "wasm32")]
+pub mod web;
+#[cfg(target_arch = "wasm32")]
+pub use web as platform;
+
 pub use error::Error;
 
 pub use platform::application;
 pub use platform::menu;
 pub use platform::util;
 pub use platform::win_main as runloop; // TODO: rename to "runloop"
+pub use platform::ChildView;
 pub use platform::WindowBuilder;
 pub use util::{get_locale, init};
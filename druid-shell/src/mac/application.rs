@@ -14,13 +14,111 @@
 
 //! macOS implementation of features at the application scope.
 
+use std::ffi::c_void;
+use std::path::Path;
+
+use super::menu::Menu;
 use super::util;
 use crate::clipboard::ClipboardItem;
 use cocoa::appkit::{NSApp, NSPasteboardTypeString};
-use cocoa::base::{id, nil, BOOL, YES};
+use cocoa::base::{id, nil, BOOL, NO, YES};
+use cocoa::foundation::{NSArray, NSInteger};
+use log::warn;
+use objc::declare::ClassDecl;
+use objc::runtime::{Class, Object, Sel};
+
+/// `NSCriticalRequest`, from `NSRequestUserAttentionType`.
+const NS_CRITICAL_REQUEST: NSInteger = 0;
+/// `NSInformationalRequest`, from `NSRequestUserAttentionType`.
+const NS_INFORMATIONAL_REQUEST: NSInteger = 10;
+
+// These take and return CoreFoundation references, which we treat as
+// opaque pointers.
+#[link(name = "CoreFoundation", kind = "framework")]
+extern "C" {
+    fn CFDataCreate(allocator: *const c_void, bytes: *const u8, length: isize) -> *const c_void;
+    fn CFRelease(cf: *const c_void);
+}
+
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGDataProviderCreateWithCFData(data: *const c_void) -> *const c_void;
+    fn CGFontCreateWithDataProvider(provider: *const c_void) -> *const c_void;
+}
+
+#[link(name = "CoreText", kind = "framework")]
+extern "C" {
+    fn CTFontManagerRegisterGraphicsFont(font: *const c_void, error: *mut *const c_void) -> u8;
+}
 
 pub struct Application;
 
+// Wrap pointer because lazy_static requires Sync.
+struct AppDelegateClass(*const Class);
+unsafe impl Sync for AppDelegateClass {}
+
+lazy_static! {
+    /// The application delegate, which currently only exists to provide
+    /// the dock menu.
+    static ref APP_DELEGATE_CLASS: AppDelegateClass = unsafe {
+        let mut decl = ClassDecl::new("DruidAppDelegate", class!(NSObject))
+            .expect("App Delegate definition failed");
+        decl.add_ivar::<*mut c_void>("dockMenu");
+
+        extern "C" fn application_dock_menu(this: &Object, _: Sel, _sender: id) -> id {
+            unsafe {
+                let menu: *mut c_void = *this.get_ivar("dockMenu");
+                menu as id
+            }
+        }
+        decl.add_method(
+            sel!(applicationDockMenu:),
+            application_dock_menu as extern "C" fn(&Object, Sel, id) -> id,
+        );
+        // Dock menu items are dispatched through the responder chain, which
+        // won't reach a window if the application is inactive; in that case
+        // they end up here, and we forward them to the main window's view.
+        decl.add_method(
+            sel!(handleMenuItem:),
+            handle_dock_menu_item as extern "C" fn(&mut Object, Sel, id),
+        );
+        AppDelegateClass(decl.register())
+    };
+}
+
+extern "C" fn handle_dock_menu_item(_this: &mut Object, _: Sel, item: id) {
+    unsafe {
+        let mut window: id = msg_send![NSApp(), mainWindow];
+        if window == nil {
+            let windows: id = msg_send![NSApp(), windows];
+            window = msg_send![windows, firstObject];
+        }
+        if window == nil {
+            warn!("dock menu item selected with no open windows");
+            return;
+        }
+        let content_view: id = msg_send![window, contentView];
+        let subviews: id = msg_send![content_view, subviews];
+        let view: id = msg_send![subviews, firstObject];
+        let handles: BOOL = msg_send![view, respondsToSelector: sel!(handleMenuItem:)];
+        if view != nil && handles == YES {
+            let () = msg_send![view, handleMenuItem: item];
+        }
+    }
+}
+
+/// Get the application delegate, installing it if necessary.
+unsafe fn app_delegate() -> id {
+    let delegate: id = msg_send![NSApp(), delegate];
+    if delegate != nil {
+        return delegate;
+    }
+    let delegate: id = msg_send![APP_DELEGATE_CLASS.0, new];
+    (*delegate).set_ivar("dockMenu", nil as *mut c_void);
+    let () = msg_send![NSApp(), setDelegate: delegate];
+    delegate
+}
+
 impl Application {
     pub fn quit() {
         unsafe {
@@ -44,6 +142,136 @@ impl Application {
         }
     }
 
+    /// Open a URL with the default handler for its scheme.
+    ///
+    /// For `http` and `https` URLs this is the user's web browser.
+    pub fn open_url(url: &str) {
+        unsafe {
+            let nsurl: id = msg_send![class!(NSURL), URLWithString: util::make_nsstring(url)];
+            if nsurl == nil {
+                warn!("failed to parse url '{}'", url);
+                return;
+            }
+            let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+            let success: BOOL = msg_send![workspace, openURL: nsurl];
+            if success != YES {
+                warn!("failed to open url '{}'", url);
+            }
+        }
+    }
+
+    /// Show a file or directory in the Finder, selecting it.
+    pub fn reveal_in_file_manager(path: &Path) {
+        unsafe {
+            let path = util::make_nsstring(&path.to_string_lossy());
+            let nsurl: id = msg_send![class!(NSURL), fileURLWithPath: path];
+            let urls = NSArray::arrayWithObjects(nil, &[nsurl]);
+            let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+            let () = msg_send![workspace, activateFileViewerSelectingURLs: urls];
+        }
+    }
+
+    /// Set the label shown in the badge on the application's dock icon,
+    /// such as a count of unread items.
+    ///
+    /// Passing `None` removes the badge.
+    pub fn set_badge_label(label: Option<&str>) {
+        unsafe {
+            let label = label.map(util::make_nsstring).unwrap_or(nil);
+            let dock_tile: id = msg_send![NSApp(), dockTile];
+            let () = msg_send![dock_tile, setBadgeLabel: label];
+        }
+    }
+
+    /// Bounce the dock icon to request the user's attention.
+    ///
+    /// If `critical` is `true`, the icon bounces until the application is
+    /// activated; otherwise it bounces once. Does nothing if the application
+    /// is already active.
+    pub fn request_user_attention(critical: bool) {
+        let kind = if critical {
+            NS_CRITICAL_REQUEST
+        } else {
+            NS_INFORMATIONAL_REQUEST
+        };
+        unsafe {
+            let _: NSInteger = msg_send![NSApp(), requestUserAttention: kind];
+        }
+    }
+
+    /// Set the menu shown when the user right-clicks the dock icon.
+    ///
+    /// Items are appended below the system's own entries, and are delivered
+    /// as commands to the main window.
+    pub fn set_dock_menu(menu: Option<Menu>) {
+        unsafe {
+            let delegate = app_delegate();
+            let is_ours: BOOL = msg_send![delegate, isKindOfClass: APP_DELEGATE_CLASS.0];
+            if is_ours == NO {
+                warn!("application delegate is not ours, can't set dock menu");
+                return;
+            }
+            let old: *mut c_void = *(*delegate).get_ivar("dockMenu");
+            if !old.is_null() {
+                let () = msg_send![old as id, release];
+            }
+            let new = match menu {
+                Some(menu) => {
+                    let () = msg_send![menu.menu, retain];
+                    menu.menu
+                }
+                None => nil,
+            };
+            (*delegate).set_ivar("dockMenu", new as *mut c_void);
+        }
+    }
+
+    /// Whether "Increase contrast" is turned on in the accessibility
+    /// display settings.
+    pub fn is_high_contrast() -> bool {
+        unsafe {
+            let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+            let increase: BOOL = msg_send![workspace, accessibilityDisplayShouldIncreaseContrast];
+            increase == YES
+        }
+    }
+
+    /// Whether "Reduce motion" is turned on in the accessibility display
+    /// settings.
+    pub fn prefers_reduced_motion() -> bool {
+        unsafe {
+            let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+            let reduce: BOOL = msg_send![workspace, accessibilityDisplayShouldReduceMotion];
+            reduce == YES
+        }
+    }
+
+    /// Make a font available to the application, from the contents of a
+    /// TrueType or OpenType file.
+    ///
+    /// The font can then be selected by its family name.
+    pub fn register_font(data: &[u8]) {
+        unsafe {
+            let cf_data = CFDataCreate(std::ptr::null(), data.as_ptr(), data.len() as isize);
+            let provider = CGDataProviderCreateWithCFData(cf_data);
+            CFRelease(cf_data);
+            let font = CGFontCreateWithDataProvider(provider);
+            CFRelease(provider);
+            if font.is_null() {
+                warn!("failed to register font: invalid font data");
+                return;
+            }
+            let mut error = std::ptr::null();
+            if CTFontManagerRegisterGraphicsFont(font, &mut error) == 0 {
+                warn!("failed to register font");
+                if !error.is_null() {
+                    CFRelease(error);
+                }
+            }
+            CFRelease(font);
+        }
+    }
+
     /// Returns the contents of the clipboard, if any.
     pub fn get_clipboard_contents() -> Option<ClipboardItem> {
         unsafe {
@@ -79,7 +307,7 @@ impl Application {
                     msg_send![pasteboard, clearContents];
                     msg_send![pasteboard, setString: nsstring forType: NSPasteboardTypeString];
                 }
-                other => log::warn!("unhandled clipboard data {:?}", other),
+                other => warn!("unhandled clipboard data {:?}", other),
             }
         }
     }
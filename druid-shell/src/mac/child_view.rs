@@ -0,0 +1,117 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Native child views, macOS implementation.
+
+use std::ffi::c_void;
+
+use cocoa::base::{id, nil, NO, YES};
+use cocoa::foundation::{NSPoint, NSRect, NSSize};
+use objc::rc::StrongPtr;
+use raw_window_handle::macos::MacOSHandle;
+use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+
+use crate::kurbo::Rect;
+use crate::Error;
+
+use super::WindowHandle;
+
+/// A view shown inside a druid window, either created by other code or
+/// created as a surface for GPU rendering.
+pub struct ChildView {
+    nsview: StrongPtr,
+}
+
+impl ChildView {
+    /// Wrap a view, which will become a subview of the druid view when
+    /// attached. The view is retained.
+    ///
+    /// # Safety
+    ///
+    /// The pointer must be a valid `NSView`.
+    pub unsafe fn from_nsview(nsview: id) -> ChildView {
+        ChildView {
+            nsview: StrongPtr::retain(nsview),
+        }
+    }
+
+    /// Create an empty, layer-backed view, for rendering into with a
+    /// graphics API such as Metal or OpenGL.
+    pub fn new_surface() -> Result<ChildView, Error> {
+        unsafe {
+            let frame = NSRect::new(NSPoint::new(0.0, 0.0), NSSize::new(1.0, 1.0));
+            let nsview: id = msg_send![class!(NSView), alloc];
+            let nsview: id = msg_send![nsview, initWithFrame: frame];
+            if nsview == nil {
+                return Err(Error::Null);
+            }
+            let () = msg_send![nsview, setWantsLayer: YES];
+            Ok(ChildView {
+                nsview: StrongPtr::new(nsview),
+            })
+        }
+    }
+
+    /// The wrapped view.
+    pub fn nsview(&self) -> id {
+        *self.nsview
+    }
+
+    /// Add the view to the window, initially hidden.
+    pub fn attach(&self, parent: &WindowHandle) {
+        if let Some(parent) = parent.nsview.as_ref() {
+            unsafe {
+                let () = msg_send![*self.nsview, setHidden: YES];
+                let () = msg_send![*parent.load(), addSubview: *self.nsview];
+            }
+        }
+    }
+
+    /// Set the area covered by the view, in px relative to the window.
+    pub fn set_frame(&self, frame: Rect) {
+        // Our view is flipped, so this is relative to the top left.
+        let frame = NSRect::new(
+            NSPoint::new(frame.x0, frame.y0),
+            NSSize::new(frame.width(), frame.height()),
+        );
+        unsafe {
+            let () = msg_send![*self.nsview, setFrame: frame];
+        }
+    }
+
+    pub fn set_visible(&self, visible: bool) {
+        let hidden = if visible { NO } else { YES };
+        unsafe {
+            let () = msg_send![*self.nsview, setHidden: hidden];
+        }
+    }
+
+    /// Remove the view from its window.
+    pub fn detach(&self) {
+        unsafe {
+            let () = msg_send![*self.nsview, removeFromSuperview];
+        }
+    }
+}
+
+unsafe impl HasRawWindowHandle for ChildView {
+    fn raw_window_handle(&self) -> RawWindowHandle {
+        let ns_window: id = unsafe { msg_send![*self.nsview, window] };
+        RawWindowHandle::MacOS(MacOSHandle {
+            ns_window: ns_window as *mut c_void,
+            ns_view: *self.nsview as *mut c_void,
+            ..MacOSHandle::empty()
+        })
+    }
+}
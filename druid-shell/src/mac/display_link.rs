@@ -0,0 +1,187 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Pacing animation frames with the display, using `CVDisplayLink`.
+
+#![allow(non_upper_case_globals)]
+
+use std::ffi::c_void;
+use std::ptr::null_mut;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use cocoa::base::{id, nil, NO};
+use cocoa::foundation::NSAutoreleasePool;
+use log::error;
+
+type CVDisplayLinkRef = *mut c_void;
+type CVReturn = i32;
+
+const kCVReturnSuccess: CVReturn = 0;
+
+#[repr(C)]
+struct CVSMPTETime {
+    subframes: i16,
+    subframe_divisor: i16,
+    counter: u32,
+    type_: u32,
+    flags: u32,
+    hours: i16,
+    minutes: i16,
+    seconds: i16,
+    frames: i16,
+}
+
+#[repr(C)]
+struct CVTimeStamp {
+    version: u32,
+    video_time_scale: i32,
+    video_time: i64,
+    host_time: u64,
+    rate_scalar: f64,
+    video_refresh_period: i64,
+    smpte_time: CVSMPTETime,
+    flags: u64,
+    reserved: u64,
+}
+
+type CVDisplayLinkOutputCallback = extern "C" fn(
+    CVDisplayLinkRef,
+    *const CVTimeStamp,
+    *const CVTimeStamp,
+    u64,
+    *mut u64,
+    *mut c_void,
+) -> CVReturn;
+
+#[link(name = "CoreVideo", kind = "framework")]
+extern "C" {
+    fn CVDisplayLinkCreateWithActiveCGDisplays(link: *mut CVDisplayLinkRef) -> CVReturn;
+    fn CVDisplayLinkSetOutputCallback(
+        link: CVDisplayLinkRef,
+        callback: CVDisplayLinkOutputCallback,
+        user_info: *mut c_void,
+    ) -> CVReturn;
+    fn CVDisplayLinkStart(link: CVDisplayLinkRef) -> CVReturn;
+    fn CVDisplayLinkStop(link: CVDisplayLinkRef) -> CVReturn;
+    fn CVDisplayLinkIsRunning(link: CVDisplayLinkRef) -> bool;
+    fn CVDisplayLinkRelease(link: CVDisplayLinkRef);
+    fn CVGetCurrentHostTime() -> u64;
+    fn CVGetHostClockFrequency() -> f64;
+}
+
+/// A display link that redraws a view once per display refresh, while started.
+pub(crate) struct DisplayLink {
+    link: CVDisplayLinkRef,
+    shared: Box<Shared>,
+}
+
+/// The state shared with the display link's thread.
+struct Shared {
+    view: id,
+    /// The host time at which the frame being drawn is expected to be shown.
+    output_time: AtomicU64,
+    /// Whether a redraw has been requested and not yet performed.
+    pending: AtomicBool,
+}
+
+impl DisplayLink {
+    /// Create a display link for the view, which must outlive it.
+    pub(crate) fn new(view: id) -> Option<DisplayLink> {
+        let mut shared = Box::new(Shared {
+            view,
+            output_time: AtomicU64::new(0),
+            pending: AtomicBool::new(false),
+        });
+        unsafe {
+            let mut link = null_mut();
+            if CVDisplayLinkCreateWithActiveCGDisplays(&mut link) != kCVReturnSuccess {
+                error!("failed to create display link");
+                return None;
+            }
+            let user_info = &mut *shared as *mut Shared as *mut c_void;
+            CVDisplayLinkSetOutputCallback(link, output_callback, user_info);
+            Some(DisplayLink { link, shared })
+        }
+    }
+
+    /// Start redrawing the view on each refresh, if not already started.
+    pub(crate) fn start(&self) {
+        unsafe {
+            if !CVDisplayLinkIsRunning(self.link) {
+                CVDisplayLinkStart(self.link);
+            }
+        }
+    }
+
+    /// Stop redrawing the view.
+    pub(crate) fn stop(&self) {
+        unsafe {
+            if CVDisplayLinkIsRunning(self.link) {
+                CVDisplayLinkStop(self.link);
+            }
+        }
+    }
+
+    /// Called when the view is drawn, to allow the next redraw request.
+    ///
+    /// Returns the time the frame is expected to be shown, if it was
+    /// requested by the display link.
+    pub(crate) fn begin_frame(&self) -> Option<Instant> {
+        if !self.shared.pending.swap(false, Ordering::AcqRel) {
+            return None;
+        }
+        let output = self.shared.output_time.load(Ordering::Acquire);
+        unsafe {
+            let now = CVGetCurrentHostTime();
+            let ahead = output.saturating_sub(now) as f64 / CVGetHostClockFrequency();
+            Some(Instant::now() + Duration::from_secs_f64(ahead))
+        }
+    }
+}
+
+impl Drop for DisplayLink {
+    fn drop(&mut self) {
+        unsafe {
+            // This waits for a callback in progress to return.
+            CVDisplayLinkStop(self.link);
+            CVDisplayLinkRelease(self.link);
+        }
+    }
+}
+
+extern "C" fn output_callback(
+    _link: CVDisplayLinkRef,
+    _now: *const CVTimeStamp,
+    output_time: *const CVTimeStamp,
+    _flags_in: u64,
+    _flags_out: *mut u64,
+    user_info: *mut c_void,
+) -> CVReturn {
+    unsafe {
+        let shared = &*(user_info as *const Shared);
+        // Skip refreshes while the last redraw is still waiting to happen.
+        if !shared.pending.load(Ordering::Acquire) {
+            shared
+                .output_time
+                .store((*output_time).host_time, Ordering::Release);
+            shared.pending.store(true, Ordering::Release);
+            let pool = NSAutoreleasePool::new(nil);
+            let () = msg_send![shared.view, performSelectorOnMainThread: sel!(redraw)
+                withObject: nil waitUntilDone: NO];
+            pool.drain();
+        }
+    }
+    kCVReturnSuccess
+}
@@ -0,0 +1,276 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Input method support, by way of `NSTextInputClient`.
+//!
+//! When the input method is enabled, key events are passed through
+//! `interpretKeyEvents:`, and the text input system calls back into the
+//! view with the methods below. Keys that don't produce text arrive at
+//! `doCommandBySelector:`, and plain typing arrives at `insertText:`;
+//! both of these are delivered to the handler as the key event that
+//! caused them, so that only composed text becomes an `ImeEvent`.
+
+use std::ffi::c_void;
+
+use cocoa::base::{id, nil, BOOL, NO, YES};
+use cocoa::foundation::{NSArray, NSInteger, NSPoint, NSRange, NSRect, NSSize, NSUInteger};
+use objc::declare::ClassDecl;
+use objc::runtime::{Object, Protocol, Sel};
+
+use crate::ime::{utf16_to_utf8_offset, ImeEvent};
+use crate::kurbo::Rect;
+use crate::window::Text;
+
+use super::util::from_nsstring;
+use super::{ViewState, WinCtxImpl};
+
+/// `NSNotFound`, used for ranges we can't describe.
+const NS_NOT_FOUND: NSUInteger = NSInteger::max_value() as NSUInteger;
+
+/// The IME state of a view.
+#[derive(Default)]
+pub(super) struct ImeState {
+    pub(super) enabled: bool,
+    /// The text being composed, if any.
+    marked_text: String,
+    /// The key event being interpreted, until the text input system
+    /// decides what it means.
+    pending_key: Option<crate::keyboard::KeyEvent>,
+    /// The area of the text cursor, in view coordinates.
+    pub(super) cursor_rect: Rect,
+}
+
+pub(super) fn add_methods(decl: &mut ClassDecl) {
+    if let Some(protocol) = Protocol::get("NSTextInputClient") {
+        decl.add_protocol(protocol);
+    }
+    unsafe {
+        decl.add_method(
+            sel!(hasMarkedText),
+            has_marked_text as extern "C" fn(&Object, Sel) -> BOOL,
+        );
+        decl.add_method(
+            sel!(markedRange),
+            marked_range as extern "C" fn(&Object, Sel) -> NSRange,
+        );
+        decl.add_method(
+            sel!(selectedRange),
+            selected_range as extern "C" fn(&Object, Sel) -> NSRange,
+        );
+        decl.add_method(
+            sel!(setMarkedText:selectedRange:replacementRange:),
+            set_marked_text as extern "C" fn(&mut Object, Sel, id, NSRange, NSRange),
+        );
+        decl.add_method(
+            sel!(unmarkText),
+            unmark_text as extern "C" fn(&mut Object, Sel),
+        );
+        decl.add_method(
+            sel!(validAttributesForMarkedText),
+            valid_attributes as extern "C" fn(&Object, Sel) -> id,
+        );
+        decl.add_method(
+            sel!(attributedSubstringForProposedRange:actualRange:),
+            attributed_substring as extern "C" fn(&Object, Sel, NSRange, *mut NSRange) -> id,
+        );
+        decl.add_method(
+            sel!(insertText:replacementRange:),
+            insert_text as extern "C" fn(&mut Object, Sel, id, NSRange),
+        );
+        decl.add_method(
+            sel!(characterIndexForPoint:),
+            character_index as extern "C" fn(&Object, Sel, NSPoint) -> NSUInteger,
+        );
+        decl.add_method(
+            sel!(firstRectForCharacterRange:actualRange:),
+            first_rect as extern "C" fn(&Object, Sel, NSRange, *mut NSRange) -> NSRect,
+        );
+        decl.add_method(
+            sel!(doCommandBySelector:),
+            do_command as extern "C" fn(&mut Object, Sel, Sel),
+        );
+    }
+}
+
+fn view_state(this: &Object) -> &mut ViewState {
+    unsafe {
+        let view_state: *mut c_void = *this.get_ivar("viewState");
+        &mut *(view_state as *mut ViewState)
+    }
+}
+
+/// Pass a key event to the text input system.
+///
+/// Returns `false` if the input method is disabled, in which case the
+/// caller should deliver the event itself.
+pub(super) fn interpret_key_event(this: &mut Object, nsevent: id) -> bool {
+    let event = super::make_key_event(nsevent);
+    let state = view_state(this);
+    if !state.ime.enabled {
+        return false;
+    }
+    state.ime.pending_key = Some(event);
+    unsafe {
+        let events = NSArray::arrayWithObject(nil, nsevent);
+        let () = msg_send![this, interpretKeyEvents: events];
+    }
+    // If nothing claimed the key, the input method consumed it.
+    view_state(this).ime.pending_key = None;
+    true
+}
+
+/// Drop any composition in progress, for instance when the input method
+/// is disabled.
+pub(super) fn discard_marked_text(view: id) {
+    unsafe {
+        let state = view_state(&*view);
+        if !state.ime.marked_text.is_empty() {
+            state.ime.marked_text.clear();
+            let input_context: id = msg_send![view, inputContext];
+            if input_context != nil {
+                let () = msg_send![input_context, discardMarkedText];
+            }
+        }
+    }
+}
+
+fn send_ime_event(state: &mut ViewState, event: ImeEvent) {
+    let mut ctx = WinCtxImpl {
+        nsview: &state.nsview,
+        text: Text::new(),
+    };
+    state.handler.ime(event, &mut ctx);
+}
+
+/// Get the text of an `NSString` or `NSAttributedString`.
+fn string_from(text: id) -> String {
+    unsafe {
+        let is_attributed: BOOL = msg_send![text, isKindOfClass: class!(NSAttributedString)];
+        let text: id = if is_attributed == YES {
+            msg_send![text, string]
+        } else {
+            text
+        };
+        from_nsstring(text)
+    }
+}
+
+extern "C" fn has_marked_text(this: &Object, _: Sel) -> BOOL {
+    if view_state(this).ime.marked_text.is_empty() {
+        NO
+    } else {
+        YES
+    }
+}
+
+extern "C" fn marked_range(this: &Object, _: Sel) -> NSRange {
+    let marked = &view_state(this).ime.marked_text;
+    if marked.is_empty() {
+        NSRange::new(NS_NOT_FOUND, 0)
+    } else {
+        let length = marked.encode_utf16().count() as NSUInteger;
+        NSRange::new(0, length)
+    }
+}
+
+extern "C" fn selected_range(_this: &Object, _: Sel) -> NSRange {
+    // We don't expose the document to the input method.
+    NSRange::new(NS_NOT_FOUND, 0)
+}
+
+extern "C" fn set_marked_text(
+    this: &mut Object,
+    _: Sel,
+    text: id,
+    selected: NSRange,
+    _replacement: NSRange,
+) {
+    let text = string_from(text);
+    let state = view_state(this);
+    state.ime.pending_key = None;
+    let cursor = if selected.location == NS_NOT_FOUND {
+        None
+    } else {
+        Some(utf16_to_utf8_offset(&text, selected.location as usize))
+    };
+    state.ime.marked_text = text.clone();
+    send_ime_event(state, ImeEvent::Preedit { text, cursor });
+}
+
+extern "C" fn unmark_text(this: &mut Object, _: Sel) {
+    let state = view_state(this);
+    if !state.ime.marked_text.is_empty() {
+        let text = std::mem::replace(&mut state.ime.marked_text, String::new());
+        send_ime_event(state, ImeEvent::Commit(text));
+    }
+}
+
+extern "C" fn valid_attributes(_this: &Object, _: Sel) -> id {
+    unsafe { NSArray::array(nil) }
+}
+
+extern "C" fn attributed_substring(
+    _this: &Object,
+    _: Sel,
+    _range: NSRange,
+    _actual: *mut NSRange,
+) -> id {
+    nil
+}
+
+extern "C" fn insert_text(this: &mut Object, _: Sel, text: id, _replacement: NSRange) {
+    let text = string_from(text);
+    let state = view_state(this);
+    let was_composing = !state.ime.marked_text.is_empty();
+    state.ime.marked_text.clear();
+    match state.ime.pending_key.take() {
+        // Plain typing goes through the normal key path.
+        Some(key) if !was_composing && key.text() == Some(text.as_str()) => {
+            let mut ctx = WinCtxImpl {
+                nsview: &state.nsview,
+                text: Text::new(),
+            };
+            state.handler.key_down(key, &mut ctx);
+        }
+        _ => send_ime_event(state, ImeEvent::Commit(text)),
+    }
+}
+
+extern "C" fn character_index(_this: &Object, _: Sel, _point: NSPoint) -> NSUInteger {
+    NS_NOT_FOUND
+}
+
+extern "C" fn first_rect(this: &Object, _: Sel, _range: NSRange, _actual: *mut NSRange) -> NSRect {
+    let rect = view_state(this).ime.cursor_rect;
+    let rect = NSRect::new(
+        NSPoint::new(rect.x0, rect.y0),
+        NSSize::new(rect.width(), rect.height()),
+    );
+    unsafe {
+        let in_window: NSRect = msg_send![this, convertRect: rect toView: nil];
+        let window: id = msg_send![this, window];
+        msg_send![window, convertRectToScreen: in_window]
+    }
+}
+
+extern "C" fn do_command(this: &mut Object, _: Sel, _command: Sel) {
+    let state = view_state(this);
+    if let Some(key) = state.ime.pending_key.take() {
+        let mut ctx = WinCtxImpl {
+            nsview: &state.nsview,
+            text: Text::new(),
+        };
+        state.handler.key_down(key, &mut ctx);
+    }
+}
@@ -16,16 +16,20 @@
 
 use crate::util::make_nsstring;
 use cocoa::appkit::{NSEventModifierFlags, NSMenu, NSMenuItem};
-use cocoa::base::{id, nil, NO};
-use cocoa::foundation::NSAutoreleasePool;
+use cocoa::base::{id, nil, NO, YES};
+use cocoa::foundation::{NSAutoreleasePool, NSInteger, NSSize};
 
 use crate::hotkey::{HotKey, KeyCompare};
-use crate::keyboard::{KeyCode, KeyModifiers};
+use crate::image::RgbaImage;
+use crate::keyboard::{KbKey, KeyCode, KeyModifiers};
 
 pub struct Menu {
     pub menu: id,
 }
 
+/// The height at which menu item icons are shown, in points.
+const ICON_HEIGHT: f64 = 16.0;
+
 /// Strip the access keys from the menu strong.
 ///
 /// Changes "E&xit" to "Exit". Actual ampersands are escaped as "&&".
@@ -47,7 +51,7 @@ fn strip_access_key(raw_menu_text: &str) -> String {
 }
 
 fn make_menu_item(id: u32, text: &str, key: Option<&HotKey>, enabled: bool, selected: bool) -> id {
-    let key_equivalent = key.map(HotKey::key_equivalent).unwrap_or("");
+    let key_equivalent = key.map(HotKey::key_equivalent).unwrap_or_default();
     let stripped_text = strip_access_key(text);
     unsafe {
         let item = NSMenuItem::alloc(nil)
@@ -121,6 +125,55 @@ impl Menu {
             self.menu.addItem_(sep);
         }
     }
+
+    /// Set the icon shown beside an item already added to this menu.
+    ///
+    /// The icon is scaled to 16 points high, so for high-resolution
+    /// displays it should be at least 32 pixels high.
+    pub fn set_item_icon(&mut self, id: u32, icon: &RgbaImage) {
+        if icon.width == 0 || icon.height == 0 {
+            return;
+        }
+        unsafe {
+            let item: id = msg_send![self.menu, itemWithTag: id as NSInteger];
+            if item != nil {
+                let () = msg_send![item, setImage: make_icon(icon)];
+            }
+        }
+    }
+}
+
+/// Create an autoreleased `NSImage` from an icon.
+unsafe fn make_icon(icon: &RgbaImage) -> id {
+    let rep: id = msg_send![class!(NSBitmapImageRep), alloc];
+    let rep: id = msg_send![rep,
+        initWithBitmapDataPlanes: std::ptr::null_mut::<*mut u8>()
+        pixelsWide: icon.width as NSInteger
+        pixelsHigh: icon.height as NSInteger
+        bitsPerSample: 8 as NSInteger
+        samplesPerPixel: 4 as NSInteger
+        hasAlpha: YES
+        isPlanar: NO
+        colorSpaceName: make_nsstring("NSDeviceRGBColorSpace")
+        bytesPerRow: (icon.width * 4) as NSInteger
+        bitsPerPixel: 32 as NSInteger];
+    // The representation expects premultiplied alpha.
+    let dst: *mut u8 = msg_send![rep, bitmapData];
+    let len = icon.pixels.len().min(icon.width * icon.height * 4);
+    let dst = std::slice::from_raw_parts_mut(dst, len);
+    for (dst, src) in dst.chunks_mut(4).zip(icon.pixels.chunks(4)) {
+        let alpha = src[3] as u32;
+        for i in 0..3 {
+            dst[i] = (src[i] as u32 * alpha / 255) as u8;
+        }
+        dst[3] = src[3];
+    }
+    let width = ICON_HEIGHT * icon.width as f64 / icon.height as f64;
+    let image: id = msg_send![class!(NSImage), alloc];
+    let image: id = msg_send![image, initWithSize: NSSize::new(width, ICON_HEIGHT)];
+    let () = msg_send![image, addRepresentation: rep];
+    let () = msg_send![rep, release];
+    image.autorelease()
 }
 
 impl HotKey {
@@ -128,53 +181,102 @@ impl HotKey {
     /// objects.
     ///
     /// Returns the empty string if no key equivalent is known.
-    fn key_equivalent(&self) -> &str {
-        match self.key {
-            KeyCompare::Text(t) => t,
-
-            // from NSText.h
-            KeyCompare::Code(KeyCode::Return) => "\u{0003}",
-            KeyCompare::Code(KeyCode::Backspace) => "\u{0008}",
-            KeyCompare::Code(KeyCode::Delete) => "\u{007f}",
-            // from NSEvent.h
-            KeyCompare::Code(KeyCode::Insert) => "\u{F727}",
-            KeyCompare::Code(KeyCode::Home) => "\u{F729}",
-            KeyCompare::Code(KeyCode::End) => "\u{F72B}",
-            KeyCompare::Code(KeyCode::PageUp) => "\u{F72C}",
-            KeyCompare::Code(KeyCode::PageDown) => "\u{F72D}",
-            KeyCompare::Code(KeyCode::PrintScreen) => "\u{F72E}",
-            KeyCompare::Code(KeyCode::ScrollLock) => "\u{F72F}",
-            KeyCompare::Code(KeyCode::ArrowUp) => "\u{F700}",
-            KeyCompare::Code(KeyCode::ArrowDown) => "\u{F701}",
-            KeyCompare::Code(KeyCode::ArrowLeft) => "\u{F702}",
-            KeyCompare::Code(KeyCode::ArrowRight) => "\u{F703}",
-            KeyCompare::Code(KeyCode::F1) => "\u{F704}",
-            KeyCompare::Code(KeyCode::F2) => "\u{F705}",
-            KeyCompare::Code(KeyCode::F3) => "\u{F706}",
-            KeyCompare::Code(KeyCode::F4) => "\u{F707}",
-            KeyCompare::Code(KeyCode::F5) => "\u{F708}",
-            KeyCompare::Code(KeyCode::F6) => "\u{F709}",
-            KeyCompare::Code(KeyCode::F7) => "\u{F70A}",
-            KeyCompare::Code(KeyCode::F8) => "\u{F70B}",
-            KeyCompare::Code(KeyCode::F9) => "\u{F70C}",
-            KeyCompare::Code(KeyCode::F10) => "\u{F70D}",
-            //KeyCompare::Code(KeyCode::F11)            => "\u{F70E}",
-            //KeyCompare::Code(KeyCode::F12)            => "\u{F70F}",
-            //KeyCompare::Code(KeyCode::F13)            => "\u{F710}",
-            //KeyCompare::Code(KeyCode::F14)            => "\u{F711}",
-            //KeyCompare::Code(KeyCode::F15)            => "\u{F712}",
-            //KeyCompare::Code(KeyCode::F16)            => "\u{F713}",
-            //KeyCompare::Code(KeyCode::F17)            => "\u{F714}",
-            //KeyCompare::Code(KeyCode::F18)            => "\u{F715}",
-            //KeyCompare::Code(KeyCode::F19)            => "\u{F716}",
-            //KeyCompare::Code(KeyCode::F20)            => "\u{F717}",
-            KeyCompare::Code(other) => {
-                eprintln!("no key equivalent for {:?}", other);
-                ""
-            }
+    fn key_equivalent(&self) -> String {
+        let code = match self.key {
+            KeyCompare::Text(t) => return t.into(),
+            KeyCompare::Key(KbKey::Character(c)) => return c.to_string(),
+            KeyCompare::Key(key) => match named_key_code(key) {
+                Some(code) => code,
+                None => {
+                    eprintln!("no key equivalent for {:?}", key);
+                    return String::new();
+                }
+            },
+            KeyCompare::Code(code) => code,
+        };
+        code_equivalent(code).into()
+    }
+}
+
+/// The Cocoa key equivalent for a key code.
+fn code_equivalent(code: KeyCode) -> &'static str {
+    match code {
+        // from NSText.h
+        KeyCode::Return => "\u{0003}",
+        KeyCode::Backspace => "\u{0008}",
+        KeyCode::Delete => "\u{007f}",
+        // from NSEvent.h
+        KeyCode::Insert => "\u{F727}",
+        KeyCode::Home => "\u{F729}",
+        KeyCode::End => "\u{F72B}",
+        KeyCode::PageUp => "\u{F72C}",
+        KeyCode::PageDown => "\u{F72D}",
+        KeyCode::PrintScreen => "\u{F72E}",
+        KeyCode::ScrollLock => "\u{F72F}",
+        KeyCode::ArrowUp => "\u{F700}",
+        KeyCode::ArrowDown => "\u{F701}",
+        KeyCode::ArrowLeft => "\u{F702}",
+        KeyCode::ArrowRight => "\u{F703}",
+        KeyCode::F1 => "\u{F704}",
+        KeyCode::F2 => "\u{F705}",
+        KeyCode::F3 => "\u{F706}",
+        KeyCode::F4 => "\u{F707}",
+        KeyCode::F5 => "\u{F708}",
+        KeyCode::F6 => "\u{F709}",
+        KeyCode::F7 => "\u{F70A}",
+        KeyCode::F8 => "\u{F70B}",
+        KeyCode::F9 => "\u{F70C}",
+        KeyCode::F10 => "\u{F70D}",
+        //KeyCode::F11            => "\u{F70E}",
+        //KeyCode::F12            => "\u{F70F}",
+        //KeyCode::F13            => "\u{F710}",
+        //KeyCode::F14            => "\u{F711}",
+        //KeyCode::F15            => "\u{F712}",
+        //KeyCode::F16            => "\u{F713}",
+        //KeyCode::F17            => "\u{F714}",
+        //KeyCode::F18            => "\u{F715}",
+        //KeyCode::F19            => "\u{F716}",
+        //KeyCode::F20            => "\u{F717}",
+        other => {
+            eprintln!("no key equivalent for {:?}", other);
+            ""
         }
     }
+}
 
+/// The key code used for the key equivalent of a named logical key.
+fn named_key_code(key: KbKey) -> Option<KeyCode> {
+    let code = match key {
+        KbKey::Enter => KeyCode::Return,
+        KbKey::Backspace => KeyCode::Backspace,
+        KbKey::Delete => KeyCode::Delete,
+        KbKey::Insert => KeyCode::Insert,
+        KbKey::Home => KeyCode::Home,
+        KbKey::End => KeyCode::End,
+        KbKey::PageUp => KeyCode::PageUp,
+        KbKey::PageDown => KeyCode::PageDown,
+        KbKey::PrintScreen => KeyCode::PrintScreen,
+        KbKey::ScrollLock => KeyCode::ScrollLock,
+        KbKey::ArrowUp => KeyCode::ArrowUp,
+        KbKey::ArrowDown => KeyCode::ArrowDown,
+        KbKey::ArrowLeft => KeyCode::ArrowLeft,
+        KbKey::ArrowRight => KeyCode::ArrowRight,
+        KbKey::F1 => KeyCode::F1,
+        KbKey::F2 => KeyCode::F2,
+        KbKey::F3 => KeyCode::F3,
+        KbKey::F4 => KeyCode::F4,
+        KbKey::F5 => KeyCode::F5,
+        KbKey::F6 => KeyCode::F6,
+        KbKey::F7 => KeyCode::F7,
+        KbKey::F8 => KeyCode::F8,
+        KbKey::F9 => KeyCode::F9,
+        KbKey::F10 => KeyCode::F10,
+        _ => return None,
+    };
+    Some(code)
+}
+
+impl HotKey {
     fn key_modifier_mask(&self) -> NSEventModifierFlags {
         let mods: KeyModifiers = self.mods.into();
         let mut flags = NSEventModifierFlags::empty();
@@ -16,18 +16,29 @@
 #![allow(non_snake_case)]
 
 pub mod application;
+mod child_view;
 pub mod dialog;
+mod display_link;
+mod ime;
 pub mod menu;
 pub mod util;
+#[cfg(feature = "video")]
+mod video;
+#[cfg(feature = "webview")]
+mod webview;
 pub mod win_main;
 
+pub use child_view::ChildView;
 use cocoa::appkit::{
     NSApp, NSApplication, NSApplicationActivateIgnoringOtherApps, NSAutoresizingMaskOptions,
-    NSBackingStoreBuffered, NSEvent, NSEventModifierFlags, NSRunningApplication, NSView,
-    NSViewHeightSizable, NSViewWidthSizable, NSWindow, NSWindowStyleMask,
+    NSBackingStoreBuffered, NSEvent, NSEventModifierFlags, NSFilenamesPboardType,
+    NSPasteboardTypeString, NSRunningApplication, NSView, NSViewHeightSizable, NSViewWidthSizable,
+    NSWindow, NSWindowStyleMask,
 };
 use cocoa::base::{id, nil, BOOL, NO, YES};
-use cocoa::foundation::{NSAutoreleasePool, NSPoint, NSRect, NSSize, NSString};
+use cocoa::foundation::{
+    NSArray, NSAutoreleasePool, NSInteger, NSPoint, NSRect, NSSize, NSString, NSUInteger,
+};
 pub use menu::Menu;
 use objc::declare::ClassDecl;
 use objc::rc::WeakPtr;
@@ -36,30 +47,45 @@ use std::any::Any;
 use std::ffi::c_void;
 use std::ffi::OsString;
 use std::mem;
+use std::ptr::null_mut;
 use std::sync::{Arc, Mutex, Weak};
 use std::time::Instant;
+#[cfg(feature = "video")]
+pub use video::VideoDecoder;
+#[cfg(feature = "webview")]
+pub use webview::WebView;
 
-use log::{error, info};
+use log::{error, info, warn};
 
 use cairo::{Context, QuartzSurface};
 
-use crate::kurbo::{Point, Vec2};
+use crate::kurbo::{Point, Rect, Vec2};
 use piet_common::{Piet, RenderContext};
+use raw_window_handle::macos::MacOSHandle;
+use raw_window_handle::RawWindowHandle;
 
 use crate::clipboard::ClipboardItem;
 use crate::dialog::{FileDialogOptions, FileDialogType};
+use crate::dnd::{DragContents, DragData, DragEvent, DragImage, DropEffect};
 use crate::keyboard::{KeyCode, KeyEvent, KeyModifiers};
 use crate::platform::application::Application;
-use crate::util::make_nsstring;
+use crate::print::{PageSetup, Printable};
+use crate::util::{from_nsstring, make_nsstring};
 use crate::window::{
-    Cursor, FileInfo, MouseButton, MouseEvent, Text, TimerToken, WinCtx, WinHandler,
+    Cursor, FileInfo, IdleToken, MouseButton, MouseEvent, PenEvent, PenEventKind, ScrollDevice,
+    ScrollPhase, Text, TimerToken, WheelEvent, WinCtx, WinHandler,
 };
 use crate::Error;
 
+use display_link::DisplayLink;
 use util::assert_main_thread;
 
 #[allow(non_upper_case_globals)]
 const NSWindowDidBecomeKeyNotification: &str = "NSWindowDidBecomeKeyNotification";
+#[allow(non_upper_case_globals)]
+const NSWindowWillCloseNotification: &str = "NSWindowWillCloseNotification";
+#[allow(non_upper_case_globals)]
+const NSWindowTabbingModePreferred: NSInteger = 1;
 
 #[derive(Clone, Default)]
 pub struct WindowHandle {
@@ -69,7 +95,9 @@ pub struct WindowHandle {
     /// TODO: remove option (issue has been filed against objc, or we could manually impl default with nil)
     /// https://github.com/SSheldon/rust-objc/issues/77
     nsview: Option<WeakPtr>,
-    idle_queue: Weak<Mutex<Vec<Box<dyn IdleCallback>>>>,
+    idle_queue: Weak<Mutex<Vec<IdleKind>>>,
+    /// Whether the view is embedded in a view we don't own.
+    embedded: bool,
 }
 
 /// Builder abstraction for creating new windows.
@@ -78,12 +106,16 @@ pub struct WindowBuilder {
     title: String,
     enable_mouse_move_events: bool,
     menu: Option<Menu>,
+    /// The view to embed this window's view in, if any.
+    parent: Option<id>,
+    /// Windows with the same identifier are shown as tabs in one window.
+    tabbing_identifier: Option<String>,
 }
 
 #[derive(Clone)]
 pub struct IdleHandle {
     nsview: WeakPtr,
-    idle_queue: Weak<Mutex<Vec<Box<dyn IdleCallback>>>>,
+    idle_queue: Weak<Mutex<Vec<IdleKind>>>,
 }
 
 // TODO: move this out of platform-dependent section.
@@ -96,12 +128,30 @@ impl<F: FnOnce(&dyn Any) + Send> IdleCallback for F {
         (*self)(a)
     }
 }
+
+/// An entry in a window's idle queue.
+enum IdleKind {
+    Callback(Box<dyn IdleCallback>),
+    Token(IdleToken),
+}
+
 /// This is the state associated with our custom NSView.
 struct ViewState {
     nsview: WeakPtr,
     handler: Box<dyn WinHandler>,
-    idle_queue: Arc<Mutex<Vec<Box<dyn IdleCallback>>>>,
+    idle_queue: Arc<Mutex<Vec<IdleKind>>>,
     last_mods: KeyModifiers,
+    /// The `NSDragOperation` mask for a drag we initiated.
+    drag_operations: NSUInteger,
+    ime: ime::ImeState,
+    /// Whether the pen in proximity of the tablet is an eraser.
+    pen_is_eraser: bool,
+    /// Whether animation frames are drawn in step with the display.
+    vsync: bool,
+    /// Drives animation frames while the view is animating, if `vsync` is on.
+    display_link: Option<DisplayLink>,
+    /// The time the frame being drawn is expected to be shown, if known.
+    presentation_time: Option<Instant>,
 }
 
 struct WinCtxImpl<'a> {
@@ -116,6 +166,8 @@ impl WindowBuilder {
             title: String::new(),
             enable_mouse_move_events: true,
             menu: None,
+            parent: None,
+            tabbing_identifier: None,
         }
     }
 
@@ -135,41 +187,43 @@ impl WindowBuilder {
         self.enable_mouse_move_events = to;
     }
 
+    /// Embed the window's view in a view owned by another framework,
+    /// filling it.
+    ///
+    /// No window is created, and the menu is not installed.
+    pub fn set_parent(&mut self, parent: RawWindowHandle) {
+        match parent {
+            RawWindowHandle::MacOS(handle) if !handle.ns_view.is_null() => {
+                self.parent = Some(handle.ns_view as id)
+            }
+            _ => warn!("unsupported parent window handle: {:?}", parent),
+        }
+    }
+
+    /// Show the window as a tab, alongside the other windows with the same
+    /// identifier.
+    pub fn set_tabbing_identifier(&mut self, identifier: impl Into<String>) {
+        self.tabbing_identifier = Some(identifier.into());
+    }
+
     pub fn build(self) -> Result<WindowHandle, Error> {
         assert_main_thread();
         unsafe {
-            let style_mask = NSWindowStyleMask::NSTitledWindowMask
-                | NSWindowStyleMask::NSClosableWindowMask
-                | NSWindowStyleMask::NSMiniaturizableWindowMask
-                | NSWindowStyleMask::NSResizableWindowMask;
-            let rect = NSRect::new(NSPoint::new(0., 0.), NSSize::new(500., 400.));
-
-            let window = NSWindow::alloc(nil).initWithContentRect_styleMask_backing_defer_(
-                rect,
-                style_mask,
-                NSBackingStoreBuffered,
-                NO,
-            );
-
-            window.cascadeTopLeftFromPoint_(NSPoint::new(20.0, 20.0));
-            window.setTitle_(make_nsstring(&self.title));
-            // TODO: this should probably be a tracking area instead
-            window.setAcceptsMouseMovedEvents_(YES);
+            let content_view = match self.parent {
+                Some(parent) => parent,
+                None => self.build_window(),
+            };
 
             let (view, idle_queue) = make_view(self.handler.expect("view"));
-            let content_view = window.contentView();
-            let frame = NSView::frame(content_view);
+            let frame: NSRect = msg_send![content_view, bounds];
             view.initWithFrame_(frame);
-            match self.menu {
-                Some(menu) => NSApp().setMainMenu_(menu.menu),
-                _ => (),
-            }
             content_view.addSubview_(view);
             let view_state: *mut c_void = *(*view).get_ivar("viewState");
             let view_state = &mut *(view_state as *mut ViewState);
             let handle = WindowHandle {
                 nsview: Some(view_state.nsview.clone()),
                 idle_queue,
+                embedded: self.parent.is_some(),
             };
             (*view_state).handler.connect(&crate::window::WindowHandle {
                 inner: handle.clone(),
@@ -185,6 +239,37 @@ impl WindowBuilder {
             Ok(handle)
         }
     }
+
+    /// Create the NSWindow, returning its content view.
+    unsafe fn build_window(&self) -> id {
+        let style_mask = NSWindowStyleMask::NSTitledWindowMask
+            | NSWindowStyleMask::NSClosableWindowMask
+            | NSWindowStyleMask::NSMiniaturizableWindowMask
+            | NSWindowStyleMask::NSResizableWindowMask;
+        let rect = NSRect::new(NSPoint::new(0., 0.), NSSize::new(500., 400.));
+
+        let window = NSWindow::alloc(nil).initWithContentRect_styleMask_backing_defer_(
+            rect,
+            style_mask,
+            NSBackingStoreBuffered,
+            NO,
+        );
+
+        window.cascadeTopLeftFromPoint_(NSPoint::new(20.0, 20.0));
+        window.setTitle_(make_nsstring(&self.title));
+        // TODO: this should probably be a tracking area instead
+        window.setAcceptsMouseMovedEvents_(YES);
+        if let Some(identifier) = self.tabbing_identifier.as_ref() {
+            let () = msg_send![window, setTabbingIdentifier: make_nsstring(identifier)];
+            let () = msg_send![window, setTabbingMode: NSWindowTabbingModePreferred];
+        }
+
+        match self.menu {
+            Some(ref menu) => NSApp().setMainMenu_(menu.menu),
+            _ => (),
+        }
+        window.contentView()
+    }
 }
 
 // Wrap pointer because lazy_static requires Sync.
@@ -223,6 +308,14 @@ lazy_static! {
             sel!(windowDidBecomeKey:),
             window_did_become_key as extern "C" fn(&mut Object, Sel, id),
         );
+        decl.add_method(
+            sel!(windowWillClose:),
+            window_will_close as extern "C" fn(&mut Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(accessibilityDisplayOptionsDidChange:),
+            accessibility_display_options_did_change as extern "C" fn(&mut Object, Sel, id),
+        );
         decl.add_method(
             sel!(setFrameSize:),
             set_frame_size as extern "C" fn(&mut Object, Sel, NSSize),
@@ -251,6 +344,10 @@ lazy_static! {
             sel!(mouseDragged:),
             mouse_move as extern "C" fn(&mut Object, Sel, id),
         );
+        decl.add_method(
+            sel!(tabletProximity:),
+            tablet_proximity as extern "C" fn(&mut Object, Sel, id),
+        );
         decl.add_method(
             sel!(scrollWheel:),
             scroll_wheel as extern "C" fn(&mut Object, Sel, id),
@@ -268,6 +365,30 @@ lazy_static! {
             sel!(drawRect:),
             draw_rect as extern "C" fn(&mut Object, Sel, NSRect),
         );
+        decl.add_method(
+            sel!(draggingEntered:),
+            dragging_entered as extern "C" fn(&mut Object, Sel, id) -> NSUInteger,
+        );
+        decl.add_method(
+            sel!(draggingUpdated:),
+            dragging_updated as extern "C" fn(&mut Object, Sel, id) -> NSUInteger,
+        );
+        decl.add_method(
+            sel!(draggingExited:),
+            dragging_exited as extern "C" fn(&mut Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(performDragOperation:),
+            perform_drag_operation as extern "C" fn(&mut Object, Sel, id) -> BOOL,
+        );
+        decl.add_method(
+            sel!(draggingSession:sourceOperationMaskForDraggingContext:),
+            source_operation_mask as extern "C" fn(&mut Object, Sel, id, NSInteger) -> NSUInteger,
+        );
+        decl.add_method(
+            sel!(draggingSession:endedAtPoint:operation:),
+            dragging_session_ended as extern "C" fn(&mut Object, Sel, id, NSPoint, NSUInteger),
+        );
         decl.add_method(sel!(runIdle), run_idle as extern "C" fn(&mut Object, Sel));
         decl.add_method(sel!(redraw), redraw as extern "C" fn(&mut Object, Sel));
         decl.add_method(
@@ -282,11 +403,12 @@ lazy_static! {
             sel!(dispatchMenuItem:),
             dispatch_menu_item as extern "C" fn(&mut Object, Sel, id),
         );
+        ime::add_methods(&mut decl);
         ViewClass(decl.register())
     };
 }
 
-fn make_view(handler: Box<dyn WinHandler>) -> (id, Weak<Mutex<Vec<Box<dyn IdleCallback>>>>) {
+fn make_view(handler: Box<dyn WinHandler>) -> (id, Weak<Mutex<Vec<IdleKind>>>) {
     let idle_queue = Arc::new(Mutex::new(Vec::new()));
     let queue_handle = Arc::downgrade(&idle_queue);
     unsafe {
@@ -297,11 +419,29 @@ fn make_view(handler: Box<dyn WinHandler>) -> (id, Weak<Mutex<Vec<Box<dyn IdleCa
             handler,
             idle_queue,
             last_mods: KeyModifiers::default(),
+            drag_operations: 0,
+            ime: Default::default(),
+            pen_is_eraser: false,
+            vsync: true,
+            display_link: None,
+            presentation_time: None,
         };
         let state_ptr = Box::into_raw(Box::new(state));
         (*view).set_ivar("viewState", state_ptr as *mut c_void);
         let options: NSAutoresizingMaskOptions = NSViewWidthSizable | NSViewHeightSizable;
         view.setAutoresizingMask_(options);
+        let drag_types =
+            NSArray::arrayWithObjects(nil, &[NSFilenamesPboardType, NSPasteboardTypeString]);
+        let () = msg_send![view, registerForDraggedTypes: drag_types];
+
+        // This is posted to the workspace's notification center, rather than
+        // the default one.
+        let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+        let notif_center: id = msg_send![workspace, notificationCenter];
+        let notif_string = NSString::alloc(nil)
+            .init_str("NSWorkspaceAccessibilityDisplayOptionsDidChangeNotification")
+            .autorelease();
+        let () = msg_send![notif_center, addObserver: view selector: sel!(accessibilityDisplayOptionsDidChange:) name: notif_string object: nil];
         (view.autorelease(), queue_handle)
     }
 }
@@ -379,6 +519,16 @@ fn mouse_down(this: &mut Object, nsevent: id, button: MouseButton) {
             nsview: &(*view_state).nsview,
             text: Text::new(),
         };
+        let is_eraser = (*view_state).pen_is_eraser;
+        let handler = &mut *(*view_state).handler;
+        send_pen_event(
+            handler,
+            is_eraser,
+            nsevent,
+            this as id,
+            PenEventKind::Down,
+            &mut ctx,
+        );
         (*view_state).handler.mouse_down(&event, &mut ctx);
     }
 }
@@ -400,6 +550,16 @@ fn mouse_up(this: &mut Object, nsevent: id, button: MouseButton) {
             nsview: &(*view_state).nsview,
             text: Text::new(),
         };
+        let is_eraser = (*view_state).pen_is_eraser;
+        let handler = &mut *(*view_state).handler;
+        send_pen_event(
+            handler,
+            is_eraser,
+            nsevent,
+            this as id,
+            PenEventKind::Up,
+            &mut ctx,
+        );
         (*view_state).handler.mouse_up(&event, &mut ctx);
     }
 }
@@ -413,18 +573,73 @@ extern "C" fn mouse_move(this: &mut Object, _: Sel, nsevent: id) {
             nsview: &(*view_state).nsview,
             text: Text::new(),
         };
+        let is_eraser = (*view_state).pen_is_eraser;
+        let handler = &mut *(*view_state).handler;
+        send_pen_event(
+            handler,
+            is_eraser,
+            nsevent,
+            this as id,
+            PenEventKind::Move,
+            &mut ctx,
+        );
         (*view_state).handler.mouse_move(&event, &mut ctx);
     }
 }
 
+extern "C" fn tablet_proximity(this: &mut Object, _: Sel, nsevent: id) {
+    // NSPointingDeviceType
+    const NS_ERASER_POINTING_DEVICE: NSUInteger = 3;
+    unsafe {
+        let view_state: *mut c_void = *this.get_ivar("viewState");
+        let view_state = &mut *(view_state as *mut ViewState);
+        let device_type: NSUInteger = msg_send![nsevent, pointingDeviceType];
+        (*view_state).pen_is_eraser = device_type == NS_ERASER_POINTING_DEVICE;
+    }
+}
+
+/// If a mouse event comes from a tablet, send the pen state to the handler.
+///
+/// This is called before the mouse event itself is delivered.
+unsafe fn send_pen_event(
+    handler: &mut dyn WinHandler,
+    is_eraser: bool,
+    nsevent: id,
+    view: id,
+    kind: PenEventKind,
+    ctx: &mut WinCtxImpl,
+) {
+    // NSEventSubtype
+    const NS_TABLET_POINT_EVENT_SUBTYPE: i16 = 1;
+    let subtype: i16 = msg_send![nsevent, subtype];
+    if subtype != NS_TABLET_POINT_EVENT_SUBTYPE {
+        return;
+    }
+    let point = nsevent.locationInWindow();
+    let view_point = view.convertPoint_fromView_(point, nil);
+    let pressure: f32 = msg_send![nsevent, pressure];
+    // The tilt is reported from -1 to 1 on each axis, with y pointing up.
+    let tilt: NSPoint = msg_send![nsevent, tilt];
+    let event = PenEvent {
+        pos: Point::new(view_point.x as f64, view_point.y as f64),
+        mods: make_modifiers(nsevent.modifierFlags()),
+        kind,
+        pressure: f64::from(pressure),
+        tilt: Vec2::new(tilt.x as f64 * 90.0, -tilt.y as f64 * 90.0),
+        is_eraser,
+    };
+    handler.pen(&event, ctx);
+}
+
 extern "C" fn scroll_wheel(this: &mut Object, _: Sel, nsevent: id) {
     unsafe {
         let view_state: *mut c_void = *this.get_ivar("viewState");
         let view_state = &mut *(view_state as *mut ViewState);
+        let precise = nsevent.hasPreciseScrollingDeltas() == cocoa::base::YES;
         let (dx, dy) = {
             let dx = -nsevent.scrollingDeltaX() as f64;
             let dy = -nsevent.scrollingDeltaY() as f64;
-            if nsevent.hasPreciseScrollingDeltas() == cocoa::base::YES {
+            if precise {
                 (dx, dy)
             } else {
                 (dx * 32.0, dy * 32.0)
@@ -433,27 +648,64 @@ extern "C" fn scroll_wheel(this: &mut Object, _: Sel, nsevent: id) {
         let mods = nsevent.modifierFlags();
         let mods = make_modifiers(mods);
 
-        let delta = Vec2::new(dx, dy);
+        let phase: NSUInteger = msg_send![nsevent, phase];
+        let momentum_phase: NSUInteger = msg_send![nsevent, momentumPhase];
+        let event = WheelEvent {
+            delta: Vec2::new(dx, dy),
+            mods,
+            phase: scroll_phase(phase, momentum_phase),
+            device: if precise {
+                ScrollDevice::Trackpad
+            } else {
+                ScrollDevice::Wheel
+            },
+        };
         let mut ctx = WinCtxImpl {
             nsview: &(*view_state).nsview,
             text: Text::new(),
         };
-        (*view_state).handler.wheel(delta, mods, &mut ctx);
+        (*view_state).handler.wheel(&event, &mut ctx);
+    }
+}
+
+/// Convert the `phase` and `momentumPhase` of a scroll wheel `NSEvent`.
+fn scroll_phase(phase: NSUInteger, momentum_phase: NSUInteger) -> ScrollPhase {
+    // NSEventPhase values
+    const BEGAN: NSUInteger = 0x1;
+    const CHANGED: NSUInteger = 0x4;
+    const ENDED: NSUInteger = 0x8;
+    const CANCELLED: NSUInteger = 0x10;
+
+    if momentum_phase & (ENDED | CANCELLED) != 0 {
+        ScrollPhase::MomentumEnded
+    } else if momentum_phase != 0 {
+        ScrollPhase::Momentum
+    } else if phase & BEGAN != 0 {
+        ScrollPhase::Began
+    } else if phase & CHANGED != 0 {
+        ScrollPhase::Changed
+    } else if phase & (ENDED | CANCELLED) != 0 {
+        ScrollPhase::Ended
+    } else {
+        ScrollPhase::None
     }
 }
 
 extern "C" fn key_down(this: &mut Object, _: Sel, nsevent: id) {
     let event = make_key_event(nsevent);
+    let interpreted = ime::interpret_key_event(this, nsevent);
 
     let view_state = unsafe {
         let view_state: *mut c_void = *this.get_ivar("viewState");
         &mut *(view_state as *mut ViewState)
     };
-    let mut ctx = WinCtxImpl {
-        nsview: &(*view_state).nsview,
-        text: Text::new(),
-    };
-    (*view_state).handler.key_down(event, &mut ctx);
+    if !interpreted {
+        let mut ctx = WinCtxImpl {
+            nsview: &(*view_state).nsview,
+            text: Text::new(),
+        };
+        (*view_state).handler.key_down(event, &mut ctx);
+    }
     view_state.last_mods = event.mods;
 }
 
@@ -507,6 +759,10 @@ extern "C" fn draw_rect(this: &mut Object, _: Sel, dirtyRect: NSRect) {
         let mut piet_ctx = Piet::new(&mut cairo_ctx);
         let view_state: *mut c_void = *this.get_ivar("viewState");
         let view_state = &mut *(view_state as *mut ViewState);
+        (*view_state).presentation_time = (*view_state)
+            .display_link
+            .as_ref()
+            .and_then(|link| link.begin_frame());
         let mut ctx = WinCtxImpl {
             nsview: &(*view_state).nsview,
             text: Text::new(),
@@ -516,8 +772,19 @@ extern "C" fn draw_rect(this: &mut Object, _: Sel, dirtyRect: NSRect) {
             error!("{}", e)
         }
 
-        if anim {
-            // TODO: synchronize with screen refresh rate using CVDisplayLink instead.
+        // While animating, the display link requests each frame, if vsync is on.
+        if anim && (*view_state).vsync && (*view_state).display_link.is_none() {
+            (*view_state).display_link = DisplayLink::new(this as *mut _);
+        }
+        let paced = anim && (*view_state).vsync && (*view_state).display_link.is_some();
+        if let Some(link) = (*view_state).display_link.as_ref() {
+            if paced {
+                link.start();
+            } else {
+                link.stop();
+            }
+        }
+        if anim && !paced {
             let () = msg_send!(this as *const _, performSelectorOnMainThread: sel!(redraw)
                 withObject: nil waitUntilDone: NO);
         }
@@ -527,6 +794,222 @@ extern "C" fn draw_rect(this: &mut Object, _: Sel, dirtyRect: NSRect) {
     }
 }
 
+/// Extract the location and contents of an `NSDraggingInfo`.
+fn drag_event(sender: id, view: id) -> DragEvent {
+    unsafe {
+        let point: NSPoint = msg_send![sender, draggingLocation];
+        let view_point = view.convertPoint_fromView_(point, nil);
+        let pos = Point::new(view_point.x as f64, view_point.y as f64);
+        let mods: NSEventModifierFlags = msg_send![class!(NSEvent), modifierFlags];
+        let mods = make_modifiers(mods);
+
+        let pasteboard: id = msg_send![sender, draggingPasteboard];
+        let mut data = DragData::default();
+        let filenames: id = msg_send![pasteboard, propertyListForType: NSFilenamesPboardType];
+        if filenames != nil {
+            let count: usize = msg_send![filenames, count];
+            for i in 0..count {
+                let path: id = msg_send![filenames, objectAtIndex: i];
+                data.files.push(from_nsstring(path).into());
+            }
+        }
+        let text: id = msg_send![pasteboard, stringForType: NSPasteboardTypeString];
+        if text != nil {
+            data.text = Some(from_nsstring(text));
+        }
+        DragEvent { pos, mods, data }
+    }
+}
+
+/// Convert a `DropEffect` to the corresponding `NSDragOperation`.
+fn drag_operation(effect: DropEffect) -> NSUInteger {
+    match effect {
+        DropEffect::None => 0,
+        DropEffect::Copy => 1,
+        DropEffect::Link => 2,
+        DropEffect::Move => 16,
+    }
+}
+
+extern "C" fn dragging_entered(this: &mut Object, _: Sel, sender: id) -> NSUInteger {
+    unsafe {
+        let view_state: *mut c_void = *this.get_ivar("viewState");
+        let view_state = &mut *(view_state as *mut ViewState);
+        let event = drag_event(sender, this as id);
+        let mut ctx = WinCtxImpl {
+            nsview: &(*view_state).nsview,
+            text: Text::new(),
+        };
+        drag_operation((*view_state).handler.drag_enter(&event, &mut ctx))
+    }
+}
+
+extern "C" fn dragging_updated(this: &mut Object, _: Sel, sender: id) -> NSUInteger {
+    unsafe {
+        let view_state: *mut c_void = *this.get_ivar("viewState");
+        let view_state = &mut *(view_state as *mut ViewState);
+        let event = drag_event(sender, this as id);
+        let mut ctx = WinCtxImpl {
+            nsview: &(*view_state).nsview,
+            text: Text::new(),
+        };
+        drag_operation((*view_state).handler.drag_over(&event, &mut ctx))
+    }
+}
+
+extern "C" fn dragging_exited(this: &mut Object, _: Sel, _sender: id) {
+    unsafe {
+        let view_state: *mut c_void = *this.get_ivar("viewState");
+        let view_state = &mut *(view_state as *mut ViewState);
+        let mut ctx = WinCtxImpl {
+            nsview: &(*view_state).nsview,
+            text: Text::new(),
+        };
+        (*view_state).handler.drag_leave(&mut ctx);
+    }
+}
+
+extern "C" fn perform_drag_operation(this: &mut Object, _: Sel, sender: id) -> BOOL {
+    unsafe {
+        let view_state: *mut c_void = *this.get_ivar("viewState");
+        let view_state = &mut *(view_state as *mut ViewState);
+        let event = drag_event(sender, this as id);
+        let mut ctx = WinCtxImpl {
+            nsview: &(*view_state).nsview,
+            text: Text::new(),
+        };
+        if (*view_state).handler.drag_drop(&event, &mut ctx) {
+            YES
+        } else {
+            NO
+        }
+    }
+}
+
+extern "C" fn source_operation_mask(
+    this: &mut Object,
+    _: Sel,
+    _session: id,
+    _context: NSInteger,
+) -> NSUInteger {
+    unsafe {
+        let view_state: *mut c_void = *this.get_ivar("viewState");
+        let view_state = &mut *(view_state as *mut ViewState);
+        view_state.drag_operations
+    }
+}
+
+extern "C" fn dragging_session_ended(
+    this: &mut Object,
+    _: Sel,
+    _session: id,
+    _point: NSPoint,
+    operation: NSUInteger,
+) {
+    unsafe {
+        let view_state: *mut c_void = *this.get_ivar("viewState");
+        let view_state = &mut *(view_state as *mut ViewState);
+        view_state.drag_operations = 0;
+        let effect = match operation {
+            0 => DropEffect::None,
+            op if op & 16 != 0 => DropEffect::Move,
+            op if op & 2 != 0 => DropEffect::Link,
+            _ => DropEffect::Copy,
+        };
+        let mut ctx = WinCtxImpl {
+            nsview: &(*view_state).nsview,
+            text: Text::new(),
+        };
+        (*view_state).handler.drag_end(effect, &mut ctx);
+    }
+}
+
+/// Create an `NSImage` from premultiplied RGBA pixels.
+unsafe fn make_drag_image(image: &DragImage) -> id {
+    let rep: id = msg_send![class!(NSBitmapImageRep), alloc];
+    let rep: id = msg_send![rep,
+        initWithBitmapDataPlanes: std::ptr::null_mut::<*mut u8>()
+        pixelsWide: image.width as NSInteger
+        pixelsHigh: image.height as NSInteger
+        bitsPerSample: 8 as NSInteger
+        samplesPerPixel: 4 as NSInteger
+        hasAlpha: YES
+        isPlanar: NO
+        colorSpaceName: make_nsstring("NSDeviceRGBColorSpace")
+        bytesPerRow: (image.width * 4) as NSInteger
+        bitsPerPixel: 32 as NSInteger];
+    let dst: *mut u8 = msg_send![rep, bitmapData];
+    let len = image.pixels.len().min(image.width * image.height * 4);
+    std::ptr::copy_nonoverlapping(image.pixels.as_ptr(), dst, len);
+    let size = NSSize::new(image.width as f64, image.height as f64);
+    let nsimage: id = msg_send![class!(NSImage), alloc];
+    let nsimage: id = msg_send![nsimage, initWithSize: size];
+    let () = msg_send![nsimage, addRepresentation: rep];
+    let () = msg_send![rep, release];
+    nsimage.autorelease()
+}
+
+/// Begin a dragging session from `view`, in response to the current event.
+unsafe fn begin_drag(view: id, contents: DragContents) {
+    let event: id = msg_send![NSApp(), currentEvent];
+    if event == nil {
+        warn!("start_drag called outside of an event handler");
+        return;
+    }
+    let mut writers = Vec::new();
+    for path in &contents.data.files {
+        let path = make_nsstring(&path.to_string_lossy());
+        let url: id = msg_send![class!(NSURL), fileURLWithPath: path];
+        writers.push(url);
+    }
+    if contents.data.text.is_some() || !contents.custom.is_empty() {
+        let item: id = msg_send![class!(NSPasteboardItem), new];
+        if let Some(text) = contents.data.text.as_ref() {
+            let () =
+                msg_send![item, setString: make_nsstring(text) forType: NSPasteboardTypeString];
+        }
+        for (type_id, bytes) in &contents.custom {
+            let data: id =
+                msg_send![class!(NSData), dataWithBytes: bytes.as_ptr() length: bytes.len()];
+            let () = msg_send![item, setData: data forType: make_nsstring(type_id)];
+        }
+        writers.push(item.autorelease());
+    }
+    if writers.is_empty() {
+        warn!("start_drag called with empty contents");
+        return;
+    }
+
+    let point = view.convertPoint_fromView_(event.locationInWindow(), nil);
+    let image = contents.image.as_ref().map(|img| make_drag_image(img));
+    let size = contents
+        .image
+        .as_ref()
+        .map(|img| NSSize::new(img.width as f64, img.height as f64))
+        .unwrap_or_else(|| NSSize::new(32.0, 32.0));
+    let frame = NSRect::new(
+        NSPoint::new(point.x - size.width / 2.0, point.y - size.height / 2.0),
+        size,
+    );
+
+    let items: Vec<id> = writers
+        .into_iter()
+        .map(|writer| {
+            let item: id = msg_send![class!(NSDraggingItem), alloc];
+            let item: id = msg_send![item, initWithPasteboardWriter: writer];
+            let () = msg_send![item, setDraggingFrame: frame contents: image.unwrap_or(nil)];
+            item.autorelease()
+        })
+        .collect();
+    let items = NSArray::arrayWithObjects(nil, &items);
+
+    let view_state: *mut c_void = *(*view).get_ivar("viewState");
+    let view_state = &mut *(view_state as *mut ViewState);
+    // NSDragOperationCopy, and optionally NSDragOperationMove
+    view_state.drag_operations = if contents.allow_move { 1 | 16 } else { 1 };
+    let _: id = msg_send![view, beginDraggingSessionWithItems: items event: event source: view];
+}
+
 extern "C" fn run_idle(this: &mut Object, _: Sel) {
     let view_state = unsafe {
         let view_state: *mut c_void = *this.get_ivar("viewState");
@@ -536,9 +1019,15 @@ extern "C" fn run_idle(this: &mut Object, _: Sel) {
         &mut view_state.idle_queue.lock().expect("queue"),
         Vec::new(),
     );
-    let handler_as_any = view_state.handler.as_any();
-    for callback in queue {
-        callback.call(handler_as_any);
+    let mut ctx = WinCtxImpl {
+        nsview: &view_state.nsview,
+        text: Text::new(),
+    };
+    for item in queue {
+        match item {
+            IdleKind::Callback(callback) => callback.call(view_state.handler.as_any()),
+            IdleKind::Token(token) => view_state.handler.idle(token, &mut ctx),
+        }
     }
 }
 
@@ -602,8 +1091,51 @@ extern "C" fn window_did_become_key(this: &mut Object, _: Sel, _notification: id
     }
 }
 
+extern "C" fn window_will_close(this: &mut Object, _: Sel, _notification: id) {
+    unsafe {
+        let view_state: *mut c_void = *this.get_ivar("viewState");
+        let view_state = &mut *(view_state as *mut ViewState);
+        let mut ctx = WinCtxImpl {
+            nsview: &(*view_state).nsview,
+            text: Text::new(),
+        };
+        (*view_state).handler.destroy(&mut ctx);
+    }
+}
+
+extern "C" fn accessibility_display_options_did_change(
+    this: &mut Object,
+    _: Sel,
+    _notification: id,
+) {
+    unsafe {
+        let view_state: *mut c_void = *this.get_ivar("viewState");
+        let view_state = &mut *(view_state as *mut ViewState);
+        let mut ctx = WinCtxImpl {
+            nsview: &(*view_state).nsview,
+            text: Text::new(),
+        };
+        (*view_state).handler.accessibility_changed(&mut ctx);
+    }
+}
+
+/// The height of the screen whose bottom left corner is the origin of the
+/// screen coordinates.
+unsafe fn primary_screen_height() -> f64 {
+    let screens: id = msg_send![class!(NSScreen), screens];
+    if screens.count() == 0 {
+        return 0.0;
+    }
+    let frame: NSRect = msg_send![screens.objectAtIndex(0), frame];
+    frame.size.height
+}
+
 impl WindowHandle {
     pub fn show(&self) {
+        if self.embedded {
+            // The host decides when its window is shown.
+            return;
+        }
         unsafe {
             let current_app = NSRunningApplication::currentApplication(nil);
             current_app.activateWithOptions_(NSApplicationActivateIgnoringOtherApps);
@@ -616,6 +1148,10 @@ impl WindowHandle {
                     .autorelease();
                 let notif_center: id = msg_send![notif_center_class, defaultCenter];
                 msg_send![notif_center, addObserver:*nsview.load() selector: sel!(windowDidBecomeKey:) name: notif_string object: window];
+                let notif_string = NSString::alloc(nil)
+                    .init_str(NSWindowWillCloseNotification)
+                    .autorelease();
+                msg_send![notif_center, addObserver:*nsview.load() selector: sel!(windowWillClose:) name: notif_string object: window];
                 window.makeKeyAndOrderFront_(nil)
             }
         }
@@ -626,12 +1162,117 @@ impl WindowHandle {
         if let Some(ref nsview) = self.nsview {
             unsafe {
                 let view = nsview.load();
+                if self.embedded {
+                    let () = msg_send![*view, removeFromSuperview];
+                    return;
+                }
                 let window: id = msg_send![*view, window];
                 window.close();
             }
         }
     }
 
+    /// Bring this window to the front of the window stack and give it focus.
+    pub fn bring_to_front_and_focus(&self) {
+        if self.embedded {
+            return;
+        }
+        if let Some(ref nsview) = self.nsview {
+            unsafe {
+                let window: id = msg_send![*nsview.load(), window];
+                window.makeKeyAndOrderFront_(nil);
+            }
+        }
+    }
+
+    /// Minimize the window to the dock.
+    pub fn minimize(&self) {
+        if self.embedded {
+            return;
+        }
+        if let Some(ref nsview) = self.nsview {
+            unsafe {
+                let window: id = msg_send![*nsview.load(), window];
+                let () = msg_send![window, miniaturize: nil];
+            }
+        }
+    }
+
+    /// Toggle the window between its user size and its largest size.
+    pub fn zoom(&self) {
+        if self.embedded {
+            return;
+        }
+        if let Some(ref nsview) = self.nsview {
+            unsafe {
+                let window: id = msg_send![*nsview.load(), window];
+                let () = msg_send![window, zoom: nil];
+            }
+        }
+    }
+
+    /// Show the next tab in the window's tab group.
+    pub fn select_next_tab(&self) {
+        self.send_to_window(sel!(selectNextTab:));
+    }
+
+    /// Show the previous tab in the window's tab group.
+    pub fn select_previous_tab(&self) {
+        self.send_to_window(sel!(selectPreviousTab:));
+    }
+
+    /// Merge all of the application's windows into tabs of this window.
+    pub fn merge_all_windows(&self) {
+        self.send_to_window(sel!(mergeAllWindows:));
+    }
+
+    /// Send an action message to the window, unless it is embedded.
+    fn send_to_window(&self, action: Sel) {
+        if self.embedded {
+            return;
+        }
+        if let Some(ref nsview) = self.nsview {
+            unsafe {
+                let window: id = msg_send![*nsview.load(), window];
+                let _: id = msg_send![window, performSelector: action withObject: nil];
+            }
+        }
+    }
+
+    /// The window's frame, in screen coordinates with the origin at the top
+    /// left of the primary screen.
+    pub fn get_frame(&self) -> Rect {
+        if let Some(ref nsview) = self.nsview {
+            unsafe {
+                let window: id = msg_send![*nsview.load(), window];
+                let frame: NSRect = msg_send![window, frame];
+                let top = primary_screen_height() - frame.origin.y - frame.size.height;
+                return Rect::from_origin_size(
+                    (frame.origin.x, top),
+                    (frame.size.width, frame.size.height),
+                );
+            }
+        }
+        Rect::ZERO
+    }
+
+    /// Move the window's top left corner to `position`, in the coordinates
+    /// used by [`get_frame`].
+    ///
+    /// [`get_frame`]: #method.get_frame
+    pub fn set_position(&self, position: Point) {
+        if self.embedded {
+            return;
+        }
+        if let Some(ref nsview) = self.nsview {
+            unsafe {
+                let window: id = msg_send![*nsview.load(), window];
+                let point = NSPoint::new(position.x, primary_screen_height() - position.y);
+                let () = msg_send![window, setFrameTopLeftPoint: point];
+            }
+        }
+    }
+
     // Request invalidation of the entire window contents.
     pub fn invalidate(&self) {
         if let Some(ref nsview) = self.nsview {
@@ -668,6 +1309,32 @@ impl WindowHandle {
         }
     }
 
+    /// Set whether animation frames are drawn in step with the display's
+    /// refresh, which is the default.
+    ///
+    /// Otherwise, each frame is drawn as soon as the last one is done.
+    pub fn set_vsync(&self, vsync: bool) {
+        if let Some(ref nsview) = self.nsview {
+            unsafe {
+                let view = *nsview.load();
+                if view.is_null() {
+                    return;
+                }
+                let view_state: *mut c_void = *(*view).get_ivar("viewState");
+                let view_state = &mut *(view_state as *mut ViewState);
+                view_state.vsync = vsync;
+            }
+        }
+    }
+
+    /// Print a document, after showing the system's print dialog.
+    ///
+    /// Printing is not supported on this platform, so this only logs a
+    /// warning.
+    pub fn print(&self, _setup: PageSetup, _doc: Box<dyn Printable>) {
+        warn!("printing is not supported on this platform");
+    }
+
     /// Get a handle that can be used to schedule an idle task.
     pub fn get_idle_handle(&self) -> Option<IdleHandle> {
         // TODO: maybe try harder to return None if window has been dropped.
@@ -677,6 +1344,27 @@ impl WindowHandle {
         })
     }
 
+    /// Get the raw handle of the window, for use with other libraries.
+    pub fn raw_window_handle(&self) -> RawWindowHandle {
+        let (ns_window, ns_view) = match self.nsview.as_ref() {
+            Some(nsview) => unsafe {
+                let view = *nsview.load();
+                let window: id = if view.is_null() {
+                    nil
+                } else {
+                    msg_send![view, window]
+                };
+                (window as *mut c_void, view as *mut c_void)
+            },
+            None => (null_mut(), null_mut()),
+        };
+        RawWindowHandle::MacOS(MacOSHandle {
+            ns_window,
+            ns_view,
+            ..MacOSHandle::empty()
+        })
+    }
+
     /// Get the dpi of the window.
     ///
     /// TODO: we want to migrate this from dpi (with 96 as nominal) to a scale
@@ -740,6 +1428,18 @@ impl IdleHandle {
     where
         F: FnOnce(&dyn Any) + Send + 'static,
     {
+        self.add_idle_kind(IdleKind::Callback(Box::new(callback)));
+    }
+
+    /// Request a call to the window handler's [`idle`] method, with the
+    /// given token.
+    ///
+    /// [`idle`]: ../trait.WinHandler.html#method.idle
+    pub fn schedule_idle(&self, token: IdleToken) {
+        self.add_idle_kind(IdleKind::Token(token));
+    }
+
+    fn add_idle_kind(&self, kind: IdleKind) {
         if let Some(queue) = self.idle_queue.upgrade() {
             let mut queue = queue.lock().expect("queue lock");
             if queue.is_empty() {
@@ -750,7 +1450,7 @@ impl IdleHandle {
                         withObject: nil waitUntilDone: NO);
                 }
             }
-            queue.push(Box::new(callback));
+            queue.push(kind);
         }
     }
 }
@@ -803,6 +1503,37 @@ impl<'a> WinCtx<'a> for WinCtxImpl<'a> {
     fn set_clipboard_contents(&mut self, contents: ClipboardItem) {
         Application::set_clipboard_contents(contents);
     }
+
+    fn start_drag(&mut self, contents: DragContents) {
+        unsafe { begin_drag(*self.nsview.load(), contents) }
+    }
+
+    fn presentation_time(&self) -> Option<Instant> {
+        unsafe {
+            let view = *self.nsview.load();
+            let view_state: *mut c_void = *(*view).get_ivar("viewState");
+            (*(view_state as *mut ViewState)).presentation_time
+        }
+    }
+
+    fn set_ime_enabled(&mut self, enabled: bool) {
+        unsafe {
+            let view = *self.nsview.load();
+            if !enabled {
+                ime::discard_marked_text(view);
+            }
+            let view_state: *mut c_void = *(*view).get_ivar("viewState");
+            (*(view_state as *mut ViewState)).ime.enabled = enabled;
+        }
+    }
+
+    fn set_ime_cursor_rect(&mut self, rect: Rect) {
+        unsafe {
+            let view = *self.nsview.load();
+            let view_state: *mut c_void = *(*view).get_ivar("viewState");
+            (*(view_state as *mut ViewState)).ime.cursor_rect = rect;
+        }
+    }
 }
 
 /// Convert an `Instant` into an NSTimeInterval, i.e. a fractional number
@@ -0,0 +1,215 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Video decoding, macOS implementation, using `AVAssetReader`.
+
+use std::ffi::c_void;
+use std::path::Path;
+use std::time::Duration;
+
+use cocoa::base::{id, nil, BOOL, YES};
+use objc::rc::StrongPtr;
+
+use log::warn;
+
+use crate::video::{bgrx_to_rgba, VideoFrame};
+use crate::Error;
+
+use super::util::make_nsstring;
+
+#[link(name = "AVFoundation", kind = "framework")]
+extern "C" {
+    static AVMediaTypeVideo: id;
+}
+
+#[link(name = "CoreMedia", kind = "framework")]
+extern "C" {
+    fn CMSampleBufferGetPresentationTimeStamp(sample: *mut c_void) -> CMTime;
+    fn CMSampleBufferGetImageBuffer(sample: *mut c_void) -> *mut c_void;
+    fn CMTimeMakeWithSeconds(seconds: f64, timescale: i32) -> CMTime;
+    fn CMTimeGetSeconds(time: CMTime) -> f64;
+    static kCMTimePositiveInfinity: CMTime;
+}
+
+#[link(name = "CoreVideo", kind = "framework")]
+extern "C" {
+    fn CVPixelBufferLockBaseAddress(buffer: *mut c_void, flags: u64) -> i32;
+    fn CVPixelBufferUnlockBaseAddress(buffer: *mut c_void, flags: u64) -> i32;
+    fn CVPixelBufferGetBaseAddress(buffer: *mut c_void) -> *mut u8;
+    fn CVPixelBufferGetBytesPerRow(buffer: *mut c_void) -> usize;
+    fn CVPixelBufferGetWidth(buffer: *mut c_void) -> usize;
+    fn CVPixelBufferGetHeight(buffer: *mut c_void) -> usize;
+    static kCVPixelBufferPixelFormatTypeKey: id;
+}
+
+#[link(name = "CoreFoundation", kind = "framework")]
+extern "C" {
+    fn CFRelease(object: *mut c_void);
+}
+
+/// `kCVPixelFormatType_32BGRA`
+const PIXEL_FORMAT_BGRA: u32 = 0x4247_5241;
+/// `kCVPixelBufferLock_ReadOnly`
+const LOCK_READ_ONLY: u64 = 1;
+/// `AVAssetReaderStatusReading`
+const STATUS_READING: isize = 1;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct CMTime {
+    value: i64,
+    timescale: i32,
+    flags: u32,
+    epoch: i64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct CMTimeRange {
+    start: CMTime,
+    duration: CMTime,
+}
+
+/// Reads the frames of a video file.
+pub struct VideoDecoder {
+    asset: StrongPtr,
+    track: StrongPtr,
+    reader: StrongPtr,
+    output: StrongPtr,
+}
+
+impl VideoDecoder {
+    /// Open a video file, ready to decode its first frame.
+    pub fn open(path: &Path) -> Result<VideoDecoder, Error> {
+        let path = path.to_str().ok_or(Error::Null)?;
+        unsafe {
+            let url: id = msg_send![class!(NSURL), fileURLWithPath: make_nsstring(path)];
+            let asset: id = msg_send![class!(AVURLAsset), URLAssetWithURL: url options: nil];
+            if asset == nil {
+                return Err(Error::Null);
+            }
+            let tracks: id = msg_send![asset, tracksWithMediaType: AVMediaTypeVideo];
+            let track: id = msg_send![tracks, firstObject];
+            if track == nil {
+                return Err(Error::Null);
+            }
+            let (reader, output) = start_reader(asset, track, None)?;
+            Ok(VideoDecoder {
+                asset: StrongPtr::retain(asset),
+                track: StrongPtr::retain(track),
+                reader,
+                output,
+            })
+        }
+    }
+
+    /// The length of the video, if known.
+    pub fn duration(&self) -> Option<Duration> {
+        let seconds = unsafe {
+            let duration: CMTime = msg_send![*self.asset, duration];
+            CMTimeGetSeconds(duration)
+        };
+        if seconds.is_finite() && seconds >= 0.0 {
+            Some(from_seconds(seconds))
+        } else {
+            None
+        }
+    }
+
+    /// Move to the given time. The next frame is the one shown at that time.
+    pub fn seek(&mut self, time: Duration) {
+        // A reader can only read forwards, so we start a new one.
+        unsafe {
+            let () = msg_send![*self.reader, cancelReading];
+            match start_reader(*self.asset, *self.track, Some(time)) {
+                Ok((reader, output)) => {
+                    self.reader = reader;
+                    self.output = output;
+                }
+                Err(e) => warn!("failed to seek video: {:?}", e),
+            }
+        }
+    }
+
+    /// Decode the next frame, or return `None` at the end of the video.
+    pub fn next_frame(&mut self) -> Option<VideoFrame> {
+        unsafe {
+            let status: isize = msg_send![*self.reader, status];
+            if status != STATUS_READING {
+                return None;
+            }
+            let sample: *mut c_void = msg_send![*self.output, copyNextSampleBuffer];
+            if sample.is_null() {
+                return None;
+            }
+            let time = CMTimeGetSeconds(CMSampleBufferGetPresentationTimeStamp(sample));
+            let buffer = CMSampleBufferGetImageBuffer(sample);
+            let frame = if buffer.is_null() {
+                None
+            } else {
+                CVPixelBufferLockBaseAddress(buffer, LOCK_READ_ONLY);
+                let width = CVPixelBufferGetWidth(buffer);
+                let height = CVPixelBufferGetHeight(buffer);
+                let stride = CVPixelBufferGetBytesPerRow(buffer);
+                let data = CVPixelBufferGetBaseAddress(buffer);
+                let src = std::slice::from_raw_parts(data, stride * height);
+                let pixels = bgrx_to_rgba(src, width, height, stride as isize);
+                CVPixelBufferUnlockBaseAddress(buffer, LOCK_READ_ONLY);
+                Some(VideoFrame {
+                    time: from_seconds(time.max(0.0)),
+                    width,
+                    height,
+                    pixels,
+                })
+            };
+            CFRelease(sample);
+            frame
+        }
+    }
+}
+
+/// Create a reader for the track, starting at the given time.
+unsafe fn start_reader(
+    asset: id,
+    track: id,
+    start: Option<Duration>,
+) -> Result<(StrongPtr, StrongPtr), Error> {
+    let reader: id = msg_send![class!(AVAssetReader), assetReaderWithAsset: asset error: nil];
+    if reader == nil {
+        return Err(Error::Null);
+    }
+    let format: id = msg_send![class!(NSNumber), numberWithUnsignedInt: PIXEL_FORMAT_BGRA];
+    let settings: id = msg_send![class!(NSDictionary), dictionaryWithObject: format
+        forKey: kCVPixelBufferPixelFormatTypeKey];
+    let output: id = msg_send![class!(AVAssetReaderTrackOutput),
+        assetReaderTrackOutputWithTrack: track outputSettings: settings];
+    let () = msg_send![reader, addOutput: output];
+    if let Some(start) = start {
+        let seconds = start.as_secs() as f64 + f64::from(start.subsec_nanos()) * 1e-9;
+        let range = CMTimeRange {
+            start: CMTimeMakeWithSeconds(seconds, 600),
+            duration: kCMTimePositiveInfinity,
+        };
+        let () = msg_send![reader, setTimeRange: range];
+    }
+    let started: BOOL = msg_send![reader, startReading];
+    if started != YES {
+        return Err(Error::Null);
+    }
+    Ok((StrongPtr::retain(reader), StrongPtr::retain(output)))
+}
+
+fn from_seconds(seconds: f64) -> Duration {
+    Duration::from_nanos((seconds * 1e9) as u64)
+}
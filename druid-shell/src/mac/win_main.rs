@@ -43,6 +43,12 @@ impl RunLoop {
     }
 }
 
+/// Process any pending events, without blocking.
+///
+/// This does nothing: an embedded view's events are delivered by the
+/// host's run loop, on the main thread.
+pub fn pump_events() {}
+
 /// Request to quit the application, exiting the runloop.
 pub fn request_quit() {
     unsafe {
@@ -0,0 +1,78 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Exporting printable content to PDF.
+//!
+//! This is only available on platforms where piet draws with cairo.
+
+use std::path::Path;
+
+use log::error;
+use piet_common::{Piet, RenderContext};
+
+use crate::kurbo::{Affine, Vec2};
+use crate::print::{PageSetup, Printable};
+use crate::Error;
+
+/// Write a document to a PDF file, with one page of the file for each page
+/// of the document.
+pub fn write_pdf(path: &Path, setup: &PageSetup, doc: &mut dyn Printable) -> Result<(), Error> {
+    let surface = cairo::PdfSurface::new(setup.paper.width, setup.paper.height, path);
+    if surface.status() != cairo::Status::Success {
+        error!("failed to create PDF surface: {:?}", surface.status());
+        return Err(Error::Null);
+    }
+    let mut cairo_ctx = cairo::Context::new(&surface);
+    let page_size = setup.printable_size();
+    let pages = {
+        // Nothing has been drawn on the surface yet, so the pagination
+        // context can be discarded.
+        cairo_ctx.save();
+        let mut piet = Piet::new(&mut cairo_ctx);
+        let pages = doc.paginate(page_size, &mut piet);
+        if let Err(e) = piet.finish() {
+            error!("{}", e);
+        }
+        cairo_ctx.restore();
+        pages
+    };
+    for page in 0..pages {
+        cairo_ctx.save();
+        cairo_ctx.rectangle(
+            setup.margin,
+            setup.margin,
+            page_size.width,
+            page_size.height,
+        );
+        cairo_ctx.clip();
+        {
+            let mut piet = Piet::new(&mut cairo_ctx);
+            piet.transform(Affine::translate(Vec2::new(setup.margin, setup.margin)));
+            doc.paint_page(page, page_size, &mut piet);
+            if let Err(e) = piet.finish() {
+                error!("{}", e);
+            }
+        }
+        cairo_ctx.restore();
+        cairo_ctx.show_page();
+    }
+    surface.finish();
+    match cairo_ctx.status() {
+        cairo::Status::Success => Ok(()),
+        status => {
+            error!("failed to write PDF: {:?}", status);
+            Err(Error::Null)
+        }
+    }
+}
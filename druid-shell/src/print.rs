@@ -0,0 +1,96 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Printing, and exporting to PDF.
+//!
+//! Content to be printed implements [`Printable`], and is painted one page
+//! at a time onto ordinary piet contexts. It can be sent to a printer with
+//! [`WindowHandle::print`], which shows the system's print dialog, or
+//! written straight to a PDF file with [`pdf::write_pdf`].
+//!
+//! Printing through the system dialog is only supported with GTK. PDF
+//! export is available where piet draws with cairo.
+//!
+//! [`Printable`]: trait.Printable.html
+//! [`WindowHandle::print`]: ../window/struct.WindowHandle.html#method.print
+//! [`pdf::write_pdf`]: ../pdf/fn.write_pdf.html
+
+use piet_common::Piet;
+
+use crate::kurbo::Size;
+
+/// Content that can be printed.
+///
+/// All sizes are in points, of which there are 72 to the inch.
+pub trait Printable {
+    /// Prepare to print onto pages with a printable area of `page_size`,
+    /// returning the number of pages.
+    ///
+    /// The render context is only provided for measuring text; anything
+    /// painted on it is discarded.
+    fn paginate(&mut self, page_size: Size, piet: &mut Piet) -> usize;
+
+    /// Paint a page, numbered from zero.
+    ///
+    /// The origin of the render context is at the top left corner of the
+    /// printable area.
+    fn paint_page(&mut self, page: usize, page_size: Size, piet: &mut Piet);
+}
+
+/// The size of the paper, and the margin around the printable area.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageSetup {
+    /// The size of the paper, in points.
+    pub paper: Size,
+    /// The margin on each side of the page, in points.
+    pub margin: f64,
+}
+
+impl PageSetup {
+    /// A4 paper, with half-inch margins.
+    pub fn a4() -> PageSetup {
+        PageSetup {
+            paper: Size::new(595.0, 842.0),
+            margin: 36.0,
+        }
+    }
+
+    /// US Letter paper, with half-inch margins.
+    pub fn letter() -> PageSetup {
+        PageSetup {
+            paper: Size::new(612.0, 792.0),
+            margin: 36.0,
+        }
+    }
+
+    /// Set the margin on each side of the page, in points.
+    pub fn margin(mut self, margin: f64) -> PageSetup {
+        self.margin = margin;
+        self
+    }
+
+    /// The size of the area inside the margins.
+    pub fn printable_size(&self) -> Size {
+        Size::new(
+            (self.paper.width - 2.0 * self.margin).max(0.0),
+            (self.paper.height - 2.0 * self.margin).max(0.0),
+        )
+    }
+}
+
+impl Default for PageSetup {
+    fn default() -> Self {
+        PageSetup::a4()
+    }
+}
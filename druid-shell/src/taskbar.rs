@@ -0,0 +1,56 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Integration with the Windows taskbar.
+//!
+//! These types are available on all platforms, so that code using them
+//! doesn't need to be conditionally compiled; they only have an effect on
+//! Windows.
+
+/// The progress indicator shown on a window's taskbar button.
+///
+/// Progress values are in the range `0.0..=1.0`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TaskbarProgress {
+    /// No progress is shown.
+    None,
+    /// Progress is shown, but the amount of work remaining is unknown.
+    Indeterminate,
+    /// Progress is being made.
+    Normal(f64),
+    /// Progress is stopped, but can be resumed.
+    Paused(f64),
+    /// An error occurred.
+    Error(f64),
+}
+
+/// A task in an application's jump list.
+///
+/// Choosing the task launches a new instance of the application with the
+/// given arguments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JumpListTask {
+    /// The title shown in the jump list.
+    pub title: String,
+    /// A longer description, shown as a tooltip.
+    pub description: Option<String>,
+    /// The command-line arguments passed to the new instance.
+    pub arguments: String,
+}
+
+impl Default for TaskbarProgress {
+    fn default() -> Self {
+        TaskbarProgress::None
+    }
+}
@@ -0,0 +1,71 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Video decoding.
+//!
+//! A [`VideoDecoder`] reads the frames of a video file in order, using the
+//! platform's media framework. It does not keep time: the caller decides
+//! when each frame should be shown, using its presentation time.
+//!
+//! This module is only available with the `video` feature.
+//!
+//! [`VideoDecoder`]: struct.VideoDecoder.html
+
+use std::time::Duration;
+
+pub use crate::platform::VideoDecoder;
+
+/// A decoded video frame.
+#[derive(Clone)]
+pub struct VideoFrame {
+    /// The time at which the frame should be shown, from the start of
+    /// the video.
+    pub time: Duration,
+    pub width: usize,
+    pub height: usize,
+    /// The pixels, row by row from the top, as RGBA with separate alpha.
+    pub pixels: Vec<u8>,
+}
+
+/// Convert BGRX pixels, as produced by most platform decoders, to RGBA.
+///
+/// A negative stride means that the rows are stored bottom-up.
+#[allow(dead_code)]
+pub(crate) fn bgrx_to_rgba(src: &[u8], width: usize, height: usize, stride: isize) -> Vec<u8> {
+    let mut pixels = Vec::with_capacity(width * height * 4);
+    let row_len = stride.abs() as usize;
+    for y in 0..height {
+        let row = if stride < 0 { height - 1 - y } else { y };
+        let row = &src[row * row_len..row * row_len + width * 4];
+        for px in row.chunks(4) {
+            pixels.extend_from_slice(&[px[2], px[1], px[0], 255]);
+        }
+    }
+    pixels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bottom_up_rows() {
+        // Two rows of one pixel each, with padding, stored bottom-up.
+        let src = [1, 2, 3, 0, 9, 9, 4, 5, 6, 0, 9, 9];
+        assert_eq!(
+            bgrx_to_rgba(&src, 1, 2, -6),
+            vec![6, 5, 4, 255, 3, 2, 1, 255]
+        );
+    }
+}
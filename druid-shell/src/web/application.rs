@@ -0,0 +1,88 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Web implementation of features at the application scope.
+
+use std::cell::RefCell;
+use std::path::Path;
+
+use log::warn;
+
+use crate::clipboard::ClipboardItem;
+
+thread_local! {
+    /// The clipboard used for copying and pasting within the page.
+    ///
+    /// Browsers only give asynchronous, permission-gated access to the
+    /// system clipboard, so we keep our own.
+    static CLIPBOARD: RefCell<Option<ClipboardItem>> = RefCell::new(None);
+}
+
+pub struct Application;
+
+impl Application {
+    /// Quitting is up to the user, who can close the tab; this does nothing.
+    pub fn quit() {
+        crate::runloop::request_quit();
+    }
+
+    /// Open a URL in a new browser tab.
+    pub fn open_url(url: &str) {
+        let result = web_sys::window().map(|window| window.open_with_url_and_target(url, "_blank"));
+        if let Some(Err(e)) = result {
+            warn!("failed to open url '{}': {:?}", url, e);
+        }
+    }
+
+    /// Web pages have no access to the file manager.
+    pub fn reveal_in_file_manager(path: &Path) {
+        warn!(
+            "cannot reveal '{}': no file manager on the web",
+            path.display()
+        );
+    }
+
+    /// Whether the browser is using a forced, high-contrast color scheme.
+    pub fn is_high_contrast() -> bool {
+        matches_media("(forced-colors: active)")
+    }
+
+    /// Whether the user has asked the browser to minimize animation.
+    pub fn prefers_reduced_motion() -> bool {
+        matches_media("(prefers-reduced-motion: reduce)")
+    }
+
+    /// Application fonts are not yet supported; this does nothing.
+    pub fn register_font(_data: &[u8]) {
+        warn!("registering fonts is not yet supported on the web");
+    }
+
+    /// Returns the contents of the clipboard, if any.
+    pub fn get_clipboard_contents() -> Option<ClipboardItem> {
+        CLIPBOARD.with(|clipboard| clipboard.borrow().clone())
+    }
+
+    /// Sets the contents of the clipboard.
+    pub fn set_clipboard_contents(item: ClipboardItem) {
+        CLIPBOARD.with(|clipboard| *clipboard.borrow_mut() = Some(item));
+    }
+}
+
+/// Whether a CSS media query matches the page.
+fn matches_media(query: &str) -> bool {
+    web_sys::window()
+        .and_then(|window| window.match_media(query).unwrap_or(None))
+        .map(|list| list.matches())
+        .unwrap_or(false)
+}
@@ -0,0 +1,782 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Web implementation of window creation.
+//!
+//! Each window is an HTML canvas, drawn with `piet-web`. If the page has a
+//! canvas with the id `druid`, the first window uses it; otherwise, and for
+//! any further windows, a canvas filling the viewport is added to the page.
+//!
+//! Timers are built on `std::time::Instant`, which is not available on
+//! `wasm32-unknown-unknown`; until druid moves to a portable clock, widgets
+//! that request timers will not work here.
+
+pub mod application;
+mod child_view;
+pub mod menu;
+pub mod util;
+#[cfg(feature = "video")]
+mod video;
+#[cfg(feature = "webview")]
+mod webview;
+pub mod win_main;
+
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::mem;
+use std::rc::{Rc, Weak};
+use std::sync::{Arc, Mutex, Weak as ArcWeak};
+use std::time::Instant;
+
+use log::{error, warn};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::convert::FromWasmAbi;
+use wasm_bindgen::JsCast;
+use web_sys::{CanvasRenderingContext2d, EventTarget, HtmlCanvasElement};
+
+use piet_common::{Piet, RenderContext};
+use raw_window_handle::web::WebHandle;
+use raw_window_handle::RawWindowHandle;
+
+use crate::kurbo::{Point, Rect, Vec2};
+
+use crate::clipboard::ClipboardItem;
+use crate::dialog::{FileDialogOptions, FileDialogType};
+use crate::dnd::DragContents;
+use crate::keyboard::{KeyCode, KeyEvent, KeyModifiers};
+use crate::platform::application::Application;
+use crate::print::{PageSetup, Printable};
+use crate::window::{
+    Cursor, FileInfo, IdleToken, MouseButton, MouseEvent, ScrollDevice, ScrollPhase, Text,
+    TimerToken, WheelEvent, WinCtx, WinHandler,
+};
+use crate::Error;
+
+pub use child_view::ChildView;
+pub use menu::Menu;
+#[cfg(feature = "video")]
+pub use video::VideoDecoder;
+#[cfg(feature = "webview")]
+pub use webview::WebView;
+
+/// The id of a canvas in the page to be used by the first window.
+const CANVAS_ID: &str = "druid";
+
+/// The distance scrolled by one line, for wheel events measured in lines.
+const SCROLL_LINE_DELTA: f64 = 120.0;
+
+/// How long to wait, in milliseconds, before retrying idle callbacks that
+/// couldn't run because the window handler was in use.
+const IDLE_RETRY_MS: i32 = 10;
+
+thread_local! {
+    /// The state of every open window, keyed by window id.
+    ///
+    /// This is what keeps the state alive; event listeners only hold weak
+    /// references.
+    static WINDOWS: RefCell<HashMap<usize, Rc<WindowState>>> = RefCell::new(HashMap::new());
+}
+
+#[derive(Clone, Default)]
+pub struct WindowHandle {
+    state: Weak<WindowState>,
+}
+
+/// Builder abstraction for creating new windows.
+pub struct WindowBuilder {
+    handler: Option<Box<dyn WinHandler>>,
+    title: String,
+    enable_mouse_move_events: bool,
+}
+
+#[derive(Clone)]
+pub struct IdleHandle {
+    window_id: usize,
+    idle_queue: ArcWeak<Mutex<Vec<IdleKind>>>,
+}
+
+// TODO: move this out of platform-dependent section.
+trait IdleCallback: Send {
+    fn call(self: Box<Self>, a: &dyn Any);
+}
+
+impl<F: FnOnce(&dyn Any) + Send> IdleCallback for F {
+    fn call(self: Box<F>, a: &dyn Any) {
+        (*self)(a)
+    }
+}
+
+/// An entry in a window's idle queue.
+enum IdleKind {
+    Callback(Box<dyn IdleCallback>),
+    Token(IdleToken),
+}
+
+/// The state associated with each window.
+struct WindowState {
+    id: usize,
+    window: web_sys::Window,
+    canvas: HtmlCanvasElement,
+    context: CanvasRenderingContext2d,
+    handler: RefCell<Box<dyn WinHandler>>,
+    idle_queue: Arc<Mutex<Vec<IdleKind>>>,
+    /// Whether an animation frame has been requested and not yet painted.
+    frame_pending: Cell<bool>,
+}
+
+struct WinCtxImpl<'a> {
+    handle: &'a WindowHandle,
+    text: Text<'static>,
+}
+
+impl WindowBuilder {
+    pub fn new() -> WindowBuilder {
+        WindowBuilder {
+            handler: None,
+            title: String::new(),
+            enable_mouse_move_events: true,
+        }
+    }
+
+    pub fn set_handler(&mut self, handler: Box<dyn WinHandler>) {
+        self.handler = Some(handler);
+    }
+
+    pub fn set_title(&mut self, title: impl Into<String>) {
+        self.title = title.into();
+    }
+
+    /// Menus are not shown on the web.
+    pub fn set_menu(&mut self, _menu: Menu) {}
+
+    pub fn set_enable_mouse_move_events(&mut self, to: bool) {
+        self.enable_mouse_move_events = to;
+    }
+
+    /// Windows can't be embedded in other windows on the web; to put
+    /// the window in a particular place, give the canvas the id "druid".
+    pub fn set_parent(&mut self, parent: RawWindowHandle) {
+        warn!("unsupported parent window handle: {:?}", parent);
+    }
+
+    pub fn build(self) -> Result<WindowHandle, Error> {
+        let handler = self
+            .handler
+            .expect("Tried to build a window without setting the handler");
+
+        let window = web_sys::window().ok_or(Error::Null)?;
+        let document = window.document().ok_or(Error::Null)?;
+        let is_first = WINDOWS.with(|windows| windows.borrow().is_empty());
+        let existing = document
+            .get_element_by_id(CANVAS_ID)
+            .filter(|_| is_first)
+            .and_then(|element| element.dyn_into::<HtmlCanvasElement>().ok());
+        let canvas = match existing {
+            Some(canvas) => canvas,
+            None => {
+                let canvas = document
+                    .create_element("canvas")
+                    .map_err(|_| Error::Null)?
+                    .dyn_into::<HtmlCanvasElement>()
+                    .map_err(|_| Error::Null)?;
+                let style = canvas.style();
+                let _ = style.set_property("display", "none");
+                let _ = style.set_property("width", "100vw");
+                let _ = style.set_property("height", "100vh");
+                let body = document.body().ok_or(Error::Null)?;
+                body.append_child(&canvas).map_err(|_| Error::Null)?;
+                canvas
+            }
+        };
+        // Only focusable elements receive keyboard events.
+        let _ = canvas.set_attribute("tabindex", "0");
+        if !self.title.is_empty() {
+            document.set_title(&self.title);
+        }
+
+        let context = canvas
+            .get_context("2d")
+            .ok()
+            .and_then(|context| context)
+            .and_then(|context| context.dyn_into::<CanvasRenderingContext2d>().ok())
+            .ok_or(Error::Null)?;
+
+        let window_id = next_window_id();
+        // This lets the canvas be found from a raw window handle.
+        let _ = canvas.set_attribute("data-raw-handle", &window_id.to_string());
+        let state = Rc::new(WindowState {
+            id: window_id,
+            window,
+            canvas,
+            context,
+            handler: RefCell::new(handler),
+            idle_queue: Arc::new(Mutex::new(Vec::new())),
+            frame_pending: Cell::new(false),
+        });
+        let handle = WindowHandle {
+            state: Rc::downgrade(&state),
+        };
+        WINDOWS.with(|windows| windows.borrow_mut().insert(window_id, state.clone()));
+
+        register_listeners(&state, &handle, self.enable_mouse_move_events);
+
+        state
+            .handler
+            .borrow_mut()
+            .connect(&crate::window::WindowHandle {
+                inner: handle.clone(),
+            });
+
+        Ok(handle)
+    }
+}
+
+/// Add an event listener that lives as long as the page.
+fn add_listener<E>(target: &EventTarget, name: &str, listener: impl FnMut(E) + 'static)
+where
+    E: FromWasmAbi + 'static,
+{
+    let closure = Closure::wrap(Box::new(listener) as Box<dyn FnMut(E)>);
+    if let Err(e) = target.add_event_listener_with_callback(name, closure.as_ref().unchecked_ref())
+    {
+        error!("failed to add '{}' listener: {:?}", name, e);
+    }
+    closure.forget();
+}
+
+fn register_listeners(state: &WindowState, handle: &WindowHandle, mouse_move: bool) {
+    let canvas: &EventTarget = state.canvas.as_ref();
+
+    let h = handle.clone();
+    add_listener(state.window.as_ref(), "resize", move |_: web_sys::Event| {
+        h.resize();
+    });
+
+    let h = handle.clone();
+    add_listener(canvas, "mousedown", move |event: web_sys::MouseEvent| {
+        if let Some(state) = h.state.upgrade() {
+            let _ = state.canvas.focus();
+        }
+        if let Some(button) = get_mouse_button(event.button()) {
+            let event = MouseEvent {
+                pos: Point::new(event.offset_x() as f64, event.offset_y() as f64),
+                mods: get_modifiers(&event),
+                // For mouse down events, the DOM reports the click count.
+                count: event.detail().max(1) as u32,
+                button,
+            };
+            h.with_handler(|handler, ctx| handler.mouse_down(&event, ctx));
+        }
+    });
+
+    let h = handle.clone();
+    add_listener(canvas, "mouseup", move |event: web_sys::MouseEvent| {
+        if let Some(button) = get_mouse_button(event.button()) {
+            let event = MouseEvent {
+                pos: Point::new(event.offset_x() as f64, event.offset_y() as f64),
+                mods: get_modifiers(&event),
+                count: 0,
+                button,
+            };
+            h.with_handler(|handler, ctx| handler.mouse_up(&event, ctx));
+        }
+    });
+
+    if mouse_move {
+        let h = handle.clone();
+        add_listener(canvas, "mousemove", move |event: web_sys::MouseEvent| {
+            let event = MouseEvent {
+                pos: Point::new(event.offset_x() as f64, event.offset_y() as f64),
+                mods: get_modifiers(&event),
+                count: 0,
+                button: get_mouse_button_from_buttons(event.buttons()),
+            };
+            h.with_handler(|handler, ctx| handler.mouse_move(&event, ctx));
+        });
+    }
+
+    let h = handle.clone();
+    add_listener(canvas, "wheel", move |event: web_sys::WheelEvent| {
+        let (scale, device) = match event.delta_mode() {
+            web_sys::WheelEvent::DOM_DELTA_LINE => (SCROLL_LINE_DELTA, ScrollDevice::Wheel),
+            web_sys::WheelEvent::DOM_DELTA_PAGE => {
+                let page = h
+                    .state
+                    .upgrade()
+                    .map(|state| state.canvas.client_height() as f64)
+                    .unwrap_or(SCROLL_LINE_DELTA);
+                (page, ScrollDevice::Wheel)
+            }
+            _ => (1.0, ScrollDevice::Trackpad),
+        };
+        // The DOM reports no gesture phases.
+        let wheel = WheelEvent {
+            delta: Vec2::new(event.delta_x() * scale, event.delta_y() * scale),
+            mods: get_modifiers(&event),
+            phase: ScrollPhase::None,
+            device,
+        };
+        h.with_handler(|handler, ctx| handler.wheel(&wheel, ctx));
+        event.prevent_default();
+    });
+
+    // Right clicks are for the application, not the browser.
+    add_listener(canvas, "contextmenu", move |event: web_sys::MouseEvent| {
+        event.prevent_default();
+    });
+
+    let h = handle.clone();
+    add_listener(canvas, "keydown", move |event: web_sys::KeyboardEvent| {
+        let key_event = make_key_event(&event);
+        let handled = h
+            .with_handler(|handler, ctx| handler.key_down(key_event, ctx))
+            .unwrap_or(false);
+        if handled {
+            event.prevent_default();
+        }
+    });
+
+    let h = handle.clone();
+    add_listener(canvas, "keyup", move |event: web_sys::KeyboardEvent| {
+        let key_event = make_key_event(&event);
+        h.with_handler(|handler, ctx| handler.key_up(key_event, ctx));
+    });
+
+    let h = handle.clone();
+    add_listener(canvas, "focus", move |_: web_sys::FocusEvent| {
+        h.with_handler(|handler, ctx| handler.got_focus(ctx));
+    });
+}
+
+fn get_mouse_button(button: i16) -> Option<MouseButton> {
+    match button {
+        0 => Some(MouseButton::Left),
+        1 => Some(MouseButton::Middle),
+        2 => Some(MouseButton::Right),
+        3 => Some(MouseButton::X1),
+        4 => Some(MouseButton::X2),
+        _ => None,
+    }
+}
+
+fn get_mouse_button_from_buttons(buttons: u16) -> MouseButton {
+    //TODO: this doesn't correctly handle multiple buttons being pressed.
+    match buttons {
+        b if b & 1 != 0 => MouseButton::Left,
+        b if b & 2 != 0 => MouseButton::Right,
+        b if b & 4 != 0 => MouseButton::Middle,
+        b if b & 8 != 0 => MouseButton::X1,
+        b if b & 16 != 0 => MouseButton::X2,
+        _ => MouseButton::Left,
+    }
+}
+
+fn get_modifiers(event: &web_sys::MouseEvent) -> KeyModifiers {
+    KeyModifiers {
+        shift: event.shift_key(),
+        alt: event.alt_key(),
+        ctrl: event.ctrl_key(),
+        meta: event.meta_key(),
+    }
+}
+
+fn make_key_event(event: &web_sys::KeyboardEvent) -> KeyEvent {
+    let key_code = KeyCode::from_dom_code(&event.code(), event.key_code());
+    // `key` is either the text produced by the key, or a name like "Enter".
+    let key = event.key();
+    let mut chars = key.chars();
+    let text = match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    };
+    let mods = KeyModifiers {
+        shift: event.shift_key(),
+        alt: event.alt_key(),
+        ctrl: event.ctrl_key(),
+        meta: event.meta_key(),
+    };
+    KeyEvent::new(key_code, event.repeat(), mods, text, text)
+}
+
+impl WindowHandle {
+    pub fn show(&self) {
+        if let Some(state) = self.state.upgrade() {
+            let _ = state.canvas.style().set_property("display", "block");
+            let _ = state.canvas.focus();
+        }
+        self.resize();
+    }
+
+    /// Close the window, removing its canvas from the page.
+    pub fn close(&self) {
+        if let Some(state) = self.state.upgrade() {
+            self.with_handler(|handler, ctx| handler.destroy(ctx));
+            state.canvas.remove();
+            WINDOWS.with(|windows| windows.borrow_mut().remove(&state.id));
+        }
+    }
+
+    /// Bring this window to the front of the window stack and give it focus.
+    ///
+    /// Each window is a canvas in the page, so this does nothing.
+    pub fn bring_to_front_and_focus(&self) {}
+
+    /// Minimize the window.
+    ///
+    /// Each window is a canvas in the page, so this does nothing.
+    pub fn minimize(&self) {}
+
+    /// Toggle the window between maximized and its normal size.
+    ///
+    /// Each window is a canvas in the page, so this does nothing.
+    pub fn zoom(&self) {}
+
+    /// The window's frame, in screen coordinates.
+    ///
+    /// Each window is a canvas in the page, so this is always empty.
+    pub fn get_frame(&self) -> Rect {
+        Rect::ZERO
+    }
+
+    /// Move the window's top left corner.
+    ///
+    /// Each window is a canvas in the page, so this does nothing.
+    pub fn set_position(&self, _position: Point) {}
+
+    // Request invalidation of the entire window contents.
+    pub fn invalidate(&self) {
+        if let Some(state) = self.state.upgrade() {
+            if !state.frame_pending.replace(true) {
+                let handle = self.clone();
+                let callback = Closure::once_into_js(move || handle.paint());
+                if let Err(e) = state
+                    .window
+                    .request_animation_frame(callback.unchecked_ref())
+                {
+                    error!("failed to request animation frame: {:?}", e);
+                }
+            }
+        }
+    }
+
+    /// Set the title of the page.
+    pub fn set_title(&self, title: &str) {
+        if let Some(document) = self.state.upgrade().and_then(|s| s.window.document()) {
+            document.set_title(title);
+        }
+    }
+
+    /// Menus are not shown on the web.
+    pub fn set_menu(&self, _menu: Menu) {}
+
+    /// Menus are not shown on the web.
+    pub fn show_context_menu(&self, _menu: Menu, _x: f64, _y: f64) {}
+
+    /// Set whether animation frames are drawn in step with the display's
+    /// refresh.
+    ///
+    /// Frames are requested with `requestAnimationFrame`, which always
+    /// follows the display, so this does nothing.
+    pub fn set_vsync(&self, _vsync: bool) {}
+
+    /// Print a document, after showing the system's print dialog.
+    ///
+    /// Printing is not supported on this platform, so this only logs a
+    /// warning.
+    pub fn print(&self, _setup: PageSetup, _doc: Box<dyn Printable>) {
+        warn!("printing is not supported on this platform");
+    }
+
+    /// Get a handle that can be used to schedule an idle task.
+    pub fn get_idle_handle(&self) -> Option<IdleHandle> {
+        let state = self.state.upgrade()?;
+        Some(IdleHandle {
+            window_id: state.id,
+            idle_queue: Arc::downgrade(&state.idle_queue),
+        })
+    }
+
+    /// Get the raw handle of the window, for use with other libraries.
+    ///
+    /// The id is also set as the canvas's `data-raw-handle` attribute.
+    pub fn raw_window_handle(&self) -> RawWindowHandle {
+        let id = self
+            .state
+            .upgrade()
+            .map(|state| state.id as u32)
+            .unwrap_or(0);
+        RawWindowHandle::Web(WebHandle {
+            id,
+            ..WebHandle::empty()
+        })
+    }
+
+    /// Get the dpi of the window.
+    ///
+    /// This follows the browser's device pixel ratio, which includes the
+    /// page zoom.
+    pub fn get_dpi(&self) -> f32 {
+        self.state
+            .upgrade()
+            .map(|state| 96.0 * state.window.device_pixel_ratio() as f32)
+            .unwrap_or(96.0)
+    }
+
+    // TODO: the following methods are cut'n'paste code. A good way to DRY
+    // would be to have a platform-independent trait with these as methods with
+    // default implementations.
+
+    /// Convert a dimension in px units to physical pixels (rounding).
+    pub fn px_to_pixels(&self, x: f32) -> i32 {
+        (x * self.get_dpi() * (1.0 / 96.0)).round() as i32
+    }
+
+    /// Convert a point in px units to physical pixels (rounding).
+    pub fn px_to_pixels_xy(&self, x: f32, y: f32) -> (i32, i32) {
+        let scale = self.get_dpi() * (1.0 / 96.0);
+        ((x * scale).round() as i32, (y * scale).round() as i32)
+    }
+
+    /// Convert a dimension in physical pixels to px units.
+    pub fn pixels_to_px<T: Into<f64>>(&self, x: T) -> f32 {
+        (x.into() as f32) * 96.0 / self.get_dpi()
+    }
+
+    /// Convert a point in physical pixels to px units.
+    pub fn pixels_to_px_xy<T: Into<f64>>(&self, x: T, y: T) -> (f32, f32) {
+        let scale = 96.0 / self.get_dpi();
+        ((x.into() as f32) * scale, (y.into() as f32) * scale)
+    }
+
+    #[deprecated(since = "0.3", note = "use methods on WinCtx instead")]
+    pub fn file_dialog(
+        &self,
+        _ty: FileDialogType,
+        _options: FileDialogOptions,
+    ) -> Result<OsString, Error> {
+        Err(Error::Null)
+    }
+
+    /// Match the canvas's backing store to its size on the page, and tell
+    /// the handler about it.
+    ///
+    /// Sizes are reported in physical pixels, like on other platforms.
+    fn resize(&self) {
+        let state = match self.state.upgrade() {
+            Some(state) => state,
+            None => return,
+        };
+        let scale = state.window.device_pixel_ratio();
+        let width = (state.canvas.client_width() as f64 * scale) as u32;
+        let height = (state.canvas.client_height() as f64 * scale) as u32;
+        state.canvas.set_width(width);
+        state.canvas.set_height(height);
+        self.with_handler(|handler, ctx| handler.size(width, height, ctx));
+        self.invalidate();
+    }
+
+    fn paint(&self) {
+        let state = match self.state.upgrade() {
+            Some(state) => state,
+            None => return,
+        };
+        state.frame_pending.set(false);
+        // The handler paints in px units.
+        let scale = state.window.device_pixel_ratio();
+        let mut context = state.context.clone();
+        if let Err(e) = context.set_transform(scale, 0.0, 0.0, scale, 0.0, 0.0) {
+            error!("failed to set canvas transform: {:?}", e);
+        }
+        let anim = self
+            .with_handler(|handler, ctx| {
+                let mut piet_ctx = Piet::new(&mut context, &state.window);
+                let anim = handler.paint(&mut piet_ctx, ctx);
+                if let Err(e) = piet_ctx.finish() {
+                    error!("{}", e)
+                }
+                anim
+            })
+            .unwrap_or(false);
+        if anim {
+            self.invalidate();
+        }
+    }
+
+    /// Call a closure with the window's handler and a context, if the window
+    /// is still alive and the handler isn't already in use.
+    pub(crate) fn with_handler<F, R>(&self, f: F) -> Option<R>
+    where
+        F: FnOnce(&mut dyn WinHandler, &mut dyn WinCtx) -> R,
+    {
+        let state = self.state.upgrade()?;
+        let mut handler = match state.handler.try_borrow_mut() {
+            Ok(handler) => handler,
+            Err(_) => {
+                warn!("dropping event, window handler is already in use");
+                return None;
+            }
+        };
+        let mut ctx = WinCtxImpl {
+            handle: self,
+            text: Text::new(state.context.clone()),
+        };
+        Some(f(&mut **handler, &mut ctx))
+    }
+}
+
+// There is only one thread on the web.
+unsafe impl Send for IdleHandle {}
+
+impl IdleHandle {
+    /// Add an idle handler, which is called (once) when the message loop
+    /// is empty. The idle handler will be run from the main UI thread, and
+    /// won't be scheduled if the associated view has been dropped.
+    ///
+    /// Note: the name "idle" suggests that it will be scheduled with a lower
+    /// priority than other UI events, but that's not necessarily the case.
+    pub fn add_idle<F>(&self, callback: F)
+    where
+        F: FnOnce(&dyn Any) + Send + 'static,
+    {
+        self.add_idle_kind(IdleKind::Callback(Box::new(callback)));
+    }
+
+    /// Request a call to the window handler's [`idle`] method, with the
+    /// given token.
+    ///
+    /// [`idle`]: ../trait.WinHandler.html#method.idle
+    pub fn schedule_idle(&self, token: IdleToken) {
+        self.add_idle_kind(IdleKind::Token(token));
+    }
+
+    fn add_idle_kind(&self, kind: IdleKind) {
+        if let Some(queue) = self.idle_queue.upgrade() {
+            let mut queue = queue.lock().expect("queue lock");
+            if queue.is_empty() {
+                let window_id = self.window_id;
+                set_timeout(0, move || run_idle(window_id));
+            }
+            queue.push(kind);
+        }
+    }
+}
+
+fn run_idle(window_id: usize) {
+    let state = match WINDOWS.with(|windows| windows.borrow().get(&window_id).cloned()) {
+        Some(state) => state,
+        None => return,
+    };
+    // The callbacks stay queued while the handler is in use, since nothing
+    // else schedules them again while the queue isn't empty.
+    let mut handler = match state.handler.try_borrow_mut() {
+        Ok(handler) => handler,
+        Err(_) => {
+            set_timeout(IDLE_RETRY_MS, move || run_idle(window_id));
+            return;
+        }
+    };
+    let queue: Vec<_> = mem::replace(&mut state.idle_queue.lock().expect("queue"), Vec::new());
+    let handle = WindowHandle {
+        state: Rc::downgrade(&state),
+    };
+    let mut ctx = WinCtxImpl {
+        handle: &handle,
+        text: Text::new(state.context.clone()),
+    };
+    for item in queue {
+        match item {
+            IdleKind::Callback(callback) => callback.call(handler.as_any()),
+            IdleKind::Token(token) => handler.idle(token, &mut ctx),
+        }
+    }
+}
+
+/// Run a closure once, after the given number of milliseconds.
+fn set_timeout(millis: i32, f: impl FnOnce() + 'static) {
+    let callback = Closure::once_into_js(f);
+    let result = web_sys::window().map(|window| {
+        window
+            .set_timeout_with_callback_and_timeout_and_arguments_0(callback.unchecked_ref(), millis)
+    });
+    if let Some(Err(e)) = result {
+        error!("failed to set timeout: {:?}", e);
+    }
+}
+
+impl<'a> WinCtx<'a> for WinCtxImpl<'a> {
+    fn invalidate(&mut self) {
+        self.handle.invalidate();
+    }
+
+    fn text_factory(&mut self) -> &mut Text<'a> {
+        &mut self.text
+    }
+
+    fn set_cursor(&mut self, cursor: &Cursor) {
+        if let Some(state) = self.handle.state.upgrade() {
+            let cursor = match cursor {
+                Cursor::Arrow => "default",
+                Cursor::IBeam => "text",
+                Cursor::Crosshair => "crosshair",
+                Cursor::OpenHand => "grab",
+                Cursor::NotAllowed => "not-allowed",
+                Cursor::ResizeLeftRight => "ew-resize",
+                Cursor::ResizeUpDown => "ns-resize",
+            };
+            let _ = state.canvas.style().set_property("cursor", cursor);
+        }
+    }
+
+    fn request_timer(&mut self, deadline: Instant) -> TimerToken {
+        let interval = deadline
+            .checked_duration_since(Instant::now())
+            .map(|d| d.as_millis() as i32)
+            .unwrap_or(0);
+        let token = TimerToken::new(next_timer_id());
+        let handle = self.handle.clone();
+        set_timeout(interval, move || {
+            handle.with_handler(|handler, ctx| handler.timer(token, ctx));
+        });
+        token
+    }
+
+    /// The browser only gives access to files asynchronously.
+    fn open_file_sync(&mut self, _options: FileDialogOptions) -> Option<FileInfo> {
+        warn!("synchronous file dialogs are not supported on the web");
+        None
+    }
+
+    fn set_clipboard_contents(&mut self, contents: ClipboardItem) {
+        Application::set_clipboard_contents(contents);
+    }
+
+    fn start_drag(&mut self, _contents: DragContents) {
+        //TODO: implement with the HTML drag and drop API
+        warn!("dragging out of the window is not yet supported on the web");
+    }
+}
+
+fn next_window_id() -> usize {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static WINDOW_ID: AtomicUsize = AtomicUsize::new(1);
+    WINDOW_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+fn next_timer_id() -> usize {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static TIMER_ID: AtomicUsize = AtomicUsize::new(1);
+    TIMER_ID.fetch_add(1, Ordering::Relaxed)
+}
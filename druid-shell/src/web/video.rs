@@ -0,0 +1,45 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Video decoding, which is not supported on the web.
+
+use std::convert::Infallible;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::video::VideoFrame;
+use crate::Error;
+
+/// Reads the frames of a video file.
+///
+/// Opening a file always fails on this platform.
+pub struct VideoDecoder {
+    never: Infallible,
+}
+
+impl VideoDecoder {
+    pub fn open(_path: &Path) -> Result<VideoDecoder, Error> {
+        Err(Error::Null)
+    }
+
+    pub fn duration(&self) -> Option<Duration> {
+        match self.never {}
+    }
+
+    pub fn seek(&mut self, _time: Duration) {}
+
+    pub fn next_frame(&mut self) -> Option<VideoFrame> {
+        match self.never {}
+    }
+}
@@ -0,0 +1,33 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Web implementation of the runloop.
+//!
+//! The browser owns the event loop, so there is nothing to run: once the
+//! windows are built, their event listeners keep the application alive.
+
+pub struct RunLoop {}
+
+impl RunLoop {
+    pub fn new() -> RunLoop {
+        RunLoop {}
+    }
+
+    pub fn run(&mut self) {}
+}
+
+/// Events are delivered by the browser, so there is nothing to pump.
+pub fn pump_events() {}
+
+pub fn request_quit() {}
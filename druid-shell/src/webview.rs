@@ -0,0 +1,42 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Embedded web content.
+//!
+//! A [`WebView`] shows pages using the platform's web engine. It is placed
+//! in a window as a [`ChildView`]. Scripts running in the page can send
+//! strings to the application by calling `window.druid.postMessage()`.
+//!
+//! This module is only available with the `webview` feature.
+//!
+//! [`WebView`]: struct.WebView.html
+//! [`ChildView`]: ../struct.ChildView.html
+
+pub use crate::platform::WebView;
+
+/// The name of the script message handler installed in each page.
+#[allow(dead_code)]
+pub(crate) const MESSAGE_HANDLER: &str = "druid";
+
+/// A script run at the start of each page, providing `window.druid`.
+///
+/// Both WebKit ports deliver messages through `window.webkit.messageHandlers`.
+#[allow(dead_code)]
+pub(crate) const BRIDGE_SCRIPT: &str = r#"
+window.druid = {
+    postMessage: function(message) {
+        window.webkit.messageHandlers.druid.postMessage(String(message));
+    }
+};
+"#;
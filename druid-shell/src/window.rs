@@ -17,13 +17,17 @@
 use std::any::Any;
 use std::ops::Deref;
 use std::path::PathBuf;
+use std::time::Instant;
 
 use crate::clipboard::ClipboardItem;
 //TODO: why is this pub?
 use crate::dialog::FileDialogOptions;
+use crate::dnd::{DragContents, DragEvent, DropEffect};
+use crate::ime::ImeEvent;
 pub use crate::keyboard::{KeyEvent, KeyModifiers};
-use crate::kurbo::{Point, Vec2};
+use crate::kurbo::{Point, Rect, Vec2};
 use crate::platform;
+use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
 
 // It's possible we'll want to make this type alias at a lower level,
 // see https://github.com/linebender/piet/pull/37 for more discussion.
@@ -47,12 +51,35 @@ impl TimerToken {
     }
 }
 
+/// A token that identifies an idle request made with
+/// [`IdleHandle::schedule_idle()`].
+///
+/// Unlike timer tokens, idle tokens are chosen by the caller, so that
+/// requests can be made from code that runs outside of the handler.
+///
+/// [`IdleHandle::schedule_idle()`]: platform/struct.IdleHandle.html#method.schedule_idle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Hash)]
+pub struct IdleToken(usize);
+
+impl IdleToken {
+    /// Create a new idle token.
+    pub const fn new(id: usize) -> IdleToken {
+        IdleToken(id)
+    }
+}
+
 // Handle to Window Level Utilities
 #[derive(Clone, Default)]
 pub struct WindowHandle {
     pub inner: platform::WindowHandle,
 }
 
+unsafe impl HasRawWindowHandle for WindowHandle {
+    fn raw_window_handle(&self) -> RawWindowHandle {
+        self.inner.raw_window_handle()
+    }
+}
+
 impl Deref for WindowHandle {
     type Target = platform::WindowHandle;
 
@@ -94,6 +121,59 @@ pub trait WinCtx<'a> {
     fn open_file_sync(&mut self, options: FileDialogOptions) -> Option<FileInfo>;
 
     fn set_clipboard_contents(&mut self, contents: ClipboardItem);
+
+    /// Begin dragging something out of the window.
+    ///
+    /// This should be called in response to a mouse down or mouse move
+    /// event. When the drag finishes, the handler receives a
+    /// [`WinHandler::drag_end()`] call.
+    ///
+    /// [`WinHandler::drag_end()`]: trait.WinHandler.html#method.drag_end
+    fn start_drag(&mut self, contents: DragContents);
+
+    /// Capture or release the mouse.
+    ///
+    /// While the mouse is captured, the window receives mouse moves and
+    /// button releases even when the cursor is outside of it. On some
+    /// platforms, such as macOS and GTK, pressing a button already grabs
+    /// the mouse until it is released, and this does nothing.
+    #[allow(unused_variables)]
+    fn set_mouse_capture(&mut self, capture: bool) {}
+
+    /// Show or hide the on-screen keyboard.
+    ///
+    /// This only does anything on platforms that have one; text widgets
+    /// should show it when they gain focus and hide it when they lose it.
+    #[allow(unused_variables)]
+    fn set_soft_keyboard_visible(&mut self, visible: bool) {}
+
+    /// Enable or disable the platform's input method.
+    ///
+    /// While it is enabled, key presses that are part of a composition are
+    /// consumed by the input method, and the handler receives
+    /// [`WinHandler::ime()`] calls instead. Text widgets should enable it
+    /// when they gain focus and disable it when they lose it.
+    ///
+    /// [`WinHandler::ime()`]: trait.WinHandler.html#method.ime
+    #[allow(unused_variables)]
+    fn set_ime_enabled(&mut self, enabled: bool) {}
+
+    /// Set the area of the text cursor, in px units relative to the window.
+    ///
+    /// The input method places its candidate window next to this area.
+    #[allow(unused_variables)]
+    fn set_ime_cursor_rect(&mut self, rect: Rect) {}
+
+    /// The time the frame being painted is expected to appear on the display.
+    ///
+    /// This is only known during [`WinHandler::paint()`], on platforms that
+    /// synchronize painting with the display's refresh, and otherwise is
+    /// `None`.
+    ///
+    /// [`WinHandler::paint()`]: trait.WinHandler.html#tymethod.paint
+    fn presentation_time(&self) -> Option<Instant> {
+        None
+    }
 }
 
 /// App behavior, supplied by the app.
@@ -112,6 +192,14 @@ pub trait WinHandler {
     #[allow(unused_variables)]
     fn size(&mut self, width: u32, height: u32, ctx: &mut dyn WinCtx) {}
 
+    /// Called when the part of the window not covered by system UI, such
+    /// as a notch or a status bar, changes. Like the size, this is in
+    /// physical pixels.
+    ///
+    /// This is only called on platforms where such UI can overlap windows.
+    #[allow(unused_variables)]
+    fn safe_area(&mut self, area: Rect, ctx: &mut dyn WinCtx) {}
+
     /// Request the handler to paint the window contents. Return value
     /// indicates whether window is animating, i.e. whether another paint
     /// should be scheduled for the next animation frame.
@@ -142,16 +230,26 @@ pub trait WinHandler {
     #[allow(unused_variables)]
     fn key_up(&mut self, event: KeyEvent, ctx: &mut dyn WinCtx) {}
 
-    /// Called on a mouse wheel event.
+    /// Called when the input method updates or commits a composition.
     ///
-    /// The polarity is the amount to be added to the scroll position,
-    /// in other words the opposite of the direction the content should
-    /// move on scrolling. This polarity is consistent with the
-    /// deltaX and deltaY values in a web [WheelEvent].
+    /// This is only called while the input method is enabled with
+    /// [`WinCtx::set_ime_enabled()`].
     ///
-    /// [WheelEvent]: https://w3c.github.io/uievents/#event-type-wheel
+    /// [`WinCtx::set_ime_enabled()`]: trait.WinCtx.html#method.set_ime_enabled
+    #[allow(unused_variables)]
+    fn ime(&mut self, event: ImeEvent, ctx: &mut dyn WinCtx) {}
+
+    /// Called when a pen touches, moves over, or leaves the window's surface.
+    ///
+    /// Pens also move the mouse, so this is followed by the corresponding
+    /// mouse event; handlers that don't care about pressure or tilt can
+    /// ignore this.
     #[allow(unused_variables)]
-    fn wheel(&mut self, delta: Vec2, mods: KeyModifiers, ctx: &mut dyn WinCtx) {}
+    fn pen(&mut self, event: &PenEvent, ctx: &mut dyn WinCtx) {}
+
+    /// Called on a mouse wheel or trackpad scroll event.
+    #[allow(unused_variables)]
+    fn wheel(&mut self, event: &WheelEvent, ctx: &mut dyn WinCtx) {}
 
     /// Called when the mouse moves.
     #[allow(unused_variables)]
@@ -165,6 +263,48 @@ pub trait WinHandler {
     #[allow(unused_variables)]
     fn mouse_up(&mut self, event: &MouseEvent, ctx: &mut dyn WinCtx) {}
 
+    /// Called when something dragged from another application first enters
+    /// the window.
+    ///
+    /// The return value indicates whether the drop would be accepted, and
+    /// is used by the platform to give the user feedback.
+    #[allow(unused_variables)]
+    fn drag_enter(&mut self, event: &DragEvent, ctx: &mut dyn WinCtx) -> DropEffect {
+        DropEffect::None
+    }
+
+    /// Called when a drag moves within the window.
+    ///
+    /// As with [`drag_enter`], the return value indicates whether the drop
+    /// would be accepted at the current location.
+    ///
+    /// [`drag_enter`]: #method.drag_enter
+    #[allow(unused_variables)]
+    fn drag_over(&mut self, event: &DragEvent, ctx: &mut dyn WinCtx) -> DropEffect {
+        DropEffect::None
+    }
+
+    /// Called when a drag leaves the window without being dropped.
+    #[allow(unused_variables)]
+    fn drag_leave(&mut self, ctx: &mut dyn WinCtx) {}
+
+    /// Called when the user drops something onto the window.
+    ///
+    /// Return `true` if the drop was accepted.
+    #[allow(unused_variables)]
+    fn drag_drop(&mut self, event: &DragEvent, ctx: &mut dyn WinCtx) -> bool {
+        false
+    }
+
+    /// Called when a drag started with [`WinCtx::start_drag()`] finishes.
+    ///
+    /// The effect is the operation performed by the receiver, and is
+    /// `DropEffect::None` if the drag was cancelled.
+    ///
+    /// [`WinCtx::start_drag()`]: trait.WinCtx.html#tymethod.start_drag
+    #[allow(unused_variables)]
+    fn drag_end(&mut self, effect: DropEffect, ctx: &mut dyn WinCtx) {}
+
     /// Called on timer event.
     ///
     /// This is called at (approximately) the requested deadline by a
@@ -175,10 +315,42 @@ pub trait WinHandler {
     #[allow(unused_variables)]
     fn timer(&mut self, token: TimerToken, ctx: &mut dyn WinCtx) {}
 
+    /// Called when the main loop is idle, after a request made with
+    /// [`IdleHandle::schedule_idle()`]. The token is the one passed to
+    /// that call.
+    ///
+    /// [`IdleHandle::schedule_idle()`]: platform/struct.IdleHandle.html#method.schedule_idle
+    #[allow(unused_variables)]
+    fn idle(&mut self, token: IdleToken, ctx: &mut dyn WinCtx) {}
+
     /// Called when this window becomes the focused window.
     #[allow(unused_variables)]
     fn got_focus(&mut self, ctx: &mut dyn WinCtx) {}
 
+    /// Called when the user changes an accessibility setting, such as
+    /// [high contrast] or [reduced motion].
+    ///
+    /// Every window is told; the settings themselves are queried through
+    /// the [`Application`].
+    ///
+    /// [high contrast]: application/struct.Application.html#method.is_high_contrast
+    /// [reduced motion]: application/struct.Application.html#method.prefers_reduced_motion
+    /// [`Application`]: application/struct.Application.html
+    #[allow(unused_variables)]
+    fn accessibility_changed(&mut self, ctx: &mut dyn WinCtx) {}
+
+    /// Called when the application is moved to the background.
+    ///
+    /// This only happens on mobile platforms, where a suspended application
+    /// may be killed without further notice; it is a good time to save state.
+    #[allow(unused_variables)]
+    fn suspended(&mut self, ctx: &mut dyn WinCtx) {}
+
+    /// Called when the application returns to the foreground after being
+    /// [suspended](#method.suspended).
+    #[allow(unused_variables)]
+    fn resumed(&mut self, ctx: &mut dyn WinCtx) {}
+
     /// Called when the window is being destroyed. Note that this happens
     /// earlier in the sequence than drop (at WM_DESTROY, while the latter is
     /// WM_NCDESTROY).
@@ -200,12 +372,132 @@ pub struct MouseEvent {
     pub mods: KeyModifiers,
     /// The number of mouse clicks associated with this event. This will always
     /// be `0` for a mouse-up event.
+    ///
+    /// Presses that follow each other within the platform's double-click
+    /// time and distance count up: a double click is `2`, a triple click `3`,
+    /// and so on.
     pub count: u32,
     /// The currently pressed button in the case of a move or click event,
     /// or the released button in the case of a mouse-up event.
     pub button: MouseButton,
 }
 
+/// The state of a pen (stylus) on a tablet or touch screen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PenEvent {
+    /// The location of the pen in the current window, in px units.
+    pub pos: Point,
+    /// Keyboard modifiers at the time of the event.
+    pub mods: KeyModifiers,
+    /// Whether the pen touched the surface, moved, or was lifted.
+    pub kind: PenEventKind,
+    /// The pressure of the tip on the surface, from `0.0` to `1.0`.
+    ///
+    /// This is `0.0` while the pen hovers, and `1.0` for pens that don't
+    /// report pressure.
+    pub pressure: f64,
+    /// The tilt of the pen, in degrees from perpendicular to the surface,
+    /// towards positive x and positive y respectively.
+    pub tilt: Vec2,
+    /// `true` if the pen is being used as an eraser, either because it was
+    /// flipped over or because its eraser button is pressed.
+    pub is_eraser: bool,
+}
+
+/// What happened to a pen, in a [`PenEvent`].
+///
+/// [`PenEvent`]: struct.PenEvent.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PenEventKind {
+    /// The pen touched the surface.
+    Down,
+    /// The pen moved, either on the surface or hovering above it.
+    Move,
+    /// The pen was lifted from the surface.
+    Up,
+}
+
+/// A mouse wheel event.
+///
+/// An event generated by a mouse wheel or trackpad device. Perhaps a
+/// better name would have been "ScrollEvent", but we follow the lead
+/// of the W3C in naming, also partly to emphasize that this represents
+/// an event from the device, as opposed to a particular intended action.
+/// For example, in many cases a wheel event might cause a zoom.
+///
+/// See the
+/// [wiki](https://linebender.gitbook.io/linebender-graphics-wiki/mouse-wheel)
+/// for more discussion, including testing on various platforms.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WheelEvent {
+    /// The wheel movement, in px units.
+    ///
+    /// The polarity is the amount to be added to the scroll position,
+    /// in other words the opposite of the direction the content should
+    /// move on scrolling. This polarity is consistent with the
+    /// deltaX and deltaY values in a web [WheelEvent].
+    ///
+    /// [WheelEvent]: https://w3c.github.io/uievents/#event-type-wheel
+    pub delta: Vec2,
+    /// The keyboard modifiers at the time of the event.
+    pub mods: KeyModifiers,
+    /// Where this event falls in a scroll gesture.
+    pub phase: ScrollPhase,
+    /// The kind of device that produced the event.
+    pub device: ScrollDevice,
+}
+
+/// Where a wheel event falls in a scroll gesture.
+///
+/// Trackpads on some platforms report when the user's fingers touch and
+/// leave the pad; after they leave, scrolling may continue for a while
+/// with simulated momentum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollPhase {
+    /// The device doesn't report gestures, as is the case for mouse wheels.
+    None,
+    /// The user started scrolling.
+    Began,
+    /// The user is scrolling.
+    Changed,
+    /// The user stopped scrolling. Momentum events may follow.
+    Ended,
+    /// The scroll is continuing with momentum, after the user stopped.
+    Momentum,
+    /// The momentum scroll finished.
+    MomentumEnded,
+}
+
+/// The kind of device that produced a wheel event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollDevice {
+    /// A wheel, which scrolls in steps of some number of lines.
+    Wheel,
+    /// A device with precise, pixel-level deltas, such as a trackpad.
+    Trackpad,
+}
+
+impl WheelEvent {
+    /// Create a wheel event for a mouse wheel, which reports no phases.
+    pub fn wheel(delta: Vec2, mods: KeyModifiers) -> WheelEvent {
+        WheelEvent {
+            delta,
+            mods,
+            phase: ScrollPhase::None,
+            device: ScrollDevice::Wheel,
+        }
+    }
+
+    /// Returns `true` if the user is in the middle of a scroll gesture,
+    /// with their fingers on the device.
+    pub fn is_in_gesture(&self) -> bool {
+        match self.phase {
+            ScrollPhase::Began | ScrollPhase::Changed => true,
+            _ => false,
+        }
+    }
+}
+
 /// An indicator of which mouse button was pressed.
 #[derive(PartialEq, Eq, Clone, Copy, Debug)]
 pub enum MouseButton {
@@ -14,17 +14,23 @@
 
 //! Windows implementation of features at the application scope.
 
+use std::mem;
+use std::path::Path;
 use std::ptr;
 
-use winapi::shared::minwindef::FALSE;
-use winapi::shared::minwindef::UINT;
+use winapi::shared::minwindef::{BOOL, FALSE, TRUE, UINT};
 use winapi::shared::ntdef::{LPWSTR, WCHAR};
 use winapi::shared::winerror::ERROR_SUCCESS;
 use winapi::um::errhandlingapi::GetLastError;
+use winapi::um::shellapi::ShellExecuteW;
 use winapi::um::winbase::{GlobalAlloc, GlobalLock, GlobalUnlock, GMEM_MOVEABLE};
 use winapi::um::winuser::*;
 
+use log::warn;
+
+use super::taskbar;
 use crate::clipboard::ClipboardItem;
+use crate::taskbar::JumpListTask;
 use crate::util::{FromWide, ToWide};
 
 pub struct Application;
@@ -34,6 +40,74 @@ impl Application {
         crate::runloop::request_quit();
     }
 
+    /// Open a URL with the default handler for its scheme.
+    ///
+    /// For `http` and `https` URLs this is the user's web browser.
+    pub fn open_url(url: &str) {
+        shell_execute(url, None);
+    }
+
+    /// Show a file or directory in Explorer, selecting it.
+    pub fn reveal_in_file_manager(path: &Path) {
+        let args = format!("/select,\"{}\"", path.display());
+        shell_execute("explorer.exe", Some(&args));
+    }
+
+    /// Replace the tasks in the application's jump list.
+    pub fn set_jump_list(tasks: &[JumpListTask]) {
+        if let Err(e) = taskbar::set_jump_list(tasks) {
+            warn!("failed to set jump list: {:?}", e);
+        }
+    }
+
+    /// Add a file to the recent documents shown in the application's
+    /// jump list.
+    pub fn add_recent_document(path: &Path) {
+        taskbar::add_recent_document(path);
+    }
+
+    /// Whether high contrast is turned on in the system's accessibility
+    /// settings.
+    pub fn is_high_contrast() -> bool {
+        let mut info = HIGHCONTRASTW {
+            cbSize: mem::size_of::<HIGHCONTRASTW>() as UINT,
+            dwFlags: 0,
+            lpszDefaultScheme: ptr::null_mut(),
+        };
+        unsafe {
+            let ok = SystemParametersInfoW(
+                SPI_GETHIGHCONTRAST,
+                info.cbSize,
+                &mut info as *mut HIGHCONTRASTW as *mut _,
+                0,
+            );
+            ok != FALSE && info.dwFlags & HCF_HIGHCONTRASTON != 0
+        }
+    }
+
+    /// Whether animations are turned off in the system's accessibility
+    /// settings.
+    pub fn prefers_reduced_motion() -> bool {
+        let mut animate: BOOL = TRUE;
+        unsafe {
+            let ok = SystemParametersInfoW(
+                SPI_GETCLIENTAREAANIMATION,
+                0,
+                &mut animate as *mut BOOL as *mut _,
+                0,
+            );
+            ok != FALSE && animate == FALSE
+        }
+    }
+
+    /// Make a font available to the application, from the contents of a
+    /// TrueType or OpenType file.
+    //TODO: fonts added with `AddFontMemResourceEx` aren't visible to
+    // DirectWrite; this needs a custom font collection loader.
+    pub fn register_font(_data: &[u8]) {
+        warn!("registering fonts is not yet supported on Windows");
+    }
+
     /// Returns the contents of the clipboard, if any.
     pub fn get_clipboard_contents() -> Option<ClipboardItem> {
         unsafe {
@@ -63,13 +137,34 @@ impl Application {
                     GlobalUnlock(wstr_copy);
                     SetClipboardData(CF_UNICODETEXT, wstr_copy);
                 }
-                other => log::warn!("unhandled clipboard data {:?}", other),
+                other => warn!("unhandled clipboard data {:?}", other),
             }
             CloseClipboard();
         }
     }
 }
 
+fn shell_execute(file: &str, params: Option<&str>) {
+    let verb = "open".to_wide();
+    let file_wide = file.to_wide();
+    let params = params.map(|p| p.to_wide());
+    let params_ptr = params.as_ref().map(|p| p.as_ptr()).unwrap_or(ptr::null());
+    unsafe {
+        // ShellExecute returns a value greater than 32 on success.
+        let result = ShellExecuteW(
+            ptr::null_mut(),
+            verb.as_ptr(),
+            file_wide.as_ptr(),
+            params_ptr,
+            ptr::null(),
+            SW_SHOWNORMAL,
+        );
+        if result as usize <= 32 {
+            warn!("failed to open '{}', error={}", file, result as usize);
+        }
+    }
+}
+
 unsafe fn get_clipboard_impl() -> Option<ClipboardItem> {
     for format in iter_clipboard_types() {
         match format {
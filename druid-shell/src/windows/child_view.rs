@@ -0,0 +1,194 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Native child windows, Windows implementation.
+
+use std::cell::RefCell;
+use std::ffi::c_void;
+use std::ptr::null_mut;
+
+use winapi::shared::minwindef::HINSTANCE;
+use winapi::shared::windef::{HBRUSH, HICON, HWND};
+use winapi::um::libloaderapi::GetModuleHandleW;
+use winapi::um::winuser::*;
+
+use raw_window_handle::windows::WindowsHandle;
+use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+
+use crate::kurbo::Rect;
+use crate::util::ToWide;
+use crate::Error;
+
+use super::WindowHandle;
+
+/// The window class of surfaces created by [`ChildView::new_surface`].
+///
+/// [`ChildView::new_surface`]: struct.ChildView.html#method.new_surface
+const SURFACE_CLASS_NAME: &str = "druid-surface";
+
+lazy_static! {
+    /// Whether the surface window class was registered.
+    static ref SURFACE_CLASS: bool = unsafe {
+        let class_name = SURFACE_CLASS_NAME.to_wide();
+        let wnd = WNDCLASSW {
+            // OpenGL needs a device context of its own.
+            style: CS_OWNDC | CS_HREDRAW | CS_VREDRAW,
+            lpfnWndProc: Some(DefWindowProcW),
+            cbClsExtra: 0,
+            cbWndExtra: 0,
+            hInstance: GetModuleHandleW(null_mut()),
+            hIcon: 0 as HICON,
+            hCursor: LoadCursorW(0 as HINSTANCE, IDC_ARROW),
+            hbrBackground: 0 as HBRUSH,
+            lpszMenuName: null_mut(),
+            lpszClassName: class_name.as_ptr(),
+        };
+        RegisterClassW(&wnd) != 0
+    };
+}
+
+/// A window shown inside a druid window, either created by other code or
+/// created as a surface for GPU rendering.
+pub struct ChildView {
+    hwnd: HWND,
+    parent: RefCell<Option<WindowHandle>>,
+    /// Whether we created the window, and so should destroy it.
+    owned: bool,
+}
+
+impl ChildView {
+    /// Wrap a window, which will become a child of the druid window when
+    /// attached.
+    ///
+    /// # Safety
+    ///
+    /// The window must stay valid for as long as this is alive.
+    pub unsafe fn from_hwnd(hwnd: HWND) -> ChildView {
+        ChildView {
+            hwnd,
+            parent: RefCell::new(None),
+            owned: false,
+        }
+    }
+
+    /// Create an empty window, for rendering into with a graphics API such
+    /// as Direct3D, Vulkan, or OpenGL. The window is destroyed when this is
+    /// dropped.
+    pub fn new_surface() -> Result<ChildView, Error> {
+        if !*SURFACE_CLASS {
+            return Err(Error::Null);
+        }
+        let class_name = SURFACE_CLASS_NAME.to_wide();
+        let hwnd = unsafe {
+            CreateWindowExW(
+                0,
+                class_name.as_ptr(),
+                null_mut(),
+                WS_POPUP | WS_CLIPSIBLINGS | WS_CLIPCHILDREN,
+                0,
+                0,
+                1,
+                1,
+                null_mut(),
+                null_mut(),
+                GetModuleHandleW(null_mut()),
+                null_mut(),
+            )
+        };
+        if hwnd.is_null() {
+            return Err(Error::Null);
+        }
+        Ok(ChildView {
+            hwnd,
+            parent: RefCell::new(None),
+            owned: true,
+        })
+    }
+
+    /// The wrapped window.
+    pub fn hwnd(&self) -> HWND {
+        self.hwnd
+    }
+
+    /// Make the view a child of the window, initially hidden.
+    pub fn attach(&self, parent: &WindowHandle) {
+        if let Some(parent_hwnd) = parent.get_hwnd() {
+            unsafe {
+                let style = GetWindowLongPtrW(self.hwnd, GWL_STYLE) as u32;
+                let style = (style & !(WS_POPUP | WS_CAPTION | WS_THICKFRAME)) | WS_CHILD;
+                SetWindowLongPtrW(self.hwnd, GWL_STYLE, style as _);
+                ShowWindow(self.hwnd, SW_HIDE);
+                SetParent(self.hwnd, parent_hwnd);
+            }
+            *self.parent.borrow_mut() = Some(parent.clone());
+        }
+    }
+
+    /// Set the area covered by the view, in px relative to the window.
+    pub fn set_frame(&self, frame: Rect) {
+        if let Some(parent) = self.parent.borrow().as_ref() {
+            let (x0, y0) = parent.px_to_pixels_xy(frame.x0 as f32, frame.y0 as f32);
+            let (x1, y1) = parent.px_to_pixels_xy(frame.x1 as f32, frame.y1 as f32);
+            unsafe {
+                SetWindowPos(
+                    self.hwnd,
+                    null_mut(),
+                    x0,
+                    y0,
+                    x1 - x0,
+                    y1 - y0,
+                    SWP_NOZORDER | SWP_NOACTIVATE,
+                );
+            }
+        }
+    }
+
+    pub fn set_visible(&self, visible: bool) {
+        let cmd = if visible { SW_SHOWNA } else { SW_HIDE };
+        unsafe {
+            ShowWindow(self.hwnd, cmd);
+        }
+    }
+
+    /// Remove the view from its window, hiding it.
+    pub fn detach(&self) {
+        if self.parent.borrow_mut().take().is_some() {
+            unsafe {
+                ShowWindow(self.hwnd, SW_HIDE);
+                SetParent(self.hwnd, null_mut());
+            }
+        }
+    }
+}
+
+unsafe impl HasRawWindowHandle for ChildView {
+    fn raw_window_handle(&self) -> RawWindowHandle {
+        let hinstance = unsafe { GetWindowLongPtrW(self.hwnd, GWLP_HINSTANCE) as *mut c_void };
+        RawWindowHandle::Windows(WindowsHandle {
+            hwnd: self.hwnd as *mut c_void,
+            hinstance,
+            ..WindowsHandle::empty()
+        })
+    }
+}
+
+impl Drop for ChildView {
+    fn drop(&mut self) {
+        if self.owned {
+            unsafe {
+                DestroyWindow(self.hwnd);
+            }
+        }
+    }
+}
@@ -0,0 +1,152 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Input method support, Windows implementation.
+//!
+//! We draw the composition ourselves, so the default composition window is
+//! suppressed, and the composition string is read on each
+//! `WM_IME_COMPOSITION`. See [Input Method Manager] for details.
+//!
+//! [Input Method Manager]: https://docs.microsoft.com/en-us/windows/win32/intl/input-method-manager
+
+#![allow(non_snake_case)]
+
+use std::ptr::null_mut;
+
+use winapi::ctypes::c_void;
+use winapi::shared::minwindef::*;
+use winapi::shared::ntdef::LONG;
+use winapi::shared::windef::{HWND, POINT, RECT};
+
+use crate::ime::{utf16_to_utf8_offset, ImeEvent};
+use crate::kurbo::Rect;
+
+// TODO: remove these when they get added to winapi
+type HIMC = *mut c_void;
+
+const GCS_COMPSTR: DWORD = 0x0008;
+const GCS_CURSORPOS: DWORD = 0x0080;
+const GCS_RESULTSTR: DWORD = 0x0800;
+const CFS_EXCLUDE: DWORD = 0x0080;
+const IACE_DEFAULT: DWORD = 0x0010;
+const NI_COMPOSITIONSTR: DWORD = 0x0015;
+const CPS_CANCEL: DWORD = 0x0004;
+pub(crate) const ISC_SHOWUICOMPOSITIONWINDOW: LPARAM = 0x8000_0000u32 as LPARAM;
+
+#[repr(C)]
+struct CANDIDATEFORM {
+    dwIndex: DWORD,
+    dwStyle: DWORD,
+    ptCurrentPos: POINT,
+    rcArea: RECT,
+}
+
+#[link(name = "imm32")]
+extern "system" {
+    fn ImmGetContext(hwnd: HWND) -> HIMC;
+    fn ImmReleaseContext(hwnd: HWND, himc: HIMC) -> BOOL;
+    fn ImmGetCompositionStringW(himc: HIMC, index: DWORD, buf: LPVOID, len: DWORD) -> LONG;
+    fn ImmSetCandidateWindow(himc: HIMC, form: *mut CANDIDATEFORM) -> BOOL;
+    fn ImmAssociateContextEx(hwnd: HWND, himc: HIMC, flags: DWORD) -> BOOL;
+    fn ImmNotifyIME(himc: HIMC, action: DWORD, index: DWORD, value: DWORD) -> BOOL;
+}
+
+/// Enable or disable the input method for a window, cancelling any
+/// composition in progress when disabling.
+pub(crate) fn set_enabled(hwnd: HWND, enabled: bool) {
+    unsafe {
+        if enabled {
+            ImmAssociateContextEx(hwnd, null_mut(), IACE_DEFAULT);
+        } else {
+            let himc = ImmGetContext(hwnd);
+            if !himc.is_null() {
+                ImmNotifyIME(himc, NI_COMPOSITIONSTR, CPS_CANCEL, 0);
+                ImmReleaseContext(hwnd, himc);
+            }
+            ImmAssociateContextEx(hwnd, null_mut(), 0);
+        }
+    }
+}
+
+/// Place the candidate window next to the text cursor, which is given in
+/// physical pixels.
+pub(crate) fn set_cursor_rect(hwnd: HWND, rect: Rect) {
+    unsafe {
+        let himc = ImmGetContext(hwnd);
+        if himc.is_null() {
+            return;
+        }
+        let area = RECT {
+            left: rect.x0 as i32,
+            top: rect.y0 as i32,
+            right: rect.x1 as i32,
+            bottom: rect.y1 as i32,
+        };
+        let mut form = CANDIDATEFORM {
+            dwIndex: 0,
+            dwStyle: CFS_EXCLUDE,
+            ptCurrentPos: POINT {
+                x: area.left,
+                y: area.bottom,
+            },
+            rcArea: area,
+        };
+        ImmSetCandidateWindow(himc, &mut form);
+        ImmReleaseContext(hwnd, himc);
+    }
+}
+
+/// Read the composition state after a `WM_IME_COMPOSITION` message.
+///
+/// A message can both commit text and start a new composition, in which
+/// case the commit comes first.
+pub(crate) fn composition_events(hwnd: HWND, lparam: LPARAM) -> Vec<ImeEvent> {
+    let mut events = Vec::new();
+    unsafe {
+        let himc = ImmGetContext(hwnd);
+        if himc.is_null() {
+            return events;
+        }
+        let flags = lparam as DWORD;
+        if flags & GCS_RESULTSTR != 0 {
+            if let Some(text) = get_string(himc, GCS_RESULTSTR) {
+                events.push(ImeEvent::Commit(text));
+            }
+        }
+        if flags & GCS_COMPSTR != 0 {
+            if let Some(text) = get_string(himc, GCS_COMPSTR) {
+                let cursor = if flags & GCS_CURSORPOS != 0 {
+                    let pos = ImmGetCompositionStringW(himc, GCS_CURSORPOS, null_mut(), 0);
+                    Some(utf16_to_utf8_offset(&text, pos.max(0) as usize))
+                } else {
+                    None
+                };
+                events.push(ImeEvent::Preedit { text, cursor });
+            }
+        }
+        ImmReleaseContext(hwnd, himc);
+    }
+    events
+}
+
+unsafe fn get_string(himc: HIMC, index: DWORD) -> Option<String> {
+    // The length is in bytes, and may be negative on error.
+    let len = ImmGetCompositionStringW(himc, index, null_mut(), 0);
+    if len < 0 {
+        return None;
+    }
+    let mut buf = vec![0u16; len as usize / 2];
+    ImmGetCompositionStringW(himc, index, buf.as_mut_ptr() as LPVOID, len as DWORD);
+    Some(String::from_utf16_lossy(&buf))
+}
@@ -22,12 +22,14 @@ use winapi::shared::windef::*;
 use winapi::um::winuser::*;
 
 use crate::hotkey::HotKey;
+use crate::image::RgbaImage;
 use crate::util::ToWide;
 
 /// A menu object, which can be either a top-level menubar or a
 /// submenu.
 pub struct Menu {
     hmenu: HMENU,
+    accels: Vec<(u32, HotKey)>,
 }
 
 impl Drop for Menu {
@@ -43,7 +45,10 @@ impl Menu {
     pub fn new() -> Menu {
         unsafe {
             let hmenu = CreateMenu();
-            Menu { hmenu }
+            Menu {
+                hmenu,
+                accels: Vec::new(),
+            }
         }
     }
 
@@ -51,14 +56,24 @@ impl Menu {
     pub fn new_for_popup() -> Menu {
         unsafe {
             let hmenu = CreatePopupMenu();
-            Menu { hmenu }
+            Menu {
+                hmenu,
+                accels: Vec::new(),
+            }
         }
     }
 
     pub fn into_hmenu(self) -> HMENU {
+        self.into_parts().0
+    }
+
+    /// Give up ownership of the menu, along with the hotkeys of its
+    /// enabled items, which the window matches against key presses.
+    pub(crate) fn into_parts(mut self) -> (HMENU, Vec<(u32, HotKey)>) {
         let hmenu = self.hmenu;
+        let accels = mem::replace(&mut self.accels, Vec::new());
         mem::forget(self);
-        hmenu
+        (hmenu, accels)
     }
 
     /// Add a dropdown menu. This takes the menu by ownership, but we'll
@@ -66,6 +81,10 @@ impl Menu {
     ///
     /// The `text` field has all the fun behavior of winapi CreateMenu.
     pub fn add_dropdown(&mut self, menu: Menu, text: &str, enabled: bool) {
+        let (hmenu, accels) = menu.into_parts();
+        if enabled {
+            self.accels.extend(accels);
+        }
         unsafe {
             let mut flags = MF_POPUP;
             if !enabled {
@@ -74,22 +93,32 @@ impl Menu {
             AppendMenuW(
                 self.hmenu,
                 flags,
-                menu.into_hmenu() as UINT_PTR,
+                hmenu as UINT_PTR,
                 text.to_wide().as_ptr(),
             );
         }
     }
 
     /// Add an item to the menu.
+    ///
+    /// The hotkey, if any, is shown after the item's text. Windows menus
+    /// don't handle their own shortcuts, so the window checks key presses
+    /// against the hotkeys of its menu before passing them on.
     pub fn add_item(
         &mut self,
         id: u32,
         text: &str,
-        _key: Option<&HotKey>,
+        key: Option<&HotKey>,
         enabled: bool,
         selected: bool,
     ) {
-        // TODO: actually wire up accelerators for key.
+        let text = match key {
+            Some(key) => format!("{}\t{}", text, key.label()),
+            None => text.to_string(),
+        };
+        if let Some(key) = key.filter(|_| enabled) {
+            self.accels.push((id, key.clone()));
+        }
         unsafe {
             let mut flags = MF_STRING;
             if !enabled {
@@ -108,4 +137,9 @@ impl Menu {
             AppendMenuW(self.hmenu, MF_SEPARATOR, 0, null());
         }
     }
+
+    /// Set the icon shown beside an item already added to this menu.
+    ///
+    /// Icons are not yet shown in Windows menus, so this does nothing.
+    pub fn set_item_icon(&mut self, _id: u32, _icon: &RgbaImage) {}
 }
@@ -17,14 +17,27 @@
 #![allow(non_snake_case)]
 
 pub mod application;
+mod child_view;
 pub mod dcomp;
 pub mod dialog;
+mod ime;
 pub mod menu;
 pub mod paint;
+pub mod taskbar;
 mod timers;
 pub mod util;
+#[cfg(feature = "video")]
+mod video;
+#[cfg(feature = "webview")]
+mod webview;
 pub mod win_main;
 
+pub use child_view::ChildView;
+#[cfg(feature = "video")]
+pub use video::VideoDecoder;
+#[cfg(feature = "webview")]
+pub use webview::WebView;
+
 use std::any::Any;
 use std::cell::{Cell, RefCell};
 use std::ffi::OsString;
@@ -33,6 +46,7 @@ use std::ops::Deref;
 use std::ptr::{null, null_mut};
 use std::rc::{Rc, Weak};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use log::{debug, error, warn};
 
@@ -46,6 +60,9 @@ use winapi::shared::minwindef::*;
 use winapi::shared::windef::*;
 use winapi::shared::winerror::*;
 use winapi::um::d2d1::*;
+use winapi::um::dwmapi::{DwmGetCompositionTimingInfo, DWM_TIMING_INFO};
+use winapi::um::profileapi::{QueryPerformanceCounter, QueryPerformanceFrequency};
+use winapi::um::shellapi::*;
 use winapi::um::unknwnbase::*;
 use winapi::um::winnt::*;
 use winapi::um::winuser::*;
@@ -56,16 +73,25 @@ use direct2d::math::SizeU;
 use direct2d::render_target::{GenericRenderTarget, HwndRenderTarget, RenderTarget};
 
 use piet_common::{Piet, RenderContext};
+use raw_window_handle::windows::WindowsHandle;
+use raw_window_handle::RawWindowHandle;
 
 use crate::application::Application;
 use crate::clipboard::ClipboardItem;
+use crate::common_util::ClickCounter;
 use crate::dialog::{FileDialogOptions, FileDialogType};
+use crate::dnd::{DragContents, DragData, DragEvent, DropEffect};
+use crate::hotkey::HotKey;
+use crate::ime::ImeEvent;
 use crate::keyboard::{KeyCode, KeyEvent, KeyModifiers};
-use crate::kurbo::{Point, Vec2};
+use crate::kurbo::{Point, Rect, Vec2};
 use crate::menu::Menu;
+use crate::print::{PageSetup, Printable};
+use crate::taskbar::TaskbarProgress;
 use crate::util::{as_result, FromWide, ToWide, OPTIONAL_FUNCTIONS};
 use crate::window::{
-    self, Cursor, FileInfo, MouseButton, MouseEvent, Text, TimerToken, WinCtx, WinHandler,
+    self, Cursor, FileInfo, IdleToken, MouseButton, MouseEvent, PenEvent, PenEventKind, Text,
+    TimerToken, WheelEvent, WinCtx, WinHandler,
 };
 use crate::Error;
 
@@ -77,6 +103,37 @@ extern "system" {
     pub fn DwmFlush();
 }
 
+/// The time of the compositor's next vertical blank, when a frame presented
+/// now is expected to be shown.
+fn next_vblank() -> Option<Instant> {
+    unsafe {
+        let mut info: DWM_TIMING_INFO = mem::zeroed();
+        info.cbSize = mem::size_of::<DWM_TIMING_INFO>() as u32;
+        // Since Windows 8.1, timing is only available for the whole desktop.
+        if FAILED(DwmGetCompositionTimingInfo(null_mut(), &mut info)) {
+            return None;
+        }
+        let mut now = mem::zeroed();
+        let mut frequency = mem::zeroed();
+        if QueryPerformanceCounter(&mut now) == 0 || QueryPerformanceFrequency(&mut frequency) == 0
+        {
+            return None;
+        }
+        let now = *now.QuadPart() as u64;
+        let frequency = *frequency.QuadPart() as u64;
+        let period = info.qpcRefreshPeriod;
+        if period == 0 || frequency == 0 {
+            return None;
+        }
+        let mut vblank = info.qpcVBlank;
+        if vblank <= now {
+            vblank += ((now - vblank) / period + 1) * period;
+        }
+        let ahead = (vblank - now) as f64 / frequency as f64;
+        Some(Instant::now() + Duration::from_secs_f64(ahead))
+    }
+}
+
 /// Builder abstraction for creating new windows.
 pub struct WindowBuilder {
     handler: Option<Box<dyn WinHandler>>,
@@ -84,6 +141,8 @@ pub struct WindowBuilder {
     title: String,
     menu: Option<Menu>,
     present_strategy: PresentStrategy,
+    /// The window to create this one as a child of, if any.
+    parent: Option<HWND>,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -127,7 +186,7 @@ pub struct WindowHandle {
 #[derive(Clone)]
 pub struct IdleHandle {
     pub(crate) hwnd: HWND,
-    queue: Arc<Mutex<Vec<Box<dyn IdleCallback>>>>,
+    queue: Arc<Mutex<Vec<IdleKind>>>,
 }
 
 trait IdleCallback: Send {
@@ -140,13 +199,23 @@ impl<F: FnOnce(&mut dyn Any) + Send> IdleCallback for F {
     }
 }
 
+/// An entry in a window's idle queue.
+enum IdleKind {
+    Callback(Box<dyn IdleCallback>),
+    Token(IdleToken),
+}
+
 /// This is the low level window state. All mutable contents are protected
 /// by interior mutability, so we can handle reentrant calls.
 struct WindowState {
     hwnd: Cell<HWND>,
     dpi: Cell<f32>,
     wndproc: Box<dyn WndProc>,
-    idle_queue: Arc<Mutex<Vec<Box<dyn IdleCallback>>>>,
+    idle_queue: Arc<Mutex<Vec<IdleKind>>>,
+    /// Whether presenting a frame waits for the display's vertical blank.
+    vsync: Cell<bool>,
+    /// The hotkeys of the window's menu, with the ids of their items.
+    accels: RefCell<Vec<(u32, HotKey)>>,
 
     // This field doesn't really need to be shared; it could be plumbed
     // as a mutable reference down through WinCtx, but that would require
@@ -183,6 +252,7 @@ struct WndState {
     /// The `char` of the last `WM_CHAR` event, if there has not already been
     /// a `WM_KEYUP` event.
     stashed_char: Option<char>,
+    click_counter: ClickCounter,
     //TODO: track surrogate orphan
 }
 
@@ -190,12 +260,14 @@ struct WndState {
 struct WinCtxOwner<'a> {
     handle: std::cell::Ref<'a, WindowHandle>,
     dwrite: &'a directwrite::Factory,
+    presentation_time: Option<Instant>,
 }
 
 /// The Windows implementation of the context provided to WinHandler calls.
 struct WinCtxImpl<'a> {
     handle: &'a WindowHandle,
     text: Text<'a>,
+    presentation_time: Option<Instant>,
 }
 
 /// State for DirectComposition. This is optional because it is only supported
@@ -292,6 +364,55 @@ impl MyWndProc {
             msg, hwnd, wparam, lparam
         );
     }
+
+    /// The id of the menu item whose hotkey matches this key event, if any.
+    ///
+    /// Menu hotkeys take precedence over the handler, as they do on other
+    /// platforms.
+    fn menu_accelerator(&self, event: &KeyEvent) -> Option<u32> {
+        let state = self.handle.borrow().state.upgrade()?;
+        let accels = state.accels.borrow();
+        accels
+            .iter()
+            .find(|(_, key)| key.matches(event))
+            .map(|(id, _)| *id)
+    }
+
+    /// Convert the state of a pen pointer to a `PenEvent`.
+    fn pen_event(&self, hwnd: HWND, info: &POINTER_PEN_INFO, kind: PenEventKind) -> PenEvent {
+        let mut point = info.pointerInfo.ptPixelLocation;
+        unsafe {
+            ScreenToClient(hwnd, &mut point);
+        }
+        let (px, py) = self.handle.borrow().pixels_to_px_xy(point.x, point.y);
+        let in_contact = info.pointerInfo.pointerFlags & POINTER_FLAG_INCONTACT != 0;
+        let pressure = if !in_contact {
+            0.0
+        } else if info.penMask & PEN_MASK_PRESSURE != 0 {
+            // The pressure is reported in the range 0 to 1024.
+            f64::from(info.pressure) / 1024.0
+        } else {
+            1.0
+        };
+        let tilt_x = if info.penMask & PEN_MASK_TILT_X != 0 {
+            f64::from(info.tiltX)
+        } else {
+            0.0
+        };
+        let tilt_y = if info.penMask & PEN_MASK_TILT_Y != 0 {
+            f64::from(info.tiltY)
+        } else {
+            0.0
+        };
+        PenEvent {
+            pos: Point::new(px as f64, py as f64),
+            mods: get_mod_state(),
+            kind,
+            pressure,
+            tilt: Vec2::new(tilt_x, tilt_y),
+            is_eraser: info.penFlags & (PEN_FLAG_ERASER | PEN_FLAG_INVERTED) != 0,
+        }
+    }
 }
 
 impl<'a> WinCtxOwner<'a> {
@@ -299,7 +420,11 @@ impl<'a> WinCtxOwner<'a> {
         handle: std::cell::Ref<'a, WindowHandle>,
         dwrite: &'a directwrite::Factory,
     ) -> WinCtxOwner<'a> {
-        WinCtxOwner { handle, dwrite }
+        WinCtxOwner {
+            handle,
+            dwrite,
+            presentation_time: None,
+        }
     }
 
     fn ctx<'b>(&'b mut self) -> WinCtxImpl<'b>
@@ -310,6 +435,7 @@ impl<'a> WinCtxOwner<'a> {
         WinCtxImpl {
             handle: self.handle.deref(),
             text,
+            presentation_time: self.presentation_time,
         }
     }
 }
@@ -341,8 +467,12 @@ impl WndProc for MyWndProc {
                             .map(|rt| rt.as_generic());
                         s.render_target = rt.ok();
                     }
+                    let vsync = self.handle.borrow().vsync();
                     let mut c = WinCtxOwner::new(self.handle.borrow(), &self.dwrite_factory);
                     s.handler.rebuild_resources(&mut c.ctx());
+                    if vsync {
+                        c.presentation_time = next_vblank();
+                    }
                     s.render(
                         &self.d2d_factory,
                         &self.dwrite_factory,
@@ -351,7 +481,7 @@ impl WndProc for MyWndProc {
                     );
                     if let Some(ref mut ds) = s.dcomp_state {
                         if !ds.sizing {
-                            (*ds.swap_chain).Present(1, 0);
+                            (*ds.swap_chain).Present(if vsync { 1 } else { 0 }, 0);
                             let _ = ds.dcomp_device.commit();
                         }
                     }
@@ -531,7 +661,10 @@ impl WndProc for MyWndProc {
                     let event = KeyEvent::new(key_code, is_repeat, modifiers, text, text);
 
                     let mut c = WinCtxOwner::new(self.handle.borrow(), &self.dwrite_factory);
-                    if s.handler.key_down(event, &mut c.ctx()) {
+                    if let Some(id) = self.menu_accelerator(&event) {
+                        s.handler.command(id, &mut c.ctx());
+                        Some(0)
+                    } else if s.handler.key_down(event, &mut c.ctx()) {
                         Some(0)
                     } else {
                         None
@@ -559,7 +692,10 @@ impl WndProc for MyWndProc {
                     let event = KeyEvent::new(key_code, is_repeat, modifiers, "", "");
 
                     let mut c = WinCtxOwner::new(self.handle.borrow(), &self.dwrite_factory);
-                    if s.handler.key_down(event, &mut c.ctx()) {
+                    if let Some(id) = self.menu_accelerator(&event) {
+                        s.handler.command(id, &mut c.ctx());
+                        Some(0)
+                    } else if s.handler.key_down(event, &mut c.ctx()) {
                         Some(0)
                     } else {
                         None
@@ -584,6 +720,39 @@ impl WndProc for MyWndProc {
                 }
                 Some(0)
             }
+            WM_IME_SETCONTEXT => unsafe {
+                // We show the composition inline, so hide the system's window.
+                let lparam = lparam & !ime::ISC_SHOWUICOMPOSITIONWINDOW;
+                Some(DefWindowProcW(hwnd, msg, wparam, lparam))
+            },
+            WM_IME_STARTCOMPOSITION => Some(0),
+            WM_IME_COMPOSITION => {
+                if let Ok(mut s) = self.state.try_borrow_mut() {
+                    let s = s.as_mut().unwrap();
+                    let mut c = WinCtxOwner::new(self.handle.borrow(), &self.dwrite_factory);
+                    for event in ime::composition_events(hwnd, lparam) {
+                        s.handler.ime(event, &mut c.ctx());
+                    }
+                } else {
+                    self.log_dropped_msg(hwnd, msg, wparam, lparam);
+                }
+                // Not passing this on means we don't also get `WM_CHAR`s
+                // for the result string.
+                Some(0)
+            }
+            WM_IME_ENDCOMPOSITION => {
+                // A cancelled composition ends without a result.
+                if let Ok(mut s) = self.state.try_borrow_mut() {
+                    let s = s.as_mut().unwrap();
+                    let mut c = WinCtxOwner::new(self.handle.borrow(), &self.dwrite_factory);
+                    let event = ImeEvent::Preedit {
+                        text: String::new(),
+                        cursor: None,
+                    };
+                    s.handler.ime(event, &mut c.ctx());
+                }
+                Some(0)
+            }
             //TODO: WM_SYSCOMMAND
             WM_MOUSEWHEEL => {
                 // TODO: apply mouse sensitivity based on
@@ -594,7 +763,8 @@ impl WndProc for MyWndProc {
                     let delta = Vec2::new(0.0, -delta_y);
                     let mods = get_mod_state();
                     let mut c = WinCtxOwner::new(self.handle.borrow(), &self.dwrite_factory);
-                    s.handler.wheel(delta, mods, &mut c.ctx());
+                    s.handler
+                        .wheel(&WheelEvent::wheel(delta, mods), &mut c.ctx());
                 } else {
                     self.log_dropped_msg(hwnd, msg, wparam, lparam);
                 }
@@ -607,12 +777,35 @@ impl WndProc for MyWndProc {
                     let delta = Vec2::new(delta_x, 0.0);
                     let mods = get_mod_state();
                     let mut c = WinCtxOwner::new(self.handle.borrow(), &self.dwrite_factory);
-                    s.handler.wheel(delta, mods, &mut c.ctx());
+                    s.handler
+                        .wheel(&WheelEvent::wheel(delta, mods), &mut c.ctx());
                 } else {
                     self.log_dropped_msg(hwnd, msg, wparam, lparam);
                 }
                 Some(0)
             }
+            WM_POINTERDOWN | WM_POINTERUPDATE | WM_POINTERUP => {
+                // The low word of wparam is the pointer id.
+                let pointer_id = LOWORD(wparam as u32) as UINT32;
+                let pen_info = unsafe { get_pen_info(pointer_id) };
+                if let Some(info) = pen_info {
+                    if let Ok(mut s) = self.state.try_borrow_mut() {
+                        let s = s.as_mut().unwrap();
+                        let kind = match msg {
+                            WM_POINTERDOWN => PenEventKind::Down,
+                            WM_POINTERUP => PenEventKind::Up,
+                            _ => PenEventKind::Move,
+                        };
+                        let event = self.pen_event(hwnd, &info, kind);
+                        let mut c = WinCtxOwner::new(self.handle.borrow(), &self.dwrite_factory);
+                        s.handler.pen(&event, &mut c.ctx());
+                    } else {
+                        self.log_dropped_msg(hwnd, msg, wparam, lparam);
+                    }
+                }
+                // Let the system go on to generate the mouse messages.
+                None
+            }
             WM_MOUSEMOVE => {
                 if let Ok(mut s) = self.state.try_borrow_mut() {
                     let s = s.as_mut().unwrap();
@@ -644,8 +837,8 @@ impl WndProc for MyWndProc {
                 }
                 Some(0)
             }
-            // TODO: not clear where double-click processing should happen. Currently disabled
-            // because CS_DBLCLKS is not set
+            // We count clicks ourselves, rather than setting CS_DBLCLKS, so that
+            // we can report triple clicks and beyond.
             WM_LBUTTONDBLCLK | WM_LBUTTONDOWN | WM_LBUTTONUP | WM_MBUTTONDBLCLK
             | WM_MBUTTONDOWN | WM_MBUTTONUP | WM_RBUTTONDBLCLK | WM_RBUTTONDOWN | WM_RBUTTONUP
             | WM_XBUTTONDBLCLK | WM_XBUTTONDOWN | WM_XBUTTONUP => {
@@ -667,17 +860,21 @@ impl WndProc for MyWndProc {
                         }
                         _ => unreachable!(),
                     };
-                    let count = match msg {
-                        WM_LBUTTONDOWN | WM_MBUTTONDOWN | WM_RBUTTONDOWN | WM_XBUTTONDOWN => 1,
-                        WM_LBUTTONDBLCLK | WM_MBUTTONDBLCLK | WM_RBUTTONDBLCLK
-                        | WM_XBUTTONDBLCLK => 2,
-                        WM_LBUTTONUP | WM_MBUTTONUP | WM_RBUTTONUP | WM_XBUTTONUP => 0,
-                        _ => unreachable!(),
-                    };
                     let x = LOWORD(lparam as u32) as i16 as i32;
                     let y = HIWORD(lparam as u32) as i16 as i32;
                     let (px, py) = self.handle.borrow().pixels_to_px_xy(x, y);
                     let pos = Point::new(px as f64, py as f64);
+                    let count = match msg {
+                        WM_LBUTTONDOWN | WM_MBUTTONDOWN | WM_RBUTTONDOWN | WM_XBUTTONDOWN
+                        | WM_LBUTTONDBLCLK | WM_MBUTTONDBLCLK | WM_RBUTTONDBLCLK
+                        | WM_XBUTTONDBLCLK => {
+                            let (interval, distance) = unsafe { multi_click_limits(s.dpi) };
+                            s.click_counter.set_limits(interval, distance);
+                            s.click_counter.count_for_click(Instant::now(), pos, button)
+                        }
+                        WM_LBUTTONUP | WM_MBUTTONUP | WM_RBUTTONUP | WM_XBUTTONUP => 0,
+                        _ => unreachable!(),
+                    };
                     let mods = get_mod_state();
                     let event = MouseEvent {
                         pos,
@@ -696,6 +893,56 @@ impl WndProc for MyWndProc {
                 }
                 Some(0)
             }
+            // Note: WM_DROPFILES only tells us about the drop itself; we get no
+            // notification while the drag is in progress, so there's no way to
+            // give feedback about whether the drop will be accepted. Doing that
+            // requires implementing IDropTarget.
+            WM_DROPFILES => unsafe {
+                if let Ok(mut s) = self.state.try_borrow_mut() {
+                    let s = s.as_mut().unwrap();
+                    let hdrop = wparam as HDROP;
+                    let mut data = DragData::default();
+                    let count = DragQueryFileW(hdrop, 0xFFFF_FFFF, null_mut(), 0);
+                    for i in 0..count {
+                        let len = DragQueryFileW(hdrop, i, null_mut(), 0) as usize;
+                        let mut buf = vec![0u16; len + 1];
+                        DragQueryFileW(hdrop, i, buf.as_mut_ptr(), buf.len() as UINT);
+                        data.files.push(buf[..len].to_os_string().into());
+                    }
+                    let mut point = POINT { x: 0, y: 0 };
+                    DragQueryPoint(hdrop, &mut point);
+                    DragFinish(hdrop);
+
+                    let (px, py) = self.handle.borrow().pixels_to_px_xy(point.x, point.y);
+                    let event = DragEvent {
+                        pos: Point::new(px as f64, py as f64),
+                        mods: get_mod_state(),
+                        data,
+                    };
+                    let mut c = WinCtxOwner::new(self.handle.borrow(), &self.dwrite_factory);
+                    if s.handler.drag_enter(&event, &mut c.ctx()) == DropEffect::None {
+                        s.handler.drag_leave(&mut c.ctx());
+                    } else {
+                        s.handler.drag_drop(&event, &mut c.ctx());
+                    }
+                } else {
+                    self.log_dropped_msg(hwnd, msg, wparam, lparam);
+                }
+                Some(0)
+            },
+            WM_SETTINGCHANGE
+                if wparam == SPI_SETHIGHCONTRAST as WPARAM
+                    || wparam == SPI_SETCLIENTAREAANIMATION as WPARAM =>
+            {
+                if let Ok(mut s) = self.state.try_borrow_mut() {
+                    let s = s.as_mut().unwrap();
+                    let mut c = WinCtxOwner::new(self.handle.borrow(), &self.dwrite_factory);
+                    s.handler.accessibility_changed(&mut c.ctx());
+                } else {
+                    self.log_dropped_msg(hwnd, msg, wparam, lparam);
+                }
+                None
+            }
             WM_DESTROY => {
                 if let Ok(mut s) = self.state.try_borrow_mut() {
                     let s = s.as_mut().unwrap();
@@ -724,9 +971,12 @@ impl WndProc for MyWndProc {
                 if let Ok(mut s) = self.state.try_borrow_mut() {
                     let s = s.as_mut().unwrap();
                     let queue = self.handle.borrow().take_idle_queue();
-                    let handler_as_any = s.handler.as_any();
-                    for callback in queue {
-                        callback.call(handler_as_any);
+                    let mut c = WinCtxOwner::new(self.handle.borrow(), &self.dwrite_factory);
+                    for item in queue {
+                        match item {
+                            IdleKind::Callback(callback) => callback.call(s.handler.as_any()),
+                            IdleKind::Token(token) => s.handler.idle(token, &mut c.ctx()),
+                        }
                     }
                     Some(0)
                 } else {
@@ -755,6 +1005,7 @@ impl WindowBuilder {
             title: String::new(),
             menu: None,
             present_strategy: Default::default(),
+            parent: None,
         }
     }
 
@@ -785,6 +1036,18 @@ impl WindowBuilder {
         self.present_strategy = present_strategy;
     }
 
+    /// Create the window as a child of a window owned by another framework,
+    /// filling its client area.
+    ///
+    /// A child window has no menu. The host is responsible for resizing it
+    /// when the parent changes size.
+    pub fn set_parent(&mut self, parent: RawWindowHandle) {
+        match parent {
+            RawWindowHandle::Windows(handle) => self.parent = Some(handle.hwnd as HWND),
+            _ => warn!("unsupported parent window handle: {:?}", parent),
+        }
+    }
+
     pub fn build(self) -> Result<WindowHandle, Error> {
         unsafe {
             // Maybe separate registration in build api? Probably only need to
@@ -805,6 +1068,8 @@ impl WindowBuilder {
                 dpi: Cell::new(0.0),
                 wndproc: Box::new(wndproc),
                 idle_queue: Default::default(),
+                vsync: Cell::new(true),
+                accels: Default::default(),
                 timers: Arc::new(Mutex::new(TimerSlots::new(1))),
             };
             let win = Rc::new(window);
@@ -823,13 +1088,25 @@ impl WindowBuilder {
                 96.0
             };
             win.dpi.set(dpi);
-            let width = (500.0 * (dpi / 96.0)) as i32;
-            let height = (400.0 * (dpi / 96.0)) as i32;
-
-            let hmenu = match self.menu {
-                Some(menu) => menu.into_hmenu(),
-                None => 0 as HMENU,
+            let mut width = (500.0 * (dpi / 96.0)) as i32;
+            let mut height = (400.0 * (dpi / 96.0)) as i32;
+            let (mut x, mut y) = (CW_USEDEFAULT, CW_USEDEFAULT);
+            let mut dwStyle = self.dwStyle;
+
+            let (hmenu, accels) = match self.menu {
+                Some(menu) if self.parent.is_none() => menu.into_parts(),
+                _ => (0 as HMENU, Vec::new()),
             };
+            win.accels.replace(accels);
+            if let Some(parent) = self.parent {
+                let mut rect: RECT = mem::zeroed();
+                GetClientRect(parent, &mut rect);
+                x = 0;
+                y = 0;
+                width = rect.right - rect.left;
+                height = rect.bottom - rect.top;
+                dwStyle = WS_CHILD | WS_CLIPSIBLINGS | WS_CLIPCHILDREN;
+            }
             let mut dwExStyle = 0;
             if self.present_strategy == PresentStrategy::Flip {
                 dwExStyle |= WS_EX_NOREDIRECTIONBITMAP;
@@ -838,12 +1115,12 @@ impl WindowBuilder {
                 dwExStyle,
                 class_name.as_ptr(),
                 self.title.to_wide().as_ptr(),
-                self.dwStyle,
-                CW_USEDEFAULT,
-                CW_USEDEFAULT,
+                dwStyle,
+                x,
+                y,
                 width,
                 height,
-                0 as HWND,
+                self.parent.unwrap_or(0 as HWND),
                 hmenu,
                 0 as HINSTANCE,
                 win.clone(),
@@ -858,6 +1135,9 @@ impl WindowBuilder {
             });
 
             win.hwnd.set(hwnd);
+            DragAcceptFiles(hwnd, TRUE);
+            // The input method is enabled when a text widget asks for it.
+            ime::set_enabled(hwnd, false);
             let state = WndState {
                 handler: self.handler.unwrap(),
                 render_target: None,
@@ -865,6 +1145,7 @@ impl WindowBuilder {
                 dpi,
                 stashed_key_code: KeyCode::Unknown(0.into()),
                 stashed_char: None,
+                click_counter: ClickCounter::default(),
             };
             win.wndproc.connect(&handle, state);
             mem::drop(win);
@@ -873,6 +1154,35 @@ impl WindowBuilder {
     }
 }
 
+/// Get the state of the pointer of a `WM_POINTER*` message, if it is a pen.
+///
+/// The pointer API needs Windows 8; on earlier versions, pens only produce
+/// mouse messages.
+unsafe fn get_pen_info(pointer_id: UINT32) -> Option<POINTER_PEN_INFO> {
+    let get_pointer_type = OPTIONAL_FUNCTIONS.GetPointerType?;
+    let get_pointer_pen_info = OPTIONAL_FUNCTIONS.GetPointerPenInfo?;
+    let mut pointer_type = 0;
+    if get_pointer_type(pointer_id, &mut pointer_type) == FALSE || pointer_type != PT_PEN {
+        return None;
+    }
+    let mut info: POINTER_PEN_INFO = mem::zeroed();
+    if get_pointer_pen_info(pointer_id, &mut info) == FALSE {
+        return None;
+    }
+    Some(info)
+}
+
+/// The system double-click time, and the distance in px within which
+/// presses count as a multiple click.
+unsafe fn multi_click_limits(dpi: f32) -> (Duration, f64) {
+    let interval = Duration::from_millis(GetDoubleClickTime() as u64);
+    // These are the dimensions of a rectangle centered on the first click.
+    let width = GetSystemMetrics(SM_CXDOUBLECLK);
+    let height = GetSystemMetrics(SM_CYDOUBLECLK);
+    let distance = width.max(height) as f64 / 2.0 * 96.0 / dpi as f64;
+    (interval, distance)
+}
+
 /// Choose an adapter. Here the heuristic is to choose the adapter with the
 /// largest video memory, which will generally be the discrete adapter. It's
 /// possible that on some systems the integrated adapter might be a better
@@ -1086,6 +1396,78 @@ impl WindowHandle {
         }
     }
 
+    /// Bring this window to the front of the window stack and give it focus,
+    /// restoring it if it is minimized.
+    pub fn bring_to_front_and_focus(&self) {
+        if let Some(w) = self.state.upgrade() {
+            let hwnd = w.hwnd.get();
+            unsafe {
+                if IsIconic(hwnd) != FALSE {
+                    ShowWindow(hwnd, SW_RESTORE);
+                }
+                SetForegroundWindow(hwnd);
+            }
+        }
+    }
+
+    /// Minimize the window to the taskbar.
+    pub fn minimize(&self) {
+        if let Some(w) = self.state.upgrade() {
+            unsafe {
+                ShowWindow(w.hwnd.get(), SW_MINIMIZE);
+            }
+        }
+    }
+
+    /// Toggle the window between maximized and its normal size.
+    pub fn zoom(&self) {
+        if let Some(w) = self.state.upgrade() {
+            let hwnd = w.hwnd.get();
+            unsafe {
+                let cmd = if IsZoomed(hwnd) != FALSE {
+                    SW_RESTORE
+                } else {
+                    SW_MAXIMIZE
+                };
+                ShowWindow(hwnd, cmd);
+            }
+        }
+    }
+
+    /// The window's frame, in screen coordinates, in px units.
+    pub fn get_frame(&self) -> Rect {
+        if let Some(w) = self.state.upgrade() {
+            let hwnd = w.hwnd.get();
+            let mut rect: RECT = unsafe { mem::zeroed() };
+            if unsafe { GetWindowRect(hwnd, &mut rect) } != FALSE {
+                let (x0, y0) = self.pixels_to_px_xy(rect.left, rect.top);
+                let (x1, y1) = self.pixels_to_px_xy(rect.right, rect.bottom);
+                return Rect::new(x0 as f64, y0 as f64, x1 as f64, y1 as f64);
+            }
+        }
+        Rect::ZERO
+    }
+
+    /// Move the window's top left corner to `position`, in screen
+    /// coordinates, in px units.
+    pub fn set_position(&self, position: Point) {
+        if let Some(w) = self.state.upgrade() {
+            let hwnd = w.hwnd.get();
+            let (x, y) = self.px_to_pixels_xy(position.x as f32, position.y as f32);
+            unsafe {
+                SetWindowPos(
+                    hwnd,
+                    null_mut(),
+                    x,
+                    y,
+                    0,
+                    0,
+                    SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE,
+                );
+            }
+        }
+    }
+
     pub fn invalidate(&self) {
         if let Some(w) = self.state.upgrade() {
             let hwnd = w.hwnd.get();
@@ -1108,7 +1490,7 @@ impl WindowHandle {
     }
 
     pub fn set_menu(&self, menu: Menu) {
-        let hmenu = menu.into_hmenu();
+        let (hmenu, accels) = menu.into_parts();
         if let Some(w) = self.state.upgrade() {
             let hwnd = w.hwnd.get();
             unsafe {
@@ -1117,6 +1499,7 @@ impl WindowHandle {
                     warn!("failed to set window menu");
                 } else {
                     DestroyMenu(old_menu);
+                    w.accels.replace(accels);
                 }
             }
         }
@@ -1138,6 +1521,16 @@ impl WindowHandle {
         }
     }
 
+    /// Set the progress shown on this window's taskbar button.
+    pub fn set_taskbar_progress(&self, progress: TaskbarProgress) {
+        if let Some(w) = self.state.upgrade() {
+            let hwnd = w.hwnd.get();
+            if let Err(e) = taskbar::set_progress(hwnd, progress) {
+                warn!("failed to set taskbar progress: {:?}", e);
+            }
+        }
+    }
+
     /// Get the raw HWND handle, for uses that are not wrapped in
     /// druid_win_shell.
     pub fn get_hwnd(&self) -> Option<HWND> {
@@ -1158,6 +1551,28 @@ impl WindowHandle {
         unsafe { get_file_dialog_path(hwnd, ty, options) }
     }
 
+    /// Set whether animation frames are presented in step with the display's
+    /// refresh, which is the default.
+    ///
+    /// Otherwise, each frame is presented as soon as it is painted.
+    pub fn set_vsync(&self, vsync: bool) {
+        if let Some(w) = self.state.upgrade() {
+            w.vsync.set(vsync);
+        }
+    }
+
+    fn vsync(&self) -> bool {
+        self.state.upgrade().map(|w| w.vsync.get()).unwrap_or(true)
+    }
+
+    /// Print a document, after showing the system's print dialog.
+    ///
+    /// Printing is not supported on this platform, so this only logs a
+    /// warning.
+    pub fn print(&self, _setup: PageSetup, _doc: Box<dyn Printable>) {
+        warn!("printing is not supported on this platform");
+    }
+
     /// Get a handle that can be used to schedule an idle task.
     pub fn get_idle_handle(&self) -> Option<IdleHandle> {
         self.state.upgrade().map(|w| IdleHandle {
@@ -1166,7 +1581,7 @@ impl WindowHandle {
         })
     }
 
-    fn take_idle_queue(&self) -> Vec<Box<dyn IdleCallback>> {
+    fn take_idle_queue(&self) -> Vec<IdleKind> {
         if let Some(w) = self.state.upgrade() {
             mem::replace(&mut w.idle_queue.lock().unwrap(), Vec::new())
         } else {
@@ -1174,6 +1589,21 @@ impl WindowHandle {
         }
     }
 
+    /// Get the raw handle of the window, for use with other libraries.
+    pub fn raw_window_handle(&self) -> RawWindowHandle {
+        let hwnd = self.get_hwnd().unwrap_or(null_mut());
+        let hinstance = if hwnd.is_null() {
+            null_mut()
+        } else {
+            unsafe { GetWindowLongPtrW(hwnd, GWLP_HINSTANCE) as *mut c_void }
+        };
+        RawWindowHandle::Windows(WindowsHandle {
+            hwnd: hwnd as *mut c_void,
+            hinstance,
+            ..WindowsHandle::empty()
+        })
+    }
+
     /// Get the dpi of the window.
     pub fn get_dpi(&self) -> f32 {
         if let Some(w) = self.state.upgrade() {
@@ -1237,13 +1667,25 @@ impl IdleHandle {
     where
         F: FnOnce(&mut dyn Any) + Send + 'static,
     {
+        self.add_idle_kind(IdleKind::Callback(Box::new(callback)));
+    }
+
+    /// Request a call to the window handler's [`idle`] method, with the
+    /// given token.
+    ///
+    /// [`idle`]: ../trait.WinHandler.html#method.idle
+    pub fn schedule_idle(&self, token: IdleToken) {
+        self.add_idle_kind(IdleKind::Token(token));
+    }
+
+    fn add_idle_kind(&self, kind: IdleKind) {
         let mut queue = self.queue.lock().unwrap();
         if queue.is_empty() {
             unsafe {
                 PostMessageW(self.hwnd, XI_RUN_IDLE, 0, 0);
             }
         }
-        queue.push(Box::new(callback));
+        queue.push(kind);
     }
 
     fn invalidate(&self) {
@@ -1260,6 +1702,10 @@ impl<'a> WinCtx<'a> for WinCtxImpl<'a> {
         self.handle.invalidate();
     }
 
+    fn presentation_time(&self) -> Option<Instant> {
+        self.presentation_time
+    }
+
     /// Get a reference to the text factory.
     fn text_factory(&mut self) -> &mut Text<'a> {
         &mut self.text
@@ -1308,6 +1754,42 @@ impl<'a> WinCtx<'a> for WinCtxImpl<'a> {
     fn set_clipboard_contents(&mut self, contents: ClipboardItem) {
         Application::set_clipboard_contents(contents);
     }
+
+    fn start_drag(&mut self, _contents: DragContents) {
+        //TODO: this needs IDataObject and IDropSource implementations for DoDragDrop.
+        warn!("dragging out of a window is not yet supported on Windows");
+    }
+
+    fn set_mouse_capture(&mut self, capture: bool) {
+        if let Some(hwnd) = self.handle.get_hwnd() {
+            unsafe {
+                if capture {
+                    SetCapture(hwnd);
+                } else if GetCapture() == hwnd {
+                    ReleaseCapture();
+                }
+            }
+        }
+    }
+
+    fn set_ime_enabled(&mut self, enabled: bool) {
+        if let Some(hwnd) = self.handle.get_hwnd() {
+            ime::set_enabled(hwnd, enabled);
+        }
+    }
+
+    fn set_ime_cursor_rect(&mut self, rect: Rect) {
+        if let Some(hwnd) = self.handle.get_hwnd() {
+            let scale = f64::from(self.handle.get_dpi()) / 96.0;
+            let rect = Rect::new(
+                rect.x0 * scale,
+                rect.y0 * scale,
+                rect.x1 * scale,
+                rect.y1 * scale,
+            );
+            ime::set_cursor_rect(hwnd, rect);
+        }
+    }
 }
 
 /// Casts render target to hwnd variant.
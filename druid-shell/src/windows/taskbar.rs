@@ -0,0 +1,220 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Taskbar progress and jump lists, Windows implementation.
+//!
+//! For more information, see the documentation for [ITaskbarList3] and
+//! [ICustomDestinationList].
+//!
+//! [ITaskbarList3]: https://docs.microsoft.com/en-us/windows/win32/api/shobjidl_core/nn-shobjidl_core-itaskbarlist3
+//! [ICustomDestinationList]: https://docs.microsoft.com/en-us/windows/win32/api/shobjidl_core/nn-shobjidl_core-icustomdestinationlist
+
+#![allow(non_upper_case_globals)]
+
+use std::path::Path;
+use std::ptr::null_mut;
+
+use winapi::ctypes::{c_int, c_void};
+use winapi::shared::guiddef::{GUID, REFIID};
+use winapi::shared::minwindef::*;
+use winapi::shared::ntdef::LPCWSTR;
+use winapi::shared::windef::HWND;
+use winapi::shared::winerror::HRESULT;
+use winapi::shared::wtypes::{PROPERTYKEY, VARTYPE, VT_LPWSTR};
+use winapi::shared::wtypesbase::CLSCTX_INPROC_SERVER;
+use winapi::um::combaseapi::*;
+use winapi::um::propidl::REFPROPVARIANT;
+use winapi::um::propsys::IPropertyStore;
+use winapi::um::shobjidl_core::*;
+use winapi::um::unknwnbase::{IUnknown, IUnknownVtbl};
+use winapi::Interface;
+use wio::com::ComPtr;
+
+use crate::taskbar::{JumpListTask, TaskbarProgress};
+use crate::util::{as_result, ToWide};
+use crate::Error;
+
+// TODO: remove these when they get added to winapi
+DEFINE_GUID! {CLSID_TaskbarList,
+0x56FDF344, 0xFD6D, 0x11D0, 0x95, 0x8A, 0x00, 0x60, 0x97, 0xC9, 0xA0, 0x90}
+DEFINE_GUID! {CLSID_DestinationList,
+0x77F10CF0, 0x3DB5, 0x4966, 0xB5, 0x20, 0xB7, 0xC5, 0x4F, 0xD3, 0x5E, 0xD6}
+DEFINE_GUID! {CLSID_EnumerableObjectCollection,
+0x2D3468C1, 0x36A7, 0x43B6, 0xAC, 0x24, 0xD3, 0xF0, 0x2F, 0xD9, 0x60, 0x7A}
+DEFINE_GUID! {CLSID_ShellLink,
+0x00021401, 0x0000, 0x0000, 0xC0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x46}
+
+const PKEY_Title: PROPERTYKEY = PROPERTYKEY {
+    fmtid: GUID {
+        Data1: 0xF29F85E0,
+        Data2: 0x4FF9,
+        Data3: 0x1068,
+        Data4: [0xAB, 0x91, 0x08, 0x00, 0x2B, 0x27, 0xB3, 0xD9],
+    },
+    pid: 2,
+};
+
+/// `KDC_RECENT`, from `KNOWNDESTCATEGORY`.
+const KDC_RECENT: c_int = 2;
+
+/// `SHARD_PATHW`, from `SHARD`.
+const SHARD_PATHW: UINT = 3;
+
+#[link(name = "shell32")]
+extern "system" {
+    fn SHAddToRecentDocs(flags: UINT, pv: LPCVOID);
+}
+
+RIDL! {#[uuid(0x92CA9DCD, 0x5622, 0x4BBA, 0xA8, 0x05, 0x5E, 0x9F, 0x54, 0x1B, 0xD8, 0xC9)]
+interface IObjectArray(IObjectArrayVtbl): IUnknown(IUnknownVtbl) {
+    fn GetCount(pcObjects: *mut UINT,) -> HRESULT,
+    fn GetAt(uiIndex: UINT, riid: REFIID, ppv: *mut *mut c_void,) -> HRESULT,
+}}
+
+RIDL! {#[uuid(0x5632B1A4, 0xE38A, 0x400A, 0x92, 0x8A, 0xD4, 0xCD, 0x63, 0x23, 0x02, 0x95)]
+interface IObjectCollection(IObjectCollectionVtbl): IObjectArray(IObjectArrayVtbl) {
+    fn AddObject(punk: *mut IUnknown,) -> HRESULT,
+    fn AddFromArray(poaSource: *mut IObjectArray,) -> HRESULT,
+    fn RemoveObjectAt(uiIndex: UINT,) -> HRESULT,
+    fn Clear() -> HRESULT,
+}}
+
+RIDL! {#[uuid(0x6332DEBF, 0x87B5, 0x4670, 0x90, 0xC0, 0x5E, 0x57, 0xB4, 0x08, 0xA4, 0x9E)]
+interface ICustomDestinationList(ICustomDestinationListVtbl): IUnknown(IUnknownVtbl) {
+    fn SetAppID(pszAppID: LPCWSTR,) -> HRESULT,
+    fn BeginList(pcMinSlots: *mut UINT, riid: REFIID, ppv: *mut *mut c_void,) -> HRESULT,
+    fn AppendCategory(pszCategory: LPCWSTR, poa: *mut IObjectArray,) -> HRESULT,
+    fn AppendKnownCategory(category: c_int,) -> HRESULT,
+    fn AddUserTasks(poa: *mut IObjectArray,) -> HRESULT,
+    fn CommitList() -> HRESULT,
+    fn GetRemovedDestinations(riid: REFIID, ppv: *mut *mut c_void,) -> HRESULT,
+    fn DeleteList(pszAppID: LPCWSTR,) -> HRESULT,
+    fn AbortList() -> HRESULT,
+}}
+
+/// A `PROPVARIANT` holding a wide string.
+///
+/// This has the same layout as `PROPVARIANT`, but is simpler to construct.
+/// The string is borrowed, so this must not be passed to `PropVariantClear`.
+#[repr(C)]
+struct StringPropVariant {
+    vt: VARTYPE,
+    reserved: [WORD; 3],
+    value: *const u16,
+    padding: *mut c_void,
+}
+
+/// The resolution used when passing progress to the taskbar.
+const PROGRESS_TOTAL: u64 = 10_000;
+
+unsafe fn create_instance<I: Interface>(class: &GUID) -> Result<ComPtr<I>, Error> {
+    let mut ptr: *mut I = null_mut();
+    as_result(CoCreateInstance(
+        class,
+        null_mut(),
+        CLSCTX_INPROC_SERVER,
+        &I::uuidof(),
+        &mut ptr as *mut *mut I as *mut LPVOID,
+    ))?;
+    Ok(ComPtr::from_raw(ptr))
+}
+
+pub(crate) fn set_progress(hwnd: HWND, progress: TaskbarProgress) -> Result<(), Error> {
+    let (state, value) = match progress {
+        TaskbarProgress::None => (TBPF_NOPROGRESS, None),
+        TaskbarProgress::Indeterminate => (TBPF_INDETERMINATE, None),
+        TaskbarProgress::Normal(v) => (TBPF_NORMAL, Some(v)),
+        TaskbarProgress::Paused(v) => (TBPF_PAUSED, Some(v)),
+        TaskbarProgress::Error(v) => (TBPF_ERROR, Some(v)),
+    };
+    unsafe {
+        let taskbar: ComPtr<ITaskbarList3> = create_instance(&CLSID_TaskbarList)?;
+        as_result(taskbar.HrInit())?;
+        // The value must be set before the state, or setting the value
+        // switches the state back to normal.
+        if let Some(value) = value {
+            let completed = (value.max(0.0).min(1.0) * PROGRESS_TOTAL as f64) as u64;
+            as_result(taskbar.SetProgressValue(hwnd, completed, PROGRESS_TOTAL))?;
+        }
+        as_result(taskbar.SetProgressState(hwnd, state))
+    }
+}
+
+/// Replace the application's jump list with the given tasks.
+///
+/// The list also includes the system-maintained recent documents category,
+/// which is populated by [`add_recent_document`].
+///
+/// [`add_recent_document`]: fn.add_recent_document.html
+pub(crate) fn set_jump_list(tasks: &[JumpListTask]) -> Result<(), Error> {
+    let exe = std::env::current_exe().map_err(|_| Error::Null)?;
+    let exe = exe.as_os_str().to_wide();
+    unsafe {
+        let list: ComPtr<ICustomDestinationList> = create_instance(&CLSID_DestinationList)?;
+        let mut min_slots: UINT = 0;
+        let mut removed: *mut IObjectArray = null_mut();
+        as_result(list.BeginList(
+            &mut min_slots,
+            &IObjectArray::uuidof(),
+            &mut removed as *mut *mut IObjectArray as *mut *mut c_void,
+        ))?;
+        let _removed = ComPtr::from_raw(removed);
+
+        let collection: ComPtr<IObjectCollection> =
+            create_instance(&CLSID_EnumerableObjectCollection)?;
+        for task in tasks {
+            let link = make_task_link(&exe, task)?;
+            as_result(collection.AddObject(link.as_raw() as *mut IUnknown))?;
+        }
+        let array = collection.cast::<IObjectArray>()?;
+
+        as_result(list.AppendKnownCategory(KDC_RECENT))?;
+        as_result(list.AddUserTasks(array.as_raw()))?;
+        as_result(list.CommitList())
+    }
+}
+
+unsafe fn make_task_link(exe: &[u16], task: &JumpListTask) -> Result<ComPtr<IShellLinkW>, Error> {
+    let link: ComPtr<IShellLinkW> = create_instance(&CLSID_ShellLink)?;
+    as_result(link.SetPath(exe.as_ptr()))?;
+    as_result(link.SetArguments(task.arguments.to_wide().as_ptr()))?;
+    if let Some(description) = task.description.as_ref() {
+        as_result(link.SetDescription(description.to_wide().as_ptr()))?;
+    }
+
+    // The title is only settable through the link's property store.
+    let store = link.cast::<IPropertyStore>()?;
+    let title = task.title.to_wide();
+    let value = StringPropVariant {
+        vt: VT_LPWSTR as VARTYPE,
+        reserved: [0; 3],
+        value: title.as_ptr(),
+        padding: null_mut(),
+    };
+    as_result(store.SetValue(
+        &PKEY_Title,
+        &value as *const StringPropVariant as REFPROPVARIANT,
+    ))?;
+    as_result(store.Commit())?;
+    Ok(link)
+}
+
+/// Add a file to the system's list of recently used documents, which is
+/// shown in the application's jump list.
+pub(crate) fn add_recent_document(path: &Path) {
+    let path = path.as_os_str().to_wide();
+    unsafe {
+        SHAddToRecentDocs(SHARD_PATHW, path.as_ptr() as *const c_void);
+    }
+}
@@ -23,6 +23,7 @@ use std::os::windows::ffi::{OsStrExt, OsStringExt};
 use std::ptr;
 use std::slice;
 use winapi::ctypes::c_void;
+use winapi::shared::basetsd::UINT32;
 use winapi::shared::guiddef::REFIID;
 use winapi::shared::minwindef::*;
 use winapi::shared::ntdef::*;
@@ -39,7 +40,9 @@ use winapi::um::wincon::*;
 use winapi::um::wingdi::CreateSolidBrush;
 // This needs to be explicit, otherwise HRESULT will conflict
 use winapi::um::winnt::{FILE_SHARE_WRITE, GENERIC_READ, GENERIC_WRITE};
-use winapi::um::winuser::{LoadIconW, RegisterClassW, IDI_APPLICATION, WNDCLASSW};
+use winapi::um::winuser::{
+    LoadIconW, RegisterClassW, IDI_APPLICATION, POINTER_INPUT_TYPE, POINTER_PEN_INFO, WNDCLASSW,
+};
 
 use direct2d::enums::DrawTextOptions;
 
@@ -123,6 +126,10 @@ type DCompositionCreateDevice2 = unsafe extern "system" fn(
 ) -> HRESULT;
 type CreateDXGIFactory2 =
     unsafe extern "system" fn(Flags: UINT, riid: REFIID, ppFactory: *mut *mut c_void) -> HRESULT;
+type GetPointerType =
+    unsafe extern "system" fn(pointerId: UINT32, pointerType: *mut POINTER_INPUT_TYPE) -> BOOL;
+type GetPointerPenInfo =
+    unsafe extern "system" fn(pointerId: UINT32, penInfo: *mut POINTER_PEN_INFO) -> BOOL;
 
 #[allow(non_snake_case)] // For member fields
 pub struct OptionalFunctions {
@@ -131,6 +138,8 @@ pub struct OptionalFunctions {
     pub SetProcessDpiAwareness: Option<SetProcessDpiAwareness>,
     pub DCompositionCreateDevice2: Option<DCompositionCreateDevice2>,
     pub CreateDXGIFactory2: Option<CreateDXGIFactory2>,
+    pub GetPointerType: Option<GetPointerType>,
+    pub GetPointerPenInfo: Option<GetPointerPenInfo>,
 }
 
 #[allow(non_snake_case)] // For local variables
@@ -184,6 +193,8 @@ fn load_optional_functions() -> OptionalFunctions {
     let mut SetProcessDpiAwareness = None;
     let mut DCompositionCreateDevice2 = None;
     let mut CreateDXGIFactory2 = None;
+    let mut GetPointerType = None;
+    let mut GetPointerPenInfo = None;
 
     if shcore.is_null() {
         error!("No shcore.dll");
@@ -196,6 +207,8 @@ fn load_optional_functions() -> OptionalFunctions {
         error!("No user32.dll");
     } else {
         load_function!(user32, GetDpiForSystem, "10");
+        load_function!(user32, GetPointerType, "8");
+        load_function!(user32, GetPointerPenInfo, "8");
     }
 
     if !dcomp.is_null() {
@@ -212,6 +225,8 @@ fn load_optional_functions() -> OptionalFunctions {
         SetProcessDpiAwareness,
         DCompositionCreateDevice2,
         CreateDXGIFactory2,
+        GetPointerType,
+        GetPointerPenInfo,
     }
 }
 
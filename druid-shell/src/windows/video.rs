@@ -0,0 +1,432 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Video decoding, Windows implementation, using the Media Foundation
+//! [source reader].
+//!
+//! [source reader]: https://docs.microsoft.com/en-us/windows/win32/medfound/source-reader
+
+#![allow(non_upper_case_globals)]
+
+use std::mem;
+use std::path::Path;
+use std::ptr::null_mut;
+use std::sync::Once;
+use std::time::Duration;
+
+use winapi::ctypes::c_void;
+use winapi::shared::basetsd::{UINT32, UINT64, UINT8};
+use winapi::shared::guiddef::{GUID, REFGUID, REFIID};
+use winapi::shared::minwindef::{BOOL, BYTE, DWORD, LPVOID, UINT, ULONG, WORD};
+use winapi::shared::ntdef::{LONGLONG, LPCWSTR, LPWSTR};
+use winapi::shared::winerror::HRESULT;
+use winapi::shared::wtypes::{VARTYPE, VT_I8, VT_UI8};
+use winapi::um::cguid::GUID_NULL;
+use winapi::um::combaseapi::CoInitializeEx;
+use winapi::um::objbase::COINIT_APARTMENTTHREADED;
+use winapi::um::propidl::{PROPVARIANT, REFPROPVARIANT};
+use winapi::um::unknwnbase::{IUnknown, IUnknownVtbl};
+use wio::com::ComPtr;
+
+use log::warn;
+
+use crate::util::{as_result, ToWide};
+use crate::video::{bgrx_to_rgba, VideoFrame};
+use crate::Error;
+
+// TODO: remove these when they get added to winapi
+DEFINE_GUID! {MF_SOURCE_READER_ENABLE_VIDEO_PROCESSING,
+0xfb394f3d, 0xccf1, 0x42ee, 0xbb, 0xb3, 0xf9, 0xb8, 0x45, 0xd5, 0x68, 0x1d}
+DEFINE_GUID! {MF_MT_MAJOR_TYPE,
+0x48eba18e, 0xf8c9, 0x4687, 0xbf, 0x11, 0x0a, 0x74, 0xc9, 0xf9, 0x6a, 0x8f}
+DEFINE_GUID! {MF_MT_SUBTYPE,
+0xf7e34c9a, 0x42e8, 0x4714, 0xb7, 0x4b, 0xcb, 0x29, 0xd7, 0x2c, 0x35, 0xe5}
+DEFINE_GUID! {MF_MT_FRAME_SIZE,
+0x1652c33d, 0xd6b2, 0x4012, 0xb8, 0x34, 0x72, 0x03, 0x08, 0x49, 0xa3, 0x7d}
+DEFINE_GUID! {MF_MT_DEFAULT_STRIDE,
+0x644b4e48, 0x1e02, 0x4516, 0xb0, 0xeb, 0xc0, 0x1c, 0xa9, 0xd4, 0x9a, 0xc6}
+DEFINE_GUID! {MF_PD_DURATION,
+0x6c990d33, 0xbb8e, 0x477a, 0x85, 0x98, 0x0d, 0x5d, 0x96, 0xfc, 0xd8, 0x8a}
+DEFINE_GUID! {MFMediaType_Video,
+0x73646976, 0x0000, 0x0010, 0x80, 0x00, 0x00, 0xaa, 0x00, 0x38, 0x9b, 0x71}
+DEFINE_GUID! {MFVideoFormat_RGB32,
+0x00000016, 0x0000, 0x0010, 0x80, 0x00, 0x00, 0xaa, 0x00, 0x38, 0x9b, 0x71}
+
+/// `MF_VERSION`, from the SDK and API versions of Windows 7.
+const MF_VERSION: ULONG = 0x0002_0070;
+const MFSTARTUP_LITE: DWORD = 0x1;
+
+const MF_SOURCE_READER_FIRST_VIDEO_STREAM: DWORD = 0xffff_fffc;
+const MF_SOURCE_READER_MEDIASOURCE: DWORD = 0xffff_ffff;
+const MF_SOURCE_READERF_ENDOFSTREAM: DWORD = 0x2;
+const MF_SOURCE_READERF_CURRENTMEDIATYPECHANGED: DWORD = 0x20;
+
+/// `MF_ATTRIBUTE_TYPE`.
+type MfAttributeType = UINT;
+/// `MF_ATTRIBUTES_MATCH_TYPE`.
+type MfAttributesMatchType = UINT;
+
+RIDL! {#[uuid(0x2cd2d921, 0xc447, 0x44a7, 0xa1, 0x3c, 0x4a, 0xda, 0xbf, 0xc2, 0x47, 0xe3)]
+interface IMFAttributes(IMFAttributesVtbl): IUnknown(IUnknownVtbl) {
+    fn GetItem(guidKey: REFGUID, pValue: *mut PROPVARIANT,) -> HRESULT,
+    fn GetItemType(guidKey: REFGUID, pType: *mut MfAttributeType,) -> HRESULT,
+    fn CompareItem(guidKey: REFGUID, Value: REFPROPVARIANT, pbResult: *mut BOOL,) -> HRESULT,
+    fn Compare(
+        pTheirs: *mut IMFAttributes,
+        MatchType: MfAttributesMatchType,
+        pbResult: *mut BOOL,
+    ) -> HRESULT,
+    fn GetUINT32(guidKey: REFGUID, punValue: *mut UINT32,) -> HRESULT,
+    fn GetUINT64(guidKey: REFGUID, punValue: *mut UINT64,) -> HRESULT,
+    fn GetDouble(guidKey: REFGUID, pfValue: *mut f64,) -> HRESULT,
+    fn GetGUID(guidKey: REFGUID, pguidValue: *mut GUID,) -> HRESULT,
+    fn GetStringLength(guidKey: REFGUID, pcchLength: *mut UINT32,) -> HRESULT,
+    fn GetString(
+        guidKey: REFGUID,
+        pwszValue: LPWSTR,
+        cchBufSize: UINT32,
+        pcchLength: *mut UINT32,
+    ) -> HRESULT,
+    fn GetAllocatedString(
+        guidKey: REFGUID,
+        ppwszValue: *mut LPWSTR,
+        pcchLength: *mut UINT32,
+    ) -> HRESULT,
+    fn GetBlobSize(guidKey: REFGUID, pcbBlobSize: *mut UINT32,) -> HRESULT,
+    fn GetBlob(
+        guidKey: REFGUID,
+        pBuf: *mut UINT8,
+        cbBufSize: UINT32,
+        pcbBlobSize: *mut UINT32,
+    ) -> HRESULT,
+    fn GetAllocatedBlob(guidKey: REFGUID, ppBuf: *mut *mut UINT8, pcbSize: *mut UINT32,) -> HRESULT,
+    fn GetUnknown(guidKey: REFGUID, riid: REFIID, ppv: *mut LPVOID,) -> HRESULT,
+    fn SetItem(guidKey: REFGUID, Value: REFPROPVARIANT,) -> HRESULT,
+    fn DeleteItem(guidKey: REFGUID,) -> HRESULT,
+    fn DeleteAllItems() -> HRESULT,
+    fn SetUINT32(guidKey: REFGUID, unValue: UINT32,) -> HRESULT,
+    fn SetUINT64(guidKey: REFGUID, unValue: UINT64,) -> HRESULT,
+    fn SetDouble(guidKey: REFGUID, fValue: f64,) -> HRESULT,
+    fn SetGUID(guidKey: REFGUID, guidValue: REFGUID,) -> HRESULT,
+    fn SetString(guidKey: REFGUID, wszValue: LPCWSTR,) -> HRESULT,
+    fn SetBlob(guidKey: REFGUID, pBuf: *const UINT8, cbBufSize: UINT32,) -> HRESULT,
+    fn SetUnknown(guidKey: REFGUID, pUnknown: *mut IUnknown,) -> HRESULT,
+    fn LockStore() -> HRESULT,
+    fn UnlockStore() -> HRESULT,
+    fn GetCount(pcItems: *mut UINT32,) -> HRESULT,
+    fn GetItemByIndex(unIndex: UINT32, pguidKey: *mut GUID, pValue: *mut PROPVARIANT,) -> HRESULT,
+    fn CopyAllItems(pDest: *mut IMFAttributes,) -> HRESULT,
+}}
+
+RIDL! {#[uuid(0x44ae0fa8, 0xea31, 0x4109, 0x8d, 0x2e, 0x4c, 0xae, 0x49, 0x97, 0xc5, 0x55)]
+interface IMFMediaType(IMFMediaTypeVtbl): IMFAttributes(IMFAttributesVtbl) {
+    fn GetMajorType(pguidMajorType: *mut GUID,) -> HRESULT,
+    fn IsCompressedFormat(pfCompressed: *mut BOOL,) -> HRESULT,
+    fn IsEqual(pIMediaType: *mut IMFMediaType, pdwFlags: *mut DWORD,) -> HRESULT,
+    fn GetRepresentation(guidRepresentation: GUID, ppvRepresentation: *mut LPVOID,) -> HRESULT,
+    fn FreeRepresentation(guidRepresentation: GUID, pvRepresentation: LPVOID,) -> HRESULT,
+}}
+
+RIDL! {#[uuid(0x045fa593, 0x8799, 0x42b8, 0xbc, 0x8d, 0x89, 0x68, 0xc6, 0x45, 0x35, 0x07)]
+interface IMFMediaBuffer(IMFMediaBufferVtbl): IUnknown(IUnknownVtbl) {
+    fn Lock(
+        ppbBuffer: *mut *mut BYTE,
+        pcbMaxLength: *mut DWORD,
+        pcbCurrentLength: *mut DWORD,
+    ) -> HRESULT,
+    fn Unlock() -> HRESULT,
+    fn GetCurrentLength(pcbCurrentLength: *mut DWORD,) -> HRESULT,
+    fn SetCurrentLength(cbCurrentLength: DWORD,) -> HRESULT,
+    fn GetMaxLength(pcbMaxLength: *mut DWORD,) -> HRESULT,
+}}
+
+RIDL! {#[uuid(0xc40a00f2, 0xb93a, 0x4d80, 0xae, 0x8c, 0x5a, 0x1c, 0x63, 0x4f, 0x58, 0xe4)]
+interface IMFSample(IMFSampleVtbl): IMFAttributes(IMFAttributesVtbl) {
+    fn GetSampleFlags(pdwSampleFlags: *mut DWORD,) -> HRESULT,
+    fn SetSampleFlags(dwSampleFlags: DWORD,) -> HRESULT,
+    fn GetSampleTime(phnsSampleTime: *mut LONGLONG,) -> HRESULT,
+    fn SetSampleTime(hnsSampleTime: LONGLONG,) -> HRESULT,
+    fn GetSampleDuration(phnsSampleDuration: *mut LONGLONG,) -> HRESULT,
+    fn SetSampleDuration(hnsSampleDuration: LONGLONG,) -> HRESULT,
+    fn GetBufferCount(pdwBufferCount: *mut DWORD,) -> HRESULT,
+    fn GetBufferByIndex(dwIndex: DWORD, ppBuffer: *mut *mut IMFMediaBuffer,) -> HRESULT,
+    fn ConvertToContiguousBuffer(ppBuffer: *mut *mut IMFMediaBuffer,) -> HRESULT,
+    fn AddBuffer(pBuffer: *mut IMFMediaBuffer,) -> HRESULT,
+    fn RemoveBufferByIndex(dwIndex: DWORD,) -> HRESULT,
+    fn RemoveAllBuffers() -> HRESULT,
+    fn GetTotalLength(pcbTotalLength: *mut DWORD,) -> HRESULT,
+    fn CopyToBuffer(pBuffer: *mut IMFMediaBuffer,) -> HRESULT,
+}}
+
+RIDL! {#[uuid(0x70ae66f2, 0xc809, 0x4e4f, 0x89, 0x15, 0xbd, 0xcb, 0x40, 0x6b, 0x79, 0x93)]
+interface IMFSourceReader(IMFSourceReaderVtbl): IUnknown(IUnknownVtbl) {
+    fn GetStreamSelection(dwStreamIndex: DWORD, pfSelected: *mut BOOL,) -> HRESULT,
+    fn SetStreamSelection(dwStreamIndex: DWORD, fSelected: BOOL,) -> HRESULT,
+    fn GetNativeMediaType(
+        dwStreamIndex: DWORD,
+        dwMediaTypeIndex: DWORD,
+        ppMediaType: *mut *mut IMFMediaType,
+    ) -> HRESULT,
+    fn GetCurrentMediaType(dwStreamIndex: DWORD, ppMediaType: *mut *mut IMFMediaType,) -> HRESULT,
+    fn SetCurrentMediaType(
+        dwStreamIndex: DWORD,
+        pdwReserved: *mut DWORD,
+        pMediaType: *mut IMFMediaType,
+    ) -> HRESULT,
+    fn SetCurrentPosition(guidTimeFormat: REFGUID, varPosition: REFPROPVARIANT,) -> HRESULT,
+    fn ReadSample(
+        dwStreamIndex: DWORD,
+        dwControlFlags: DWORD,
+        pdwActualStreamIndex: *mut DWORD,
+        pdwStreamFlags: *mut DWORD,
+        pllTimestamp: *mut LONGLONG,
+        ppSample: *mut *mut IMFSample,
+    ) -> HRESULT,
+    fn Flush(dwStreamIndex: DWORD,) -> HRESULT,
+    fn GetServiceForStream(
+        dwStreamIndex: DWORD,
+        guidService: REFGUID,
+        riid: REFIID,
+        ppvObject: *mut LPVOID,
+    ) -> HRESULT,
+    fn GetPresentationAttribute(
+        dwStreamIndex: DWORD,
+        guidAttribute: REFGUID,
+        pvarAttribute: *mut PROPVARIANT,
+    ) -> HRESULT,
+}}
+
+#[link(name = "mfplat")]
+extern "system" {
+    fn MFStartup(version: ULONG, flags: DWORD) -> HRESULT;
+    fn MFCreateAttributes(attributes: *mut *mut IMFAttributes, initial_size: UINT32) -> HRESULT;
+    fn MFCreateMediaType(media_type: *mut *mut IMFMediaType) -> HRESULT;
+}
+
+#[link(name = "mfreadwrite")]
+extern "system" {
+    fn MFCreateSourceReaderFromURL(
+        url: LPCWSTR,
+        attributes: *mut IMFAttributes,
+        reader: *mut *mut IMFSourceReader,
+    ) -> HRESULT;
+}
+
+/// Media Foundation times are in units of 100ns.
+const TICKS_PER_SEC: u64 = 10_000_000;
+
+/// A `PROPVARIANT` holding a 64-bit integer.
+///
+/// This has the same layout as `PROPVARIANT`, but is simpler to construct.
+#[repr(C)]
+struct IntPropVariant {
+    vt: VARTYPE,
+    reserved: [WORD; 3],
+    value: i64,
+    padding: *mut c_void,
+}
+
+/// Reads the frames of a video file.
+pub struct VideoDecoder {
+    reader: ComPtr<IMFSourceReader>,
+    width: usize,
+    height: usize,
+    stride: isize,
+}
+
+fn startup() -> Result<(), Error> {
+    static START: Once = Once::new();
+    let mut result = Ok(());
+    START.call_once(|| unsafe {
+        // This fails harmlessly if COM was already initialized.
+        CoInitializeEx(null_mut(), COINIT_APARTMENTTHREADED);
+        result = as_result(MFStartup(MF_VERSION, MFSTARTUP_LITE));
+    });
+    result
+}
+
+impl VideoDecoder {
+    /// Open a video file, ready to decode its first frame.
+    pub fn open(path: &Path) -> Result<VideoDecoder, Error> {
+        startup()?;
+        unsafe {
+            let mut attributes: *mut IMFAttributes = null_mut();
+            as_result(MFCreateAttributes(&mut attributes, 1))?;
+            let attributes = ComPtr::from_raw(attributes);
+            // Let the reader convert to RGB for us.
+            as_result(attributes.SetUINT32(&MF_SOURCE_READER_ENABLE_VIDEO_PROCESSING, 1))?;
+
+            let url = path.as_os_str().to_wide();
+            let mut reader: *mut IMFSourceReader = null_mut();
+            as_result(MFCreateSourceReaderFromURL(
+                url.as_ptr(),
+                attributes.as_raw(),
+                &mut reader,
+            ))?;
+            let reader = ComPtr::from_raw(reader);
+
+            let mut media_type: *mut IMFMediaType = null_mut();
+            as_result(MFCreateMediaType(&mut media_type))?;
+            let media_type = ComPtr::from_raw(media_type);
+            as_result(media_type.SetGUID(&MF_MT_MAJOR_TYPE, &MFMediaType_Video))?;
+            as_result(media_type.SetGUID(&MF_MT_SUBTYPE, &MFVideoFormat_RGB32))?;
+            as_result(reader.SetCurrentMediaType(
+                MF_SOURCE_READER_FIRST_VIDEO_STREAM,
+                null_mut(),
+                media_type.as_raw(),
+            ))?;
+
+            let mut decoder = VideoDecoder {
+                reader,
+                width: 0,
+                height: 0,
+                stride: 0,
+            };
+            decoder.update_format()?;
+            Ok(decoder)
+        }
+    }
+
+    /// The length of the video, if known.
+    pub fn duration(&self) -> Option<Duration> {
+        unsafe {
+            let mut value: PROPVARIANT = mem::zeroed();
+            let hr = self.reader.GetPresentationAttribute(
+                MF_SOURCE_READER_MEDIASOURCE,
+                &MF_PD_DURATION,
+                &mut value,
+            );
+            let value = &*(&value as *const PROPVARIANT as *const IntPropVariant);
+            if as_result(hr).is_err() || value.vt != VT_UI8 as VARTYPE {
+                return None;
+            }
+            Some(from_ticks(value.value))
+        }
+    }
+
+    /// Move to the given time. The next frame is the one shown at that time,
+    /// or if the format can't seek precisely, an earlier one.
+    pub fn seek(&mut self, time: Duration) {
+        let ticks = time.as_secs() * TICKS_PER_SEC + u64::from(time.subsec_nanos()) / 100;
+        let position = IntPropVariant {
+            vt: VT_I8 as VARTYPE,
+            reserved: [0; 3],
+            value: ticks as i64,
+            padding: null_mut(),
+        };
+        let hr = unsafe {
+            self.reader.SetCurrentPosition(
+                &GUID_NULL,
+                &position as *const IntPropVariant as REFPROPVARIANT,
+            )
+        };
+        if let Err(e) = as_result(hr) {
+            warn!("failed to seek video: {:?}", e);
+        }
+    }
+
+    /// Decode the next frame, or return `None` at the end of the video.
+    pub fn next_frame(&mut self) -> Option<VideoFrame> {
+        unsafe {
+            loop {
+                let mut flags: DWORD = 0;
+                let mut time: i64 = 0;
+                let mut sample: *mut IMFSample = null_mut();
+                let hr = self.reader.ReadSample(
+                    MF_SOURCE_READER_FIRST_VIDEO_STREAM,
+                    0,
+                    null_mut(),
+                    &mut flags,
+                    &mut time,
+                    &mut sample,
+                );
+                if let Err(e) = as_result(hr) {
+                    warn!("failed to decode video: {:?}", e);
+                    return None;
+                }
+                if flags & MF_SOURCE_READERF_CURRENTMEDIATYPECHANGED != 0
+                    && self.update_format().is_err()
+                {
+                    return None;
+                }
+                if flags & MF_SOURCE_READERF_ENDOFSTREAM != 0 {
+                    return None;
+                }
+                // The reader may produce no sample, for instance on a gap
+                // in the stream.
+                if sample.is_null() {
+                    continue;
+                }
+                let sample = ComPtr::from_raw(sample);
+                return self.convert(&sample, time);
+            }
+        }
+    }
+
+    unsafe fn convert(&self, sample: &ComPtr<IMFSample>, time: i64) -> Option<VideoFrame> {
+        let mut buffer: *mut IMFMediaBuffer = null_mut();
+        as_result(sample.ConvertToContiguousBuffer(&mut buffer)).ok()?;
+        let buffer = ComPtr::from_raw(buffer);
+        let mut data: *mut BYTE = null_mut();
+        let mut len: DWORD = 0;
+        as_result(buffer.Lock(&mut data, null_mut(), &mut len)).ok()?;
+        let needed = self.stride.abs() as usize * self.height;
+        let pixels = if (len as usize) < needed {
+            warn!("video frame is smaller than its format");
+            None
+        } else {
+            let src = std::slice::from_raw_parts(data, len as usize);
+            Some(bgrx_to_rgba(src, self.width, self.height, self.stride))
+        };
+        buffer.Unlock();
+        Some(VideoFrame {
+            time: from_ticks(time),
+            width: self.width,
+            height: self.height,
+            pixels: pixels?,
+        })
+    }
+
+    /// Read the frame size and stride of the current output format.
+    fn update_format(&mut self) -> Result<(), Error> {
+        unsafe {
+            let mut media_type: *mut IMFMediaType = null_mut();
+            as_result(
+                self.reader
+                    .GetCurrentMediaType(MF_SOURCE_READER_FIRST_VIDEO_STREAM, &mut media_type),
+            )?;
+            let media_type = ComPtr::from_raw(media_type);
+            let mut size: u64 = 0;
+            as_result(media_type.GetUINT64(&MF_MT_FRAME_SIZE, &mut size))?;
+            self.width = (size >> 32) as usize;
+            self.height = (size & 0xffff_ffff) as usize;
+            let mut stride: u32 = 0;
+            let hr = media_type.GetUINT32(&MF_MT_DEFAULT_STRIDE, &mut stride);
+            self.stride = match as_result(hr) {
+                // The attribute holds a signed value.
+                Ok(()) => stride as i32 as isize,
+                Err(_) => self.width as isize * 4,
+            };
+            Ok(())
+        }
+    }
+}
+
+fn from_ticks(ticks: i64) -> Duration {
+    let ticks = ticks.max(0) as u64;
+    Duration::new(
+        ticks / TICKS_PER_SEC,
+        ((ticks % TICKS_PER_SEC) * 100) as u32,
+    )
+}
@@ -0,0 +1,55 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Embedded web content, which is not yet supported on Windows.
+//!
+//! This needs bindings to WebView2, which is distributed separately from
+//! the operating system.
+
+use std::convert::Infallible;
+
+use crate::Error;
+
+use super::ChildView;
+
+/// A view showing web content.
+///
+/// Creating one always fails on this platform.
+pub struct WebView {
+    never: Infallible,
+}
+
+impl WebView {
+    pub fn new(_on_message: impl FnMut(String) + 'static) -> Result<WebView, Error> {
+        Err(Error::Null)
+    }
+
+    pub fn view(&self) -> &ChildView {
+        match self.never {}
+    }
+
+    pub fn load_url(&self, _url: &str) {}
+
+    pub fn load_html(&self, _html: &str, _base_url: Option<&str>) {}
+
+    pub fn go_back(&self) {}
+
+    pub fn go_forward(&self) {}
+
+    pub fn reload(&self) {}
+
+    pub fn stop(&self) {}
+
+    pub fn eval_script(&self, _script: &str) {}
+}
@@ -107,6 +107,12 @@ impl RunLoop {
     }
 }
 
+/// Process any pending events, without blocking.
+///
+/// This does nothing: an embedded window's messages are dispatched by the
+/// host's message loop, on the same thread.
+pub fn pump_events() {}
+
 /// Request to quit the application, exiting the runloop.
 pub fn request_quit() {
     unsafe {
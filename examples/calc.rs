@@ -16,7 +16,7 @@
 
 use druid::{AppLauncher, Data, LensWrap, Widget, WindowDesc};
 
-use druid::widget::{Button, Column, DynLabel, Padding, Row};
+use druid::widget::{Button, DynLabel, Flex, Padding};
 
 #[derive(Clone, Data)]
 struct CalcState {
@@ -161,67 +161,65 @@ fn flex_row<T: Data>(
     w3: impl Widget<T> + 'static,
     w4: impl Widget<T> + 'static,
 ) -> impl Widget<T> {
-    let mut row = Row::new();
-    row.add_child(w1, 1.0);
-    row.add_child(w2, 1.0);
-    row.add_child(w3, 1.0);
-    row.add_child(w4, 1.0);
-    row
+    Flex::row()
+        .with_flex_child(w1, 1.0)
+        .with_flex_child(w2, 1.0)
+        .with_flex_child(w3, 1.0)
+        .with_flex_child(w4, 1.0)
 }
 
 fn build_calc() -> impl Widget<CalcState> {
-    let mut column = Column::new();
     let display = LensWrap::new(
         DynLabel::new(|data: &String, _env| data.clone()),
         lenses::calc_state::Value,
     );
-    column.add_child(pad(display), 0.0);
-    column.add_child(
-        flex_row(
-            op_button_label('c', "CE".to_string()),
-            op_button('C'),
-            op_button('⌫'),
-            op_button('÷'),
-        ),
-        1.0,
-    );
-    column.add_child(
-        flex_row(
-            digit_button(7),
-            digit_button(8),
-            digit_button(9),
-            op_button('×'),
-        ),
-        1.0,
-    );
-    column.add_child(
-        flex_row(
-            digit_button(4),
-            digit_button(5),
-            digit_button(6),
-            op_button('−'),
-        ),
-        1.0,
-    );
-    column.add_child(
-        flex_row(
-            digit_button(1),
-            digit_button(2),
-            digit_button(3),
-            op_button('+'),
-        ),
-        1.0,
-    );
-    column.add_child(
-        flex_row(
-            op_button('±'),
-            digit_button(0),
-            op_button('.'),
-            op_button('='),
-        ),
-        1.0,
-    );
-    column
+    Flex::column()
+        .with_child(pad(display))
+        .with_flex_child(
+            flex_row(
+                op_button_label('c', "CE".to_string()),
+                op_button('C'),
+                op_button('⌫'),
+                op_button('÷'),
+            ),
+            1.0,
+        )
+        .with_flex_child(
+            flex_row(
+                digit_button(7),
+                digit_button(8),
+                digit_button(9),
+                op_button('×'),
+            ),
+            1.0,
+        )
+        .with_flex_child(
+            flex_row(
+                digit_button(4),
+                digit_button(5),
+                digit_button(6),
+                op_button('−'),
+            ),
+            1.0,
+        )
+        .with_flex_child(
+            flex_row(
+                digit_button(1),
+                digit_button(2),
+                digit_button(3),
+                op_button('+'),
+            ),
+            1.0,
+        )
+        .with_flex_child(
+            flex_row(
+                op_button('±'),
+                digit_button(0),
+                op_button('.'),
+                op_button('='),
+            ),
+            1.0,
+        )
 }
 
 fn main() {
@@ -14,25 +14,20 @@
 
 //! This example shows how to construct a basic layout.
 
-use druid::widget::{Button, Column, Label, Padding, Row, SizedBox};
+use druid::widget::{Button, Flex, Label, Padding, SizedBox};
 use druid::{AppLauncher, Widget, WindowDesc};
 
 fn build_app() -> impl Widget<u32> {
-    // Begin construction of vertical layout
-    let mut col = Column::new();
-
     // Construct a horizontal layout.
-    let mut header = Row::new();
-    header.add_child(SizedBox::new(Label::new("One")).width(60.0), 0.0);
-    // Spacing element that will fill all available space in between label
-    // and a button. Notice that weight is non-zero.
-    header.add_child(SizedBox::empty().expand(), 1.0);
-    header.add_child(
-        Padding::uniform(20.0, Button::new("Two", Button::noop)),
-        0.0,
-    );
+    let header = Flex::row()
+        .with_child(SizedBox::new(Label::new("One")).width(60.0))
+        // Spacing element that will fill all available space in between label
+        // and a button. Notice that weight is non-zero.
+        .with_flex_child(SizedBox::empty().expand(), 1.0)
+        .with_child(Padding::new((20.0, 10.0), Button::new("Two", Button::noop)));
 
-    col.add_child(SizedBox::new(header).height(100.0), 0.0);
+    // Begin construction of vertical layout
+    let mut col = Flex::column().with_child(SizedBox::new(header).height(100.0));
 
     for i in 0..5 {
         // Give a larger weight to one of the buttons for it to
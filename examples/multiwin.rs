@@ -16,10 +16,11 @@
 
 use druid::kurbo::Size;
 use druid::menu::{ContextMenu, MenuDesc, MenuItem};
-use druid::widget::{Align, Button, Column, Label, Padding, Row};
+use druid::widget::{Align, Button, Flex, Label, Padding};
 use druid::{
-    AppLauncher, BaseState, BoxConstraints, Command, Data, Env, Event, EventCtx, LayoutCtx,
-    LocalizedString, PaintCtx, Selector, UpdateCtx, Widget, WindowDesc,
+    AppDelegate, AppLauncher, BaseState, BoxConstraints, Command, Data, DelegateCtx, Env, Event,
+    EventCtx, LayoutCtx, LocalizedString, PaintCtx, Selector, UpdateCtx, Widget, WindowDesc,
+    WindowId, WindowPlacement,
 };
 
 const MENU_COUNT_ACTION: Selector = Selector::new("menu-count-action");
@@ -36,10 +37,23 @@ fn main() {
     simple_logger::init().unwrap();
     let main_window = WindowDesc::new(ui_builder).menu(make_menu(&State::default()));
     AppLauncher::with_window(main_window)
+        .delegate(Delegate)
         .launch(State::default())
         .expect("launch failed");
 }
 
+struct Delegate;
+
+impl AppDelegate<State> for Delegate {
+    fn window_added(&mut self, id: WindowId, _data: &State, _env: &Env, ctx: &mut DelegateCtx) {
+        println!("Window added, id: {:?}, {} open", id, ctx.windows().len());
+    }
+
+    fn window_removed(&mut self, id: WindowId, _data: &State, _env: &Env, ctx: &mut DelegateCtx) {
+        println!("Window removed, id: {:?}, {} open", id, ctx.windows().len());
+    }
+}
+
 // this is just an experiment for how we might reduce boilerplate.
 trait EventCtxExt {
     fn set_menu<T: 'static>(&mut self, menu: MenuDesc<T>);
@@ -58,42 +72,43 @@ fn ui_builder() -> impl Widget<State> {
     let label = Label::new(text);
     let inc_button = Button::<State>::new("Add menu item", |ctx, data, _env| {
         data.menu_count += 1;
-        ctx.set_menu(make_menu::<State>(data));
+        ctx.set_menu(make_menu(data));
     });
     let dec_button = Button::<State>::new("Remove menu item", |ctx, data, _env| {
         data.menu_count = data.menu_count.saturating_sub(1);
-        ctx.set_menu(make_menu::<State>(data));
+        ctx.set_menu(make_menu(data));
     });
 
-    let mut col = Column::new();
-    col.add_child(Align::centered(Padding::uniform(5.0, label)), 1.0);
-    let mut row = Row::new();
-    row.add_child(Padding::uniform(5.0, inc_button), 1.0);
-    row.add_child(Padding::uniform(5.0, dec_button), 1.0);
-    col.add_child(row, 1.0);
+    let row = Flex::row()
+        .with_flex_child(Padding::uniform(5.0, inc_button), 1.0)
+        .with_flex_child(Padding::uniform(5.0, dec_button), 1.0);
+    let col = Flex::column()
+        .with_flex_child(Align::centered(Padding::uniform(5.0, label)), 1.0)
+        .with_flex_child(row, 1.0);
 
     EventInterceptor::new(col, |event, ctx, data, _env| match event {
         Event::Command(ref cmd) if cmd.selector == druid::command::sys::NEW_FILE => {
-            let new_win = WindowDesc::new(ui_builder).menu(make_menu(data));
+            let new_win = WindowDesc::new(ui_builder)
+                .menu(make_menu(data))
+                .placement(WindowPlacement::Cascade);
             let command = Command::new(druid::command::sys::NEW_WINDOW, new_win);
             ctx.submit_command(command, None);
             None
         }
         Event::Command(ref cmd) if cmd.selector == MENU_COUNT_ACTION => {
             data.selected = *cmd.get_object().unwrap();
-            ctx.set_menu(make_menu::<State>(data));
             None
         }
         // wouldn't it be nice if a menu (like a button) could just mutate state
         // directly if desired?
         Event::Command(ref cmd) if cmd.selector == MENU_INCREMENT_ACTION => {
             data.menu_count += 1;
-            ctx.set_menu(make_menu::<State>(data));
+            ctx.set_menu(make_menu(data));
             None
         }
         Event::Command(ref cmd) if cmd.selector == MENU_DECREMENT_ACTION => {
             data.menu_count = data.menu_count.saturating_sub(1);
-            ctx.set_menu(make_menu::<State>(data));
+            ctx.set_menu(make_menu(data));
             None
         }
         Event::MouseDown(ref mouse) if mouse.button.is_right() => {
@@ -156,7 +171,7 @@ impl<T: Data> Widget<T> for EventInterceptor<T> {
 }
 
 #[allow(unused_assignments)]
-fn make_menu<T: Data>(state: &State) -> MenuDesc<T> {
+fn make_menu(state: &State) -> MenuDesc<State> {
     let mut base = MenuDesc::empty();
     #[cfg(target_os = "macos")]
     {
@@ -175,13 +190,13 @@ fn make_menu<T: Data>(state: &State) -> MenuDesc<T> {
                             .with_arg("count", move |_, _| i.into()),
                         Command::new(MENU_COUNT_ACTION, i),
                     )
-                    .disabled_if(|| i % 3 == 0)
-                    .selected_if(|| i == state.selected)
+                    .disabled_if(move |_, _| i % 3 == 0)
+                    .selected_if(move |data: &State, _| i == data.selected)
                 })
             }),
         );
     }
-    base
+    base.append(druid::menu::sys::common::window_menu())
 }
 
 fn make_context_menu<T: Data>() -> MenuDesc<T> {
@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use druid::widget::{Column, Padding, Radio, RadioGroup, SizedBox};
+use druid::widget::{Flex, Padding, Radio, RadioGroup, SizedBox};
 use druid::{AppLauncher, Data, Widget, WindowDesc};
 
 #[derive(Clone, PartialEq, Data)]
@@ -24,37 +24,20 @@ enum Choice {
 }
 
 fn build_widget() -> impl Widget<Choice> {
-    let mut col = Column::new();
-
-    col.add_child(
-        Padding::uniform(5.0, Radio::new("First choice", Choice::A)),
-        0.0,
-    );
-    col.add_child(
-        Padding::uniform(5.0, Radio::new("Second choice", Choice::B)),
-        0.0,
-    );
-    col.add_child(
-        Padding::uniform(5.0, Radio::new("Worst choice", Choice::C)),
-        0.0,
-    );
-    col.add_child(
-        Padding::uniform(5.0, Radio::new("Best choice", Choice::D)),
-        0.0,
-    );
-
-    col.add_child(SizedBox::empty(), 1.0);
-
-    col.add_child(
-        RadioGroup::new(vec![
+    Flex::column()
+        .with_child(Padding::uniform(5.0, Radio::new("First choice", Choice::A)))
+        .with_child(Padding::uniform(
+            5.0,
+            Radio::new("Second choice", Choice::B),
+        ))
+        .with_child(Padding::uniform(5.0, Radio::new("Worst choice", Choice::C)))
+        .with_child(Padding::uniform(5.0, Radio::new("Best choice", Choice::D)))
+        .with_flex_child(SizedBox::empty(), 1.0)
+        .with_child(RadioGroup::new(vec![
             ("Good times", Choice::A),
             ("Ergonomics", Choice::B),
             ("No fourth choice!", Choice::C),
-        ]),
-        0.0,
-    );
-
-    col
+        ]))
 }
 
 fn main() {
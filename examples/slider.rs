@@ -12,9 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use druid::widget::{
-    Align, Button, Checkbox, Column, DynLabel, Label, Padding, ProgressBar, Row, Slider,
-};
+use druid::widget::{Align, Button, Checkbox, DynLabel, Flex, Label, Padding, ProgressBar, Slider};
 use druid::{AppLauncher, Data, LensWrap, Widget, WindowDesc};
 
 #[derive(Clone, Data)]
@@ -53,7 +51,6 @@ mod lenses {
 }
 
 fn build_widget() -> impl Widget<DemoState> {
-    let mut col = Column::new();
     let label = DynLabel::new(|data: &DemoState, _env| {
         if data.double {
             format!("2x the value: {0:.2}", data.value * 2.0)
@@ -61,11 +58,11 @@ fn build_widget() -> impl Widget<DemoState> {
             format!("actual value: {0:.2}", data.value)
         }
     });
-    let mut row = Row::new();
     let checkbox = LensWrap::new(Checkbox::new(), lenses::demo_state::Double);
     let checkbox_label = Label::new("double the value");
-    row.add_child(checkbox, 0.0);
-    row.add_child(Padding::uniform(5.0, checkbox_label), 1.0);
+    let row = Flex::row()
+        .with_child(checkbox)
+        .with_flex_child(Padding::uniform(5.0, checkbox_label), 1.0);
 
     let bar = LensWrap::new(ProgressBar::new(), lenses::demo_state::Value);
     let slider = LensWrap::new(Slider::new(), lenses::demo_state::Value);
@@ -80,13 +77,13 @@ fn build_widget() -> impl Widget<DemoState> {
         data.value -= 0.1
     });
 
-    col.add_child(Padding::uniform(5.0, bar), 1.0);
-    col.add_child(Padding::uniform(5.0, slider), 1.0);
-    col.add_child(Padding::uniform(5.0, label), 1.0);
-    col.add_child(Padding::uniform(5.0, row), 1.0);
-    col.add_child(Padding::uniform(5.0, Align::right(button_1)), 0.0);
-    col.add_child(Padding::uniform(5.0, button_2), 1.0);
-    col
+    Flex::column()
+        .with_flex_child(Padding::uniform(5.0, bar), 1.0)
+        .with_flex_child(Padding::uniform(5.0, slider), 1.0)
+        .with_flex_child(Padding::uniform(5.0, label), 1.0)
+        .with_flex_child(Padding::uniform(5.0, row), 1.0)
+        .with_child(Padding::uniform(5.0, Align::right(button_1)))
+        .with_flex_child(Padding::uniform(5.0, button_2), 1.0)
 }
 
 fn main() {
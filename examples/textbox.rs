@@ -12,8 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use druid::widget::{Column, DynLabel, Padding, TextBox};
-use druid::{AppLauncher, Data, LocalizedString, MenuDesc, Widget, WindowDesc};
+use druid::widget::{DynLabel, Flex, Padding, TextBox};
+use druid::{AppLauncher, Data, MenuDesc, Widget, WindowDesc};
 
 fn main() {
     let window = WindowDesc::new(build_widget).menu(make_main_menu());
@@ -24,25 +24,18 @@ fn main() {
 }
 
 fn build_widget() -> impl Widget<String> {
-    let mut col = Column::new();
-
     let textbox = TextBox::new();
-    let textbox_2 = TextBox::new();
+    let textbox_2 = TextBox::with_placeholder("Type something");
     let label = DynLabel::new(|data: &String, _env| format!("value: {}", data));
 
-    col.add_child(Padding::uniform(5.0, textbox), 1.0);
-    col.add_child(Padding::uniform(5.0, textbox_2), 1.0);
-    col.add_child(Padding::uniform(5.0, label), 1.0);
-    col
+    Flex::column()
+        .with_flex_child(Padding::uniform(5.0, textbox), 1.0)
+        .with_flex_child(Padding::uniform(5.0, textbox_2), 1.0)
+        .with_flex_child(Padding::uniform(5.0, label), 1.0)
 }
 
 fn make_main_menu<T: Data>() -> MenuDesc<T> {
-    let edit_menu = MenuDesc::new(LocalizedString::new("common-menu-edit-menu"))
-        .append(druid::menu::sys::common::cut())
-        .append(druid::menu::sys::common::copy())
-        .append(druid::menu::sys::common::paste());
-
     MenuDesc::platform_default()
         .unwrap_or(MenuDesc::empty())
-        .append(edit_menu)
+        .append(druid::menu::sys::common::edit_menu())
 }
@@ -18,6 +18,10 @@ common-menu-file-new = New
 common-menu-file-new-window = New Window
 
 common-menu-file-open = Open...
+common-menu-file-open-recent = Open Recent
+# an item in the 'Open Recent' menu; $name is the name of the file
+common-menu-file-recent-file = { $name }
+common-menu-file-clear-recent = Clear Menu
 common-menu-file-close = Close
 
 common-menu-file-save = Save
@@ -39,3 +43,35 @@ common-menu-copy = Copy
 common-menu-paste = Paste
 common-menu-undo = Undo
 common-menu-redo = Redo
+common-menu-select-all = Select All
+
+# common 'Window' menu items.
+common-menu-window-menu = Window
+common-menu-window-minimize = Minimize
+common-menu-window-zoom = Zoom
+# an item listing an open window; $title is the window's title
+common-menu-window-item = { $title }
+
+# the macOS 'Window' menu items for tabs
+macos-menu-window-previous-tab = Show Previous Tab
+macos-menu-window-next-tab = Show Next Tab
+macos-menu-window-merge-all = Merge All Windows
+
+# Long dates, such as "December 31, 2019"
+date-long = { $month } { $day }, { $year }
+date-month-1 = January
+date-month-2 = February
+date-month-3 = March
+date-month-4 = April
+date-month-5 = May
+date-month-6 = June
+date-month-7 = July
+date-month-8 = August
+date-month-9 = September
+date-month-10 = October
+date-month-11 = November
+date-month-12 = December
+
+# The time-travel debugging window
+time-travel-title = Time travel
+inspector-title = Inspector
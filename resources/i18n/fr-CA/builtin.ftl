@@ -18,6 +18,10 @@ common-menu-file-new = Nouveau
 common-menu-file-new-window = Nouvelle fenêtre
 
 common-menu-file-open = Ouvrir...
+common-menu-file-open-recent = Ouvrir un fichier récent
+# an item in the 'Open Recent' menu; $name is the name of the file
+common-menu-file-recent-file = { $name }
+common-menu-file-clear-recent = Effacer le menu
 common-menu-file-close = Fermer
 
 common-menu-file-save = Enregistrer
@@ -39,3 +43,38 @@ common-menu-copy = Copier
 common-menu-paste = Coller
 common-menu-undo = Annuler
 common-menu-redo = Rétablir
+common-menu-select-all = Tout sélectionner
+
+# common 'Window' menu items.
+common-menu-window-menu = Fenêtre
+common-menu-window-minimize = Réduire
+common-menu-window-zoom = Zoom
+# an item listing an open window; $title is the window's title
+common-menu-window-item = { $title }
+
+# the macOS 'Window' menu items for tabs
+macos-menu-window-previous-tab = Afficher l'onglet précédent
+macos-menu-window-next-tab = Afficher l'onglet suivant
+macos-menu-window-merge-all = Fusionner toutes les fenêtres
+
+# Long dates, such as "31 décembre 2019"
+date-long = { $day ->
+    [1] 1er
+   *[other] { $day }
+} { $month } { $year }
+date-month-1 = janvier
+date-month-2 = février
+date-month-3 = mars
+date-month-4 = avril
+date-month-5 = mai
+date-month-6 = juin
+date-month-7 = juillet
+date-month-8 = août
+date-month-9 = septembre
+date-month-10 = octobre
+date-month-11 = novembre
+date-month-12 = décembre
+
+# The time-travel debugging window
+time-travel-title = Voyage dans le temps
+inspector-title = Inspecteur
@@ -0,0 +1,252 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Animating values over time.
+//!
+//! An [`Animator`] holds a value that moves smoothly towards a target,
+//! according to a [`Transition`]. A widget advances it on each
+//! [`Event::AnimFrame`], requesting another frame for as long as the
+//! animation is running.
+//!
+//! [`Animator`]: struct.Animator.html
+//! [`Transition`]: struct.Transition.html
+//! [`Event::AnimFrame`]: enum.Event.html#variant.AnimFrame
+
+use std::time::Duration;
+
+use crate::kurbo::{Point, Rect, Size, Vec2};
+use crate::piet::Color;
+use crate::Easing;
+
+/// A value that can be interpolated between two end points.
+pub trait Interpolate: Clone {
+    /// The value a fraction `t` of the way from `self` to `other`.
+    ///
+    /// `t` is usually between 0 and 1, but easing curves that overshoot
+    /// may give values outside of that range.
+    fn interpolate(&self, other: &Self, t: f64) -> Self;
+}
+
+/// How a value moves from one end point to another: how long it takes,
+/// and the easing curve it follows.
+#[derive(Debug, Clone, Copy)]
+pub struct Transition {
+    duration: Duration,
+    easing: Easing,
+}
+
+/// A value that moves towards its target over time.
+#[derive(Debug, Clone)]
+pub struct Animator<V> {
+    transition: Transition,
+    from: V,
+    to: V,
+    /// The time since the current animation started, if it is running.
+    elapsed: Option<Duration>,
+}
+
+impl Transition {
+    /// A transition taking the given time, easing in and out.
+    pub fn new(duration: Duration) -> Self {
+        Transition {
+            duration,
+            easing: Easing::default(),
+        }
+    }
+
+    /// A transition that changes the value immediately.
+    pub fn instant() -> Self {
+        Transition::new(Duration::from_secs(0))
+    }
+
+    /// Set the easing curve. The default is [`Easing::EaseInOut`].
+    ///
+    /// [`Easing::EaseInOut`]: enum.Easing.html#variant.EaseInOut
+    pub fn easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// The time the transition takes.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// The fraction of the distance covered after `elapsed`.
+    fn progress(&self, elapsed: Duration) -> f64 {
+        if elapsed >= self.duration {
+            return 1.0;
+        }
+        let t = elapsed.as_secs_f64() / self.duration.as_secs_f64();
+        self.easing.eval(t)
+    }
+}
+
+impl Default for Transition {
+    fn default() -> Self {
+        Transition::new(Duration::from_millis(250))
+    }
+}
+
+impl<V: Interpolate> Animator<V> {
+    /// Create an animator at rest at `value`.
+    pub fn new(value: V, transition: Transition) -> Self {
+        Animator {
+            transition,
+            from: value.clone(),
+            to: value,
+            elapsed: None,
+        }
+    }
+
+    /// The current value.
+    pub fn value(&self) -> V {
+        match self.elapsed {
+            Some(elapsed) => {
+                let t = self.transition.progress(elapsed);
+                self.from.interpolate(&self.to, t)
+            }
+            None => self.to.clone(),
+        }
+    }
+
+    /// The value the animator is moving towards.
+    pub fn target(&self) -> &V {
+        &self.to
+    }
+
+    /// Returns `true` while the value is moving.
+    pub fn is_running(&self) -> bool {
+        self.elapsed.is_some()
+    }
+
+    /// Change the transition used by later animations.
+    pub fn set_transition(&mut self, transition: Transition) {
+        self.transition = transition;
+    }
+
+    /// Start moving towards a new target, from the current value.
+    ///
+    /// The caller should then request an animation frame, and pass the
+    /// frame intervals to [`advance`].
+    ///
+    /// [`advance`]: #method.advance
+    pub fn animate_to(&mut self, target: V) {
+        self.from = self.value();
+        self.to = target;
+        self.elapsed = if self.transition.duration == Duration::from_secs(0) {
+            None
+        } else {
+            Some(Duration::from_secs(0))
+        };
+    }
+
+    /// Change the value immediately, stopping any animation.
+    pub fn jump_to(&mut self, value: V) {
+        self.from = value.clone();
+        self.to = value;
+        self.elapsed = None;
+    }
+
+    /// Advance the animation by an [`Event::AnimFrame`] interval, in
+    /// nanoseconds.
+    ///
+    /// Returns `true` if the animation is still running, in which case
+    /// another frame should be requested.
+    ///
+    /// [`Event::AnimFrame`]: enum.Event.html#variant.AnimFrame
+    pub fn advance(&mut self, nanos: u64) -> bool {
+        if let Some(elapsed) = self.elapsed {
+            let elapsed = elapsed + Duration::from_nanos(nanos);
+            self.elapsed = if elapsed >= self.transition.duration {
+                None
+            } else {
+                Some(elapsed)
+            };
+        }
+        self.is_running()
+    }
+}
+
+impl Interpolate for f64 {
+    fn interpolate(&self, other: &f64, t: f64) -> f64 {
+        self + (other - self) * t
+    }
+}
+
+impl Interpolate for Point {
+    fn interpolate(&self, other: &Point, t: f64) -> Point {
+        self.lerp(*other, t)
+    }
+}
+
+impl Interpolate for Vec2 {
+    fn interpolate(&self, other: &Vec2, t: f64) -> Vec2 {
+        self.lerp(*other, t)
+    }
+}
+
+impl Interpolate for Size {
+    fn interpolate(&self, other: &Size, t: f64) -> Size {
+        Size::new(
+            self.width.interpolate(&other.width, t),
+            self.height.interpolate(&other.height, t),
+        )
+    }
+}
+
+impl Interpolate for Rect {
+    fn interpolate(&self, other: &Rect, t: f64) -> Rect {
+        Rect::from_points(
+            self.origin().interpolate(&other.origin(), t),
+            Point::new(self.x1, self.y1).interpolate(&Point::new(other.x1, other.y1), t),
+        )
+    }
+}
+
+/// Colors are interpolated component by component, including alpha.
+impl Interpolate for Color {
+    fn interpolate(&self, other: &Color, t: f64) -> Color {
+        let (c1, c2) = (self.as_rgba_u32(), other.as_rgba_u32());
+        let component = |shift: u32| {
+            let a = ((c1 >> shift) & 0xff) as f64;
+            let b = ((c2 >> shift) & 0xff) as f64;
+            a.interpolate(&b, t).round().max(0.0).min(255.0) as u8
+        };
+        Color::rgba8(component(24), component(16), component(8), component(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn animate() {
+        let linear = Transition::new(Duration::from_millis(100)).easing(Easing::Linear);
+        let mut animator = Animator::new(0.0, linear);
+        assert!(!animator.is_running());
+
+        animator.animate_to(10.0);
+        assert!(animator.advance(50_000_000));
+        assert!((animator.value() - 5.0).abs() < 1e-9);
+
+        // Retargeting starts from where the value is now.
+        animator.animate_to(0.0);
+        assert!(animator.advance(50_000_000));
+        assert!((animator.value() - 2.5).abs() < 1e-9);
+        assert!(!animator.advance(50_000_000));
+        assert_eq!(animator.value(), 0.0);
+    }
+}
@@ -15,20 +15,88 @@
 //! Window building and app lifecycle.
 
 use std::cell::RefCell;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::sync::Arc;
 
+use log::{error, warn};
+
+use crate::kurbo::{Point, Rect, Size, Vec2};
+use crate::localization::L10nManager;
+#[cfg(feature = "persistence")]
+use crate::persist::Persistence;
+use crate::replay::{Recorder, Replayer};
+use crate::shell::application::Application;
+use crate::shell::raw_window_handle::RawWindowHandle;
 use crate::shell::window::WindowHandle;
 use crate::shell::{init, runloop, Error as PlatformError, WindowBuilder};
+use crate::time_travel::{self, History};
 use crate::win_handler::AppState;
 use crate::window::{Window, WindowId};
-use crate::{theme, Data, DruidHandler, LocalizedString, MenuDesc, Widget};
+use crate::{
+    theme, AppDelegate, Command, Data, DruidHandler, Env, L10nResources, LocalizedString, MenuDesc,
+    Recording, Selector, Widget,
+};
+
+/// The command-line flag used to launch the application from a jump list task.
+const JUMP_LIST_TASK_FLAG: &str = "--druid-task=";
 
 /// Handles initial setup of an application, and starts the runloop.
 pub struct AppLauncher<T> {
     windows: Vec<WindowDesc<T>>,
+    jump_list: Vec<JumpListTask>,
+    fonts: Vec<Vec<u8>>,
+    l10n: Option<L10nResources>,
+    #[cfg(feature = "tokio")]
+    tokio_runtime: Option<tokio::runtime::Runtime>,
+    #[cfg(feature = "persistence")]
+    persistence: Option<Persistence<T>>,
+    time_travel: bool,
+    record_events: Option<PathBuf>,
+    replay_events: Option<Recording>,
+    env_setup: Option<Box<dyn FnOnce(Env) -> Env>>,
+    delegate: Option<Box<dyn AppDelegate<T>>>,
+    quit_on_last_window: bool,
+}
+
+/// A task in the application's jump list. (windows only)
+///
+/// Choosing a task launches a new instance of the application, which
+/// receives the task's command once its first window is open.
+#[derive(Debug, Clone)]
+pub struct JumpListTask {
+    // Only shown by the platforms that have jump lists.
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    title: String,
+    description: Option<String>,
+    command: Selector,
+}
+
+/// Where a new window is placed, relative to the window it was opened
+/// from.
+///
+/// This applies to windows opened with the [`NEW_WINDOW`] command; the
+/// windows an application is launched with are always placed by the
+/// platform.
+///
+/// [`NEW_WINDOW`]: command/sys/constant.NEW_WINDOW.html
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WindowPlacement {
+    /// Wherever the platform puts new windows. This is the default.
+    Default,
+    /// Down and to the right of the parent window, by the height of a
+    /// title bar.
+    Cascade,
+    /// Centered on the parent window.
+    CenteredOnParent,
+    /// With the top left corner at the mouse pointer, as of the last mouse
+    /// event in the parent window.
+    AtPointer,
 }
 
+/// The distance between cascaded windows, in px.
+const CASCADE_OFFSET: f64 = 22.0;
+
 /// A function that can create a widget.
 type WidgetBuilderFn<T> = dyn Fn() -> Box<dyn Widget<T>> + 'static;
 
@@ -40,6 +108,17 @@ pub struct WindowDesc<T> {
     pub(crate) root_builder: Arc<WidgetBuilderFn<T>>,
     pub(crate) title: Option<LocalizedString<T>>,
     pub(crate) menu: Option<MenuDesc<T>>,
+    pub(crate) placement: WindowPlacement,
+    pub(crate) tabbing_identifier: Option<String>,
+    /// The id of the window built from this description.
+    ///
+    /// Commands can be submitted to this id before the window is built,
+    /// for instance right after sending the description in a
+    /// [`NEW_WINDOW`] command. If the description is used again while its
+    /// window is still open, the new window gets a fresh id.
+    ///
+    /// [`NEW_WINDOW`]: command/sys/constant.NEW_WINDOW.html
+    pub id: WindowId,
     //TODO: more things you can configure on a window, like size?
 }
 
@@ -48,9 +127,175 @@ impl<T: Data + 'static> AppLauncher<T> {
     pub fn with_window(window: WindowDesc<T>) -> Self {
         AppLauncher {
             windows: vec![window],
+            jump_list: Vec::new(),
+            fonts: Vec::new(),
+            l10n: None,
+            #[cfg(feature = "tokio")]
+            tokio_runtime: None,
+            #[cfg(feature = "persistence")]
+            persistence: None,
+            time_travel: false,
+            record_events: None,
+            replay_events: None,
+            env_setup: None,
+            delegate: None,
+            quit_on_last_window: !cfg!(target_os = "macos"),
         }
     }
 
+    /// Set the application's [`AppDelegate`], which is told about windows
+    /// opening and closing, and sees every command first.
+    ///
+    /// [`AppDelegate`]: trait.AppDelegate.html
+    pub fn delegate(mut self, delegate: impl AppDelegate<T> + 'static) -> Self {
+        self.delegate = Some(Box::new(delegate));
+        self
+    }
+
+    /// Set whether the application quits when its last window is closed.
+    ///
+    /// By default it does, except on macOS, where applications
+    /// conventionally keep running without windows.
+    pub fn quit_when_last_window_closes(mut self, quit: bool) -> Self {
+        self.quit_on_last_window = quit;
+        self
+    }
+
+    /// Load a font from the contents of a TrueType or OpenType file.
+    ///
+    /// Fonts are registered when the application is launched, after which
+    /// they can be selected by family name, for instance by setting
+    /// [`theme::FONT_NAME`] in [`configure_env`].
+    ///
+    /// This is not yet supported on Windows or the web.
+    ///
+    /// [`theme::FONT_NAME`]: theme/constant.FONT_NAME.html
+    /// [`configure_env`]: #method.configure_env
+    pub fn with_font(mut self, data: impl Into<Vec<u8>>) -> Self {
+        self.fonts.push(data.into());
+        self
+    }
+
+    /// Load a font from a TrueType or OpenType file.
+    ///
+    /// See [`with_font`] for details. If the file can't be read, this
+    /// logs a warning, and the font is skipped.
+    ///
+    /// [`with_font`]: #method.with_font
+    pub fn with_font_file(self, path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        match std::fs::read(path) {
+            Ok(data) => self.with_font(data),
+            Err(e) => {
+                warn!("failed to read font '{}': {}", path.display(), e);
+                self
+            }
+        }
+    }
+
+    /// Provide the application's localization strings.
+    ///
+    /// These are used to resolve [`LocalizedString`]s, alongside Druid's
+    /// own strings.
+    ///
+    /// [`LocalizedString`]: struct.LocalizedString.html
+    pub fn localization(mut self, resources: L10nResources) -> Self {
+        self.l10n = Some(resources);
+        self
+    }
+
+    /// Provide a function to customize the initial [`Env`], such as by
+    /// overriding theme values.
+    ///
+    /// It is passed the default theme, and returns the environment that
+    /// the application uses.
+    ///
+    /// [`Env`]: struct.Env.html
+    pub fn configure_env(mut self, f: impl FnOnce(Env) -> Env + 'static) -> Self {
+        self.env_setup = Some(Box::new(f));
+        self
+    }
+
+    /// Run a tokio runtime alongside the application.
+    ///
+    /// Futures started with [`EventCtx::spawn`] are run on this runtime,
+    /// and it can be used to run the futures returned by
+    /// [`CommandSink::complete`] and [`CommandSink::forward_channel`]. The
+    /// runtime is shut down when the application quits.
+    ///
+    /// This requires the `tokio` feature.
+    ///
+    /// [`EventCtx::spawn`]: struct.EventCtx.html#method.spawn
+    /// [`CommandSink::complete`]: struct.CommandSink.html#method.complete
+    /// [`CommandSink::forward_channel`]: struct.CommandSink.html#method.forward_channel
+    #[cfg(feature = "tokio")]
+    pub fn tokio_runtime(mut self, runtime: tokio::runtime::Runtime) -> Self {
+        self.tokio_runtime = Some(runtime);
+        self
+    }
+
+    /// Save parts of the application's data between runs.
+    ///
+    /// The saved state is restored into the data passed to [`launch`].
+    ///
+    /// This requires the `persistence` feature.
+    ///
+    /// [`launch`]: #method.launch
+    #[cfg(feature = "persistence")]
+    pub fn persistence(mut self, persistence: Persistence<T>) -> Self {
+        self.persistence = Some(persistence);
+        self
+    }
+
+    /// Record the application's data after every change, and open a window
+    /// for stepping back and forth through the recording.
+    ///
+    /// Stepping to an earlier snapshot displays it in every window, which
+    /// helps to find where an update went wrong. Recording pauses while a
+    /// snapshot is displayed, and returning to the live state discards any
+    /// changes made in the meantime.
+    ///
+    /// This is intended for use during development only.
+    pub fn time_travel(mut self) -> Self {
+        self.time_travel = true;
+        self
+    }
+
+    /// Record the input delivered to the application's windows to a file,
+    /// replacing any existing file.
+    ///
+    /// The recording can be replayed with [`replay_events`], or in a test
+    /// with [`Harness::replay`], to reproduce a problem a user ran into.
+    /// Keys without a platform independent [`KeyCode`] are not recorded.
+    ///
+    /// [`replay_events`]: #method.replay_events
+    /// [`Harness::replay`]: struct.Harness.html#method.replay
+    /// [`KeyCode`]: enum.KeyCode.html
+    pub fn record_events(mut self, path: impl Into<PathBuf>) -> Self {
+        self.record_events = Some(path.into());
+        self
+    }
+
+    /// Replay a recording of input, at the times it was originally
+    /// delivered, counted from when the first window is shown.
+    ///
+    /// Events are delivered alongside any real input, to the windows in the
+    /// order they were opened. Windows are not resized to match recorded
+    /// sizes, so the application should be launched the same way as when
+    /// the recording was made.
+    pub fn replay_events(mut self, recording: Recording) -> Self {
+        self.replay_events = Some(recording);
+        self
+    }
+
+    /// Add a task to the application's jump list.
+    ///
+    /// This only has an effect on Windows.
+    pub fn jump_list_task(mut self, task: JumpListTask) -> Self {
+        self.jump_list.push(task);
+        self
+    }
+
     /// Initialize a minimal logger for printing logs out to stderr.
     ///
     /// Meant for use during development only.
@@ -66,19 +311,124 @@ impl<T: Data + 'static> AppLauncher<T> {
     pub fn launch(self, data: T) -> Result<(), PlatformError> {
         init();
         let mut main_loop = runloop::RunLoop::new();
-        let env = theme::init();
+        for font in &self.fonts {
+            Application::register_font(font);
+        }
+        #[cfg(feature = "tokio")]
+        {
+            if let Some(runtime) = self.tokio_runtime.as_ref() {
+                crate::ext_event::set_runtime(runtime.handle().clone());
+            }
+        }
+        let mut env = theme::init();
+        if let Some(resources) = self.l10n {
+            env.set_localization(L10nManager::from_resources(resources));
+        }
+        if let Some(f) = self.env_setup {
+            env = f(env);
+        }
+        env.set_high_contrast(Application::is_high_contrast());
+        env.set_reduced_motion(Application::prefers_reduced_motion());
+        #[cfg(feature = "persistence")]
+        let (data, persistence) = {
+            let (mut data, mut persistence) = (data, self.persistence);
+            if let Some(persistence) = persistence.as_mut() {
+                persistence.restore(&mut data);
+            }
+            (data, persistence)
+        };
         let state = AppState::new(data, env);
+        state.borrow_mut().delegate = self.delegate;
+        state.borrow_mut().quit_on_last_window = self.quit_on_last_window;
+        #[cfg(feature = "persistence")]
+        {
+            state.borrow_mut().persistence = persistence;
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            let tasks = self
+                .jump_list
+                .iter()
+                .map(JumpListTask::to_platform)
+                .collect::<Vec<_>>();
+            Application::set_jump_list(&tasks);
+        }
 
-        for desc in self.windows {
+        if let Some(path) = self.record_events {
+            match Recorder::create(&path) {
+                Ok(recorder) => state.borrow_mut().recorder = Some(recorder),
+                Err(e) => error!("failed to create '{}': {}", path.display(), e),
+            }
+        }
+        if let Some(recording) = self.replay_events {
+            state.borrow_mut().replayer = Some(Replayer::new(recording));
+        }
+
+        if let Some(cmd) = launch_task_command(&self.jump_list) {
+            state.borrow_mut().add_launch_command(cmd);
+        }
+
+        let mut windows = self.windows;
+        if self.time_travel {
+            let history = Rc::new(RefCell::new(History::new()));
+            history.borrow_mut().record(&state.borrow().data);
+            state.borrow_mut().history = Some(history.clone());
+            windows.push(time_travel::inspector(history));
+        }
+
+        for desc in windows {
             let window = desc.build_native(&state)?;
             window.show();
         }
 
         main_loop.run();
+        #[cfg(feature = "persistence")]
+        {
+            state.borrow_mut().save_state();
+        }
         Ok(())
     }
 }
 
+impl JumpListTask {
+    /// Create a new task, which will send `command` to the new instance of
+    /// the application.
+    pub fn new(title: impl Into<String>, command: Selector) -> Self {
+        JumpListTask {
+            title: title.into(),
+            description: None,
+            command,
+        }
+    }
+
+    /// Set the description of the task, shown as a tooltip.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    #[cfg(target_os = "windows")]
+    fn to_platform(&self) -> crate::shell::taskbar::JumpListTask {
+        crate::shell::taskbar::JumpListTask {
+            title: self.title.clone(),
+            description: self.description.clone(),
+            arguments: format!("{}{}", JUMP_LIST_TASK_FLAG, self.command.as_str()),
+        }
+    }
+}
+
+/// If the application was launched from a jump list task, returns the
+/// task's command.
+fn launch_task_command(tasks: &[JumpListTask]) -> Option<Command> {
+    let arg = std::env::args().find(|arg| arg.starts_with(JUMP_LIST_TASK_FLAG))?;
+    let name = &arg[JUMP_LIST_TASK_FLAG.len()..];
+    tasks
+        .iter()
+        .find(|task| task.command.as_str() == name)
+        .map(|task| task.command.clone().into())
+}
+
 impl<T: Data + 'static> WindowDesc<T> {
     /// Create a new `WindowDesc`, taking a funciton that will generate the root
     /// [`Widget`] for this window.
@@ -98,6 +448,9 @@ impl<T: Data + 'static> WindowDesc<T> {
             root_builder,
             title: None,
             menu: MenuDesc::platform_default(),
+            placement: WindowPlacement::Default,
+            tabbing_identifier: None,
+            id: WindowId::new(),
         }
     }
 
@@ -114,18 +467,32 @@ impl<T: Data + 'static> WindowDesc<T> {
     pub(crate) fn build_native(
         &self,
         state: &Rc<RefCell<AppState<T>>>,
+    ) -> Result<WindowHandle, PlatformError> {
+        self.build_native_in(state, None)
+    }
+
+    /// Attempt to create a platform window, embedded in `parent` if given.
+    ///
+    /// Embedded windows have no menu.
+    pub(crate) fn build_native_in(
+        &self,
+        state: &Rc<RefCell<AppState<T>>>,
+        parent: Option<RawWindowHandle>,
     ) -> Result<WindowHandle, PlatformError> {
         let mut title = self
             .title
             .clone()
             .unwrap_or(LocalizedString::new("app-name"));
         title.resolve(&state.borrow().data, &state.borrow().env);
-        let mut menu = self.menu.to_owned();
+        let mut menu = match parent {
+            Some(_) => None,
+            None => self.menu.to_owned(),
+        };
         let platform_menu = menu
             .as_mut()
             .map(|m| m.build_window_menu(&state.borrow().data, &state.borrow().env));
 
-        let id = WindowId::new();
+        let id = state.borrow().unused_window_id(self.id);
         let handler = DruidHandler::new_shared(state.clone(), id);
 
         let mut builder = WindowBuilder::new();
@@ -134,6 +501,15 @@ impl<T: Data + 'static> WindowDesc<T> {
         if let Some(menu) = platform_menu {
             builder.set_menu(menu);
         }
+        if let Some(parent) = parent {
+            builder.set_parent(parent);
+        }
+        #[cfg(target_os = "macos")]
+        {
+            if let Some(identifier) = self.tabbing_identifier.as_ref() {
+                builder.set_tabbing_identifier(identifier.as_str());
+            }
+        }
 
         let root = (self.root_builder)();
         state
@@ -145,9 +521,99 @@ impl<T: Data + 'static> WindowDesc<T> {
         })
     }
 
+    /// Add this window to the app state without creating a platform window,
+    /// returning its handler.
+    #[cfg(not(any(target_os = "windows", target_arch = "wasm32")))]
+    pub(crate) fn build_headless(&self, state: &Rc<RefCell<AppState<T>>>) -> DruidHandler<T> {
+        let mut title = self
+            .title
+            .clone()
+            .unwrap_or(LocalizedString::new("app-name"));
+        title.resolve(&state.borrow().data, &state.borrow().env);
+        let mut menu = self.menu.to_owned();
+        // Building the menu assigns the ids used to look up its commands.
+        if let Some(m) = menu.as_mut() {
+            m.build_window_menu(&state.borrow().data, &state.borrow().env);
+        }
+
+        let id = state.borrow().unused_window_id(self.id);
+        let root = (self.root_builder)();
+        state
+            .borrow_mut()
+            .add_window(id, Window::new(root, title, menu));
+        DruidHandler::new_shared(state.clone(), id)
+    }
+
     /// Set the menu for this window.
     pub fn menu(mut self, menu: MenuDesc<T>) -> Self {
         self.menu = Some(menu);
         self
     }
+
+    /// Set where this window is placed, when it is opened from another
+    /// window.
+    pub fn placement(mut self, placement: WindowPlacement) -> Self {
+        self.placement = placement;
+        self
+    }
+
+    /// Show this window as a native tab, alongside the other open windows
+    /// with the same identifier. (mac only)
+    ///
+    /// The tabs can be switched between and merged with the items in
+    /// [`menu::sys::mac::window`]. The window whose tab is shown receives
+    /// [`WINDOW_GOT_FOCUS`].
+    ///
+    /// [`menu::sys::mac::window`]: menu/sys/mac/window/index.html
+    /// [`WINDOW_GOT_FOCUS`]: command/sys/constant.WINDOW_GOT_FOCUS.html
+    pub fn tabbing_identifier(mut self, identifier: impl Into<String>) -> Self {
+        self.tabbing_identifier = Some(identifier.into());
+        self
+    }
+}
+
+impl WindowPlacement {
+    /// The position of the top left corner of a window of the given size,
+    /// or `None` to let the platform decide.
+    pub(crate) fn position(
+        self,
+        parent: Rect,
+        pointer: Option<Point>,
+        size: Size,
+    ) -> Option<Point> {
+        match self {
+            WindowPlacement::Default => None,
+            WindowPlacement::Cascade => {
+                Some(parent.origin() + Vec2::new(CASCADE_OFFSET, CASCADE_OFFSET))
+            }
+            WindowPlacement::CenteredOnParent => Some(Point::new(
+                parent.x0 + (parent.width() - size.width) / 2.0,
+                parent.y0 + (parent.height() - size.height) / 2.0,
+            )),
+            WindowPlacement::AtPointer => pointer,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placement_positions() {
+        let parent = Rect::new(100.0, 100.0, 500.0, 400.0);
+        let size = Size::new(200.0, 100.0);
+        let pointer = Some(Point::new(150.0, 120.0));
+        let position = |placement: WindowPlacement| placement.position(parent, pointer, size);
+        assert_eq!(position(WindowPlacement::Default), None);
+        assert_eq!(
+            position(WindowPlacement::Cascade),
+            Some(Point::new(122.0, 122.0))
+        );
+        assert_eq!(
+            position(WindowPlacement::CenteredOnParent),
+            Some(Point::new(200.0, 200.0))
+        );
+        assert_eq!(position(WindowPlacement::AtPointer), pointer);
+    }
 }
@@ -0,0 +1,107 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Customizing application-level behaviour.
+
+use std::collections::VecDeque;
+
+use crate::{Command, Data, Env, WindowId};
+
+/// A type that handles application-level behaviour, such as keeping track
+/// of the open windows.
+///
+/// A delegate is set with [`AppLauncher::delegate`]. All of its methods have
+/// default implementations that do nothing.
+///
+/// [`AppLauncher::delegate`]: struct.AppLauncher.html#method.delegate
+pub trait AppDelegate<T: Data> {
+    /// Called with every command, before it is delivered to its target
+    /// window.
+    ///
+    /// This includes the built in commands, so that, for instance, a
+    /// [`CLOSE_WINDOW`] can be intercepted to ask about unsaved changes.
+    /// Return `false` to stop the command from going any further.
+    ///
+    /// [`CLOSE_WINDOW`]: command/sys/constant.CLOSE_WINDOW.html
+    #[allow(unused_variables)]
+    fn command(
+        &mut self,
+        target: WindowId,
+        cmd: &Command,
+        data: &mut T,
+        env: &Env,
+        ctx: &mut DelegateCtx,
+    ) -> bool {
+        true
+    }
+
+    /// Called when a window has been opened.
+    ///
+    /// To change the data in response, submit a command to handle in
+    /// [`command`].
+    ///
+    /// [`command`]: #method.command
+    #[allow(unused_variables)]
+    fn window_added(&mut self, id: WindowId, data: &T, env: &Env, ctx: &mut DelegateCtx) {}
+
+    /// Called when a window has been closed, whether by a command or by the
+    /// user.
+    ///
+    /// By this time the window is no longer in [`DelegateCtx::windows`].
+    ///
+    /// [`DelegateCtx::windows`]: struct.DelegateCtx.html#method.windows
+    #[allow(unused_variables)]
+    fn window_removed(&mut self, id: WindowId, data: &T, env: &Env, ctx: &mut DelegateCtx) {}
+}
+
+/// A description of an open window.
+#[derive(Debug, Clone)]
+pub struct WindowInfo {
+    /// The window's id, which commands can be submitted to.
+    pub id: WindowId,
+    /// The window's title, in the current locale.
+    pub title: String,
+    /// Whether this is the window that most recently had focus.
+    pub focused: bool,
+}
+
+/// A context passed to [`AppDelegate`] methods.
+///
+/// Windows are managed by submitting the built in commands to them, such as
+/// [`SHOW_WINDOW`] to bring a window to front, or [`CLOSE_WINDOW`].
+///
+/// [`AppDelegate`]: trait.AppDelegate.html
+/// [`SHOW_WINDOW`]: command/sys/constant.SHOW_WINDOW.html
+/// [`CLOSE_WINDOW`]: command/sys/constant.CLOSE_WINDOW.html
+pub struct DelegateCtx<'a> {
+    pub(crate) windows: Vec<WindowInfo>,
+    pub(crate) command_queue: &'a mut VecDeque<(WindowId, Command)>,
+}
+
+impl<'a> DelegateCtx<'a> {
+    /// The open windows, sorted by id.
+    pub fn windows(&self) -> &[WindowInfo] {
+        &self.windows
+    }
+
+    /// The window that most recently had focus, if it is still open.
+    pub fn focused_window(&self) -> Option<WindowId> {
+        self.windows.iter().find(|w| w.focused).map(|w| w.id)
+    }
+
+    /// Submit a command to be delivered to the window `target`.
+    pub fn submit_command(&mut self, cmd: impl Into<Command>, target: WindowId) {
+        self.command_queue.push_back((target, cmd.into()));
+    }
+}
@@ -0,0 +1,76 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Fills that can be either a solid color or a gradient.
+
+use crate::kurbo::Shape;
+use crate::piet::{Color, LinearGradient, RadialGradient, RenderContext};
+use crate::PaintCtx;
+
+/// The fill of a widget's background.
+///
+/// This can be stored in the [`Env`], so a theme can give widgets such as
+/// [`Button`] a gradient fill where it would otherwise only pick a color.
+///
+/// # Examples
+///
+/// ```
+/// use druid::piet::{Color, LinearGradient, UnitPoint};
+/// use druid::{theme, Background, Env};
+///
+/// let fill = LinearGradient::new(
+///     UnitPoint::TOP,
+///     UnitPoint::BOTTOM,
+///     (Color::rgb8(0x40, 0x40, 0x40), Color::BLACK),
+/// );
+/// let env = theme::init().adding(theme::BUTTON_BACKGROUND, Background::from(fill));
+/// ```
+///
+/// [`Env`]: struct.Env.html
+/// [`Button`]: widget/struct.Button.html
+#[derive(Debug, Clone)]
+pub enum Background {
+    Color(Color),
+    LinearGradient(LinearGradient),
+    RadialGradient(RadialGradient),
+}
+
+impl Background {
+    /// Fill `shape` with this background.
+    pub fn paint(&self, paint_ctx: &mut PaintCtx, shape: impl Shape) {
+        match self {
+            Background::Color(color) => paint_ctx.fill(shape, color),
+            Background::LinearGradient(gradient) => paint_ctx.fill(shape, gradient),
+            Background::RadialGradient(gradient) => paint_ctx.fill(shape, gradient),
+        }
+    }
+}
+
+impl From<Color> for Background {
+    fn from(color: Color) -> Background {
+        Background::Color(color)
+    }
+}
+
+impl From<LinearGradient> for Background {
+    fn from(gradient: LinearGradient) -> Background {
+        Background::LinearGradient(gradient)
+    }
+}
+
+impl From<RadialGradient> for Background {
+    fn from(gradient: RadialGradient) -> Background {
+        Background::RadialGradient(gradient)
+    }
+}
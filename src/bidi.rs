@@ -0,0 +1,203 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bidirectional text and right-to-left layout.
+//!
+//! The functions here work on a single line of text, and follow the
+//! [Unicode bidirectional algorithm] closely enough for labels and
+//! single-line text fields. Paragraph separators should be handled by the
+//! caller, by splitting the text into lines first.
+//!
+//! [Unicode bidirectional algorithm]: https://unicode.org/reports/tr9/
+
+use unicode_bidi::{BidiInfo, Level};
+
+use crate::piet::UnitPoint;
+use crate::theme;
+use crate::unicode_segmentation::UnicodeSegmentation;
+use crate::{Env, Rect};
+
+/// Languages whose scripts are written right to left.
+const RTL_LANGUAGES: &[&str] = &[
+    "ar", "dv", "fa", "he", "iw", "ks", "ku", "ps", "sd", "ug", "ur", "yi",
+];
+
+/// Whether a language, given as a BCP47 tag, is written right to left.
+pub(crate) fn is_rtl_language(tag: &str) -> bool {
+    let language = tag.split(|c| c == '-' || c == '_').next().unwrap_or("");
+    RTL_LANGUAGES.contains(&language.to_lowercase().as_str())
+}
+
+/// Whether the layout direction in the environment is right to left.
+pub(crate) fn is_rtl(env: &Env) -> bool {
+    env.try_get(theme::RIGHT_TO_LEFT).unwrap_or(false)
+}
+
+/// Mirror an alignment horizontally, if the layout is right to left.
+pub(crate) fn mirror_align(align: UnitPoint, rtl: bool) -> UnitPoint {
+    if !rtl {
+        return align;
+    }
+    let unit = align.resolve(Rect::new(0., 0., 1., 1.));
+    UnitPoint::new(1.0 - unit.x, unit.y)
+}
+
+/// Whether a line must be laid out with the bidirectional algorithm,
+/// because it contains right-to-left text or is in a right-to-left layout.
+pub(crate) fn needs_bidi(text: &str, rtl: bool) -> bool {
+    rtl || BidiInfo::new(text, None).levels.iter().any(|l| l.is_rtl())
+}
+
+/// A grapheme of a line, with its embedding level.
+struct Unit {
+    start: usize,
+    end: usize,
+    rtl: bool,
+}
+
+/// The graphemes of a line, in visual order from left to right.
+fn visual_units(text: &str, rtl: bool) -> Vec<Unit> {
+    let base = if rtl { Level::rtl() } else { Level::ltr() };
+    let levels = BidiInfo::new(text, Some(base)).levels;
+    let units = text
+        .grapheme_indices(true)
+        .map(|(start, g)| (start, start + g.len(), levels[start].number()))
+        .collect::<Vec<_>>();
+
+    // Rule L2: from the highest level down to the lowest odd level, reverse
+    // every run of graphemes at that level or higher.
+    let mut order = (0..units.len()).collect::<Vec<_>>();
+    let highest = units.iter().map(|u| u.2).max().unwrap_or(0);
+    let lowest_odd = units.iter().map(|u| u.2 | 1).min().unwrap_or(1);
+    for level in (lowest_odd..=highest).rev() {
+        let mut i = 0;
+        while i < order.len() {
+            if units[order[i]].2 < level {
+                i += 1;
+                continue;
+            }
+            let run_start = i;
+            while i < order.len() && units[order[i]].2 >= level {
+                i += 1;
+            }
+            order[run_start..i].reverse();
+        }
+    }
+
+    order
+        .into_iter()
+        .map(|i| {
+            let (start, end, level) = units[i];
+            Unit {
+                start,
+                end,
+                rtl: level % 2 == 1,
+            }
+        })
+        .collect()
+}
+
+/// Reorder a line into the order it is displayed in, for text backends
+/// that draw glyphs strictly left to right.
+///
+/// On platforms whose text backends apply the bidirectional algorithm
+/// themselves, the line is returned unchanged.
+pub(crate) fn visual_text(text: &str, rtl: bool) -> String {
+    if cfg!(any(target_os = "windows", target_arch = "wasm32")) || !needs_bidi(text, rtl) {
+        return text.to_owned();
+    }
+    visual_units(text, rtl)
+        .iter()
+        .map(|unit| &text[unit.start..unit.end])
+        .collect()
+}
+
+/// The caret positions in a line, in visual order from left to right.
+///
+/// Each position is a byte offset in the text, paired with its horizontal
+/// distance from the start of the line, as measured by `measure`. Where
+/// two runs of different direction meet, one offset can be displayed in two
+/// places; only the leftmost is included.
+pub(crate) fn caret_stops(
+    text: &str,
+    rtl: bool,
+    mut measure: impl FnMut(&str) -> f64,
+) -> Vec<(usize, f64)> {
+    fn push(stops: &mut Vec<(usize, f64)>, offset: usize, x: f64) {
+        if stops.iter().all(|(o, _)| *o != offset) {
+            stops.push((offset, x));
+        }
+    }
+
+    let mut stops = Vec::new();
+    let mut x = 0.0;
+    for unit in visual_units(text, rtl) {
+        let (left, right) = if unit.rtl {
+            (unit.end, unit.start)
+        } else {
+            (unit.start, unit.end)
+        };
+        push(&mut stops, left, x);
+        x += measure(&text[unit.start..unit.end]);
+        push(&mut stops, right, x);
+    }
+    if stops.is_empty() {
+        stops.push((0, 0.0));
+    }
+    stops
+}
+
+/// The horizontal position of the caret at `offset`, in a line with the
+/// given caret stops.
+pub(crate) fn caret_x(stops: &[(usize, f64)], offset: usize) -> f64 {
+    stops
+        .iter()
+        .find(|(o, _)| *o == offset)
+        .map(|(_, x)| *x)
+        .unwrap_or(0.0)
+}
+
+/// The offset of the caret stop visually next to `offset`, to the right
+/// if `right` is `true` and otherwise to the left.
+pub(crate) fn visual_neighbor(stops: &[(usize, f64)], offset: usize, right: bool) -> usize {
+    let i = match stops.iter().position(|(o, _)| *o == offset) {
+        Some(i) => i,
+        None => return offset,
+    };
+    let next = if right {
+        (i + 1).min(stops.len() - 1)
+    } else {
+        i.saturating_sub(1)
+    };
+    stops[next].0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn visual_order() {
+        // Hebrew letters are reversed, the digits and Latin letters aren't.
+        let text = "ab \u{5d0}\u{5d1} cd";
+        let stops = caret_stops(text, false, |s| s.chars().count() as f64);
+        let offsets = stops.iter().map(|(o, _)| *o).collect::<Vec<_>>();
+        assert_eq!(offsets, vec![0, 1, 2, 3, 7, 5, 8, 9, 10]);
+        assert_eq!(caret_x(&stops, 7), 3.0);
+        assert_eq!(visual_neighbor(&stops, 3, true), 7);
+        assert_eq!(visual_neighbor(&stops, 5, false), 7);
+        assert!(is_rtl_language("he-IL"));
+        assert!(!is_rtl_language("en-US"));
+    }
+}
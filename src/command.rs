@@ -62,6 +62,34 @@ pub mod sys {
     /// should be the id of the window to close.
     pub const CLOSE_WINDOW: Selector = Selector::new("druid-builtin.close-window");
 
+    /// Bring a window to the front and focus it. The command's argument
+    /// should be the id of the window.
+    pub const SHOW_WINDOW: Selector = Selector::new("druid-builtin.show-window");
+
+    /// Minimize the window the command is sent to.
+    pub const MINIMIZE_WINDOW: Selector = Selector::new("druid-builtin.menu-window-minimize");
+
+    /// Toggle the window the command is sent to between its largest and its
+    /// normal size.
+    pub const ZOOM_WINDOW: Selector = Selector::new("druid-builtin.menu-window-zoom");
+
+    /// Show the next tab in the tab group of the window the command is sent
+    /// to. (mac only)
+    pub const SHOW_NEXT_TAB: Selector = Selector::new("druid-builtin.menu-window-next-tab");
+
+    /// Show the previous tab in the tab group of the window the command is
+    /// sent to. (mac only)
+    pub const SHOW_PREVIOUS_TAB: Selector = Selector::new("druid-builtin.menu-window-previous-tab");
+
+    /// Merge all of the application's windows into tabs of the window the
+    /// command is sent to. (mac only)
+    pub const MERGE_ALL_WINDOWS: Selector = Selector::new("druid-builtin.menu-window-merge-all");
+
+    /// Sent to every widget in a window when the window gains focus.
+    ///
+    /// On macOS this includes switching to the window's tab.
+    pub const WINDOW_GOT_FOCUS: Selector = Selector::new("druid-builtin.window-got-focus");
+
     /// Display a context (right-click) menu. The argument should be a...
     //TODO
     pub const SHOW_CONTEXT_MENU: Selector = Selector::new("druid-builtin.show-context-menu");
@@ -72,12 +100,229 @@ pub mod sys {
     /// [`MenuDesc`]: ../struct.MenuDesc.html
     pub const SET_MENU: Selector = Selector::new("druid-builtin.set-menu");
 
+    /// Change the locale used to resolve [`LocalizedString`]s, in all windows.
+    ///
+    /// The argument should be a `String` containing a BCP47 language tag,
+    /// such as `"fr-FR"`. Window titles, menus and labels are re-resolved,
+    /// and the command is then delivered to the widgets of the window that
+    /// submitted it.
+    ///
+    /// [`LocalizedString`]: ../struct.LocalizedString.html
+    pub const SET_LOCALE: Selector = Selector::new("druid-builtin.set-locale");
+
+    /// Sent to every window when the user changes an accessibility setting,
+    /// such as high contrast, after the env has been updated to match.
+    pub const ACCESSIBILITY_CHANGED: Selector =
+        Selector::new("druid-builtin.accessibility-changed");
+
     /// Show the application preferences.
     pub const SHOW_PREFERENCES: Selector = Selector::new("druid-builtin.menu-show-preferences");
 
     /// Show the application about window.
     pub const SHOW_ABOUT: Selector = Selector::new("druid-builtin.menu-show-about");
 
+    /// Open a URL with the system's default handler, such as a web browser.
+    ///
+    /// The argument should be a `String` containing the URL.
+    pub const OPEN_URL: Selector = Selector::new("druid-builtin.open-url");
+
+    /// Show a file in the platform file manager (Finder, Explorer).
+    ///
+    /// The argument should be a `PathBuf`.
+    pub const SHOW_IN_FILE_MANAGER: Selector = Selector::new("druid-builtin.show-in-file-manager");
+
+    /// Set the menu shown when right-clicking the application's dock icon.
+    /// The argument should be a [`MenuDesc`] object. (mac only)
+    ///
+    /// Commands from this menu are delivered to the application's main window.
+    ///
+    /// [`MenuDesc`]: ../struct.MenuDesc.html
+    pub const SET_DOCK_MENU: Selector = Selector::new("druid-builtin.set-dock-menu");
+
+    /// Set the badge shown on the application's dock icon. The argument
+    /// should be a `String`; an empty string removes the badge. (mac only)
+    pub const SET_BADGE_LABEL: Selector = Selector::new("druid-builtin.set-badge-label");
+
+    /// Bounce the dock icon to get the user's attention. If the argument is
+    /// `true`, the icon keeps bouncing until the application is activated.
+    /// (mac only)
+    pub const REQUEST_ATTENTION: Selector = Selector::new("druid-builtin.request-attention");
+
+    /// Set the progress shown on the window's taskbar button. The argument
+    /// should be a [`TaskbarProgress`]. (windows only)
+    ///
+    /// [`TaskbarProgress`]: ../enum.TaskbarProgress.html
+    pub const SET_TASKBAR_PROGRESS: Selector = Selector::new("druid-builtin.set-taskbar-progress");
+
+    /// Add a file to the recent documents in the application's jump list.
+    /// The argument should be a `PathBuf`. (windows only)
+    pub const ADD_RECENT_DOCUMENT: Selector = Selector::new("druid-builtin.add-recent-document");
+
+    /// Sent to every widget when the application moves to the background,
+    /// where it may be killed by the system without further notice. This is
+    /// a good time to save state. (mobile only)
+    pub const APP_SUSPENDED: Selector = Selector::new("druid-builtin.app-suspended");
+
+    /// Sent to every widget when the application returns to the foreground
+    /// after [`APP_SUSPENDED`]. (mobile only)
+    ///
+    /// [`APP_SUSPENDED`]: constant.APP_SUSPENDED.html
+    pub const APP_RESUMED: Selector = Selector::new("druid-builtin.app-resumed");
+
+    /// Set how often the window paints animation frames. The argument should
+    /// be a [`FrameRate`].
+    ///
+    /// [`FrameRate`]: ../enum.FrameRate.html
+    pub const SET_FRAME_RATE: Selector = Selector::new("druid-builtin.set-frame-rate");
+
+    /// Toggle the debug overlay showing the layout of every widget in the
+    /// window. It can also be turned on for all windows by setting the
+    /// `DRUID_DEBUG_PAINT` environment variable.
+    pub const TOGGLE_DEBUG_PAINT: Selector = Selector::new("druid-builtin.toggle-debug-paint");
+
+    /// Open an inspector showing the live widget tree of the window, with
+    /// each widget's type, bounds and the type of its data. Choosing a
+    /// widget in the inspector highlights it in the window.
+    pub const SHOW_INSPECTOR: Selector = Selector::new("druid-builtin.show-inspector");
+
+    /// Toggle an overlay showing the time the window's last frame spent in
+    /// each pass over the widget tree. If the argument is `true`, the
+    /// widgets that took the longest are also shown. Profiling can also be
+    /// turned on for all windows with the `DRUID_PROFILE` environment
+    /// variable.
+    pub const TOGGLE_PROFILER: Selector = Selector::new("druid-builtin.toggle-profiler");
+
+    /// Ask the window for a description of its widgets. Once the window
+    /// has been painted, it is sent a [`WIDGET_TREE`] command with the
+    /// widgets it painted.
+    ///
+    /// [`WIDGET_TREE`]: constant.WIDGET_TREE.html
+    pub const DESCRIBE_WIDGETS: Selector = Selector::new("druid-builtin.describe-widgets");
+
+    /// The response to [`DESCRIBE_WIDGETS`]. The argument is a
+    /// [`WidgetTree`].
+    ///
+    /// [`DESCRIBE_WIDGETS`]: constant.DESCRIBE_WIDGETS.html
+    /// [`WidgetTree`]: ../../struct.WidgetTree.html
+    pub const WIDGET_TREE: Selector = Selector::new("druid-builtin.widget-tree");
+
+    /// Ask the window for an image of its contents. Once the window has
+    /// been painted, it is sent a [`WINDOW_IMAGE`] command with the image.
+    ///
+    /// The argument, if any, should be an `f64`: the number of pixels to a
+    /// unit of the window's coordinate space. The default is `1.0`. The
+    /// debug overlay and focus ring are left out of the image. This is
+    /// only supported where piet draws with cairo.
+    ///
+    /// [`WINDOW_IMAGE`]: constant.WINDOW_IMAGE.html
+    pub const RENDER_WINDOW: Selector = Selector::new("druid-builtin.render-window");
+
+    /// The response to [`RENDER_WINDOW`]. The argument is an [`RgbaImage`].
+    ///
+    /// [`RENDER_WINDOW`]: constant.RENDER_WINDOW.html
+    /// [`RgbaImage`]: ../../struct.RgbaImage.html
+    pub const WINDOW_IMAGE: Selector = Selector::new("druid-builtin.window-image");
+
+    /// Load a URL in the window's [`WebView`]s. The argument should be a
+    /// `String`.
+    ///
+    /// [`WebView`]: ../widget/struct.WebView.html
+    #[cfg(feature = "webview")]
+    pub const WEBVIEW_LOAD_URL: Selector = Selector::new("druid-builtin.webview-load-url");
+
+    /// Show an HTML document in the window's [`WebView`]s. The argument
+    /// should be a `String`.
+    ///
+    /// [`WebView`]: ../widget/struct.WebView.html
+    #[cfg(feature = "webview")]
+    pub const WEBVIEW_LOAD_HTML: Selector = Selector::new("druid-builtin.webview-load-html");
+
+    /// Go back in the history of the window's web views.
+    #[cfg(feature = "webview")]
+    pub const WEBVIEW_GO_BACK: Selector = Selector::new("druid-builtin.webview-go-back");
+
+    /// Go forward in the history of the window's web views.
+    #[cfg(feature = "webview")]
+    pub const WEBVIEW_GO_FORWARD: Selector = Selector::new("druid-builtin.webview-go-forward");
+
+    /// Reload the pages shown by the window's web views.
+    #[cfg(feature = "webview")]
+    pub const WEBVIEW_RELOAD: Selector = Selector::new("druid-builtin.webview-reload");
+
+    /// Stop loading the pages shown by the window's web views.
+    #[cfg(feature = "webview")]
+    pub const WEBVIEW_STOP: Selector = Selector::new("druid-builtin.webview-stop");
+
+    /// Run a script in the pages shown by the window's web views. The
+    /// argument should be a `String`.
+    #[cfg(feature = "webview")]
+    pub const WEBVIEW_EVAL_SCRIPT: Selector = Selector::new("druid-builtin.webview-eval-script");
+
+    /// Submitted by a [`WebView`] when its page calls
+    /// `window.druid.postMessage()`. The argument is the message, as a
+    /// `String`.
+    ///
+    /// [`WebView`]: ../widget/struct.WebView.html
+    #[cfg(feature = "webview")]
+    pub const WEBVIEW_MESSAGE: Selector = Selector::new("druid-builtin.webview-message");
+
+    /// Start playing the window's [`Video`]s.
+    ///
+    /// [`Video`]: ../widget/struct.Video.html
+    #[cfg(feature = "video")]
+    pub const VIDEO_PLAY: Selector = Selector::new("druid-builtin.video-play");
+
+    /// Pause the window's videos.
+    #[cfg(feature = "video")]
+    pub const VIDEO_PAUSE: Selector = Selector::new("druid-builtin.video-pause");
+
+    /// Move the window's videos to a new position. The argument should be a
+    /// `Duration`, from the start of the video.
+    #[cfg(feature = "video")]
+    pub const VIDEO_SEEK: Selector = Selector::new("druid-builtin.video-seek");
+
+    /// Submitted by a [`Video`] when it reaches the end.
+    ///
+    /// [`Video`]: ../widget/struct.Video.html
+    #[cfg(feature = "video")]
+    pub const VIDEO_ENDED: Selector = Selector::new("druid-builtin.video-ended");
+
+    /// Start playing the window's [`Lottie`] animations.
+    ///
+    /// [`Lottie`]: ../widget/struct.Lottie.html
+    #[cfg(feature = "lottie")]
+    pub const LOTTIE_PLAY: Selector = Selector::new("druid-builtin.lottie-play");
+
+    /// Pause the window's Lottie animations.
+    #[cfg(feature = "lottie")]
+    pub const LOTTIE_PAUSE: Selector = Selector::new("druid-builtin.lottie-pause");
+
+    /// Move the window's Lottie animations to a new position. The argument
+    /// should be a `Duration`, from the start of the animation.
+    #[cfg(feature = "lottie")]
+    pub const LOTTIE_SEEK: Selector = Selector::new("druid-builtin.lottie-seek");
+
+    /// Set whether the window's Lottie animations loop. The argument should
+    /// be a `bool`.
+    #[cfg(feature = "lottie")]
+    pub const LOTTIE_SET_LOOPING: Selector = Selector::new("druid-builtin.lottie-set-looping");
+
+    /// Submitted by a [`Lottie`] when it reaches the end without looping.
+    ///
+    /// [`Lottie`]: ../widget/struct.Lottie.html
+    #[cfg(feature = "lottie")]
+    pub const LOTTIE_ENDED: Selector = Selector::new("druid-builtin.lottie-ended");
+
+    /// Scroll the window's [`Scroll`] containers to an offset. The argument
+    /// should be a `Vec2`.
+    ///
+    /// The offset is limited to the content once it has been laid out, so
+    /// `Vec2::new(0.0, f64::INFINITY)` scrolls to the bottom, even of
+    /// content that has just grown.
+    ///
+    /// [`Scroll`]: ../widget/struct.Scroll.html
+    pub const SCROLL_TO: Selector = Selector::new("druid-builtin.scroll-to");
+
     /// Show all applications.
     pub const SHOW_ALL: Selector = Selector::new("druid-builtin.menu-show-all");
 
@@ -87,9 +332,17 @@ pub mod sys {
     /// System command. A file picker dialog will be shown to the user, and an
     /// Open Event will be sent if a file is chosen.
     ///
-    /// The argument should be a `FileDialogOptions` struct.
+    /// The argument should be a `FileDialogOptions` struct, or a `PathBuf`
+    /// to open that file without showing a dialog.
     pub const OPEN_FILE: Selector = Selector::new("druid-builtin.menu-file-open");
 
+    /// Clear the list of recently opened files. This is sent by the last
+    /// item of the [`recent_files`] menu, and should be handled by the
+    /// application.
+    ///
+    /// [`recent_files`]: ../../menu/sys/fn.recent_files.html
+    pub const CLEAR_RECENT_FILES: Selector = Selector::new("druid-builtin.menu-file-clear-recent");
+
     /// Save the current file.
     pub const SAVE_FILE: Selector = Selector::new("druid-builtin.menu-file-save");
 
@@ -119,6 +372,9 @@ pub mod sys {
 
     /// Redo.
     pub const REDO: Selector = Selector::new("druid-builtin.menu-redo");
+
+    /// Select all of the content of the focused widget.
+    pub const SELECT_ALL: Selector = Selector::new("druid-builtin.menu-select-all");
 }
 
 impl Selector {
@@ -129,6 +385,11 @@ impl Selector {
     pub const fn new(s: &'static str) -> Selector {
         Selector(s)
     }
+
+    /// The string identifying this selector.
+    pub(crate) fn as_str(&self) -> &'static str {
+        self.0
+    }
 }
 
 impl Command {
@@ -141,6 +402,15 @@ impl Command {
         }
     }
 
+    /// Create a command with an argument that was sent from another thread.
+    pub(crate) fn from_boxed(selector: Selector, arg: Box<dyn Any + Send>) -> Self {
+        let object: Arc<dyn Any + Send> = arg.into();
+        Command {
+            selector,
+            object: Some(object),
+        }
+    }
+
     /// Return a reference to this command's object, if it has one.
     pub fn get_object<T: Any>(&self) -> Option<&T> {
         match self.object.as_ref() {
@@ -17,6 +17,8 @@
 use std::rc::Rc;
 use std::sync::Arc;
 
+use crate::kurbo::Vec2;
+
 pub use druid_derive_data::Data;
 
 /// A trait used to represent value types.
@@ -79,6 +81,12 @@ impl Data for f64 {
     }
 }
 
+impl Data for Vec2 {
+    fn same(&self, other: &Self) -> bool {
+        self.x.same(&other.x) && self.y.same(&other.y)
+    }
+}
+
 impl<T> Data for Arc<T> {
     fn same(&self, other: &Self) -> bool {
         Arc::ptr_eq(self, other)
@@ -0,0 +1,221 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An overlay showing the layout of every widget, for debugging.
+
+use crate::inspector::short_name;
+use crate::kurbo::{Affine, Point, Rect};
+use crate::piet::{Color, RenderContext};
+use crate::text::FontSet;
+use crate::{theme, BaseState, Env, PaintCtx, WidgetId, WidgetInfo, WidgetTree};
+
+/// The environment variable that turns the overlay on for every window.
+pub(crate) const DEBUG_PAINT_VAR: &str = "DRUID_DEBUG_PAINT";
+
+const LABEL_SIZE: f64 = 9.0;
+
+/// The colors of widgets, by their depth in the tree.
+const COLORS: [Color; 4] = [
+    Color::rgb8(0xe0, 0x40, 0x40),
+    Color::rgb8(0x40, 0xa0, 0xe0),
+    Color::rgb8(0x50, 0xc0, 0x50),
+    Color::rgb8(0xe0, 0xa0, 0x30),
+];
+
+/// The color of the widget selected in the inspector.
+const HIGHLIGHT_COLOR: Color = Color::rgb8(0x60, 0x90, 0xff);
+
+/// The state of the overlay, and of the inspector, while a window is
+/// painted.
+pub(crate) struct DebugPaint {
+    /// Whether the overlay is drawn.
+    overlay: bool,
+    /// The widgets painted so far, if they are being recorded.
+    widgets: Option<Vec<WidgetInfo>>,
+    /// The widget to highlight.
+    highlight: Option<WidgetId>,
+    font: Option<FontSet>,
+    /// For each widget being painted, the corners of its children painted
+    /// so far, in window coordinates.
+    children: Vec<Vec<(Point, Point)>>,
+}
+
+impl DebugPaint {
+    /// The state for painting a window, or `None` if there is neither an
+    /// overlay to paint nor widgets to record.
+    pub(crate) fn new(
+        overlay: bool,
+        record: bool,
+        highlight: Option<WidgetId>,
+    ) -> Option<DebugPaint> {
+        if !overlay && !record {
+            return None;
+        }
+        Some(DebugPaint {
+            overlay,
+            widgets: if record { Some(Vec::new()) } else { None },
+            highlight,
+            font: None,
+            children: Vec::new(),
+        })
+    }
+
+    /// Whether the overlay is turned on by the environment.
+    pub(crate) fn enabled_by_env() -> bool {
+        std::env::var_os(DEBUG_PAINT_VAR).is_some()
+    }
+
+    /// The widgets painted, if they were recorded.
+    pub(crate) fn finish(self) -> Option<WidgetTree> {
+        self.widgets.map(WidgetTree::new)
+    }
+}
+
+/// Paint a widget, then the overlay over it: its bounds, any space
+/// between its bounds and its children as padding, and its type and id.
+///
+/// If widgets are being recorded, the widget is also added to the tree.
+/// It is tinted if it is selected in the inspector.
+pub(crate) fn paint_with_overlay(
+    paint_ctx: &mut PaintCtx,
+    state: &BaseState,
+    id: WidgetId,
+    type_name: &'static str,
+    data_type: &'static str,
+    env: &Env,
+    paint: impl FnOnce(&mut PaintCtx),
+) {
+    let size = state.size();
+    let transform = paint_ctx.current_transform();
+    let corner = Point::new(size.width, size.height);
+    let depth = match paint_ctx.debug.as_mut() {
+        Some(debug) => {
+            let depth = debug.children.len();
+            if let Some(widgets) = debug.widgets.as_mut() {
+                widgets.push(WidgetInfo {
+                    id,
+                    type_name,
+                    data_type,
+                    depth,
+                    layout_rect: state.layout_rect,
+                    window_rect: Rect::from_points(transform * Point::ORIGIN, transform * corner),
+                    is_hot: state.is_hot,
+                    is_active: state.is_active,
+                    has_focus: state.has_focus,
+                });
+            }
+            debug.children.push(Vec::new());
+            depth
+        }
+        None => {
+            paint(paint_ctx);
+            return;
+        }
+    };
+
+    paint(paint_ctx);
+
+    // Taken out of the context while painting, so both can be borrowed.
+    let mut debug = paint_ctx.debug.take().unwrap();
+    let children = debug.children.pop().unwrap_or_default();
+    if let Some(parent) = debug.children.last_mut() {
+        parent.push((transform * Point::ORIGIN, transform * corner));
+    }
+
+    let bounds = Rect::from_origin_size(Point::ORIGIN, size);
+    if debug.highlight == Some(id) {
+        paint_ctx.fill(bounds, &HIGHLIGHT_COLOR.with_alpha(0.4));
+    }
+    if !debug.overlay {
+        paint_ctx.debug = Some(debug);
+        return;
+    }
+
+    let color = &COLORS[depth % COLORS.len()];
+    if let Some(content) = content_rect(inverse(transform), &children) {
+        let content = content.intersect(bounds);
+        for strip in &padding_strips(bounds, content) {
+            if strip.area() > 0.0 {
+                paint_ctx.fill(*strip, &color.clone().with_alpha(0.2));
+            }
+        }
+    }
+    let outline = Rect::new(0.5, 0.5, size.width - 0.5, size.height - 0.5);
+    paint_ctx.stroke(outline, color, 1.0);
+
+    let font = debug.font.get_or_insert_with(|| {
+        FontSet::new(paint_ctx.text(), env.get(theme::FONT_NAME), LABEL_SIZE, env)
+    });
+    let label = format!("{} #{}", short_name(type_name), id.to_raw());
+    let line = font.layout(paint_ctx.text(), &label);
+    line.draw(paint_ctx, (2.0, LABEL_SIZE + 1.0), color);
+    paint_ctx.debug = Some(debug);
+}
+
+/// The bounding box of a widget's children, in its own coordinates.
+/// The inverse of an affine transform, which this version of kurbo doesn't
+/// provide.
+fn inverse(transform: Affine) -> Affine {
+    let [a, b, c, d, e, f] = transform.as_coeffs();
+    let inv_det = (a * d - b * c).recip();
+    Affine::new([
+        inv_det * d,
+        -inv_det * b,
+        -inv_det * c,
+        inv_det * a,
+        inv_det * (c * f - d * e),
+        inv_det * (b * e - a * f),
+    ])
+}
+
+fn content_rect(to_local: Affine, children: &[(Point, Point)]) -> Option<Rect> {
+    children
+        .iter()
+        .map(|(p0, p1)| Rect::from_points(to_local * *p0, to_local * *p1))
+        .fold(None, |acc: Option<Rect>, rect| {
+            Some(acc.map(|acc| acc.union(rect)).unwrap_or(rect))
+        })
+}
+
+/// The space between a widget's bounds and its content, above, below, to
+/// the left and to the right.
+fn padding_strips(bounds: Rect, content: Rect) -> [Rect; 4] {
+    [
+        Rect::new(bounds.x0, bounds.y0, bounds.x1, content.y0),
+        Rect::new(bounds.x0, content.y1, bounds.x1, bounds.y1),
+        Rect::new(bounds.x0, content.y0, content.x0, content.y1),
+        Rect::new(content.x1, content.y0, bounds.x1, content.y1),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn padding() {
+        let bounds = Rect::new(0.0, 0.0, 100.0, 50.0);
+        let content = Rect::new(10.0, 5.0, 90.0, 45.0);
+        let strips = padding_strips(bounds, content);
+        let area: f64 = strips.iter().map(|r| r.area()).sum();
+        assert_eq!(area, bounds.area() - content.area());
+    }
+
+    #[test]
+    fn inverse_round_trips() {
+        let transform = Affine::translate((10.0, -4.0)) * Affine::scale(2.0);
+        let p = inverse(transform) * (transform * Point::new(3.0, 7.0));
+        assert!((p.x - 3.0).abs() < 1e-9 && (p.y - 7.0).abs() < 1e-9);
+    }
+}
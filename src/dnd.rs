@@ -0,0 +1,89 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Drag and drop, both between widgets and with other applications.
+
+use std::any::Any;
+use std::sync::Arc;
+
+use crate::kurbo::Point;
+use crate::shell::dnd::{DragData, DragEvent as PlatformDragEvent, DropEffect};
+use crate::KeyModifiers;
+
+/// A drag event, as delivered to widgets.
+///
+/// A drag either comes from another application, in which case [`data`]
+/// describes its contents, or was started within this application with
+/// [`EventCtx::start_internal_drag`], in which case it carries an arbitrary
+/// payload, available through [`payload`].
+///
+/// [`data`]: #structfield.data
+/// [`payload`]: #method.payload
+/// [`EventCtx::start_internal_drag`]: struct.EventCtx.html#method.start_internal_drag
+#[derive(Debug, Clone)]
+pub struct DragEvent {
+    /// The location of the drag, in the widget's coordinate space.
+    pub pos: Point,
+    /// Keyboard modifiers at the time of the event.
+    pub mods: KeyModifiers,
+    /// The contents of a drag from another application.
+    ///
+    /// This is empty for drags started within the application.
+    pub data: DragData,
+    payload: Option<Arc<dyn Any>>,
+}
+
+/// Drag state of a window, threaded through the event context.
+#[derive(Default)]
+pub(crate) struct DragState {
+    /// The response to the drag event currently being handled.
+    pub(crate) drop_effect: DropEffect,
+    /// The payload of the in-app drag in progress, if any.
+    pub(crate) internal: Option<Arc<dyn Any>>,
+}
+
+impl DragEvent {
+    pub(crate) fn internal(pos: Point, mods: KeyModifiers, payload: Arc<dyn Any>) -> Self {
+        DragEvent {
+            pos,
+            mods,
+            data: DragData::default(),
+            payload: Some(payload),
+        }
+    }
+
+    /// Returns `true` if this drag was started within the application.
+    pub fn is_internal(&self) -> bool {
+        self.payload.is_some()
+    }
+
+    /// Get the payload of an in-app drag, if it is of type `P`.
+    ///
+    /// Drop targets will generally use this both to decide whether to accept
+    /// a drag, and to get at the dragged item when it is dropped.
+    pub fn payload<P: Any>(&self) -> Option<&P> {
+        self.payload.as_ref().and_then(|p| p.downcast_ref())
+    }
+}
+
+impl From<PlatformDragEvent> for DragEvent {
+    fn from(src: PlatformDragEvent) -> DragEvent {
+        DragEvent {
+            pos: src.pos,
+            mods: src.mods,
+            data: src.data,
+            payload: None,
+        }
+    }
+}
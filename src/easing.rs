@@ -0,0 +1,181 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Easing curves.
+
+/// An easing curve, mapping the fraction of an animation's duration that
+/// has passed to the fraction of the distance covered.
+///
+/// All curves start at 0 and end at 1. The [`Spring`] curve overshoots the
+/// end before settling, and a [`CubicBezier`] may too.
+///
+/// Besides being used by a [`Transition`], curves can be evaluated directly
+/// by widgets doing their own interpolation.
+///
+/// [`Spring`]: #variant.Spring
+/// [`CubicBezier`]: #variant.CubicBezier
+/// [`Transition`]: struct.Transition.html
+#[derive(Debug, Clone, Copy)]
+pub enum Easing {
+    /// Constant speed.
+    Linear,
+    /// Starts slowly, following a cubic curve.
+    EaseIn,
+    /// Ends slowly, following a cubic curve.
+    EaseOut,
+    /// Starts and ends slowly, following a cubic curve.
+    EaseInOut,
+    /// A cubic Bézier curve from (0, 0) to (1, 1), with the given control
+    /// points, as in CSS's `cubic-bezier()`.
+    ///
+    /// The x coordinates should be between 0 and 1.
+    CubicBezier { x1: f64, y1: f64, x2: f64, y2: f64 },
+    /// A damped spring.
+    ///
+    /// `damping` is the damping ratio: below 1 the spring oscillates around
+    /// the end, and the lower it is the more it does. `frequency` is its
+    /// angular frequency, in radians over the length of the animation; it
+    /// should be high enough that the spring has settled by the end.
+    Spring { damping: f64, frequency: f64 },
+    /// A custom curve.
+    Custom(fn(f64) -> f64),
+}
+
+impl Easing {
+    /// A spring that overshoots a little, and settles well within the
+    /// animation.
+    pub const SPRING: Easing = Easing::Spring {
+        damping: 0.5,
+        frequency: 14.0,
+    };
+
+    /// A cubic Bézier curve with the given control points.
+    pub fn cubic_bezier(x1: f64, y1: f64, x2: f64, y2: f64) -> Easing {
+        Easing::CubicBezier { x1, y1, x2, y2 }
+    }
+
+    /// The fraction of the distance covered at time `t`.
+    ///
+    /// `t` is clamped to the range from 0 to 1.
+    pub fn eval(&self, t: f64) -> f64 {
+        let t = t.max(0.0).min(1.0);
+        match *self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut if t < 0.5 => 4.0 * t * t * t,
+            Easing::EaseInOut => 1.0 - (2.0 - 2.0 * t).powi(3) / 2.0,
+            Easing::CubicBezier { x1, y1, x2, y2 } => {
+                let s = solve_bezier(x1, x2, t);
+                bezier(y1, y2, s)
+            }
+            Easing::Spring { damping, frequency } => spring(damping, frequency, t),
+            Easing::Custom(f) => f(t),
+        }
+    }
+}
+
+impl Default for Easing {
+    fn default() -> Self {
+        Easing::EaseInOut
+    }
+}
+
+/// One coordinate of a cubic Bézier from 0 to 1, with control point
+/// coordinates `c1` and `c2`, at parameter `s`.
+fn bezier(c1: f64, c2: f64, s: f64) -> f64 {
+    let r = 1.0 - s;
+    3.0 * r * r * s * c1 + 3.0 * r * s * s * c2 + s * s * s
+}
+
+/// Find the parameter at which the x coordinate of the curve is `x`.
+///
+/// The curve is monotonic in x when the control points are in range, so
+/// this bisects, after trying a few Newton steps which usually converge
+/// much faster.
+fn solve_bezier(x1: f64, x2: f64, x: f64) -> f64 {
+    const EPSILON: f64 = 1e-7;
+    let mut s = x;
+    for _ in 0..8 {
+        let err = bezier(x1, x2, s) - x;
+        if err.abs() < EPSILON {
+            return s;
+        }
+        let r = 1.0 - s;
+        let slope = 3.0 * r * r * x1 + 6.0 * r * s * (x2 - x1) + 3.0 * s * s * (1.0 - x2);
+        if slope.abs() < 1e-6 {
+            break;
+        }
+        s -= err / slope;
+    }
+    let (mut lo, mut hi) = (0.0, 1.0);
+    s = x;
+    while hi - lo > EPSILON {
+        if bezier(x1, x2, s) < x {
+            lo = s;
+        } else {
+            hi = s;
+        }
+        s = (lo + hi) / 2.0;
+    }
+    s
+}
+
+/// The position of a spring released from 0 towards 1.
+fn spring(damping: f64, frequency: f64, t: f64) -> f64 {
+    if t >= 1.0 {
+        return 1.0;
+    }
+    let decay = (-damping * frequency * t).exp();
+    if damping < 1.0 {
+        let damped = frequency * (1.0 - damping * damping).sqrt();
+        let phase = damped * t;
+        1.0 - decay * (phase.cos() + damping * frequency / damped * phase.sin())
+    } else {
+        1.0 - decay * (1.0 + frequency * t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn end_points() {
+        let curves = [
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+            Easing::cubic_bezier(0.25, 0.1, 0.25, 1.0),
+            Easing::SPRING,
+        ];
+        for curve in curves.iter() {
+            assert!(curve.eval(0.0).abs() < 1e-9, "{:?}", curve);
+            assert!((curve.eval(1.0) - 1.0).abs() < 1e-9, "{:?}", curve);
+        }
+        assert!((Easing::EaseInOut.eval(0.5) - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn cubic_bezier() {
+        // With control points on the diagonal, the curve is linear.
+        let linear = Easing::cubic_bezier(1.0 / 3.0, 1.0 / 3.0, 2.0 / 3.0, 2.0 / 3.0);
+        for &t in &[0.1, 0.3, 0.5, 0.9] {
+            assert!((linear.eval(t) - t).abs() < 1e-6);
+        }
+        // CSS's ease-in is much slower than linear at the start.
+        assert!(Easing::cubic_bezier(0.42, 0.0, 1.0, 1.0).eval(0.25) < 0.15);
+    }
+}
@@ -0,0 +1,86 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Running druid inside a window owned by another framework.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::shell::raw_window_handle::RawWindowHandle;
+use crate::shell::window::WindowHandle;
+use crate::shell::{init, runloop, Error as PlatformError};
+use crate::win_handler::AppState;
+use crate::{theme, Data, WindowDesc};
+
+/// A druid window embedded in a window owned by another framework, such as
+/// a plugin host.
+///
+/// Unlike [`AppLauncher`], this doesn't take over the process: the host
+/// keeps running its own event loop, which delivers events to the embedded
+/// window. The window fills its parent, and follows its size.
+///
+/// Embedding is supported in HWNDs on Windows, NSViews on macOS, and X11
+/// windows on Linux. Embedded windows have no menu, and should not use
+/// the [`QUIT_APP`] command, which would quit the host.
+///
+/// [`AppLauncher`]: struct.AppLauncher.html
+/// [`QUIT_APP`]: command/sys/constant.QUIT_APP.html
+pub struct EmbeddedApp<T: Data> {
+    state: Rc<RefCell<AppState<T>>>,
+    handle: WindowHandle,
+}
+
+impl<T: Data + 'static> EmbeddedApp<T> {
+    /// Create the window described by `desc` inside `parent`.
+    ///
+    /// The parent must stay alive for as long as the embedded window; call
+    /// [`close`] before destroying it.
+    ///
+    /// [`close`]: #method.close
+    pub fn new(
+        desc: WindowDesc<T>,
+        data: T,
+        parent: RawWindowHandle,
+    ) -> Result<EmbeddedApp<T>, PlatformError> {
+        init();
+        let state = AppState::new(data, theme::init());
+        let handle = desc.build_native_in(&state, Some(parent))?;
+        handle.show();
+        Ok(EmbeddedApp { state, handle })
+    }
+
+    /// The current application data.
+    pub fn data(&self) -> T {
+        self.state.borrow().data.clone()
+    }
+
+    /// The handle of the embedded window.
+    pub fn window(&self) -> &WindowHandle {
+        &self.handle
+    }
+
+    /// Process pending events without blocking.
+    ///
+    /// This only needs to be called by hosts that don't run the platform's
+    /// own event loop, which is only possible on Linux, where GTK may not
+    /// be running. Elsewhere it does nothing.
+    pub fn pump_events(&self) {
+        runloop::pump_events();
+    }
+
+    /// Remove the embedded window from its parent.
+    pub fn close(self) {
+        self.handle.close();
+    }
+}
@@ -20,11 +20,14 @@ use std::marker::PhantomData;
 use std::ops::Deref;
 use std::sync::Arc;
 
+use unic_langid::LanguageIdentifier;
+
 use crate::kurbo::{Point, Rect, Size};
-use crate::piet::{Color, LinearGradient};
+use crate::piet::{Color, LinearGradient, RadialGradient};
 
-use crate::localization::L10nManager;
-use crate::Data;
+use crate::localization::{L10nManager, L10nResources};
+use crate::theme;
+use crate::{Background, Data};
 
 /// An environment passed down through all widget traversals.
 ///
@@ -43,6 +46,8 @@ pub struct Env(Arc<EnvImpl>);
 struct EnvImpl {
     map: HashMap<String, Value>,
     l10n: Arc<L10nManager>,
+    /// The values replaced by the high-contrast theme, while it is applied.
+    displaced: Option<HashMap<String, Option<Value>>>,
 }
 
 /// A typed key.
@@ -68,7 +73,10 @@ pub enum Value {
     Rect(Rect),
     Color(Color),
     LinearGradient(Arc<LinearGradient>),
+    RadialGradient(Arc<RadialGradient>),
+    Background(Arc<Background>),
     Float(f64),
+    Bool(bool),
     String(String),
 }
 
@@ -161,6 +169,76 @@ impl Env {
     pub fn localization_manager(&self) -> &L10nManager {
         &self.0.l10n
     }
+
+    /// Resolve localized strings for a different locale.
+    ///
+    /// This also updates [`theme::RIGHT_TO_LEFT`] to match the new locale.
+    /// Widgets see the change as a change of environment in their next
+    /// `update`, where [`LocalizedString`]s are re-resolved.
+    ///
+    /// [`theme::RIGHT_TO_LEFT`]: theme/constant.RIGHT_TO_LEFT.html
+    /// [`LocalizedString`]: struct.LocalizedString.html
+    pub(crate) fn set_locale(&mut self, locale: LanguageIdentifier) {
+        let l10n = self.0.l10n.with_locale(locale);
+        self.set_localization(l10n);
+    }
+
+    /// Replace the localization manager, updating [`theme::RIGHT_TO_LEFT`]
+    /// to match its locale.
+    ///
+    /// [`theme::RIGHT_TO_LEFT`]: theme/constant.RIGHT_TO_LEFT.html
+    pub(crate) fn set_localization(&mut self, l10n: L10nManager) {
+        let rtl = l10n.is_right_to_left();
+        let env = Arc::make_mut(&mut self.0);
+        env.l10n = Arc::new(l10n);
+        env.map
+            .insert(theme::RIGHT_TO_LEFT.into(), Value::Bool(rtl));
+    }
+
+    /// Update [`theme::REDUCED_MOTION`].
+    ///
+    /// [`theme::REDUCED_MOTION`]: theme/constant.REDUCED_MOTION.html
+    pub(crate) fn set_reduced_motion(&mut self, reduced: bool) {
+        if self.try_get(theme::REDUCED_MOTION) != Some(reduced) {
+            let env = Arc::make_mut(&mut self.0);
+            env.map
+                .insert(theme::REDUCED_MOTION.into(), Value::Bool(reduced));
+        }
+    }
+
+    /// Apply or remove the [high-contrast theme].
+    ///
+    /// The values it replaces are kept, so that turning it off again
+    /// restores the theme the application configured.
+    ///
+    /// [high-contrast theme]: theme/fn.high_contrast.html
+    pub(crate) fn set_high_contrast(&mut self, enabled: bool) {
+        if self.try_get(theme::HIGH_CONTRAST).unwrap_or(false) == enabled {
+            return;
+        }
+        let env = Arc::make_mut(&mut self.0);
+        if enabled {
+            let palette = theme::high_contrast(Env(Arc::new(EnvImpl {
+                map: HashMap::new(),
+                l10n: env.l10n.clone(),
+                displaced: None,
+            })));
+            let displaced = palette
+                .0
+                .map
+                .iter()
+                .map(|(key, value)| (key.clone(), env.map.insert(key.clone(), value.clone())))
+                .collect();
+            env.displaced = Some(displaced);
+        } else if let Some(displaced) = env.displaced.take() {
+            for (key, old) in displaced {
+                match old {
+                    Some(value) => env.map.insert(key, value),
+                    None => env.map.remove(&key),
+                };
+            }
+        }
+    }
 }
 
 impl Debug for Value {
@@ -172,7 +250,10 @@ impl Debug for Value {
             Value::Color(c) => write!(f, "Color {:?}", c),
             // TODO: make PaintBrush impl debug?
             Value::LinearGradient(g) => write!(f, "LinearGradient {:?}", g),
+            Value::RadialGradient(g) => write!(f, "RadialGradient {:?}", g),
+            Value::Background(b) => write!(f, "Background {:?}", b),
             Value::Float(x) => write!(f, "Float {}", x),
+            Value::Bool(b) => write!(f, "Bool {}", b),
             Value::String(s) => write!(f, "String {:?}", s),
         }
     }
@@ -220,7 +301,10 @@ impl Value {
             (Rect(_), Rect(_)) => true,
             (Color(_), Color(_)) => true,
             (LinearGradient(_), LinearGradient(_)) => true,
+            (RadialGradient(_), RadialGradient(_)) => true,
+            (Background(_), Background(_)) => true,
             (Float(_), Float(_)) => true,
+            (Bool(_), Bool(_)) => true,
             (String(_), String(_)) => true,
             _ => false,
         }
@@ -238,7 +322,10 @@ impl Data for Value {
             (Size(s1), Size(s2)) => s1.width.same(&s2.width) && s1.height.same(&s2.height),
             (Color(c1), Color(c2)) => c1.as_rgba_u32() == c2.as_rgba_u32(),
             (LinearGradient(g1), LinearGradient(g2)) => Arc::ptr_eq(g1, g2),
+            (RadialGradient(g1), RadialGradient(g2)) => Arc::ptr_eq(g1, g2),
+            (Background(b1), Background(b2)) => Arc::ptr_eq(b1, b2),
             (Float(f1), Float(f2)) => f1.same(&f2),
+            (Bool(b1), Bool(b2)) => b1 == b2,
             (String(s1), String(s2)) => s1 == s2,
             _ => false,
         }
@@ -253,7 +340,8 @@ impl Data for Env {
 
 impl Data for EnvImpl {
     fn same(&self, other: &EnvImpl) -> bool {
-        self.map.len() == other.map.len()
+        Arc::ptr_eq(&self.l10n, &other.l10n)
+            && self.map.len() == other.map.len()
             && self
                 .map
                 .iter()
@@ -263,10 +351,11 @@ impl Data for EnvImpl {
 
 impl std::default::Default for Env {
     fn default() -> Self {
-        let l10n = L10nManager::new(vec!["builtin.ftl".into()], "./resources/i18n/");
+        let l10n = L10nManager::from_resources(L10nResources::default());
         let inner = EnvImpl {
             l10n: Arc::new(l10n),
             map: HashMap::new(),
+            displaced: None,
         };
         Env(Arc::new(inner))
     }
@@ -362,9 +451,12 @@ macro_rules! impl_value_type_arc {
 }
 
 impl_value_type_owned!(f64, Float);
+impl_value_type_owned!(bool, Bool);
 impl_value_type_owned!(Color, Color);
 impl_value_type_owned!(Rect, Rect);
 impl_value_type_owned!(Point, Point);
 impl_value_type_owned!(Size, Size);
 impl_value_type_borrowed!(str, String, String);
 impl_value_type_arc!(LinearGradient, LinearGradient);
+impl_value_type_arc!(RadialGradient, RadialGradient);
+impl_value_type_arc!(Background, Background);
@@ -17,10 +17,12 @@
 use crate::kurbo::{Rect, Shape, Size, Vec2};
 
 use druid_shell::clipboard::ClipboardItem;
-use druid_shell::keyboard::{KeyEvent, KeyModifiers};
-use druid_shell::window::{FileInfo, MouseEvent, TimerToken};
+use druid_shell::dnd::DropEffect;
+use druid_shell::ime::ImeEvent;
+use druid_shell::keyboard::KeyEvent;
+use druid_shell::window::{FileInfo, IdleToken, MouseEvent, PenEvent, TimerToken, WheelEvent};
 
-use crate::Command;
+use crate::{Command, DragEvent};
 
 /// An event, propagated downwards during event flow.
 ///
@@ -80,6 +82,13 @@ pub enum Event {
     ///
     /// [`set_cursor`]: struct.EventCtx.html#method.set_cursor
     MouseMoved(MouseEvent),
+    /// Called when a pen touches, moves over, or leaves a tablet or screen.
+    ///
+    /// This carries the pressure, tilt and eraser state of the pen, and is
+    /// delivered just before the corresponding `MouseDown`, `MouseMoved` or
+    /// `MouseUp` event, following the same routing. Widgets that don't care
+    /// about pens can ignore it and handle the mouse events alone.
+    Pen(PenEvent),
     /// Called when a key is pressed.
     ///
     /// Note: the intent is for each physical key press to correspond to
@@ -95,8 +104,56 @@ pub enum Event {
     KeyUp(KeyEvent),
     /// Called when a paste command is received.
     Paste(ClipboardItem),
+    /// Called when the input method updates or commits a composition.
+    ///
+    /// Like key events, this is delivered to the focused widget, and only
+    /// while it has enabled the input method with
+    /// [`EventCtx::set_ime_enabled`]. Text widgets should show the preedit
+    /// text at the cursor until it is committed.
+    ///
+    /// [`EventCtx::set_ime_enabled`]: struct.EventCtx.html#method.set_ime_enabled
+    Ime(ImeEvent),
     /// Called when the mouse wheel or trackpad is scrolled.
     Wheel(WheelEvent),
+    /// Called when a drag enters the widget.
+    ///
+    /// The drag may come from another application, or from a widget that
+    /// called [`EventCtx::start_internal_drag`]; see [`DragEvent`].
+    ///
+    /// A widget that wishes to accept the drop should call
+    /// [`set_drop_effect`] here and in response to subsequent [`DragOver`]
+    /// events. Like mouse events, drag events are only delivered to widgets
+    /// whose layout rect contains the drag location.
+    ///
+    /// [`EventCtx::start_internal_drag`]: struct.EventCtx.html#method.start_internal_drag
+    /// [`DragEvent`]: struct.DragEvent.html
+    /// [`set_drop_effect`]: struct.EventCtx.html#method.set_drop_effect
+    /// [`DragOver`]: #variant.DragOver
+    DragEnter(DragEvent),
+    /// Called when a drag moves within the widget.
+    DragOver(DragEvent),
+    /// Called when a drag leaves the widget, or is cancelled.
+    DragLeave,
+    /// Called when something is dropped on the widget.
+    ///
+    /// Widgets should only act on a drop if they accepted the drag, by way
+    /// of [`set_drop_effect`], in response to the most recent `DragEnter` or
+    /// `DragOver`. A widget that consumes the drop should call
+    /// [`set_handled`], which reports success back to the source.
+    ///
+    /// [`set_handled`]: struct.EventCtx.html#method.set_handled
+    /// [`set_drop_effect`]: struct.EventCtx.html#method.set_drop_effect
+    Drop(DragEvent),
+    /// Called when a drag started with [`EventCtx::start_drag`] or
+    /// [`EventCtx::start_internal_drag`] finishes.
+    ///
+    /// This is delivered only to the widget that started the drag (and its
+    /// ancestors). The effect is the operation the receiver performed; if it
+    /// is `DropEffect::Move`, the widget should remove the dragged data.
+    ///
+    /// [`EventCtx::start_drag`]: struct.EventCtx.html#method.start_drag
+    /// [`EventCtx::start_internal_drag`]: struct.EventCtx.html#method.start_internal_drag
+    DragEnd(DropEffect),
     /// Called when the "hot" status changes.
     ///
     /// See [`is_hot`](struct.BaseState.html#method.is_hot) for
@@ -112,6 +169,13 @@ pub enum Event {
     /// On the first frame when transitioning from idle to animating, `interval`
     /// will be 0. (This logic is presently per-window but might change to
     /// per-widget to make it more consistent). Otherwise it is in nanoseconds.
+    ///
+    /// Frames are paced by the display where possible, and at most as often
+    /// as the window's [`FrameRate`]. The time the frame is expected to be
+    /// shown is available from [`EventCtx::presentation_time`].
+    ///
+    /// [`FrameRate`]: enum.FrameRate.html
+    /// [`EventCtx::presentation_time`]: struct.EventCtx.html#method.presentation_time
     AnimFrame(u64),
     /// Called on a timer event.
     ///
@@ -124,6 +188,14 @@ pub enum Event {
     ///
     /// [`EventCtx::request_timer()`]: struct.EventCtx.html#method.request_timer
     Timer(TimerToken),
+    /// Called when the window is idle, after a request made with
+    /// `IdleHandle::schedule_idle()`.
+    ///
+    /// This is how code running outside of event handling, such as a
+    /// callback from a native view, gets back into the widget tree. Like
+    /// commands, idle events are delivered to all widgets; use the token
+    /// to tell them apart.
+    Idle(IdleToken),
     /// Called with an arbitrary [`Command`], submitted from elsewhere in
     /// the application.
     ///
@@ -137,32 +209,6 @@ pub enum Event {
     Command(Command),
 }
 
-/// A mouse wheel event.
-///
-/// An event generated by a mouse wheel or trackpad device. Perhaps a
-/// better name would have been "ScrollEvent", but we follow the lead
-/// of the W3C in naming, also partly to emphasize that this represents
-/// an event from the device, as opposed to a particular intended action.
-/// For example, in many cases a wheel event might cause a zoom.
-///
-/// See the
-/// [wiki](https://linebender.gitbook.io/linebender-graphics-wiki/mouse-wheel)
-/// for more discussion, including testing on various platforms.
-#[derive(Debug, Clone)]
-pub struct WheelEvent {
-    /// The wheel movement.
-    ///
-    /// The polarity is the amount to be added to the scroll position,
-    /// in other words the opposite of the direction the content should
-    /// move on scrolling. This polarity is consistent with the
-    /// deltaX and deltaY values in a web [WheelEvent].
-    ///
-    /// [WheelEvent]: https://w3c.github.io/uievents/#event-type-wheel
-    pub delta: Vec2,
-    /// The keyboard modifiers at the time of the event.
-    pub mods: KeyModifiers,
-}
-
 impl Event {
     /// Transform the event for the contents of a scrolling container.
     pub fn transform_scroll(&self, offset: Vec2, viewport: Rect) -> Option<Event> {
@@ -196,6 +242,42 @@ impl Event {
                     None
                 }
             }
+            Event::Pen(pen_event) => {
+                if viewport.winding(pen_event.pos) != 0 {
+                    let mut pen_event = pen_event.clone();
+                    pen_event.pos += offset;
+                    Some(Event::Pen(pen_event))
+                } else {
+                    None
+                }
+            }
+            Event::DragEnter(drag_event) => {
+                if viewport.winding(drag_event.pos) != 0 {
+                    let mut drag_event = drag_event.clone();
+                    drag_event.pos += offset;
+                    Some(Event::DragEnter(drag_event))
+                } else {
+                    None
+                }
+            }
+            Event::DragOver(drag_event) => {
+                if viewport.winding(drag_event.pos) != 0 {
+                    let mut drag_event = drag_event.clone();
+                    drag_event.pos += offset;
+                    Some(Event::DragOver(drag_event))
+                } else {
+                    Some(Event::DragLeave)
+                }
+            }
+            Event::Drop(drag_event) => {
+                if viewport.winding(drag_event.pos) != 0 {
+                    let mut drag_event = drag_event.clone();
+                    drag_event.pos += offset;
+                    Some(Event::Drop(drag_event))
+                } else {
+                    None
+                }
+            }
             _ => Some(self.clone()),
         }
     }
@@ -0,0 +1,301 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Submitting commands from other threads, and running futures.
+//!
+//! With the `tokio` feature, an application can also be given a tokio
+//! runtime, with [`AppLauncher::tokio_runtime`]; futures are then spawned
+//! on it rather than on threads of their own.
+//!
+//! [`AppLauncher::tokio_runtime`]: struct.AppLauncher.html#method.tokio_runtime
+
+use std::any::Any;
+#[cfg(feature = "tokio")]
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+use std::thread::{self, Thread};
+
+use log::warn;
+
+use crate::shell::platform::IdleHandle;
+use crate::shell::window::IdleToken;
+use crate::{Command, Selector, WindowId};
+
+/// The idle token used to wake a window when commands are submitted from
+/// another thread.
+pub(crate) const EXT_COMMAND_TOKEN: IdleToken = IdleToken::new(0x6578_7463);
+
+/// A command submitted from another thread, waiting to be delivered.
+pub(crate) struct ExtCommand {
+    pub(crate) selector: Selector,
+    pub(crate) arg: Option<Box<dyn Any + Send>>,
+    pub(crate) target: Option<WindowId>,
+}
+
+/// The commands submitted to a window from other threads.
+pub(crate) type ExtQueue = Arc<Mutex<VecDeque<ExtCommand>>>;
+
+#[cfg(feature = "tokio")]
+thread_local! {
+    /// The runtime that futures are spawned on, if the application has one.
+    static RUNTIME: RefCell<Option<tokio::runtime::Handle>> = RefCell::new(None);
+}
+
+/// Spawn futures from this thread on the given runtime.
+#[cfg(feature = "tokio")]
+pub(crate) fn set_runtime(runtime: tokio::runtime::Handle) {
+    RUNTIME.with(|rt| *rt.borrow_mut() = Some(runtime));
+}
+
+/// A handle for submitting commands from any thread.
+///
+/// A `CommandSink` is obtained from [`EventCtx::command_sink`], and can be
+/// cloned and moved to other threads. Commands submitted through it are
+/// delivered on the main thread, as though they had been submitted by a
+/// widget in the window the sink was obtained from. If that window has been
+/// closed, they are dropped.
+///
+/// Unlike [`Command`]s themselves, the arguments passed through a sink must
+/// be `Send`.
+///
+/// [`EventCtx::command_sink`]: struct.EventCtx.html#method.command_sink
+/// [`Command`]: struct.Command.html
+#[derive(Clone)]
+pub struct CommandSink {
+    queue: ExtQueue,
+    idle: Option<IdleHandle>,
+}
+
+impl CommandSink {
+    pub(crate) fn new(queue: ExtQueue, idle: Option<IdleHandle>) -> CommandSink {
+        CommandSink { queue, idle }
+    }
+
+    /// Returns `true` if both sinks deliver to the same window.
+    pub(crate) fn same_window(&self, other: &CommandSink) -> bool {
+        Arc::ptr_eq(&self.queue, &other.queue)
+    }
+
+    /// Submit a command with an argument.
+    ///
+    /// If `window_id` is `None`, the command goes to the window this sink
+    /// was obtained from.
+    pub fn submit_command(
+        &self,
+        selector: Selector,
+        arg: impl Any + Send,
+        window_id: impl Into<Option<WindowId>>,
+    ) {
+        self.push(selector, Some(Box::new(arg)), window_id.into());
+    }
+
+    /// Submit a command without an argument.
+    pub fn submit_selector(&self, selector: Selector, window_id: impl Into<Option<WindowId>>) {
+        self.push(selector, None, window_id.into());
+    }
+
+    /// Wrap a future so that its output is submitted as a command when it
+    /// completes.
+    ///
+    /// The returned future can be run by any executor. [`EventCtx::spawn`]
+    /// takes care of this for the common case.
+    ///
+    /// [`EventCtx::spawn`]: struct.EventCtx.html#method.spawn
+    pub fn complete<F>(
+        &self,
+        selector: Selector,
+        window_id: impl Into<Option<WindowId>>,
+        future: F,
+    ) -> impl Future<Output = ()>
+    where
+        F: Future,
+        F::Output: Any + Send,
+    {
+        let sink = self.clone();
+        let target = window_id.into();
+        async move {
+            let output = future.await;
+            sink.submit_command(selector, output, target);
+        }
+    }
+
+    /// Submit each item received on a channel as a command, until all of
+    /// the channel's senders are dropped.
+    ///
+    /// The returned future should be spawned on a tokio runtime. Items are
+    /// delivered in order, each as the argument of a separate command.
+    #[cfg(feature = "tokio")]
+    pub fn forward_channel<A: Any + Send>(
+        &self,
+        mut receiver: tokio::sync::mpsc::Receiver<A>,
+        selector: Selector,
+        window_id: impl Into<Option<WindowId>>,
+    ) -> impl Future<Output = ()> {
+        let sink = self.clone();
+        let target = window_id.into();
+        async move {
+            while let Some(item) = receiver.recv().await {
+                sink.submit_command(selector.clone(), item, target);
+            }
+        }
+    }
+
+    fn push(&self, selector: Selector, arg: Option<Box<dyn Any + Send>>, target: Option<WindowId>) {
+        let mut queue = match self.queue.lock() {
+            Ok(queue) => queue,
+            Err(_) => {
+                warn!("command sink is poisoned; dropping {}", selector);
+                return;
+            }
+        };
+        // Only the first command in a batch needs to wake the window.
+        let wake = queue.is_empty();
+        queue.push_back(ExtCommand {
+            selector,
+            arg,
+            target,
+        });
+        drop(queue);
+        if wake {
+            if let Some(idle) = self.idle.as_ref() {
+                idle.schedule_idle(EXT_COMMAND_TOKEN);
+            }
+        }
+    }
+}
+
+impl ExtCommand {
+    pub(crate) fn into_command(self) -> Command {
+        match self.arg {
+            Some(arg) => Command::from_boxed(self.selector, arg),
+            None => self.selector.into(),
+        }
+    }
+}
+
+/// Run a future to completion, then submit its output as a command.
+///
+/// The future runs on the application's tokio runtime if it has one, and
+/// otherwise on a new thread.
+pub(crate) fn spawn<F>(sink: CommandSink, selector: Selector, target: Option<WindowId>, future: F)
+where
+    F: Future + Send + 'static,
+    F::Output: Any + Send,
+{
+    #[cfg(feature = "tokio")]
+    {
+        let runtime = RUNTIME.with(|rt| rt.borrow().clone());
+        if let Some(runtime) = runtime {
+            runtime.spawn(sink.complete(selector, target, future));
+            return;
+        }
+    }
+    let name = format!("druid-task-{}", selector.as_str());
+    let result = thread::Builder::new().name(name).spawn(move || {
+        let output = block_on(future);
+        sink.submit_command(selector, output, target);
+    });
+    if let Err(e) = result {
+        warn!("failed to spawn task thread: {}", e);
+    }
+}
+
+/// Run a future on the current thread, parking it while the future is
+/// pending.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = Box::pin(future);
+    let waker = thread_waker(thread::current());
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match Pin::as_mut(&mut future).poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+/// A waker that unparks a thread.
+fn thread_waker(thread: Thread) -> Waker {
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, wake, wake_by_ref, drop_waker);
+
+    unsafe fn clone(ptr: *const ()) -> RawWaker {
+        let thread = Arc::from_raw(ptr as *const Thread);
+        let cloned = thread.clone();
+        std::mem::forget(thread);
+        RawWaker::new(Arc::into_raw(cloned) as *const (), &VTABLE)
+    }
+    unsafe fn wake(ptr: *const ()) {
+        Arc::from_raw(ptr as *const Thread).unpark();
+    }
+    unsafe fn wake_by_ref(ptr: *const ()) {
+        (*(ptr as *const Thread)).unpark();
+    }
+    unsafe fn drop_waker(ptr: *const ()) {
+        drop(Arc::from_raw(ptr as *const Thread));
+    }
+
+    let raw = RawWaker::new(Arc::into_raw(Arc::new(thread)) as *const (), &VTABLE);
+    unsafe { Waker::from_raw(raw) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A future that is pending until it has been woken from another thread.
+    struct WokenLater(Arc<Mutex<bool>>);
+
+    impl Future for WokenLater {
+        type Output = u32;
+        fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<u32> {
+            let mut started = self.0.lock().unwrap();
+            if *started {
+                return Poll::Ready(42);
+            }
+            *started = true;
+            let waker = cx.waker().clone();
+            thread::spawn(move || waker.wake());
+            Poll::Pending
+        }
+    }
+
+    #[test]
+    fn spawn_submits_output() {
+        const DONE: Selector = Selector::new("test.done");
+        let queue = ExtQueue::default();
+        let sink = CommandSink::new(queue.clone(), None);
+        assert_eq!(block_on(WokenLater(Default::default())), 42);
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        spawn(sink, DONE, None, async move {
+            let value = WokenLater(Default::default()).await;
+            tx.send(()).unwrap();
+            value
+        });
+        rx.recv().unwrap();
+        // The command is queued just after the future completes.
+        let cmd = loop {
+            if let Some(cmd) = queue.lock().unwrap().pop_front() {
+                break cmd.into_command();
+            }
+            thread::yield_now();
+        };
+        assert_eq!(cmd.selector, DONE);
+        assert_eq!(cmd.get_object::<u32>(), Some(&42));
+    }
+}
@@ -0,0 +1,147 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Watching files for changes on disk.
+//!
+//! This is available with the `notify` feature.
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::channel;
+use std::thread;
+use std::time::Duration;
+
+use log::warn;
+use notify::{DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::{CommandSink, Selector, WindowId};
+
+/// How long to wait for a burst of changes to settle before reporting them.
+const SETTLE_DELAY: Duration = Duration::from_millis(100);
+
+/// A change to a watched file or directory.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileEvent {
+    /// A file or directory was created.
+    Created(PathBuf),
+    /// A file's contents or metadata changed.
+    Modified(PathBuf),
+    /// A file or directory was removed.
+    Removed(PathBuf),
+    /// A file or directory was renamed; the first path is the old name.
+    Renamed(PathBuf, PathBuf),
+    /// Some changes may have been missed, and watched paths should be
+    /// checked again.
+    Rescan,
+}
+
+/// Watches files and directories, reporting changes as commands.
+///
+/// Each change is submitted as a command with the watcher's selector and a
+/// [`FileEvent`] argument. Changes that arrive in quick succession, as when
+/// an editor saves a file, are coalesced.
+///
+/// Watching stops when the `FileWatcher` is dropped, so it is usually kept
+/// in the widget or delegate handling its commands.
+///
+/// # Examples
+/// ```no_run
+/// # use druid::{EventCtx, FileWatcher, Selector};
+/// const FILE_CHANGED: Selector = Selector::new("my-app.file-changed");
+///
+/// fn watch(ctx: &mut EventCtx) -> FileWatcher {
+///     let mut watcher = FileWatcher::new(ctx.command_sink(), FILE_CHANGED, None).unwrap();
+///     watcher.watch("notes.txt", false).unwrap();
+///     watcher
+/// }
+/// ```
+///
+/// [`FileEvent`]: enum.FileEvent.html
+pub struct FileWatcher {
+    watcher: RecommendedWatcher,
+}
+
+impl FileWatcher {
+    /// Create a new watcher, delivering its commands through `sink`.
+    ///
+    /// If `window_id` is `None`, commands go to the window the sink was
+    /// obtained from.
+    pub fn new(
+        sink: CommandSink,
+        selector: Selector,
+        window_id: impl Into<Option<WindowId>>,
+    ) -> Result<FileWatcher, notify::Error> {
+        let target = window_id.into();
+        let (tx, rx) = channel();
+        let watcher = notify::watcher(tx, SETTLE_DELAY)?;
+        let name = format!("druid-watcher-{}", selector.as_str());
+        // The thread exits when the watcher, and with it the sender, is dropped.
+        thread::Builder::new().name(name).spawn(move || {
+            for event in rx {
+                if let Some(event) = FileEvent::from_notify(event) {
+                    sink.submit_command(selector.clone(), event, target);
+                }
+            }
+        })?;
+        Ok(FileWatcher { watcher })
+    }
+
+    /// Start watching a path.
+    ///
+    /// If the path is a directory and `recursive` is `true`, its
+    /// subdirectories are watched as well.
+    pub fn watch(&mut self, path: impl AsRef<Path>, recursive: bool) -> Result<(), notify::Error> {
+        let mode = if recursive {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+        self.watcher.watch(path, mode)
+    }
+
+    /// Stop watching a path.
+    pub fn unwatch(&mut self, path: impl AsRef<Path>) -> Result<(), notify::Error> {
+        self.watcher.unwatch(path)
+    }
+}
+
+impl FileEvent {
+    /// The path affected by this change; for renames, the new path.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            FileEvent::Created(path)
+            | FileEvent::Modified(path)
+            | FileEvent::Removed(path)
+            | FileEvent::Renamed(_, path) => Some(path),
+            FileEvent::Rescan => None,
+        }
+    }
+
+    fn from_notify(event: DebouncedEvent) -> Option<FileEvent> {
+        match event {
+            DebouncedEvent::Create(path) => Some(FileEvent::Created(path)),
+            DebouncedEvent::Write(path) | DebouncedEvent::Chmod(path) => {
+                Some(FileEvent::Modified(path))
+            }
+            DebouncedEvent::Remove(path) => Some(FileEvent::Removed(path)),
+            DebouncedEvent::Rename(from, to) => Some(FileEvent::Renamed(from, to)),
+            DebouncedEvent::Rescan => Some(FileEvent::Rescan),
+            // These precede the events above, which are reported instead.
+            DebouncedEvent::NoticeWrite(_) | DebouncedEvent::NoticeRemove(_) => None,
+            DebouncedEvent::Error(e, path) => {
+                warn!("error watching {:?}: {}", path, e);
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,130 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Moving keyboard focus between widgets with the Tab key.
+//!
+//! Focus is moved in two passes over the widget tree. The first collects,
+//! in order, the widgets that [accept focus]; the second asks the one
+//! after the focused widget to take focus, which it does as if it had
+//! called [`request_focus`].
+//!
+//! [accept focus]: ../trait.Widget.html#method.accepts_focus
+//! [`request_focus`]: ../struct.EventCtx.html#method.request_focus
+
+use crate::{KeyCode, KeyEvent, Selector, WidgetId};
+
+/// Sent to the widget tree to collect the focus chain.
+pub(crate) const COLLECT_FOCUS_CHAIN: Selector = Selector::new("druid-builtin.collect-focus-chain");
+
+/// Sent to the widget tree to give focus to the target widget.
+pub(crate) const TAKE_FOCUS: Selector = Selector::new("druid-builtin.take-focus");
+
+/// Keyboard focus state of a window, threaded through the event context.
+#[derive(Debug, Default)]
+pub(crate) struct FocusState {
+    /// Whether focus was last moved with the keyboard, in which case the
+    /// focused widget shows that it has focus.
+    pub(crate) visible: bool,
+    /// While the chain is collected, the widgets that accept focus, and
+    /// whether each has focus.
+    pub(crate) chain: Vec<(WidgetId, bool)>,
+    /// While focus is moved, the widget to take it.
+    pub(crate) target: Option<WidgetId>,
+}
+
+/// The widget that focus moves to from the focused widget in the chain,
+/// wrapping around at either end.
+///
+/// If no widget in the chain has focus, focus moves to the first widget,
+/// or the last one when moving backwards.
+pub(crate) fn next_in_chain(chain: &[(WidgetId, bool)], forward: bool) -> Option<WidgetId> {
+    if chain.is_empty() {
+        return None;
+    }
+    let len = chain.len();
+    // A widget that accepts focus may contain another; the innermost has it.
+    let next = match chain.iter().rposition(|(_, has_focus)| *has_focus) {
+        Some(i) if forward => (i + 1) % len,
+        Some(i) => (i + len - 1) % len,
+        None if forward => 0,
+        None => len - 1,
+    };
+    Some(chain[next].0)
+}
+
+/// Whether a key press activates the focused widget, as a click would.
+///
+/// This is the space bar or either enter key, without modifiers other than
+/// shift; held keys don't repeat the activation.
+pub(crate) fn is_activation_key(key: &KeyEvent) -> bool {
+    let mods = key.mods;
+    if mods.ctrl || mods.alt || mods.meta || key.is_repeat {
+        return false;
+    }
+    match key.key_code {
+        KeyCode::Space | KeyCode::Return | KeyCode::NumpadEnter => true,
+        _ => false,
+    }
+}
+
+#[cfg(all(test, not(any(target_os = "windows", target_arch = "wasm32"))))]
+mod tests {
+    use super::*;
+    use crate::widget::{Button, Column, TextBox};
+    use crate::{Harness, KeyModifiers};
+
+    #[test]
+    fn next() {
+        let chain = [
+            (WidgetId(1), false),
+            (WidgetId(2), true),
+            (WidgetId(3), false),
+        ];
+        assert_eq!(next_in_chain(&chain, true), Some(WidgetId(3)));
+        assert_eq!(next_in_chain(&chain, false), Some(WidgetId(1)));
+        let chain = [(WidgetId(1), false), (WidgetId(3), true)];
+        assert_eq!(next_in_chain(&chain, true), Some(WidgetId(1)));
+        let chain = [(WidgetId(1), false), (WidgetId(3), false)];
+        assert_eq!(next_in_chain(&chain, true), Some(WidgetId(1)));
+        assert_eq!(next_in_chain(&chain, false), Some(WidgetId(3)));
+        assert_eq!(next_in_chain(&[], true), None);
+    }
+
+    #[test]
+    fn tab_moves_focus() {
+        let mut harness = Harness::new(
+            || {
+                let mut col = Column::new();
+                col.add_child(Button::new("go", Button::noop), 0.0);
+                col.add_child(TextBox::new(), 0.0);
+                col
+            },
+            String::new(),
+        );
+        let none = KeyModifiers::default();
+        let tab = KeyEvent::for_test(none, "\t", KeyCode::Tab);
+        let x = KeyEvent::for_test(none, "x", KeyCode::KeyX);
+
+        // The button, then the text box.
+        assert!(harness.key_down(tab));
+        assert!(harness.key_down(tab));
+        harness.key_down(x);
+        assert_eq!(harness.data(), "x");
+
+        // Back to the button, which ignores typing.
+        assert!(harness.key_down(tab));
+        harness.key_down(x);
+        assert_eq!(harness.data(), "x");
+    }
+}
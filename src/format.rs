@@ -0,0 +1,289 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Locale-aware formatting of numbers and dates.
+//!
+//! These are exposed through the [`L10nManager`], so that they follow the
+//! active locale; for instance, in a [`DynLabel`]:
+//!
+//! ```
+//! use druid::widget::DynLabel;
+//!
+//! let label = DynLabel::new(|data: &f64, env| {
+//!     env.localization_manager().format_number(*data, 2)
+//! });
+//! ```
+//!
+//! The conventions of each locale are approximations of those in the
+//! [CLDR], covering the more common languages and regions.
+//!
+//! [`L10nManager`]: localization/struct.L10nManager.html
+//! [`DynLabel`]: widget/struct.DynLabel.html
+//! [CLDR]: http://cldr.unicode.org
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Languages that write `1.234,5`.
+const DOT_GROUPING_LANGUAGES: &[&str] = &[
+    "da", "de", "el", "es", "id", "it", "nl", "pt", "ro", "sl", "sr", "tr", "vi",
+];
+
+/// Languages that write `1 234,5`, with a no-break space.
+const SPACE_GROUPING_LANGUAGES: &[&str] = &[
+    "bg", "cs", "et", "fi", "hu", "lt", "lv", "nb", "nn", "no", "pl", "ru", "sk", "sv", "uk",
+];
+
+/// Languages that only group digits in numbers of five digits or more.
+const MIN_GROUPING_2_LANGUAGES: &[&str] = &["es", "pl"];
+
+/// Languages that write short dates as `31.12.2019`.
+const DOT_DATE_LANGUAGES: &[&str] = &[
+    "cs", "da", "de", "fi", "nb", "nn", "no", "pl", "ru", "sk", "tr", "uk",
+];
+
+/// Languages that write short dates as `2019-12-31`.
+const ISO_DATE_LANGUAGES: &[&str] = &["lt", "sv"];
+
+/// Languages that write short dates as `2019/12/31`.
+const YEAR_FIRST_DATE_LANGUAGES: &[&str] = &["ja", "zh"];
+
+/// A day in the Gregorian calendar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    /// The year, such as `2019`.
+    pub year: i32,
+    /// The month, from 1 to 12.
+    pub month: u32,
+    /// The day of the month, from 1 to 31.
+    pub day: u32,
+}
+
+/// How much detail to include when formatting a [`Date`].
+///
+/// [`Date`]: struct.Date.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateStyle {
+    /// All numeric, such as `12/31/2019`.
+    Short,
+    /// With the month's name, such as `December 31, 2019`.
+    Long,
+}
+
+/// The characters used to write numbers in a locale.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct NumberSymbols {
+    decimal: char,
+    group: char,
+    /// The number of digits before the decimal mark for grouping to start.
+    min_grouping: usize,
+}
+
+/// The language and region subtags of a BCP47 tag, such as `("en", "US")`.
+fn subtags(tag: &str) -> (String, Option<String>) {
+    let mut parts = tag.split(|c| c == '-' || c == '_');
+    let language = parts.next().unwrap_or("").to_lowercase();
+    let region = parts
+        .find(|p| p.len() == 2 || (p.len() == 3 && p.chars().all(|c| c.is_ascii_digit())))
+        .map(str::to_uppercase);
+    (language, region)
+}
+
+impl Date {
+    /// Create a new date.
+    pub fn new(year: i32, month: u32, day: u32) -> Date {
+        Date { year, month, day }
+    }
+
+    /// The date at the given time, in UTC.
+    pub fn from_system_time(time: SystemTime) -> Date {
+        let secs = match time.duration_since(UNIX_EPOCH) {
+            Ok(since) => since.as_secs() as i64,
+            Err(e) => {
+                // A time part way through a second is in the second before.
+                let before = e.duration();
+                -(before.as_secs() as i64) - if before.subsec_nanos() > 0 { 1 } else { 0 }
+            }
+        };
+        Date::from_days_since_epoch(secs.div_euclid(86_400))
+    }
+
+    /// Convert a count of days since 1970-01-01.
+    ///
+    /// This is the `civil_from_days` algorithm described at
+    /// <http://howardhinnant.github.io/date_algorithms.html>.
+    fn from_days_since_epoch(days: i64) -> Date {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+        let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+        Date {
+            year: year as i32,
+            month,
+            day,
+        }
+    }
+
+    /// Format the date as numbers only, following the conventions of `tag`.
+    pub(crate) fn format_short(&self, tag: &str) -> String {
+        let (language, region) = subtags(tag);
+        let language = language.as_str();
+        let Date { year, month, day } = *self;
+        match (language, region.as_ref().map(String::as_str)) {
+            (_, Some("US")) | (_, Some("PH")) => format!("{}/{}/{}", month, day, year),
+            ("en", Some("CA")) | ("fr", Some("CA")) => {
+                format!("{}-{:02}-{:02}", year, month, day)
+            }
+            _ if ISO_DATE_LANGUAGES.contains(&language) => {
+                format!("{}-{:02}-{:02}", year, month, day)
+            }
+            _ if YEAR_FIRST_DATE_LANGUAGES.contains(&language) => {
+                format!("{}/{:02}/{:02}", year, month, day)
+            }
+            ("ko", _) => format!("{}. {}. {}.", year, month, day),
+            _ if DOT_DATE_LANGUAGES.contains(&language) => {
+                format!("{:02}.{:02}.{}", day, month, year)
+            }
+            ("nl", _) => format!("{}-{}-{}", day, month, year),
+            // Without a region, assume US English.
+            ("en", None) => format!("{}/{}/{}", month, day, year),
+            _ => format!("{:02}/{:02}/{}", day, month, year),
+        }
+    }
+}
+
+impl NumberSymbols {
+    /// The symbols for a locale, given as a BCP47 tag.
+    pub(crate) fn for_locale(tag: &str) -> NumberSymbols {
+        let (language, region) = subtags(tag);
+        let language = language.as_str();
+        let (decimal, group) = if region.as_ref().map(String::as_str) == Some("CH") {
+            ('.', '\u{2019}')
+        } else if language == "fr" {
+            (',', '\u{202F}')
+        } else if DOT_GROUPING_LANGUAGES.contains(&language) {
+            (',', '.')
+        } else if SPACE_GROUPING_LANGUAGES.contains(&language) {
+            (',', '\u{A0}')
+        } else {
+            ('.', ',')
+        };
+        let min_grouping = if MIN_GROUPING_2_LANGUAGES.contains(&language) {
+            5
+        } else {
+            4
+        };
+        NumberSymbols {
+            decimal,
+            group,
+            min_grouping,
+        }
+    }
+
+    /// The character between the integer and fractional parts of a number.
+    pub(crate) fn decimal(&self) -> char {
+        self.decimal
+    }
+
+    /// Format a number with exactly `fraction_digits` digits after the
+    /// decimal mark, grouping the digits before it.
+    pub(crate) fn format(&self, number: f64, fraction_digits: usize) -> String {
+        if !number.is_finite() {
+            return number.to_string();
+        }
+        let plain = format!("{:.*}", fraction_digits, number.abs());
+        let (int_part, frac_part) = match plain.find('.') {
+            Some(idx) => (&plain[..idx], Some(&plain[idx + 1..])),
+            None => (plain.as_str(), None),
+        };
+        let mut result = String::with_capacity(plain.len() + int_part.len() / 3 + 1);
+        // Don't write "-0" for small negative numbers that round to zero.
+        if number < 0.0 && plain.chars().any(|c| c.is_ascii_digit() && c != '0') {
+            result.push('-');
+        }
+        let grouped = int_part.len() >= self.min_grouping;
+        for (i, digit) in int_part.chars().enumerate() {
+            if grouped && i > 0 && (int_part.len() - i) % 3 == 0 {
+                result.push(self.group);
+            }
+            result.push(digit);
+        }
+        if let Some(frac_part) = frac_part {
+            result.push(self.decimal);
+            result.push_str(frac_part);
+        }
+        result
+    }
+
+    /// Parse a number written with these symbols.
+    ///
+    /// Group separators are ignored wherever they are, as are spaces, so
+    /// that a user's input doesn't need to be grouped correctly.
+    pub(crate) fn parse(&self, text: &str) -> Option<f64> {
+        let normalized: String = text
+            .trim()
+            .chars()
+            .filter(|&c| c != self.group && !c.is_whitespace())
+            .map(|c| if c == self.decimal { '.' } else { c })
+            .collect();
+        normalized.parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn numbers() {
+        let en = NumberSymbols::for_locale("en-US");
+        let de = NumberSymbols::for_locale("de-DE");
+        let fr = NumberSymbols::for_locale("fr-CA");
+        let es = NumberSymbols::for_locale("es-ES");
+        assert_eq!(en.format(1234567.891, 2), "1,234,567.89");
+        assert_eq!(de.format(1234567.891, 2), "1.234.567,89");
+        assert_eq!(fr.format(-1234.5, 1), "-1\u{202F}234,5");
+        assert_eq!(es.format(1234.0, 0), "1234");
+        assert_eq!(es.format(12345.0, 0), "12.345");
+        assert_eq!(en.format(-0.001, 2), "0.00");
+        assert_eq!(de.parse("1.234,5"), Some(1234.5));
+        assert_eq!(fr.parse("1 234,5"), Some(1234.5));
+        assert_eq!(en.parse("abc"), None);
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(Date::from_days_since_epoch(0), Date::new(1970, 1, 1));
+        assert_eq!(Date::from_days_since_epoch(18_261), Date::new(2019, 12, 31));
+        assert_eq!(Date::from_days_since_epoch(-1), Date::new(1969, 12, 31));
+        assert_eq!(Date::from_days_since_epoch(11_016), Date::new(2000, 2, 29));
+        let day_before = UNIX_EPOCH - Duration::from_secs(86_400);
+        assert_eq!(Date::from_system_time(day_before), Date::new(1969, 12, 31));
+        let just_before = UNIX_EPOCH - Duration::from_nanos(1);
+        assert_eq!(Date::from_system_time(just_before), Date::new(1969, 12, 31));
+
+        let date = Date::new(2019, 3, 7);
+        assert_eq!(date.format_short("en-US"), "3/7/2019");
+        assert_eq!(date.format_short("en-GB"), "07/03/2019");
+        assert_eq!(date.format_short("de-DE"), "07.03.2019");
+        assert_eq!(date.format_short("fr-CA"), "2019-03-07");
+        assert_eq!(date.format_short("ja"), "2019/03/07");
+    }
+}
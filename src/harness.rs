@@ -0,0 +1,394 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A harness for testing widgets.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::kurbo::{Point, Rect, Size, Vec2};
+use crate::snapshot;
+use crate::{
+    BaseState, BoxConstraints, Command, Data, Env, Event, EventCtx, HeadlessApp, InputEvent,
+    KeyEvent, KeyModifiers, LayoutCtx, MouseButton, MouseEvent, PaintCtx, Recording, RgbaImage,
+    ScrollDevice, ScrollPhase, UpdateCtx, WheelEvent, Widget, WidgetPod, WindowDesc,
+};
+
+/// The width and height of the window a harness mounts its widget in, by
+/// default.
+const DEFAULT_SIZE: f64 = 400.0;
+
+/// The interval between animation frames, in nanoseconds.
+const FRAME_INTERVAL_NS: u64 = 1_000_000_000 / 60;
+
+thread_local! {
+    /// The layout rects recorded by probes, in window coordinates.
+    static PROBES: RefCell<HashMap<&'static str, Rect>> = RefCell::new(HashMap::new());
+}
+
+/// A widget under test, mounted in a headless window.
+///
+/// Events are injected with methods such as [`click`] and [`key_down`].
+/// After each one, the window is laid out and painted if it has been
+/// invalidated, just as a real window would be, so that the results can
+/// be checked straight away: the application data with [`data`], the
+/// commands widgets have submitted with [`take_commands`], and the layout
+/// of any widget wrapped in a [`Probe`] with [`layout_rect`].
+///
+/// Time only moves when the test says so. Timers fire from
+/// [`advance_time`], and animation frames, delivered by [`animate`], are
+/// a fixed interval apart.
+///
+/// # Examples
+/// ```no_run
+/// use druid::kurbo::Point;
+/// use druid::widget::Checkbox;
+/// use druid::Harness;
+///
+/// let mut harness = Harness::new(|| Checkbox::new(), false);
+/// harness.click(Point::new(5.0, 200.0));
+/// assert!(harness.data());
+/// ```
+///
+/// [`click`]: #method.click
+/// [`key_down`]: #method.key_down
+/// [`data`]: #method.data
+/// [`take_commands`]: #method.take_commands
+/// [`Probe`]: struct.Probe.html
+/// [`layout_rect`]: #method.layout_rect
+/// [`advance_time`]: #method.advance_time
+/// [`animate`]: #method.animate
+pub struct Harness<T: Data> {
+    app: HeadlessApp<T>,
+}
+
+/// A widget that records its layout rect, so that tests can find it with
+/// [`Harness::layout_rect`].
+///
+/// [`Harness::layout_rect`]: struct.Harness.html#method.layout_rect
+pub struct Probe<T: Data> {
+    name: &'static str,
+    child: WidgetPod<T, Box<dyn Widget<T>>>,
+}
+
+impl<T: Data + 'static> Harness<T> {
+    /// Mount the widget returned by `root` in a 400x400 window.
+    pub fn new<W: Widget<T> + 'static>(root: impl Fn() -> W + 'static, data: T) -> Self {
+        Harness::with_size(root, data, Size::new(DEFAULT_SIZE, DEFAULT_SIZE))
+    }
+
+    /// Mount the widget returned by `root` in a window of the given size.
+    pub fn with_size<W: Widget<T> + 'static>(
+        root: impl Fn() -> W + 'static,
+        data: T,
+        size: Size,
+    ) -> Self {
+        PROBES.with(|probes| probes.borrow_mut().clear());
+        let mut app = HeadlessApp::new(WindowDesc::new(root), data, size);
+        {
+            let mut state = app.state().borrow_mut();
+            state.command_log = Some(Vec::new());
+            state.set_frame_interval(Some(FRAME_INTERVAL_NS));
+        }
+        app.paint().expect("failed to paint the harness window");
+        Harness { app }
+    }
+
+    /// The current application data.
+    pub fn data(&self) -> T {
+        self.app.data()
+    }
+
+    /// The underlying headless application.
+    pub fn app(&mut self) -> &mut HeadlessApp<T> {
+        &mut self.app
+    }
+
+    /// Move the mouse to a point, in window coordinates.
+    pub fn mouse_move(&mut self, pos: Point) {
+        self.app.window().mouse_move(&mouse_event(pos, 0));
+        self.settle();
+    }
+
+    /// Press the left mouse button at a point.
+    pub fn mouse_down(&mut self, pos: Point) {
+        self.app.window().mouse_down(&mouse_event(pos, 1));
+        self.settle();
+    }
+
+    /// Release the left mouse button at a point.
+    pub fn mouse_up(&mut self, pos: Point) {
+        self.app.window().mouse_up(&mouse_event(pos, 1));
+        self.settle();
+    }
+
+    /// Move to a point and click the left mouse button.
+    pub fn click(&mut self, pos: Point) {
+        self.mouse_move(pos);
+        self.mouse_down(pos);
+        self.mouse_up(pos);
+    }
+
+    /// Turn a mouse wheel by `delta`, with the mouse where it was last moved.
+    pub fn wheel(&mut self, delta: Vec2, mods: KeyModifiers) {
+        let event = WheelEvent {
+            delta,
+            mods,
+            phase: ScrollPhase::None,
+            device: ScrollDevice::Wheel,
+        };
+        self.app.window().wheel(&event);
+        self.settle();
+    }
+
+    /// Send a key down event, returning `true` if it was handled.
+    pub fn key_down(&mut self, event: KeyEvent) -> bool {
+        let handled = self.app.window().key_down(event);
+        self.settle();
+        handled
+    }
+
+    /// Send a key up event.
+    pub fn key_up(&mut self, event: KeyEvent) {
+        self.app.window().key_up(event);
+        self.settle();
+    }
+
+    /// Send a command to the window, as if it had been submitted by a widget.
+    pub fn submit_command(&mut self, command: impl Into<Command>) {
+        self.app.submit_command(command);
+        self.settle();
+    }
+
+    /// Resize the window, as the user would.
+    pub fn resize(&mut self, size: Size) {
+        self.app.resize(size);
+        self.settle();
+    }
+
+    /// Move time forward, firing any timers that come due.
+    pub fn advance_time(&mut self, duration: Duration) {
+        self.app.advance_time(duration);
+        self.settle();
+    }
+
+    /// Deliver the events a recording made for its first window, advancing
+    /// time between them as it passed when they were recorded.
+    ///
+    /// Recorded sizes resize the harness window.
+    pub fn replay(&mut self, recording: &Recording) {
+        let mut now = Duration::default();
+        for recorded in recording.events().iter().filter(|e| e.window == 0) {
+            if recorded.time > now {
+                self.advance_time(recorded.time - now);
+                now = recorded.time;
+            }
+            match &recorded.event {
+                InputEvent::Size(width, height) => self.app.window().resize(*width, *height),
+                InputEvent::MouseDown(mouse) => self.app.window().mouse_down(mouse),
+                InputEvent::MouseUp(mouse) => self.app.window().mouse_up(mouse),
+                InputEvent::MouseMove(mouse) => self.app.window().mouse_move(mouse),
+                InputEvent::Wheel(wheel) => self.app.window().wheel(wheel),
+                InputEvent::KeyDown(key) => {
+                    self.app.window().key_down(*key);
+                }
+                InputEvent::KeyUp(key) => self.app.window().key_up(*key),
+                InputEvent::Command(id) => self.app.window().command(*id),
+            }
+            self.settle();
+        }
+    }
+
+    /// Paint up to `frames` animation frames, stopping early if the
+    /// animation ends. Frames are 1/60 s apart.
+    pub fn animate(&mut self, frames: usize) {
+        for _ in 0..frames {
+            if !self.app.window().is_invalidated() {
+                break;
+            }
+            self.app
+                .paint()
+                .expect("failed to paint the harness window");
+        }
+    }
+
+    /// The commands processed since the last call, in order.
+    ///
+    /// This includes commands sent with [`submit_command`], as well as those
+    /// submitted by widgets.
+    ///
+    /// [`submit_command`]: #method.submit_command
+    pub fn take_commands(&mut self) -> Vec<Command> {
+        let mut state = self.app.state().borrow_mut();
+        state
+            .command_log
+            .as_mut()
+            .map(|log| log.drain(..).collect())
+            .unwrap_or_default()
+    }
+
+    /// The layout rect, in window coordinates, of the [`Probe`] with the
+    /// given name, as of the last paint.
+    ///
+    /// [`Probe`]: struct.Probe.html
+    pub fn layout_rect(&self, probe: &str) -> Option<Rect> {
+        PROBES.with(|probes| probes.borrow().get(probe).cloned())
+    }
+
+    /// Lay out and paint the window into an image.
+    pub fn render(&mut self) -> RgbaImage {
+        self.app
+            .paint()
+            .expect("failed to paint the harness window")
+    }
+
+    /// Compare the window's appearance against the golden file
+    /// `tests/snapshots/<name>.pam`, with the default tolerance.
+    ///
+    /// See [`check_snapshot`] for how golden files are created and updated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the images don't match.
+    ///
+    /// [`check_snapshot`]: snapshot/fn.check_snapshot.html
+    pub fn assert_snapshot(&mut self, name: &str) {
+        self.assert_snapshot_with_tolerance(name, snapshot::DEFAULT_TOLERANCE);
+    }
+
+    /// Compare the window's appearance against a golden file, allowing
+    /// each color channel to differ by up to `tolerance`.
+    pub fn assert_snapshot_with_tolerance(&mut self, name: &str, tolerance: u8) {
+        let image = self.render();
+        let path = snapshot::default_dir().join(name).with_extension("pam");
+        if let Err(e) = snapshot::check_snapshot(&image, &path, tolerance) {
+            panic!("snapshot '{}' doesn't match: {}", name, e);
+        }
+    }
+
+    /// Lay out and paint the window, if an event invalidated it.
+    fn settle(&mut self) {
+        if self.app.window().is_invalidated() {
+            self.app
+                .paint()
+                .expect("failed to paint the harness window");
+        }
+    }
+}
+
+fn mouse_event(pos: Point, count: u32) -> MouseEvent {
+    MouseEvent {
+        pos,
+        mods: Default::default(),
+        count,
+        button: MouseButton::Left,
+    }
+}
+
+impl<T: Data> Probe<T> {
+    /// Wrap a widget, recording its layout under `name`.
+    pub fn new(name: &'static str, child: impl Widget<T> + 'static) -> Self {
+        Probe {
+            name,
+            child: WidgetPod::new(child).boxed(),
+        }
+    }
+}
+
+impl<T: Data> Widget<T> for Probe<T> {
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env) {
+        let origin = paint_ctx.current_transform() * Point::ORIGIN;
+        let rect = Rect::from_origin_size(origin, base_state.size());
+        PROBES.with(|probes| probes.borrow_mut().insert(self.name, rect));
+        self.child.paint(paint_ctx, data, env);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+        let size = self.child.layout(ctx, bc, data, env);
+        self.child
+            .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+        size
+    }
+
+    fn event(&mut self, event: &Event, ctx: &mut EventCtx, data: &mut T, env: &Env) {
+        self.child.event(event, ctx, data, env);
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: Option<&T>, data: &T, env: &Env) {
+        self.child.update(ctx, data, env);
+    }
+}
+
+/// Lenses to the halves of a pair, for tests whose data is a tuple.
+#[cfg(test)]
+pub(crate) mod pair {
+    use crate::Lens;
+
+    /// A lens to the first element of a pair.
+    pub(crate) struct First;
+
+    /// A lens to the second element of a pair.
+    pub(crate) struct Second;
+
+    impl<A, B> Lens<(A, B), A> for First {
+        fn get<'a>(&self, data: &'a (A, B)) -> &'a A {
+            &data.0
+        }
+
+        fn with_mut<V, F: FnOnce(&mut A) -> V>(&self, data: &mut (A, B), f: F) -> V {
+            f(&mut data.0)
+        }
+    }
+
+    impl<A, B> Lens<(A, B), B> for Second {
+        fn get<'a>(&self, data: &'a (A, B)) -> &'a B {
+            &data.1
+        }
+
+        fn with_mut<V, F: FnOnce(&mut B) -> V>(&self, data: &mut (A, B), f: F) -> V {
+            f(&mut data.1)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widget::{Button, Padding};
+    use crate::Selector;
+
+    #[test]
+    fn probe_and_commands() {
+        const CLICKED: Selector = Selector::new("test.clicked");
+        let mut harness = Harness::new(
+            || {
+                let button = Button::new("go", |ctx, data: &mut u32, _env| {
+                    *data += 1;
+                    ctx.submit_command(CLICKED.into(), None);
+                });
+                Padding::uniform(10.0, Probe::new("button", button))
+            },
+            0u32,
+        );
+        let rect = harness.layout_rect("button").unwrap();
+        assert_eq!(rect.origin(), Point::new(10.0, 10.0));
+
+        harness.click(rect.center());
+        assert_eq!(harness.data(), 1);
+        let commands = harness.take_commands();
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].selector, CLICKED);
+    }
+}
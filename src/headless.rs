@@ -0,0 +1,154 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Running an application without a display.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::kurbo::{Point, Size};
+use crate::shell::headless::{HeadlessWindow, RgbaImage};
+use crate::shell::Error as PlatformError;
+use crate::win_handler::AppState;
+use crate::{theme, Command, Data, DruidHandler, MouseButton, MouseEvent, Widget, WindowDesc};
+
+/// An application with a single window that renders offscreen.
+///
+/// This runs the same event, update, layout and paint passes as a real
+/// window, but events are injected by calling methods, and nothing is ever
+/// shown on screen. It is intended for exercising widgets in tests, and for
+/// rendering on machines without a display server.
+///
+/// Windows opened by commands such as [`NEW_WINDOW`] are not headless.
+///
+/// [`NEW_WINDOW`]: command/sys/constant.NEW_WINDOW.html
+pub struct HeadlessApp<T: Data> {
+    state: Rc<RefCell<AppState<T>>>,
+    window: HeadlessWindow,
+}
+
+impl<T: Data + 'static> HeadlessApp<T> {
+    /// Create the window described by `desc`, with the given size in px.
+    pub fn new(desc: WindowDesc<T>, data: T, size: Size) -> HeadlessApp<T> {
+        let state = AppState::new(data, theme::init());
+        let handler = desc.build_headless(&state);
+        let window = HeadlessWindow::new(
+            Box::new(handler),
+            size.width.round() as u32,
+            size.height.round() as u32,
+        );
+        HeadlessApp { state, window }
+    }
+
+    /// Render a widget, given its data, into an image of `size` px.
+    ///
+    /// This lays out and paints the widget once in a new headless app, for
+    /// uses such as thumbnails and previews. To render a window that is
+    /// already open, submit a [`RENDER_WINDOW`] command to it instead.
+    ///
+    /// [`RENDER_WINDOW`]: command/sys/constant.RENDER_WINDOW.html
+    pub fn render_widget(
+        widget: impl Widget<T> + 'static,
+        data: T,
+        size: Size,
+    ) -> Result<RgbaImage, PlatformError> {
+        let widget = RefCell::new(Some(widget));
+        let desc = WindowDesc::new(move || {
+            widget
+                .borrow_mut()
+                .take()
+                .expect("the root widget is only built once")
+        });
+        HeadlessApp::new(desc, data, size).paint()
+    }
+
+    /// The shared application state.
+    pub(crate) fn state(&self) -> &Rc<RefCell<AppState<T>>> {
+        &self.state
+    }
+
+    /// The current application data.
+    pub fn data(&self) -> T {
+        self.state.borrow().data.clone()
+    }
+
+    /// The underlying shell window, for injecting arbitrary events.
+    pub fn window(&mut self) -> &mut HeadlessWindow {
+        &mut self.window
+    }
+
+    /// Change the size of the window.
+    pub fn resize(&mut self, size: Size) {
+        self.window
+            .resize(size.width.round() as u32, size.height.round() as u32);
+    }
+
+    /// Click the left mouse button at a point, in window coordinates.
+    pub fn click(&mut self, pos: Point) {
+        let event = MouseEvent {
+            pos,
+            mods: Default::default(),
+            count: 1,
+            button: MouseButton::Left,
+        };
+        self.window.mouse_move(&MouseEvent { count: 0, ..event });
+        self.window.mouse_down(&event);
+        self.window.mouse_up(&event);
+    }
+
+    /// Send a command to the window, as if it had been submitted by a widget.
+    pub fn submit_command(&mut self, command: impl Into<Command>) {
+        let command = command.into();
+        self.window.with_handler(|handler, ctx| {
+            handler
+                .as_any()
+                .downcast_mut::<DruidHandler<T>>()
+                .expect("headless window has a druid handler")
+                .submit_command(command, ctx)
+        });
+    }
+
+    /// Move time forward, firing any timers that come due.
+    pub fn advance_time(&mut self, duration: Duration) {
+        self.window.advance_time(duration);
+    }
+
+    /// Lay out and paint the window into an image.
+    pub fn paint(&mut self) -> Result<RgbaImage, PlatformError> {
+        self.window.paint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widget::Checkbox;
+
+    #[test]
+    fn click_checkbox() {
+        let desc = WindowDesc::new(|| Checkbox::new());
+        let mut app = HeadlessApp::new(desc, false, Size::new(100.0, 100.0));
+        app.paint().unwrap();
+        app.click(Point::new(50.0, 50.0));
+        assert_eq!(app.data(), true);
+    }
+
+    #[test]
+    fn render_widget() {
+        let size = Size::new(40.0, 30.0);
+        let image = HeadlessApp::render_widget(Checkbox::new(), true, size).unwrap();
+        assert_eq!((image.width, image.height), (40, 30));
+    }
+}
@@ -0,0 +1,279 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Work done on the UI thread while the event loop is quiet.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::shell::window::{IdleToken, WindowHandle};
+use crate::{Command, WindowId};
+
+/// The idle token used to run a window's idle tasks.
+pub(crate) const IDLE_TASK_TOKEN: IdleToken = IdleToken::new(0x6964_6c65);
+
+/// How long idle tasks may run at once, while the window isn't animating.
+const IDLE_BUDGET: Duration = Duration::from_millis(8);
+
+/// The expected time between animation frames.
+const FRAME_INTERVAL: Duration = Duration::from_micros(16_667);
+
+/// The time left free before an animation frame is due.
+const FRAME_MARGIN: Duration = Duration::from_millis(4);
+
+/// How long idle tasks may run at once, however soon a frame is due, so
+/// that they always make progress.
+const MIN_BUDGET: Duration = Duration::from_millis(1);
+
+/// How urgently an idle task should run, relative to the others.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IdlePriority {
+    Low,
+    Normal,
+    High,
+}
+
+/// Whether an idle task has more work to do.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IdleStatus {
+    /// The task should be run again.
+    Pending,
+    /// The task is finished.
+    Done,
+}
+
+/// Incremental work to be done on the UI thread when it is otherwise idle,
+/// such as building a search index or making thumbnails.
+///
+/// A task is added with [`EventCtx::add_idle_task`]. It is called
+/// repeatedly, with an [`IdleCtx`] that says when to stop, until it returns
+/// [`IdleStatus::Done`]. Each call should do a small piece of work, checking
+/// [`IdleCtx::should_yield`] as it goes, and return [`IdleStatus::Pending`]
+/// when asked to yield, so that no frame is dropped while it runs.
+///
+/// Tasks run from the highest [`priority`] down, and in the order they
+/// were added. A task whose [`deadline`] has passed runs before all others.
+/// Results are delivered by submitting commands.
+///
+/// [`EventCtx::add_idle_task`]: struct.EventCtx.html#method.add_idle_task
+/// [`IdleCtx`]: struct.IdleCtx.html
+/// [`IdleCtx::should_yield`]: struct.IdleCtx.html#method.should_yield
+/// [`IdleStatus::Done`]: enum.IdleStatus.html#variant.Done
+/// [`IdleStatus::Pending`]: enum.IdleStatus.html#variant.Pending
+/// [`priority`]: #method.priority
+/// [`deadline`]: #method.deadline
+pub struct IdleTask {
+    priority: IdlePriority,
+    deadline: Option<Instant>,
+    work: Box<dyn FnMut(&mut IdleCtx) -> IdleStatus>,
+}
+
+/// The context passed to a running idle task.
+pub struct IdleCtx<'a> {
+    deadline: Instant,
+    command_queue: &'a mut VecDeque<(WindowId, Command)>,
+    window_id: WindowId,
+}
+
+/// The idle tasks of a window.
+#[derive(Default)]
+pub(crate) struct IdleQueue {
+    /// Each task, and the order it was added in.
+    tasks: Vec<(u64, IdleTask)>,
+    next_id: u64,
+    /// Whether the idle token has been scheduled and not yet delivered.
+    scheduled: bool,
+}
+
+impl IdleTask {
+    /// Create a task with normal priority and no deadline.
+    pub fn new(work: impl FnMut(&mut IdleCtx) -> IdleStatus + 'static) -> Self {
+        IdleTask {
+            priority: IdlePriority::Normal,
+            deadline: None,
+            work: Box::new(work),
+        }
+    }
+
+    /// Set the priority of the task.
+    pub fn priority(mut self, priority: IdlePriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Set a time after which the task runs ahead of all others.
+    ///
+    /// Tasks still only run when the event loop is idle; this orders them,
+    /// rather than interrupting other work.
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+}
+
+impl<'a> IdleCtx<'a> {
+    /// Returns `true` if the task should return, to let the event loop run.
+    pub fn should_yield(&self) -> bool {
+        Instant::now() >= self.deadline
+    }
+
+    /// The time left before the task should yield.
+    pub fn time_remaining(&self) -> Duration {
+        let now = Instant::now();
+        if now < self.deadline {
+            self.deadline - now
+        } else {
+            Duration::from_secs(0)
+        }
+    }
+
+    /// Submit a [`Command`], to be run once the idle tasks yield.
+    ///
+    /// If `window_id` is `None`, the command is sent to the window the task
+    /// was added in.
+    ///
+    /// [`Command`]: struct.Command.html
+    pub fn submit_command(&mut self, command: Command, window_id: impl Into<Option<WindowId>>) {
+        let window_id = window_id.into().unwrap_or(self.window_id);
+        self.command_queue.push_back((window_id, command))
+    }
+
+    /// Get the window id.
+    pub fn window_id(&self) -> WindowId {
+        self.window_id
+    }
+}
+
+impl IdleQueue {
+    pub(crate) fn push(&mut self, task: IdleTask) {
+        self.tasks.push((self.next_id, task));
+        self.next_id += 1;
+    }
+
+    /// Ask for the window's idle callback, if there are tasks to run.
+    pub(crate) fn schedule(&mut self, handle: &WindowHandle) {
+        if self.scheduled || self.tasks.is_empty() {
+            return;
+        }
+        if let Some(idle) = handle.get_idle_handle() {
+            idle.schedule_idle(IDLE_TASK_TOKEN);
+            self.scheduled = true;
+        }
+    }
+
+    /// Run tasks until they are finished or it is time to yield.
+    ///
+    /// If the window is animating, with its last frame painted at
+    /// `last_frame`, this yields in time for the next frame.
+    pub(crate) fn run(
+        &mut self,
+        last_frame: Option<Instant>,
+        command_queue: &mut VecDeque<(WindowId, Command)>,
+        window_id: WindowId,
+    ) {
+        self.scheduled = false;
+        let deadline = budget_end(Instant::now(), last_frame);
+        let mut ctx = IdleCtx {
+            deadline,
+            command_queue,
+            window_id,
+        };
+        while !ctx.should_yield() {
+            let index = match self.next(Instant::now()) {
+                Some(index) => index,
+                None => break,
+            };
+            if (self.tasks[index].1.work)(&mut ctx) == IdleStatus::Done {
+                self.tasks.remove(index);
+            }
+        }
+    }
+
+    /// The index of the task to run next.
+    fn next(&self, now: Instant) -> Option<usize> {
+        let overdue = self
+            .tasks
+            .iter()
+            .enumerate()
+            .filter_map(|(i, (_, task))| task.deadline.filter(|d| *d <= now).map(|d| (i, d)))
+            .min_by_key(|(_, deadline)| *deadline);
+        if let Some((index, _)) = overdue {
+            return Some(index);
+        }
+        self.tasks
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, (id, task))| (std::cmp::Reverse(task.priority), *id))
+            .map(|(index, _)| index)
+    }
+}
+
+/// The time idle tasks starting at `now` should yield by.
+fn budget_end(now: Instant, last_frame: Option<Instant>) -> Instant {
+    match last_frame {
+        Some(last) => (last + FRAME_INTERVAL - FRAME_MARGIN).max(now + MIN_BUDGET),
+        None => now + IDLE_BUDGET,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+
+    #[test]
+    fn order() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut queue = IdleQueue::default();
+        let now = Instant::now();
+        for (name, priority, deadline) in vec![
+            ("low", IdlePriority::Low, None),
+            ("normal", IdlePriority::Normal, None),
+            ("high", IdlePriority::High, None),
+            ("overdue", IdlePriority::Low, Some(now)),
+            ("normal 2", IdlePriority::Normal, None),
+        ] {
+            let log = log.clone();
+            let mut task = IdleTask::new(move |_| {
+                log.borrow_mut().push(name);
+                IdleStatus::Done
+            })
+            .priority(priority);
+            if let Some(deadline) = deadline {
+                task = task.deadline(deadline);
+            }
+            queue.push(task);
+        }
+        let mut commands = VecDeque::new();
+        queue.run(None, &mut commands, WindowId::new());
+        assert_eq!(
+            *log.borrow(),
+            vec!["overdue", "high", "normal", "normal 2", "low"]
+        );
+    }
+
+    #[test]
+    fn budget() {
+        let now = Instant::now();
+        assert_eq!(budget_end(now, None), now + IDLE_BUDGET);
+        // A frame painted just now leaves most of the interval.
+        let end = budget_end(now, Some(now));
+        assert_eq!(end, now + FRAME_INTERVAL - FRAME_MARGIN);
+        // A frame that is nearly due still leaves the minimum.
+        let end = budget_end(now, Some(now - FRAME_INTERVAL));
+        assert_eq!(end, now + MIN_BUDGET);
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Space around the edges of a rectangle.
+
+use crate::kurbo::Size;
+use crate::BoxConstraints;
+
+/// Distances inward from each edge of a rectangle, such as the padding
+/// around a widget.
+///
+/// `Insets` can be created from a single `f64`, for the same distance on
+/// every side, from a `(horizontal, vertical)` pair, or from a
+/// `(left, top, right, bottom)` tuple.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Insets {
+    /// The space at the left edge.
+    pub left: f64,
+    /// The space at the top edge.
+    pub top: f64,
+    /// The space at the right edge.
+    pub right: f64,
+    /// The space at the bottom edge.
+    pub bottom: f64,
+}
+
+impl Insets {
+    /// No space on any side.
+    pub const ZERO: Insets = Insets::uniform(0.0);
+
+    /// Insets with a distinct distance for each side.
+    pub const fn new(left: f64, top: f64, right: f64, bottom: f64) -> Insets {
+        Insets {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    /// The same distance on every side.
+    pub const fn uniform(distance: f64) -> Insets {
+        Insets::new(distance, distance, distance, distance)
+    }
+
+    /// One distance for the left and right, and another for the top and
+    /// bottom.
+    pub const fn uniform_xy(horizontal: f64, vertical: f64) -> Insets {
+        Insets::new(horizontal, vertical, horizontal, vertical)
+    }
+
+    /// The sum of the left and right insets.
+    pub fn x_value(self) -> f64 {
+        self.left + self.right
+    }
+
+    /// The sum of the top and bottom insets.
+    pub fn y_value(self) -> f64 {
+        self.top + self.bottom
+    }
+
+    /// The constraints left for the content inside these insets. Neither
+    /// bound goes below zero, however large the insets.
+    pub(crate) fn shrink(self, bc: &BoxConstraints) -> BoxConstraints {
+        let shrink = |size: Size| {
+            Size::new(
+                (size.width - self.x_value()).max(0.0),
+                (size.height - self.y_value()).max(0.0),
+            )
+        };
+        BoxConstraints::new(shrink(bc.min()), shrink(bc.max()))
+    }
+}
+
+impl From<f64> for Insets {
+    fn from(distance: f64) -> Insets {
+        Insets::uniform(distance)
+    }
+}
+
+impl From<(f64, f64)> for Insets {
+    fn from((horizontal, vertical): (f64, f64)) -> Insets {
+        Insets::uniform_xy(horizontal, vertical)
+    }
+}
+
+impl From<(f64, f64, f64, f64)> for Insets {
+    fn from((left, top, right, bottom): (f64, f64, f64, f64)) -> Insets {
+        Insets::new(left, top, right, bottom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions() {
+        assert_eq!(Insets::from(3.0), Insets::new(3.0, 3.0, 3.0, 3.0));
+        assert_eq!(Insets::from((1.0, 2.0)), Insets::new(1.0, 2.0, 1.0, 2.0));
+        assert_eq!(
+            Insets::from((1.0, 2.0, 3.0, 4.0)),
+            Insets::new(1.0, 2.0, 3.0, 4.0)
+        );
+        assert_eq!(Insets::default(), Insets::ZERO);
+    }
+
+    #[test]
+    fn sums() {
+        let insets = Insets::new(1.0, 2.0, 3.0, 4.0);
+        assert_eq!(insets.x_value(), 4.0);
+        assert_eq!(insets.y_value(), 6.0);
+    }
+
+    #[test]
+    fn shrink_constraints() {
+        let insets = Insets::new(1.0, 2.0, 3.0, 4.0);
+        let bc = BoxConstraints::new(Size::new(10.0, 10.0), Size::new(100.0, 50.0));
+        let inner = insets.shrink(&bc);
+        assert_eq!(inner.min(), Size::new(6.0, 4.0));
+        assert_eq!(inner.max(), Size::new(96.0, 44.0));
+
+        // The content gets no space, rather than negative space.
+        let inner = Insets::uniform(40.0).shrink(&bc);
+        assert_eq!(inner.min(), Size::ZERO);
+        assert_eq!(inner.max(), Size::new(20.0, 0.0));
+    }
+}
@@ -0,0 +1,179 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A window showing the live widget tree of another window.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use crate::kurbo::{Rect, Size};
+use crate::piet::RenderContext;
+use crate::text::FontSet;
+use crate::theme;
+use crate::widget::Scroll;
+use crate::{
+    BaseState, BoxConstraints, Command, Data, Env, Event, EventCtx, LayoutCtx, LocalizedString,
+    PaintCtx, Selector, TimerToken, UpdateCtx, Widget, WidgetId, WidgetInfo, WidgetTree,
+    WindowDesc, WindowId,
+};
+
+/// Sent to the inspected window when the highlighted widget changes, so
+/// that it repaints.
+pub(crate) const INSPECTOR_HIGHLIGHT: Selector = Selector::new("druid-builtin.inspector-highlight");
+
+/// How often the inspector refreshes its copy of the tree.
+const REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+
+const ROW_HEIGHT: f64 = 18.0;
+const INDENT: f64 = 12.0;
+
+/// The tree of an inspected window, as of the last time it was painted,
+/// and the widget selected in the inspector.
+#[derive(Debug, Default)]
+pub(crate) struct Inspection {
+    pub(crate) tree: WidgetTree,
+    pub(crate) highlight: Option<WidgetId>,
+}
+
+pub(crate) type SharedTree = Rc<RefCell<Inspection>>;
+
+impl Inspection {
+    /// Replace the tree with a newly painted one, keeping the selection if
+    /// the widget is still there.
+    pub(crate) fn set_tree(&mut self, tree: WidgetTree) {
+        self.highlight = self.highlight.filter(|id| tree.get(*id).is_some());
+        self.tree = tree;
+    }
+}
+
+/// The list of widgets shown in the inspector.
+struct TreeView {
+    tree: SharedTree,
+    target: WindowId,
+    font: Option<FontSet>,
+    timer: TimerToken,
+}
+
+/// The inspector window for the window `target`.
+pub(crate) fn window<T: Data + 'static>(tree: SharedTree, target: WindowId) -> WindowDesc<T> {
+    let mut desc = WindowDesc::new(move || {
+        Scroll::new(TreeView {
+            tree: tree.clone(),
+            target,
+            font: None,
+            timer: TimerToken::INVALID,
+        })
+    })
+    .title(LocalizedString::new("inspector-title"));
+    desc.menu = None;
+    desc
+}
+
+/// A line describing a widget: its type and id, as in the debug overlay,
+/// its bounds, and the type of its data.
+fn describe(node: &WidgetInfo) -> String {
+    let rect = node.window_rect;
+    format!(
+        "{} #{}  ({}, {}) {}×{}  {}",
+        short_name(node.type_name),
+        node.id.to_raw(),
+        rect.x0.round(),
+        rect.y0.round(),
+        rect.width().round(),
+        rect.height().round(),
+        node.data_type,
+    )
+}
+
+/// The name of a type without its path or generic parameters.
+pub(crate) fn short_name(type_name: &str) -> &str {
+    let name = type_name.split('<').next().unwrap_or(type_name);
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+impl<T: Data> Widget<T> for TreeView {
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, _data: &T, env: &Env) {
+        let font = self
+            .font
+            .get_or_insert_with(|| FontSet::from_env(paint_ctx.text(), env));
+        let inspection = self.tree.borrow();
+        let text_color = env.get(theme::LABEL_COLOR);
+        for (i, node) in inspection.tree.widgets().iter().enumerate() {
+            let y = i as f64 * ROW_HEIGHT;
+            if inspection.highlight == Some(node.id) {
+                let row = Rect::new(0.0, y, base_state.size().width, y + ROW_HEIGHT);
+                paint_ctx.fill(row, &env.get(theme::PRIMARY_DARK));
+            }
+            let line = font.layout(paint_ctx.text(), &describe(node));
+            let x = 4.0 + node.depth as f64 * INDENT;
+            line.draw(paint_ctx, (x, y + ROW_HEIGHT - 5.0), &text_color);
+        }
+    }
+
+    fn layout(&mut self, _ctx: &mut LayoutCtx, bc: &BoxConstraints, _data: &T, _env: &Env) -> Size {
+        let rows = self.tree.borrow().tree.widgets().len();
+        bc.constrain(Size::new(bc.max().width, rows as f64 * ROW_HEIGHT))
+    }
+
+    fn event(&mut self, event: &Event, ctx: &mut EventCtx, _data: &mut T, _env: &Env) {
+        if self.timer == TimerToken::INVALID {
+            self.timer = ctx.request_timer(Instant::now() + REFRESH_INTERVAL);
+        }
+        match event {
+            Event::Timer(token) if *token == self.timer => {
+                ctx.invalidate();
+                self.timer = ctx.request_timer(Instant::now() + REFRESH_INTERVAL);
+            }
+            Event::MouseDown(mouse) => {
+                let row = (mouse.pos.y / ROW_HEIGHT).floor() as usize;
+                let mut inspection = self.tree.borrow_mut();
+                let clicked = inspection.tree.widgets().get(row).map(|node| node.id);
+                inspection.highlight = if clicked == inspection.highlight {
+                    None
+                } else {
+                    clicked
+                };
+                ctx.submit_command(Command::from(INSPECTOR_HIGHLIGHT), Some(self.target));
+                ctx.invalidate();
+                ctx.set_handled();
+            }
+            _ => (),
+        }
+    }
+
+    fn update(&mut self, _ctx: &mut UpdateCtx, _old_data: Option<&T>, _data: &T, _env: &Env) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describe_node() {
+        let node = WidgetInfo {
+            id: WidgetId(3),
+            type_name: "druid::widget::padding::Padding<u32>",
+            data_type: "u32",
+            depth: 1,
+            layout_rect: Rect::new(0.0, 10.0, 100.0, 40.0),
+            window_rect: Rect::new(10.0, 20.0, 110.0, 50.0),
+            is_hot: false,
+            is_active: false,
+            has_focus: false,
+        };
+        assert_eq!(describe(&node), "Padding #3  (10, 20) 100×30  u32");
+        assert_eq!(short_name("Box<dyn druid::Widget<u32>>"), "Box");
+    }
+}
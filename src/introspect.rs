@@ -0,0 +1,162 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A description of the widgets in a window, for tooling.
+
+use crate::kurbo::{Point, Rect};
+use crate::WidgetId;
+
+/// A widget in a [`WidgetTree`], as it was when its window was last painted.
+///
+/// [`WidgetTree`]: struct.WidgetTree.html
+#[derive(Debug, Clone)]
+pub struct WidgetInfo {
+    /// The widget's id.
+    pub id: WidgetId,
+    /// The widget's type, as given by `std::any::type_name`.
+    pub type_name: &'static str,
+    /// The type of the data the widget sees, after any lenses.
+    pub data_type: &'static str,
+    /// The number of ancestors the widget has; the root widget is at
+    /// depth 0.
+    pub depth: usize,
+    /// The widget's layout rect, in its parent's coordinate space.
+    pub layout_rect: Rect,
+    /// The widget's bounds, in window coordinates.
+    pub window_rect: Rect,
+    /// Whether the widget is [hot].
+    ///
+    /// [hot]: struct.BaseState.html#method.is_hot
+    pub is_hot: bool,
+    /// Whether the widget is [active].
+    ///
+    /// [active]: struct.BaseState.html#method.is_active
+    pub is_active: bool,
+    /// Whether the widget or one of its descendants has [focus].
+    ///
+    /// [focus]: struct.BaseState.html#method.has_focus
+    pub has_focus: bool,
+}
+
+/// The widgets mounted in a window.
+///
+/// This is a snapshot, taken while the window is painted; it is sent in
+/// response to [`DESCRIBE_WIDGETS`]. Widgets are listed in the order they
+/// were painted, each followed by its descendants, so widgets that are
+/// not painted, such as the hidden branch of an `Either`, are not part of
+/// the tree.
+///
+/// [`DESCRIBE_WIDGETS`]: command/sys/constant.DESCRIBE_WIDGETS.html
+#[derive(Debug, Clone, Default)]
+pub struct WidgetTree {
+    widgets: Vec<WidgetInfo>,
+}
+
+impl WidgetTree {
+    pub(crate) fn new(widgets: Vec<WidgetInfo>) -> Self {
+        WidgetTree { widgets }
+    }
+
+    /// All the widgets, each followed by its descendants.
+    pub fn widgets(&self) -> &[WidgetInfo] {
+        &self.widgets
+    }
+
+    /// The root widget of the window.
+    pub fn root(&self) -> Option<&WidgetInfo> {
+        self.widgets.first()
+    }
+
+    /// The widget with the given id.
+    pub fn get(&self, id: WidgetId) -> Option<&WidgetInfo> {
+        self.widgets.iter().find(|w| w.id == id)
+    }
+
+    /// The direct children of a widget.
+    pub fn children(&self, id: WidgetId) -> impl Iterator<Item = &WidgetInfo> {
+        let (start, depth) = match self.index(id) {
+            Some(i) => (i + 1, self.widgets[i].depth),
+            None => (self.widgets.len(), 0),
+        };
+        self.widgets[start..]
+            .iter()
+            .take_while(move |w| w.depth > depth)
+            .filter(move |w| w.depth == depth + 1)
+    }
+
+    /// The parent of a widget.
+    pub fn parent(&self, id: WidgetId) -> Option<&WidgetInfo> {
+        let i = self.index(id)?;
+        let depth = self.widgets[i].depth;
+        self.widgets[..i].iter().rev().find(|w| w.depth < depth)
+    }
+
+    /// The widget that has focus, if any.
+    ///
+    /// This is the innermost widget whose focus flag is set; its ancestors
+    /// have the flag set too.
+    pub fn focused(&self) -> Option<&WidgetInfo> {
+        self.widgets.iter().filter(|w| w.has_focus).last()
+    }
+
+    /// The innermost widget containing a point, in window coordinates.
+    pub fn widget_at(&self, point: Point) -> Option<&WidgetInfo> {
+        self.widgets
+            .iter()
+            .filter(|w| w.window_rect.contains(point))
+            .last()
+    }
+
+    fn index(&self, id: WidgetId) -> Option<usize> {
+        self.widgets.iter().position(|w| w.id == id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(id: u64, depth: usize, rect: Rect, has_focus: bool) -> WidgetInfo {
+        WidgetInfo {
+            id: WidgetId(id),
+            type_name: "",
+            data_type: "",
+            depth,
+            layout_rect: rect,
+            window_rect: rect,
+            is_hot: false,
+            is_active: false,
+            has_focus,
+        }
+    }
+
+    #[test]
+    fn walk() {
+        let tree = WidgetTree::new(vec![
+            info(1, 0, Rect::new(0.0, 0.0, 100.0, 100.0), true),
+            info(2, 1, Rect::new(0.0, 0.0, 100.0, 50.0), false),
+            info(3, 2, Rect::new(10.0, 10.0, 90.0, 40.0), false),
+            info(4, 1, Rect::new(0.0, 50.0, 100.0, 100.0), true),
+        ]);
+        let children: Vec<_> = tree.children(WidgetId(1)).map(|w| w.id).collect();
+        assert_eq!(children, vec![WidgetId(2), WidgetId(4)]);
+        assert_eq!(tree.children(WidgetId(3)).count(), 0);
+        assert_eq!(tree.parent(WidgetId(3)).map(|w| w.id), Some(WidgetId(2)));
+        assert_eq!(tree.parent(WidgetId(4)).map(|w| w.id), Some(WidgetId(1)));
+        assert!(tree.parent(WidgetId(1)).is_none());
+        assert_eq!(tree.focused().map(|w| w.id), Some(WidgetId(4)));
+        let hit = tree.widget_at(Point::new(20.0, 20.0));
+        assert_eq!(hit.map(|w| w.id), Some(WidgetId(3)));
+    }
+}
@@ -0,0 +1,232 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Cancellable background jobs that report their progress.
+
+use std::any::Any;
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::warn;
+
+use crate::{CommandSink, Selector};
+
+/// The minimum time between two progress commands from the same job.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A handle to a running background job.
+///
+/// A job is started with [`EventCtx::start_job`]. It is cancelled when this
+/// handle is dropped, so a widget that stores the handle cancels its job
+/// when it is removed from the tree. Use [`detach`] to let a job run to
+/// completion regardless.
+///
+/// Cancellation is cooperative: the job should check
+/// [`JobContext::is_cancelled`] periodically and return early. Once a job
+/// is cancelled it submits no further commands, but one it submitted just
+/// before may still be waiting to be delivered; a widget that cancels a job
+/// should ignore any result that arrives after it has moved on.
+///
+/// [`EventCtx::start_job`]: struct.EventCtx.html#method.start_job
+/// [`detach`]: #method.detach
+/// [`JobContext::is_cancelled`]: struct.JobContext.html#method.is_cancelled
+pub struct Job {
+    cancelled: Arc<AtomicBool>,
+    detached: bool,
+}
+
+/// The context passed to a running job.
+pub struct JobContext {
+    sink: CommandSink,
+    progress: Option<Selector>,
+    cancelled: Arc<AtomicBool>,
+    last_progress: Cell<Option<Instant>>,
+}
+
+impl Job {
+    /// Cancel the job.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns `true` if the job has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Let the job continue after this handle is dropped.
+    pub fn detach(mut self) {
+        self.detached = true;
+    }
+}
+
+impl Drop for Job {
+    fn drop(&mut self) {
+        if !self.detached {
+            self.cancel();
+        }
+    }
+}
+
+impl JobContext {
+    /// Returns `true` if the job has been cancelled, and should stop.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Report the job's progress, in the range `0.0..=1.0`.
+    ///
+    /// The progress is submitted as a command with an `f64` argument, which
+    /// can be stored in the data of a [`ProgressBar`]. Reports are dropped
+    /// if they arrive too soon after the previous one, so this can be called
+    /// as often as is convenient.
+    ///
+    /// [`ProgressBar`]: widget/struct.ProgressBar.html
+    pub fn report_progress(&self, progress: f64) {
+        self.report_progress_at(progress, Instant::now());
+    }
+
+    /// Report the job's progress, as though at the given time.
+    fn report_progress_at(&self, progress: f64, now: Instant) {
+        let selector = match self.progress.as_ref() {
+            Some(selector) => selector,
+            None => return,
+        };
+        if self.is_cancelled() {
+            return;
+        }
+        let due = self
+            .last_progress
+            .get()
+            .map(|last| now.duration_since(last) >= PROGRESS_INTERVAL)
+            .unwrap_or(true);
+        if due || progress >= 1.0 {
+            self.last_progress.set(Some(now));
+            self.sink
+                .submit_command(selector.clone(), progress.max(0.0).min(1.0), None);
+        }
+    }
+}
+
+/// Run a job on a new thread, submitting its result as a command.
+pub(crate) fn start<F, R>(
+    sink: CommandSink,
+    progress: Option<Selector>,
+    done: Selector,
+    task: F,
+) -> Job
+where
+    F: FnOnce(&JobContext) -> R + Send + 'static,
+    R: Any + Send,
+{
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let ctx = JobContext {
+        sink,
+        progress,
+        cancelled: cancelled.clone(),
+        last_progress: Cell::new(None),
+    };
+    let name = format!("druid-job-{}", done.as_str());
+    let result = thread::Builder::new().name(name).spawn(move || {
+        let result = task(&ctx);
+        if !ctx.is_cancelled() {
+            ctx.sink.submit_command(done, result, None);
+        }
+    });
+    if let Err(e) = result {
+        warn!("failed to spawn job thread: {}", e);
+        cancelled.store(true, Ordering::SeqCst);
+    }
+    Job {
+        cancelled,
+        detached: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ext_event::ExtQueue;
+    use std::sync::mpsc;
+
+    const PROGRESS: Selector = Selector::new("test.progress");
+    const DONE: Selector = Selector::new("test.done");
+
+    #[test]
+    fn progress_is_throttled() {
+        let queue = ExtQueue::default();
+        let ctx = JobContext {
+            sink: CommandSink::new(queue.clone(), None),
+            progress: Some(PROGRESS),
+            cancelled: Arc::new(AtomicBool::new(false)),
+            last_progress: Cell::new(None),
+        };
+        let epoch = Instant::now();
+        let at = |ms| epoch + Duration::from_millis(ms);
+        ctx.report_progress_at(0.2, at(0));
+        // Too soon after the first report.
+        ctx.report_progress_at(0.3, at(10));
+        ctx.report_progress_at(0.4, at(50));
+        // Completion is always reported.
+        ctx.report_progress_at(1.0, at(51));
+        let reported = queue
+            .lock()
+            .unwrap()
+            .drain(..)
+            .map(|ext| *ext.into_command().get_object::<f64>().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(reported, [0.2, 0.4, 1.0]);
+    }
+
+    #[test]
+    fn progress_and_cancellation() {
+        let queue = ExtQueue::default();
+        let sink = CommandSink::new(queue.clone(), None);
+        let (tx, rx) = mpsc::channel();
+        let job = start(sink.clone(), Some(PROGRESS), DONE, move |ctx| {
+            // The first report and the final one are never throttled.
+            ctx.report_progress(0.5);
+            ctx.report_progress(1.0);
+            tx.send(()).unwrap();
+            7u32
+        });
+        rx.recv().unwrap();
+        job.detach();
+        let commands = loop {
+            let queue = queue.lock().unwrap();
+            if queue.len() == 3 {
+                break queue.iter().map(|c| c.selector.clone()).collect::<Vec<_>>();
+            }
+            drop(queue);
+            thread::yield_now();
+        };
+        assert_eq!(commands, [PROGRESS, PROGRESS, DONE]);
+
+        let (tx, rx) = mpsc::channel();
+        let (go_tx, go_rx) = mpsc::channel::<()>();
+        queue.lock().unwrap().clear();
+        let job = start(sink, Some(PROGRESS), DONE, move |ctx| {
+            go_rx.recv().unwrap();
+            ctx.report_progress(0.5);
+            tx.send(ctx.is_cancelled()).unwrap();
+        });
+        drop(job);
+        go_tx.send(()).unwrap();
+        assert!(rx.recv().unwrap());
+        assert!(queue.lock().unwrap().is_empty());
+    }
+}
@@ -125,6 +125,10 @@ where
             .with_mut(data, |data| inner.event(event, ctx, data, env))
     }
 
+    fn accepts_focus(&self) -> bool {
+        self.inner.accepts_focus()
+    }
+
     fn update(&mut self, ctx: &mut UpdateCtx, old_data: Option<&T>, data: &T, env: &Env) {
         if let Some(old_data) = old_data {
             if self.lens.get(old_data).same(self.lens.get(data)) {
@@ -16,31 +16,74 @@
 
 #![deny(intra_doc_link_resolution_failure)]
 
-pub use druid_shell::{self as shell, kurbo, piet};
+pub use druid_shell::{self as shell, kurbo, piet, raw_window_handle};
 
 pub mod widget;
 
+mod animation;
 mod app;
+mod app_delegate;
+mod background;
+mod bidi;
 pub mod command;
 mod data;
+mod debug_paint;
+mod dnd;
+mod easing;
+mod embed;
 mod env;
 mod event;
+mod ext_event;
+#[cfg(feature = "notify")]
+mod file_watcher;
+mod focus;
+mod format;
+#[cfg(not(any(target_os = "windows", target_arch = "wasm32")))]
+mod harness;
+#[cfg(not(any(target_os = "windows", target_arch = "wasm32")))]
+mod headless;
+mod idle;
+mod insets;
+mod inspector;
+mod introspect;
+mod job;
 mod lens;
 pub mod localization;
+#[cfg(feature = "lottie")]
+mod lottie;
 pub mod menu;
+#[cfg(feature = "persistence")]
+mod persist;
+mod print;
+mod profiler;
+mod replay;
+mod shadow;
+mod shared;
+#[cfg(not(any(target_os = "windows", target_arch = "wasm32")))]
+pub mod snapshot;
+mod text;
 pub mod theme;
+mod time_travel;
+mod trace;
+mod undo;
+mod validation;
 mod win_handler;
 mod window;
 
+use std::any::Any;
 use std::collections::VecDeque;
+use std::future::Future;
 use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 
 use std::time::Instant;
 
 use log::{error, warn};
 
 use kurbo::{Affine, Point, Rect, Shape, Size, Vec2};
-use piet::{Piet, RenderContext};
+use piet::{Color, ImageFormat, InterpolationMode, Piet, RenderContext};
+use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
 
 pub use unicode_segmentation;
 
@@ -50,23 +93,66 @@ pub use unicode_segmentation;
 use druid_shell::application::Application;
 pub use druid_shell::clipboard::ClipboardItem;
 pub use druid_shell::dialog::{FileDialogOptions, FileDialogType};
-pub use druid_shell::keyboard::{KeyCode, KeyEvent, KeyModifiers};
+pub use druid_shell::dnd::{DragContents, DragData, DragImage, DropEffect};
+pub use druid_shell::ime::ImeEvent;
+pub use druid_shell::keyboard::{KbKey, KeyCode, KeyEvent, KeyLocation, KeyModifiers};
 #[allow(unused)]
 use druid_shell::platform::IdleHandle;
-pub use druid_shell::window::{Cursor, MouseButton, MouseEvent, TimerToken};
+pub use druid_shell::print::PageSetup;
+pub use druid_shell::taskbar::TaskbarProgress;
+pub use druid_shell::window::{
+    Cursor, IdleToken, MouseButton, MouseEvent, PenEvent, PenEventKind, ScrollDevice, ScrollPhase,
+    TimerToken, WheelEvent,
+};
 use druid_shell::window::{Text, WinCtx, WindowHandle};
+
+use debug_paint::DebugPaint;
+use dnd::DragState;
+use ext_event::ExtQueue;
+use focus::FocusState;
+use idle::IdleQueue;
+use profiler::Pass;
 pub use shell::hotkey::{HotKey, RawMods, SysMods};
 
-pub use app::{AppLauncher, WindowDesc};
+pub use animation::{Animator, Interpolate, Transition};
+pub use app::{AppLauncher, JumpListTask, WindowDesc, WindowPlacement};
+pub use app_delegate::{AppDelegate, DelegateCtx, WindowInfo};
+pub use background::Background;
 pub use command::{Command, Selector};
 pub use data::Data;
+pub use dnd::DragEvent;
+pub use easing::Easing;
+pub use embed::EmbeddedApp;
 pub use env::{Env, Key, Value};
-pub use event::{Event, WheelEvent};
+pub use event::Event;
+pub use ext_event::CommandSink;
+#[cfg(feature = "notify")]
+pub use file_watcher::{FileEvent, FileWatcher};
+pub use format::{Date, DateStyle};
+#[cfg(not(any(target_os = "windows", target_arch = "wasm32")))]
+pub use harness::{Harness, Probe};
+#[cfg(not(any(target_os = "windows", target_arch = "wasm32")))]
+pub use headless::HeadlessApp;
+pub use idle::{IdleCtx, IdlePriority, IdleStatus, IdleTask};
+pub use insets::Insets;
+pub use introspect::{WidgetInfo, WidgetTree};
+pub use job::{Job, JobContext};
 pub use lens::{Lens, LensWrap};
-pub use localization::LocalizedString;
+pub use localization::{L10nResources, LocalizedString};
 pub use menu::MenuDesc;
+#[cfg(feature = "persistence")]
+pub use persist::Persistence;
+pub use print::PrintDocument;
+pub use replay::{InputEvent, RecordedEvent, Recording};
+pub use shared::SharedState;
+#[cfg(not(any(target_os = "windows", target_arch = "wasm32")))]
+pub use shell::headless::HeadlessWindow;
+pub use shell::image::RgbaImage;
+pub use text::{DocumentLayout, TextAlignment, TextLayout};
+pub use undo::UndoManager;
+pub use validation::{FormValidator, Validator};
 pub use win_handler::DruidHandler;
-pub use window::{Window, WindowId};
+pub use window::{FrameRate, Window, WindowId};
 
 /// A container for one widget in the hierarchy.
 ///
@@ -85,11 +171,36 @@ pub struct WidgetPod<T: Data, W: Widget<T>> {
     old_data: Option<T>,
     env: Option<Env>,
     inner: W,
+    id: WidgetId,
+    /// The type of the widget, as it was before boxing.
+    type_name: &'static str,
+    /// The constraints and size of the last layout.
+    layout_cache: Option<(BoxConstraints, Size)>,
 }
 
 /// Convenience type for dynamic boxed widget.
 pub type BoxedWidget<T> = WidgetPod<T, Box<dyn Widget<T>>>;
 
+/// A unique identifier for a widget, assigned when its [`WidgetPod`] is
+/// created.
+///
+/// [`WidgetPod`]: struct.WidgetPod.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct WidgetId(u64);
+
+static WIDGET_ID_COUNTER: AtomicU64 = AtomicU64::new(1);
+
+impl WidgetId {
+    fn next() -> WidgetId {
+        WidgetId(WIDGET_ID_COUNTER.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// The id as a number, such as for display.
+    pub fn to_raw(self) -> u64 {
+        self.0
+    }
+}
+
 /// Generic state for all widgets in the hierarchy.
 ///
 /// This struct contains the widget's layout rect, flags
@@ -114,10 +225,19 @@ pub struct BaseState {
     // This should become an invalidation rect.
     needs_inval: bool,
 
+    /// This widget or a descendant needs to be laid out again.
+    needs_layout: bool,
+
     is_hot: bool,
 
     is_active: bool,
 
+    /// A drag from another application is over this widget.
+    is_drag_hot: bool,
+
+    /// This widget or a descendant has started a drag that has not finished.
+    is_drag_source: bool,
+
     /// Any descendant is active.
     has_active: bool,
 
@@ -135,6 +255,10 @@ pub struct BaseState {
 
     /// This widget or a descendant has requested focus.
     request_focus: bool,
+
+    /// An area, in this widget's coordinates, that a descendant has asked
+    /// to have scrolled into view.
+    request_scroll: Option<Rect>,
 }
 
 /// The trait implemented by all widgets.
@@ -222,6 +346,21 @@ pub trait Widget<T> {
     /// [`Command`]: struct.Command.html
     fn event(&mut self, event: &Event, ctx: &mut EventCtx, data: &mut T, env: &Env);
 
+    /// Whether the widget can take keyboard focus.
+    ///
+    /// Widgets that accept focus are reached, in order, by pressing Tab,
+    /// and those that get focus this way receive [`FocusChanged`] as they
+    /// would after calling [`request_focus`]. Wrappers that contain their
+    /// child directly, rather than in a [`WidgetPod`], should forward this
+    /// to the child.
+    ///
+    /// [`FocusChanged`]: enum.Event.html#variant.FocusChanged
+    /// [`request_focus`]: struct.EventCtx.html#method.request_focus
+    /// [`WidgetPod`]: struct.WidgetPod.html
+    fn accepts_focus(&self) -> bool {
+        false
+    }
+
     /// Handle a change of data.
     ///
     /// This method is called whenever the data changes. When the appearance of
@@ -255,6 +394,10 @@ impl<T> Widget<T> for Box<dyn Widget<T>> {
         self.deref_mut().event(event, ctx, data, env)
     }
 
+    fn accepts_focus(&self) -> bool {
+        self.deref().accepts_focus()
+    }
+
     fn update(&mut self, ctx: &mut UpdateCtx, old_data: Option<&T>, data: &T, env: &Env) {
         self.deref_mut().update(ctx, old_data, data, env);
     }
@@ -271,6 +414,29 @@ pub struct PaintCtx<'a, 'b: 'a> {
     /// The render context for actually painting.
     pub render_ctx: &'a mut Piet<'b>,
     pub window_id: WindowId,
+    /// The text cursor area for the input method, in window coordinates.
+    ime_cursor_rect: Option<Rect>,
+    /// The state of the debug overlay, if it is shown.
+    debug: Option<DebugPaint>,
+    /// Whether focus was last moved with the keyboard.
+    focus_visible: bool,
+    /// The layout rects being collected by [`collect_child_rects`].
+    ///
+    /// [`collect_child_rects`]: #method.collect_child_rects
+    child_rects: Option<ChildRects>,
+    /// The transform from the coordinate space of the widget being painted
+    /// to that of the render context, which piet doesn't report.
+    transform: Affine,
+    /// The transforms to go back to as saved states are restored.
+    saved_transforms: Vec<Affine>,
+}
+
+/// The layout rects of the widgets two levels below the one collecting
+/// them, which are found as they are painted.
+struct ChildRects {
+    /// How many levels below the collecting widget the current one is.
+    depth: usize,
+    rects: Vec<Rect>,
 }
 
 impl<'a, 'b: 'a> Deref for PaintCtx<'a, 'b> {
@@ -287,6 +453,273 @@ impl<'a, 'b: 'a> DerefMut for PaintCtx<'a, 'b> {
     }
 }
 
+impl<'a, 'b: 'a> PaintCtx<'a, 'b> {
+    /// Tell the input method where the text cursor is, so that it can
+    /// place its candidate window next to it.
+    ///
+    /// The rect is in the coordinate space of the widget being painted.
+    /// Text widgets should call this each time they are painted while they
+    /// have focus.
+    pub fn set_ime_cursor_rect(&mut self, rect: Rect) {
+        let origin = self.transform * rect.origin();
+        self.ime_cursor_rect = Some(Rect::from_origin_size(origin, rect.size()));
+    }
+
+    /// Save the render context's state, including its transform and clip,
+    /// to be restored by [`restore`].
+    ///
+    /// [`restore`]: #method.restore
+    pub fn save(&mut self) -> Result<(), piet::Error> {
+        self.render_ctx.save()?;
+        self.saved_transforms.push(self.transform);
+        Ok(())
+    }
+
+    /// Restore the state of the render context saved by the last [`save`].
+    ///
+    /// [`save`]: #method.save
+    pub fn restore(&mut self) -> Result<(), piet::Error> {
+        self.render_ctx.restore()?;
+        if let Some(transform) = self.saved_transforms.pop() {
+            self.transform = transform;
+        }
+        Ok(())
+    }
+
+    /// Apply a transform to what is painted from now on.
+    pub fn transform(&mut self, transform: Affine) {
+        self.render_ctx.transform(transform);
+        self.transform = self.transform * transform;
+    }
+
+    /// The transform from the coordinate space of the widget being painted
+    /// to that of the window, or of the image being painted offscreen.
+    pub fn current_transform(&self) -> Affine {
+        self.transform
+    }
+
+    /// Whether the widget should show that it has focus.
+    ///
+    /// This is the case when it has focus, and focus was last moved with
+    /// the keyboard rather than by clicking, so that a focus indicator is
+    /// shown to keyboard users without distracting everyone else.
+    pub fn is_focus_visible(&self, base_state: &BaseState) -> bool {
+        self.focus_visible && base_state.has_focus
+    }
+
+    /// Run `f`, which should paint a single child, and return the layout
+    /// rects of that child's own children that are painted, in its
+    /// coordinate space.
+    pub(crate) fn collect_child_rects(&mut self, f: impl FnOnce(&mut PaintCtx)) -> Vec<Rect> {
+        let outer = self.child_rects.replace(ChildRects {
+            depth: 0,
+            rects: Vec::new(),
+        });
+        f(self);
+        std::mem::replace(&mut self.child_rects, outer)
+            .map(|collected| collected.rects)
+            .unwrap_or_default()
+    }
+
+    /// Paint a focus ring along the outline of a shape, styled with the
+    /// theme's [`FOCUS_RING_COLOR`] and [`FOCUS_RING_WIDTH`], if the widget
+    /// should show that it has focus.
+    ///
+    /// [`FOCUS_RING_COLOR`]: theme/constant.FOCUS_RING_COLOR.html
+    /// [`FOCUS_RING_WIDTH`]: theme/constant.FOCUS_RING_WIDTH.html
+    pub fn paint_focus_ring(&mut self, base_state: &BaseState, shape: impl Shape, env: &Env) {
+        if self.is_focus_visible(base_state) {
+            let width = env.get(theme::FOCUS_RING_WIDTH);
+            self.stroke(shape, &env.get(theme::FOCUS_RING_COLOR), width);
+        }
+    }
+
+    /// Paint with the render context's state saved, and restored afterwards,
+    /// so that transforms and clips applied by `f` only affect what it paints.
+    pub fn with_save(&mut self, f: impl FnOnce(&mut PaintCtx)) {
+        if let Err(e) = self.save() {
+            error!("saving render context failed: {:?}", e);
+            return;
+        }
+        f(self);
+        if let Err(e) = self.restore() {
+            error!("restoring render context failed: {:?}", e);
+        }
+    }
+
+    /// Paint with an additional transform, applied in the coordinate space
+    /// of the widget being painted.
+    ///
+    /// For example, `Affine::translate(center) * Affine::rotate(angle) *
+    /// Affine::translate(-center)` rotates what `f` paints about `center`.
+    /// The transform only affects painting: events are still delivered to
+    /// children by their layout rects.
+    pub fn with_transform(&mut self, transform: Affine, f: impl FnOnce(&mut PaintCtx)) {
+        self.with_save(|paint_ctx| {
+            paint_ctx.transform(transform);
+            f(paint_ctx);
+        });
+    }
+
+    /// Paint with everything outside of `shape`, in the coordinate space of
+    /// the widget being painted, clipped away.
+    pub fn with_clip(&mut self, shape: impl Shape, f: impl FnOnce(&mut PaintCtx)) {
+        self.with_save(|paint_ctx| {
+            paint_ctx.clip(shape);
+            f(paint_ctx);
+        });
+    }
+
+    /// Paint with the given opacity, from `0.0` to `1.0`, applied to what
+    /// `f` paints as a whole, so that its overlapping parts don't show
+    /// through each other.
+    ///
+    /// What `f` paints is composited offscreen, and clipped to `size`, from
+    /// the origin. Where offscreen painting isn't available, and while the
+    /// debug overlay is shown, it is painted directly, without the opacity.
+    pub fn with_opacity(&mut self, opacity: f64, size: Size, f: impl FnOnce(&mut PaintCtx)) {
+        let opacity = opacity.max(0.0).min(1.0);
+        if opacity == 0.0 {
+            return;
+        }
+        if opacity == 1.0 {
+            f(self);
+            return;
+        }
+        let scale = self.device_scale();
+        let mut f = Some(f);
+        let image = self.paint_offscreen(size, scale, |paint_ctx| {
+            if let Some(f) = f.take() {
+                f(paint_ctx);
+            }
+        });
+        match image {
+            Some(mut image) => {
+                for pixel in image.pixels.chunks_mut(4) {
+                    pixel[3] = (pixel[3] as f64 * opacity).round() as u8;
+                }
+                self.draw_offscreen(&image, scale);
+            }
+            None => {
+                if let Some(f) = f.take() {
+                    f(self);
+                }
+            }
+        }
+    }
+
+    /// Paint the blurred shadow of a rectangle.
+    ///
+    /// See [`shadow`] for details.
+    ///
+    /// [`shadow`]: #method.shadow
+    pub fn blurred_rect(&mut self, rect: Rect, blur_radius: f64, color: &Color) {
+        self.shadow(rect, 0.0, blur_radius, color);
+    }
+
+    /// Paint the blurred shadow of a rectangle with rounded corners.
+    ///
+    /// The blur radius is as for CSS shadows; a gaussian blur with a
+    /// standard deviation of half of it. The shadow extends to one and a
+    /// half times the blur radius beyond `rect`. For a drop shadow, paint
+    /// this before the shape that casts it, with `rect` a little offset.
+    pub fn shadow(&mut self, rect: Rect, corner_radius: f64, blur_radius: f64, color: &Color) {
+        let scale = self.device_scale();
+        let image = shadow::shadow_image(rect.size(), corner_radius, blur_radius, color, scale);
+        let origin = shadow::shadow_bounds(rect, blur_radius).origin();
+        self.with_transform(Affine::translate(origin.to_vec2()), |paint_ctx| {
+            paint_ctx.draw_offscreen(&image, scale);
+        });
+    }
+
+    /// The number of pixels to a unit of the widget's coordinate space.
+    pub(crate) fn device_scale(&self) -> f64 {
+        let coeffs = self.transform.as_coeffs();
+        coeffs[0].hypot(coeffs[1])
+    }
+
+    /// Draw an image, such as one made with [`paint_offscreen`], at the origin.
+    ///
+    /// Returns `false` if it couldn't be drawn.
+    ///
+    /// [`paint_offscreen`]: #method.paint_offscreen
+    pub(crate) fn draw_offscreen(&mut self, image: &RgbaImage, scale: f64) -> bool {
+        let (width, height) = (image.width, image.height);
+        let piet_image = match self.render_ctx.make_image(
+            width,
+            height,
+            &image.pixels,
+            ImageFormat::RgbaSeparate,
+        ) {
+            Ok(image) => image,
+            Err(e) => {
+                warn!("failed to make offscreen image: {:?}", e);
+                return false;
+            }
+        };
+        // The image is a whole number of pixels, so may be a little larger.
+        let image_size = Size::new(width as f64 / scale, height as f64 / scale);
+        let rect = Rect::from_origin_size(Point::ORIGIN, image_size);
+        self.render_ctx
+            .draw_image(&piet_image, rect, InterpolationMode::NearestNeighbor);
+        true
+    }
+
+    /// Paint into a new offscreen image, covering `size` at `scale` pixels
+    /// to the unit.
+    ///
+    /// Returns `None` if the image couldn't be made, or if the debug overlay
+    /// is shown, as it would be baked into the image. This is only available
+    /// where piet draws with cairo; elsewhere, it always returns `None`.
+    #[cfg(not(any(target_os = "windows", target_arch = "wasm32")))]
+    pub(crate) fn paint_offscreen(
+        &mut self,
+        size: Size,
+        scale: f64,
+        f: impl FnOnce(&mut PaintCtx),
+    ) -> Option<RgbaImage> {
+        if self.debug.is_some() {
+            return None;
+        }
+        let width = (size.width * scale).ceil().max(1.0) as u32;
+        let height = (size.height * scale).ceil().max(1.0) as u32;
+        let (window_id, focus_visible) = (self.window_id, self.focus_visible);
+        let child_rects = &mut self.child_rects;
+        let result = shell::headless::render_image(width, height, |piet| {
+            piet.transform(Affine::scale(scale));
+            let mut paint_ctx = PaintCtx {
+                render_ctx: piet,
+                window_id,
+                ime_cursor_rect: None,
+                debug: None,
+                focus_visible,
+                child_rects: child_rects.take(),
+                transform: Affine::scale(scale),
+                saved_transforms: Vec::new(),
+            };
+            f(&mut paint_ctx);
+            *child_rects = paint_ctx.child_rects.take();
+        });
+        match result {
+            Ok((image, ())) => Some(image),
+            Err(e) => {
+                error!("painting offscreen failed: {:?}", e);
+                None
+            }
+        }
+    }
+
+    #[cfg(any(target_os = "windows", target_arch = "wasm32"))]
+    pub(crate) fn paint_offscreen(
+        &mut self,
+        _size: Size,
+        _scale: f64,
+        _f: impl FnOnce(&mut PaintCtx),
+    ) -> Option<RgbaImage> {
+        None
+    }
+}
+
 /// A context provided to layout handling methods of widgets.
 ///
 /// As of now, the main service provided is access to a factory for
@@ -308,8 +741,16 @@ pub struct EventCtx<'a, 'b> {
     // want to group that into a single struct.
     win_ctx: &'a mut dyn WinCtx<'b>,
     cursor: &'a mut Option<Cursor>,
+    /// The state of any in-progress drag.
+    drag: &'a mut DragState,
+    /// The state of keyboard focus in the window.
+    focus: &'a mut FocusState,
     /// Commands submitted to be run after this event.
     command_queue: &'a mut VecDeque<(WindowId, Command)>,
+    /// Commands submitted to this window from other threads.
+    ext_queue: &'a ExtQueue,
+    /// Work to be done when the window is idle.
+    idle: &'a mut IdleQueue,
     window_id: WindowId,
     // TODO: migrate most usage of `WindowHandle` to `WinCtx` instead.
     window: &'a WindowHandle,
@@ -333,6 +774,8 @@ pub struct UpdateCtx<'a, 'b: 'a> {
     // `EventCtx` (and possibly using the same structure). But for
     // now keep it super-simple.
     needs_inval: bool,
+    needs_layout: bool,
+    request_anim: bool,
     window_id: WindowId,
 }
 
@@ -350,7 +793,7 @@ pub struct UpdateCtx<'a, 'b: 'a> {
 ///
 /// [`layout`]: trait.Widget.html#tymethod.layout
 /// [Flutter BoxConstraints]: https://api.flutter.dev/flutter/rendering/BoxConstraints-class.html
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct BoxConstraints {
     min: Size,
     max: Size,
@@ -368,9 +811,17 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
             old_data: None,
             env: None,
             inner,
+            id: WidgetId::next(),
+            type_name: std::any::type_name::<W>(),
+            layout_cache: None,
         }
     }
 
+    /// The id of the widget.
+    pub fn id(&self) -> WidgetId {
+        self.id
+    }
+
     /// Set layout rectangle.
     ///
     /// Intended to be called on child widget in container's `layout`
@@ -398,7 +849,33 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
     /// [`paint`]: trait.Widget.html#method.paint
     /// [`paint_with_offset`]: #method.paint_with_offset
     pub fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &T, env: &Env) {
-        self.inner.paint(paint_ctx, &self.state, data, &env);
+        let (id, type_name) = (self.id, self.type_name);
+        let WidgetPod { state, inner, .. } = self;
+        if let Some(collected) = paint_ctx.child_rects.as_mut() {
+            if collected.depth == 1 {
+                collected.rects.push(state.layout_rect);
+            }
+            collected.depth += 1;
+        }
+        profiler::measure(id, type_name, Pass::Paint, || {
+            if paint_ctx.debug.is_none() {
+                inner.paint(paint_ctx, state, data, env);
+                return;
+            }
+            let data_type = std::any::type_name::<T>();
+            debug_paint::paint_with_overlay(
+                paint_ctx,
+                state,
+                id,
+                type_name,
+                data_type,
+                env,
+                |paint_ctx| inner.paint(paint_ctx, state, data, env),
+            );
+        });
+        if let Some(collected) = paint_ctx.child_rects.as_mut() {
+            collected.depth -= 1;
+        }
     }
 
     /// Paint the widget, translating it by the origin of its layout rectangle.
@@ -420,6 +897,12 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
     /// Generally called by container widgets as part of their [`layout`]
     /// method.
     ///
+    /// The result is cached: if the constraints are the same as last time,
+    /// the data and environment are those of the last update, and neither
+    /// the widget nor its descendants have been updated or invalidated since,
+    /// the previous size is returned without calling the widget's `layout`,
+    /// leaving the layout rects of its children as they were.
+    ///
     /// [`layout`]: trait.Widget.html#method.layout
     pub fn layout(
         &mut self,
@@ -428,7 +911,26 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
         data: &T,
         env: &Env,
     ) -> Size {
-        self.inner.layout(layout_ctx, bc, data, &env)
+        if let Some((cached_bc, size)) = self.layout_cache {
+            // A parent may lay its child out with data or an environment
+            // of its own making, such as an animated value.
+            let data_same = self.old_data.as_ref().map(|d| d.same(data));
+            let env_same = self.env.as_ref().map(|e| e.same(env));
+            if !self.state.needs_layout
+                && cached_bc == *bc
+                && data_same == Some(true)
+                && env_same == Some(true)
+            {
+                return size;
+            }
+        }
+        let inner = &mut self.inner;
+        let size = profiler::measure(self.id, self.type_name, Pass::Layout, || {
+            inner.layout(layout_ctx, bc, data, &env)
+        });
+        self.layout_cache = Some((*bc, size));
+        self.state.needs_layout = false;
+        size
     }
 
     /// Propagate an event.
@@ -451,7 +953,11 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
         let mut child_ctx = EventCtx {
             win_ctx: ctx.win_ctx,
             cursor: ctx.cursor,
+            drag: ctx.drag,
+            focus: ctx.focus,
             command_queue: ctx.command_queue,
+            ext_queue: ctx.ext_queue,
+            idle: ctx.idle,
             window: &ctx.window,
             window_id: ctx.window_id,
             base_state: &mut self.state,
@@ -495,6 +1001,50 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
                 mouse_event.pos -= rect.origin().to_vec2();
                 Event::MouseMoved(mouse_event)
             }
+            Event::Pen(pen_event) => {
+                recurse = had_active || !ctx.had_active && rect.winding(pen_event.pos) != 0;
+                let mut pen_event = pen_event.clone();
+                pen_event.pos -= rect.origin().to_vec2();
+                Event::Pen(pen_event)
+            }
+            Event::DragEnter(drag_event) => {
+                child_ctx.base_state.is_drag_hot = rect.winding(drag_event.pos) != 0;
+                recurse = child_ctx.base_state.is_drag_hot;
+                let mut drag_event = drag_event.clone();
+                drag_event.pos -= rect.origin().to_vec2();
+                Event::DragEnter(drag_event)
+            }
+            Event::DragOver(drag_event) => {
+                let was_drag_hot = child_ctx.base_state.is_drag_hot;
+                child_ctx.base_state.is_drag_hot = rect.winding(drag_event.pos) != 0;
+                let mut drag_event = drag_event.clone();
+                drag_event.pos -= rect.origin().to_vec2();
+                match (was_drag_hot, child_ctx.base_state.is_drag_hot) {
+                    (false, true) => Event::DragEnter(drag_event),
+                    (true, false) => Event::DragLeave,
+                    (is_hot, _) => {
+                        recurse = is_hot;
+                        Event::DragOver(drag_event)
+                    }
+                }
+            }
+            Event::DragLeave => {
+                recurse = child_ctx.base_state.is_drag_hot;
+                child_ctx.base_state.is_drag_hot = false;
+                Event::DragLeave
+            }
+            Event::Drop(drag_event) => {
+                recurse = child_ctx.base_state.is_drag_hot;
+                child_ctx.base_state.is_drag_hot = false;
+                let mut drag_event = drag_event.clone();
+                drag_event.pos -= rect.origin().to_vec2();
+                Event::Drop(drag_event)
+            }
+            Event::DragEnd(effect) => {
+                recurse = child_ctx.base_state.is_drag_source;
+                child_ctx.base_state.is_drag_source = false;
+                Event::DragEnd(*effect)
+            }
             Event::KeyDown(e) => {
                 recurse = child_ctx.base_state.has_focus;
                 Event::KeyDown(*e)
@@ -507,6 +1057,10 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
                 recurse = child_ctx.base_state.has_focus;
                 Event::Paste(e.clone())
             }
+            Event::Ime(e) => {
+                recurse = child_ctx.base_state.has_focus;
+                Event::Ime(e.clone())
+            }
             Event::Wheel(wheel_event) => {
                 recurse = had_active || child_ctx.base_state.is_hot;
                 Event::Wheel(wheel_event.clone())
@@ -529,7 +1083,18 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
                 recurse = child_ctx.base_state.request_timer;
                 Event::Timer(*id)
             }
-            Event::Command(cmd) => Event::Command(cmd.clone()),
+            Event::Idle(token) => Event::Idle(*token),
+            Event::Command(cmd) => {
+                if cmd.selector == focus::COLLECT_FOCUS_CHAIN && self.inner.accepts_focus() {
+                    let has_focus = child_ctx.base_state.has_focus;
+                    child_ctx.focus.chain.push((self.id, has_focus));
+                } else if cmd.selector == focus::TAKE_FOCUS
+                    && child_ctx.focus.target == Some(self.id)
+                {
+                    child_ctx.base_state.request_focus = true;
+                }
+                Event::Command(cmd.clone())
+            }
         };
         child_ctx.base_state.needs_inval = false;
         if let Some(is_hot) = hot_changed {
@@ -539,15 +1104,25 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
         }
         if recurse {
             child_ctx.base_state.has_active = false;
-            self.inner.event(&child_event, &mut child_ctx, data, &env);
+            let inner = &mut self.inner;
+            profiler::measure(self.id, self.type_name, Pass::Event, || {
+                trace::event_delivered(&child_event);
+                inner.event(&child_event, &mut child_ctx, data, &env)
+            });
             child_ctx.base_state.has_active |= child_ctx.base_state.is_active;
         };
         ctx.base_state.needs_inval |= child_ctx.base_state.needs_inval;
+        ctx.base_state.needs_layout |= child_ctx.base_state.needs_layout;
         ctx.base_state.request_anim |= child_ctx.base_state.request_anim;
         ctx.base_state.request_timer |= child_ctx.base_state.request_timer;
         ctx.base_state.is_hot |= child_ctx.base_state.is_hot;
         ctx.base_state.has_active |= child_ctx.base_state.has_active;
         ctx.base_state.request_focus |= child_ctx.base_state.request_focus;
+        ctx.base_state.is_drag_source |= child_ctx.base_state.is_drag_source;
+        if let Some(request) = child_ctx.base_state.request_scroll.take() {
+            let origin = request.origin() + rect.origin().to_vec2();
+            ctx.base_state.request_scroll = Some(Rect::from_origin_size(origin, request.size()));
+        }
         ctx.is_handled |= child_ctx.is_handled;
     }
 
@@ -572,7 +1147,15 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
         if data_same && env_same {
             return;
         }
-        self.inner.update(ctx, self.old_data.as_ref(), data, env);
+        let request_anim = std::mem::replace(&mut ctx.request_anim, false);
+        let (inner, old_data) = (&mut self.inner, self.old_data.as_ref());
+        profiler::measure(self.id, self.type_name, Pass::Update, || {
+            inner.update(ctx, old_data, data, env)
+        });
+        // The flag routes the next animation frame down to this widget.
+        self.state.request_anim |= ctx.request_anim;
+        ctx.request_anim |= request_anim;
+        self.state.needs_layout = true;
         self.old_data = Some(data.clone());
         self.env = Some(env.clone());
     }
@@ -589,6 +1172,9 @@ impl<T: Data, W: Widget<T> + 'static> WidgetPod<T, W> {
             old_data: self.old_data,
             env: self.env,
             inner: Box::new(self.inner),
+            id: self.id,
+            type_name: self.type_name,
+            layout_cache: self.layout_cache,
         }
     }
 }
@@ -727,6 +1313,9 @@ impl<'a, 'b> EventCtx<'a, 'b> {
     ///
     /// Right now, it just invalidates the entire window, but we'll want
     /// finer grained invalidation before long.
+    ///
+    /// This also has the widget laid out again before it is next painted,
+    /// as the change may affect its size or that of its children.
     pub fn invalidate(&mut self) {
         // Note: for the current functionality, we could shortcut and just
         // request an invalidate on the window. But when we do fine-grained
@@ -734,6 +1323,7 @@ impl<'a, 'b> EventCtx<'a, 'b> {
         // that needs to be propagated (with, likely, special handling for
         // scrolling).
         self.base_state.needs_inval = true;
+        self.base_state.needs_layout = true;
     }
 
     /// Get an object which can create text layouts.
@@ -758,12 +1348,75 @@ impl<'a, 'b> EventCtx<'a, 'b> {
         *self.cursor = Some(cursor.clone());
     }
 
+    /// Indicate whether an in-progress drag would be accepted.
+    ///
+    /// Call this in response to [`DragEnter`] and [`DragOver`] events; the
+    /// effect is reported to the platform, which uses it to give the user
+    /// feedback. If no widget sets an effect, the drag is rejected, and no
+    /// [`Drop`] event will be delivered.
+    ///
+    /// [`DragEnter`]: enum.Event.html#variant.DragEnter
+    /// [`DragOver`]: enum.Event.html#variant.DragOver
+    /// [`Drop`]: enum.Event.html#variant.Drop
+    pub fn set_drop_effect(&mut self, effect: DropEffect) {
+        self.drag.drop_effect = effect;
+    }
+
+    /// Begin dragging something to another widget in the same window.
+    ///
+    /// The payload can be any type; drop targets retrieve it with
+    /// [`DragEvent::payload`]. Until the mouse button is released, mouse
+    /// movement is delivered as [`DragEnter`], [`DragOver`] and [`DragLeave`]
+    /// events to the widgets under the cursor, which can accept the drag
+    /// with [`set_drop_effect`]. Releasing the button delivers [`Drop`] to
+    /// the widget under the cursor, and pressing escape cancels the drag.
+    /// Either way, this widget then receives a [`DragEnd`] event.
+    ///
+    /// This is typically called in response to a mouse move while the widget
+    /// is active, once the mouse has moved some minimum distance.
+    ///
+    /// [`DragEvent::payload`]: struct.DragEvent.html#method.payload
+    /// [`DragEnter`]: enum.Event.html#variant.DragEnter
+    /// [`DragOver`]: enum.Event.html#variant.DragOver
+    /// [`DragLeave`]: enum.Event.html#variant.DragLeave
+    /// [`Drop`]: enum.Event.html#variant.Drop
+    /// [`DragEnd`]: enum.Event.html#variant.DragEnd
+    /// [`set_drop_effect`]: #method.set_drop_effect
+    pub fn start_internal_drag(&mut self, payload: impl Any) {
+        self.base_state.is_active = false;
+        self.base_state.is_drag_source = true;
+        self.drag.internal = Some(Arc::new(payload));
+    }
+
+    /// Returns `true` if an in-app drag is in progress in this window.
+    pub fn is_dragging(&self) -> bool {
+        self.drag.internal.is_some()
+    }
+
+    /// Begin dragging something out of the window.
+    ///
+    /// This is typically called in response to a mouse move while the widget
+    /// is active. The platform takes over the mouse for the duration of the
+    /// drag, so the widget's active state is cleared. When the drag finishes,
+    /// the widget receives a [`DragEnd`] event.
+    ///
+    /// [`DragEnd`]: enum.Event.html#variant.DragEnd
+    pub fn start_drag(&mut self, contents: DragContents) {
+        self.base_state.is_active = false;
+        self.base_state.is_drag_source = true;
+        self.win_ctx.start_drag(contents);
+    }
+
     /// Set the "active" state of the widget.
     ///
+    /// While any widget is active, the window captures the mouse: the active
+    /// widget keeps receiving mouse moves and the button release even when
+    /// the cursor leaves its bounds or the window. A widget should typically
+    /// become active on mouse down and stop being active on mouse up.
+    ///
     /// See [`BaseState::is_active`](struct.BaseState.html#method.is_hot).
     pub fn set_active(&mut self, active: bool) {
         self.base_state.is_active = active;
-        // TODO: plumb mouse grab through to platform (through druid-shell)
     }
 
     /// Query the "hot" state of the widget.
@@ -791,6 +1444,14 @@ impl<'a, 'b> EventCtx<'a, 'b> {
         &self.window
     }
 
+    /// Get the raw platform handle of the window.
+    ///
+    /// This can be given to libraries such as wgpu, to render into the
+    /// window's surface.
+    pub fn raw_window_handle(&self) -> RawWindowHandle {
+        HasRawWindowHandle::raw_window_handle(self.window)
+    }
+
     /// Set the event as "handled", which stops its propagation to other
     /// widgets.
     pub fn set_handled(&mut self) {
@@ -816,6 +1477,25 @@ impl<'a, 'b> EventCtx<'a, 'b> {
         self.base_state.request_focus = true;
     }
 
+    /// Ask the enclosing [`Scroll`] containers to scroll so that `rect`, in
+    /// this widget's coordinates, is visible.
+    ///
+    /// [`Scroll`]: widget/struct.Scroll.html
+    pub fn scroll_to_view(&mut self, rect: Rect) {
+        self.base_state.request_scroll = Some(rect);
+    }
+
+    /// Enable or disable the platform's input method for the window.
+    ///
+    /// Text widgets should enable it when they gain focus, and disable it
+    /// when they lose it. While it is enabled, the focused widget receives
+    /// [`Ime`] events for text composed with the input method.
+    ///
+    /// [`Ime`]: enum.Event.html#variant.Ime
+    pub fn set_ime_enabled(&mut self, enabled: bool) {
+        self.win_ctx.set_ime_enabled(enabled);
+    }
+
     /// Request an animation frame.
     pub fn request_anim_frame(&mut self) {
         self.base_state.request_anim = true;
@@ -836,8 +1516,13 @@ impl<'a, 'b> EventCtx<'a, 'b> {
     /// submitted during the handling of an event are executed before
     /// the [`update()`] method is called.
     ///
+    /// The command is delivered to the window with the given id, such as
+    /// the [`id`] of a [`WindowDesc`], or to this window if it is `None`.
+    ///
     /// [`Command`]: struct.Command.html
     /// [`update()`]: trait.Widget.html#tymethod.update
+    /// [`id`]: struct.WindowDesc.html#structfield.id
+    /// [`WindowDesc`]: struct.WindowDesc.html
     pub fn submit_command(&mut self, command: Command, window_id: impl Into<Option<WindowId>>) {
         let window_id = window_id.into().unwrap_or(self.window_id);
         self.command_queue.push_back((window_id, command))
@@ -847,6 +1532,129 @@ impl<'a, 'b> EventCtx<'a, 'b> {
     pub fn window_id(&self) -> WindowId {
         self.window_id
     }
+
+    /// The time the current frame is expected to appear on the display.
+    ///
+    /// This is known while handling [`Event::AnimFrame`], on platforms that
+    /// synchronize painting with the display; animations can use it to show
+    /// their state as of the moment the frame is seen.
+    ///
+    /// [`Event::AnimFrame`]: enum.Event.html#variant.AnimFrame
+    pub fn presentation_time(&self) -> Option<Instant> {
+        self.win_ctx.presentation_time()
+    }
+
+    /// Print a document, after showing the system's print dialog.
+    ///
+    /// This is only supported with GTK; elsewhere, a document can still be
+    /// exported with [`PrintDocument::write_pdf`].
+    ///
+    /// [`PrintDocument::write_pdf`]: struct.PrintDocument.html#method.write_pdf
+    pub fn print(&mut self, document: PrintDocument, setup: PageSetup) {
+        self.window.print(setup, document.into_printable());
+    }
+
+    /// Get a [`CommandSink`], for submitting commands to this window from
+    /// other threads.
+    ///
+    /// [`CommandSink`]: struct.CommandSink.html
+    pub fn command_sink(&self) -> CommandSink {
+        CommandSink::new(self.ext_queue.clone(), self.window.get_idle_handle())
+    }
+
+    /// Run a future in the background, and submit its output as a command.
+    ///
+    /// The command has the given selector, and the future's output as its
+    /// argument. If `window_id` is `None`, it is sent to this window.
+    ///
+    /// If the application was given a tokio runtime with
+    /// [`AppLauncher::tokio_runtime`], the future runs there. Otherwise each
+    /// future is run on its own thread, so this is suited to blocking work
+    /// such as reading files, as well as to futures that don't depend on a
+    /// particular runtime.
+    ///
+    /// [`AppLauncher::tokio_runtime`]: struct.AppLauncher.html#method.tokio_runtime
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use druid::{EventCtx, Selector};
+    /// const FILE_LOADED: Selector = Selector::new("my-app.file-loaded");
+    ///
+    /// fn load(ctx: &mut EventCtx) {
+    ///     ctx.spawn(FILE_LOADED, None, async {
+    ///         std::fs::read_to_string("notes.txt").unwrap_or_default()
+    ///     });
+    /// }
+    /// ```
+    pub fn spawn<F>(
+        &mut self,
+        selector: Selector,
+        window_id: impl Into<Option<WindowId>>,
+        future: F,
+    ) where
+        F: Future + Send + 'static,
+        F::Output: Any + Send,
+    {
+        ext_event::spawn(self.command_sink(), selector, window_id.into(), future);
+    }
+
+    /// Start a cancellable job on a background thread.
+    ///
+    /// The job is passed a [`JobContext`], through which it can report its
+    /// progress; each report is submitted to this window as a command with
+    /// the `progress` selector and an `f64` argument. When the job returns,
+    /// its result is submitted as a command with the `done` selector.
+    ///
+    /// The job is cancelled when the returned [`Job`] is dropped, so it
+    /// should be stored by the widget that owns it.
+    ///
+    /// [`JobContext`]: struct.JobContext.html
+    /// [`Job`]: struct.Job.html
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use druid::{EventCtx, Job, Selector};
+    /// const PROGRESS: Selector = Selector::new("my-app.index-progress");
+    /// const INDEXED: Selector = Selector::new("my-app.indexed");
+    ///
+    /// fn index(ctx: &mut EventCtx, files: Vec<String>) -> Job {
+    ///     ctx.start_job(PROGRESS, INDEXED, move |job| {
+    ///         let mut words = 0;
+    ///         for (i, file) in files.iter().enumerate() {
+    ///             if job.is_cancelled() {
+    ///                 break;
+    ///             }
+    ///             let text = std::fs::read_to_string(file).unwrap_or_default();
+    ///             words += text.split_whitespace().count();
+    ///             job.report_progress((i + 1) as f64 / files.len() as f64);
+    ///         }
+    ///         words
+    ///     })
+    /// }
+    /// ```
+    pub fn start_job<F, R>(
+        &mut self,
+        progress: impl Into<Option<Selector>>,
+        done: Selector,
+        task: F,
+    ) -> Job
+    where
+        F: FnOnce(&JobContext) -> R + Send + 'static,
+        R: Any + Send,
+    {
+        job::start(self.command_sink(), progress.into(), done, task)
+    }
+
+    /// Add a task to be run on the UI thread while the window is idle.
+    ///
+    /// Unlike [`start_job`], the task runs on this thread, a piece at a
+    /// time, between events and frames. See [`IdleTask`] for details.
+    ///
+    /// [`start_job`]: #method.start_job
+    /// [`IdleTask`]: struct.IdleTask.html
+    pub fn add_idle_task(&mut self, task: IdleTask) {
+        self.idle.push(task);
+    }
 }
 
 impl<'a, 'b> LayoutCtx<'a, 'b> {
@@ -864,10 +1672,19 @@ impl<'a, 'b> LayoutCtx<'a, 'b> {
 impl<'a, 'b> UpdateCtx<'a, 'b> {
     /// Invalidate.
     ///
-    /// See [`EventCtx::invalidate`](struct.EventCtx.html#method.invalidate) for
-    /// more discussion.
+    /// As with [`EventCtx::invalidate`](struct.EventCtx.html#method.invalidate),
+    /// this also has the widget laid out again before it is next painted.
     pub fn invalidate(&mut self) {
         self.needs_inval = true;
+        self.needs_layout = true;
+    }
+
+    /// Request an animation frame.
+    ///
+    /// This lets a widget start animating in response to a change in its
+    /// data.
+    pub fn request_anim_frame(&mut self) {
+        self.request_anim = true;
     }
 
     /// Get an object which can create text layouts.
@@ -884,8 +1701,144 @@ impl<'a, 'b> UpdateCtx<'a, 'b> {
         &self.window
     }
 
+    /// Get the raw platform handle of the window.
+    ///
+    /// This can be given to libraries such as wgpu, to render into the
+    /// window's surface.
+    pub fn raw_window_handle(&self) -> RawWindowHandle {
+        HasRawWindowHandle::raw_window_handle(self.window)
+    }
+
     /// Get the window id.
     pub fn window_id(&self) -> WindowId {
         self.window_id
     }
 }
+
+#[cfg(all(test, not(any(target_os = "windows", target_arch = "wasm32"))))]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    use crate::harness::pair::{First, Second};
+    use crate::widget::{Checkbox, Column};
+
+    /// A widget that counts how many times it is laid out.
+    struct LayoutCounter(Rc<Cell<usize>>);
+
+    impl Widget<u32> for LayoutCounter {
+        fn paint(&mut self, _: &mut PaintCtx, _: &BaseState, _: &u32, _: &Env) {}
+
+        fn layout(&mut self, _: &mut LayoutCtx, bc: &BoxConstraints, _: &u32, _: &Env) -> Size {
+            self.0.set(self.0.get() + 1);
+            bc.constrain(Size::new(10.0, 10.0))
+        }
+
+        fn event(&mut self, _: &Event, _: &mut EventCtx, _: &mut u32, _: &Env) {}
+
+        fn update(&mut self, _: &mut UpdateCtx, _: Option<&u32>, _: &u32, _: &Env) {}
+    }
+
+    #[test]
+    fn layout_is_cached() {
+        let count = Rc::new(Cell::new(0));
+        let counter = count.clone();
+        let mut harness = Harness::new(
+            move || {
+                let counter = Probe::new("counter", LayoutCounter(counter.clone()));
+                let mut col = Column::new();
+                col.add_child(
+                    Probe::new("check", LensWrap::new(Checkbox::new(), First)),
+                    0.0,
+                );
+                col.add_child(LensWrap::new(counter, Second), 0.0);
+                col
+            },
+            (false, 0u32),
+        );
+        assert_eq!(count.get(), 1);
+
+        // Toggling the checkbox lays out the column again, but not the counter.
+        let check = harness.layout_rect("check").unwrap();
+        harness.click(check.center());
+        assert!(harness.data().0);
+        assert_eq!(count.get(), 1);
+
+        // New constraints do reach it.
+        harness.resize(Size::new(300.0, 300.0));
+        assert_eq!(count.get(), 2);
+    }
+
+    /// Paints two overlapping squares, with half opacity.
+    struct Overlapping;
+
+    impl Widget<u32> for Overlapping {
+        fn paint(&mut self, paint_ctx: &mut PaintCtx, _: &BaseState, _: &u32, _: &Env) {
+            paint_ctx.with_opacity(0.5, Size::new(30.0, 20.0), |paint_ctx| {
+                paint_ctx.fill(Rect::new(0.0, 0.0, 20.0, 20.0), &Color::WHITE);
+                paint_ctx.fill(Rect::new(10.0, 0.0, 30.0, 20.0), &Color::WHITE);
+            });
+        }
+
+        fn layout(&mut self, _: &mut LayoutCtx, bc: &BoxConstraints, _: &u32, _: &Env) -> Size {
+            bc.constrain(Size::new(30.0, 20.0))
+        }
+
+        fn event(&mut self, _: &Event, _: &mut EventCtx, _: &mut u32, _: &Env) {}
+
+        fn update(&mut self, _: &mut UpdateCtx, _: Option<&u32>, _: &u32, _: &Env) {}
+    }
+
+    #[test]
+    fn opacity_applies_to_group() {
+        let mut harness = Harness::with_size(|| Overlapping, 0, Size::new(40.0, 40.0));
+        let image = harness.render();
+        let background = image.pixel(35, 35);
+        let single = image.pixel(5, 10);
+        // The overlap isn't any brighter than the rest.
+        assert_ne!(single, background);
+        assert_eq!(single, image.pixel(15, 10));
+    }
+
+    /// Asks for animation frames for as long as it is shown, counting them.
+    struct Animating(Rc<Cell<usize>>);
+
+    impl Widget<u32> for Animating {
+        fn paint(&mut self, _: &mut PaintCtx, _: &BaseState, _: &u32, _: &Env) {}
+
+        fn layout(&mut self, _: &mut LayoutCtx, bc: &BoxConstraints, _: &u32, _: &Env) -> Size {
+            bc.constrain(Size::new(10.0, 10.0))
+        }
+
+        fn event(&mut self, event: &Event, ctx: &mut EventCtx, _: &mut u32, _: &Env) {
+            match event {
+                Event::MouseMoved(_) => ctx.request_anim_frame(),
+                Event::AnimFrame(_) => {
+                    self.0.set(self.0.get() + 1);
+                    ctx.request_anim_frame();
+                }
+                _ => (),
+            }
+        }
+
+        fn update(&mut self, _: &mut UpdateCtx, _: Option<&u32>, _: &u32, _: &Env) {}
+    }
+
+    #[test]
+    fn tiny_frame_rate_is_clamped() {
+        let frames = Rc::new(Cell::new(0));
+        let counter = frames.clone();
+        let mut harness = Harness::new(move || Animating(counter.clone()), 0);
+        let rate = FrameRate::Capped(1e-300);
+        harness.submit_command(Command::new(command::sys::SET_FRAME_RATE, rate));
+        harness.mouse_move(Point::new(5.0, 5.0));
+        // Frames are delayed as for a rate of one per second.
+        harness.animate(3);
+        assert_eq!(frames.get(), 1);
+        harness.advance_time(Duration::from_millis(1500));
+        harness.animate(3);
+        assert_eq!(frames.get(), 2);
+    }
+}
@@ -27,14 +27,19 @@
 //! At runtime, you resolve your [`LocalizedString`] into an actual string,
 //! passing it the current [`Env`] and [`Data`].
 //!
+//! The strings themselves are supplied to [`AppLauncher::localization`] as
+//! [`L10nResources`].
 //!
 //! [Fluent]: https://projectfluent.org
 //! [fluent-rs]: https://github.com/projectfluent/fluent-rs
 //! [`LocalizedString`]: struct.LocalizedString.html
 //! [`Env`]: struct.Env.html
 //! [`Data`]: trait.Data.html
+//! [`AppLauncher::localization`]: struct.AppLauncher.html#method.localization
+//! [`L10nResources`]: struct.L10nResources.html
 
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
 use std::{fs, io};
 
@@ -42,6 +47,7 @@ use log::{debug, error, warn};
 
 use crate::data::Data;
 use crate::env::Env;
+use crate::format::{Date, DateStyle, NumberSymbols};
 use crate::shell::get_locale;
 
 use fluent_bundle::{
@@ -51,10 +57,11 @@ use fluent_locale::{negotiate_languages, NegotiationStrategy};
 use fluent_syntax::ast::Pattern as FluentPattern;
 use unic_langid::LanguageIdentifier;
 
-// Localization looks for string files in druid/resources, but this path is hardcoded;
+// Druid's own strings are looked for in druid/resources, but this path is hardcoded;
 // it will only work if you're running an example from the druid/ directory.
-// At some point we will need to bundle strings with applications, and choose
-// the path dynamically.
+// Elsewhere, only the embedded en-US strings are available. Applications supply
+// their own strings with `L10nResources`.
+const BUILTIN_DIR: &str = "./resources/i18n";
 static FALLBACK_STRINGS: &str = include_str!("../resources/i18n/en-US/builtin.ftl");
 
 /// Provides access to the localization strings for the current locale.
@@ -62,16 +69,51 @@ static FALLBACK_STRINGS: &str = include_str!("../resources/i18n/en-US/builtin.ft
 pub struct L10nManager {
     res_mgr: ResourceManager,
     current_bundle: BundleStack,
-    resources: Vec<String>,
     current_locale: LanguageIdentifier,
 }
 
+/// The localization strings of an application.
+///
+/// Strings can be loaded from directories of `.ftl` files, or supplied
+/// directly, for instance with `include_str!`, so that they are bundled with
+/// the executable. Druid's own strings are always included.
+///
+/// When a message is missing for the current locale, it is looked up in the
+/// next best locale, ending with the fallback locale. Within one locale,
+/// directly supplied strings take precedence over directories, which are
+/// searched in the order they were added.
+///
+/// # Examples
+/// ```no_run
+/// use druid::L10nResources;
+///
+/// let resources = L10nResources::new()
+///     .with_dir("./resources/i18n", vec!["app.ftl".into()])
+///     .with_strings("en-US", "app-title = My App")
+///     .with_strings("fr-FR", "app-title = Mon appli");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct L10nResources {
+    dirs: Vec<ResourceDir>,
+    embedded: Vec<(LanguageIdentifier, String)>,
+    fallback_locale: Option<LanguageIdentifier>,
+}
+
+/// A directory of the form `base_dir/{locale}/{res_id}`.
+#[derive(Debug, Clone, PartialEq)]
+struct ResourceDir {
+    base_dir: String,
+    res_ids: Vec<String>,
+}
+
 /// Manages a collection of localization files.
+#[derive(Clone)]
 struct ResourceManager {
     resources: HashMap<String, Arc<FluentResource>>,
     locales: Vec<LanguageIdentifier>,
     default_locale: LanguageIdentifier,
-    path_scheme: String,
+    dirs: Vec<ResourceDir>,
+    embedded: Vec<(LanguageIdentifier, Arc<FluentResource>)>,
 }
 
 //NOTE: instead of a closure, at some point we can use something like a lens for this.
@@ -120,44 +162,105 @@ impl BundleStack {
     }
 }
 
-//NOTE: much of this is adapted from https://github.com/projectfluent/fluent-rs/blob/master/fluent-resmgr/src/resource_manager.rs
-impl ResourceManager {
-    /// Loads a new localization resource from disk, as needed.
-    fn get_resource(&mut self, res_id: &str, locale: &str) -> Arc<FluentResource> {
-        let path = self
-            .path_scheme
-            .replace("{locale}", locale)
-            .replace("{res_id}", res_id);
-        if let Some(res) = self.resources.get(&path) {
-            res.clone()
-        } else {
-            let string = fs::read_to_string(&path).unwrap_or_else(|_| {
-                if (res_id, locale) == ("builtin.ftl", "en-US") {
-                    FALLBACK_STRINGS.to_string()
-                } else {
-                    error!("missing resouce {}/{}", locale, res_id);
-                    String::new()
+impl L10nResources {
+    /// Create an empty set of resources, with only Druid's own strings.
+    pub fn new() -> Self {
+        L10nResources::default()
+    }
+
+    /// Load strings from a directory.
+    ///
+    /// `resources` is a list of file names that contain strings. `base_dir`
+    /// is a path to a directory that includes per-locale subdirectories.
+    ///
+    /// This directory should be of the structure `base_dir/{locale}/{resource}`,
+    /// where '{locale}' is a valid BCP47 language tag, and {resource} is a `.ftl`
+    /// included in `resources`.
+    pub fn with_dir(mut self, base_dir: impl Into<String>, resources: Vec<String>) -> Self {
+        self.dirs.push(ResourceDir {
+            base_dir: base_dir.into(),
+            res_ids: resources,
+        });
+        self
+    }
+
+    /// Add the contents of a `.ftl` file for the given locale.
+    ///
+    /// If `locale` is not a valid BCP47 language tag, this logs a warning,
+    /// and the strings are skipped.
+    pub fn with_strings(mut self, locale: &str, source: impl Into<String>) -> Self {
+        match locale.parse() {
+            Ok(locale) => self.embedded.push((locale, source.into())),
+            Err(_) => warn!("invalid locale '{}' for localization strings", locale),
+        }
+        self
+    }
+
+    /// Set the locale used when no strings match the current locale.
+    ///
+    /// The default is `en-US`.
+    pub fn fallback_locale(mut self, locale: &str) -> Self {
+        match locale.parse() {
+            Ok(locale) => self.fallback_locale = Some(locale),
+            Err(_) => warn!("invalid fallback locale '{}'", locale),
+        }
+        self
+    }
+}
+
+impl ResourceDir {
+    /// The locales which have a subdirectory in this directory.
+    fn available_locales(&self) -> Vec<LanguageIdentifier> {
+        fn read_locales(base_dir: &str) -> Result<Vec<LanguageIdentifier>, io::Error> {
+            let mut locales = vec![];
+
+            let res_dir = fs::read_dir(base_dir)?;
+            for entry in res_dir {
+                if let Ok(entry) = entry {
+                    let path = entry.path();
+                    if path.is_dir() {
+                        if let Some(name) = path.file_name() {
+                            if let Some(name) = name.to_str() {
+                                match name.parse() {
+                                    Ok(langid) => locales.push(langid),
+                                    Err(_) => warn!("ignoring non-locale directory {:?}", path),
+                                }
+                            }
+                        }
+                    }
                 }
-            });
-            let res = match FluentResource::try_new(string) {
-                Ok(res) => Arc::new(res),
-                Err((res, _err)) => Arc::new(res),
-            };
-            self.resources.insert(path, res.clone());
-            res
+            }
+            Ok(locales)
         }
+        read_locales(&self.base_dir).unwrap_or_default()
     }
+}
 
+//NOTE: much of this is adapted from https://github.com/projectfluent/fluent-rs/blob/master/fluent-resmgr/src/resource_manager.rs
+impl ResourceManager {
     /// Return the best localization bundle for the provided `LanguageIdentifier`.
-    fn get_bundle(&mut self, locale: &LanguageIdentifier, resource_ids: &[String]) -> BundleStack {
+    fn get_bundle(&mut self, locale: &LanguageIdentifier) -> BundleStack {
         let resolved_locales = self.resolve_locales(locale.clone());
         debug!("resolved: {}", PrintLocales(resolved_locales.as_slice()));
         let mut stack = Vec::new();
-        for locale in &resolved_locales {
-            let mut bundle = FluentBundle::new(&resolved_locales);
-            for res_id in resource_ids {
-                let res = self.get_resource(&res_id, &locale.to_string());
-                bundle.add_resource(res).unwrap();
+        for (i, locale) in resolved_locales.iter().enumerate() {
+            // Each bundle selects plural variants with the rules of its own
+            // locale, so a message from a fallback locale is still correct.
+            let mut bundle = FluentBundle::new(&resolved_locales[i..]);
+            add_functions(&mut bundle, locale);
+            for (res_locale, res) in &self.embedded {
+                if res_locale == locale {
+                    add_resource(&mut bundle, res.clone());
+                }
+            }
+            let locale = locale.to_string();
+            for dir in &self.dirs {
+                for res_id in &dir.res_ids {
+                    let res = load_resource(&mut self.resources, &dir.base_dir, res_id, &locale);
+                    if let Some(res) = res {
+                        add_resource(&mut bundle, res);
+                    }
+                }
             }
             stack.push(bundle);
         }
@@ -188,58 +291,140 @@ impl L10nManager {
     /// where '{locale}' is a valid BCP47 language tag, and {resource} is a `.ftl`
     /// included in `resources`.
     pub fn new(resources: Vec<String>, base_dir: &str) -> Self {
-        fn get_available_locales(base_dir: &str) -> Result<Vec<LanguageIdentifier>, io::Error> {
-            let mut locales = vec![];
+        L10nManager::from_resources(L10nResources::new().with_dir(base_dir, resources))
+    }
 
-            let res_dir = fs::read_dir(base_dir)?;
-            for entry in res_dir {
-                if let Ok(entry) = entry {
-                    let path = entry.path();
-                    if path.is_dir() {
-                        if let Some(name) = path.file_name() {
-                            if let Some(name) = name.to_str() {
-                                let langid: LanguageIdentifier =
-                                    name.parse().expect("Parsing failed.");
-                                locales.push(langid);
-                            }
-                        }
-                    }
-                }
-            }
-            Ok(locales)
+    /// Create a localization manager for the strings of an application,
+    /// resolved for the system's locale.
+    pub fn from_resources(resources: L10nResources) -> Self {
+        let L10nResources {
+            mut dirs,
+            embedded,
+            fallback_locale,
+        } = resources;
+        let builtin = ResourceDir {
+            base_dir: BUILTIN_DIR.to_string(),
+            res_ids: vec!["builtin.ftl".to_string()],
+        };
+        if !dirs.contains(&builtin) {
+            dirs.push(builtin);
         }
+        let embedded: Vec<_> = embedded
+            .into_iter()
+            .map(|(locale, source)| (locale, parse_resource(source)))
+            .collect();
 
-        let default_locale: LanguageIdentifier =
-            "en-US".parse().expect("failed to parse default locale");
+        let default_locale = fallback_locale
+            .unwrap_or_else(|| "en-US".parse().expect("failed to parse default locale"));
         let current_locale = get_locale()
             .parse()
             .unwrap_or_else(|_| default_locale.clone());
-        let locales = get_available_locales(base_dir).unwrap_or_default();
+        let mut locales: Vec<LanguageIdentifier> = Vec::new();
+        let candidates = dirs
+            .iter()
+            .flat_map(ResourceDir::available_locales)
+            .chain(embedded.iter().map(|(locale, _)| locale.clone()))
+            .chain(std::iter::once(default_locale.clone()));
+        for locale in candidates {
+            if !locales.contains(&locale) {
+                locales.push(locale);
+            }
+        }
         debug!(
             "available locales {}, current {}",
             PrintLocales(&locales),
             current_locale,
         );
-        let mut path_scheme = base_dir.to_string();
-        path_scheme.push_str("/{locale}/{res_id}");
 
         let mut res_mgr = ResourceManager {
             resources: HashMap::new(),
-            path_scheme,
             default_locale,
             locales,
+            dirs,
+            embedded,
         };
 
-        let current_bundle = res_mgr.get_bundle(&current_locale, &resources);
+        let current_bundle = res_mgr.get_bundle(&current_locale);
 
         L10nManager {
             res_mgr,
             current_bundle,
-            resources,
             current_locale,
         }
     }
 
+    /// Return a manager for the same resources, resolved for a different locale.
+    ///
+    /// Resources that were already loaded are shared with `self`.
+    pub(crate) fn with_locale(&self, locale: LanguageIdentifier) -> L10nManager {
+        let mut res_mgr = self.res_mgr.clone();
+        let current_bundle = res_mgr.get_bundle(&locale);
+        L10nManager {
+            res_mgr,
+            current_bundle,
+            current_locale: locale,
+        }
+    }
+
+    /// The locale strings are currently resolved for.
+    pub fn current_locale(&self) -> &LanguageIdentifier {
+        &self.current_locale
+    }
+
+    /// The locales which have a directory of resources.
+    ///
+    /// This is a reasonable list of choices for a language picker.
+    pub fn available_locales(&self) -> &[LanguageIdentifier] {
+        &self.res_mgr.locales
+    }
+
+    /// Format a number for the current locale, with `fraction_digits`
+    /// digits after the decimal mark.
+    ///
+    /// For instance, `1234.5` with two digits is `1,234.50` in the United
+    /// States, and `1.234,50` in Germany.
+    pub fn format_number(&self, number: f64, fraction_digits: usize) -> String {
+        NumberSymbols::for_locale(&self.current_locale.to_string()).format(number, fraction_digits)
+    }
+
+    /// Parse a number entered in the format of the current locale.
+    ///
+    /// This accepts the output of [`format_number`], with or without the
+    /// digit grouping.
+    ///
+    /// [`format_number`]: #method.format_number
+    pub fn parse_number(&self, text: &str) -> Option<f64> {
+        NumberSymbols::for_locale(&self.current_locale.to_string()).parse(text)
+    }
+
+    /// Format a date for the current locale.
+    ///
+    /// Long dates use the `date-long` and `date-month-{n}` messages, so the
+    /// month names come from the same bundles as other strings.
+    pub fn format_date(&self, date: Date, style: DateStyle) -> String {
+        let tag = self.current_locale.to_string();
+        if style == DateStyle::Short {
+            return date.format_short(&tag);
+        }
+        let month = format!("date-month-{}", date.month);
+        let no_args: Option<&FluentArgs> = None;
+        let month = match self.localize(&month, no_args) {
+            Some(month) => month,
+            None => return date.format_short(&tag),
+        };
+        let mut args = FluentArgs::new();
+        args.insert("day", date.day.into());
+        args.insert("month", month.into());
+        args.insert("year", date.year.into());
+        self.localize("date-long", &args)
+            .unwrap_or_else(|| date.format_short(&tag))
+    }
+
+    /// Whether the current locale is written right to left.
+    pub fn is_right_to_left(&self) -> bool {
+        crate::bidi::is_rtl_language(&self.current_locale.to_string())
+    }
+
     /// Fetch a localized string from the current bundle by key.
     ///
     /// In general, this should not be used directly; [`LocalizedString`]
@@ -286,7 +471,6 @@ impl L10nManager {
             Some(result)
         }
     }
-    //TODO: handle locale change
 }
 
 impl<T> LocalizedString<T> {
@@ -325,6 +509,26 @@ impl<T: Data> LocalizedString<T> {
     /// is a function that will return a value for the given key from the current
     /// environment and data.
     ///
+    /// Numeric arguments should be passed as numbers, rather than formatted
+    /// strings, so that messages can select the plural form for the current
+    /// locale and control the format with the `NUMBER` function:
+    ///
+    /// ```ftl
+    /// cart-items = { $count ->
+    ///     [one] One item in your cart
+    ///    *[other] { $count } items in your cart
+    /// }
+    /// cart-weight = { NUMBER($kilos, maximumFractionDigits: 1) } kg
+    /// ```
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use druid::LocalizedString;
+    ///
+    /// let items = LocalizedString::new("cart-items")
+    ///     .with_arg("count", |data: &Arc<Vec<String>>, _env| data.len().into());
+    /// ```
+    ///
     /// [`ArgClosure`]: type.ArgClosure.html
     pub fn with_arg(
         mut self,
@@ -364,6 +568,121 @@ impl<T: Data> LocalizedString<T> {
     }
 }
 
+/// Load a localization resource from disk, as needed.
+///
+/// Returns `None` if the directory has no strings for `locale`.
+fn load_resource(
+    cache: &mut HashMap<String, Arc<FluentResource>>,
+    base_dir: &str,
+    res_id: &str,
+    locale: &str,
+) -> Option<Arc<FluentResource>> {
+    let path = format!("{}/{}/{}", base_dir, locale, res_id);
+    if let Some(res) = cache.get(&path) {
+        return Some(res.clone());
+    }
+    let string = match fs::read_to_string(&path) {
+        Ok(string) => string,
+        Err(_) if (res_id, locale) == ("builtin.ftl", "en-US") => FALLBACK_STRINGS.to_string(),
+        Err(_) => {
+            // A locale may only be translated in some of the directories.
+            if Path::new(base_dir).join(locale).is_dir() {
+                error!("missing resouce {}/{}", locale, res_id);
+            }
+            return None;
+        }
+    };
+    let res = parse_resource(string);
+    cache.insert(path, res.clone());
+    Some(res)
+}
+
+fn parse_resource(source: String) -> Arc<FluentResource> {
+    match FluentResource::try_new(source) {
+        Ok(res) => Arc::new(res),
+        Err((res, _err)) => Arc::new(res),
+    }
+}
+
+/// Add a resource to a bundle, keeping existing messages with the same ids.
+fn add_resource(bundle: &mut FluentBundle<Arc<FluentResource>>, res: Arc<FluentResource>) {
+    if let Err(errs) = bundle.add_resource(res) {
+        for err in errs {
+            debug!("localization resource conflict {:?}", err);
+        }
+    }
+}
+
+/// Register the functions that can be called from messages in a locale.
+fn add_functions(bundle: &mut FluentBundle<Arc<FluentResource>>, locale: &LanguageIdentifier) {
+    let decimal = NumberSymbols::for_locale(&locale.to_string()).decimal();
+    let added = bundle.add_function("NUMBER", move |positional, named| {
+        number_function(positional, named, decimal)
+    });
+    if let Err(err) = added {
+        error!("failed to add NUMBER function: {:?}", err);
+    }
+}
+
+/// The `NUMBER` function, which controls how a numeric argument is shown.
+///
+/// This supports the `minimumFractionDigits` and `maximumFractionDigits`
+/// options, and writes the locale's decimal mark. The result is still a
+/// number, so it can select plural variants; in English,
+/// `NUMBER($count, minimumFractionDigits: 1)` selects `other` even when
+/// `$count` is `1`. Where the decimal mark isn't a point, a number with a
+/// fractional part only selects the default variant.
+fn number_function<'a>(
+    positional: &[FluentValue<'a>],
+    named: &FluentArgs,
+    decimal: char,
+) -> FluentValue<'a> {
+    let value = match positional.first() {
+        Some(FluentValue::Number(n)) => n,
+        Some(other) => return other.clone(),
+        None => return FluentValue::from("NUMBER()"),
+    };
+    let min_digits = number_option(named, "minimumFractionDigits");
+    let max_digits = number_option(named, "maximumFractionDigits");
+    if min_digits.is_none() && max_digits.is_none() {
+        return FluentValue::Number(value.clone());
+    }
+    let number: f64 = match value.parse() {
+        Ok(number) => number,
+        Err(_) => return FluentValue::Number(value.clone()),
+    };
+    let min_digits = min_digits.unwrap_or(0);
+    let max_digits = max_digits.unwrap_or(3).max(min_digits);
+    let formatted = format_fraction(number, min_digits, max_digits, decimal);
+    FluentValue::Number(formatted.into())
+}
+
+/// Read an integer option of a function call.
+fn number_option(named: &FluentArgs, key: &str) -> Option<usize> {
+    match named.get(key) {
+        Some(FluentValue::Number(n)) => n.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Format a number with between `min_digits` and `max_digits` digits after
+/// the decimal mark, dropping trailing zeros beyond the minimum.
+fn format_fraction(number: f64, min_digits: usize, max_digits: usize, decimal: char) -> String {
+    let mut result = format!("{:.*}", max_digits, number);
+    let mut extra = max_digits - min_digits;
+    while extra > 0 && result.ends_with('0') {
+        result.pop();
+        extra -= 1;
+    }
+    if result.ends_with('.') {
+        result.pop();
+    }
+    if decimal != '.' {
+        result = result.replace('.', &decimal.to_string());
+    }
+    result
+}
+
 impl<T> std::fmt::Debug for ArgSource<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "Arg Resolver {:p}", self.0)
@@ -403,7 +722,8 @@ mod tests {
             resources: HashMap::new(),
             locales: vec![en_us.clone(), en_ca.clone(), en_gb.clone(), fr_fr.clone()],
             default_locale: en_us.clone(),
-            path_scheme: String::new(),
+            dirs: Vec::new(),
+            embedded: Vec::new(),
         };
 
         let en_za: LanguageIdentifier = "en-GB".parse().unwrap();
@@ -429,4 +749,90 @@ mod tests {
         assert_eq!(resmgr.resolve_locales(cn_hk), vec![en_us.clone()]);
         assert_eq!(resmgr.resolve_locales(pt_pt), vec![en_us.clone()]);
     }
+
+    #[test]
+    fn switch_locale() {
+        let en_us =
+            L10nManager::from_resources(L10nResources::new()).with_locale("en-US".parse().unwrap());
+        let fr_ca = en_us.with_locale("fr-CA".parse().unwrap());
+        let args: FluentArgs = vec![("count", FluentValue::from("5"))]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            en_us.localize("hello-counter", &args),
+            Some("Current value is 5".to_string())
+        );
+        assert_eq!(
+            fr_ca.localize("hello-counter", &args),
+            Some("La valeur actuelle est 5".to_string())
+        );
+        assert_eq!(fr_ca.current_locale().to_string(), "fr-CA");
+    }
+
+    #[test]
+    fn app_strings() {
+        let resources = L10nResources::new()
+            .with_strings("en-US", "greeting = Hello\nfarewell = Goodbye")
+            .with_strings("fr-CA", "greeting = Bonjour\nhello-counter = Salut");
+        let fr_ca = L10nManager::from_resources(resources).with_locale("fr-CA".parse().unwrap());
+        let no_args: Option<&FluentArgs> = None;
+        assert_eq!(
+            fr_ca.localize("greeting", no_args),
+            Some("Bonjour".to_string())
+        );
+        // Missing messages fall back to the default locale.
+        assert_eq!(
+            fr_ca.localize("farewell", no_args),
+            Some("Goodbye".to_string())
+        );
+        // Application strings take precedence over the builtin ones.
+        assert_eq!(
+            fr_ca.localize("hello-counter", no_args),
+            Some("Salut".to_string())
+        );
+    }
+
+    #[test]
+    fn plurals_and_numbers() {
+        let resources = L10nResources::new()
+            .with_strings(
+                "en-US",
+                "files = { $n ->\n    [one] { $n } file\n   *[other] { $n } files\n}\n\
+                 size = { NUMBER($mb, minimumFractionDigits: 1) } MB",
+            )
+            .with_strings(
+                "fr-CA",
+                "items = { $n ->\n    [one] { $n } article\n   *[other] { $n } articles\n}\n\
+                 size = { NUMBER($mb, minimumFractionDigits: 1) } Mo",
+            );
+        let l10n = L10nManager::from_resources(resources);
+        let en_us = l10n.with_locale("en-US".parse().unwrap());
+        let fr_ca = l10n.with_locale("fr-CA".parse().unwrap());
+        let count = |n: u32| -> FluentArgs<'static> { vec![("n", n.into())].into_iter().collect() };
+
+        assert_eq!(en_us.localize("files", &count(1)), Some("1 file".into()));
+        assert_eq!(en_us.localize("files", &count(0)), Some("0 files".into()));
+        assert_eq!(fr_ca.localize("items", &count(0)), Some("0 article".into()));
+        // A fallback message uses the plural rules of its own locale.
+        assert_eq!(fr_ca.localize("files", &count(0)), Some("0 files".into()));
+
+        let size: FluentArgs = vec![("mb", 2.0.into())].into_iter().collect();
+        assert_eq!(en_us.localize("size", &size), Some("2.0 MB".into()));
+        assert_eq!(fr_ca.localize("size", &size), Some("2,0 Mo".into()));
+        assert_eq!(format_fraction(1.2346, 0, 3, '.'), "1.235");
+        assert_eq!(format_fraction(1.5, 2, 3, '.'), "1.50");
+        assert_eq!(format_fraction(2.0, 0, 3, '.'), "2");
+        assert_eq!(format_fraction(1.25, 0, 3, ','), "1,25");
+    }
+
+    #[test]
+    fn long_dates() {
+        let l10n = L10nManager::from_resources(L10nResources::new());
+        let en_us = l10n.with_locale("en-US".parse().unwrap());
+        let fr_ca = l10n.with_locale("fr-CA".parse().unwrap());
+        let date = Date::new(2019, 3, 1);
+        assert_eq!(en_us.format_date(date, DateStyle::Long), "March 1, 2019");
+        assert_eq!(fr_ca.format_date(date, DateStyle::Long), "1er mars 2019");
+        assert_eq!(fr_ca.format_number(1234.5, 2), "1\u{202F}234,50");
+    }
 }
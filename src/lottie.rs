@@ -0,0 +1,707 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Loading and painting Lottie animations.
+//!
+//! This supports the parts of the [Lottie format] that most exported
+//! animations use: shape, solid and null layers, with parenting; groups of
+//! rectangles, ellipses and paths, with fills, strokes and transforms; and
+//! animated properties with hold, linear and eased keyframes.
+//!
+//! Precompositions, images, text, masks, mattes, gradients and effects are
+//! skipped. Opacity is applied to each fill and stroke, rather than to
+//! whole layers and groups.
+//!
+//! [Lottie format]: https://lottiefiles.github.io/lottie-docs/
+
+use std::time::Duration;
+
+use log::warn;
+use serde_json::{Map, Value};
+
+use crate::kurbo::{Affine, BezPath, Point, Rect, RoundedRect, Shape, Size, Vec2};
+use crate::piet::{Color, RenderContext};
+use crate::{Easing, PaintCtx};
+
+/// The magic number for approximating a quarter circle with a cubic.
+const KAPPA: f64 = 0.552_284_749_8;
+
+/// A parsed animation.
+pub(crate) struct Composition {
+    frame_rate: f64,
+    in_point: f64,
+    out_point: f64,
+    size: Size,
+    layers: Vec<Layer>,
+}
+
+struct Layer {
+    index: Option<i64>,
+    parent: Option<i64>,
+    in_point: f64,
+    out_point: f64,
+    start_time: f64,
+    transform: Transform,
+    content: Content,
+}
+
+enum Content {
+    /// A layer that only exists to be a parent to others.
+    Null,
+    Solid(Color, Size),
+    Shapes(Vec<Item>),
+}
+
+struct Transform {
+    anchor: Property,
+    position: Position,
+    scale: Property,
+    rotation: Property,
+    opacity: Property,
+}
+
+enum Position {
+    Combined(Property),
+    Split(Property, Property),
+}
+
+enum Item {
+    Group(Vec<Item>),
+    Rect {
+        position: Property,
+        size: Property,
+        roundness: Property,
+    },
+    Ellipse {
+        position: Property,
+        size: Property,
+    },
+    Path {
+        closed: bool,
+        shape: Property,
+    },
+    Fill {
+        color: Property,
+        opacity: Property,
+    },
+    Stroke {
+        color: Property,
+        opacity: Property,
+        width: Property,
+    },
+    Transform(Transform),
+}
+
+/// A property that may be animated, of any number of components.
+///
+/// A path is stored with its vertices, then its in tangents, then its out
+/// tangents, so that it can be interpolated like any other value.
+struct Property {
+    keyframes: Vec<Keyframe>,
+}
+
+struct Keyframe {
+    time: f64,
+    value: Vec<f64>,
+    /// The value at the next keyframe, in older files.
+    end: Option<Vec<f64>>,
+    easing: Easing,
+    hold: bool,
+}
+
+impl Composition {
+    pub(crate) fn from_json(json: &str) -> Result<Composition, String> {
+        let root: Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        let root = root.as_object().ok_or("not a JSON object")?;
+        let number = |key: &str| root.get(key).and_then(Value::as_f64);
+        let frame_rate = number("fr")
+            .filter(|fr| *fr > 0.0)
+            .ok_or("missing frame rate")?;
+        let size = Size::new(number("w").unwrap_or(0.0), number("h").unwrap_or(0.0));
+        let layers = root
+            .get("layers")
+            .and_then(Value::as_array)
+            .map(|layers| layers.iter().filter_map(Layer::parse).collect())
+            .unwrap_or_default();
+        Ok(Composition {
+            frame_rate,
+            in_point: number("ip").unwrap_or(0.0),
+            out_point: number("op").unwrap_or(0.0),
+            size,
+            layers,
+        })
+    }
+
+    pub(crate) fn size(&self) -> Size {
+        self.size
+    }
+
+    /// The first frame, and the frame at which the animation ends.
+    pub(crate) fn frames(&self) -> (f64, f64) {
+        (self.in_point, self.out_point)
+    }
+
+    /// The number of frames in a given length of time.
+    pub(crate) fn frames_in(&self, duration: Duration) -> f64 {
+        duration.as_secs_f64() * self.frame_rate
+    }
+
+    /// Paint a frame, in the composition's coordinate space.
+    pub(crate) fn paint(&self, paint_ctx: &mut PaintCtx, frame: f64) {
+        // Layers are listed from the top down.
+        for layer in self.layers.iter().rev() {
+            if frame < layer.in_point || frame >= layer.out_point {
+                continue;
+            }
+            let time = frame - layer.start_time;
+            let (affine, opacity) = self.layer_transform(layer, frame);
+            let opacity = opacity * layer.transform.opacity(time);
+            with_transform(paint_ctx, affine, |paint_ctx| match &layer.content {
+                Content::Null => (),
+                Content::Solid(color, size) => {
+                    let rect = Rect::from_origin_size(Point::ORIGIN, *size);
+                    paint_ctx.fill(rect, &with_opacity(color, opacity));
+                }
+                Content::Shapes(items) => paint_group(paint_ctx, items, time, opacity),
+            });
+        }
+    }
+
+    /// The transform of a layer, including its parents, and the opacity
+    /// of its parents.
+    fn layer_transform(&self, layer: &Layer, frame: f64) -> (Affine, f64) {
+        let mut affine = layer.transform.affine(frame - layer.start_time);
+        let mut opacity = 1.0;
+        let mut parent = layer.parent;
+        // A bound on the depth guards against cycles.
+        for _ in 0..self.layers.len() {
+            let index = match parent {
+                Some(index) => index,
+                None => break,
+            };
+            let parent_layer = match self.layers.iter().find(|l| l.index == Some(index)) {
+                Some(layer) => layer,
+                None => break,
+            };
+            let time = frame - parent_layer.start_time;
+            affine = parent_layer.transform.affine(time) * affine;
+            // Null layers don't pass on their opacity.
+            if let Content::Null = parent_layer.content {
+            } else {
+                opacity *= parent_layer.transform.opacity(time);
+            }
+            parent = parent_layer.parent;
+        }
+        (affine, opacity)
+    }
+}
+
+/// Paint the items of a group.
+///
+/// Fills and strokes apply to the shapes above them in the group. Items
+/// are painted from the bottom up, so that those listed first are on top.
+fn paint_group(paint_ctx: &mut PaintCtx, items: &[Item], time: f64, opacity: f64) {
+    let transform = items.iter().rev().find_map(|item| match item {
+        Item::Transform(transform) => Some(transform),
+        _ => None,
+    });
+    let (affine, opacity) = match transform {
+        Some(transform) => (transform.affine(time), opacity * transform.opacity(time)),
+        None => (Affine::default(), opacity),
+    };
+    with_transform(paint_ctx, affine, |paint_ctx| {
+        for (i, item) in items.iter().enumerate().rev() {
+            match item {
+                Item::Group(items) => paint_group(paint_ctx, items, time, opacity),
+                Item::Fill { color, opacity: o } => {
+                    let color = color.color(time, opacity * o.percent(time));
+                    for path in paths(&items[..i], time) {
+                        paint_ctx.fill(path, &color);
+                    }
+                }
+                Item::Stroke {
+                    color,
+                    opacity: o,
+                    width,
+                } => {
+                    let color = color.color(time, opacity * o.percent(time));
+                    let width = width.scalar(time);
+                    for path in paths(&items[..i], time) {
+                        paint_ctx.stroke(path, &color, width);
+                    }
+                }
+                _ => (),
+            }
+        }
+    });
+}
+
+/// The paths of the shapes among some items of a group.
+fn paths(items: &[Item], time: f64) -> Vec<BezPath> {
+    items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Rect {
+                position,
+                size,
+                roundness,
+            } => {
+                let center = position.point(time);
+                let size = size.vec2(time);
+                let origin = center - size / 2.0;
+                let rect = RoundedRect::from_origin_size(origin, size, roundness.scalar(time));
+                Some(rect.into_bez_path(0.1))
+            }
+            Item::Ellipse { position, size } => {
+                Some(ellipse(position.point(time), size.vec2(time) / 2.0))
+            }
+            Item::Path { closed, shape } => Some(path(*closed, &shape.value(time))),
+            _ => None,
+        })
+        .collect()
+}
+
+fn ellipse(center: Point, radii: Vec2) -> BezPath {
+    let (rx, ry) = (radii.x, radii.y);
+    let (kx, ky) = (rx * KAPPA, ry * KAPPA);
+    let (cx, cy) = (center.x, center.y);
+    let mut path = BezPath::new();
+    path.move_to((cx, cy - ry));
+    path.curve_to((cx + kx, cy - ry), (cx + rx, cy - ky), (cx + rx, cy));
+    path.curve_to((cx + rx, cy + ky), (cx + kx, cy + ry), (cx, cy + ry));
+    path.curve_to((cx - kx, cy + ry), (cx - rx, cy + ky), (cx - rx, cy));
+    path.curve_to((cx - rx, cy - ky), (cx - kx, cy - ry), (cx, cy - ry));
+    path.close_path();
+    path
+}
+
+/// Build a path from its flattened vertices and tangents.
+fn path(closed: bool, values: &[f64]) -> BezPath {
+    let count = values.len() / 6;
+    let point = |list: usize, i: usize| {
+        let at = (list * count + i) * 2;
+        Vec2::new(values[at], values[at + 1])
+    };
+    let vertex = |i: usize| Point::ORIGIN + point(0, i);
+    let mut path = BezPath::new();
+    if count == 0 {
+        return path;
+    }
+    path.move_to(vertex(0));
+    let segments = if closed { count } else { count - 1 };
+    for i in 0..segments {
+        let next = (i + 1) % count;
+        let (from, to) = (vertex(i), vertex(next));
+        path.curve_to(from + point(2, i), to + point(1, next), to);
+    }
+    if closed {
+        path.close_path();
+    }
+    path
+}
+
+fn with_transform(paint_ctx: &mut PaintCtx, affine: Affine, f: impl FnOnce(&mut PaintCtx)) {
+    if let Err(e) = paint_ctx.save() {
+        warn!("saving render context failed: {:?}", e);
+        return;
+    }
+    paint_ctx.transform(affine);
+    f(paint_ctx);
+    if let Err(e) = paint_ctx.restore() {
+        warn!("restoring render context failed: {:?}", e);
+    }
+}
+
+fn with_opacity(color: &Color, opacity: f64) -> Color {
+    let rgba = color.as_rgba_u32();
+    let alpha = (rgba & 0xff) as f64 / 255.0;
+    color.clone().with_alpha(alpha * opacity)
+}
+
+impl Layer {
+    fn parse(value: &Value) -> Option<Layer> {
+        let layer = value.as_object()?;
+        let number = |key: &str| layer.get(key).and_then(Value::as_f64);
+        let content = match layer.get("ty").and_then(Value::as_i64) {
+            Some(1) => {
+                let color = layer
+                    .get("sc")
+                    .and_then(Value::as_str)
+                    .and_then(parse_hex_color)?;
+                let size = Size::new(number("sw")?, number("sh")?);
+                Content::Solid(color, size)
+            }
+            Some(3) => Content::Null,
+            Some(4) => Content::Shapes(parse_items(layer.get("shapes"))),
+            Some(ty) => {
+                warn!("skipping unsupported lottie layer type {}", ty);
+                return None;
+            }
+            None => return None,
+        };
+        Some(Layer {
+            index: layer.get("ind").and_then(Value::as_i64),
+            parent: layer.get("parent").and_then(Value::as_i64),
+            in_point: number("ip").unwrap_or(0.0),
+            out_point: number("op").unwrap_or(std::f64::INFINITY),
+            start_time: number("st").unwrap_or(0.0),
+            transform: Transform::parse(layer.get("ks").and_then(Value::as_object)),
+            content,
+        })
+    }
+}
+
+fn parse_items(value: Option<&Value>) -> Vec<Item> {
+    let items = match value.and_then(Value::as_array) {
+        Some(items) => items,
+        None => return Vec::new(),
+    };
+    items
+        .iter()
+        .filter_map(|item| {
+            let item = item.as_object()?;
+            let prop = |key: &str| Property::parse(item.get(key));
+            let parsed = match item.get("ty").and_then(Value::as_str)? {
+                "gr" => Item::Group(parse_items(item.get("it"))),
+                "rc" => Item::Rect {
+                    position: prop("p"),
+                    size: prop("s"),
+                    roundness: prop("r"),
+                },
+                "el" => Item::Ellipse {
+                    position: prop("p"),
+                    size: prop("s"),
+                },
+                "sh" => {
+                    let (closed, shape) = Property::parse_shape(item.get("ks"));
+                    Item::Path { closed, shape }
+                }
+                "fl" => Item::Fill {
+                    color: prop("c"),
+                    opacity: Property::parse_or(item.get("o"), 100.0),
+                },
+                "st" => Item::Stroke {
+                    color: prop("c"),
+                    opacity: Property::parse_or(item.get("o"), 100.0),
+                    width: Property::parse_or(item.get("w"), 1.0),
+                },
+                "tr" => Item::Transform(Transform::parse(Some(item))),
+                ty => {
+                    warn!("skipping unsupported lottie shape type {:?}", ty);
+                    return None;
+                }
+            };
+            Some(parsed)
+        })
+        .collect()
+}
+
+impl Transform {
+    fn parse(value: Option<&Map<String, Value>>) -> Transform {
+        let get = |key: &str| value.and_then(|map| map.get(key));
+        let position = match get("p").and_then(Value::as_object) {
+            Some(p) if p.get("s").and_then(Value::as_bool).unwrap_or(false) => {
+                Position::Split(Property::parse(p.get("x")), Property::parse(p.get("y")))
+            }
+            _ => Position::Combined(Property::parse(get("p"))),
+        };
+        Transform {
+            anchor: Property::parse(get("a")),
+            position,
+            scale: Property::parse_or(get("s"), 100.0),
+            rotation: Property::parse(get("r")),
+            opacity: Property::parse_or(get("o"), 100.0),
+        }
+    }
+
+    fn affine(&self, time: f64) -> Affine {
+        let position = match &self.position {
+            Position::Combined(p) => p.vec2(time),
+            Position::Split(x, y) => Vec2::new(x.scalar(time), y.scalar(time)),
+        };
+        let scale = self.scale.value(time);
+        let sx = scale.get(0).copied().unwrap_or(100.0) / 100.0;
+        let sy = scale.get(1).copied().unwrap_or(sx * 100.0) / 100.0;
+        Affine::translate(position)
+            * Affine::rotate(self.rotation.scalar(time).to_radians())
+            * Affine::new([sx, 0.0, 0.0, sy, 0.0, 0.0])
+            * Affine::translate(-self.anchor.vec2(time))
+    }
+
+    fn opacity(&self, time: f64) -> f64 {
+        self.opacity.percent(time)
+    }
+}
+
+impl Property {
+    fn constant(value: Vec<f64>) -> Property {
+        Property {
+            keyframes: vec![Keyframe {
+                time: 0.0,
+                value,
+                end: None,
+                easing: Easing::Linear,
+                hold: true,
+            }],
+        }
+    }
+
+    fn parse(value: Option<&Value>) -> Property {
+        Property::parse_or(value, 0.0)
+    }
+
+    /// Parse a property, which has the given value when missing.
+    fn parse_or(value: Option<&Value>, default: f64) -> Property {
+        let k = match value.and_then(Value::as_object).and_then(|p| p.get("k")) {
+            Some(k) => k,
+            None => return Property::constant(vec![default]),
+        };
+        match k.as_array() {
+            Some(list) if list.first().map(Value::is_object).unwrap_or(false) => {
+                let keyframes = list
+                    .iter()
+                    .filter_map(|k| Keyframe::parse(k, numbers))
+                    .collect::<Vec<_>>();
+                if keyframes.is_empty() {
+                    Property::constant(vec![default])
+                } else {
+                    Property { keyframes }
+                }
+            }
+            _ => Property::constant(numbers(k).unwrap_or_else(|| vec![default])),
+        }
+    }
+
+    /// Parse the property of a path, and whether the path is closed.
+    fn parse_shape(value: Option<&Value>) -> (bool, Property) {
+        let k = match value.and_then(Value::as_object).and_then(|p| p.get("k")) {
+            Some(k) => k,
+            None => return (false, Property::constant(Vec::new())),
+        };
+        if let Some(list) = k.as_array() {
+            let keyframes = list
+                .iter()
+                .filter_map(|k| Keyframe::parse(k, |v| shape(first(v)).map(|(_, s)| s)))
+                .collect::<Vec<_>>();
+            let closed = list
+                .first()
+                .and_then(|k| k.get("s"))
+                .and_then(|s| shape(first(s)))
+                .map(|(closed, _)| closed)
+                .unwrap_or(false);
+            if keyframes.is_empty() {
+                return (closed, Property::constant(Vec::new()));
+            }
+            return (closed, Property { keyframes });
+        }
+        match shape(k) {
+            Some((closed, values)) => (closed, Property::constant(values)),
+            None => (false, Property::constant(Vec::new())),
+        }
+    }
+
+    /// The value at a time, in frames.
+    fn value(&self, time: f64) -> Vec<f64> {
+        let frames = &self.keyframes;
+        let next = frames.iter().position(|k| k.time > time);
+        let (current, next) = match next {
+            Some(0) => return frames[0].value.clone(),
+            Some(i) => (&frames[i - 1], Some(&frames[i])),
+            None => (&frames[frames.len() - 1], None),
+        };
+        let end = match (current.end.as_ref(), next) {
+            _ if current.hold => return current.value.clone(),
+            (Some(end), Some(next)) => (end, next.time),
+            (None, Some(next)) => (&next.value, next.time),
+            (_, None) => return current.value.clone(),
+        };
+        let (end, end_time) = end;
+        let t = (time - current.time) / (end_time - current.time);
+        let t = current.easing.eval(t);
+        current
+            .value
+            .iter()
+            .zip(end.iter())
+            .map(|(a, b)| a + (b - a) * t)
+            .collect()
+    }
+
+    fn scalar(&self, time: f64) -> f64 {
+        self.value(time).get(0).copied().unwrap_or(0.0)
+    }
+
+    /// A value given as a percentage, as a fraction.
+    fn percent(&self, time: f64) -> f64 {
+        self.scalar(time) / 100.0
+    }
+
+    fn vec2(&self, time: f64) -> Vec2 {
+        let value = self.value(time);
+        let x = value.get(0).copied().unwrap_or(0.0);
+        Vec2::new(x, value.get(1).copied().unwrap_or(0.0))
+    }
+
+    fn point(&self, time: f64) -> Point {
+        Point::ORIGIN + self.vec2(time)
+    }
+
+    /// A color with components from 0 to 1, combined with an opacity.
+    fn color(&self, time: f64, opacity: f64) -> Color {
+        let value = self.value(time);
+        let component = |i: usize, default: f64| {
+            let c = value.get(i).copied().unwrap_or(default);
+            (c.max(0.0).min(1.0) * 255.0).round() as u8
+        };
+        let alpha = value.get(3).copied().unwrap_or(1.0) * opacity;
+        Color::rgba8(component(0, 0.0), component(1, 0.0), component(2, 0.0), 255)
+            .with_alpha(alpha.max(0.0).min(1.0))
+    }
+}
+
+impl Keyframe {
+    fn parse(value: &Value, parse_value: impl Fn(&Value) -> Option<Vec<f64>>) -> Option<Keyframe> {
+        let keyframe = value.as_object()?;
+        let time = keyframe.get("t").and_then(Value::as_f64)?;
+        // The last keyframe of older files only has a time.
+        let value = keyframe.get("s").and_then(&parse_value).unwrap_or_default();
+        let end = keyframe.get("e").and_then(&parse_value);
+        let handle = |key: &str, axis: &str| {
+            keyframe
+                .get(key)
+                .and_then(|h| h.get(axis))
+                .and_then(|v| first(v).as_f64())
+        };
+        let easing = match (
+            handle("o", "x"),
+            handle("o", "y"),
+            handle("i", "x"),
+            handle("i", "y"),
+        ) {
+            (Some(x1), Some(y1), Some(x2), Some(y2)) => Easing::cubic_bezier(x1, y1, x2, y2),
+            _ => Easing::Linear,
+        };
+        let hold = keyframe.get("h").and_then(Value::as_i64) == Some(1);
+        Some(Keyframe {
+            time,
+            value,
+            end,
+            easing,
+            hold,
+        })
+    }
+}
+
+/// The first element of an array, or the value itself.
+fn first(value: &Value) -> &Value {
+    value
+        .as_array()
+        .and_then(|list| list.first())
+        .unwrap_or(value)
+}
+
+/// A number, or a list of numbers.
+fn numbers(value: &Value) -> Option<Vec<f64>> {
+    match value {
+        Value::Number(n) => n.as_f64().map(|n| vec![n]),
+        Value::Array(list) => list.iter().map(Value::as_f64).collect(),
+        _ => None,
+    }
+}
+
+/// A path, flattened, and whether it is closed.
+fn shape(value: &Value) -> Option<(bool, Vec<f64>)> {
+    let shape = value.as_object()?;
+    let closed = shape.get("c").and_then(Value::as_bool).unwrap_or(false);
+    let mut values = Vec::new();
+    for key in &["v", "i", "o"] {
+        for point in shape.get(*key)?.as_array()? {
+            values.extend(numbers(point)?.into_iter().take(2));
+        }
+    }
+    Some((closed, values))
+}
+
+/// Parse a color in `#rrggbb` form, as used for solid layers.
+fn parse_hex_color(hex: &str) -> Option<Color> {
+    let hex = hex.trim_start_matches('#');
+    if hex.len() != 6 {
+        return None;
+    }
+    let rgb = u32::from_str_radix(hex, 16).ok()?;
+    Some(Color::rgba8(
+        (rgb >> 16) as u8,
+        (rgb >> 8) as u8,
+        rgb as u8,
+        255,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keyframes() {
+        let json = r#"{"a": 1, "k": [
+            {"t": 0, "s": [0, 10], "o": {"x": [0.333], "y": [0]}, "i": {"x": [0.667], "y": [1]}},
+            {"t": 10, "s": [100, 10], "h": 1},
+            {"t": 20, "s": [50, 50]}
+        ]}"#;
+        let property = Property::parse(Some(&serde_json::from_str(json).unwrap()));
+        assert_eq!(property.value(-5.0), vec![0.0, 10.0]);
+        // The curve is symmetric, so it is halfway at the midpoint.
+        assert!((property.scalar(5.0) - 50.0).abs() < 1e-3);
+        assert_eq!(property.value(15.0), vec![100.0, 10.0]);
+        assert_eq!(property.value(25.0), vec![50.0, 50.0]);
+    }
+
+    #[test]
+    fn composition() {
+        let json = r#"{"fr": 30, "ip": 0, "op": 60, "w": 200, "h": 100, "layers": [
+            {"ty": 4, "ind": 1, "ks": {}, "shapes": [
+                {"ty": "gr", "it": [
+                    {"ty": "el", "p": {"k": [50, 50]}, "s": {"k": [20, 20]}},
+                    {"ty": "fl", "c": {"k": [1, 0, 0, 1]}, "o": {"k": 100}},
+                    {"ty": "tr", "p": {"k": [0, 0]}}
+                ]},
+                {"ty": "gf"}
+            ]},
+            {"ty": 5}
+        ]}"#;
+        let composition = Composition::from_json(json).unwrap();
+        assert_eq!(composition.size(), Size::new(200.0, 100.0));
+        assert_eq!(composition.frames(), (0.0, 60.0));
+        assert_eq!(composition.layers.len(), 1);
+        assert_eq!(composition.frames_in(Duration::from_millis(500)), 15.0);
+    }
+
+    #[test]
+    #[cfg(not(any(target_os = "windows", target_arch = "wasm32")))]
+    fn empty_shape_keyframes() {
+        let json = r#"{"fr": 30, "ip": 0, "op": 60, "w": 100, "h": 100, "layers": [
+            {"ty": 4, "ks": {}, "shapes": [
+                {"ty": "sh", "ks": {"k": []}},
+                {"ty": "fl", "c": {"k": [1, 0, 0, 1]}, "o": {"k": 100}}
+            ]}
+        ]}"#;
+        let composition = Composition::from_json(json).unwrap();
+        assert_eq!(composition.layers.len(), 1);
+        let mut harness = crate::Harness::with_size(
+            move || crate::widget::Lottie::new(json),
+            0u32,
+            Size::new(100.0, 100.0),
+        );
+        harness.render();
+    }
+}
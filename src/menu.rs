@@ -35,6 +35,11 @@
 //! To change the menu for a window, you issue a [`SET_MENU`] command, the payload
 //! of which should be a new [`MenuDesc`]. The new menu will replace the old menu.
 //!
+//! Items that only need to be enabled or checked depending on the app data
+//! don't need a new menu: [`MenuItem::disabled_if`] and [`MenuItem::selected_if`]
+//! take predicates that are re-evaluated whenever the data changes, and the
+//! native menu is rebuilt if any of them changes its answer.
+//!
 //! ## The macOS app menu
 //!
 //! On macOS, the main menu belongs to the application, not to the window.
@@ -105,13 +110,17 @@
 //! [`Command` event]: ../enum.Event.html#variant.Command
 //! [`Selector`]: ../struct.Selector.html
 //! [`SET_MENU`]: ../struct.Selector.html#associatedconstant.SET_MENU
+//! [`MenuItem::disabled_if`]: struct.MenuItem.html#method.disabled_if
+//! [`MenuItem::selected_if`]: struct.MenuItem.html#method.selected_if
 
 use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use crate::kurbo::Point;
 use crate::shell::hotkey::{HotKey, KeyCompare, RawMods, SysMods};
 use crate::shell::menu::Menu as PlatformMenu;
-use crate::{command, Command, Data, Env, KeyCode, LocalizedString, Selector};
+use crate::{command, Command, Data, Env, KeyCode, LocalizedString, RgbaImage, Selector, WindowId};
 
 /// A platform-agnostic description of an application, window, or context
 /// menu.
@@ -120,6 +129,8 @@ pub struct MenuDesc<T> {
     item: MenuItem<T>,
     //TODO: make me an RC if we're cloning regularly?
     items: Vec<MenuEntry<T>>,
+    /// The entries listing the open windows, if this menu lists them.
+    open_windows: Option<Vec<MenuEntry<T>>>,
 }
 
 /// An item in a menu, which may be a normal item, a submenu, or a separator.
@@ -149,10 +160,19 @@ pub struct MenuItem<T> {
     //highlighted: bool,
     selected: bool,
     enabled: bool, // (or state is stored elsewhere)
+    disabled_if: Option<ItemPredicate<T>>,
+    selected_if: Option<ItemPredicate<T>>,
+    /// The enabled and selected states the platform item was built with.
+    built_state: (bool, bool),
+    icon: Option<RgbaImage>,
     /// Identifies the platform object corresponding to this item.
     platform_id: MenuItemId,
 }
 
+/// Wraps a predicate deciding part of the state of a menu item from the data.
+#[derive(Clone)]
+struct ItemPredicate<T>(Arc<dyn Fn(&T, &Env) -> bool + 'static>);
+
 #[derive(Debug, Clone)]
 pub struct ContextMenu<T> {
     pub(crate) menu: MenuDesc<T>,
@@ -178,12 +198,25 @@ impl<T> MenuItem<T> {
             tool_tip: None,
             selected: false,
             enabled: true,
+            disabled_if: None,
+            selected_if: None,
+            built_state: (true, false),
+            icon: None,
             platform_id: MenuItemId::PLACEHOLDER,
         }
     }
 
     /// A builder method that adds a hotkey for this item.
     ///
+    /// The hotkey is shown beside the item in the native menu, and
+    /// pressing it while the window is focused sends the item's command,
+    /// as long as the item is enabled. Use [`SysMods`] for shortcuts that
+    /// should use Command on macOS and Ctrl elsewhere.
+    ///
+    /// Hotkeys are only registered for window menus, not context menus.
+    ///
+    /// [`SysMods`]: ../enum.SysMods.html
+    ///
     /// # Example
     ///
     /// ```
@@ -207,11 +240,28 @@ impl<T> MenuItem<T> {
         self
     }
 
-    /// Disable this menu item if the provided predicate is true.
-    pub fn disabled_if(mut self, mut p: impl FnMut() -> bool) -> Self {
-        if p() {
-            self.enabled = false;
-        }
+    /// Disable this menu item whenever the provided predicate is true.
+    ///
+    /// The predicate is re-evaluated each time the data changes, so that,
+    /// for instance, "Save" can be disabled while the document is clean.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use druid::{command, Data, LocalizedString, MenuDesc};
+    /// # use druid::menu::MenuItem;
+    /// #[derive(Clone, Data)]
+    /// struct Document {
+    ///     dirty: bool,
+    /// }
+    ///
+    /// let save = MenuItem::new(LocalizedString::new("common-menu-file-save"), command::sys::SAVE_FILE)
+    ///     .disabled_if(|doc: &Document, _env| !doc.dirty);
+    ///
+    /// # MenuDesc::<Document>::empty().append(save);
+    /// ```
+    pub fn disabled_if(mut self, p: impl Fn(&T, &Env) -> bool + 'static) -> Self {
+        self.disabled_if = Some(ItemPredicate(Arc::new(p)));
         self
     }
 
@@ -222,13 +272,39 @@ impl<T> MenuItem<T> {
         self
     }
 
-    /// Mark this item as selected, if the provided predicate is true.
-    pub fn selected_if(mut self, mut p: impl FnMut() -> bool) -> Self {
-        if p() {
-            self.selected = true;
-        }
+    /// Mark this item as selected whenever the provided predicate is true.
+    ///
+    /// Like [`disabled_if`], the predicate is re-evaluated each time the
+    /// data changes.
+    ///
+    /// [`disabled_if`]: #method.disabled_if
+    pub fn selected_if(mut self, p: impl Fn(&T, &Env) -> bool + 'static) -> Self {
+        self.selected_if = Some(ItemPredicate(Arc::new(p)));
+        self
+    }
+
+    /// A builder method that adds an icon, shown beside the title.
+    ///
+    /// Icons are shown on macOS and Linux, at 16 points high; elsewhere they
+    /// are ignored.
+    pub fn icon(mut self, icon: RgbaImage) -> Self {
+        self.icon = Some(icon);
         self
     }
+
+    /// Whether this item is enabled and selected, for the given data.
+    fn state(&self, data: &T, env: &Env) -> (bool, bool) {
+        let test = |p: &Option<ItemPredicate<T>>| p.as_ref().map(|p| (p.0)(data, env));
+        let enabled = self.enabled && !test(&self.disabled_if).unwrap_or(false);
+        let selected = self.selected || test(&self.selected_if).unwrap_or(false);
+        (enabled, selected)
+    }
+
+    /// Evaluate this item's state for building the platform item.
+    fn build_state(&mut self, data: &T, env: &Env) -> (bool, bool) {
+        self.built_state = self.state(data, env);
+        self.built_state
+    }
 }
 
 impl<T: Data> MenuDesc<T> {
@@ -243,6 +319,7 @@ impl<T: Data> MenuDesc<T> {
         MenuDesc {
             item,
             items: Vec::new(),
+            open_windows: None,
         }
     }
 
@@ -252,7 +329,13 @@ impl<T: Data> MenuDesc<T> {
     pub fn platform_default() -> Option<MenuDesc<T>> {
         #[cfg(target_os = "macos")]
         return Some(MenuDesc::empty().append(sys::mac::application::default()));
-        #[cfg(target_os = "windows")]
+        #[cfg(any(
+            target_os = "windows",
+            target_os = "linux",
+            target_os = "android",
+            target_os = "ios",
+            target_arch = "wasm32"
+        ))]
         return None;
 
         // we want to explicitly handle all platforms; log if a platform is missing.
@@ -310,6 +393,16 @@ impl<T: Data> MenuDesc<T> {
         self
     }
 
+    /// List the application's open windows at the end of this menu.
+    ///
+    /// The window that the menu belongs to is checked, and choosing a window
+    /// brings it to the front. The list is kept up to date as windows are
+    /// opened and closed.
+    pub fn append_window_list(mut self) -> Self {
+        self.open_windows = Some(Vec::new());
+        self
+    }
+
     /// The number of items in the menu.
     pub fn len(&self) -> usize {
         self.items.len()
@@ -336,38 +429,92 @@ impl<T: Data> MenuDesc<T> {
         } else {
             PlatformMenu::new()
         };
-        for item in &mut self.items {
+        let open_windows = self.open_windows.iter_mut().flatten();
+        for item in self.items.iter_mut().chain(open_windows) {
             match item {
                 MenuEntry::Item(ref mut item) => {
                     item.title.resolve(data, env);
                     item.platform_id = MenuItemId::next();
+                    let (enabled, selected) = item.build_state(data, env);
                     menu.add_item(
                         item.platform_id.as_u32(),
                         item.title.localized_str(),
                         item.hotkey.as_ref(),
-                        item.enabled,
-                        item.selected,
+                        enabled,
+                        selected,
                     );
+                    if let Some(icon) = item.icon.as_ref() {
+                        menu.set_item_icon(item.platform_id.as_u32(), icon);
+                    }
                 }
                 MenuEntry::Separator => menu.add_separator(),
                 MenuEntry::SubMenu(ref mut submenu) => {
                     let sub = submenu.build_native_menu(data, env, false);
                     submenu.item.title.resolve(data, env);
-                    menu.add_dropdown(
-                        sub,
-                        &submenu.item.title.localized_str(),
-                        submenu.item.enabled,
-                    );
+                    let (enabled, _) = submenu.item.build_state(data, env);
+                    menu.add_dropdown(sub, &submenu.item.title.localized_str(), enabled);
                 }
             }
         }
         menu
     }
 
+    /// Returns `true` if the state of any item in this menu differs from
+    /// the state it was built with, so that the platform menu needs to be
+    /// rebuilt.
+    pub(crate) fn needs_rebuild(&self, data: &T, env: &Env) -> bool {
+        self.items.iter().any(|entry| match entry {
+            MenuEntry::Item(item) => item.state(data, env) != item.built_state,
+            MenuEntry::SubMenu(menu) => {
+                menu.item.state(data, env) != menu.item.built_state || menu.needs_rebuild(data, env)
+            }
+            MenuEntry::Separator => false,
+        })
+    }
+
+    /// Replace the lists of open windows in this menu and its submenus,
+    /// returning `true` if any of them lists windows.
+    ///
+    /// `current` is the window this menu belongs to.
+    pub(crate) fn set_open_windows(
+        &mut self,
+        windows: &[(WindowId, String)],
+        current: WindowId,
+    ) -> bool {
+        let mut lists_windows = false;
+        for entry in &mut self.items {
+            if let MenuEntry::SubMenu(menu) = entry {
+                lists_windows |= menu.set_open_windows(windows, current);
+            }
+        }
+        if let Some(entries) = self.open_windows.as_mut() {
+            entries.clear();
+            if !windows.is_empty() {
+                entries.push(MenuEntry::Separator);
+            }
+            for (id, title) in windows {
+                let title = title.clone();
+                let item = MenuItem::new(
+                    LocalizedString::new("common-menu-window-item")
+                        .with_arg("title", move |_, _| title.clone().into()),
+                    Command::new(command::sys::SHOW_WINDOW, *id),
+                );
+                entries.push(if *id == current {
+                    item.selected().into()
+                } else {
+                    item.into()
+                });
+            }
+            lists_windows = true;
+        }
+        lists_windows
+    }
+
     /// Given a command identifier from druid-shell, returns the command
     /// corresponding to that id in this menu, if one exists.
     pub(crate) fn command_for_id(&self, id: u32) -> Option<Command> {
-        for item in &self.items {
+        let open_windows = self.open_windows.iter().flatten();
+        for item in self.items.iter().chain(open_windows) {
             match item {
                 MenuEntry::Item(item) if item.platform_id.as_u32() == id => {
                     return Some(item.command.clone())
@@ -384,12 +531,108 @@ impl<T: Data> MenuDesc<T> {
     }
 }
 
+impl<T> std::fmt::Debug for ItemPredicate<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Item Predicate {:p}", self.0)
+    }
+}
+
 impl<T> ContextMenu<T> {
     pub fn new(menu: MenuDesc<T>, location: Point) -> Self {
         ContextMenu { menu, location }
     }
 }
 
+/// A list of recently opened files, most recent first, for the
+/// [`recent_files`] submenu.
+///
+/// This is meant to be part of the application's data. With the
+/// `persistence` feature it can be serialized, so it can be saved between
+/// runs with [`Persistence`].
+///
+/// [`recent_files`]: sys/fn.recent_files.html
+/// [`Persistence`]: ../struct.Persistence.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecentFiles {
+    paths: Vec<PathBuf>,
+    max: usize,
+}
+
+impl RecentFiles {
+    /// The number of files kept by default.
+    pub const DEFAULT_MAX: usize = 10;
+
+    /// Create an empty list, which keeps at most `max` files.
+    pub fn new(max: usize) -> Self {
+        RecentFiles {
+            paths: Vec::new(),
+            max,
+        }
+    }
+
+    /// Add a file to the front of the list, removing it from further back
+    /// if it was already there, and dropping the oldest file if the list is
+    /// full.
+    pub fn add(&mut self, path: impl Into<PathBuf>) {
+        let path = path.into();
+        self.paths.retain(|p| *p != path);
+        self.paths.insert(0, path);
+        self.paths.truncate(self.max);
+    }
+
+    /// Remove a file from the list, such as one that no longer exists.
+    pub fn remove(&mut self, path: &Path) {
+        self.paths.retain(|p| p != path);
+    }
+
+    /// Remove all files from the list.
+    pub fn clear(&mut self) {
+        self.paths.clear();
+    }
+
+    /// The files, most recent first.
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// Whether the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+}
+
+impl Default for RecentFiles {
+    fn default() -> Self {
+        RecentFiles::new(RecentFiles::DEFAULT_MAX)
+    }
+}
+
+impl Data for RecentFiles {
+    fn same(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+// Only the paths are saved; a restored list keeps the default maximum.
+#[cfg(feature = "persistence")]
+impl serde::Serialize for RecentFiles {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(&self.paths, serializer)
+    }
+}
+
+#[cfg(feature = "persistence")]
+impl<'de> serde::Deserialize<'de> for RecentFiles {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let paths: Vec<PathBuf> = serde::Deserialize::deserialize(deserializer)?;
+        let mut files = RecentFiles::default();
+        for path in paths.into_iter().rev() {
+            files.add(path);
+        }
+        Ok(files)
+    }
+}
+
 impl MenuItemId {
     /// The value for a menu item that has not been instantiated by
     /// the platform.
@@ -452,6 +695,40 @@ impl<T> From<MenuDesc<T>> for MenuEntry<T> {
 pub mod sys {
     use super::*;
 
+    /// An 'Open Recent' submenu, listing `files`.
+    ///
+    /// Choosing a file sends an [`OPEN_FILE`] command with its path, so it is
+    /// opened without showing a dialog. The last item, 'Clear Menu', sends
+    /// [`CLEAR_RECENT_FILES`]. When the list changes, the menu should be
+    /// rebuilt and replaced with [`SET_MENU`].
+    ///
+    /// [`OPEN_FILE`]: ../../command/sys/constant.OPEN_FILE.html
+    /// [`CLEAR_RECENT_FILES`]: ../../command/sys/constant.CLEAR_RECENT_FILES.html
+    /// [`SET_MENU`]: ../../command/sys/constant.SET_MENU.html
+    pub fn recent_files<T: Data>(files: &RecentFiles) -> MenuDesc<T> {
+        let items = files.paths().iter().map(|path| {
+            let name = path
+                .file_name()
+                .unwrap_or_else(|| path.as_os_str())
+                .to_string_lossy()
+                .into_owned();
+            let title = LocalizedString::new("common-menu-file-recent-file")
+                .with_arg("name", move |_, _| name.clone().into());
+            MenuItem::new(title, Command::new(command::sys::OPEN_FILE, path.clone()))
+        });
+        let mut clear = MenuItem::new(
+            LocalizedString::new("common-menu-file-clear-recent"),
+            command::sys::CLEAR_RECENT_FILES,
+        );
+        if files.is_empty() {
+            clear = clear.disabled();
+        }
+        MenuDesc::new(LocalizedString::new("common-menu-file-open-recent"))
+            .append_iter(|| items)
+            .append_separator()
+            .append(clear)
+    }
+
     /// Menu items that exist on all platforms.
     pub mod common {
         use super::*;
@@ -495,6 +772,77 @@ pub mod sys {
                 item.hotkey(SysMods::CmdShift, "z")
             }
         }
+
+        /// The 'Select All' menu item.
+        pub fn select_all<T: Data>() -> MenuItem<T> {
+            MenuItem::new(
+                LocalizedString::new("common-menu-select-all"),
+                command::sys::SELECT_ALL,
+            )
+            .hotkey(SysMods::Cmd, "a")
+        }
+
+        /// The 'Minimize' menu item.
+        pub fn minimize<T: Data>() -> MenuItem<T> {
+            let item = MenuItem::new(
+                LocalizedString::new("common-menu-window-minimize"),
+                command::sys::MINIMIZE_WINDOW,
+            );
+
+            #[cfg(target_os = "macos")]
+            {
+                item.hotkey(RawMods::Meta, "m")
+            }
+            #[cfg(not(target_os = "macos"))]
+            {
+                item
+            }
+        }
+
+        /// The 'Zoom' menu item, which toggles the window between its
+        /// largest and its normal size.
+        pub fn zoom<T: Data>() -> MenuItem<T> {
+            MenuItem::new(
+                LocalizedString::new("common-menu-window-zoom"),
+                command::sys::ZOOM_WINDOW,
+            )
+        }
+
+        /// The standard 'Window' menu.
+        ///
+        /// It has 'Minimize' and 'Zoom' items, followed by a list of the
+        /// application's open windows, as described in
+        /// [`MenuDesc::append_window_list`].
+        ///
+        /// [`MenuDesc::append_window_list`]: ../../struct.MenuDesc.html#method.append_window_list
+        pub fn window_menu<T: Data>() -> MenuDesc<T> {
+            MenuDesc::new(LocalizedString::new("common-menu-window-menu"))
+                .append(minimize())
+                .append(zoom())
+                .append_window_list()
+        }
+
+        /// The standard 'Edit' menu.
+        ///
+        /// Its commands are delivered to the focused widget: text boxes and
+        /// selectable labels handle the clipboard items and 'Select All'
+        /// themselves, 'Paste' arrives as an [`Event::Paste`] with the
+        /// clipboard contents, and 'Undo' and 'Redo' are handled by an
+        /// enclosing [`Undoable`] if the focused widget doesn't handle them.
+        ///
+        /// [`Event::Paste`]: ../../../enum.Event.html#variant.Paste
+        /// [`Undoable`]: ../../../widget/struct.Undoable.html
+        pub fn edit_menu<T: Data>() -> MenuDesc<T> {
+            MenuDesc::new(LocalizedString::new("common-menu-edit-menu"))
+                .append(undo())
+                .append(redo())
+                .append_separator()
+                .append(cut())
+                .append(copy())
+                .append(paste())
+                .append_separator()
+                .append(select_all())
+        }
     }
 
     /// Windows.
@@ -628,6 +976,21 @@ pub mod sys {
                 .append(file::default())
         }
 
+        /// A basic macOS menu bar, whose 'About' and 'Preferences' items send
+        /// the given commands.
+        ///
+        /// See [`application::with_commands`] for details.
+        ///
+        /// [`application::with_commands`]: application/fn.with_commands.html
+        pub fn menu_bar_with<T: Data>(
+            about: impl Into<Option<Command>>,
+            preferences: impl Into<Option<Command>>,
+        ) -> MenuDesc<T> {
+            MenuDesc::new(LocalizedString::new(""))
+                .append(application::with_commands(about, preferences))
+                .append(file::default())
+        }
+
         /// The application menu
         pub mod application {
             use super::*;
@@ -647,6 +1010,57 @@ pub mod sys {
                     .append(quit())
             }
 
+            /// An Application menu whose 'About' and 'Preferences' items send
+            /// the given commands, instead of the placeholder [`SHOW_ABOUT`]
+            /// and [`SHOW_PREFERENCES`].
+            ///
+            /// An item whose command is `None` is left out of the menu. To
+            /// open a window, pass a [`NEW_WINDOW`] command:
+            ///
+            /// ```
+            /// # use druid::widget::Label;
+            /// # use druid::{command, Command, LocalizedString, WindowDesc};
+            /// use druid::menu::{sys::mac, MenuDesc};
+            ///
+            /// let about = WindowDesc::<u32>::new(|| Label::new("Version 1.0"))
+            ///     .title(LocalizedString::new("macos-menu-about-app"));
+            /// let menu: MenuDesc<u32> = mac::application::with_commands(
+            ///     Command::new(command::sys::NEW_WINDOW, about),
+            ///     None,
+            /// );
+            /// ```
+            ///
+            /// [`SHOW_ABOUT`]: ../../../../command/sys/constant.SHOW_ABOUT.html
+            /// [`SHOW_PREFERENCES`]: ../../../../command/sys/constant.SHOW_PREFERENCES.html
+            /// [`NEW_WINDOW`]: ../../../../command/sys/constant.NEW_WINDOW.html
+            pub fn with_commands<T: Data>(
+                about: impl Into<Option<Command>>,
+                preferences: impl Into<Option<Command>>,
+            ) -> MenuDesc<T> {
+                let mut menu = MenuDesc::new(LocalizedString::new("macos-menu-application-menu"));
+                if let Some(command) = about.into() {
+                    menu = menu
+                        .append(MenuItem::new(
+                            LocalizedString::new("macos-menu-about-app"),
+                            command,
+                        ))
+                        .append_separator();
+                }
+                if let Some(command) = preferences.into() {
+                    menu = menu
+                        .append(
+                            MenuItem::new(LocalizedString::new("macos-menu-preferences"), command)
+                                .hotkey(RawMods::Meta, ","),
+                        )
+                        .append_separator();
+                }
+                menu.append(hide())
+                    .append(hide_others())
+                    .append(show_all().disabled())
+                    .append_separator()
+                    .append(quit())
+            }
+
             /// The 'About App' menu item.
             pub fn about<T: Data>() -> MenuItem<T> {
                 MenuItem::new(
@@ -803,5 +1217,79 @@ pub mod sys {
                 .hotkey(RawMods::Meta, "p")
             }
         }
+
+        /// The window menu.
+        pub mod window {
+            use super::*;
+
+            /// The default Window menu, with items for native tabs.
+            ///
+            /// Windows are shown as tabs if they have a [`tabbing_identifier`].
+            ///
+            /// [`tabbing_identifier`]: ../../../../struct.WindowDesc.html#method.tabbing_identifier
+            pub fn default<T: Data>() -> MenuDesc<T> {
+                MenuDesc::new(LocalizedString::new("common-menu-window-menu"))
+                    .append(common::minimize())
+                    .append(common::zoom())
+                    .append_separator()
+                    .append(show_previous_tab())
+                    .append(show_next_tab())
+                    .append(merge_all_windows())
+                    .append_window_list()
+            }
+
+            /// The 'Show Previous Tab' menu item.
+            pub fn show_previous_tab<T: Data>() -> MenuItem<T> {
+                MenuItem::new(
+                    LocalizedString::new("macos-menu-window-previous-tab"),
+                    command::sys::SHOW_PREVIOUS_TAB,
+                )
+            }
+
+            /// The 'Show Next Tab' menu item.
+            pub fn show_next_tab<T: Data>() -> MenuItem<T> {
+                MenuItem::new(
+                    LocalizedString::new("macos-menu-window-next-tab"),
+                    command::sys::SHOW_NEXT_TAB,
+                )
+            }
+
+            /// The 'Merge All Windows' menu item.
+            pub fn merge_all_windows<T: Data>() -> MenuItem<T> {
+                MenuItem::new(
+                    LocalizedString::new("macos-menu-window-merge-all"),
+                    command::sys::MERGE_ALL_WINDOWS,
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recent_files_order() {
+        let mut files = RecentFiles::new(3);
+        for name in &["a", "b", "c", "d"] {
+            files.add(*name);
+        }
+        files.add("c");
+        let expected: Vec<PathBuf> = vec!["c".into(), "d".into(), "b".into()];
+        assert_eq!(files.paths(), &expected[..]);
+        files.remove(Path::new("d"));
+        assert_eq!(files.paths().len(), 2);
+    }
+
+    #[test]
+    fn predicates_follow_data() {
+        let env = Env::default();
+        let menu: MenuDesc<bool> = MenuDesc::empty().append(
+            MenuItem::new(LocalizedString::new("save"), command::sys::SAVE_FILE)
+                .disabled_if(|dirty: &bool, _| !dirty),
+        );
+        assert!(!menu.needs_rebuild(&true, &env));
+        assert!(menu.needs_rebuild(&false, &env));
     }
 }
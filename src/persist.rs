@@ -0,0 +1,223 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Saving application state between runs.
+//!
+//! This is available with the `persistence` feature.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use log::warn;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::Data;
+
+/// The default time between saves while the application is running.
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The name of the file the state is saved to.
+const STATE_FILE: &str = "state.json";
+
+type Save<T> = Box<dyn Fn(&T) -> serde_json::Result<String>>;
+type Restore<T> = Box<dyn Fn(&mut T, &str) -> serde_json::Result<()>>;
+
+/// A description of which parts of the application's data are saved
+/// between runs, and where.
+///
+/// The state is restored when the application is launched, and saved when
+/// it quits or is suspended, as well as periodically as the data is
+/// updated. The file is only written when the saved value has changed, so
+/// changes to other parts of the data cost nothing. It is stored as JSON,
+/// in a per-user directory named after the application: `%APPDATA%` on
+/// Windows, `~/Library/Application Support` on macOS, and
+/// `$XDG_CONFIG_HOME` (or `~/.config`) elsewhere.
+///
+/// # Examples
+/// ```no_run
+/// # use druid::{AppLauncher, Persistence, WindowDesc};
+/// # use druid::widget::Slider;
+/// #[derive(Clone, druid::Data)]
+/// struct AppState {
+///     volume: f64,
+///     playing: bool,
+/// }
+///
+/// let persistence = Persistence::new(
+///     "my-player",
+///     |data: &AppState| data.volume,
+///     |data: &mut AppState, volume| data.volume = volume,
+/// );
+/// # let window = WindowDesc::new(|| druid::widget::Label::new("hi"));
+/// AppLauncher::with_window(window)
+///     .persistence(persistence)
+///     .launch(AppState { volume: 0.5, playing: false })
+///     .expect("launch failed");
+/// ```
+pub struct Persistence<T> {
+    path: Option<PathBuf>,
+    save: Save<T>,
+    restore: Restore<T>,
+    interval: Duration,
+    /// The saved JSON, as of the last save or restore.
+    saved: Option<String>,
+    last_save: Option<Instant>,
+}
+
+impl<T: Data> Persistence<T> {
+    /// Persist the value produced by `store`, which is passed back to
+    /// `restore` on the next launch.
+    pub fn new<S: Serialize + DeserializeOwned>(
+        app_name: &str,
+        store: impl Fn(&T) -> S + 'static,
+        restore: impl Fn(&mut T, S) + 'static,
+    ) -> Self {
+        Persistence {
+            path: state_dir(app_name).map(|dir| dir.join(STATE_FILE)),
+            save: Box::new(move |data| serde_json::to_string_pretty(&store(data))),
+            restore: Box::new(move |data, json| {
+                restore(data, serde_json::from_str(json)?);
+                Ok(())
+            }),
+            interval: DEFAULT_INTERVAL,
+            saved: None,
+            last_save: None,
+        }
+    }
+
+    /// Builder-style method to save to a specific file, instead of the
+    /// platform's default location.
+    pub fn path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Builder-style method to set the time between saves while the
+    /// application is running.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Restore the saved state, if there is any, into `data`.
+    pub(crate) fn restore(&mut self, data: &mut T) {
+        let path = match self.path.as_ref() {
+            Some(path) => path,
+            None => return,
+        };
+        let json = match fs::read_to_string(path) {
+            Ok(json) => json,
+            // Nothing has been saved yet.
+            Err(_) => return,
+        };
+        if let Err(e) = (self.restore)(data, &json) {
+            warn!("failed to restore state from '{}': {}", path.display(), e);
+        }
+        self.saved = (self.save)(data).ok();
+        self.last_save = Some(Instant::now());
+    }
+
+    /// Save the state if it has changed, and the interval has passed since
+    /// the last save.
+    pub(crate) fn save_if_due(&mut self, data: &T) {
+        let due = self
+            .last_save
+            .map(|time| time.elapsed() >= self.interval)
+            .unwrap_or(true);
+        if due {
+            self.save(data);
+        }
+    }
+
+    /// Save the state if it has changed since the last save.
+    pub(crate) fn save(&mut self, data: &T) {
+        self.last_save = Some(Instant::now());
+        let path = match self.path.as_ref() {
+            Some(path) => path,
+            None => return,
+        };
+        let json = match (self.save)(data) {
+            Ok(json) => json,
+            Err(e) => {
+                warn!("failed to save state to '{}': {}", path.display(), e);
+                return;
+            }
+        };
+        if self.saved.as_ref() == Some(&json) {
+            return;
+        }
+        match write_state(path, &json) {
+            Ok(()) => self.saved = Some(json),
+            Err(e) => warn!("failed to save state to '{}': {}", path.display(), e),
+        }
+    }
+}
+
+/// Write the state through a temporary file, so that a crash while
+/// writing doesn't lose the previous state.
+fn write_state(path: &Path, json: &str) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, json).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, path).map_err(|e| e.to_string())
+}
+
+/// The platform's directory for per-user application state.
+fn state_dir(app_name: &str) -> Option<PathBuf> {
+    let env_dir = |var| std::env::var_os(var).map(PathBuf::from);
+    let base = if cfg!(target_os = "windows") {
+        env_dir("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        env_dir("HOME").map(|home| home.join("Library/Application Support"))
+    } else {
+        env_dir("XDG_CONFIG_HOME").or_else(|| env_dir("HOME").map(|home| home.join(".config")))
+    };
+    if base.is_none() {
+        warn!("no directory for application state; it won't be saved");
+    }
+    base.map(|base| base.join(app_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn save_and_restore() {
+        let path = std::env::temp_dir().join(format!("druid-persist-{}.json", std::process::id()));
+        let persistence = || {
+            Persistence::new("test", |data: &(u32, bool)| data.0, |data, n| data.0 = n)
+                .path(path.clone())
+        };
+        let mut first = persistence();
+        first.restore(&mut (0, false));
+        first.save(&(42, true));
+
+        let mut data = (0, false);
+        persistence().restore(&mut data);
+        assert_eq!(data, (42, false));
+        fs::remove_file(&path).unwrap();
+
+        // Data outside the saved value doesn't cause a write.
+        first.save(&(42, false));
+        assert!(!path.exists());
+        first.save(&(7, false));
+        assert!(path.exists());
+        fs::remove_file(&path).unwrap();
+    }
+}
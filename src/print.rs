@@ -0,0 +1,179 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Printing widgets and custom content, and exporting them to PDF.
+
+#[cfg(not(any(target_os = "windows", target_arch = "wasm32")))]
+use std::path::Path;
+
+use crate::kurbo::{Affine, Point, Rect, Size, Vec2};
+use crate::piet::{Piet, RenderContext};
+#[cfg(not(any(target_os = "windows", target_arch = "wasm32")))]
+use crate::shell::print::PageSetup;
+use crate::shell::print::Printable;
+#[cfg(not(any(target_os = "windows", target_arch = "wasm32")))]
+use crate::shell::Error as PlatformError;
+use crate::{BoxConstraints, Data, Env, LayoutCtx, PaintCtx, Widget, WidgetPod, WindowId};
+
+/// A document that can be printed, or exported to PDF.
+///
+/// This is either a widget, which is split across as many pages as it
+/// needs, or a closure that paints each page itself. Documents are printed
+/// with [`EventCtx::print`].
+///
+/// [`EventCtx::print`]: struct.EventCtx.html#method.print
+pub struct PrintDocument {
+    inner: Box<dyn Printable>,
+}
+
+struct WidgetDocument<T: Data> {
+    root: WidgetPod<T, Box<dyn Widget<T>>>,
+    data: T,
+    env: Env,
+    window_id: WindowId,
+}
+
+struct PaintDocument {
+    pages: usize,
+    paint: Box<dyn FnMut(&mut PaintCtx, usize, Size)>,
+    window_id: WindowId,
+}
+
+impl PrintDocument {
+    /// A document showing a widget with the given data.
+    ///
+    /// The widget is laid out at the width of the page, with unbounded
+    /// height, and then cut into pieces the height of a page. Nothing is
+    /// done to avoid cutting through a line of text.
+    pub fn widget<T: Data + 'static>(
+        widget: impl Widget<T> + 'static,
+        data: T,
+        env: Env,
+    ) -> PrintDocument {
+        PrintDocument {
+            inner: Box::new(WidgetDocument {
+                root: WidgetPod::new(widget).boxed(),
+                data,
+                env,
+                window_id: WindowId::new(),
+            }),
+        }
+    }
+
+    /// A document with a given number of pages, each painted by a closure.
+    ///
+    /// The closure is given the number of the page, from zero, and the size
+    /// of the area inside the margins, in points. The origin is at the top
+    /// left corner of that area.
+    pub fn paint(pages: usize, paint: impl FnMut(&mut PaintCtx, usize, Size) + 'static) -> Self {
+        PrintDocument {
+            inner: Box::new(PaintDocument {
+                pages,
+                paint: Box::new(paint),
+                window_id: WindowId::new(),
+            }),
+        }
+    }
+
+    /// Write the document to a PDF file.
+    ///
+    /// This is only available where piet draws with cairo.
+    #[cfg(not(any(target_os = "windows", target_arch = "wasm32")))]
+    pub fn write_pdf(
+        &mut self,
+        path: impl AsRef<Path>,
+        setup: &PageSetup,
+    ) -> Result<(), PlatformError> {
+        crate::shell::pdf::write_pdf(path.as_ref(), setup, &mut *self.inner)
+    }
+
+    pub(crate) fn into_printable(self) -> Box<dyn Printable> {
+        self.inner
+    }
+}
+
+fn paint_ctx<'a, 'b>(piet: &'a mut Piet<'b>, window_id: WindowId) -> PaintCtx<'a, 'b> {
+    PaintCtx {
+        render_ctx: piet,
+        window_id,
+        ime_cursor_rect: None,
+        debug: None,
+        focus_visible: false,
+        child_rects: None,
+        transform: Affine::default(),
+        saved_transforms: Vec::new(),
+    }
+}
+
+impl<T: Data> Printable for WidgetDocument<T> {
+    fn paginate(&mut self, page_size: Size, piet: &mut Piet) -> usize {
+        let mut layout_ctx = LayoutCtx {
+            text_factory: piet.text(),
+            window_id: self.window_id,
+        };
+        let bc = BoxConstraints::new(
+            Size::new(page_size.width, 0.0),
+            Size::new(page_size.width, std::f64::INFINITY),
+        );
+        let size = self
+            .root
+            .layout(&mut layout_ctx, &bc, &self.data, &self.env);
+        self.root
+            .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+        if page_size.height <= 0.0 {
+            return 1;
+        }
+        ((size.height / page_size.height).ceil() as usize).max(1)
+    }
+
+    fn paint_page(&mut self, page: usize, page_size: Size, piet: &mut Piet) {
+        let (root, data, env) = (&mut self.root, &self.data, &self.env);
+        let mut paint_ctx = paint_ctx(piet, self.window_id);
+        let page_rect = Rect::from_origin_size(Point::ORIGIN, page_size);
+        paint_ctx.with_clip(page_rect, |paint_ctx| {
+            let offset = Vec2::new(0.0, -(page as f64) * page_size.height);
+            paint_ctx.with_transform(Affine::translate(offset), |paint_ctx| {
+                root.paint(paint_ctx, data, env);
+            });
+        });
+    }
+}
+
+impl Printable for PaintDocument {
+    fn paginate(&mut self, _page_size: Size, _piet: &mut Piet) -> usize {
+        self.pages
+    }
+
+    fn paint_page(&mut self, page: usize, page_size: Size, piet: &mut Piet) {
+        let mut paint_ctx = paint_ctx(piet, self.window_id);
+        (self.paint)(&mut paint_ctx, page, page_size);
+    }
+}
+
+#[cfg(all(test, not(any(target_os = "windows", target_arch = "wasm32"))))]
+mod tests {
+    use super::*;
+    use crate::shell::headless::render_image;
+    use crate::theme;
+    use crate::widget::SizedBox;
+
+    #[test]
+    fn paginate_widget() {
+        let widget = SizedBox::<()>::empty().height(250.0);
+        let mut doc = PrintDocument::widget(widget, (), theme::init());
+        let page_size = Size::new(100.0, 100.0);
+        let (_, pages) = render_image(1, 1, |piet| doc.inner.paginate(page_size, piet)).unwrap();
+        assert_eq!(pages, 3);
+    }
+}
@@ -0,0 +1,285 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Measuring the time spent in each pass over the widget tree.
+//!
+//! Profiling is turned on for every window by setting the `DRUID_PROFILE`
+//! environment variable, or for one window with [`TOGGLE_PROFILER`]:
+//!
+//! - with any value, the time of each frame is shown in an overlay, and
+//!   frames over budget are logged;
+//! - with `log`, every frame is logged, without the overlay;
+//! - with `verbose`, the widgets that took the longest are also shown.
+//!
+//! [`TOGGLE_PROFILER`]: ../command/sys/constant.TOGGLE_PROFILER.html
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use log::info;
+
+use crate::kurbo::{Point, Rect};
+use crate::piet::{Color, Piet, RenderContext};
+use crate::text::FontSet;
+use crate::trace;
+use crate::{theme, Env, WidgetId};
+
+/// The environment variable that turns on profiling for every window.
+pub(crate) const PROFILE_VAR: &str = "DRUID_PROFILE";
+
+/// Frames that take longer than this are logged.
+const FRAME_BUDGET: Duration = Duration::from_millis(16);
+
+/// The number of widgets listed in the verbose breakdown.
+const SLOWEST_COUNT: usize = 5;
+
+const OVERLAY_TEXT_SIZE: f64 = 11.0;
+const OVERLAY_LINE_HEIGHT: f64 = 14.0;
+
+thread_local! {
+    /// The number of verbose profilers; while there are any, every widget
+    /// is timed.
+    static VERBOSE_COUNT: Cell<usize> = Cell::new(0);
+    static WIDGET_TIMES: RefCell<WidgetTimes> = RefCell::new(WidgetTimes::default());
+}
+
+/// A pass over the widget tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Pass {
+    Event,
+    Update,
+    Layout,
+    Paint,
+}
+
+/// The time spent in each pass during a frame.
+#[derive(Debug, Clone, Copy, Default)]
+struct PassTimes {
+    event: Duration,
+    update: Duration,
+    layout: Duration,
+    paint: Duration,
+}
+
+/// The time each type of widget spent on its own, excluding its children.
+#[derive(Default)]
+struct WidgetTimes {
+    /// For each widget being measured, the time spent in its children.
+    stack: Vec<Duration>,
+    totals: HashMap<(&'static str, Pass), Duration>,
+}
+
+/// A window's profiler.
+pub(crate) struct Profiler {
+    overlay: bool,
+    log_every_frame: bool,
+    verbose: bool,
+    current: PassTimes,
+    last: PassTimes,
+    /// The widgets that took the longest in the last frame, if verbose.
+    slowest: Vec<(&'static str, Pass, Duration)>,
+    font: Option<FontSet>,
+}
+
+impl Profiler {
+    pub(crate) fn new(verbose: bool) -> Profiler {
+        if verbose {
+            VERBOSE_COUNT.with(|count| count.set(count.get() + 1));
+        }
+        Profiler {
+            overlay: true,
+            log_every_frame: false,
+            verbose,
+            current: PassTimes::default(),
+            last: PassTimes::default(),
+            slowest: Vec::new(),
+            font: None,
+        }
+    }
+
+    /// The profiler requested by the environment, if any.
+    pub(crate) fn from_env() -> Option<Profiler> {
+        let value = std::env::var(PROFILE_VAR).ok()?;
+        let mut profiler = Profiler::new(value == "verbose");
+        if value == "log" {
+            profiler.overlay = false;
+            profiler.log_every_frame = true;
+        }
+        Some(profiler)
+    }
+
+    pub(crate) fn add(&mut self, pass: Pass, elapsed: Duration) {
+        let times = &mut self.current;
+        match pass {
+            Pass::Event => times.event += elapsed,
+            Pass::Update => times.update += elapsed,
+            Pass::Layout => times.layout += elapsed,
+            Pass::Paint => times.paint += elapsed,
+        }
+    }
+
+    /// Finish a frame, after it is painted: the times since the last frame
+    /// become the ones reported.
+    pub(crate) fn end_frame(&mut self) {
+        self.last = std::mem::replace(&mut self.current, PassTimes::default());
+        if self.verbose {
+            let mut slowest = WIDGET_TIMES.with(|times| {
+                let mut times = times.borrow_mut();
+                times
+                    .totals
+                    .drain()
+                    .map(|((name, pass), elapsed)| (name, pass, elapsed))
+                    .collect::<Vec<_>>()
+            });
+            slowest.sort_by(|a, b| b.2.cmp(&a.2));
+            slowest.truncate(SLOWEST_COUNT);
+            self.slowest = slowest;
+        }
+        if self.log_every_frame || self.last.total() > FRAME_BUDGET {
+            info!("frame: {}", self.last);
+            for line in self.slowest_lines() {
+                info!("  {}", line);
+            }
+        }
+    }
+
+    /// Draw the times of the last frame over the top left of the window.
+    pub(crate) fn paint_overlay(&mut self, piet: &mut Piet, env: &Env) {
+        if !self.overlay {
+            return;
+        }
+        let mut lines = vec![self.last.to_string()];
+        lines.extend(self.slowest_lines());
+
+        let font = self.font.get_or_insert_with(|| {
+            FontSet::new(
+                piet.text(),
+                env.get(theme::FONT_NAME),
+                OVERLAY_TEXT_SIZE,
+                env,
+            )
+        });
+        let layouts = lines
+            .iter()
+            .map(|line| font.layout(piet.text(), line))
+            .collect::<Vec<_>>();
+        let width = layouts.iter().map(|l| l.width()).fold(0.0, f64::max);
+        let height = layouts.len() as f64 * OVERLAY_LINE_HEIGHT;
+        let background = Rect::from_origin_size(Point::ORIGIN, (width + 8.0, height + 6.0));
+        piet.fill(background, &Color::BLACK.with_alpha(0.7));
+        for (i, layout) in layouts.iter().enumerate() {
+            let baseline = (i + 1) as f64 * OVERLAY_LINE_HEIGHT;
+            layout.draw(piet, (4.0, baseline), &Color::WHITE);
+        }
+    }
+
+    fn slowest_lines(&self) -> Vec<String> {
+        self.slowest
+            .iter()
+            .map(|(name, pass, elapsed)| {
+                format!(
+                    "{} {:?} {:.1} ms",
+                    crate::inspector::short_name(name),
+                    pass,
+                    millis(*elapsed)
+                )
+            })
+            .collect()
+    }
+}
+
+impl Drop for Profiler {
+    fn drop(&mut self) {
+        if self.verbose {
+            VERBOSE_COUNT.with(|count| count.set(count.get() - 1));
+        }
+    }
+}
+
+impl PassTimes {
+    fn total(&self) -> Duration {
+        self.event + self.update + self.layout + self.paint
+    }
+}
+
+impl fmt::Display for PassTimes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:.1} ms (event {:.1}, update {:.1}, layout {:.1}, paint {:.1})",
+            millis(self.total()),
+            millis(self.event),
+            millis(self.update),
+            millis(self.layout),
+            millis(self.paint),
+        )
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Run one widget's part of a pass inside its tracing span, timing it if
+/// a verbose profiler is active.
+pub(crate) fn measure<R>(
+    id: WidgetId,
+    type_name: &'static str,
+    pass: Pass,
+    f: impl FnOnce() -> R,
+) -> R {
+    let span = trace::widget_span(pass, type_name, id);
+    let _entered = span.enter();
+    if VERBOSE_COUNT.with(|count| count.get()) == 0 {
+        return f();
+    }
+    WIDGET_TIMES.with(|times| times.borrow_mut().stack.push(Duration::default()));
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed();
+    WIDGET_TIMES.with(|times| {
+        let mut times = times.borrow_mut();
+        let children = times.stack.pop().unwrap_or_default();
+        if let Some(parent) = times.stack.last_mut() {
+            *parent += elapsed;
+        }
+        let own = elapsed.checked_sub(children).unwrap_or_default();
+        *times.totals.entry((type_name, pass)).or_default() += own;
+    });
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn widget_times_exclude_children() {
+        let mut profiler = Profiler::new(true);
+        measure(WidgetId::next(), "Outer", Pass::Layout, || {
+            measure(WidgetId::next(), "Inner", Pass::Layout, || {
+                std::thread::sleep(Duration::from_millis(20))
+            })
+        });
+        profiler.add(Pass::Layout, Duration::from_millis(20));
+        profiler.end_frame();
+
+        assert_eq!(profiler.slowest[0].0, "Inner");
+        let outer = profiler.slowest.iter().find(|w| w.0 == "Outer").unwrap();
+        assert!(outer.2 < Duration::from_millis(10));
+        assert_eq!(profiler.last.layout, Duration::from_millis(20));
+    }
+}
@@ -0,0 +1,505 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Recording the input delivered to an application, and replaying it.
+//!
+//! A recording is a text file with one event per line: the time since the
+//! first window was opened, in milliseconds, the window the event was delivered to, numbered
+//! by the order in which windows were opened, and the event itself.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, LineWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use log::error;
+
+use crate::kurbo::{Point, Vec2};
+use crate::{
+    KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, ScrollDevice, ScrollPhase,
+    TimerToken, WheelEvent, WindowId,
+};
+
+/// The first line of a recording, identifying the format.
+const HEADER: &str = "# druid events 1";
+
+/// An input event, as delivered to a window by the platform.
+#[derive(Debug, Clone)]
+pub enum InputEvent {
+    /// The window was resized, to a width and height in px.
+    Size(u32, u32),
+    MouseDown(MouseEvent),
+    MouseUp(MouseEvent),
+    MouseMove(MouseEvent),
+    Wheel(WheelEvent),
+    KeyDown(KeyEvent),
+    KeyUp(KeyEvent),
+    /// A menu item with the given platform id was chosen.
+    Command(u32),
+}
+
+/// An input event in a recording.
+#[derive(Debug, Clone)]
+pub struct RecordedEvent {
+    /// The time of the event, since the first window was opened.
+    pub time: Duration,
+    /// The window the event was delivered to, numbered by the order in
+    /// which windows were opened.
+    pub window: usize,
+    pub event: InputEvent,
+}
+
+/// A recording of the input delivered to an application.
+///
+/// Recordings are made with [`AppLauncher::record_events`], and can be
+/// replayed with [`AppLauncher::replay_events`], or in a test with
+/// [`Harness::replay`].
+///
+/// [`AppLauncher::record_events`]: struct.AppLauncher.html#method.record_events
+/// [`AppLauncher::replay_events`]: struct.AppLauncher.html#method.replay_events
+/// [`Harness::replay`]: struct.Harness.html#method.replay
+#[derive(Debug, Clone, Default)]
+pub struct Recording {
+    events: Vec<RecordedEvent>,
+}
+
+/// Writes events to a recording as they happen.
+pub(crate) struct Recorder {
+    start: Instant,
+    windows: Vec<WindowId>,
+    out: LineWriter<File>,
+}
+
+/// Delivers the events of a recording at their original times.
+///
+/// Each window replays its own events, with a timer for the next one that
+/// is requested when the window is painted.
+pub(crate) struct Replayer {
+    start: Instant,
+    windows: Vec<WindowId>,
+    pending: HashMap<usize, VecDeque<RecordedEvent>>,
+    timers: HashMap<WindowId, TimerToken>,
+}
+
+impl Recording {
+    /// Load a recording from a file.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Recording> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    /// The events of the recording, in order.
+    pub fn events(&self) -> &[RecordedEvent] {
+        &self.events
+    }
+}
+
+impl std::str::FromStr for Recording {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Recording> {
+        let mut lines = s.lines();
+        if lines.next() != Some(HEADER) {
+            return Err(invalid("not a druid event recording"));
+        }
+        let events = lines
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                parse_event(line).ok_or_else(|| invalid(&format!("invalid event '{}'", line)))
+            })
+            .collect::<io::Result<_>>()?;
+        Ok(Recording { events })
+    }
+}
+
+impl Recorder {
+    /// Create a recording at `path`, replacing any existing file.
+    pub(crate) fn create(path: &Path) -> io::Result<Recorder> {
+        let mut out = LineWriter::new(File::create(path)?);
+        writeln!(out, "{}", HEADER)?;
+        Ok(Recorder {
+            start: Instant::now(),
+            windows: Vec::new(),
+            out,
+        })
+    }
+
+    pub(crate) fn add_window(&mut self, window_id: WindowId) {
+        if self.windows.is_empty() {
+            self.start = Instant::now();
+        }
+        window_index(&mut self.windows, window_id);
+    }
+
+    pub(crate) fn record(&mut self, window_id: WindowId, event: &InputEvent) {
+        let event = RecordedEvent {
+            time: self.start.elapsed(),
+            window: window_index(&mut self.windows, window_id),
+            event: event.clone(),
+        };
+        // Keys we have no name for can't be replayed, so they're left out.
+        if let Some(line) = format_event(&event) {
+            if let Err(e) = writeln!(self.out, "{}", line) {
+                error!("failed to record event: {}", e);
+            }
+        }
+    }
+}
+
+impl Replayer {
+    pub(crate) fn new(recording: Recording) -> Replayer {
+        let mut pending = HashMap::new();
+        for event in recording.events {
+            pending
+                .entry(event.window)
+                .or_insert_with(VecDeque::new)
+                .push_back(event);
+        }
+        Replayer {
+            start: Instant::now(),
+            windows: Vec::new(),
+            pending,
+            timers: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn add_window(&mut self, window_id: WindowId) {
+        if self.windows.is_empty() {
+            self.start = Instant::now();
+        }
+        window_index(&mut self.windows, window_id);
+    }
+
+    /// The time at which the window's next event is due, if a timer needs
+    /// to be requested for it.
+    pub(crate) fn next_deadline(&mut self, window_id: WindowId) -> Option<Instant> {
+        if self.timers.contains_key(&window_id) {
+            return None;
+        }
+        let index = window_index(&mut self.windows, window_id);
+        let next = self.pending.get(&index)?.front()?;
+        Some(self.start + next.time)
+    }
+
+    /// Remember the timer requested for the window's next event.
+    pub(crate) fn set_timer(&mut self, window_id: WindowId, token: TimerToken) {
+        self.timers.insert(window_id, token);
+    }
+
+    /// If `token` is the window's replay timer, take the events that are
+    /// now due.
+    pub(crate) fn take_due(
+        &mut self,
+        window_id: WindowId,
+        token: TimerToken,
+    ) -> Option<Vec<InputEvent>> {
+        if self.timers.get(&window_id) != Some(&token) {
+            return None;
+        }
+        self.timers.remove(&window_id);
+        let elapsed = self.start.elapsed();
+        let index = window_index(&mut self.windows, window_id);
+        let queue = self.pending.get_mut(&index)?;
+        let mut due = Vec::new();
+        while queue.front().map(|e| e.time <= elapsed).unwrap_or(false) {
+            due.extend(queue.pop_front().map(|e| e.event));
+        }
+        Some(due)
+    }
+}
+
+/// The number of a window, by the order in which windows first appear.
+fn window_index(windows: &mut Vec<WindowId>, window_id: WindowId) -> usize {
+    match windows.iter().position(|id| *id == window_id) {
+        Some(index) => index,
+        None => {
+            windows.push(window_id);
+            windows.len() - 1
+        }
+    }
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn format_event(event: &RecordedEvent) -> Option<String> {
+    let mut line = format!("{} {} ", event.time.as_millis(), event.window);
+    let _ = match &event.event {
+        InputEvent::Size(width, height) => write!(line, "size {} {}", width, height),
+        InputEvent::MouseDown(mouse) => write!(line, "down {}", format_mouse(mouse)),
+        InputEvent::MouseUp(mouse) => write!(line, "up {}", format_mouse(mouse)),
+        InputEvent::MouseMove(mouse) => write!(line, "move {}", format_mouse(mouse)),
+        InputEvent::Wheel(wheel) => write!(
+            line,
+            "wheel {} {} {} {} {}",
+            wheel.delta.x,
+            wheel.delta.y,
+            format_mods(wheel.mods),
+            phase_name(wheel.phase),
+            device_name(wheel.device),
+        ),
+        InputEvent::KeyDown(key) => write!(line, "keydown {}", format_key(key)?),
+        InputEvent::KeyUp(key) => write!(line, "keyup {}", format_key(key)?),
+        InputEvent::Command(id) => write!(line, "command {}", id),
+    };
+    Some(line)
+}
+
+fn parse_event(line: &str) -> Option<RecordedEvent> {
+    let mut fields = line.split(' ');
+    let time = Duration::from_millis(fields.next()?.parse().ok()?);
+    let window = fields.next()?.parse().ok()?;
+    let event = match fields.next()? {
+        "size" => InputEvent::Size(fields.next()?.parse().ok()?, fields.next()?.parse().ok()?),
+        "down" => InputEvent::MouseDown(parse_mouse(&mut fields)?),
+        "up" => InputEvent::MouseUp(parse_mouse(&mut fields)?),
+        "move" => InputEvent::MouseMove(parse_mouse(&mut fields)?),
+        "wheel" => InputEvent::Wheel(WheelEvent {
+            delta: Vec2::new(fields.next()?.parse().ok()?, fields.next()?.parse().ok()?),
+            mods: parse_mods(fields.next()?)?,
+            phase: parse_phase(fields.next()?)?,
+            device: parse_device(fields.next()?)?,
+        }),
+        "keydown" => InputEvent::KeyDown(parse_key(&mut fields)?),
+        "keyup" => InputEvent::KeyUp(parse_key(&mut fields)?),
+        "command" => InputEvent::Command(fields.next()?.parse().ok()?),
+        _ => return None,
+    };
+    Some(RecordedEvent {
+        time,
+        window,
+        event,
+    })
+}
+
+fn format_mouse(mouse: &MouseEvent) -> String {
+    let button = match mouse.button {
+        MouseButton::Left => "left",
+        MouseButton::Middle => "middle",
+        MouseButton::Right => "right",
+        MouseButton::X1 => "x1",
+        MouseButton::X2 => "x2",
+    };
+    format!(
+        "{} {} {} {} {}",
+        mouse.pos.x,
+        mouse.pos.y,
+        format_mods(mouse.mods),
+        mouse.count,
+        button
+    )
+}
+
+fn parse_mouse<'a>(fields: &mut impl Iterator<Item = &'a str>) -> Option<MouseEvent> {
+    let pos = Point::new(fields.next()?.parse().ok()?, fields.next()?.parse().ok()?);
+    let mods = parse_mods(fields.next()?)?;
+    let count = fields.next()?.parse().ok()?;
+    let button = match fields.next()? {
+        "left" => MouseButton::Left,
+        "middle" => MouseButton::Middle,
+        "right" => MouseButton::Right,
+        "x1" => MouseButton::X1,
+        "x2" => MouseButton::X2,
+        _ => return None,
+    };
+    Some(MouseEvent {
+        pos,
+        mods,
+        count,
+        button,
+    })
+}
+
+/// Key text is written as hex code points joined by `+`, so that spaces,
+/// tabs and the like don't need escaping; `-` is no text.
+fn format_key(key: &KeyEvent) -> Option<String> {
+    Some(format!(
+        "{} {} {} {} {}",
+        key.key_code.code_name()?,
+        key.is_repeat,
+        format_mods(key.mods),
+        format_text(key.text()),
+        format_text(key.unmod_text()),
+    ))
+}
+
+fn parse_key<'a>(fields: &mut impl Iterator<Item = &'a str>) -> Option<KeyEvent> {
+    let key_code = KeyCode::from_code_name(fields.next()?)?;
+    let is_repeat = fields.next()?.parse().ok()?;
+    let mods = parse_mods(fields.next()?)?;
+    let text = parse_text(fields.next()?)?;
+    let unmodified_text = parse_text(fields.next()?)?;
+    Some(KeyEvent::from_parts(
+        key_code,
+        is_repeat,
+        mods,
+        &text,
+        &unmodified_text,
+    ))
+}
+
+fn format_text(text: Option<&str>) -> String {
+    match text {
+        Some(text) => text
+            .chars()
+            .map(|c| format!("{:x}", c as u32))
+            .collect::<Vec<_>>()
+            .join("+"),
+        None => "-".into(),
+    }
+}
+
+fn parse_text(field: &str) -> Option<String> {
+    if field == "-" {
+        return Some(String::new());
+    }
+    field
+        .split('+')
+        .map(|c| {
+            u32::from_str_radix(c, 16)
+                .ok()
+                .and_then(std::char::from_u32)
+        })
+        .collect()
+}
+
+fn format_mods(mods: KeyModifiers) -> String {
+    let mut s = String::new();
+    for (flag, c) in &[
+        (mods.shift, 's'),
+        (mods.alt, 'a'),
+        (mods.ctrl, 'c'),
+        (mods.meta, 'm'),
+    ] {
+        if *flag {
+            s.push(*c);
+        }
+    }
+    if s.is_empty() {
+        s.push('-');
+    }
+    s
+}
+
+fn parse_mods(field: &str) -> Option<KeyModifiers> {
+    let mut mods = KeyModifiers::default();
+    for c in field.chars() {
+        match c {
+            's' => mods.shift = true,
+            'a' => mods.alt = true,
+            'c' => mods.ctrl = true,
+            'm' => mods.meta = true,
+            '-' => (),
+            _ => return None,
+        }
+    }
+    Some(mods)
+}
+
+fn phase_name(phase: ScrollPhase) -> &'static str {
+    match phase {
+        ScrollPhase::None => "none",
+        ScrollPhase::Began => "began",
+        ScrollPhase::Changed => "changed",
+        ScrollPhase::Ended => "ended",
+        ScrollPhase::Momentum => "momentum",
+        ScrollPhase::MomentumEnded => "momentum-ended",
+    }
+}
+
+fn parse_phase(field: &str) -> Option<ScrollPhase> {
+    Some(match field {
+        "none" => ScrollPhase::None,
+        "began" => ScrollPhase::Began,
+        "changed" => ScrollPhase::Changed,
+        "ended" => ScrollPhase::Ended,
+        "momentum" => ScrollPhase::Momentum,
+        "momentum-ended" => ScrollPhase::MomentumEnded,
+        _ => return None,
+    })
+}
+
+fn device_name(device: ScrollDevice) -> &'static str {
+    match device {
+        ScrollDevice::Wheel => "wheel",
+        ScrollDevice::Trackpad => "trackpad",
+    }
+}
+
+fn parse_device(field: &str) -> Option<ScrollDevice> {
+    match field {
+        "wheel" => Some(ScrollDevice::Wheel),
+        "trackpad" => Some(ScrollDevice::Trackpad),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mods = KeyModifiers {
+            shift: true,
+            ctrl: true,
+            ..Default::default()
+        };
+        let events = vec![
+            InputEvent::Size(640, 480),
+            InputEvent::MouseDown(MouseEvent {
+                pos: Point::new(10.5, 20.0),
+                mods,
+                count: 2,
+                button: MouseButton::Right,
+            }),
+            InputEvent::Wheel(WheelEvent::wheel(Vec2::new(0.0, -3.0), mods)),
+            InputEvent::KeyDown(KeyEvent::for_test(mods, "\t", KeyCode::Tab)),
+            InputEvent::Command(7),
+        ];
+        let mut text = String::from(HEADER);
+        for (i, event) in events.iter().enumerate() {
+            let event = RecordedEvent {
+                time: Duration::from_millis(i as u64 * 100),
+                window: 0,
+                event: event.clone(),
+            };
+            text.push('\n');
+            text.push_str(&format_event(&event).unwrap());
+        }
+
+        let recording: Recording = text.parse().unwrap();
+        let events = recording.events();
+        assert_eq!(events.len(), 5);
+        assert_eq!(events[4].time, Duration::from_millis(400));
+        match &events[1].event {
+            InputEvent::MouseDown(mouse) => {
+                assert_eq!(mouse.pos, Point::new(10.5, 20.0));
+                assert_eq!(mouse.count, 2);
+                assert!(mouse.mods.shift && mouse.mods.ctrl && !mouse.mods.alt);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        match &events[3].event {
+            InputEvent::KeyDown(key) => {
+                assert_eq!(key.key_code, KeyCode::Tab);
+                assert_eq!(key.text(), Some("\t"));
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        assert!("nonsense".parse::<Recording>().is_err());
+    }
+}
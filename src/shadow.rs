@@ -0,0 +1,170 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Blurred shadows of rounded rectangles, computed on the CPU.
+//!
+//! The blur is a gaussian, integrated in closed form along one axis and
+//! sampled along the other; see
+//! <https://madebyevan.com/shaders/fast-rounded-rectangle-shadows/>.
+
+use crate::kurbo::{Point, Rect, Size};
+use crate::piet::Color;
+use crate::RgbaImage;
+
+/// How many standard deviations of the blur the shadow extends beyond its
+/// rectangle.
+const EXTENT: f64 = 3.0;
+
+/// The number of samples taken across the blur, per pixel.
+const SAMPLES: usize = 4;
+
+/// The blur's standard deviation, for a blur radius.
+///
+/// As in CSS, the blur radius is twice the standard deviation.
+pub(crate) fn sigma(blur_radius: f64) -> f64 {
+    (blur_radius / 2.0).max(0.0)
+}
+
+/// The area covered by the shadow of `rect`.
+pub(crate) fn shadow_bounds(rect: Rect, blur_radius: f64) -> Rect {
+    let d = sigma(blur_radius) * EXTENT;
+    Rect::new(rect.x0 - d, rect.y0 - d, rect.x1 + d, rect.y1 + d)
+}
+
+/// Make an image of the shadow of a rectangle of the given size and corner
+/// radius, at `scale` pixels to the unit.
+///
+/// The image covers the rectangle's [`shadow_bounds`].
+///
+/// [`shadow_bounds`]: fn.shadow_bounds.html
+pub(crate) fn shadow_image(
+    size: Size,
+    corner_radius: f64,
+    blur_radius: f64,
+    color: &Color,
+    scale: f64,
+) -> RgbaImage {
+    let rect = Rect::from_origin_size(Point::ORIGIN, size);
+    let bounds = shadow_bounds(rect, blur_radius);
+    let width = (bounds.width() * scale).ceil().max(1.0) as u32;
+    let height = (bounds.height() * scale).ceil().max(1.0) as u32;
+    let rgba = color.as_rgba_u32();
+    let alpha = (rgba & 0xff) as f64;
+    let half = Size::new(size.width / 2.0, size.height / 2.0);
+    let corner = corner_radius.max(0.0).min(half.width).min(half.height);
+    let sigma = sigma(blur_radius);
+
+    let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+    for y in 0..height {
+        for x in 0..width {
+            // Pixel centers, relative to the center of the rectangle.
+            let px = bounds.x0 + (x as f64 + 0.5) / scale - half.width;
+            let py = bounds.y0 + (y as f64 + 0.5) / scale - half.height;
+            let coverage = if sigma > 0.0 {
+                rounded_box_shadow(px, py, half, corner, sigma)
+            } else {
+                rounded_box(px, py, half, corner)
+            };
+            let a = (coverage.max(0.0).min(1.0) * alpha).round() as u8;
+            pixels.extend_from_slice(&[
+                (rgba >> 24) as u8,
+                (rgba >> 16) as u8,
+                (rgba >> 8) as u8,
+                a,
+            ]);
+        }
+    }
+    RgbaImage {
+        width: width as usize,
+        height: height as usize,
+        pixels,
+    }
+}
+
+/// The coverage of an unblurred rounded rectangle, centered on the origin.
+fn rounded_box(x: f64, y: f64, half: Size, corner: f64) -> f64 {
+    let dx = (x.abs() - (half.width - corner)).max(0.0);
+    let dy = (y.abs() - (half.height - corner)).max(0.0);
+    if x.abs() > half.width || y.abs() > half.height || dx.hypot(dy) > corner {
+        0.0
+    } else {
+        1.0
+    }
+}
+
+/// The blurred coverage of a rounded rectangle, centered on the origin.
+fn rounded_box_shadow(x: f64, y: f64, half: Size, corner: f64, sigma: f64) -> f64 {
+    // The blur is only significant within a few deviations of the point.
+    let low = y - half.height;
+    let high = y + half.height;
+    let start = (-EXTENT * sigma).max(low).min(high);
+    let end = (EXTENT * sigma).max(low).min(high);
+    let step = (end - start) / SAMPLES as f64;
+    let mut sample = start + step * 0.5;
+    let mut value = 0.0;
+    for _ in 0..SAMPLES {
+        value += box_shadow_x(x, y - sample, half, corner, sigma) * gaussian(sample, sigma) * step;
+        sample += step;
+    }
+    value
+}
+
+/// The coverage along a row of the rectangle, blurred horizontally.
+fn box_shadow_x(x: f64, y: f64, half: Size, corner: f64, sigma: f64) -> f64 {
+    let delta = (half.height - corner - y.abs()).min(0.0);
+    let curved = half.width - corner + (corner * corner - delta * delta).max(0.0).sqrt();
+    let k = std::f64::consts::FRAC_1_SQRT_2 / sigma;
+    0.5 * (erf((x + curved) * k) - erf((x - curved) * k))
+}
+
+fn gaussian(x: f64, sigma: f64) -> f64 {
+    let norm = (2.0 * std::f64::consts::PI).sqrt() * sigma;
+    (-(x * x) / (2.0 * sigma * sigma)).exp() / norm
+}
+
+/// An approximation of the error function, from Abramowitz and Stegun.
+fn erf(x: f64) -> f64 {
+    let a = x.abs();
+    let t = 1.0 + (0.278_393 + (0.230_389 + 0.078_108 * (a * a)) * a) * a;
+    let t = t * t;
+    (1.0 - 1.0 / (t * t)).copysign(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coverage() {
+        let half = Size::new(20.0, 10.0);
+        // Deep inside, a blurred box is solid; far outside, it is clear.
+        assert!((rounded_box_shadow(0.0, 0.0, half, 4.0, 2.0) - 1.0).abs() < 0.01);
+        assert!(rounded_box_shadow(40.0, 0.0, half, 4.0, 2.0) < 0.001);
+        // On an edge, half is covered.
+        assert!((rounded_box_shadow(20.0, 0.0, half, 0.0, 2.0) - 0.5).abs() < 0.01);
+        // Rounded corners cover less than square ones.
+        let square = rounded_box_shadow(19.0, 9.0, half, 0.0, 2.0);
+        assert!(rounded_box_shadow(19.0, 9.0, half, 6.0, 2.0) < square);
+    }
+
+    #[test]
+    fn image() {
+        let color = Color::rgba8(0, 0, 0, 0x80);
+        let image = shadow_image(Size::new(40.0, 20.0), 0.0, 4.0, &color, 2.0);
+        // Three deviations of blur on each side, at two pixels to the unit.
+        assert_eq!((image.width, image.height), (104, 64));
+        assert_eq!(image.pixel(52, 32), [0, 0, 0, 0x80]);
+        assert_eq!(image.pixel(0, 0)[3], 0);
+    }
+}
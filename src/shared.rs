@@ -0,0 +1,173 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! State shared with code outside of druid.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::{CommandSink, Selector};
+
+/// The command submitted when a [`SharedState`] is changed. Its argument is
+/// the `usize` id of the state.
+///
+/// [`SharedState`]: struct.SharedState.html
+pub(crate) const SHARED_STATE_CHANGED: Selector =
+    Selector::new("druid-builtin.shared-state-changed");
+
+/// State owned by something other than the druid application, such as a
+/// simulation or an audio thread, but displayed and edited in the UI.
+///
+/// This is a cloneable handle to a `Mutex` holding the state. Handles can
+/// be moved to other threads, which lock the state to modify it and then
+/// call [`notify`] so that the UI picks up the change. The [`Synced`]
+/// widget copies between the state and the application's data, and the
+/// same state can be shown by `Synced` widgets in several windows.
+///
+/// [`notify`]: #method.notify
+/// [`Synced`]: widget/struct.Synced.html
+pub struct SharedState<S> {
+    inner: Arc<Shared<S>>,
+}
+
+struct Shared<S> {
+    state: Mutex<S>,
+    /// The sinks of the windows showing the state, each with the key it
+    /// was added under.
+    sinks: Mutex<Vec<(usize, CommandSink)>>,
+    next_key: AtomicUsize,
+    /// Set when a change notification is waiting to be delivered.
+    pending: AtomicBool,
+}
+
+impl<S> SharedState<S> {
+    /// Create a new shared state.
+    pub fn new(state: S) -> Self {
+        SharedState {
+            inner: Arc::new(Shared {
+                state: Mutex::new(state),
+                sinks: Mutex::new(Vec::new()),
+                next_key: AtomicUsize::new(0),
+                pending: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// Lock the state.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a thread panicked while holding the lock.
+    pub fn lock(&self) -> MutexGuard<S> {
+        self.inner.state.lock().unwrap()
+    }
+
+    /// Tell the UI that the state has changed.
+    ///
+    /// This can be called from any thread, and as often as is convenient:
+    /// notifications made before the UI has caught up are coalesced.
+    pub fn notify(&self) {
+        if self.inner.pending.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let sinks = self.inner.sinks.lock().unwrap();
+        for (i, (_, sink)) in sinks.iter().enumerate() {
+            // Several widgets in one window share a notification.
+            if sinks[..i].iter().all(|(_, other)| !other.same_window(sink)) {
+                sink.submit_command(SHARED_STATE_CHANGED, self.id(), None);
+            }
+        }
+    }
+
+    /// An id identifying this state in change notifications.
+    pub(crate) fn id(&self) -> usize {
+        &*self.inner as *const Shared<S> as usize
+    }
+
+    /// Also deliver change notifications through `sink`, until the returned
+    /// key is passed to [`remove_sink`].
+    ///
+    /// [`remove_sink`]: #method.remove_sink
+    pub(crate) fn add_sink(&self, sink: CommandSink) -> usize {
+        let key = self.inner.next_key.fetch_add(1, Ordering::SeqCst);
+        self.inner.sinks.lock().unwrap().push((key, sink));
+        key
+    }
+
+    /// Stop delivering change notifications through a sink.
+    pub(crate) fn remove_sink(&self, key: usize) {
+        self.inner.sinks.lock().unwrap().retain(|(k, _)| *k != key);
+    }
+
+    /// Mark pending notifications as handled, before reading the state.
+    pub(crate) fn clear_pending(&self) {
+        self.inner.pending.store(false, Ordering::SeqCst);
+    }
+}
+
+impl<S> Clone for SharedState<S> {
+    fn clone(&self) -> Self {
+        SharedState {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ext_event::ExtQueue;
+
+    #[test]
+    fn notifications_are_coalesced() {
+        let queue = ExtQueue::default();
+        let state = SharedState::new(0u32);
+        state.add_sink(CommandSink::new(queue.clone(), None));
+        let remote = state.clone();
+        std::thread::spawn(move || {
+            for _ in 0..10 {
+                *remote.lock() += 1;
+                remote.notify();
+            }
+        })
+        .join()
+        .unwrap();
+        assert_eq!(*state.lock(), 10);
+        assert_eq!(queue.lock().unwrap().len(), 1);
+
+        state.clear_pending();
+        state.notify();
+        let cmd = queue.lock().unwrap().pop_back().unwrap().into_command();
+        assert_eq!(cmd.get_object::<usize>(), Some(&state.id()));
+    }
+
+    #[test]
+    fn every_window_is_notified() {
+        let first = ExtQueue::default();
+        let second = ExtQueue::default();
+        let state = SharedState::new(0u32);
+        state.add_sink(CommandSink::new(first.clone(), None));
+        state.add_sink(CommandSink::new(first.clone(), None));
+        let key = state.add_sink(CommandSink::new(second.clone(), None));
+        state.notify();
+        assert_eq!(first.lock().unwrap().len(), 1);
+        assert_eq!(second.lock().unwrap().len(), 1);
+
+        state.remove_sink(key);
+        state.clear_pending();
+        state.notify();
+        assert_eq!(first.lock().unwrap().len(), 2);
+        assert_eq!(second.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,141 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Comparing rendered images against stored golden files.
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::RgbaImage;
+
+/// The environment variable that, when set, makes snapshot checks
+/// overwrite their golden files instead of comparing against them.
+pub const UPDATE_SNAPSHOTS_VAR: &str = "DRUID_UPDATE_SNAPSHOTS";
+
+/// The default difference allowed in each color channel, which absorbs
+/// small changes in antialiasing between machines.
+pub const DEFAULT_TOLERANCE: u8 = 4;
+
+/// The reason a snapshot didn't match its golden file.
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// The golden file has a different size than the image.
+    SizeMismatch {
+        expected: (usize, usize),
+        actual: (usize, usize),
+    },
+    /// Some pixels differ by more than the tolerance.
+    PixelMismatch { differing: usize },
+    /// The golden file could not be read or written.
+    Io(String),
+}
+
+/// Compare an image against the golden file at `path`.
+///
+/// If the file doesn't exist, or the [`DRUID_UPDATE_SNAPSHOTS`] environment
+/// variable is set, the image is written there instead, and the check
+/// passes. On a mismatch, the image is written next to the golden file
+/// with an `.actual.pam` extension, for inspection.
+///
+/// Golden files are stored in the [PAM] format.
+///
+/// [`DRUID_UPDATE_SNAPSHOTS`]: constant.UPDATE_SNAPSHOTS_VAR.html
+/// [PAM]: http://netpbm.sourceforge.net/doc/pam.html
+pub fn check_snapshot(image: &RgbaImage, path: &Path, tolerance: u8) -> Result<(), SnapshotError> {
+    let update = std::env::var_os(UPDATE_SNAPSHOTS_VAR).is_some();
+    if update || !path.exists() {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(SnapshotError::io)?;
+        }
+        return fs::write(path, image.to_pam()).map_err(SnapshotError::io);
+    }
+
+    let data = fs::read(path).map_err(SnapshotError::io)?;
+    let golden = RgbaImage::from_pam(&data)
+        .ok_or_else(|| SnapshotError::Io(format!("'{}' is not a PAM image", path.display())))?;
+    let result = match image.count_differing(&golden, tolerance) {
+        None => Err(SnapshotError::SizeMismatch {
+            expected: (golden.width, golden.height),
+            actual: (image.width, image.height),
+        }),
+        Some(0) => Ok(()),
+        Some(differing) => Err(SnapshotError::PixelMismatch { differing }),
+    };
+    if result.is_err() {
+        // Failing to save the actual image shouldn't hide the mismatch.
+        let _ = fs::write(actual_path(path), image.to_pam());
+    }
+    result
+}
+
+/// The directory snapshots are stored in by default: `tests/snapshots` in
+/// the package being tested.
+pub(crate) fn default_dir() -> PathBuf {
+    let root = std::env::var_os("CARGO_MANIFEST_DIR").unwrap_or_else(|| ".".into());
+    Path::new(&root).join("tests").join("snapshots")
+}
+
+fn actual_path(path: &Path) -> PathBuf {
+    path.with_extension("actual.pam")
+}
+
+impl SnapshotError {
+    fn io(e: std::io::Error) -> SnapshotError {
+        SnapshotError::Io(e.to_string())
+    }
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotError::SizeMismatch { expected, actual } => write!(
+                f,
+                "expected a {}x{} image, got {}x{}",
+                expected.0, expected.1, actual.0, actual.1
+            ),
+            SnapshotError::PixelMismatch { differing } => {
+                write!(f, "{} pixels differ from the golden file", differing)
+            }
+            SnapshotError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn golden_file() {
+        let path = std::env::temp_dir().join(format!("druid-snapshot-{}.pam", std::process::id()));
+        let mut image = RgbaImage {
+            width: 1,
+            height: 1,
+            pixels: vec![10, 20, 30, 255],
+        };
+        // The first check records the golden file.
+        check_snapshot(&image, &path, 0).unwrap();
+        image.pixels[0] = 12;
+        check_snapshot(&image, &path, 2).unwrap();
+        match check_snapshot(&image, &path, 0) {
+            Err(SnapshotError::PixelMismatch { differing: 1 }) => (),
+            other => panic!("unexpected result {:?}", other),
+        }
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(actual_path(&path)).unwrap();
+    }
+}
@@ -0,0 +1,860 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A reusable text layout, for widgets that draw their own text.
+
+use std::ops::Range;
+
+use crate::bidi;
+use crate::kurbo::{Point, Rect, Size, Vec2};
+use crate::piet::{
+    Color, FontBuilder, Piet, PietFont, PietText, PietTextLayout, RenderContext, Text,
+    TextLayout as _, TextLayoutBuilder,
+};
+use crate::theme;
+use crate::unicode_segmentation::UnicodeSegmentation;
+use crate::{Env, PaintCtx};
+
+/// Whether the text backend lacks font fallback, so that emoji must be
+/// drawn with a separate font.
+///
+/// DirectWrite and the browser find a font for each glyph themselves, but
+/// Cairo's text API draws with the one font it is given.
+const NEEDS_EMOJI_FALLBACK: bool = !cfg!(any(target_os = "windows", target_arch = "wasm32"));
+
+/// A font for text, along with the font used to draw emoji, where the text
+/// backend doesn't fall back to one by itself.
+pub(crate) struct FontSet {
+    font: PietFont,
+    emoji: Option<PietFont>,
+}
+
+/// A line of text, laid out in runs that each use one font.
+pub(crate) struct LineLayout {
+    /// Each run and its offset from the start of the line.
+    runs: Vec<(f64, PietTextLayout)>,
+    width: f64,
+}
+
+impl FontSet {
+    /// Create the fonts with the given family and size; the emoji font is
+    /// that set for [`EMOJI_FONT_NAME`] in the environment.
+    ///
+    /// [`EMOJI_FONT_NAME`]: theme/constant.EMOJI_FONT_NAME.html
+    pub(crate) fn new(t: &mut PietText, name: &str, size: f64, env: &Env) -> Self {
+        let font = t.new_font_by_name(name, size).unwrap().build().unwrap();
+        let emoji = if NEEDS_EMOJI_FALLBACK {
+            env.try_get(theme::EMOJI_FONT_NAME)
+                .map(|name| t.new_font_by_name(name, size).unwrap().build().unwrap())
+        } else {
+            None
+        };
+        FontSet { font, emoji }
+    }
+
+    /// Create the fonts for the theme's [`FONT_NAME`] and
+    /// [`TEXT_SIZE_NORMAL`].
+    ///
+    /// [`FONT_NAME`]: theme/constant.FONT_NAME.html
+    /// [`TEXT_SIZE_NORMAL`]: theme/constant.TEXT_SIZE_NORMAL.html
+    pub(crate) fn from_env(t: &mut PietText, env: &Env) -> Self {
+        let name = env.get(theme::FONT_NAME);
+        let size = env.get(theme::TEXT_SIZE_NORMAL);
+        FontSet::new(t, name, size, env)
+    }
+
+    /// Lay out a line of text, switching to the emoji font for emoji.
+    pub(crate) fn layout(&self, t: &mut PietText, text: &str) -> LineLayout {
+        let mut runs = Vec::new();
+        let mut x = 0.0;
+        for (range, emoji) in font_runs(text, self.emoji.is_some()) {
+            let font = match (emoji, self.emoji.as_ref()) {
+                (true, Some(emoji_font)) => emoji_font,
+                _ => &self.font,
+            };
+            let layout = t
+                .new_text_layout(font, &text[range])
+                .unwrap()
+                .build()
+                .unwrap();
+            let width = layout.width();
+            runs.push((x, layout));
+            x += width;
+        }
+        LineLayout { runs, width: x }
+    }
+
+    /// The width of a line of text.
+    pub(crate) fn width(&self, t: &mut PietText, text: &str) -> f64 {
+        self.layout(t, text).width()
+    }
+}
+
+impl LineLayout {
+    /// The width of the line.
+    pub(crate) fn width(&self) -> f64 {
+        self.width
+    }
+
+    /// Draw the line, starting at a point on its baseline.
+    pub(crate) fn draw(&self, rc: &mut Piet, baseline: impl Into<Point>, color: &Color) {
+        let baseline = baseline.into();
+        for (x, layout) in &self.runs {
+            rc.draw_text(layout, baseline + Vec2::new(*x, 0.0), color);
+        }
+    }
+}
+
+/// Split text into runs of emoji and other text, at grapheme boundaries.
+///
+/// Each run is returned with whether it is emoji. If `split` is `false`,
+/// the whole text is one run.
+fn font_runs(text: &str, split: bool) -> Vec<(Range<usize>, bool)> {
+    if !split {
+        return vec![(0..text.len(), false)];
+    }
+    let mut runs: Vec<(Range<usize>, bool)> = Vec::new();
+    for (offset, grapheme) in text.grapheme_indices(true) {
+        let emoji = is_emoji(grapheme);
+        let end = offset + grapheme.len();
+        match runs.last_mut() {
+            Some((range, run_emoji)) if *run_emoji == emoji => range.end = end,
+            _ => runs.push((offset..end, emoji)),
+        }
+    }
+    if runs.is_empty() {
+        runs.push((0..0, false));
+    }
+    runs
+}
+
+/// Whether a grapheme is presented as an emoji.
+///
+/// This is an approximation of the Unicode `Emoji_Presentation` property:
+/// it covers the pictographic blocks, which include the regional indicators
+/// used for flags, and any character followed by the emoji variation
+/// selector.
+pub(crate) fn is_emoji(grapheme: &str) -> bool {
+    let first = match grapheme.chars().next() {
+        Some(c) => c as u32,
+        None => return false,
+    };
+    (0x1f000..=0x1faff).contains(&first) || grapheme.contains('\u{fe0f}')
+}
+
+/// The horizontal alignment of lines of text within the width they are
+/// laid out in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextAlignment {
+    /// Lines start at the leading edge.
+    Start,
+    /// Lines are centered.
+    Center,
+    /// Lines end at the trailing edge.
+    End,
+    /// Lines fill the width, by widening the space between words.
+    ///
+    /// The last line of a paragraph is aligned to the start.
+    Justified,
+}
+
+impl Default for TextAlignment {
+    fn default() -> Self {
+        TextAlignment::Start
+    }
+}
+
+impl TextAlignment {
+    /// The equivalent alignment in terms of left and right: in a
+    /// right-to-left layout, `Start` and `End` are swapped.
+    pub(crate) fn for_direction(self, rtl: bool) -> Self {
+        match self {
+            TextAlignment::Start if rtl => TextAlignment::End,
+            TextAlignment::End if rtl => TextAlignment::Start,
+            other => other,
+        }
+    }
+
+    /// The horizontal offset of a line of width `line_width`, aligned
+    /// within `width`.
+    ///
+    /// Justified lines are offset as if aligned to the start; stretching
+    /// them is up to the caller.
+    pub(crate) fn offset(self, line_width: f64, width: f64) -> f64 {
+        let extra = width - line_width;
+        if !extra.is_finite() || extra <= 0.0 {
+            return 0.0;
+        }
+        match self {
+            TextAlignment::Start | TextAlignment::Justified => 0.0,
+            TextAlignment::Center => extra / 2.0,
+            TextAlignment::End => extra,
+        }
+    }
+}
+
+/// A single line of text, with its font and color, that can be measured,
+/// hit-tested, and drawn.
+///
+/// This wraps the underlying piet layout, rebuilding it only when the text
+/// or font change. Unless overridden, the font and color come from the
+/// [`FONT_NAME`], [`TEXT_SIZE_NORMAL`] and [`LABEL_COLOR`] theme keys.
+///
+/// [`rebuild_if_needed`] must be called, typically from a widget's `layout`
+/// method, before the layout is measured or drawn. Positions are relative
+/// to the top left of the layout, which is the point passed to [`draw`].
+///
+/// If a layout width is set with [`set_layout_width`], the text is aligned
+/// within that width according to its [`TextAlignment`].
+///
+/// [`FONT_NAME`]: theme/constant.FONT_NAME.html
+/// [`TEXT_SIZE_NORMAL`]: theme/constant.TEXT_SIZE_NORMAL.html
+/// [`LABEL_COLOR`]: theme/constant.LABEL_COLOR.html
+/// [`rebuild_if_needed`]: #method.rebuild_if_needed
+/// [`draw`]: #method.draw
+/// [`set_layout_width`]: #method.set_layout_width
+/// [`TextAlignment`]: enum.TextAlignment.html
+pub struct TextLayout {
+    text: String,
+    font_name: Option<String>,
+    font_size: Option<f64>,
+    color: Option<Color>,
+    alignment: TextAlignment,
+    layout_width: f64,
+    cache: Option<CachedLayout>,
+}
+
+struct CachedLayout {
+    font_name: String,
+    font_size: f64,
+    rtl: bool,
+    layout: LineLayout,
+    /// The offset of each caret position, and its distance from the start
+    /// of the line, from left to right.
+    boundaries: Vec<(usize, f64)>,
+}
+
+impl TextLayout {
+    /// Create a new layout for the given text.
+    pub fn new(text: impl Into<String>) -> Self {
+        TextLayout {
+            text: text.into(),
+            font_name: None,
+            font_size: None,
+            color: None,
+            alignment: TextAlignment::Start,
+            layout_width: f64::INFINITY,
+            cache: None,
+        }
+    }
+
+    /// Use the named font family, instead of the theme's.
+    pub fn font_name(mut self, name: impl Into<String>) -> Self {
+        self.font_name = Some(name.into());
+        self.cache = None;
+        self
+    }
+
+    /// Use the given font size, instead of the theme's.
+    pub fn font_size(mut self, size: f64) -> Self {
+        self.font_size = Some(size);
+        self.cache = None;
+        self
+    }
+
+    /// Draw the text in the given color, instead of the theme's.
+    pub fn text_color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Set the color of the text; `None` uses the theme's label color.
+    pub fn set_text_color(&mut self, color: Option<Color>) {
+        self.color = color;
+    }
+
+    /// Set the alignment of the text within its layout width.
+    pub fn alignment(mut self, alignment: TextAlignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// Set the width the text is aligned within.
+    ///
+    /// By default the width is unbounded, and the text is not offset.
+    pub fn set_layout_width(&mut self, width: f64) {
+        self.layout_width = width;
+    }
+
+    /// The text being laid out.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replace the text.
+    ///
+    /// The layout is rebuilt on the next call to [`rebuild_if_needed`] if
+    /// the text has changed.
+    ///
+    /// [`rebuild_if_needed`]: #method.rebuild_if_needed
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        let text = text.into();
+        if text != self.text {
+            self.text = text;
+            self.cache = None;
+        }
+    }
+
+    /// Rebuild the layout if the text or the font, including the font
+    /// resolved from the environment, have changed since it was last built.
+    pub fn rebuild_if_needed(&mut self, t: &mut PietText, env: &Env) {
+        let font_name = self
+            .font_name
+            .as_ref()
+            .map(String::as_str)
+            .unwrap_or_else(|| env.get(theme::FONT_NAME));
+        let font_size = self
+            .font_size
+            .unwrap_or_else(|| env.get(theme::TEXT_SIZE_NORMAL));
+
+        let rtl = bidi::is_rtl(env);
+
+        if let Some(cache) = self.cache.as_ref() {
+            if cache.font_name == font_name && cache.font_size == font_size && cache.rtl == rtl {
+                return;
+            }
+        }
+
+        let fonts = FontSet::new(t, font_name, font_size, env);
+        let layout = fonts.layout(t, &bidi::visual_text(&self.text, rtl));
+
+        // TODO: use the layout's hit testing, once piet has it.
+        let boundaries = if bidi::needs_bidi(&self.text, rtl) {
+            bidi::caret_stops(&self.text, rtl, |s| fonts.width(t, s))
+        } else {
+            let mut boundaries = vec![(0, 0.0)];
+            for (offset, grapheme) in self.text.grapheme_indices(true) {
+                let end = offset + grapheme.len();
+                let width = if end == self.text.len() {
+                    layout.width()
+                } else {
+                    fonts.width(t, &self.text[..end])
+                };
+                boundaries.push((end, width));
+            }
+            boundaries
+        };
+
+        self.cache = Some(CachedLayout {
+            font_name: font_name.to_owned(),
+            font_size,
+            rtl,
+            layout,
+            boundaries,
+        });
+    }
+
+    /// The size of the laid out text.
+    ///
+    /// The width is the layout width, if one is set, and otherwise that of
+    /// the text; the height is that of a line in the current font.
+    pub fn size(&self) -> Size {
+        match self.cache.as_ref() {
+            Some(cache) => {
+                let width = if self.layout_width.is_finite() {
+                    self.layout_width
+                } else {
+                    cache.layout.width()
+                };
+                Size::new(width, line_height(cache.font_size))
+            }
+            None => Size::ZERO,
+        }
+    }
+
+    /// The horizontal offset of the text within the layout width.
+    fn alignment_offset(&self) -> f64 {
+        match self.cache.as_ref() {
+            Some(cache) => self
+                .alignment
+                .for_direction(cache.rtl)
+                .offset(cache.layout.width(), self.layout_width),
+            None => 0.0,
+        }
+    }
+
+    /// Find the offset in the text of the grapheme boundary closest to
+    /// a point.
+    pub fn hit_test_point(&self, point: Point) -> usize {
+        let boundaries = match self.cache.as_ref() {
+            Some(cache) => &cache.boundaries,
+            None => return 0,
+        };
+        let point = point - Vec2::new(self.alignment_offset(), 0.0);
+        let mut prev: Option<(usize, f64)> = None;
+        for &(offset, x) in boundaries {
+            if point.x < x {
+                return match prev {
+                    Some((prev_offset, prev_x)) if point.x < (prev_x + x) / 2.0 => prev_offset,
+                    _ => offset,
+                };
+            }
+            prev = Some((offset, x));
+        }
+        prev.map(|(offset, _)| offset).unwrap_or(0)
+    }
+
+    /// The position of the caret at an offset in the text, on the top
+    /// edge of the line.
+    ///
+    /// Offsets inside a grapheme are treated as the start of that grapheme.
+    pub fn point_for_offset(&self, offset: usize) -> Point {
+        let x = self
+            .cache
+            .as_ref()
+            .and_then(|cache| {
+                let boundaries = &cache.boundaries;
+                boundaries
+                    .iter()
+                    .find(|(boundary, _)| *boundary == offset)
+                    .or_else(|| {
+                        boundaries
+                            .iter()
+                            .take_while(|(boundary, _)| *boundary <= offset)
+                            .last()
+                    })
+            })
+            .map(|(_, x)| *x)
+            .unwrap_or(0.0);
+        Point::new(x + self.alignment_offset(), 0.0)
+    }
+
+    /// Draw the text, with its top left corner at `origin`.
+    pub fn draw(&self, paint_ctx: &mut PaintCtx, origin: impl Into<Point>, env: &Env) {
+        if let Some(cache) = self.cache.as_ref() {
+            let color = self
+                .color
+                .clone()
+                .unwrap_or_else(|| env.get(theme::LABEL_COLOR));
+            let offset = Vec2::new(self.alignment_offset(), cache.font_size);
+            let baseline = origin.into() + offset;
+            cache.layout.draw(&mut **paint_ctx, baseline, &color);
+        }
+    }
+}
+
+/// The most lines kept in one chunk of a [`DocumentLayout`] after an edit.
+///
+/// [`DocumentLayout`]: struct.DocumentLayout.html
+const CHUNK_LINES: usize = 64;
+
+/// The layout of a document of many lines, for editors of large files.
+///
+/// Each line is laid out separately, and the lines are grouped into chunks
+/// that record their total length and greatest width, so that finding the
+/// line for an offset or a point only visits the chunks before it. An
+/// [`edit`] re-shapes only the lines it touches, on the next call to
+/// [`rebuild_if_needed`], and [`draw`] only draws the lines in view.
+///
+/// Lines are not wrapped, and are all the height of a line in the font,
+/// which comes from the [`FONT_NAME`] and [`TEXT_SIZE_NORMAL`] theme keys.
+///
+/// [`edit`]: #method.edit
+/// [`rebuild_if_needed`]: #method.rebuild_if_needed
+/// [`draw`]: #method.draw
+/// [`FONT_NAME`]: theme/constant.FONT_NAME.html
+/// [`TEXT_SIZE_NORMAL`]: theme/constant.TEXT_SIZE_NORMAL.html
+pub struct DocumentLayout {
+    text: String,
+    chunks: Vec<Chunk>,
+    color: Option<Color>,
+    /// The font and direction the lines were shaped with.
+    font: Option<(String, f64, bool)>,
+}
+
+#[derive(Default)]
+struct Chunk {
+    lines: Vec<DocumentLine>,
+    /// The length of the chunk's lines, in bytes.
+    len: usize,
+    /// The width of the widest shaped line.
+    width: f64,
+    /// Some lines need shaping.
+    dirty: bool,
+}
+
+struct DocumentLine {
+    /// The length of the line in bytes, including its newline.
+    len: usize,
+    layout: Option<LineLayout>,
+}
+
+impl DocumentLayout {
+    /// Create a new layout for the given text.
+    pub fn new(text: impl Into<String>) -> Self {
+        let text = text.into();
+        let lines = split_lines(&text, true);
+        DocumentLayout {
+            chunks: chunks(lines),
+            text,
+            color: None,
+            font: None,
+        }
+    }
+
+    /// Draw the text in the given color, instead of the theme's.
+    pub fn text_color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// The text being laid out.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The number of lines, which is one more than the number of newlines.
+    pub fn line_count(&self) -> usize {
+        self.chunks.iter().map(|chunk| chunk.lines.len()).sum()
+    }
+
+    /// Replace a range of the text, marking the lines it touches to be
+    /// shaped again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds, or not on character boundaries.
+    pub fn edit(&mut self, range: Range<usize>, new: &str) {
+        let (first, first_start) = self.locate(range.start);
+        let (last, last_start) = self.locate(range.end);
+        let last_len = self.chunks[last.0].lines[last.1].len;
+        let is_end =
+            last.0 == self.chunks.len() - 1 && last.1 == self.chunks[last.0].lines.len() - 1;
+
+        self.text.replace_range(range.clone(), new);
+        let end = last_start + last_len + new.len() - (range.end - range.start);
+        let lines = split_lines(&self.text[first_start..end], is_end);
+
+        // Splice the new lines in place of the old, within the first chunk.
+        let tail = self.chunks[last.0].lines.split_off(last.1 + 1);
+        self.chunks.drain(first.0 + 1..=last.0);
+        let chunk = &mut self.chunks[first.0];
+        chunk.lines.truncate(first.1);
+        chunk.lines.extend(lines);
+        chunk.lines.extend(tail);
+        let lines = std::mem::replace(&mut chunk.lines, Vec::new());
+        if lines.len() > CHUNK_LINES * 2 {
+            let new_chunks = chunks(lines);
+            self.chunks.splice(first.0..=first.0, new_chunks);
+        } else {
+            *chunk = chunk_of(lines);
+        }
+    }
+
+    /// Shape the lines that need it, or all of them if the font, including
+    /// the font resolved from the environment, has changed.
+    pub fn rebuild_if_needed(&mut self, t: &mut PietText, env: &Env) {
+        let font_name = env.get(theme::FONT_NAME);
+        let font_size = env.get(theme::TEXT_SIZE_NORMAL);
+        let rtl = bidi::is_rtl(env);
+        let same_font = self
+            .font
+            .as_ref()
+            .map(|(name, size, font_rtl)| {
+                name == font_name && *size == font_size && *font_rtl == rtl
+            })
+            .unwrap_or(false);
+        if !same_font {
+            for chunk in &mut self.chunks {
+                chunk.lines.iter_mut().for_each(|line| line.layout = None);
+                chunk.dirty = true;
+            }
+            self.font = Some((font_name.to_owned(), font_size, rtl));
+        }
+        if self.chunks.iter().all(|chunk| !chunk.dirty) {
+            return;
+        }
+        let fonts = FontSet::new(t, font_name, font_size, env);
+        let mut start = 0;
+        for chunk in &mut self.chunks {
+            if !chunk.dirty {
+                start += chunk.len;
+                continue;
+            }
+            let mut width: f64 = 0.0;
+            for line in &mut chunk.lines {
+                if line.layout.is_none() {
+                    let text = line_text(&self.text[start..start + line.len]);
+                    line.layout = Some(fonts.layout(t, &bidi::visual_text(text, rtl)));
+                }
+                width = width.max(line.layout.as_ref().map(LineLayout::width).unwrap_or(0.0));
+                start += line.len;
+            }
+            chunk.width = width;
+            chunk.dirty = false;
+        }
+    }
+
+    /// The size of the laid out text: the width of its widest line, and the
+    /// height of all of its lines.
+    pub fn size(&self) -> Size {
+        let width = self.chunks.iter().map(|c| c.width).fold(0.0, f64::max);
+        Size::new(width, self.line_count() as f64 * self.line_height())
+    }
+
+    /// The line containing an offset in the text.
+    pub fn line_for_offset(&self, offset: usize) -> usize {
+        let ((chunk, line), _) = self.locate(offset);
+        self.chunks[..chunk]
+            .iter()
+            .map(|c| c.lines.len())
+            .sum::<usize>()
+            + line
+    }
+
+    /// The range of a line in the text, excluding its newline.
+    ///
+    /// Lines past the end are empty ranges at the end of the text.
+    pub fn line_range(&self, line: usize) -> Range<usize> {
+        let mut start = 0;
+        let mut remaining = line;
+        for chunk in &self.chunks {
+            if remaining < chunk.lines.len() {
+                start += chunk.lines[..remaining]
+                    .iter()
+                    .map(|l| l.len)
+                    .sum::<usize>();
+                let len = chunk.lines[remaining].len;
+                return start..start + line_text(&self.text[start..start + len]).len();
+            }
+            remaining -= chunk.lines.len();
+            start += chunk.len;
+        }
+        self.text.len()..self.text.len()
+    }
+
+    /// The line at a distance from the top of the layout.
+    pub fn line_at_y(&self, y: f64) -> usize {
+        let height = self.line_height();
+        if height <= 0.0 || !y.is_finite() {
+            return 0;
+        }
+        let line = (y / height).floor().max(0.0) as usize;
+        line.min(self.line_count().saturating_sub(1))
+    }
+
+    /// The height of each line.
+    pub fn line_height(&self) -> f64 {
+        line_height(self.font.as_ref().map(|(_, size, _)| *size).unwrap_or(0.0))
+    }
+
+    /// Draw the lines that are within `visible`, a rectangle in the layout's
+    /// coordinate space, with the top left corner of the layout at `origin`.
+    pub fn draw(
+        &self,
+        paint_ctx: &mut PaintCtx,
+        origin: impl Into<Point>,
+        visible: Rect,
+        env: &Env,
+    ) {
+        let font_size = match self.font.as_ref() {
+            Some((_, size, _)) => *size,
+            None => return,
+        };
+        let origin = origin.into();
+        let color = self
+            .color
+            .clone()
+            .unwrap_or_else(|| env.get(theme::LABEL_COLOR));
+        let height = self.line_height();
+        let first = self.line_at_y(visible.y0);
+        let last = self.line_at_y(visible.y1);
+        let mut index = 0;
+        for chunk in &self.chunks {
+            if index + chunk.lines.len() <= first {
+                index += chunk.lines.len();
+                continue;
+            }
+            for line in &chunk.lines {
+                if index > last {
+                    return;
+                }
+                if let (true, Some(layout)) = (index >= first, line.layout.as_ref()) {
+                    let baseline = origin + Vec2::new(0.0, index as f64 * height + font_size);
+                    layout.draw(&mut **paint_ctx, baseline, &color);
+                }
+                index += 1;
+            }
+        }
+    }
+
+    /// The chunk and line containing an offset, and the offset at which
+    /// the line starts.
+    ///
+    /// An offset just past a newline is at the start of the next line.
+    fn locate(&self, offset: usize) -> ((usize, usize), usize) {
+        assert!(offset <= self.text.len(), "offset out of bounds");
+        let mut start = 0;
+        let last_chunk = self.chunks.len() - 1;
+        for (i, chunk) in self.chunks.iter().enumerate() {
+            if offset >= start + chunk.len && i < last_chunk {
+                start += chunk.len;
+                continue;
+            }
+            let last_line = chunk.lines.len() - 1;
+            for (j, line) in chunk.lines.iter().enumerate() {
+                if offset < start + line.len || (i == last_chunk && j == last_line) {
+                    return ((i, j), start);
+                }
+                start += line.len;
+            }
+        }
+        unreachable!("documents always have a line")
+    }
+}
+
+/// Split text into unshaped lines, each including its newline.
+///
+/// If `is_end` is `false`, the text is followed by more lines, so it ends
+/// with a newline and no empty line follows it.
+fn split_lines(text: &str, is_end: bool) -> Vec<DocumentLine> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for (i, _) in text.match_indices('\n') {
+        lines.push(DocumentLine {
+            len: i + 1 - start,
+            layout: None,
+        });
+        start = i + 1;
+    }
+    if is_end {
+        lines.push(DocumentLine {
+            len: text.len() - start,
+            layout: None,
+        });
+    }
+    lines
+}
+
+/// Group lines into chunks of `CHUNK_LINES`.
+fn chunks(mut lines: Vec<DocumentLine>) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+    while lines.len() > CHUNK_LINES {
+        let rest = lines.split_off(CHUNK_LINES);
+        chunks.push(chunk_of(lines));
+        lines = rest;
+    }
+    chunks.push(chunk_of(lines));
+    chunks
+}
+
+fn chunk_of(lines: Vec<DocumentLine>) -> Chunk {
+    let len = lines.iter().map(|line| line.len).sum();
+    let dirty = lines.iter().any(|line| line.layout.is_none());
+    let width = lines
+        .iter()
+        .filter_map(|line| line.layout.as_ref().map(LineLayout::width))
+        .fold(0.0, f64::max);
+    Chunk {
+        lines,
+        len,
+        width,
+        dirty,
+    }
+}
+
+/// A line of text, without its line ending.
+fn line_text(line: &str) -> &str {
+    line.trim_end_matches('\n').trim_end_matches('\r')
+}
+
+/// The height of a line of text, including space for descenders.
+fn line_height(font_size: f64) -> f64 {
+    font_size * 1.2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alignment_offset() {
+        assert_eq!(TextAlignment::Start.offset(40.0, 100.0), 0.0);
+        assert_eq!(TextAlignment::Center.offset(40.0, 100.0), 30.0);
+        assert_eq!(TextAlignment::End.offset(40.0, 100.0), 60.0);
+        assert_eq!(TextAlignment::Justified.offset(40.0, 100.0), 0.0);
+        // Text wider than the layout, or an unbounded layout, isn't offset.
+        assert_eq!(TextAlignment::End.offset(140.0, 100.0), 0.0);
+        assert_eq!(TextAlignment::Center.offset(40.0, f64::INFINITY), 0.0);
+    }
+
+    #[test]
+    fn emoji_runs() {
+        let text = "hi \u{1f44b}\u{1f3fd}!";
+        assert_eq!(
+            font_runs(text, true),
+            vec![(0..3, false), (3..11, true), (11..12, false)]
+        );
+        assert!(is_emoji("\u{1f1eb}\u{1f1f7}"));
+        assert!(is_emoji("\u{2764}\u{fe0f}"));
+        assert!(!is_emoji("a"));
+    }
+
+    #[test]
+    fn document_edits() {
+        let mut doc = DocumentLayout::new("one\ntwo\nthree");
+        assert_eq!(doc.line_count(), 3);
+        assert_eq!(doc.line_range(1), 4..7);
+        assert_eq!(doc.line_for_offset(8), 2);
+        // Pretend the lines have been shaped.
+        for chunk in &mut doc.chunks {
+            chunk.dirty = false;
+        }
+
+        doc.edit(5..5, "o\nt");
+        assert_eq!(doc.text(), "one\nto\ntwo\nthree");
+        assert_eq!(doc.line_count(), 4);
+        assert_eq!(doc.line_range(2), 7..10);
+        assert_eq!(doc.line_range(3), 11..16);
+        assert!(doc.chunks[0].dirty);
+
+        // Joining lines, and deleting a trailing newline.
+        doc.edit(3..4, "");
+        assert_eq!(doc.line_range(0), 0..5);
+        doc.edit(0..doc.text().len(), "x\n");
+        assert_eq!(doc.line_count(), 2);
+        assert_eq!(doc.line_range(1), 2..2);
+    }
+
+    #[test]
+    fn document_chunks() {
+        let text = "line\n".repeat(300);
+        let mut doc = DocumentLayout::new(text);
+        assert_eq!(doc.line_count(), 301);
+        assert_eq!(doc.chunks.len(), 5);
+        for chunk in &mut doc.chunks {
+            chunk.dirty = false;
+        }
+
+        // An edit spanning chunks only dirties the chunk it lands in.
+        let start = doc.line_range(60).start;
+        let end = doc.line_range(70).end;
+        doc.edit(start..end, "joined");
+        assert_eq!(doc.line_count(), 291);
+        assert_eq!(doc.line_for_offset(start), 60);
+        assert_eq!(doc.line_range(61), start + 7..start + 11);
+        let dirty: Vec<bool> = doc.chunks.iter().map(|c| c.dirty).collect();
+        assert_eq!(dirty, vec![true, false, false, false]);
+    }
+}
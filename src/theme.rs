@@ -16,7 +16,7 @@
 
 use crate::piet::Color;
 
-use crate::{Env, Key};
+use crate::{Background, Env, Key, Size};
 
 pub const WINDOW_BACKGROUND_COLOR: Key<Color> = Key::new("window_background_color");
 
@@ -34,14 +34,138 @@ pub const BORDER: Key<Color> = Key::new("border");
 pub const BORDER_LIGHT: Key<Color> = Key::new("border_light");
 pub const SELECTION_COLOR: Key<Color> = Key::new("selection_color");
 pub const CURSOR_COLOR: Key<Color> = Key::new("cursor_color");
+pub const DROP_TARGET_COLOR: Key<Color> = Key::new("drop_target_color");
+/// The color of validation error messages, and of the border of invalid inputs.
+pub const ERROR_COLOR: Key<Color> = Key::new("error_color");
+/// The color of the labels of disabled controls.
+pub const DISABLED_LABEL_COLOR: Key<Color> = Key::new("disabled_label_color");
+/// The color of the placeholder text shown in empty text boxes.
+pub const PLACEHOLDER_COLOR: Key<Color> = Key::new("placeholder_color");
+/// The color of the ring drawn around the focused widget, when focus was
+/// moved with the keyboard.
+pub const FOCUS_RING_COLOR: Key<Color> = Key::new("focus_ring_color");
+/// The width of the focus ring.
+pub const FOCUS_RING_WIDTH: Key<f64> = Key::new("focus_ring_width");
 
 pub const FONT_NAME: Key<&str> = Key::new("font_name");
+/// The font used to draw emoji, on platforms where text isn't automatically
+/// drawn with a fallback font for characters the main font lacks.
+pub const EMOJI_FONT_NAME: Key<&str> = Key::new("emoji_font_name");
 pub const TEXT_SIZE_NORMAL: Key<f64> = Key::new("text_size_normal");
 pub const BASIC_WIDGET_HEIGHT: Key<f64> = Key::new("basic_widget_height");
 pub const BORDERED_WIDGET_HEIGHT: Key<f64> = Key::new("bordered_widget_height");
 
 pub const SCROLL_BAR_COLOR: Key<Color> = Key::new("scroll_bar_color");
 pub const SCROLL_BAR_BORDER_COLOR: Key<Color> = Key::new("scroll_bar_border_color");
+/// How long, in seconds, scroll bars stay visible after scrolling stops or
+/// the mouse leaves them, before they fade out.
+pub const SCROLL_BAR_FADE_DELAY: Key<f64> = Key::new("scroll_bar_fade_delay");
+/// Whether scroll bars are always shown when the content can scroll, rather
+/// than only while scrolling or with the mouse over them.
+pub const SCROLL_BAR_ALWAYS_VISIBLE: Key<bool> = Key::new("scroll_bar_always_visible");
+
+/// The fill of a button.
+///
+/// This key is not set by the initial theme. When it is absent, buttons are
+/// filled with a gradient from [`BUTTON_DARK`] to [`BUTTON_LIGHT`].
+///
+/// [`BUTTON_DARK`]: constant.BUTTON_DARK.html
+/// [`BUTTON_LIGHT`]: constant.BUTTON_LIGHT.html
+pub const BUTTON_BACKGROUND: Key<&Background> = Key::new("button_background");
+/// The fill of a button while the mouse is over it.
+///
+/// When this key is absent, [`BUTTON_BACKGROUND`] is used.
+///
+/// [`BUTTON_BACKGROUND`]: constant.BUTTON_BACKGROUND.html
+pub const BUTTON_HOT_BACKGROUND: Key<&Background> = Key::new("button_hot_background");
+/// The fill of a button while it is pressed.
+///
+/// When this key is absent, the gradient of [`BUTTON_BACKGROUND`]'s
+/// fallback is flipped.
+///
+/// [`BUTTON_BACKGROUND`]: constant.BUTTON_BACKGROUND.html
+pub const BUTTON_ACTIVE_BACKGROUND: Key<&Background> = Key::new("button_active_background");
+/// The border color of a button.
+///
+/// When this key is absent, [`BORDER`] is used.
+///
+/// [`BORDER`]: constant.BORDER.html
+pub const BUTTON_BORDER_COLOR: Key<Color> = Key::new("button_border_color");
+/// The border color of a button while the mouse is over it.
+///
+/// When this key is absent, [`BORDER_LIGHT`] is used.
+///
+/// [`BORDER_LIGHT`]: constant.BORDER_LIGHT.html
+pub const BUTTON_HOT_BORDER_COLOR: Key<Color> = Key::new("button_hot_border_color");
+/// The border color of a button while it is pressed.
+///
+/// When this key is absent, the hot border color is used.
+pub const BUTTON_ACTIVE_BORDER_COLOR: Key<Color> = Key::new("button_active_border_color");
+/// The width of the border of a button.
+pub const BUTTON_BORDER_WIDTH: Key<f64> = Key::new("button_border_width");
+/// The radius of the corners of a button.
+pub const BUTTON_CORNER_RADIUS: Key<f64> = Key::new("button_corner_radius");
+/// The color of the label of a button.
+///
+/// When this key is absent, [`LABEL_COLOR`] is used. Disabled buttons always
+/// use [`DISABLED_LABEL_COLOR`].
+///
+/// [`LABEL_COLOR`]: constant.LABEL_COLOR.html
+/// [`DISABLED_LABEL_COLOR`]: constant.DISABLED_LABEL_COLOR.html
+pub const BUTTON_TEXT_COLOR: Key<Color> = Key::new("button_text_color");
+/// The color of the label of a button while the mouse is over it.
+///
+/// When this key is absent, the normal text color is used.
+pub const BUTTON_HOT_TEXT_COLOR: Key<Color> = Key::new("button_hot_text_color");
+/// The color of the label of a button while it is pressed.
+///
+/// When this key is absent, the hot text color is used.
+pub const BUTTON_ACTIVE_TEXT_COLOR: Key<Color> = Key::new("button_active_text_color");
+/// The space between the left and right edges of a button and its label.
+pub const BUTTON_HORIZONTAL_PADDING: Key<f64> = Key::new("button_horizontal_padding");
+/// The space between the top and bottom edges of a button and its label.
+pub const BUTTON_VERTICAL_PADDING: Key<f64> = Key::new("button_vertical_padding");
+/// The smallest size of a button, within its parent's constraints.
+pub const BUTTON_MIN_SIZE: Key<Size> = Key::new("button_min_size");
+/// The fill of the track of a progress bar.
+///
+/// When this key is absent, a gradient from [`BACKGROUND_LIGHT`] to
+/// [`BACKGROUND_DARK`] is used.
+///
+/// [`BACKGROUND_LIGHT`]: constant.BACKGROUND_LIGHT.html
+/// [`BACKGROUND_DARK`]: constant.BACKGROUND_DARK.html
+pub const PROGRESS_BAR_BACKGROUND: Key<&Background> = Key::new("progress_bar_background");
+/// The fill of the completed part of a progress bar.
+///
+/// When this key is absent, a gradient from [`PRIMARY_LIGHT`] to
+/// [`PRIMARY_DARK`] is used.
+///
+/// [`PRIMARY_LIGHT`]: constant.PRIMARY_LIGHT.html
+/// [`PRIMARY_DARK`]: constant.PRIMARY_DARK.html
+pub const PROGRESS_BAR_FILL: Key<&Background> = Key::new("progress_bar_fill");
+
+/// Whether the layout direction is right to left.
+///
+/// This is initially `true` when the current locale is written right to
+/// left, such as Arabic or Hebrew. In a right-to-left layout, rows place
+/// their first child on the right, alignments are mirrored, and text starts
+/// at the right edge.
+pub const RIGHT_TO_LEFT: Key<bool> = Key::new("right_to_left");
+
+/// Whether the high-contrast theme is in use.
+///
+/// This follows the system's high-contrast setting, and changes while the
+/// application is running when the user toggles it. See [`high_contrast`]
+/// for the colors it implies.
+///
+/// [`high_contrast`]: fn.high_contrast.html
+pub const HIGH_CONTRAST: Key<bool> = Key::new("high_contrast");
+
+/// Whether the user has asked for less motion on screen.
+///
+/// This follows the system's accessibility setting. When it is `true`,
+/// widgets should skip animations and change state instantly.
+pub const REDUCED_MOTION: Key<bool> = Key::new("reduced_motion");
 
 /// An initial theme.
 pub fn init() -> Env {
@@ -60,25 +184,90 @@ pub fn init() -> Env {
         .adding(BORDER_LIGHT, Color::rgb8(0xa1, 0xa1, 0xa1))
         .adding(SELECTION_COLOR, Color::rgb8(0xf3, 0x00, 0x21))
         .adding(CURSOR_COLOR, Color::WHITE)
+        .adding(DROP_TARGET_COLOR, Color::rgb8(0x5c, 0xc4, 0xff))
+        .adding(ERROR_COLOR, Color::rgb8(0xff, 0x5c, 0x5c))
+        .adding(DISABLED_LABEL_COLOR, Color::rgb8(0x80, 0x80, 0x80))
+        .adding(PLACEHOLDER_COLOR, Color::rgb8(0x80, 0x80, 0x80))
+        .adding(FOCUS_RING_COLOR, Color::rgb8(0x5c, 0xc4, 0xff))
+        .adding(FOCUS_RING_WIDTH, 2.0)
         .adding(TEXT_SIZE_NORMAL, 15.0)
         .adding(BASIC_WIDGET_HEIGHT, 18.0)
         .adding(BORDERED_WIDGET_HEIGHT, 24.0)
         .adding(SCROLL_BAR_COLOR, Color::rgb8(0xff, 0xff, 0xff))
-        .adding(SCROLL_BAR_BORDER_COLOR, Color::rgb8(0x77, 0x77, 0x77));
+        .adding(SCROLL_BAR_BORDER_COLOR, Color::rgb8(0x77, 0x77, 0x77))
+        .adding(SCROLL_BAR_FADE_DELAY, 1.5)
+        .adding(SCROLL_BAR_ALWAYS_VISIBLE, false)
+        .adding(BUTTON_BORDER_WIDTH, 2.0)
+        .adding(BUTTON_CORNER_RADIUS, 4.0)
+        .adding(BUTTON_HORIZONTAL_PADDING, 0.0)
+        .adding(BUTTON_VERTICAL_PADDING, 0.0)
+        .adding(BUTTON_MIN_SIZE, Size::ZERO)
+        .adding(HIGH_CONTRAST, false)
+        .adding(REDUCED_MOTION, false);
+    let rtl = env.localization_manager().is_right_to_left();
+    env = env.adding(RIGHT_TO_LEFT, rtl);
 
     #[cfg(target_os = "windows")]
     {
-        env = env.adding(FONT_NAME, "Segoe UI");
+        env = env
+            .adding(FONT_NAME, "Segoe UI")
+            .adding(EMOJI_FONT_NAME, "Segoe UI Emoji");
     }
     #[cfg(target_os = "macos")]
     {
         // Ideally this would be a reference to San Francisco, but Cairo's
         // "toy text" API doesn't seem to be able to access it easily.
-        env = env.adding(FONT_NAME, "Arial");
+        env = env
+            .adding(FONT_NAME, "Arial")
+            .adding(EMOJI_FONT_NAME, "Apple Color Emoji");
     }
     #[cfg(not(any(target_os = "macos", target_os = "windows")))]
     {
-        env = env.adding(FONT_NAME, "sans-serif");
+        let emoji_font = if cfg!(target_os = "ios") {
+            "Apple Color Emoji"
+        } else {
+            "Noto Color Emoji"
+        };
+        env = env
+            .adding(FONT_NAME, "sans-serif")
+            .adding(EMOJI_FONT_NAME, emoji_font);
     }
     env
 }
+
+/// Add the high-contrast colors to an environment.
+///
+/// Backgrounds are black and text and borders are white, with no
+/// intermediate grays; gradients in the built-in widgets become solid fills,
+/// since both of their stops are the same color. Accents are yellow and
+/// cyan, selections are a blue dark enough to keep white text readable, and
+/// disabled labels and placeholders are green rather than dimmed.
+///
+/// This is applied automatically when the system's high-contrast setting is
+/// on, on top of any values set in [`AppLauncher::configure_env`].
+///
+/// [`AppLauncher::configure_env`]: ../struct.AppLauncher.html#method.configure_env
+pub fn high_contrast(env: Env) -> Env {
+    env.adding(WINDOW_BACKGROUND_COLOR, Color::BLACK)
+        .adding(LABEL_COLOR, Color::WHITE)
+        .adding(PRIMARY_LIGHT, Color::rgb8(0xff, 0xff, 0x00))
+        .adding(PRIMARY_DARK, Color::rgb8(0xff, 0xff, 0x00))
+        .adding(BACKGROUND_LIGHT, Color::BLACK)
+        .adding(BACKGROUND_DARK, Color::BLACK)
+        .adding(FOREGROUND_LIGHT, Color::WHITE)
+        .adding(FOREGROUND_DARK, Color::WHITE)
+        .adding(BUTTON_DARK, Color::BLACK)
+        .adding(BUTTON_LIGHT, Color::BLACK)
+        .adding(BORDER, Color::WHITE)
+        .adding(BORDER_LIGHT, Color::rgb8(0xff, 0xff, 0x00))
+        .adding(SELECTION_COLOR, Color::rgb8(0x00, 0x4d, 0xd9))
+        .adding(CURSOR_COLOR, Color::WHITE)
+        .adding(DROP_TARGET_COLOR, Color::rgb8(0x00, 0xff, 0xff))
+        .adding(ERROR_COLOR, Color::rgb8(0xff, 0x40, 0x40))
+        .adding(DISABLED_LABEL_COLOR, Color::rgb8(0x3f, 0xf2, 0x3f))
+        .adding(PLACEHOLDER_COLOR, Color::rgb8(0x3f, 0xf2, 0x3f))
+        .adding(FOCUS_RING_COLOR, Color::rgb8(0x00, 0xff, 0xff))
+        .adding(SCROLL_BAR_COLOR, Color::WHITE)
+        .adding(SCROLL_BAR_BORDER_COLOR, Color::BLACK)
+        .adding(HIGH_CONTRAST, true)
+}
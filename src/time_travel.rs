@@ -0,0 +1,149 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Recording the history of the application's data, for debugging.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use crate::widget::{Button, Column, DynLabel, Padding, Row};
+use crate::{Command, Data, LocalizedString, Selector, Widget, WindowDesc};
+
+/// The maximum number of snapshots kept.
+const MAX_SNAPSHOTS: usize = 1000;
+
+/// Moves through the history; the argument is a [`Step`].
+///
+/// [`Step`]: enum.Step.html
+pub(crate) const TIME_TRAVEL: Selector = Selector::new("druid-builtin.time-travel");
+
+/// A movement through the history.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Step {
+    Back,
+    Forward,
+    Live,
+}
+
+/// Snapshots of the data, one for each change.
+///
+/// While an earlier snapshot is displayed, recording is paused; returning
+/// to the live state discards any changes made in the meantime.
+pub(crate) struct History<T> {
+    snapshots: VecDeque<T>,
+    /// The index of the snapshot being displayed, or `None` if live.
+    position: Option<usize>,
+}
+
+pub(crate) type SharedHistory<T> = Rc<RefCell<History<T>>>;
+
+impl<T: Data> History<T> {
+    pub(crate) fn new() -> Self {
+        History {
+            snapshots: VecDeque::new(),
+            position: None,
+        }
+    }
+
+    /// Record the data, if it has changed since the last snapshot.
+    pub(crate) fn record(&mut self, data: &T) {
+        if self.position.is_some() {
+            return;
+        }
+        if let Some(last) = self.snapshots.back() {
+            if last.same(data) {
+                return;
+            }
+        }
+        if self.snapshots.len() == MAX_SNAPSHOTS {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(data.clone());
+    }
+
+    /// Move through the history, returning the data to display.
+    pub(crate) fn step(&mut self, step: Step) -> Option<T> {
+        let last = self.snapshots.len().checked_sub(1)?;
+        let position = match (step, self.position) {
+            (Step::Back, None) => last.saturating_sub(1),
+            (Step::Back, Some(pos)) => pos.saturating_sub(1),
+            (Step::Forward, Some(pos)) if pos + 1 < last => pos + 1,
+            (Step::Forward, _) | (Step::Live, _) => last,
+        };
+        self.position = if position == last {
+            None
+        } else {
+            Some(position)
+        };
+        self.snapshots.get(position).cloned()
+    }
+
+    fn describe(&self) -> String {
+        match self.position {
+            Some(pos) => format!("Snapshot {} of {}", pos + 1, self.snapshots.len()),
+            None => format!("Live ({} snapshots)", self.snapshots.len()),
+        }
+    }
+}
+
+/// The window for stepping through the history.
+pub(crate) fn inspector<T: Data + 'static>(history: SharedHistory<T>) -> WindowDesc<T> {
+    let mut desc = WindowDesc::new(move || inspector_widget(history.clone()))
+        .title(LocalizedString::new("time-travel-title"));
+    desc.menu = None;
+    desc
+}
+
+fn inspector_widget<T: Data + 'static>(history: SharedHistory<T>) -> impl Widget<T> {
+    let step_button = |label: &str, step: Step| {
+        Button::new(label, move |ctx, _data: &mut T, _env| {
+            ctx.submit_command(Command::new(TIME_TRAVEL, step), None)
+        })
+    };
+    let mut buttons = Row::new();
+    buttons.add_child(step_button("Back", Step::Back), 1.0);
+    buttons.add_child(step_button("Forward", Step::Forward), 1.0);
+    buttons.add_child(step_button("Live", Step::Live), 1.0);
+
+    let mut col = Column::new();
+    col.add_child(
+        DynLabel::new(move |_data: &T, _env| history.borrow().describe()),
+        0.0,
+    );
+    col.add_child(buttons, 0.0);
+    Padding::uniform(8.0, col)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stepping() {
+        let mut history = History::new();
+        for i in 0..4u32 {
+            history.record(&i);
+            history.record(&i);
+        }
+        assert_eq!(history.step(Step::Back), Some(2));
+        assert_eq!(history.step(Step::Back), Some(1));
+        // Recording is paused while looking back.
+        history.record(&7);
+        assert_eq!(history.step(Step::Forward), Some(2));
+        assert_eq!(history.step(Step::Forward), Some(3));
+        assert_eq!(history.position, None);
+        assert_eq!(history.snapshots.len(), 4);
+    }
+}
@@ -0,0 +1,75 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Spans for the passes over the widget tree, used with the `tracing`
+//! feature.
+//!
+//! Each window-level pass is a `debug` span with the window's id, and each
+//! widget's part of a pass is a `trace` span inside it, with the widget's
+//! type and id. Events are recorded, at the `trace` level, as they are
+//! delivered to each widget, so a subscriber can show, say, every command
+//! a particular widget received. Without the feature, these are no-ops.
+
+use crate::profiler::Pass;
+use crate::{Event, WidgetId, WindowId};
+
+#[cfg(feature = "tracing")]
+pub(crate) use tracing::Span;
+
+/// A placeholder for a span, when tracing is disabled.
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct Span;
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct Entered;
+
+#[cfg(not(feature = "tracing"))]
+impl Span {
+    pub(crate) fn enter(&self) -> Entered {
+        Entered
+    }
+}
+
+/// The span of a pass over a window's widgets.
+#[allow(unused_variables)]
+pub(crate) fn window_span(pass: Pass, window_id: WindowId) -> Span {
+    #[cfg(feature = "tracing")]
+    {
+        tracing::debug_span!("pass", ?pass, window = ?window_id)
+    }
+    #[cfg(not(feature = "tracing"))]
+    {
+        Span
+    }
+}
+
+/// The span of one widget's part of a pass.
+#[allow(unused_variables)]
+pub(crate) fn widget_span(pass: Pass, type_name: &'static str, id: WidgetId) -> Span {
+    #[cfg(feature = "tracing")]
+    {
+        tracing::trace_span!("widget", ?pass, widget = type_name, id = id.to_raw())
+    }
+    #[cfg(not(feature = "tracing"))]
+    {
+        Span
+    }
+}
+
+/// Record an event being delivered, inside the widget's span.
+#[allow(unused_variables)]
+pub(crate) fn event_delivered(event: &Event) {
+    #[cfg(feature = "tracing")]
+    tracing::trace!(?event, "delivered");
+}
@@ -0,0 +1,173 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Undo and redo, based on snapshots of application data.
+
+use std::time::{Duration, Instant};
+
+use crate::Data;
+
+/// The default interval within which edits are grouped into one undo step.
+const DEFAULT_GROUP_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// The default number of undo steps kept.
+const DEFAULT_LIMIT: usize = 100;
+
+/// A history of snapshots of some data, for undo and redo.
+///
+/// Because [`Data`] is cheap to clone, each undo step is simply a copy of
+/// the data as it was before an edit. Edits recorded in quick succession,
+/// such as the keystrokes of someone typing, are grouped into a single
+/// step; a pause longer than the group interval, or an explicit call to
+/// [`break_group`], starts a new one.
+///
+/// Most applications will use the [`Undoable`] widget, which records its
+/// child's changes and handles the standard undo and redo commands.
+///
+/// [`Data`]: trait.Data.html
+/// [`break_group`]: #method.break_group
+/// [`Undoable`]: widget/struct.Undoable.html
+#[derive(Debug, Clone)]
+pub struct UndoManager<T> {
+    undo: Vec<T>,
+    redo: Vec<T>,
+    last_edit: Option<Instant>,
+    group_interval: Duration,
+    limit: usize,
+}
+
+impl<T: Data> UndoManager<T> {
+    /// Create a new, empty history.
+    pub fn new() -> Self {
+        UndoManager {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            last_edit: None,
+            group_interval: DEFAULT_GROUP_INTERVAL,
+            limit: DEFAULT_LIMIT,
+        }
+    }
+
+    /// Builder-style method to set the interval within which edits are
+    /// grouped. A zero interval disables grouping.
+    pub fn group_interval(mut self, interval: Duration) -> Self {
+        self.group_interval = interval;
+        self
+    }
+
+    /// Builder-style method to set the maximum number of undo steps kept.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit.max(1);
+        self
+    }
+
+    /// Record an edit, given the data as it was before the edit.
+    ///
+    /// This clears the redo history.
+    pub fn record(&mut self, old_data: &T) {
+        self.record_at(old_data, Instant::now());
+    }
+
+    fn record_at(&mut self, old_data: &T, now: Instant) {
+        let grouped = self
+            .last_edit
+            .map(|last| now.duration_since(last) < self.group_interval)
+            .unwrap_or(false);
+        // A grouped edit extends the current step, whose snapshot predates it.
+        if !grouped || self.undo.is_empty() {
+            if self.undo.len() == self.limit {
+                self.undo.remove(0);
+            }
+            self.undo.push(old_data.clone());
+        }
+        self.redo.clear();
+        self.last_edit = Some(now);
+    }
+
+    /// End the current group, so that the next edit starts a new undo step.
+    pub fn break_group(&mut self) {
+        self.last_edit = None;
+    }
+
+    /// Undo the last step, given the current data.
+    ///
+    /// Returns the data to restore, or `None` if there is nothing to undo.
+    pub fn undo(&mut self, data: &T) -> Option<T> {
+        let restored = self.undo.pop()?;
+        self.redo.push(data.clone());
+        self.last_edit = None;
+        Some(restored)
+    }
+
+    /// Redo the last undone step, given the current data.
+    ///
+    /// Returns the data to restore, or `None` if there is nothing to redo.
+    pub fn redo(&mut self, data: &T) -> Option<T> {
+        let restored = self.redo.pop()?;
+        self.undo.push(data.clone());
+        self.last_edit = None;
+        Some(restored)
+    }
+
+    /// Returns `true` if there is a step to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// Returns `true` if there is a step to redo.
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Forget all history, for instance after loading a new document.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.last_edit = None;
+    }
+}
+
+impl<T: Data> Default for UndoManager<T> {
+    fn default() -> Self {
+        UndoManager::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grouping() {
+        let mut history = UndoManager::new();
+        let start = Instant::now();
+        let later = start + Duration::from_secs(5);
+        // Typing "ab" quickly, then "c" after a pause.
+        history.record_at(&String::new(), start);
+        history.record_at(&"a".to_string(), start + Duration::from_millis(100));
+        history.record_at(&"ab".to_string(), later);
+
+        let current = "abc".to_string();
+        let current = history.undo(&current).unwrap();
+        assert_eq!(current, "ab");
+        let current = history.undo(&current).unwrap();
+        assert_eq!(current, "");
+        assert!(history.undo(&current).is_none());
+
+        let current = history.redo(&current).unwrap();
+        assert_eq!(current, "ab");
+        history.record(&current);
+        assert!(!history.can_redo());
+    }
+}
@@ -0,0 +1,225 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Validating user input.
+//!
+//! A [`Validator`] checks a single value, such as the contents of a text
+//! box, and is usually attached to the input with the [`Validated`]
+//! widget, which displays its error message. A [`FormValidator`] combines
+//! the validators of several fields, to decide whether a form as a whole
+//! can be submitted.
+//!
+//! [`Validator`]: struct.Validator.html
+//! [`Validated`]: widget/struct.Validated.html
+//! [`FormValidator`]: struct.FormValidator.html
+
+use std::sync::Arc;
+
+use crate::Lens;
+
+type Rule<T> = Arc<dyn Fn(&T) -> Result<(), String>>;
+
+/// A set of rules that a value must satisfy.
+///
+/// Rules are checked in the order they are added, and the message of the
+/// first rule that fails is reported.
+///
+/// # Examples
+/// ```
+/// use druid::Validator;
+///
+/// let name = Validator::required("A name is required")
+///     .rule(|s: &String| !s.contains('/'), "Names can't contain slashes");
+/// assert!(name.validate(&"a/b".to_string()).is_err());
+/// ```
+pub struct Validator<T> {
+    rules: Vec<Rule<T>>,
+}
+
+/// The validators of all the fields of a form.
+pub struct FormValidator<T> {
+    fields: Vec<Rule<T>>,
+}
+
+impl<T: 'static> Validator<T> {
+    /// Create a validator that accepts every value.
+    pub fn new() -> Self {
+        Validator { rules: Vec::new() }
+    }
+
+    /// Builder-style method to add a rule; `check` returns `false` if the
+    /// value is invalid, in which case `message` is reported.
+    pub fn rule(
+        mut self,
+        check: impl Fn(&T) -> bool + 'static,
+        message: impl Into<String>,
+    ) -> Self {
+        let message = message.into();
+        self.rules.push(Arc::new(move |data| {
+            if check(data) {
+                Ok(())
+            } else {
+                Err(message.clone())
+            }
+        }));
+        self
+    }
+
+    /// Check a value, returning the message of the first rule it fails.
+    pub fn validate(&self, data: &T) -> Result<(), String> {
+        self.rules.iter().map(|rule| rule(data)).collect()
+    }
+
+    /// Returns `true` if the value satisfies every rule.
+    pub fn is_valid(&self, data: &T) -> bool {
+        self.validate(data).is_ok()
+    }
+}
+
+impl Validator<String> {
+    /// A validator for text that must not be empty or only whitespace.
+    pub fn required(message: impl Into<String>) -> Self {
+        Validator::new().rule(|s: &String| !s.trim().is_empty(), message)
+    }
+
+    /// Builder-style method to require at least `len` characters.
+    pub fn min_length(self, len: usize, message: impl Into<String>) -> Self {
+        self.rule(move |s: &String| s.chars().count() >= len, message)
+    }
+
+    /// Builder-style method to require at most `len` characters.
+    pub fn max_length(self, len: usize, message: impl Into<String>) -> Self {
+        self.rule(move |s: &String| s.chars().count() <= len, message)
+    }
+}
+
+impl Validator<f64> {
+    /// A validator for numbers in the range `min..=max`.
+    pub fn range(min: f64, max: f64, message: impl Into<String>) -> Self {
+        Validator::new().rule(move |v: &f64| *v >= min && *v <= max, message)
+    }
+}
+
+impl<T: 'static> FormValidator<T> {
+    /// Create a form validator with no fields.
+    pub fn new() -> Self {
+        FormValidator { fields: Vec::new() }
+    }
+
+    /// Builder-style method to add a field, reached from the form's data
+    /// through `lens`.
+    pub fn field<U: 'static>(
+        mut self,
+        lens: impl Lens<T, U> + 'static,
+        validator: Validator<U>,
+    ) -> Self {
+        self.fields
+            .push(Arc::new(move |data| validator.validate(lens.get(data))));
+        self
+    }
+
+    /// The error messages of all the invalid fields.
+    pub fn errors(&self, data: &T) -> Vec<String> {
+        self.fields
+            .iter()
+            .filter_map(|field| field(data).err())
+            .collect()
+    }
+
+    /// Returns `true` if every field is valid.
+    ///
+    /// This can be passed to [`Button::disabled_if`] to disable a form's
+    /// submit button until it is complete.
+    ///
+    /// [`Button::disabled_if`]: widget/struct.Button.html#method.disabled_if
+    pub fn is_valid(&self, data: &T) -> bool {
+        self.fields.iter().all(|field| field(data).is_ok())
+    }
+}
+
+impl<T> Clone for Validator<T> {
+    fn clone(&self) -> Self {
+        Validator {
+            rules: self.rules.clone(),
+        }
+    }
+}
+
+impl<T> Clone for FormValidator<T> {
+    fn clone(&self) -> Self {
+        FormValidator {
+            fields: self.fields.clone(),
+        }
+    }
+}
+
+impl<T: 'static> Default for Validator<T> {
+    fn default() -> Self {
+        Validator::new()
+    }
+}
+
+impl<T: 'static> Default for FormValidator<T> {
+    fn default() -> Self {
+        FormValidator::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Signup {
+        name: String,
+        age: f64,
+    }
+
+    struct Name;
+    struct Age;
+
+    impl Lens<Signup, String> for Name {
+        fn get<'a>(&self, data: &'a Signup) -> &'a String {
+            &data.name
+        }
+        fn with_mut<V, F: FnOnce(&mut String) -> V>(&self, data: &mut Signup, f: F) -> V {
+            f(&mut data.name)
+        }
+    }
+
+    impl Lens<Signup, f64> for Age {
+        fn get<'a>(&self, data: &'a Signup) -> &'a f64 {
+            &data.age
+        }
+        fn with_mut<V, F: FnOnce(&mut f64) -> V>(&self, data: &mut Signup, f: F) -> V {
+            f(&mut data.age)
+        }
+    }
+
+    #[test]
+    fn form() {
+        let form = FormValidator::new()
+            .field(Name, Validator::required("name").max_length(4, "short"))
+            .field(Age, Validator::range(18.0, 130.0, "age"));
+        let mut signup = Signup {
+            name: " ".into(),
+            age: 12.0,
+        };
+        assert_eq!(form.errors(&signup), ["name", "age"]);
+        signup.name = "Alexandra".into();
+        signup.age = 30.0;
+        assert_eq!(form.errors(&signup), ["short"]);
+        signup.name = "Alex".into();
+        assert!(form.is_valid(&signup));
+    }
+}
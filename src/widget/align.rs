@@ -19,6 +19,7 @@ use crate::{
     UpdateCtx, Widget, WidgetPod,
 };
 
+use crate::bidi;
 use crate::piet::UnitPoint;
 
 /// A widget that aligns its child.
@@ -33,8 +34,10 @@ impl<T: Data> Align<T> {
     /// Create widget with alignment.
     ///
     /// Note that the `align` parameter is specified as a `UnitPoint` in
-    /// terms of left and right. This is inadequate for bidi-aware layout
-    /// and thus the API will change when druid gains bidi capability.
+    /// terms of left and right, for a left-to-right layout; it is mirrored
+    /// when [`RIGHT_TO_LEFT`] is set in the environment.
+    ///
+    /// [`RIGHT_TO_LEFT`]: ../theme/constant.RIGHT_TO_LEFT.html
     pub fn new(align: UnitPoint, child: impl Widget<T> + 'static) -> Align<T> {
         Align {
             align,
@@ -111,9 +114,12 @@ impl<T: Data> Widget<T> for Align<T> {
         my_size = bc.constrain(my_size);
         let extra_width = (my_size.width - size.width).max(0.);
         let extra_height = (my_size.height - size.height).max(0.);
-        let origin = self
-            .align
-            .resolve(Rect::new(0., 0., extra_width, extra_height));
+        let origin = bidi::mirror_align(self.align, bidi::is_rtl(env)).resolve(Rect::new(
+            0.,
+            0.,
+            extra_width,
+            extra_height,
+        ));
         self.child
             .set_layout_rect(Rect::from_origin_size(origin, size));
         my_size
@@ -0,0 +1,124 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget that smooths changes to its child's data.
+
+use crate::kurbo::{Point, Rect, Size};
+use crate::theme;
+use crate::{
+    Animator, BaseState, BoxConstraints, Data, Env, Event, EventCtx, Interpolate, LayoutCtx,
+    PaintCtx, Transition, UpdateCtx, Widget, WidgetPod,
+};
+
+/// A widget that animates its child's data between values.
+///
+/// When the data changes, the child doesn't see the new value right away:
+/// it is shown the intermediate values of the [`Transition`] instead. A
+/// [`ProgressBar`] wrapped in `Animated` glides from one value to the next,
+/// rather than jumping.
+///
+/// The intermediate values are only seen in `layout` and `paint`; the
+/// child's `update` is called with the new value. If the child changes the
+/// data itself, as a slider does, the change is written through and the
+/// animation stops.
+///
+/// Changes are immediate when [`theme::REDUCED_MOTION`] is set.
+///
+/// [`Transition`]: ../struct.Transition.html
+/// [`ProgressBar`]: struct.ProgressBar.html
+/// [`theme::REDUCED_MOTION`]: ../theme/constant.REDUCED_MOTION.html
+pub struct Animated<T: Data + Interpolate> {
+    child: WidgetPod<T, Box<dyn Widget<T>>>,
+    transition: Transition,
+    animator: Option<Animator<T>>,
+}
+
+impl<T: Data + Interpolate> Animated<T> {
+    /// Wrap a widget, animating changes to its data.
+    pub fn new(child: impl Widget<T> + 'static) -> Self {
+        Animated {
+            child: WidgetPod::new(child).boxed(),
+            transition: Transition::default(),
+            animator: None,
+        }
+    }
+
+    /// Set the transition used when the data changes.
+    pub fn transition(mut self, transition: Transition) -> Self {
+        self.transition = transition;
+        self
+    }
+
+    /// The value the child is shown.
+    fn shown(&self, data: &T) -> T {
+        match self.animator.as_ref() {
+            Some(animator) => animator.value(),
+            None => data.clone(),
+        }
+    }
+}
+
+impl<T: Data + Interpolate> Widget<T> for Animated<T> {
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, _base_state: &BaseState, data: &T, env: &Env) {
+        let shown = self.shown(data);
+        self.child.paint(paint_ctx, &shown, env);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+        let shown = self.shown(data);
+        let size = self.child.layout(ctx, bc, &shown, env);
+        self.child
+            .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+        size
+    }
+
+    fn event(&mut self, event: &Event, ctx: &mut EventCtx, data: &mut T, env: &Env) {
+        if let Event::AnimFrame(interval) = event {
+            if let Some(animator) = self.animator.as_mut() {
+                if animator.is_running() {
+                    if animator.advance(*interval) {
+                        ctx.request_anim_frame();
+                    }
+                    ctx.invalidate();
+                }
+            }
+        }
+        let before = self.shown(data);
+        let mut shown = before.clone();
+        self.child.event(event, ctx, &mut shown, env);
+        if !shown.same(&before) {
+            *data = shown.clone();
+            if let Some(animator) = self.animator.as_mut() {
+                animator.jump_to(shown);
+            }
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: Option<&T>, data: &T, env: &Env) {
+        let transition = self.transition;
+        let animator = self
+            .animator
+            .get_or_insert_with(|| Animator::new(data.clone(), transition));
+        if !animator.target().same(data) {
+            if env.get(theme::REDUCED_MOTION) {
+                animator.jump_to(data.clone());
+            } else {
+                animator.animate_to(data.clone());
+                ctx.request_anim_frame();
+            }
+            ctx.invalidate();
+        }
+        self.child.update(ctx, data, env);
+    }
+}
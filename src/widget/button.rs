@@ -16,18 +16,21 @@
 
 use std::marker::PhantomData;
 
+use log::error;
+
 use crate::{
     BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx, Size, UpdateCtx,
     Widget,
 };
 
 use crate::kurbo::{Rect, RoundedRect};
-use crate::piet::{
-    FontBuilder, LinearGradient, PietText, PietTextLayout, Text, TextLayout, TextLayoutBuilder,
-    UnitPoint,
-};
+use crate::piet::{LinearGradient, PietText, UnitPoint};
+use crate::unicode_segmentation::UnicodeSegmentation;
 
+use crate::bidi;
+use crate::focus;
 use crate::localization::LocalizedString;
+use crate::text::{FontSet, LineLayout, TextAlignment};
 use crate::theme;
 use crate::widget::{Align, SizedBox};
 use crate::{Point, RenderContext};
@@ -38,10 +41,45 @@ pub enum LabelText<T> {
     Specific(String),
 }
 
+/// How a [`Label`] handles text that is wider than the space it is given.
+///
+/// [`Label`]: struct.Label.html
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineBreaking {
+    /// The text is drawn on a single line, and may overflow the label.
+    Overflow,
+    /// The text is drawn on a single line, and clipped to the label.
+    Clip,
+    /// The text is drawn on a single line, and cut short with an ellipsis
+    /// if it doesn't fit.
+    Truncate,
+    /// The text is broken into lines between words, so that each line fits
+    /// in the label's width. Newlines in the text always start a new line.
+    WordWrap,
+}
+
+/// The character used to show that text has been cut short.
+const ELLIPSIS: &str = "\u{2026}";
+
 /// A label that displays some text.
 pub struct Label<T> {
     text: LabelText<T>,
     align: UnitPoint,
+    line_breaking: LineBreaking,
+    max_lines: Option<usize>,
+    text_alignment: TextAlignment,
+    /// The laid out lines, rebuilt on each layout.
+    lines: Vec<Line>,
+}
+
+/// A laid out line of a [`Label`].
+///
+/// [`Label`]: struct.Label.html
+struct Line {
+    layout: LineLayout,
+    /// For lines that are justified, each word and its offset from the
+    /// start of the line.
+    words: Vec<(f64, LineLayout)>,
 }
 
 /// A button with a text label.
@@ -49,6 +87,8 @@ pub struct Button<T> {
     label: Label<T>,
     /// A closure that will be invoked when the button is clicked.
     action: Box<dyn Fn(&mut EventCtx, &mut T, &Env)>,
+    /// A predicate deciding whether the button is disabled.
+    disabled_if: Option<Box<dyn Fn(&T, &Env) -> bool>>,
 }
 
 /// A label with dynamic text.
@@ -64,52 +104,209 @@ impl<T: Data> Label<T> {
     /// Discussion question: should this return Label or a wrapped
     /// widget (with WidgetPod)?
     pub fn new(text: impl Into<LabelText<T>>) -> Self {
-        Label {
-            text: text.into(),
-            align: UnitPoint::LEFT,
-        }
+        Label::aligned(text, UnitPoint::LEFT)
     }
 
     pub fn aligned(text: impl Into<LabelText<T>>, align: UnitPoint) -> Self {
         Label {
             text: text.into(),
             align,
+            line_breaking: LineBreaking::Overflow,
+            max_lines: None,
+            text_alignment: TextAlignment::Start,
+            lines: Vec::new(),
         }
     }
 
-    fn get_layout(&self, t: &mut PietText, env: &Env) -> PietTextLayout {
-        let font_name = env.get(theme::FONT_NAME);
-        let font_size = env.get(theme::TEXT_SIZE_NORMAL);
+    /// Set the alignment of lines relative to each other, for labels with
+    /// more than one line.
+    ///
+    /// The lines as a whole are positioned in the label according to its
+    /// `align` point. The default is [`TextAlignment::Start`], which is
+    /// the right edge in a right-to-left layout.
+    ///
+    /// [`TextAlignment::Start`]: ../enum.TextAlignment.html#variant.Start
+    pub fn text_alignment(mut self, alignment: TextAlignment) -> Self {
+        self.text_alignment = alignment;
+        self
+    }
+
+    /// Set how text that doesn't fit in the label's width is handled.
+    ///
+    /// The default is [`LineBreaking::Overflow`].
+    ///
+    /// [`LineBreaking::Overflow`]: enum.LineBreaking.html#variant.Overflow
+    pub fn line_breaking(mut self, line_breaking: LineBreaking) -> Self {
+        self.line_breaking = line_breaking;
+        self
+    }
+
+    /// Set the maximum number of lines shown when word wrapping.
+    ///
+    /// If the text needs more lines, the last one shown ends with an
+    /// ellipsis.
+    pub fn max_lines(mut self, max_lines: usize) -> Self {
+        self.max_lines = Some(max_lines.max(1));
+        self
+    }
+
+    /// Break the text into lines that fit in `width`, according to the
+    /// label's line breaking settings.
+    ///
+    /// Each line is paired with whether it ends a paragraph.
+    fn break_lines(&self, t: &mut PietText, fonts: &FontSet, width: f64) -> Vec<(String, bool)> {
         let text = self.text.display_text();
-        // TODO: caching of both the format and the layout
-        let font = t
-            .new_font_by_name(font_name, font_size)
-            .unwrap()
-            .build()
-            .unwrap();
-        t.new_text_layout(&font, text).unwrap().build().unwrap()
+        let mut lines = Vec::new();
+        match self.line_breaking {
+            LineBreaking::Overflow | LineBreaking::Clip => return vec![(text.to_owned(), true)],
+            LineBreaking::Truncate => return vec![(ellipsize(t, fonts, text, width, false), true)],
+            LineBreaking::WordWrap => {
+                for paragraph in text.split('\n') {
+                    let wrapped = wrap(t, fonts, paragraph, width);
+                    let count = wrapped.len();
+                    lines.extend(
+                        wrapped
+                            .into_iter()
+                            .enumerate()
+                            .map(|(i, line)| (line, i + 1 == count)),
+                    );
+                }
+            }
+        }
+        if let Some(max_lines) = self.max_lines {
+            if lines.len() > max_lines {
+                lines.truncate(max_lines);
+                let (last, _) = lines.pop().unwrap_or_default();
+                lines.push((ellipsize(t, fonts, &last, width, true), true));
+            }
+        }
+        lines
+    }
+
+    fn build_line(
+        &self,
+        t: &mut PietText,
+        fonts: &FontSet,
+        text: &str,
+        ends_paragraph: bool,
+        rtl: bool,
+    ) -> Line {
+        let text = &bidi::visual_text(text, rtl);
+        let layout = fonts.layout(t, text);
+        // The last line of a paragraph is never justified.
+        let words = if self.text_alignment == TextAlignment::Justified && !ends_paragraph {
+            text.split_word_bound_indices()
+                .filter(|(_, word)| !word.trim().is_empty())
+                .map(|(offset, word)| {
+                    let x = fonts.width(t, &text[..offset]);
+                    (x, fonts.layout(t, word))
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        Line { layout, words }
     }
 }
 
+/// Break a paragraph into lines no wider than `width`, between words.
+///
+/// A word that is wider than `width` on its own gets a line to itself.
+fn wrap(t: &mut PietText, fonts: &FontSet, paragraph: &str, width: f64) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in paragraph.split_word_bounds() {
+        let candidate = format!("{}{}", line, word);
+        if line.is_empty() || fonts.width(t, candidate.trim_end()) <= width {
+            line = candidate;
+        } else {
+            lines.push(line.trim_end().to_owned());
+            line = word.trim_start().to_owned();
+        }
+    }
+    lines.push(line.trim_end().to_owned());
+    lines
+}
+
+/// Cut `text` short with an ellipsis so that it fits in `width`.
+///
+/// If the text fits, it is returned unchanged, unless `force` is `true`.
+fn ellipsize(t: &mut PietText, fonts: &FontSet, text: &str, width: f64, force: bool) -> String {
+    if !force && fonts.width(t, text) <= width {
+        return text.to_owned();
+    }
+    // Find the longest prefix, ending on a grapheme boundary, that fits
+    // with the ellipsis.
+    let boundaries = text
+        .grapheme_indices(true)
+        .map(|(offset, _)| offset)
+        .chain(std::iter::once(text.len()))
+        .collect::<Vec<_>>();
+    let shortened = |end: usize| format!("{}{}", text[..end].trim_end(), ELLIPSIS);
+    let (mut lo, mut hi) = (0, boundaries.len() - 1);
+    while lo < hi {
+        let mid = (lo + hi + 1) / 2;
+        if fonts.width(t, &shortened(boundaries[mid])) <= width {
+            lo = mid;
+        } else {
+            hi = mid - 1;
+        }
+    }
+    shortened(boundaries[lo])
+}
+
 impl<T: Data> Widget<T> for Label<T> {
     fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, _data: &T, env: &Env) {
         let font_size = env.get(theme::TEXT_SIZE_NORMAL);
+        let line_height = font_size * 1.2;
+        let size = base_state.size();
+        let color = env.get(theme::LABEL_COLOR);
+
+        if self.line_breaking == LineBreaking::Clip {
+            if let Err(e) = paint_ctx.save() {
+                error!("saving render context failed: {:?}", e);
+                return;
+            }
+            paint_ctx.clip(Rect::from_origin_size(Point::ORIGIN, size));
+        }
 
-        let text_layout = self.get_layout(paint_ctx.text(), env);
-
-        // Find the origin for the text
-        let mut origin = self.align.resolve(Rect::from_origin_size(
-            Point::ORIGIN,
-            Size::new(
-                (base_state.size().width - text_layout.width()).max(0.0),
-                base_state.size().height + (font_size * 1.2) / 2.,
-            ),
+        // The lines are positioned as a block, and then each line is
+        // aligned within the block.
+        let block_width = self
+            .lines
+            .iter()
+            .map(|line| line.layout.width())
+            .fold(0.0, f64::max);
+        let block_height = line_height * self.lines.len() as f64;
+        let rtl = bidi::is_rtl(env);
+        let block_origin = bidi::mirror_align(self.align, rtl).resolve(Rect::new(
+            0.,
+            0.,
+            (size.width - block_width).max(0.0),
+            size.height - block_height,
         ));
+        for (i, line) in self.lines.iter().enumerate() {
+            let line_width = line.layout.width();
+            // The baseline leaves room for descenders below it.
+            let y = block_origin.y + line_height * i as f64 + font_size * 0.9;
+            if line.words.len() > 1 {
+                let gap = (block_width - line_width) / (line.words.len() - 1) as f64;
+                for (j, (x, word)) in line.words.iter().enumerate() {
+                    let x = block_origin.x + x + gap * j as f64;
+                    word.draw(&mut **paint_ctx, (x, y), &color);
+                }
+            } else {
+                let alignment = self.text_alignment.for_direction(rtl);
+                let x = block_origin.x + alignment.offset(line_width, block_width);
+                line.layout.draw(&mut **paint_ctx, (x, y), &color);
+            }
+        }
 
-        //Make sure we don't draw the text too low
-        origin.y = origin.y.min(base_state.size().height);
-
-        paint_ctx.draw_text(&text_layout, origin, &env.get(theme::LABEL_COLOR));
+        if self.line_breaking == LineBreaking::Clip {
+            if let Err(e) = paint_ctx.restore() {
+                error!("restoring render context failed: {:?}", e);
+            }
+        }
     }
 
     fn layout(
@@ -120,9 +317,23 @@ impl<T: Data> Widget<T> for Label<T> {
         env: &Env,
     ) -> Size {
         let font_size = env.get(theme::TEXT_SIZE_NORMAL);
-        let text_layout = self.get_layout(layout_ctx.text(), env);
+        let t = layout_ctx.text();
+        // TODO: caching of both the format and the layout
+        let fonts = FontSet::from_env(t, env);
+        let rtl = bidi::is_rtl(env);
+        let lines = self.break_lines(t, &fonts, bc.max().width);
+        self.lines = lines
+            .iter()
+            .map(|(line, ends_paragraph)| self.build_line(t, &fonts, line, *ends_paragraph, rtl))
+            .collect();
+
+        let width = self
+            .lines
+            .iter()
+            .map(|line| line.layout.width())
+            .fold(0.0, f64::max);
         // This magical 1.2 constant helps center the text vertically in the rect it's given
-        bc.constrain((text_layout.width(), font_size * 1.2))
+        bc.constrain((width, font_size * 1.2 * self.lines.len() as f64))
     }
 
     fn event(&mut self, _event: &Event, _ctx: &mut EventCtx, _data: &mut T, _env: &Env) {}
@@ -144,6 +355,7 @@ impl<T: Data + 'static> Button<T> {
         Button {
             label: Label::aligned(text, UnitPoint::CENTER),
             action: Box::new(action),
+            disabled_if: None,
         }
     }
 
@@ -159,12 +371,26 @@ impl<T: Data + 'static> Button<T> {
             SizedBox::new(Button {
                 label: Label::aligned(text, UnitPoint::CENTER),
                 action: Box::new(action),
+                disabled_if: None,
             })
             .width(width)
             .height(height),
         )
     }
 
+    /// Provide a predicate deciding whether the button is disabled.
+    ///
+    /// A disabled button is drawn with the theme's [`DISABLED_LABEL_COLOR`],
+    /// and ignores clicks. This is useful with [`FormValidator::is_valid`],
+    /// to keep a form from being submitted while it is incomplete.
+    ///
+    /// [`DISABLED_LABEL_COLOR`]: ../theme/constant.DISABLED_LABEL_COLOR.html
+    /// [`FormValidator::is_valid`]: ../struct.FormValidator.html#method.is_valid
+    pub fn disabled_if(mut self, disabled: impl Fn(&T, &Env) -> bool + 'static) -> Self {
+        self.disabled_if = Some(Box::new(disabled));
+        self
+    }
+
     /// A function that can be passed to `Button::new`, for buttons with no action.
     ///
     /// # Examples
@@ -177,38 +403,96 @@ impl<T: Data + 'static> Button<T> {
     pub fn noop(_: &mut EventCtx, _: &mut T, _: &Env) {}
 }
 
+impl<T: Data> Button<T> {
+    fn is_disabled(&self, data: &T, env: &Env) -> bool {
+        self.disabled_if
+            .as_ref()
+            .map(|disabled| disabled(data, env))
+            .unwrap_or(false)
+    }
+}
+
 impl<T: Data> Widget<T> for Button<T> {
     fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env) {
         let is_active = base_state.is_active();
         let is_hot = base_state.is_hot();
 
-        let rounded_rect =
-            RoundedRect::from_origin_size(Point::ORIGIN, base_state.size().to_vec2(), 4.);
-        let bg_gradient = if is_active {
-            LinearGradient::new(
+        let border_width = env.get(theme::BUTTON_BORDER_WIDTH);
+        let rounded_rect = RoundedRect::from_origin_size(
+            Point::ORIGIN,
+            base_state.size().to_vec2(),
+            env.get(theme::BUTTON_CORNER_RADIUS),
+        );
+        let themed = if is_active {
+            env.try_get(theme::BUTTON_ACTIVE_BACKGROUND)
+        } else if is_hot {
+            env.try_get(theme::BUTTON_HOT_BACKGROUND)
+                .or_else(|| env.try_get(theme::BUTTON_BACKGROUND))
+        } else {
+            env.try_get(theme::BUTTON_BACKGROUND)
+        };
+        let background = match themed {
+            Some(background) => background.clone(),
+            None if is_active => LinearGradient::new(
                 UnitPoint::TOP,
                 UnitPoint::BOTTOM,
                 (env.get(theme::BUTTON_LIGHT), env.get(theme::BUTTON_DARK)),
             )
-        } else {
-            LinearGradient::new(
+            .into(),
+            None => LinearGradient::new(
                 UnitPoint::TOP,
                 UnitPoint::BOTTOM,
                 (env.get(theme::BUTTON_DARK), env.get(theme::BUTTON_LIGHT)),
             )
+            .into(),
         };
 
-        let border_color = if is_hot {
-            env.get(theme::BORDER_LIGHT)
+        let normal_border = || {
+            env.try_get(theme::BUTTON_BORDER_COLOR)
+                .unwrap_or_else(|| env.get(theme::BORDER))
+        };
+        let hot_border = || {
+            env.try_get(theme::BUTTON_HOT_BORDER_COLOR)
+                .unwrap_or_else(|| env.get(theme::BORDER_LIGHT))
+        };
+        let border_color = if is_active {
+            env.try_get(theme::BUTTON_ACTIVE_BORDER_COLOR)
+                .unwrap_or_else(hot_border)
+        } else if is_hot {
+            hot_border()
         } else {
-            env.get(theme::BORDER)
+            normal_border()
         };
 
-        paint_ctx.stroke(rounded_rect, &border_color, 2.0);
+        if border_width > 0.0 {
+            paint_ctx.stroke(rounded_rect, &border_color, border_width);
+        }
 
-        paint_ctx.fill(rounded_rect, &bg_gradient);
+        background.paint(paint_ctx, rounded_rect);
+        paint_ctx.paint_focus_ring(base_state, rounded_rect, env);
 
-        self.label.paint(paint_ctx, base_state, data, env);
+        let text_color = if self.is_disabled(data, env) {
+            env.get(theme::DISABLED_LABEL_COLOR)
+        } else {
+            let normal_text = || {
+                env.try_get(theme::BUTTON_TEXT_COLOR)
+                    .unwrap_or_else(|| env.get(theme::LABEL_COLOR))
+            };
+            let hot_text = || {
+                env.try_get(theme::BUTTON_HOT_TEXT_COLOR)
+                    .unwrap_or_else(normal_text)
+            };
+            if is_active {
+                env.try_get(theme::BUTTON_ACTIVE_TEXT_COLOR)
+                    .unwrap_or_else(hot_text)
+            } else if is_hot {
+                hot_text()
+            } else {
+                normal_text()
+            }
+        };
+        let env = env.clone().adding(theme::LABEL_COLOR, text_color);
+        self.label.paint(paint_ctx, base_state, data, &env);
     }
 
     fn layout(
@@ -218,15 +502,41 @@ impl<T: Data> Widget<T> for Button<T> {
         data: &T,
         env: &Env,
     ) -> Size {
-        self.label.layout(layout_ctx, bc, data, env)
+        let padding = Size::new(
+            env.get(theme::BUTTON_HORIZONTAL_PADDING) * 2.0,
+            env.get(theme::BUTTON_VERTICAL_PADDING) * 2.0,
+        );
+        let shrink = |size: Size| {
+            Size::new(
+                (size.width - padding.width).max(0.0),
+                (size.height - padding.height).max(0.0),
+            )
+        };
+        // The label is centered, so it is painted across the whole button,
+        // and the padding only needs to be taken out of its constraints.
+        let label_bc = BoxConstraints::new(shrink(bc.min()), shrink(bc.max()));
+        let label_size = self.label.layout(layout_ctx, &label_bc, data, env);
+        let min_size = env.get(theme::BUTTON_MIN_SIZE);
+        bc.constrain((
+            (label_size.width + padding.width).max(min_size.width),
+            (label_size.height + padding.height).max(min_size.height),
+        ))
     }
 
     fn event(&mut self, event: &Event, ctx: &mut EventCtx, data: &mut T, env: &Env) {
         match event {
+            Event::MouseDown(_) if self.is_disabled(data, env) => (),
             Event::MouseDown(_) => {
                 ctx.set_active(true);
+                ctx.request_focus();
                 ctx.invalidate();
             }
+            Event::KeyDown(key) if focus::is_activation_key(key) => {
+                if !self.is_disabled(data, env) {
+                    (self.action)(ctx, data, env);
+                }
+                ctx.set_handled();
+            }
             Event::MouseUp(_) => {
                 if ctx.is_active() {
                     ctx.set_active(false);
@@ -243,7 +553,15 @@ impl<T: Data> Widget<T> for Button<T> {
         }
     }
 
+    fn accepts_focus(&self) -> bool {
+        true
+    }
+
     fn update(&mut self, ctx: &mut UpdateCtx, old_data: Option<&T>, data: &T, env: &Env) {
+        if self.disabled_if.is_some() {
+            // The disabled state may have changed along with the data.
+            ctx.invalidate();
+        }
         self.label.update(ctx, old_data, data, env)
     }
 }
@@ -256,19 +574,11 @@ impl<T: Data, F: FnMut(&T, &Env) -> String> DynLabel<T, F> {
         }
     }
 
-    fn get_layout(&mut self, t: &mut PietText, env: &Env, data: &T) -> PietTextLayout {
+    fn get_layout(&mut self, t: &mut PietText, env: &Env, data: &T) -> LineLayout {
         let text = (self.label_closure)(data, env);
 
-        let font_name = env.get(theme::FONT_NAME);
-        let font_size = env.get(theme::TEXT_SIZE_NORMAL);
-
         // TODO: caching of both the format and the layout
-        let font = t
-            .new_font_by_name(font_name, font_size)
-            .unwrap()
-            .build()
-            .unwrap();
-        t.new_text_layout(&font, &text).unwrap().build().unwrap()
+        FontSet::from_env(t, env).layout(t, &text)
     }
 }
 
@@ -286,7 +596,7 @@ impl<T: Data, F: FnMut(&T, &Env) -> String> Widget<T> for DynLabel<T, F> {
         ));
 
         let text_layout = self.get_layout(paint_ctx.text(), env, data);
-        paint_ctx.draw_text(&text_layout, origin, &env.get(theme::LABEL_COLOR));
+        text_layout.draw(&mut **paint_ctx, origin, &env.get(theme::LABEL_COLOR));
     }
 
     fn layout(
@@ -347,3 +657,56 @@ impl<T> From<LocalizedString<T>> for LabelText<T> {
         LabelText::Localized(src)
     }
 }
+
+#[cfg(all(test, not(any(target_os = "windows", target_arch = "wasm32"))))]
+mod tests {
+    use super::*;
+    use crate::piet::Color;
+    use crate::{Harness, Probe};
+
+    const FILL: Color = Color::rgb8(0x20, 0x40, 0x60);
+    const HOT_FILL: Color = Color::rgb8(0x60, 0x40, 0x20);
+
+    /// A centered button, themed with a minimum size and plain fills.
+    fn themed_button() -> Harness<u32> {
+        let mut harness = Harness::new(
+            || Align::centered(Probe::new("button", Button::new("go", Button::noop))),
+            0,
+        );
+        {
+            let mut state = harness.app().state().borrow_mut();
+            state.env = state
+                .env
+                .clone()
+                .adding(theme::BUTTON_MIN_SIZE, Size::new(120.0, 40.0))
+                .adding(theme::BUTTON_BACKGROUND, FILL)
+                .adding(theme::BUTTON_HOT_BACKGROUND, HOT_FILL);
+        }
+        harness.render();
+        harness
+    }
+
+    fn rgba(color: &Color) -> [u8; 4] {
+        let c = color.as_rgba_u32();
+        [(c >> 24) as u8, (c >> 16) as u8, (c >> 8) as u8, c as u8]
+    }
+
+    #[test]
+    fn min_size_from_theme() {
+        let harness = themed_button();
+        let rect = harness.layout_rect("button").unwrap();
+        assert_eq!(rect.size(), Size::new(120.0, 40.0));
+    }
+
+    #[test]
+    fn fill_from_theme() {
+        let mut harness = themed_button();
+        let rect = harness.layout_rect("button").unwrap();
+        // Well inside the border, and clear of the centered label.
+        let inside = Point::new(rect.x0 + 8.0, rect.center().y);
+        let (x, y) = (inside.x as usize, inside.y as usize);
+        assert_eq!(harness.render().pixel(x, y), rgba(&FILL));
+        harness.mouse_move(inside);
+        assert_eq!(harness.render().pixel(x, y), rgba(&HOT_FILL));
+    }
+}
@@ -14,6 +14,7 @@
 
 //! A checkbox widget.
 
+use crate::focus;
 use crate::kurbo::{BezPath, Point, RoundedRect, Size};
 use crate::piet::{LineCap, LineJoin, LinearGradient, RenderContext, StrokeStyle, UnitPoint};
 use crate::theme;
@@ -61,6 +62,7 @@ impl Widget<bool> for CheckboxRaw {
         };
 
         paint_ctx.stroke(rect, &border_color, 1.);
+        paint_ctx.paint_focus_ring(base_state, rect, env);
 
         if *data {
             let mut path = BezPath::new();
@@ -93,8 +95,14 @@ impl Widget<bool> for CheckboxRaw {
         match event {
             Event::MouseDown(_) => {
                 ctx.set_active(true);
+                ctx.request_focus();
                 ctx.invalidate();
             }
+            Event::KeyDown(key) if focus::is_activation_key(key) => {
+                *data = !*data;
+                ctx.invalidate();
+                ctx.set_handled();
+            }
             Event::MouseUp(_) => {
                 if ctx.is_active() {
                     ctx.set_active(false);
@@ -115,7 +123,34 @@ impl Widget<bool> for CheckboxRaw {
         }
     }
 
+    fn accepts_focus(&self) -> bool {
+        true
+    }
+
     fn update(&mut self, ctx: &mut UpdateCtx, _old_data: Option<&bool>, _data: &bool, _env: &Env) {
         ctx.invalidate();
     }
 }
+
+#[cfg(all(test, not(any(target_os = "windows", target_arch = "wasm32"))))]
+mod tests {
+    use super::*;
+    use crate::{Harness, KeyCode, KeyEvent, KeyModifiers};
+
+    #[test]
+    fn space_toggles_checkbox() {
+        let mut harness = Harness::new(Checkbox::new, false);
+        let none = KeyModifiers::default();
+        let space = KeyEvent::for_test(none, " ", KeyCode::Space);
+
+        // Nothing has focus yet.
+        assert!(!harness.key_down(space));
+        assert!(!harness.data());
+
+        harness.key_down(KeyEvent::for_test(none, "\t", KeyCode::Tab));
+        assert!(harness.key_down(space));
+        assert!(harness.data());
+        assert!(harness.key_down(space));
+        assert!(!harness.data());
+    }
+}
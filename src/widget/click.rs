@@ -0,0 +1,145 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget that makes its child clickable.
+
+use crate::kurbo::{Point, Rect, Size};
+use crate::{
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx, UpdateCtx, Widget,
+    WidgetPod,
+};
+
+use crate::focus;
+
+/// A widget that calls a closure when its child is clicked.
+///
+/// A click is a press of the mouse button on the child followed by a
+/// release while the mouse is still over it; the widget is active between
+/// the two, so it receives the release even if the mouse has left it. The
+/// widget also accepts keyboard focus, and the activation keys click it.
+///
+/// Events are passed to the child first, and the child can keep them from
+/// causing a click by marking them handled.
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::{Click, Label};
+///
+/// let link = Click::new(Label::new("Increment"), |_ctx, data: &mut u32, _env| {
+///     *data += 1;
+/// });
+/// ```
+pub struct Click<T: Data> {
+    child: WidgetPod<T, Box<dyn Widget<T>>>,
+    action: Box<dyn Fn(&mut EventCtx, &mut T, &Env)>,
+}
+
+impl<T: Data> Click<T> {
+    /// Create a widget that calls `action` when `child` is clicked.
+    pub fn new(
+        child: impl Widget<T> + 'static,
+        action: impl Fn(&mut EventCtx, &mut T, &Env) + 'static,
+    ) -> Self {
+        Click {
+            child: WidgetPod::new(child).boxed(),
+            action: Box::new(action),
+        }
+    }
+}
+
+impl<T: Data> Widget<T> for Click<T> {
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env) {
+        self.child.paint(paint_ctx, data, env);
+        let bounds = Rect::from_origin_size(Point::ORIGIN, base_state.size());
+        paint_ctx.paint_focus_ring(base_state, bounds, env);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+        let size = self.child.layout(ctx, bc, data, env);
+        self.child
+            .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+        size
+    }
+
+    fn event(&mut self, event: &Event, ctx: &mut EventCtx, data: &mut T, env: &Env) {
+        self.child.event(event, ctx, data, env);
+        if ctx.is_handled() {
+            return;
+        }
+        match event {
+            Event::MouseDown(_) => {
+                ctx.set_active(true);
+                ctx.request_focus();
+                ctx.invalidate();
+            }
+            Event::MouseUp(_) => {
+                if ctx.is_active() {
+                    ctx.set_active(false);
+                    ctx.invalidate();
+                    if ctx.is_hot() {
+                        (self.action)(ctx, data, env);
+                    }
+                }
+            }
+            Event::KeyDown(key) if focus::is_activation_key(key) => {
+                (self.action)(ctx, data, env);
+                ctx.set_handled();
+            }
+            _ => (),
+        }
+    }
+
+    fn accepts_focus(&self) -> bool {
+        true
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: Option<&T>, data: &T, env: &Env) {
+        self.child.update(ctx, data, env);
+    }
+}
+
+#[cfg(all(test, not(any(target_os = "windows", target_arch = "wasm32"))))]
+mod tests {
+    use super::*;
+    use crate::widget::{Padding, SizedBox};
+    use crate::{Harness, KeyCode, KeyEvent, KeyModifiers, Probe};
+
+    #[test]
+    fn click_wrapper() {
+        let mut harness = Harness::new(
+            || {
+                let target = SizedBox::empty().width(100.0).height(100.0);
+                let click = Click::new(target, |_ctx, data: &mut u32, _env| *data += 1);
+                Padding::uniform(10.0, Probe::new("target", click))
+            },
+            0u32,
+        );
+        let rect = harness.layout_rect("target").unwrap();
+        harness.click(rect.center());
+        assert_eq!(harness.data(), 1);
+
+        // Releasing outside the widget doesn't click it.
+        harness.mouse_move(rect.center());
+        harness.mouse_down(rect.center());
+        harness.mouse_move(Point::new(5.0, 5.0));
+        harness.mouse_up(Point::new(5.0, 5.0));
+        assert_eq!(harness.data(), 1);
+
+        // It took focus when pressed, so the space bar clicks it.
+        let space = KeyEvent::for_test(KeyModifiers::default(), " ", KeyCode::Space);
+        assert!(harness.key_down(space));
+        assert_eq!(harness.data(), 2);
+    }
+}
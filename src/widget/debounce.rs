@@ -0,0 +1,210 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Widgets that limit how often an action runs in response to data changes.
+
+use std::any::Any;
+use std::time::{Duration, Instant};
+
+use crate::kurbo::{Point, Rect, Size};
+use crate::{
+    BaseState, BoxConstraints, Command, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx, Selector,
+    TimerToken, UpdateCtx, Widget, WidgetPod,
+};
+
+type Action<T> = Box<dyn Fn(&mut EventCtx, &mut T, &Env)>;
+
+/// A widget that runs an action once its child's data has stopped changing.
+///
+/// Each time the child changes its data, a timer is restarted; the action
+/// runs when the timer fires. This is useful for things like a search box,
+/// where the search should only start once the user has paused typing.
+pub struct Debounce<T: Data> {
+    child: WidgetPod<T, Box<dyn Widget<T>>>,
+    delay: Duration,
+    action: Action<T>,
+    timer: TimerToken,
+}
+
+/// A widget that runs an action at most once per interval while its child's
+/// data is changing.
+///
+/// The first change runs the action immediately. Further changes within
+/// the interval are coalesced, and run the action once when it ends.
+pub struct Throttle<T: Data> {
+    child: WidgetPod<T, Box<dyn Widget<T>>>,
+    interval: Duration,
+    action: Action<T>,
+    timer: TimerToken,
+    pending: bool,
+}
+
+/// An action that submits the data as the argument of a command.
+fn submit_data<T: Data + Any>(selector: Selector) -> Action<T> {
+    Box::new(move |ctx, data, _env| {
+        ctx.submit_command(Command::new(selector.clone(), data.clone()), None)
+    })
+}
+
+impl<T: Data> Debounce<T> {
+    /// Create a new debouncing widget.
+    ///
+    /// The action runs `delay` after the last change to the child's data.
+    pub fn new(
+        child: impl Widget<T> + 'static,
+        delay: Duration,
+        action: impl Fn(&mut EventCtx, &mut T, &Env) + 'static,
+    ) -> Self {
+        Debounce {
+            child: WidgetPod::new(child).boxed(),
+            delay,
+            action: Box::new(action),
+            timer: TimerToken::INVALID,
+        }
+    }
+
+    /// Create a widget that submits a command, with the data as its
+    /// argument, `delay` after the last change to the child's data.
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::time::Duration;
+    /// # use druid::Selector;
+    /// # use druid::widget::{Debounce, TextBox};
+    /// const SEARCH: Selector = Selector::new("my-app.search");
+    ///
+    /// let search_box = Debounce::command(TextBox::new(), Duration::from_millis(300), SEARCH);
+    /// ```
+    pub fn command(child: impl Widget<T> + 'static, delay: Duration, selector: Selector) -> Self
+    where
+        T: Any,
+    {
+        Debounce {
+            child: WidgetPod::new(child).boxed(),
+            delay,
+            action: submit_data(selector),
+            timer: TimerToken::INVALID,
+        }
+    }
+}
+
+impl<T: Data> Throttle<T> {
+    /// Create a new throttling widget.
+    pub fn new(
+        child: impl Widget<T> + 'static,
+        interval: Duration,
+        action: impl Fn(&mut EventCtx, &mut T, &Env) + 'static,
+    ) -> Self {
+        Throttle {
+            child: WidgetPod::new(child).boxed(),
+            interval,
+            action: Box::new(action),
+            timer: TimerToken::INVALID,
+            pending: false,
+        }
+    }
+
+    /// Create a widget that submits a command, with the data as its
+    /// argument, at most once per interval while the child's data changes.
+    pub fn command(child: impl Widget<T> + 'static, interval: Duration, selector: Selector) -> Self
+    where
+        T: Any,
+    {
+        Throttle {
+            child: WidgetPod::new(child).boxed(),
+            interval,
+            action: submit_data(selector),
+            timer: TimerToken::INVALID,
+            pending: false,
+        }
+    }
+
+    fn fire(&mut self, ctx: &mut EventCtx, data: &mut T, env: &Env) {
+        (self.action)(ctx, data, env);
+        self.timer = ctx.request_timer(Instant::now() + self.interval);
+    }
+}
+
+impl<T: Data> Widget<T> for Debounce<T> {
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, _base_state: &BaseState, data: &T, env: &Env) {
+        self.child.paint(paint_ctx, data, env);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+        let size = self.child.layout(ctx, bc, data, env);
+        self.child
+            .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+        size
+    }
+
+    fn event(&mut self, event: &Event, ctx: &mut EventCtx, data: &mut T, env: &Env) {
+        match event {
+            Event::Timer(token) if *token == self.timer => {
+                self.timer = TimerToken::INVALID;
+                (self.action)(ctx, data, env);
+            }
+            _ => {
+                let old_data = data.clone();
+                self.child.event(event, ctx, data, env);
+                if !old_data.same(data) {
+                    self.timer = ctx.request_timer(Instant::now() + self.delay);
+                }
+            }
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: Option<&T>, data: &T, env: &Env) {
+        self.child.update(ctx, data, env);
+    }
+}
+
+impl<T: Data> Widget<T> for Throttle<T> {
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, _base_state: &BaseState, data: &T, env: &Env) {
+        self.child.paint(paint_ctx, data, env);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+        let size = self.child.layout(ctx, bc, data, env);
+        self.child
+            .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+        size
+    }
+
+    fn event(&mut self, event: &Event, ctx: &mut EventCtx, data: &mut T, env: &Env) {
+        match event {
+            Event::Timer(token) if *token == self.timer => {
+                self.timer = TimerToken::INVALID;
+                if self.pending {
+                    self.pending = false;
+                    self.fire(ctx, data, env);
+                }
+            }
+            _ => {
+                let old_data = data.clone();
+                self.child.event(event, ctx, data, env);
+                if !old_data.same(data) {
+                    if self.timer == TimerToken::INVALID {
+                        self.fire(ctx, data, env);
+                    } else {
+                        self.pending = true;
+                    }
+                }
+            }
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: Option<&T>, data: &T, env: &Env) {
+        self.child.update(ctx, data, env);
+    }
+}
@@ -0,0 +1,240 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Widgets that make their child a source or target of in-app drags.
+
+use std::any::Any;
+
+use crate::kurbo::{Point, Rect, Size};
+use crate::piet::RenderContext;
+use crate::theme;
+use crate::{
+    BaseState, BoxConstraints, Data, DropEffect, Env, Event, EventCtx, LayoutCtx, PaintCtx,
+    UpdateCtx, Widget, WidgetPod,
+};
+
+/// The distance the mouse must move, while pressed, before a drag begins.
+const DRAG_THRESHOLD: f64 = 4.0;
+
+/// A widget that lets its child be dragged to a [`DropTarget`].
+///
+/// When the child is pressed and then dragged beyond a small threshold,
+/// the payload closure is called with the current data; if it returns
+/// a value, an in-app drag carrying that value is started.
+///
+/// [`DropTarget`]: struct.DropTarget.html
+pub struct DragSource<T: Data, P> {
+    child: WidgetPod<T, Box<dyn Widget<T>>>,
+    make_payload: Box<dyn Fn(&T, &Env) -> Option<P>>,
+    on_drag_end: Option<Box<dyn Fn(&mut EventCtx, DropEffect, &mut T, &Env)>>,
+    press_pos: Option<Point>,
+}
+
+/// A widget that accepts in-app drags carrying a payload of type `P`.
+///
+/// While an acceptable drag hovers over the widget, it is highlighted
+/// with the [`DROP_TARGET_COLOR`] theme color. When the payload is dropped,
+/// the drop closure is called with the payload and the drop location, in
+/// the widget's coordinate space.
+///
+/// [`DROP_TARGET_COLOR`]: ../theme/constant.DROP_TARGET_COLOR.html
+pub struct DropTarget<T: Data, P> {
+    child: WidgetPod<T, Box<dyn Widget<T>>>,
+    accept: Box<dyn Fn(&P, &T) -> bool>,
+    on_drop: Box<dyn Fn(&mut EventCtx, &P, Point, &mut T, &Env)>,
+    effect: DropEffect,
+    is_drop_hot: bool,
+}
+
+impl<T: Data, P: Any> DragSource<T, P> {
+    /// Create a new drag source.
+    ///
+    /// The closure produces the payload for a drag; returning `None`
+    /// prevents the drag from starting.
+    pub fn new(
+        child: impl Widget<T> + 'static,
+        make_payload: impl Fn(&T, &Env) -> Option<P> + 'static,
+    ) -> Self {
+        DragSource {
+            child: WidgetPod::new(child).boxed(),
+            make_payload: Box::new(make_payload),
+            on_drag_end: None,
+            press_pos: None,
+        }
+    }
+
+    /// Provide a closure to be called when a drag from this widget finishes.
+    ///
+    /// The effect is the one accepted by the target; if it is
+    /// `DropEffect::Move`, this is the place to remove the dragged item.
+    pub fn on_drag_end(
+        mut self,
+        f: impl Fn(&mut EventCtx, DropEffect, &mut T, &Env) + 'static,
+    ) -> Self {
+        self.on_drag_end = Some(Box::new(f));
+        self
+    }
+}
+
+impl<T: Data, P: Any> DropTarget<T, P> {
+    /// Create a new drop target.
+    ///
+    /// The closure is called when an accepted payload is dropped on the
+    /// widget. By default, all payloads of type `P` are accepted.
+    pub fn new(
+        child: impl Widget<T> + 'static,
+        on_drop: impl Fn(&mut EventCtx, &P, Point, &mut T, &Env) + 'static,
+    ) -> Self {
+        DropTarget {
+            child: WidgetPod::new(child).boxed(),
+            accept: Box::new(|_, _| true),
+            on_drop: Box::new(on_drop),
+            effect: DropEffect::Move,
+            is_drop_hot: false,
+        }
+    }
+
+    /// Provide a predicate deciding whether a given payload is accepted.
+    pub fn accept_if(mut self, accept: impl Fn(&P, &T) -> bool + 'static) -> Self {
+        self.accept = Box::new(accept);
+        self
+    }
+
+    /// Set the effect reported to the drag source when accepting a drag.
+    ///
+    /// The default is `DropEffect::Move`.
+    pub fn effect(mut self, effect: DropEffect) -> Self {
+        self.effect = effect;
+        self
+    }
+}
+
+impl<T: Data, P: Any> Widget<T> for DragSource<T, P> {
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, _base_state: &BaseState, data: &T, env: &Env) {
+        self.child.paint(paint_ctx, data, env);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+        let size = self.child.layout(ctx, bc, data, env);
+        self.child
+            .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+        size
+    }
+
+    fn event(&mut self, event: &Event, ctx: &mut EventCtx, data: &mut T, env: &Env) {
+        self.child.event(event, ctx, data, env);
+        match event {
+            Event::MouseDown(mouse) if mouse.button.is_left() => {
+                self.press_pos = Some(mouse.pos);
+                ctx.set_active(true);
+            }
+            Event::MouseMoved(mouse) if ctx.is_active() => {
+                let exceeded = self
+                    .press_pos
+                    .map(|pos| (mouse.pos - pos).hypot() > DRAG_THRESHOLD)
+                    .unwrap_or(false);
+                if exceeded {
+                    self.press_pos = None;
+                    match (self.make_payload)(data, env) {
+                        Some(payload) => ctx.start_internal_drag(payload),
+                        None => ctx.set_active(false),
+                    }
+                }
+            }
+            Event::MouseUp(_) => {
+                self.press_pos = None;
+                ctx.set_active(false);
+            }
+            Event::DragEnd(effect) => {
+                if let Some(f) = self.on_drag_end.as_ref() {
+                    f(ctx, *effect, data, env);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: Option<&T>, data: &T, env: &Env) {
+        self.child.update(ctx, data, env);
+    }
+}
+
+impl<T: Data, P: Any> Widget<T> for DropTarget<T, P> {
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env) {
+        self.child.paint(paint_ctx, data, env);
+        if self.is_drop_hot {
+            let size = base_state.size();
+            let rect = Rect::from_origin_size(
+                Point::new(1.0, 1.0),
+                Size::new(size.width - 2.0, size.height - 2.0),
+            );
+            paint_ctx.stroke(rect, &env.get(theme::DROP_TARGET_COLOR), 2.0);
+        }
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+        let size = self.child.layout(ctx, bc, data, env);
+        self.child
+            .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+        size
+    }
+
+    fn event(&mut self, event: &Event, ctx: &mut EventCtx, data: &mut T, env: &Env) {
+        match event {
+            Event::DragEnter(drag) | Event::DragOver(drag) => {
+                let accepted = drag
+                    .payload::<P>()
+                    .map(|payload| (self.accept)(payload, data))
+                    .unwrap_or(false);
+                if accepted {
+                    ctx.set_drop_effect(self.effect);
+                }
+                if accepted != self.is_drop_hot {
+                    self.is_drop_hot = accepted;
+                    ctx.invalidate();
+                }
+                // A nested target gets the last word.
+                self.child.event(event, ctx, data, env);
+            }
+            Event::Drop(drag) => {
+                if self.is_drop_hot {
+                    self.is_drop_hot = false;
+                    ctx.invalidate();
+                }
+                self.child.event(event, ctx, data, env);
+                if ctx.is_handled() {
+                    return;
+                }
+                if let Some(payload) = drag.payload::<P>() {
+                    if (self.accept)(payload, data) {
+                        (self.on_drop)(ctx, payload, drag.pos, data, env);
+                        ctx.set_handled();
+                    }
+                }
+            }
+            Event::DragLeave => {
+                if self.is_drop_hot {
+                    self.is_drop_hot = false;
+                    ctx.invalidate();
+                }
+                self.child.event(event, ctx, data, env);
+            }
+            _ => self.child.event(event, ctx, data, env),
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: Option<&T>, data: &T, env: &Env) {
+        self.child.update(ctx, data, env);
+    }
+}
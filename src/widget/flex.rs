@@ -14,6 +14,7 @@
 
 //! A widget that arranges its children in a one-dimensional array.
 
+use crate::bidi;
 use crate::kurbo::{Point, Rect, Size};
 
 use crate::{
@@ -39,6 +40,9 @@ pub struct Column;
 /// A container with either horizontal or vertical layout.
 pub struct Flex<T: Data> {
     direction: Axis,
+    main_alignment: MainAxisAlignment,
+    cross_alignment: CrossAxisAlignment,
+    spacing: f64,
 
     children: Vec<ChildWidget<T>>,
 }
@@ -53,6 +57,47 @@ pub enum Axis {
     Vertical,
 }
 
+/// How children are arranged along the direction of a [`Flex`], when they
+/// don't fill it.
+///
+/// There is only space left over if none of the children are flex
+/// children, and the [`Flex`] is given a bounded size along its major axis.
+///
+/// [`Flex`]: struct.Flex.html
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MainAxisAlignment {
+    /// Children are packed at the start. This is the default.
+    Start,
+    /// Children are packed in the middle.
+    Center,
+    /// Children are packed at the end.
+    End,
+    /// The space left over is divided evenly between the children, with
+    /// the first and last children at the ends.
+    SpaceBetween,
+    /// The space left over is divided evenly around each child, so that the
+    /// space at the ends is half the space between children.
+    SpaceAround,
+}
+
+/// How children are positioned across the direction of a [`Flex`].
+///
+/// [`Flex`]: struct.Flex.html
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CrossAxisAlignment {
+    /// Children are aligned to the top of a row, or the left of a column.
+    /// This is the default.
+    Start,
+    /// Children are centered.
+    Center,
+    /// Children are aligned to the bottom of a row, or the right of a
+    /// column.
+    End,
+    /// Children are stretched to the full height of a row, or the full
+    /// width of a column.
+    Fill,
+}
+
 #[derive(Copy, Clone, Default)]
 struct Params {
     flex: f64,
@@ -79,6 +124,45 @@ impl Axis {
             Axis::Vertical => (minor, major),
         }
     }
+
+    /// Constraints with the given major and minor axis bounds.
+    fn constraints(&self, min: (f64, f64), max: (f64, f64)) -> BoxConstraints {
+        let (min_width, min_height) = self.pack(min.0, min.1);
+        let (max_width, max_height) = self.pack(max.0, max.1);
+        BoxConstraints::new(
+            Size::new(min_width, min_height),
+            Size::new(max_width, max_height),
+        )
+    }
+}
+
+impl MainAxisAlignment {
+    /// The space before the first child, and between each pair of children,
+    /// given the space left over.
+    fn offsets(self, extra: f64, count: usize) -> (f64, f64) {
+        let count = count as f64;
+        match self {
+            MainAxisAlignment::Start => (0.0, 0.0),
+            MainAxisAlignment::Center => (extra / 2.0, 0.0),
+            MainAxisAlignment::End => (extra, 0.0),
+            MainAxisAlignment::SpaceBetween if count > 1.0 => (0.0, extra / (count - 1.0)),
+            MainAxisAlignment::SpaceBetween => (0.0, 0.0),
+            MainAxisAlignment::SpaceAround if count > 0.0 => (extra / count / 2.0, extra / count),
+            MainAxisAlignment::SpaceAround => (0.0, 0.0),
+        }
+    }
+}
+
+impl CrossAxisAlignment {
+    /// The position of a child of the given size across a flex of the given
+    /// size.
+    fn offset(self, child: f64, total: f64) -> f64 {
+        match self {
+            CrossAxisAlignment::Start | CrossAxisAlignment::Fill => 0.0,
+            CrossAxisAlignment::Center => (total - child) / 2.0,
+            CrossAxisAlignment::End => total - child,
+        }
+    }
 }
 
 impl Row {
@@ -86,11 +170,7 @@ impl Row {
     ///
     /// The child widgets are laid out horizontally, from left to right.
     pub fn new<T: Data>() -> Flex<T> {
-        Flex {
-            direction: Axis::Horizontal,
-
-            children: Vec::new(),
-        }
+        Flex::new(Axis::Horizontal)
     }
 }
 
@@ -99,19 +179,79 @@ impl Column {
     ///
     /// The child widgets are laid out vertically, from top to bottom.
     pub fn new<T: Data>() -> Flex<T> {
+        Flex::new(Axis::Vertical)
+    }
+}
+
+impl<T: Data> Flex<T> {
+    /// Create a new horizontal flex container, the same as [`Row::new`].
+    ///
+    /// [`Row::new`]: struct.Row.html#method.new
+    pub fn row() -> Self {
+        Flex::new(Axis::Horizontal)
+    }
+
+    /// Create a new vertical flex container, the same as [`Column::new`].
+    ///
+    /// [`Column::new`]: struct.Column.html#method.new
+    pub fn column() -> Self {
+        Flex::new(Axis::Vertical)
+    }
+
+    fn new(direction: Axis) -> Self {
         Flex {
-            direction: Axis::Vertical,
+            direction,
+            main_alignment: MainAxisAlignment::Start,
+            cross_alignment: CrossAxisAlignment::Start,
+            spacing: 0.0,
 
             children: Vec::new(),
         }
     }
-}
 
-impl<T: Data> Flex<T> {
+    /// Set how children are arranged along the major axis, when they don't
+    /// fill it.
+    pub fn main_axis_alignment(mut self, alignment: MainAxisAlignment) -> Self {
+        self.main_alignment = alignment;
+        self
+    }
+
+    /// Set how children are positioned across the major axis.
+    pub fn cross_axis_alignment(mut self, alignment: CrossAxisAlignment) -> Self {
+        self.cross_alignment = alignment;
+        self
+    }
+
+    /// Set the space between each pair of children.
+    pub fn spacing(mut self, spacing: f64) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    /// Builder-style method to add a non-flex child widget.
+    ///
+    /// See [`add_child`] for how children are laid out.
+    ///
+    /// [`add_child`]: #method.add_child
+    pub fn with_child(mut self, child: impl Widget<T> + 'static) -> Self {
+        self.add_child(child, 0.0);
+        self
+    }
+
+    /// Builder-style method to add a flex child widget.
+    ///
+    /// See [`add_child`] for how children are laid out.
+    ///
+    /// [`add_child`]: #method.add_child
+    pub fn with_flex_child(mut self, child: impl Widget<T> + 'static, flex: f64) -> Self {
+        self.add_child(child, flex);
+        self
+    }
+
     /// Add a child widget.
     ///
     /// If `flex` is zero, then the child is non-flex. It is given the same
-    /// constraints on the "minor axis" as its parent, but unconstrained on the
+    /// maximum on the "minor axis" as its parent, but unconstrained on the
     /// "major axis".
     ///
     /// If `flex` is non-zero, then all the space left over after layout of
@@ -141,21 +281,24 @@ impl<T: Data> Widget<T> for Flex<T> {
         data: &T,
         env: &Env,
     ) -> Size {
+        let max_minor = self.direction.minor(bc.max);
+        // Children fill the minor axis if asked to and it is bounded; if it
+        // isn't, they fill the largest child, and are laid out again below.
+        let fill = self.cross_alignment == CrossAxisAlignment::Fill;
+        let min_minor = if fill && max_minor.is_finite() {
+            max_minor
+        } else {
+            0.0
+        };
+
         // Measure non-flex children.
         let mut total_non_flex = 0.0;
-        let mut minor = 0.0f64;
+        let mut minor = self.direction.minor(bc.min);
         for child in &mut self.children {
             if child.params.flex == 0.0 {
-                let child_bc = match self.direction {
-                    Axis::Horizontal => BoxConstraints::new(
-                        Size::new(0.0, bc.min.height),
-                        Size::new(std::f64::INFINITY, bc.max.height),
-                    ),
-                    Axis::Vertical => BoxConstraints::new(
-                        Size::new(bc.min.width, 0.0),
-                        Size::new(bc.max.width, std::f64::INFINITY),
-                    ),
-                };
+                let child_bc = self
+                    .direction
+                    .constraints((0.0, min_minor), (std::f64::INFINITY, max_minor));
                 let child_size = child.widget.layout(layout_ctx, &child_bc, data, env);
                 minor = minor.max(self.direction.minor(child_size));
                 total_non_flex += self.direction.major(child_size);
@@ -165,24 +308,18 @@ impl<T: Data> Widget<T> for Flex<T> {
             }
         }
 
+        let total_spacing = self.spacing * self.children.len().saturating_sub(1) as f64;
         let total_major = self.direction.major(bc.max);
-        let remaining = total_major - total_non_flex;
+        let remaining = total_major - total_non_flex - total_spacing;
         let flex_sum: f64 = self.children.iter().map(|child| child.params.flex).sum();
 
         // Measure flex children.
         for child in &mut self.children {
             if child.params.flex != 0.0 {
                 let major = remaining * child.params.flex / flex_sum;
-                let child_bc = match self.direction {
-                    Axis::Horizontal => BoxConstraints::new(
-                        Size::new(major, bc.min().height),
-                        Size::new(major, bc.max().height),
-                    ),
-                    Axis::Vertical => BoxConstraints::new(
-                        Size::new(bc.min().width, major),
-                        Size::new(bc.max().width, major),
-                    ),
-                };
+                let child_bc = self
+                    .direction
+                    .constraints((major, min_minor), (major, max_minor));
                 let child_size = child.widget.layout(layout_ctx, &child_bc, data, env);
                 minor = minor.max(self.direction.minor(child_size));
                 // Stash size.
@@ -191,20 +328,62 @@ impl<T: Data> Widget<T> for Flex<T> {
             }
         }
 
+        if fill && !max_minor.is_finite() {
+            for child in &mut self.children {
+                let size = child.widget.get_layout_rect().size();
+                if self.direction.minor(size) < minor {
+                    let major = self.direction.major(size);
+                    let child_bc = self.direction.constraints((major, minor), (major, minor));
+                    let child_size = child.widget.layout(layout_ctx, &child_bc, data, env);
+                    let rect = Rect::from_origin_size(Point::ORIGIN, child_size);
+                    child.widget.set_layout_rect(rect);
+                }
+            }
+        }
+
+        // The children only fill a bounded major axis if some of them flex,
+        // or if they are aligned to something other than its start.
+        let content_major = total_non_flex
+            + total_spacing
+            + self
+                .children
+                .iter()
+                .filter(|child| child.params.flex != 0.0)
+                .map(|child| self.direction.major(child.widget.get_layout_rect().size()))
+                .sum::<f64>();
+        let fills_major = flex_sum > 0.0
+            || (self.main_alignment != MainAxisAlignment::Start && total_major.is_finite());
+        let major_size = if fills_major {
+            total_major
+        } else {
+            content_major
+        };
+        let extra = (major_size - content_major).max(0.0);
+        let (start, between) = self.main_alignment.offsets(extra, self.children.len());
+
         // Finalize layout, assigning positions to each child.
-        let mut major = 0.0;
+        let mut major = start;
         for child in &mut self.children {
-            // top-align, could do center etc. based on child height
             let rect = child.widget.get_layout_rect();
-            let pos: Point = self.direction.pack(major, 0.0).into();
+            let child_minor = self
+                .cross_alignment
+                .offset(self.direction.minor(rect.size()), minor);
+            let pos: Point = self.direction.pack(major, child_minor).into();
             child.widget.set_layout_rect(rect.with_origin(pos));
-            major += self.direction.major(rect.size());
+            major += self.direction.major(rect.size()) + self.spacing + between;
         }
-        if flex_sum > 0.0 {
-            major = total_major;
+        // In a right-to-left layout, rows start from the right.
+        if let Axis::Horizontal = self.direction {
+            if bidi::is_rtl(env) {
+                for child in &mut self.children {
+                    let rect = child.widget.get_layout_rect();
+                    let pos = Point::new(major_size - rect.x1, rect.y0);
+                    child.widget.set_layout_rect(rect.with_origin(pos));
+                }
+            }
         }
         // TODO: should be able to make this `into`
-        let (width, height) = self.direction.pack(major, minor);
+        let (width, height) = self.direction.pack(major_size, minor);
         Size::new(width, height)
     }
 
@@ -220,3 +399,56 @@ impl<T: Data> Widget<T> for Flex<T> {
         }
     }
 }
+
+#[cfg(all(test, not(any(target_os = "windows", target_arch = "wasm32"))))]
+mod tests {
+    use super::*;
+    use crate::widget::SizedBox;
+    use crate::{Harness, Probe};
+
+    #[test]
+    fn flex_alignment_and_spacing() {
+        let harness = Harness::new(
+            || {
+                let mut row = Row::new()
+                    .main_axis_alignment(MainAxisAlignment::SpaceBetween)
+                    .cross_axis_alignment(CrossAxisAlignment::Center);
+                row.add_child(
+                    Probe::new("a", SizedBox::empty().width(100.0).height(50.0)),
+                    0.0,
+                );
+                row.add_child(
+                    Probe::new("b", SizedBox::empty().width(100.0).height(100.0)),
+                    0.0,
+                );
+                row
+            },
+            0u32,
+        );
+        // Rects can't be compared directly.
+        let a = harness.layout_rect("a").unwrap();
+        assert_eq!(
+            (a.origin(), a.size()),
+            (Point::new(0.0, 175.0), Size::new(100.0, 50.0))
+        );
+        let b = harness.layout_rect("b").unwrap();
+        assert_eq!(
+            (b.origin(), b.size()),
+            (Point::new(300.0, 150.0), Size::new(100.0, 100.0))
+        );
+
+        let harness = Harness::new(
+            || {
+                let mut col = Column::new().spacing(10.0);
+                col.add_child(Probe::new("a", SizedBox::empty().height(20.0)), 0.0);
+                col.add_child(Probe::new("b", SizedBox::empty().height(20.0)), 0.0);
+                col
+            },
+            0u32,
+        );
+        assert_eq!(
+            harness.layout_rect("b").unwrap().origin(),
+            Point::new(0.0, 30.0)
+        );
+    }
+}
@@ -0,0 +1,185 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget showing content rendered with a GPU API.
+
+use log::warn;
+
+use crate::kurbo::Size;
+use crate::raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+use crate::shell::ChildView;
+use crate::{
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, IdleToken, LayoutCtx, PaintCtx,
+    UpdateCtx, Widget,
+};
+
+use super::native_view::ViewHost;
+
+/// The token used to start rendering continuously.
+const START_TOKEN: IdleToken = IdleToken::new(0x6770_7573);
+
+/// A widget that owns a native surface, which the application renders into
+/// with a GPU API such as wgpu, Vulkan, Metal, or OpenGL.
+///
+/// The surface is a native view covering the widget's area, so it is drawn
+/// above all druid content. Each time the widget is painted, the render
+/// closure is called with a [`SurfaceTarget`], which provides the raw window
+/// handle needed to create a swap chain, and the current surface size.
+///
+/// By default the widget is painted again when its data changes; use
+/// [`continuous`] to render every animation frame instead, as a 3D viewport
+/// or game view would.
+///
+/// [`SurfaceTarget`]: struct.SurfaceTarget.html
+/// [`continuous`]: #method.continuous
+pub struct GpuSurface<T> {
+    view: Option<ChildView>,
+    host: ViewHost,
+    render: Box<dyn FnMut(&SurfaceTarget, &T, &Env)>,
+    continuous: bool,
+    /// Whether continuous rendering has been kicked off.
+    started: bool,
+    scale: f64,
+}
+
+/// The surface of a [`GpuSurface`], passed to its render closure.
+///
+/// [`GpuSurface`]: struct.GpuSurface.html
+pub struct SurfaceTarget<'a> {
+    view: &'a ChildView,
+    size: Size,
+    scale: f64,
+}
+
+impl<T: Data> GpuSurface<T> {
+    /// Create a new surface, with a closure that renders its contents.
+    pub fn new(render: impl FnMut(&SurfaceTarget, &T, &Env) + 'static) -> Self {
+        let view = match ChildView::new_surface() {
+            Ok(view) => Some(view),
+            Err(e) => {
+                warn!("failed to create gpu surface: {:?}", e);
+                None
+            }
+        };
+        GpuSurface {
+            view,
+            host: ViewHost::default(),
+            render: Box::new(render),
+            continuous: false,
+            started: false,
+            scale: 1.0,
+        }
+    }
+
+    /// Render every animation frame, rather than only when the data changes.
+    pub fn continuous(mut self) -> Self {
+        self.continuous = true;
+        self
+    }
+}
+
+impl<'a> SurfaceTarget<'a> {
+    /// The size of the surface, in display points.
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /// The number of physical pixels per display point.
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// The size of the surface in physical pixels, which is the size a swap
+    /// chain should be created with.
+    pub fn pixel_size(&self) -> (u32, u32) {
+        let width = (self.size.width * self.scale).round() as u32;
+        let height = (self.size.height * self.scale).round() as u32;
+        (width, height)
+    }
+}
+
+unsafe impl<'a> HasRawWindowHandle for SurfaceTarget<'a> {
+    fn raw_window_handle(&self) -> RawWindowHandle {
+        self.view.raw_window_handle()
+    }
+}
+
+impl<T: Data> Widget<T> for GpuSurface<T> {
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env) {
+        let view = match self.view.as_ref() {
+            Some(view) => view,
+            None => return,
+        };
+        let size = base_state.size();
+        self.host.paint(view, paint_ctx, size);
+        if self.host.is_attached() && size.width > 0.0 && size.height > 0.0 {
+            let target = SurfaceTarget {
+                view,
+                size,
+                scale: self.scale,
+            };
+            (self.render)(&target, data, env);
+        }
+    }
+
+    fn layout(
+        &mut self,
+        _layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        _data: &T,
+        _env: &Env,
+    ) -> Size {
+        bc.constrain(Size::new(100.0, 100.0))
+    }
+
+    fn event(&mut self, event: &Event, ctx: &mut EventCtx, _data: &mut T, _env: &Env) {
+        if !self.continuous {
+            return;
+        }
+        match event {
+            Event::Idle(token) if *token == START_TOKEN => ctx.request_anim_frame(),
+            Event::AnimFrame(_) => {
+                ctx.invalidate();
+                ctx.request_anim_frame();
+            }
+            _ => (),
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: Option<&T>, data: &T, _env: &Env) {
+        if let Some(view) = self.view.as_ref() {
+            self.host.update(view, ctx);
+        }
+        self.scale = f64::from(ctx.window().get_dpi()) / 96.0;
+        // Animation can only be requested while handling an event, so ask
+        // for an idle callback to get one.
+        if self.continuous && !self.started {
+            if let Some(idle) = ctx.window().get_idle_handle() {
+                idle.schedule_idle(START_TOKEN);
+                self.started = true;
+            }
+        }
+        if !old_data.map(|old| old.same(data)).unwrap_or(false) {
+            ctx.invalidate();
+        }
+    }
+}
+
+impl<T> Drop for GpuSurface<T> {
+    fn drop(&mut self) {
+        if let Some(view) = self.view.as_ref() {
+            self.host.detach(view);
+        }
+    }
+}
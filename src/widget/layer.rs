@@ -0,0 +1,98 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget that caches the painting of its child.
+
+use crate::kurbo::{Point, Rect, Size};
+use crate::{
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx, RgbaImage,
+    UpdateCtx, Widget, WidgetPod,
+};
+
+/// A widget that paints its child into an image, and draws that image
+/// until the child changes.
+///
+/// This is for subtrees that are expensive to paint and rarely change,
+/// such as a detailed background behind an animating foreground. The
+/// image is painted again when the child is updated or invalidated, or
+/// when its size or the window's scale changes.
+///
+/// The child must invalidate itself whenever its appearance changes, and
+/// it is clipped to its bounds. Where offscreen painting isn't
+/// available, and while the debug overlay is shown, the child is painted
+/// as usual.
+pub struct Layer<T: Data> {
+    child: WidgetPod<T, Box<dyn Widget<T>>>,
+    cache: Option<Cache>,
+}
+
+struct Cache {
+    image: RgbaImage,
+    size: Size,
+    scale: f64,
+}
+
+impl<T: Data> Layer<T> {
+    /// Create a widget caching the painting of `child`.
+    pub fn new(child: impl Widget<T> + 'static) -> Self {
+        Layer {
+            child: WidgetPod::new(child).boxed(),
+            cache: None,
+        }
+    }
+}
+
+impl<T: Data> Widget<T> for Layer<T> {
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env) {
+        let size = base_state.size();
+        let scale = paint_ctx.device_scale();
+        let stale = match self.cache.as_ref() {
+            Some(cache) => cache.size != size || cache.scale != scale,
+            None => true,
+        };
+        if stale {
+            let child = &mut self.child;
+            self.cache = paint_ctx
+                .paint_offscreen(size, scale, |paint_ctx| child.paint(paint_ctx, data, env))
+                .map(|image| Cache { image, size, scale });
+        }
+        let drawn = match self.cache.as_ref() {
+            Some(cache) => paint_ctx.draw_offscreen(&cache.image, scale),
+            None => false,
+        };
+        if !drawn {
+            self.child.paint(paint_ctx, data, env);
+        }
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+        let size = self.child.layout(ctx, bc, data, env);
+        self.child
+            .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+        size
+    }
+
+    fn event(&mut self, event: &Event, ctx: &mut EventCtx, data: &mut T, env: &Env) {
+        self.child.event(event, ctx, data, env);
+        if ctx.base_state.needs_inval {
+            self.cache = None;
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: Option<&T>, data: &T, env: &Env) {
+        // This is only called when the data or environment has changed.
+        self.cache = None;
+        self.child.update(ctx, data, env);
+    }
+}
@@ -0,0 +1,366 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget showing a row for each item in a collection.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::f64::INFINITY;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use crate::kurbo::{Point, Rect, Size};
+use crate::piet::RenderContext;
+use crate::theme;
+use crate::{
+    Animator, BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx,
+    Transition, UpdateCtx, Widget, WidgetPod,
+};
+
+/// A widget for `Arc<Vec<T>>` data, stacking a row for each item.
+///
+/// Rows are built by a closure. When items are inserted or removed, the
+/// affected rows grow or shrink into place, fading in or out, while the
+/// rows around them move to make room.
+///
+/// Rows are matched up with items by position, so a row keeps its state,
+/// such as focus, while it edits its item, and items added or removed at
+/// the end are animated. When items are inserted, removed or reordered
+/// elsewhere, give them a key with [`key`], so that each row follows its
+/// item and keeps its state for as long as the item stays in the list.
+///
+/// Like [`Either`]'s fade, rows fade to and from the window background
+/// color.
+///
+/// To load items a page at a time, put the list in a [`Scroll`] and have
+/// it ask for more with [`load_more`].
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::{DynLabel, List};
+///
+/// let names = List::new(|| DynLabel::new(|name: &String, _env| name.clone()));
+/// ```
+///
+/// [`key`]: #method.key
+/// [`Either`]: struct.Either.html
+/// [`Scroll`]: struct.Scroll.html
+/// [`load_more`]: struct.Scroll.html#method.load_more
+pub struct List<T: Data> {
+    maker: Box<dyn Fn() -> Box<dyn Widget<T>>>,
+    rows: Vec<Row<T>>,
+    transition: Transition,
+    key: Option<Box<dyn Fn(&T) -> u64>>,
+}
+
+struct Row<T: Data> {
+    child: WidgetPod<T, Box<dyn Widget<T>>>,
+    /// The item this row shows, or, while it is being removed, the item it
+    /// last showed.
+    data: T,
+    /// How much of the row is shown, from 0 to 1.
+    presence: Animator<f64>,
+    removing: bool,
+}
+
+impl<T: Data> List<T> {
+    /// Create a list that builds each row with `maker`.
+    pub fn new<W: Widget<T> + 'static>(maker: impl Fn() -> W + 'static) -> Self {
+        List {
+            maker: Box::new(move || Box::new(maker())),
+            rows: Vec::new(),
+            transition: Transition::default(),
+            key: None,
+        }
+    }
+
+    /// Match rows to items by a key, such as an id, instead of by position.
+    ///
+    /// Items with the same key are matched up in order.
+    pub fn key<K: Hash>(mut self, key: impl Fn(&T) -> K + 'static) -> Self {
+        self.key = Some(Box::new(move |item| {
+            let mut hasher = DefaultHasher::new();
+            key(item).hash(&mut hasher);
+            hasher.finish()
+        }));
+        self
+    }
+
+    /// Set the transition for rows that are inserted or removed.
+    ///
+    /// Pass [`Transition::instant`] to turn the animation off.
+    ///
+    /// [`Transition::instant`]: ../struct.Transition.html#method.instant
+    pub fn transition(mut self, transition: Transition) -> Self {
+        self.transition = transition;
+        self
+    }
+
+    fn make_row(&self, data: &T, animate: bool) -> Row<T> {
+        let mut presence = Animator::new(0.0, self.transition);
+        if animate {
+            presence.animate_to(1.0);
+        } else {
+            presence.jump_to(1.0);
+        }
+        Row {
+            child: WidgetPod::new((self.maker)()),
+            data: data.clone(),
+            presence,
+            removing: false,
+        }
+    }
+
+    /// Match the rows to a new list of items, adding rows for new items
+    /// and starting the removal of rows whose items are gone.
+    fn reconcile(&mut self, items: &[T], animate: bool) {
+        let live = self.rows.iter().filter(|row| !row.removing);
+        let matches = match &self.key {
+            Some(key) => {
+                let old = live.map(|row| key(&row.data)).collect::<Vec<_>>();
+                let new = items.iter().map(key.as_ref()).collect::<Vec<_>>();
+                match_keys(&old, &new)
+            }
+            None => (0..live.count())
+                .map(|i| if i < items.len() { Some(i) } else { None })
+                .collect(),
+        };
+
+        // Matched rows move to their item's position. Rows on their way out
+        // stay after the row that preceded them, or at the start.
+        let mut placed = (0..items.len()).map(|_| None).collect::<Vec<_>>();
+        let mut leaving = (0..=items.len()).map(|_| Vec::new()).collect::<Vec<_>>();
+        let mut anchor = 0;
+        let mut live_index = 0;
+        for mut row in std::mem::replace(&mut self.rows, Vec::new()) {
+            if row.removing {
+                leaving[anchor].push(row);
+                continue;
+            }
+            match matches[live_index] {
+                Some(j) => {
+                    row.data = items[j].clone();
+                    placed[j] = Some(row);
+                    anchor = j + 1;
+                }
+                None if animate => {
+                    row.removing = true;
+                    row.presence.animate_to(0.0);
+                    leaving[anchor].push(row);
+                }
+                None => (),
+            }
+            live_index += 1;
+        }
+
+        let mut rows = Vec::with_capacity(items.len());
+        let mut leaving = leaving.into_iter();
+        rows.extend(leaving.next().into_iter().flatten());
+        for ((item, row), after) in items.iter().zip(placed).zip(leaving) {
+            rows.push(row.unwrap_or_else(|| self.make_row(item, animate)));
+            rows.extend(after);
+        }
+        self.rows = rows;
+    }
+}
+
+/// For each of the `old` keys, the index of the `new` key it matches.
+/// Repeated keys are matched up in order.
+fn match_keys(old: &[u64], new: &[u64]) -> Vec<Option<usize>> {
+    let mut positions: HashMap<u64, VecDeque<usize>> = HashMap::new();
+    for (j, key) in new.iter().enumerate() {
+        positions.entry(*key).or_default().push_back(j);
+    }
+    old.iter()
+        .map(|key| positions.get_mut(key).and_then(|js| js.pop_front()))
+        .collect()
+}
+
+impl<T: Data> Widget<Arc<Vec<T>>> for List<T> {
+    fn paint(
+        &mut self,
+        paint_ctx: &mut PaintCtx,
+        _base_state: &BaseState,
+        _data: &Arc<Vec<T>>,
+        env: &Env,
+    ) {
+        let background = env.get(theme::WINDOW_BACKGROUND_COLOR);
+        for row in self.rows.iter_mut() {
+            let presence = row.presence.value();
+            if presence >= 1.0 {
+                row.child.paint_with_offset(paint_ctx, &row.data, env);
+                continue;
+            }
+            let rect = row.child.get_layout_rect();
+            let shown = Rect::from_origin_size(
+                rect.origin(),
+                (rect.width(), rect.height() * presence.max(0.0)),
+            );
+            let Row { child, data, .. } = row;
+            paint_ctx.with_save(|paint_ctx| {
+                paint_ctx.clip(shown);
+                child.paint_with_offset(paint_ctx, data, env);
+                paint_ctx.fill(shown, &background.clone().with_alpha(1.0 - presence));
+            });
+        }
+    }
+
+    fn layout(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        _data: &Arc<Vec<T>>,
+        env: &Env,
+    ) -> Size {
+        let child_bc = BoxConstraints::new(
+            Size::new(bc.min().width, 0.0),
+            Size::new(bc.max().width, INFINITY),
+        );
+        let mut width = 0.0f64;
+        let mut y = 0.0;
+        for row in self.rows.iter_mut() {
+            let size = row.child.layout(ctx, &child_bc, &row.data, env);
+            row.child
+                .set_layout_rect(Rect::from_origin_size(Point::new(0.0, y), size));
+            width = width.max(size.width);
+            y += size.height * row.presence.value().max(0.0);
+        }
+        bc.constrain((width, y))
+    }
+
+    fn event(&mut self, event: &Event, ctx: &mut EventCtx, data: &mut Arc<Vec<T>>, env: &Env) {
+        if let Event::AnimFrame(interval) = event {
+            let mut running = false;
+            for row in self.rows.iter_mut() {
+                running |= row.presence.advance(*interval);
+            }
+            let before = self.rows.len();
+            self.rows
+                .retain(|row| !row.removing || row.presence.is_running());
+            if running || self.rows.len() != before {
+                ctx.invalidate();
+            }
+            if running {
+                ctx.request_anim_frame();
+            }
+        }
+
+        // Rows being removed no longer take part in events.
+        let mut changed = Vec::new();
+        let live = self.rows.iter_mut().filter(|row| !row.removing);
+        for (i, (row, item)) in live.zip(data.iter()).enumerate() {
+            let mut item = item.clone();
+            row.child.event(event, ctx, &mut item, env);
+            if !item.same(&data[i]) {
+                changed.push((i, item));
+            }
+        }
+        if !changed.is_empty() {
+            let items = Arc::make_mut(data);
+            for (i, item) in changed {
+                items[i] = item;
+            }
+        }
+    }
+
+    fn update(
+        &mut self,
+        ctx: &mut UpdateCtx,
+        old_data: Option<&Arc<Vec<T>>>,
+        data: &Arc<Vec<T>>,
+        env: &Env,
+    ) {
+        let animate = old_data.is_some() && !env.get(theme::REDUCED_MOTION);
+        let count = self.rows.len();
+        self.reconcile(data, animate);
+        if self.rows.len() != count {
+            ctx.invalidate();
+        }
+        if self.rows.iter().any(|row| row.presence.is_running()) {
+            ctx.request_anim_frame();
+        }
+        for row in self.rows.iter_mut().filter(|row| !row.removing) {
+            row.child.update(ctx, &row.data, env);
+        }
+    }
+}
+
+#[cfg(all(test, not(any(target_os = "windows", target_arch = "wasm32"))))]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use crate::widget::{Click, Column, SizedBox};
+    use crate::{Harness, Probe};
+
+    #[test]
+    fn list_animates_insertion() {
+        let mut harness = Harness::new(
+            || {
+                let add = Click::new(
+                    SizedBox::empty().width(20.0).height(20.0),
+                    |_ctx, data: &mut Arc<Vec<u32>>, _env| Arc::make_mut(data).push(3),
+                );
+                let list = List::new(|| SizedBox::empty().height(20.0));
+                Column::new()
+                    .with_child(add)
+                    .with_child(Probe::new("list", list))
+            },
+            Arc::new(vec![1, 2]),
+        );
+        assert_eq!(harness.layout_rect("list").unwrap().height(), 40.0);
+
+        harness.click(Point::new(10.0, 10.0));
+        assert!(harness.layout_rect("list").unwrap().height() < 60.0);
+        harness.animate(60);
+        assert_eq!(harness.layout_rect("list").unwrap().height(), 60.0);
+    }
+
+    #[test]
+    fn edited_row_survives() {
+        let built = Rc::new(Cell::new(0));
+        let counter = built.clone();
+        let mut harness = Harness::new(
+            move || {
+                let counter = counter.clone();
+                let list = List::new(move || {
+                    counter.set(counter.get() + 1);
+                    let row = SizedBox::empty().width(20.0).height(20.0);
+                    Click::new(row, |_ctx, data: &mut u32, _env| *data += 10)
+                });
+                Column::new().with_child(Probe::new("list", list))
+            },
+            Arc::new(vec![1, 2]),
+        );
+        assert_eq!(built.get(), 2);
+
+        harness.click(Point::new(10.0, 10.0));
+        assert_eq!(*harness.data(), vec![11, 2]);
+        // The row is kept, rather than faded out and replaced.
+        harness.animate(60);
+        assert_eq!(built.get(), 2);
+        assert_eq!(harness.layout_rect("list").unwrap().height(), 40.0);
+    }
+
+    #[test]
+    fn keys_match_in_order() {
+        assert_eq!(
+            match_keys(&[1, 2, 3], &[3, 1]),
+            vec![Some(1), None, Some(0)]
+        );
+        assert_eq!(match_keys(&[5, 5], &[5]), vec![Some(0), None]);
+    }
+}
@@ -0,0 +1,243 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget playing Lottie animations.
+
+use std::path::Path;
+use std::time::Duration;
+
+use log::warn;
+
+use crate::command::sys;
+use crate::kurbo::{Affine, Rect, Size};
+use crate::lottie::Composition;
+use crate::piet::RenderContext;
+use crate::theme;
+use crate::{
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, IdleToken, LayoutCtx, PaintCtx,
+    UpdateCtx, Widget,
+};
+
+/// The token used to start playback of autoplaying animations.
+const AUTOPLAY_TOKEN: IdleToken = IdleToken::new(0x6c6f_7474);
+
+/// A widget that plays a [Lottie] animation, as exported by Bodymovin.
+///
+/// Playback is controlled with the [`LOTTIE_PLAY`], [`LOTTIE_PAUSE`],
+/// [`LOTTIE_SEEK`] and [`LOTTIE_SET_LOOPING`] commands, which apply to every
+/// animation in the window; when a non-looping animation reaches the end,
+/// the widget submits [`LOTTIE_ENDED`].
+///
+/// The animation is scaled to fit the widget, keeping its aspect ratio.
+/// Animations don't autoplay when [`REDUCED_MOTION`] is set. Only a subset
+/// of the format is supported; unsupported layers and shapes are skipped.
+/// This widget is only available with the `lottie` feature.
+///
+/// [Lottie]: https://airbnb.io/lottie/
+/// [`LOTTIE_PLAY`]: ../command/sys/constant.LOTTIE_PLAY.html
+/// [`LOTTIE_PAUSE`]: ../command/sys/constant.LOTTIE_PAUSE.html
+/// [`LOTTIE_SEEK`]: ../command/sys/constant.LOTTIE_SEEK.html
+/// [`LOTTIE_SET_LOOPING`]: ../command/sys/constant.LOTTIE_SET_LOOPING.html
+/// [`LOTTIE_ENDED`]: ../command/sys/constant.LOTTIE_ENDED.html
+/// [`REDUCED_MOTION`]: ../theme/constant.REDUCED_MOTION.html
+pub struct Lottie {
+    composition: Option<Composition>,
+    /// The frame being shown.
+    frame: f64,
+    playing: bool,
+    looping: bool,
+    autoplay: bool,
+}
+
+impl Lottie {
+    /// Create a widget playing the animation in the given JSON, showing its
+    /// first frame until playback starts.
+    pub fn new(json: &str) -> Lottie {
+        let composition = match Composition::from_json(json) {
+            Ok(composition) => Some(composition),
+            Err(e) => {
+                warn!("failed to load lottie animation: {}", e);
+                None
+            }
+        };
+        let frame = composition.as_ref().map(|c| c.frames().0).unwrap_or(0.0);
+        Lottie {
+            composition,
+            frame,
+            playing: false,
+            looping: false,
+            autoplay: false,
+        }
+    }
+
+    /// Create a widget playing the animation in the given file.
+    pub fn from_file(path: impl AsRef<Path>) -> Lottie {
+        match std::fs::read_to_string(path.as_ref()) {
+            Ok(json) => Lottie::new(&json),
+            Err(e) => {
+                warn!("failed to read {:?}: {}", path.as_ref(), e);
+                Lottie::new("")
+            }
+        }
+    }
+
+    /// Start playing as soon as the widget is shown.
+    pub fn autoplay(mut self) -> Self {
+        self.autoplay = true;
+        self
+    }
+
+    /// Set whether the animation starts again when it reaches the end.
+    pub fn looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    /// The length of the animation, if it loaded.
+    pub fn duration(&self) -> Option<Duration> {
+        self.composition.as_ref().map(|c| {
+            let (start, end) = c.frames();
+            let one_second = c.frames_in(Duration::from_secs(1));
+            Duration::from_secs_f64(((end - start) / one_second).max(0.0))
+        })
+    }
+
+    fn play(&mut self, ctx: &mut EventCtx) {
+        if let Some(composition) = self.composition.as_ref() {
+            if !self.playing {
+                // Playing again from the end starts over.
+                let (start, end) = composition.frames();
+                if self.frame >= end {
+                    self.frame = start;
+                }
+                self.playing = true;
+                ctx.request_anim_frame();
+            }
+        }
+    }
+
+    /// Move on by some time, returning `false` at the end.
+    fn advance(&mut self, interval: Duration) -> bool {
+        let composition = match self.composition.as_ref() {
+            Some(composition) => composition,
+            None => return false,
+        };
+        let (start, end) = composition.frames();
+        self.frame += composition.frames_in(interval);
+        if self.frame < end {
+            true
+        } else if self.looping && end > start {
+            self.frame = start + (self.frame - start) % (end - start);
+            true
+        } else {
+            self.frame = end;
+            false
+        }
+    }
+}
+
+impl<T: Data> Widget<T> for Lottie {
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, _data: &T, _env: &Env) {
+        let composition = match self.composition.as_ref() {
+            Some(composition) => composition,
+            None => return,
+        };
+        let natural = composition.size();
+        if natural.width <= 0.0 || natural.height <= 0.0 {
+            return;
+        }
+        // Fit the animation to our size, centered.
+        let size = base_state.size();
+        let scale = (size.width / natural.width).min(size.height / natural.height);
+        let offset = (
+            (size.width - natural.width * scale) / 2.0,
+            (size.height - natural.height * scale) / 2.0,
+        );
+        if let Err(e) = paint_ctx.save() {
+            warn!("saving render context failed: {:?}", e);
+            return;
+        }
+        paint_ctx.transform(Affine::translate(offset) * Affine::scale(scale));
+        paint_ctx.clip(Rect::from_origin_size((0.0, 0.0), natural));
+        // The last frame is the one before the end.
+        let (start, end) = composition.frames();
+        let frame = self.frame.min(end - 1.0).max(start);
+        composition.paint(paint_ctx, frame);
+        if let Err(e) = paint_ctx.restore() {
+            warn!("restoring render context failed: {:?}", e);
+        }
+    }
+
+    fn layout(
+        &mut self,
+        _layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        _data: &T,
+        _env: &Env,
+    ) -> Size {
+        let natural = match self.composition.as_ref() {
+            Some(composition) => composition.size(),
+            None => Size::new(100.0, 100.0),
+        };
+        bc.constrain(natural)
+    }
+
+    fn event(&mut self, event: &Event, ctx: &mut EventCtx, _data: &mut T, env: &Env) {
+        match event {
+            Event::Idle(token) if *token == AUTOPLAY_TOKEN && self.autoplay => {
+                self.autoplay = false;
+                if !env.get(theme::REDUCED_MOTION) {
+                    self.play(ctx);
+                }
+            }
+            Event::AnimFrame(interval) if self.playing => {
+                if self.advance(Duration::from_nanos(*interval)) {
+                    ctx.request_anim_frame();
+                } else {
+                    self.playing = false;
+                    ctx.submit_command(sys::LOTTIE_ENDED.into(), None);
+                }
+                ctx.invalidate();
+            }
+            Event::Command(cmd) if cmd.selector == sys::LOTTIE_PLAY => self.play(ctx),
+            Event::Command(cmd) if cmd.selector == sys::LOTTIE_PAUSE => self.playing = false,
+            Event::Command(cmd) if cmd.selector == sys::LOTTIE_SEEK => {
+                match (cmd.get_object::<Duration>(), self.composition.as_ref()) {
+                    (Some(time), Some(composition)) => {
+                        self.frame = composition.frames().0 + composition.frames_in(*time);
+                        ctx.invalidate();
+                    }
+                    (None, _) => warn!("LOTTIE_SEEK expects a Duration argument"),
+                    _ => (),
+                }
+            }
+            Event::Command(cmd) if cmd.selector == sys::LOTTIE_SET_LOOPING => {
+                match cmd.get_object::<bool>() {
+                    Some(looping) => self.looping = *looping,
+                    None => warn!("LOTTIE_SET_LOOPING expects a bool argument"),
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: Option<&T>, _data: &T, _env: &Env) {
+        // Playback needs an event context; the first update asks for one.
+        if self.autoplay && old_data.is_none() {
+            if let Some(idle) = ctx.window().get_idle_handle() {
+                idle.schedule_idle(AUTOPLAY_TOKEN);
+            }
+        }
+    }
+}
@@ -0,0 +1,172 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget for optional data.
+
+use crate::kurbo::{Point, Rect, Size};
+use crate::widget::SizedBox;
+use crate::{
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx, UpdateCtx, Widget,
+    WidgetPod,
+};
+
+/// A widget for `Option<T>` data, showing one child when there is a value
+/// and another when there isn't.
+///
+/// The children are built by closures when they are first shown, and
+/// dropped when the data switches to the other case, so state such as the
+/// text in a text box doesn't carry over from one value to the next.
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::{Label, Maybe, TextBox};
+///
+/// let name = Maybe::new(|| TextBox::new(), || Label::new("No name"));
+/// ```
+pub struct Maybe<T: Data> {
+    some_maker: Box<dyn Fn() -> Box<dyn Widget<T>>>,
+    none_maker: Box<dyn Fn() -> Box<dyn Widget<()>>>,
+    child: Option<MaybeChild<T>>,
+}
+
+enum MaybeChild<T: Data> {
+    Some(WidgetPod<T, Box<dyn Widget<T>>>),
+    None(WidgetPod<(), Box<dyn Widget<()>>>),
+}
+
+impl<T: Data> Maybe<T> {
+    /// Create a widget that shows the result of `some_maker` for `Some`
+    /// values, and the result of `none_maker` for `None`.
+    pub fn new<W1, W2>(
+        some_maker: impl Fn() -> W1 + 'static,
+        none_maker: impl Fn() -> W2 + 'static,
+    ) -> Self
+    where
+        W1: Widget<T> + 'static,
+        W2: Widget<()> + 'static,
+    {
+        Maybe {
+            some_maker: Box::new(move || Box::new(some_maker())),
+            none_maker: Box::new(move || Box::new(none_maker())),
+            child: None,
+        }
+    }
+
+    /// Create a widget that shows the result of `some_maker` for `Some`
+    /// values, and nothing for `None`.
+    pub fn or_empty<W: Widget<T> + 'static>(some_maker: impl Fn() -> W + 'static) -> Self {
+        Maybe::new(some_maker, SizedBox::empty)
+    }
+}
+
+impl<T: Data> Widget<Option<T>> for Maybe<T> {
+    fn paint(
+        &mut self,
+        paint_ctx: &mut PaintCtx,
+        _base_state: &BaseState,
+        data: &Option<T>,
+        env: &Env,
+    ) {
+        match (self.child.as_mut(), data) {
+            (Some(MaybeChild::Some(child)), Some(data)) => child.paint(paint_ctx, data, env),
+            (Some(MaybeChild::None(child)), None) => child.paint(paint_ctx, &(), env),
+            _ => (),
+        }
+    }
+
+    fn layout(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &Option<T>,
+        env: &Env,
+    ) -> Size {
+        match (self.child.as_mut(), data) {
+            (Some(MaybeChild::Some(child)), Some(data)) => {
+                let size = child.layout(ctx, bc, data, env);
+                child.set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+                size
+            }
+            (Some(MaybeChild::None(child)), None) => {
+                let size = child.layout(ctx, bc, &(), env);
+                child.set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+                size
+            }
+            _ => bc.min(),
+        }
+    }
+
+    fn event(&mut self, event: &Event, ctx: &mut EventCtx, data: &mut Option<T>, env: &Env) {
+        match (self.child.as_mut(), data) {
+            (Some(MaybeChild::Some(child)), Some(data)) => child.event(event, ctx, data, env),
+            (Some(MaybeChild::None(child)), None) => child.event(event, ctx, &mut (), env),
+            _ => (),
+        }
+    }
+
+    fn update(
+        &mut self,
+        ctx: &mut UpdateCtx,
+        _old_data: Option<&Option<T>>,
+        data: &Option<T>,
+        env: &Env,
+    ) {
+        let switched = match (&self.child, data) {
+            (Some(MaybeChild::Some(_)), Some(_)) | (Some(MaybeChild::None(_)), None) => false,
+            _ => true,
+        };
+        if switched {
+            self.child = Some(match data {
+                Some(_) => MaybeChild::Some(WidgetPod::new((self.some_maker)())),
+                None => MaybeChild::None(WidgetPod::new((self.none_maker)())),
+            });
+            ctx.invalidate();
+        }
+        match (self.child.as_mut(), data) {
+            (Some(MaybeChild::Some(child)), Some(data)) => child.update(ctx, data, env),
+            (Some(MaybeChild::None(child)), None) => child.update(ctx, &(), env),
+            _ => (),
+        }
+    }
+}
+
+#[cfg(all(test, not(any(target_os = "windows", target_arch = "wasm32"))))]
+mod tests {
+    use super::*;
+    use crate::widget::Click;
+    use crate::{Harness, Probe};
+
+    #[test]
+    fn maybe_switches_children() {
+        let mut harness = Harness::new(
+            || {
+                let maybe = Maybe::new(
+                    || Probe::new("some", SizedBox::empty()),
+                    || Probe::new("none", SizedBox::empty()),
+                );
+                Click::new(maybe, |_ctx, data: &mut Option<u32>, _env| {
+                    *data = Some(data.unwrap_or(0) + 1);
+                })
+            },
+            None,
+        );
+        assert!(harness.layout_rect("some").is_none());
+        assert!(harness.layout_rect("none").is_some());
+
+        harness.click(Point::new(10.0, 10.0));
+        assert_eq!(harness.data(), Some(1));
+        assert!(harness.layout_rect("some").is_some());
+    }
+}
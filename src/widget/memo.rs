@@ -0,0 +1,124 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget that skips work on its child while a key is unchanged.
+
+use crate::kurbo::{Point, Rect, Size};
+use crate::widget::SizedBox;
+use crate::{
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx, UpdateCtx, Widget,
+    WidgetPod,
+};
+
+/// A widget that only updates and lays out its child when a key derived
+/// from the data changes.
+///
+/// This keeps large, mostly static parts of the interface cheap: while
+/// the key is unchanged, updates are not passed to the child, and its
+/// previous layout is reused as long as the constraints are the same.
+/// Events still reach the child, and a child that invalidates itself in
+/// response to one is laid out again.
+///
+/// The key should capture everything the child displays; changes to other
+/// parts of the data will not be seen by the child until the key changes.
+pub struct Memo<T: Data, K: Data> {
+    child: WidgetPod<T, Box<dyn Widget<T>>>,
+    key: Box<dyn Fn(&T) -> K>,
+    builder: Option<Box<dyn Fn(&T) -> Box<dyn Widget<T>>>>,
+    last_key: Option<K>,
+    last_env: Option<Env>,
+    last_layout: Option<(BoxConstraints, Size)>,
+}
+
+impl<T: Data + 'static, K: Data> Memo<T, K> {
+    /// Create a widget that updates `child` only when `key` changes.
+    pub fn new(key: impl Fn(&T) -> K + 'static, child: impl Widget<T> + 'static) -> Self {
+        Memo {
+            child: WidgetPod::new(child).boxed(),
+            key: Box::new(key),
+            builder: None,
+            last_key: None,
+            last_env: None,
+            last_layout: None,
+        }
+    }
+
+    /// Create a widget that builds a new child whenever `key` changes.
+    ///
+    /// This is for children whose structure, and not just their contents,
+    /// depends on the data.
+    pub fn rebuild<W: Widget<T> + 'static>(
+        key: impl Fn(&T) -> K + 'static,
+        builder: impl Fn(&T) -> W + 'static,
+    ) -> Self {
+        Memo {
+            // Replaced by the first update.
+            child: WidgetPod::new(SizedBox::empty()).boxed(),
+            key: Box::new(key),
+            builder: Some(Box::new(move |data| Box::new(builder(data)))),
+            last_key: None,
+            last_env: None,
+            last_layout: None,
+        }
+    }
+}
+
+impl<T: Data, K: Data> Widget<T> for Memo<T, K> {
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, _base_state: &BaseState, data: &T, env: &Env) {
+        self.child.paint(paint_ctx, data, env);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+        if let Some((last_bc, size)) = self.last_layout {
+            if last_bc == *bc {
+                return size;
+            }
+        }
+        let size = self.child.layout(ctx, bc, data, env);
+        self.child
+            .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+        self.last_layout = Some((*bc, size));
+        size
+    }
+
+    fn event(&mut self, event: &Event, ctx: &mut EventCtx, data: &mut T, env: &Env) {
+        self.child.event(event, ctx, data, env);
+        if ctx.base_state.needs_inval {
+            self.last_layout = None;
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: Option<&T>, data: &T, env: &Env) {
+        let key = (self.key)(data);
+        let key_same = self
+            .last_key
+            .as_ref()
+            .map(|k| k.same(&key))
+            .unwrap_or(false);
+        let env_same = self.last_env.as_ref().map(|e| e.same(env)).unwrap_or(false);
+        if key_same && env_same {
+            return;
+        }
+        if !key_same {
+            if let Some(builder) = self.builder.as_ref() {
+                self.child = WidgetPod::new(builder(data));
+            }
+        }
+        self.last_key = Some(key);
+        self.last_env = Some(env.clone());
+        self.last_layout = None;
+        self.child.update(ctx, data, env);
+        ctx.invalidate();
+    }
+}
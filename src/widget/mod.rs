@@ -17,17 +17,62 @@
 mod align;
 pub use crate::widget::align::Align;
 
+mod animated;
+pub use crate::widget::animated::Animated;
+
 mod button;
-pub use crate::widget::button::{Button, DynLabel, Label, LabelText};
+pub use crate::widget::button::{Button, DynLabel, Label, LabelText, LineBreaking};
+
+mod click;
+pub use crate::widget::click::Click;
+
+mod debounce;
+pub use crate::widget::debounce::{Debounce, Throttle};
+
+mod dnd;
+pub use crate::widget::dnd::{DragSource, DropTarget};
 
 mod flex;
-pub use crate::widget::flex::{Column, Flex, Row};
+pub use crate::widget::flex::{Column, CrossAxisAlignment, Flex, MainAxisAlignment, Row};
+
+mod gpu_surface;
+pub use crate::widget::gpu_surface::{GpuSurface, SurfaceTarget};
+
+mod layer;
+pub use crate::widget::layer::Layer;
+
+mod list;
+pub use crate::widget::list::List;
+
+mod maybe;
+pub use crate::widget::maybe::Maybe;
+
+mod memo;
+pub use crate::widget::memo::Memo;
+
+mod native_view;
+pub use crate::widget::native_view::NativeView;
+
+#[cfg(feature = "video")]
+mod video;
+#[cfg(feature = "video")]
+pub use crate::widget::video::Video;
+
+#[cfg(feature = "lottie")]
+mod lottie;
+#[cfg(feature = "lottie")]
+pub use crate::widget::lottie::Lottie;
+
+#[cfg(feature = "webview")]
+mod web_view;
+#[cfg(feature = "webview")]
+pub use crate::widget::web_view::WebView;
 
 mod padding;
 pub use crate::widget::padding::Padding;
 
 mod scroll;
-pub use crate::widget::scroll::Scroll;
+pub use crate::widget::scroll::{Scroll, SnapPoints};
 
 mod progress_bar;
 pub use crate::widget::progress_bar::ProgressBar;
@@ -38,9 +83,27 @@ pub use crate::widget::slider::Slider;
 mod textbox;
 pub use crate::widget::textbox::TextBox;
 
+mod tween;
+pub use crate::widget::tween::Tween;
+
+mod undoable;
+pub use crate::widget::undoable::Undoable;
+
+mod validated;
+pub use crate::widget::validated::Validated;
+
+mod view_switcher;
+pub use crate::widget::view_switcher::{Either, SwitchTransition, ViewSwitcher};
+
+mod selectable_label;
+pub use crate::widget::selectable_label::SelectableLabel;
+
 mod sized_box;
 pub use crate::widget::sized_box::SizedBox;
 
+mod synced;
+pub use crate::widget::synced::Synced;
+
 mod checkbox;
 pub use crate::widget::checkbox::Checkbox;
 
@@ -0,0 +1,137 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget showing a native platform view.
+
+use crate::kurbo::{Point, Rect, Size};
+use crate::shell::ChildView;
+use crate::{
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx, UpdateCtx, Widget,
+};
+
+/// A widget that reserves its area for a native view, such as an `NSView`,
+/// an `HWND`, or a `GtkWidget`.
+///
+/// The view is added to the window when the widget is first updated, and
+/// removed when the widget is dropped. Each time the widget is painted, the
+/// view is moved to cover the widget's area in the window, which keeps it in
+/// place inside a [`Scroll`]. It is hidden while the widget has no area.
+///
+/// Native views are drawn above all druid content, and receive their own
+/// input events.
+///
+/// By default the widget is 100px square; use a [`SizedBox`] or [`Flex`]
+/// to give it a different size.
+///
+/// [`Scroll`]: struct.Scroll.html
+/// [`SizedBox`]: struct.SizedBox.html
+/// [`Flex`]: struct.Flex.html
+pub struct NativeView {
+    view: ChildView,
+    host: ViewHost,
+}
+
+/// The state needed to keep a native view in place over a widget.
+#[derive(Default)]
+pub(crate) struct ViewHost {
+    attached: bool,
+    /// The area the view was last moved to, in window coordinates.
+    frame: Option<Rect>,
+}
+
+impl NativeView {
+    /// Create a widget showing the given view.
+    pub fn new(view: ChildView) -> NativeView {
+        NativeView {
+            view,
+            host: ViewHost::default(),
+        }
+    }
+
+    /// The native view shown by this widget.
+    pub fn view(&self) -> &ChildView {
+        &self.view
+    }
+}
+
+impl ViewHost {
+    /// Add the view to the window, if it hasn't been already.
+    pub(crate) fn update(&mut self, view: &ChildView, ctx: &mut UpdateCtx) {
+        if !self.attached {
+            view.attach(ctx.window());
+            self.attached = true;
+            // The view is positioned when we're next painted.
+            ctx.invalidate();
+        }
+    }
+
+    /// Move the view to cover the widget being painted.
+    pub(crate) fn paint(&mut self, view: &ChildView, paint_ctx: &mut PaintCtx, size: Size) {
+        if !self.attached {
+            return;
+        }
+        let origin = paint_ctx.current_transform() * Point::ORIGIN;
+        let frame = Rect::from_origin_size(origin, size);
+        // Rects can't be compared directly.
+        if self.frame.map(|f| (f.origin(), f.size())) == Some((frame.origin(), frame.size())) {
+            return;
+        }
+        let was_visible = self.frame.map(|f| f.area() > 0.0).unwrap_or(false);
+        let visible = frame.area() > 0.0;
+        view.set_frame(frame);
+        if visible != was_visible {
+            view.set_visible(visible);
+        }
+        self.frame = Some(frame);
+    }
+
+    pub(crate) fn is_attached(&self) -> bool {
+        self.attached
+    }
+
+    pub(crate) fn detach(&mut self, view: &ChildView) {
+        if self.attached {
+            view.detach();
+            self.attached = false;
+        }
+    }
+}
+
+impl<T: Data> Widget<T> for NativeView {
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, _data: &T, _env: &Env) {
+        self.host.paint(&self.view, paint_ctx, base_state.size());
+    }
+
+    fn layout(
+        &mut self,
+        _layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        _data: &T,
+        _env: &Env,
+    ) -> Size {
+        bc.constrain(Size::new(100.0, 100.0))
+    }
+
+    fn event(&mut self, _event: &Event, _ctx: &mut EventCtx, _data: &mut T, _env: &Env) {}
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: Option<&T>, _data: &T, _env: &Env) {
+        self.host.update(&self.view, ctx);
+    }
+}
+
+impl Drop for NativeView {
+    fn drop(&mut self) {
+        self.host.detach(&self.view);
+    }
+}
@@ -15,31 +15,36 @@
 //! A widget that just adds padding during layout.
 
 use crate::{
-    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx, Point, Rect, Size,
-    UpdateCtx, Widget, WidgetPod,
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, Insets, LayoutCtx, PaintCtx, Point,
+    Rect, Size, UpdateCtx, Widget, WidgetPod,
 };
 
 /// A widget that just adds padding around its child.
 pub struct Padding<T: Data> {
-    left: f64,
-    right: f64,
-    top: f64,
-    bottom: f64,
+    insets: Insets,
 
     child: WidgetPod<T, Box<dyn Widget<T>>>,
 }
 
 impl<T: Data> Padding<T> {
-    /// Create widget with uniform padding.
-    pub fn uniform(padding: f64, child: impl Widget<T> + 'static) -> Padding<T> {
+    /// Create widget with the given padding on each side.
+    ///
+    /// The padding can be anything that converts to [`Insets`], such as a
+    /// `(horizontal, vertical)` pair, or a `(left, top, right, bottom)`
+    /// tuple.
+    ///
+    /// [`Insets`]: ../struct.Insets.html
+    pub fn new(insets: impl Into<Insets>, child: impl Widget<T> + 'static) -> Padding<T> {
         Padding {
-            left: padding,
-            right: padding,
-            top: padding,
-            bottom: padding,
+            insets: insets.into(),
             child: WidgetPod::new(child).boxed(),
         }
     }
+
+    /// Create widget with uniform padding.
+    pub fn uniform(padding: f64, child: impl Widget<T> + 'static) -> Padding<T> {
+        Padding::new(padding, child)
+    }
 }
 
 impl<T: Data> Widget<T> for Padding<T> {
@@ -54,13 +59,11 @@ impl<T: Data> Widget<T> for Padding<T> {
         data: &T,
         env: &Env,
     ) -> Size {
-        let hpad = self.left + self.right;
-        let vpad = self.top + self.bottom;
-        let min = Size::new(bc.min().width - hpad, bc.min().height - vpad);
-        let max = Size::new(bc.max().width - hpad, bc.max().height - vpad);
-        let child_bc = BoxConstraints::new(min, max);
+        let hpad = self.insets.x_value();
+        let vpad = self.insets.y_value();
+        let child_bc = self.insets.shrink(bc);
         let size = self.child.layout(layout_ctx, &child_bc, data, env);
-        let origin = Point::new(self.left, self.top);
+        let origin = Point::new(self.insets.left, self.insets.top);
         self.child
             .set_layout_rect(Rect::from_origin_size(origin, size));
         Size::new(size.width + hpad, size.height + vpad)
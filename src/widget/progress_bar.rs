@@ -53,15 +53,19 @@ impl Widget<f64> for ProgressBarRaw {
         paint_ctx.stroke(rounded_rect, &env.get(theme::BORDER), 2.0);
 
         //Paint the background
-        let background_gradient = LinearGradient::new(
-            UnitPoint::TOP,
-            UnitPoint::BOTTOM,
-            (
-                env.get(theme::BACKGROUND_LIGHT),
-                env.get(theme::BACKGROUND_DARK),
-            ),
-        );
-        paint_ctx.fill(rounded_rect, &background_gradient);
+        let background = match env.try_get(theme::PROGRESS_BAR_BACKGROUND) {
+            Some(background) => background.clone(),
+            None => LinearGradient::new(
+                UnitPoint::TOP,
+                UnitPoint::BOTTOM,
+                (
+                    env.get(theme::BACKGROUND_LIGHT),
+                    env.get(theme::BACKGROUND_DARK),
+                ),
+            )
+            .into(),
+        };
+        background.paint(paint_ctx, rounded_rect);
 
         //Paint the bar
         let calculated_bar_width = clamped * rounded_rect.width();
@@ -74,12 +78,16 @@ impl Widget<f64> for ProgressBarRaw {
             .to_vec2(),
             4.,
         );
-        let bar_gradient = LinearGradient::new(
-            UnitPoint::TOP,
-            UnitPoint::BOTTOM,
-            (env.get(theme::PRIMARY_LIGHT), env.get(theme::PRIMARY_DARK)),
-        );
-        paint_ctx.fill(rounded_rect, &bar_gradient);
+        let fill = match env.try_get(theme::PROGRESS_BAR_FILL) {
+            Some(fill) => fill.clone(),
+            None => LinearGradient::new(
+                UnitPoint::TOP,
+                UnitPoint::BOTTOM,
+                (env.get(theme::PRIMARY_LIGHT), env.get(theme::PRIMARY_DARK)),
+            )
+            .into(),
+        };
+        fill.paint(paint_ctx, rounded_rect);
     }
 
     fn layout(
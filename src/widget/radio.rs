@@ -16,6 +16,7 @@
 
 use std::marker::PhantomData;
 
+use crate::focus;
 use crate::kurbo::{Circle, Point, Rect, Size};
 use crate::piet::{LinearGradient, RenderContext, UnitPoint};
 use crate::theme;
@@ -92,6 +93,7 @@ impl<T: Data + PartialEq> Widget<T> for RadioRaw<T> {
         };
 
         paint_ctx.stroke(circle, &border_color, 1.);
+        paint_ctx.paint_focus_ring(base_state, circle, env);
 
         // Check if data enum matches our variant
         if *data == self.variant {
@@ -129,8 +131,14 @@ impl<T: Data + PartialEq> Widget<T> for RadioRaw<T> {
         match event {
             Event::MouseDown(_) => {
                 ctx.set_active(true);
+                ctx.request_focus();
                 ctx.invalidate();
             }
+            Event::KeyDown(key) if focus::is_activation_key(key) => {
+                *data = self.variant.clone();
+                ctx.invalidate();
+                ctx.set_handled();
+            }
             Event::MouseUp(_) => {
                 if ctx.is_active() {
                     ctx.set_active(false);
@@ -147,6 +155,10 @@ impl<T: Data + PartialEq> Widget<T> for RadioRaw<T> {
         }
     }
 
+    fn accepts_focus(&self) -> bool {
+        true
+    }
+
     fn update(&mut self, ctx: &mut UpdateCtx, _old_data: Option<&T>, _data: &T, _env: &Env) {
         ctx.invalidate();
     }
@@ -14,16 +14,20 @@
 
 //! A container that scrolls its contents.
 
+use std::cmp::Ordering;
 use std::f64::INFINITY;
 use std::time::{Duration, Instant};
 
-use log::error;
+use log::{error, warn};
 
 use crate::{
-    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx, Point, Rect, Size,
-    TimerToken, UpdateCtx, Vec2, Widget, WidgetPod,
+    Animator, BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, Lens, PaintCtx,
+    Point, Rect, ScrollDevice, ScrollPhase, Selector, Size, TimerToken, Transition, UpdateCtx,
+    Vec2, Widget, WidgetPod,
 };
 
+use crate::command::sys;
+
 use crate::piet::RenderContext;
 use crate::theme;
 
@@ -31,25 +35,115 @@ use crate::kurbo::{Affine, RoundedRect};
 
 const SCROLL_BAR_WIDTH: f64 = 8.;
 const SCROLL_BAR_PAD: f64 = 2.;
-
-#[derive(Debug, Clone)]
-enum ScrollDirection {
-    Horizontal,
-    Vertical,
-    All,
+/// The opacity of scroll bars while they are shown.
+const SCROLL_BAR_OPACITY: f64 = 0.7;
+/// The longest, in seconds, that scroll bars wait before fading. Longer
+/// theme values, including infinite ones, are clamped to this.
+const MAX_SCROLL_BAR_FADE_DELAY: f64 = 60.0;
+
+/// The furthest the content can be pulled past its edge, as a fraction of
+/// the viewport.
+const MAX_OVERSCROLL: f64 = 0.25;
+/// How quickly pulling past the edge stops moving the content.
+const OVERSCROLL_RESISTANCE: f64 = 0.5;
+/// The rate at which overscroll springs back, per second.
+const SPRING_BACK_RATE: f64 = 12.0;
+/// How long scrolling has to stop for before the content snaps into place.
+const SNAP_DELAY: Duration = Duration::from_millis(150);
+
+/// The offsets a [`Scroll`] comes to rest at after the user stops
+/// scrolling, set with [`snap_points`].
+///
+/// Along each axis that scrolls, the content moves to the nearest of these
+/// points. The start and the end of the content are always snap points, so
+/// that all of it can be reached.
+///
+/// [`Scroll`]: struct.Scroll.html
+/// [`snap_points`]: struct.Scroll.html#method.snap_points
+#[derive(Debug, Clone, PartialEq)]
+pub enum SnapPoints {
+    /// Multiples of the viewport size, for content made of pages, such as
+    /// a carousel.
+    Pages,
+    /// Multiples of a distance, for content made of children of the same
+    /// size, such as the photos in a strip.
+    Interval(f64),
+    /// These offsets, for content made of children of different sizes.
+    Offsets(Vec<f64>),
+    /// The starts of the children of the content widget, such as the items
+    /// of a [`Row`] or [`Column`], as they were last painted.
+    ///
+    /// [`Row`]: struct.Row.html
+    /// [`Column`]: struct.Column.html
+    Children,
 }
 
-impl ScrollDirection {
-    /// Return the maximum size the container can be given
-    /// its scroll direction and box constraints.
-    /// In practice vertical scrolling will be width limited to
-    /// box constraints and horizontal will be height limited.
-    pub fn max_size(&self, bc: &BoxConstraints) -> Size {
+impl SnapPoints {
+    /// The snap point nearest `offset` along an axis, where the viewport is
+    /// `viewport` long, the content can scroll up to `max`, and the content's
+    /// children start at `children`.
+    fn nearest(&self, offset: f64, viewport: f64, max: f64, children: &[f64]) -> f64 {
+        let multiple = |step: f64| {
+            if step > 0.0 {
+                (offset / step).round() * step
+            } else {
+                offset
+            }
+        };
+        let mut points = vec![0.0, max];
         match self {
-            ScrollDirection::Horizontal => Size::new(INFINITY, bc.max().height),
-            ScrollDirection::Vertical => Size::new(bc.max().width, INFINITY),
-            ScrollDirection::All => Size::new(INFINITY, INFINITY),
+            SnapPoints::Pages => points.push(multiple(viewport)),
+            SnapPoints::Interval(step) => points.push(multiple(*step)),
+            SnapPoints::Offsets(offsets) => points.extend(offsets),
+            SnapPoints::Children => points.extend(children),
         }
+        let distance = |point: &f64| (point.min(max).max(0.0) - offset).abs();
+        let nearest = points
+            .into_iter()
+            .min_by(|a, b| {
+                distance(a)
+                    .partial_cmp(&distance(b))
+                    .unwrap_or(Ordering::Equal)
+            })
+            .unwrap_or(offset);
+        nearest.min(max).max(0.0)
+    }
+}
+
+/// The axes along which a [`Scroll`] moves its content.
+///
+/// [`Scroll`]: struct.Scroll.html
+#[derive(Debug, Clone, Copy)]
+struct ScrollAxes {
+    horizontal: bool,
+    vertical: bool,
+}
+
+impl ScrollAxes {
+    /// Return the maximum size the child can be given, given the
+    /// container's box constraints. Along an axis that doesn't scroll, the
+    /// child is limited to the box constraints.
+    fn max_size(self, bc: &BoxConstraints) -> Size {
+        Size::new(
+            if self.horizontal {
+                INFINITY
+            } else {
+                bc.max().width
+            },
+            if self.vertical {
+                INFINITY
+            } else {
+                bc.max().height
+            },
+        )
+    }
+
+    /// Drop the parts of a movement along axes that don't scroll.
+    fn mask(self, delta: Vec2) -> Vec2 {
+        Vec2::new(
+            if self.horizontal { delta.x } else { 0.0 },
+            if self.vertical { delta.y } else { 0.0 },
+        )
     }
 }
 
@@ -67,18 +161,73 @@ impl Default for ScrollBarsState {
     }
 }
 
+/// A command submitted when the viewport nears the end of the content.
+struct LoadMore {
+    threshold: f64,
+    selector: Selector,
+    /// The size of the content when the command was last submitted. The
+    /// command is only submitted again once the content has changed size.
+    submitted_for: Option<Size>,
+}
+
+/// A lens to the scroll offset, in a form that can be boxed.
+trait OffsetLens<T> {
+    fn get(&self, data: &T) -> Vec2;
+    fn set(&self, data: &mut T, offset: Vec2);
+}
+
+impl<T, L: Lens<T, Vec2>> OffsetLens<T> for L {
+    fn get(&self, data: &T) -> Vec2 {
+        *Lens::get(self, data)
+    }
+
+    fn set(&self, data: &mut T, offset: Vec2) {
+        self.with_mut(data, |o| *o = offset);
+    }
+}
+
 /// A container that scrolls its contents.
 ///
 /// This container holds a single child, and uses the wheel to scroll it
 /// when the child's bounds are larger than the viewport.
 ///
+/// The content can also be scrolled by the program: with the
+/// [`SCROLL_TO`] command, through a lens set with [`offset_lens`], or by a
+/// descendant calling [`EventCtx::scroll_to_view`].
+///
 /// The child is laid out with completely unconstrained layout bounds.
+///
+/// [`SCROLL_TO`]: ../command/sys/constant.SCROLL_TO.html
+/// [`offset_lens`]: #method.offset_lens
+/// [`EventCtx::scroll_to_view`]: ../struct.EventCtx.html#method.scroll_to_view
 pub struct Scroll<T: Data> {
     child: WidgetPod<T, Box<dyn Widget<T>>>,
     child_size: Size,
     scroll_offset: Vec2,
-    direction: ScrollDirection,
+    /// An offset to scroll to once the child has been laid out.
+    pending_offset: Option<Vec2>,
+    offset_lens: Option<Box<dyn OffsetLens<T>>>,
+    /// The offset last read from or written to the data through the lens.
+    reported_offset: Vec2,
+    /// How far a trackpad gesture has pulled past the edges of the content.
+    overscroll: Vec2,
+    springing_back: bool,
+    axes: ScrollAxes,
     scroll_bars: ScrollBarsState,
+    snap: Option<SnapPoints>,
+    /// Fires once scrolling has stopped for `SNAP_DELAY`.
+    snap_timer: TimerToken,
+    /// The offset while the content moves to a snap point.
+    snap_animation: Animator<Vec2>,
+    /// The layout rects of the content's children, for
+    /// `SnapPoints::Children`.
+    child_rects: Vec<Rect>,
+    /// Whether the user's fingers are on the trackpad.
+    in_gesture: bool,
+    load_more: Option<LoadMore>,
+    /// Whether the data has changed since the last layout, so the size of
+    /// the content isn't known yet.
+    layout_pending: bool,
 }
 
 impl<T: Data> Scroll<T> {
@@ -92,8 +241,23 @@ impl<T: Data> Scroll<T> {
             child: WidgetPod::new(child).boxed(),
             child_size: Default::default(),
             scroll_offset: Vec2::new(0.0, 0.0),
-            direction: ScrollDirection::All,
+            pending_offset: None,
+            offset_lens: None,
+            reported_offset: Vec2::new(0.0, 0.0),
+            overscroll: Vec2::new(0.0, 0.0),
+            springing_back: false,
+            axes: ScrollAxes {
+                horizontal: true,
+                vertical: true,
+            },
             scroll_bars: ScrollBarsState::default(),
+            snap: None,
+            snap_timer: TimerToken::INVALID,
+            snap_animation: Animator::new(Vec2::new(0.0, 0.0), Transition::default()),
+            child_rects: Vec::new(),
+            in_gesture: false,
+            load_more: None,
+            layout_pending: true,
         }
     }
 
@@ -101,7 +265,7 @@ impl<T: Data> Scroll<T> {
     ///
     /// Returns `true` if the scroll has been updated.
     fn scroll(&mut self, delta: Vec2, size: Size) -> bool {
-        let mut offset = self.scroll_offset + delta;
+        let mut offset = self.scroll_offset + self.axes.mask(delta);
         offset.x = offset.x.min(self.child_size.width - size.width).max(0.0);
         offset.y = offset.y.min(self.child_size.height - size.height).max(0.0);
         if (offset - self.scroll_offset).hypot2() > 1e-12 {
@@ -112,34 +276,235 @@ impl<T: Data> Scroll<T> {
         }
     }
 
+    /// Update the scroll from a trackpad, letting the content be pulled
+    /// past its edges.
+    ///
+    /// Returns `true` if the scroll has been updated.
+    fn scroll_elastic(&mut self, delta: Vec2, size: Size) -> bool {
+        let delta = self.axes.mask(delta);
+        let max = Vec2::new(
+            (self.child_size.width - size.width).max(0.0),
+            (self.child_size.height - size.height).max(0.0),
+        );
+        let (x, over_x) = elastic_axis(self.scroll_offset.x + self.overscroll.x + delta.x, max.x);
+        let (y, over_y) = elastic_axis(self.scroll_offset.y + self.overscroll.y + delta.y, max.y);
+        let offset = Vec2::new(x, y);
+        let overscroll = Vec2::new(over_x, over_y);
+        let changed = (offset - self.scroll_offset).hypot2() > 1e-12
+            || (overscroll - self.overscroll).hypot2() > 1e-12;
+        self.scroll_offset = offset;
+        self.overscroll = overscroll;
+        changed
+    }
+
+    /// The offset at which the child is drawn, including any overscroll.
+    fn visible_offset(&self, size: Size) -> Vec2 {
+        Vec2::new(
+            rubber_band(self.overscroll.x, size.width),
+            rubber_band(self.overscroll.y, size.height),
+        ) + self.scroll_offset
+    }
+
     /// Limit scroll behavior to allow only vertical scrolling (Y-axis).
     /// The child is laid out with constrained width and infinite height.
-    pub fn vertical(mut self) -> Self {
-        self.direction = ScrollDirection::Vertical;
-        self
+    pub fn vertical(self) -> Self {
+        self.enable_horizontal(false).enable_vertical(true)
     }
 
     /// Limit scroll behavior to allow only horizontal scrolling (X-axis).
     /// The child is laid out with constrained height and infinite width.
-    pub fn horizontal(mut self) -> Self {
-        self.direction = ScrollDirection::Horizontal;
+    ///
+    /// With a mouse wheel, holding shift scrolls horizontally.
+    pub fn horizontal(self) -> Self {
+        self.enable_horizontal(true).enable_vertical(false)
+    }
+
+    /// Builder-style method to set whether the content scrolls
+    /// horizontally. When it doesn't, the child's width is limited to the
+    /// container's.
+    pub fn enable_horizontal(mut self, enabled: bool) -> Self {
+        self.axes.horizontal = enabled;
+        self
+    }
+
+    /// Builder-style method to set whether the content scrolls vertically.
+    /// When it doesn't, the child's height is limited to the container's.
+    pub fn enable_vertical(mut self, enabled: bool) -> Self {
+        self.axes.vertical = enabled;
+        self
+    }
+
+    /// Keep the scroll offset in the data, through a lens.
+    ///
+    /// The offset is written to the data as the content scrolls, and
+    /// changing it in the data scrolls the content, limited to the scrollable
+    /// range as with [`SCROLL_TO`].
+    ///
+    /// [`SCROLL_TO`]: ../command/sys/constant.SCROLL_TO.html
+    pub fn offset_lens(mut self, lens: impl Lens<T, Vec2> + 'static) -> Self {
+        self.offset_lens = Some(Box::new(lens));
+        self
+    }
+
+    /// Builder-style method to make the content come to rest at snap
+    /// points, once the user stops scrolling.
+    ///
+    /// Only scrolling by the user snaps; offsets set with [`SCROLL_TO`] or
+    /// through the [`offset_lens`] are kept as they are.
+    ///
+    /// [`SCROLL_TO`]: ../command/sys/constant.SCROLL_TO.html
+    /// [`offset_lens`]: #method.offset_lens
+    pub fn snap_points(mut self, points: SnapPoints) -> Self {
+        self.snap = Some(points);
+        self
+    }
+
+    /// Builder-style method to submit a command when the viewport comes
+    /// within `threshold` of the end of the content, so that the
+    /// application can load more of it.
+    ///
+    /// The end is the bottom, or the right edge when the content only
+    /// scrolls horizontally. The command has no argument. It is submitted
+    /// once for each size of the content: after more has been loaded, it is
+    /// submitted again when the viewport nears the new end. Content that
+    /// doesn't reach past the threshold, including no content at all, asks
+    /// for more straight away.
+    ///
+    /// # Examples
+    /// ```
+    /// # use druid::Selector;
+    /// # use druid::widget::{Label, Scroll};
+    /// const NEXT_PAGE: Selector = Selector::new("my-app.next-page");
+    ///
+    /// let feed = Scroll::new(Label::new("...")).vertical().load_more(200.0, NEXT_PAGE);
+    /// # let _: Scroll<()> = feed;
+    /// ```
+    pub fn load_more(mut self, threshold: f64, selector: Selector) -> Self {
+        self.load_more = Some(LoadMore {
+            threshold,
+            selector,
+            submitted_for: None,
+        });
         self
     }
 
+    /// Submit the load-more command, if the viewport is near the end of
+    /// content it hasn't been submitted for.
+    fn check_near_end(&mut self, ctx: &mut EventCtx, size: Size) {
+        let remaining = if self.axes.vertical {
+            self.child_size.height - self.scroll_offset.y - size.height
+        } else {
+            self.child_size.width - self.scroll_offset.x - size.width
+        };
+        let child_size = self.child_size;
+        if let Some(load_more) = self.load_more.as_mut() {
+            if remaining <= load_more.threshold && load_more.submitted_for != Some(child_size) {
+                load_more.submitted_for = Some(child_size);
+                ctx.submit_command(load_more.selector.clone().into(), None);
+            }
+        }
+    }
+
+    /// The offset the content snaps to from where it is, if any.
+    fn snap_target(&self, size: Size) -> Option<Vec2> {
+        let snap = self.snap.as_ref()?;
+        let max = Vec2::new(
+            (self.child_size.width - size.width).max(0.0),
+            (self.child_size.height - size.height).max(0.0),
+        );
+        let starts =
+            |start: fn(&Rect) -> f64| self.child_rects.iter().map(start).collect::<Vec<_>>();
+        let target = Vec2::new(
+            snap.nearest(self.scroll_offset.x, size.width, max.x, &starts(|r| r.x0)),
+            snap.nearest(self.scroll_offset.y, size.height, max.y, &starts(|r| r.y0)),
+        );
+        let target = self.axes.mask(target);
+        if (target - self.scroll_offset).hypot2() > 1e-12 {
+            Some(target)
+        } else {
+            None
+        }
+    }
+
+    /// Scroll as little as possible to show `rect`, in the child's
+    /// coordinates. Areas larger than the viewport are shown from their
+    /// start.
+    ///
+    /// Returns `true` if the scroll has been updated.
+    fn scroll_to_view(&mut self, rect: Rect, size: Size) -> bool {
+        fn axis(start: f64, end: f64, offset: f64, viewport: f64) -> f64 {
+            if start < offset || end - start > viewport {
+                start
+            } else if end > offset + viewport {
+                end - viewport
+            } else {
+                offset
+            }
+        }
+        let target = Vec2::new(
+            axis(rect.x0, rect.x1, self.scroll_offset.x, size.width),
+            axis(rect.y0, rect.y1, self.scroll_offset.y, size.height),
+        );
+        self.scroll(target - self.scroll_offset, size)
+    }
+
+    /// Show the scroll bars, and schedule their disappearance after the
+    /// theme's [`SCROLL_BAR_FADE_DELAY`].
+    ///
+    /// [`SCROLL_BAR_FADE_DELAY`]: ../theme/constant.SCROLL_BAR_FADE_DELAY.html
+    fn show_bars(&mut self, ctx: &mut EventCtx, env: &Env) {
+        self.scroll_bars.opacity = SCROLL_BAR_OPACITY;
+        if env.get(theme::SCROLL_BAR_ALWAYS_VISIBLE) {
+            return;
+        }
+        // `max` and `min` also turn NaN into zero.
+        let delay = env
+            .get(theme::SCROLL_BAR_FADE_DELAY)
+            .max(0.0)
+            .min(MAX_SCROLL_BAR_FADE_DELAY);
+        let deadline = Instant::now() + Duration::from_secs_f64(delay);
+        self.scroll_bars.timer_id = ctx.request_timer(deadline);
+    }
+
+    /// Whether `pos`, in the container's coordinates, is over the track of
+    /// a scroll bar.
+    fn is_over_track(&self, pos: Point, size: Size) -> bool {
+        let reach = SCROLL_BAR_WIDTH + 2.0 * SCROLL_BAR_PAD;
+        if pos.x > size.width || pos.y > size.height {
+            return false;
+        }
+        let vertical = self.axes.vertical
+            && size.height < self.child_size.height
+            && pos.x >= size.width - reach;
+        let horizontal = self.axes.horizontal
+            && size.width < self.child_size.width
+            && pos.y >= size.height - reach;
+        vertical || horizontal
+    }
+
     /// Draw scroll bars.
     fn draw_bars(&self, paint_ctx: &mut PaintCtx, viewport: &Rect, env: &Env) {
-        if self.scroll_bars.opacity <= 0.0 {
+        let opacity = if env.get(theme::SCROLL_BAR_ALWAYS_VISIBLE) {
+            SCROLL_BAR_OPACITY
+        } else {
+            self.scroll_bars.opacity
+        };
+        if opacity <= 0.0 {
             return;
         }
 
-        let brush = paint_ctx.render_ctx.solid_brush(
-            env.get(theme::SCROLL_BAR_COLOR)
-                .with_alpha(self.scroll_bars.opacity),
-        );
-        let border_brush = paint_ctx.render_ctx.solid_brush(
-            env.get(theme::SCROLL_BAR_BORDER_COLOR)
-                .with_alpha(self.scroll_bars.opacity),
-        );
+        // Translucent bars are hard to see against a busy background.
+        let opacity = if env.get(theme::HIGH_CONTRAST) {
+            1.0
+        } else {
+            opacity
+        };
+        let brush = paint_ctx
+            .render_ctx
+            .solid_brush(env.get(theme::SCROLL_BAR_COLOR).with_alpha(opacity));
+        let border_brush = paint_ctx
+            .render_ctx
+            .solid_brush(env.get(theme::SCROLL_BAR_BORDER_COLOR).with_alpha(opacity));
         let bar_thickness = SCROLL_BAR_WIDTH;
 
         // Scroll bar max bounds
@@ -150,26 +515,36 @@ impl<T: Data> Scroll<T> {
             self.scroll_offset.y - SCROLL_BAR_PAD + viewport.height(),
         );
 
-        let content_size = Size::new(
-            viewport.width() - 2.0 * SCROLL_BAR_PAD,
-            viewport.height() - 2.0 * SCROLL_BAR_PAD,
+        let show_vertical = self.axes.vertical && viewport.height() < self.child_size.height;
+        let show_horizontal = self.axes.horizontal && viewport.width() < self.child_size.width;
+
+        // When both bars are shown, each stops short of the corner, so that
+        // they don't overlap.
+        let corner = if show_vertical && show_horizontal {
+            bar_thickness + SCROLL_BAR_PAD
+        } else {
+            0.0
+        };
+        let track_size = Size::new(
+            viewport.width() - 2.0 * SCROLL_BAR_PAD - corner,
+            viewport.height() - 2.0 * SCROLL_BAR_PAD - corner,
         );
 
         let scale = Vec2::new(
-            content_size.width / self.child_size.width,
-            content_size.height / self.child_size.height,
+            track_size.width / self.child_size.width,
+            track_size.height / self.child_size.height,
         );
 
         // Vertical bar
-        if viewport.height() < self.child_size.height {
-            let h = (scale.y * content_size.height).ceil();
+        if show_vertical {
+            let h = (scale.y * viewport.height()).ceil();
             let dh = (scale.y * self.scroll_offset.y).ceil();
 
             let x0 = scroll_bar_bounds.x1;
             let y0 = scroll_bar_bounds.y0 + dh;
 
             let x1 = x0 - bar_thickness;
-            let y1 = (y0 + h).min(scroll_bar_bounds.y1);
+            let y1 = (y0 + h).min(scroll_bar_bounds.y1 - corner);
 
             let rect = RoundedRect::new(x0, y0, x1, y1, 5.0);
             paint_ctx.render_ctx.fill(rect, &brush);
@@ -177,14 +552,14 @@ impl<T: Data> Scroll<T> {
         }
 
         // Horizontal bar
-        if viewport.width() < self.child_size.width {
-            let w = (scale.x * content_size.width).ceil();
+        if show_horizontal {
+            let w = (scale.x * viewport.width()).ceil();
             let dw = (scale.x * self.scroll_offset.x).ceil();
 
             let x0 = scroll_bar_bounds.x0 + dw;
             let y0 = scroll_bar_bounds.y1;
 
-            let x1 = (x0 + w).min(scroll_bar_bounds.x1);
+            let x1 = (x0 + w).min(scroll_bar_bounds.x1 - corner);
             let y1 = y0 - bar_thickness;
 
             let rect = RoundedRect::new(x0, y0, x1, y1, 5.0);
@@ -194,6 +569,24 @@ impl<T: Data> Scroll<T> {
     }
 }
 
+/// Split a desired scroll position into the part within `[0, max]` and
+/// the part past either end.
+fn elastic_axis(pos: f64, max: f64) -> (f64, f64) {
+    let clamped = pos.min(max).max(0.0);
+    (clamped, pos - clamped)
+}
+
+/// The distance the content actually moves when pulled `over` past its edge,
+/// which approaches a fraction of the viewport the further it is pulled.
+fn rubber_band(over: f64, viewport: f64) -> f64 {
+    let limit = viewport * MAX_OVERSCROLL;
+    if over == 0.0 || limit <= 0.0 {
+        return 0.0;
+    }
+    let pulled = over.abs() * OVERSCROLL_RESISTANCE / limit;
+    over.signum() * limit * (1.0 - 1.0 / (pulled + 1.0))
+}
+
 impl<T: Data> Widget<T> for Scroll<T> {
     fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env) {
         if let Err(e) = paint_ctx.save() {
@@ -202,9 +595,18 @@ impl<T: Data> Widget<T> for Scroll<T> {
         }
         let viewport = Rect::from_origin_size(Point::ORIGIN, base_state.size());
         paint_ctx.clip(viewport);
-        paint_ctx.transform(Affine::translate(-self.scroll_offset));
-        self.child.paint(paint_ctx, data, env);
+        paint_ctx.transform(Affine::translate(-self.visible_offset(viewport.size())));
+        if self.snap == Some(SnapPoints::Children) {
+            let child = &mut self.child;
+            self.child_rects =
+                paint_ctx.collect_child_rects(|paint_ctx| child.paint(paint_ctx, data, env));
+        } else {
+            self.child.paint(paint_ctx, data, env);
+        }
 
+        // The scroll bars stay put when the content is pulled past its edge.
+        let overscroll = self.visible_offset(viewport.size()) - self.scroll_offset;
+        paint_ctx.transform(Affine::translate(overscroll));
         self.draw_bars(paint_ctx, &viewport, env);
 
         if let Err(e) = paint_ctx.restore() {
@@ -213,12 +615,17 @@ impl<T: Data> Widget<T> for Scroll<T> {
     }
 
     fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
-        let child_bc = BoxConstraints::new(Size::ZERO, self.direction.max_size(bc));
+        let child_bc = BoxConstraints::new(Size::ZERO, self.axes.max_size(bc));
         let size = self.child.layout(ctx, &child_bc, data, env);
         self.child_size = size;
+        self.layout_pending = false;
         self.child
             .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
         let self_size = bc.constrain(Size::new(100.0, 100.0));
+        if let Some(offset) = self.pending_offset.take() {
+            self.scroll_offset = self.axes.mask(offset);
+            self.snap_animation.jump_to(self.scroll_offset);
+        }
         let _ = self.scroll(Vec2::new(0.0, 0.0), self_size);
         self_size
     }
@@ -226,11 +633,46 @@ impl<T: Data> Widget<T> for Scroll<T> {
     fn event(&mut self, event: &Event, ctx: &mut EventCtx, data: &mut T, env: &Env) {
         let size = ctx.base_state.size();
         let viewport = Rect::from_origin_size(Point::ORIGIN, size);
-        let child_event = event.transform_scroll(self.scroll_offset, viewport);
+        let child_event = event.transform_scroll(self.visible_offset(size), viewport);
         if let Some(child_event) = child_event {
             self.child.event(&child_event, ctx, data, env)
         };
 
+        if let Some(rect) = ctx.base_state.request_scroll.take() {
+            if self.scroll_to_view(rect, size) {
+                ctx.invalidate();
+                self.show_bars(ctx, env);
+            }
+            // Enclosing containers scroll to the part that is now visible.
+            let origin = rect.origin() - self.scroll_offset;
+            let visible = Rect::from_origin_size(origin, rect.size()).intersect(viewport);
+            ctx.base_state.request_scroll = Some(visible);
+        }
+
+        if let Event::AnimFrame(interval) = event {
+            if self.springing_back {
+                let decay = (-SPRING_BACK_RATE * (*interval as f64) * 1e-9).exp();
+                self.overscroll = self.overscroll * decay;
+                if self.overscroll.hypot() < 0.5 {
+                    self.overscroll = Vec2::new(0.0, 0.0);
+                    self.springing_back = false;
+                } else {
+                    ctx.request_anim_frame();
+                }
+                ctx.invalidate();
+            }
+        }
+
+        if let Event::AnimFrame(interval) = event {
+            if self.snap_animation.is_running() {
+                if self.snap_animation.advance(*interval) {
+                    ctx.request_anim_frame();
+                }
+                self.scroll_offset = self.snap_animation.value();
+                ctx.invalidate();
+            }
+        }
+
         match event {
             // The scroll bars will fade immediately if there's some other widget requesting animation.
             // Guard by the timer id being invalid.
@@ -242,9 +684,46 @@ impl<T: Data> Widget<T> for Scroll<T> {
                     ctx.request_anim_frame();
                 }
             }
+            // The bars are shown while the mouse is over them.
+            Event::MouseMoved(mouse) if self.is_over_track(mouse.pos, size) => {
+                if self.scroll_bars.opacity < SCROLL_BAR_OPACITY {
+                    ctx.invalidate();
+                }
+                self.show_bars(ctx, env);
+            }
+            Event::Command(cmd) if cmd.selector == sys::SCROLL_TO => {
+                match cmd.get_object::<Vec2>() {
+                    Some(offset) => {
+                        self.pending_offset = Some(*offset);
+                        ctx.invalidate();
+                        self.show_bars(ctx, env);
+                    }
+                    None => warn!("SCROLL_TO expects a Vec2 argument"),
+                }
+            }
+            Event::Timer(id) if *id == self.snap_timer => {
+                self.snap_timer = TimerToken::INVALID;
+                if let Some(target) = self.snap_target(size).filter(|_| !self.in_gesture) {
+                    if env.get(theme::REDUCED_MOTION) {
+                        self.scroll_offset = target;
+                    } else {
+                        self.snap_animation.jump_to(self.scroll_offset);
+                        self.snap_animation.animate_to(target);
+                        ctx.request_anim_frame();
+                    }
+                    ctx.invalidate();
+                    self.show_bars(ctx, env);
+                }
+            }
             Event::Timer(id) if *id == self.scroll_bars.timer_id => {
-                // Schedule scroll bars animation
-                ctx.request_anim_frame();
+                if env.get(theme::REDUCED_MOTION) {
+                    // Hide the scroll bars without fading them out
+                    self.scroll_bars.opacity = 0.0;
+                    ctx.invalidate();
+                } else {
+                    // Schedule scroll bars animation
+                    ctx.request_anim_frame();
+                }
                 self.scroll_bars.timer_id = TimerToken::INVALID;
             }
             _ => (),
@@ -252,20 +731,253 @@ impl<T: Data> Widget<T> for Scroll<T> {
 
         if !ctx.is_handled() {
             if let Event::Wheel(wheel) = event {
-                if self.scroll(wheel.delta, size) {
+                // Scrolling takes over from any snap in progress.
+                self.snap_animation.jump_to(self.scroll_offset);
+                self.in_gesture = wheel.is_in_gesture();
+                let scrolled = match wheel.device {
+                    ScrollDevice::Trackpad => self.scroll_elastic(wheel.delta, size),
+                    // Shift turns a vertical wheel into a horizontal one, on
+                    // platforms where the system doesn't already.
+                    ScrollDevice::Wheel
+                        if self.axes.horizontal && wheel.mods.shift && wheel.delta.x == 0.0 =>
+                    {
+                        self.scroll(Vec2::new(wheel.delta.y, 0.0), size)
+                    }
+                    ScrollDevice::Wheel => self.scroll(wheel.delta, size),
+                };
+                // Overscroll stays in place while the user's fingers are down.
+                let spring_back = !wheel.is_in_gesture() && self.overscroll.hypot2() > 0.0;
+                if spring_back && env.get(theme::REDUCED_MOTION) {
+                    self.overscroll = Vec2::new(0.0, 0.0);
+                    ctx.invalidate();
+                } else if spring_back && !self.springing_back {
+                    self.springing_back = true;
+                    ctx.request_anim_frame();
+                } else if wheel.is_in_gesture() {
+                    self.springing_back = false;
+                }
+                if scrolled {
                     ctx.invalidate();
                     ctx.set_handled();
-
-                    // Display scroll bars and schedule their disappearance
-                    self.scroll_bars.opacity = 0.7;
-                    let deadline = Instant::now() + Duration::from_millis(1500);
-                    self.scroll_bars.timer_id = ctx.request_timer(deadline);
+                    self.show_bars(ctx, env);
                 }
+                // Snap once scrolling, including any momentum, has stopped.
+                if self.snap.is_some() && (scrolled || wheel.phase == ScrollPhase::Ended) {
+                    self.snap_timer = ctx.request_timer(Instant::now() + SNAP_DELAY);
+                }
+            }
+        }
+
+        if self.load_more.is_some() {
+            if !self.layout_pending {
+                self.check_near_end(ctx, size);
+            } else if let Event::AnimFrame(_) = event {
+                // Animation frames come before layout; check on the next one.
+                ctx.request_anim_frame();
+            }
+        }
+
+        // Changes made in layout are reported with the next event.
+        if let Some(lens) = self.offset_lens.as_ref() {
+            if !self.scroll_offset.same(&self.reported_offset) {
+                lens.set(data, self.scroll_offset);
+                self.reported_offset = self.scroll_offset;
             }
         }
     }
 
-    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: Option<&T>, data: &T, env: &Env) {
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: Option<&T>, data: &T, env: &Env) {
+        // New content may not reach the end of the viewport, and no event
+        // may come to show it; an animation frame checks once it is laid out.
+        if self.load_more.is_some() && !old_data.map_or(false, |old| old.same(data)) {
+            self.layout_pending = true;
+            ctx.request_anim_frame();
+        }
+        if let Some(lens) = self.offset_lens.as_ref() {
+            let offset = lens.get(data);
+            if !offset.same(&self.reported_offset) {
+                self.pending_offset = Some(offset);
+                self.reported_offset = offset;
+                ctx.invalidate();
+            }
+        }
         self.child.update(ctx, data, env);
     }
 }
+
+#[cfg(all(test, not(any(target_os = "windows", target_arch = "wasm32"))))]
+mod tests {
+    use super::*;
+    use crate::harness::pair::Second;
+    use crate::widget::{Column, SizedBox};
+    use crate::{Command, Harness, KeyModifiers};
+
+    #[test]
+    fn scroll_to_offset() {
+        let mut harness = Harness::new(
+            || {
+                Scroll::new(SizedBox::empty().height(1000.0))
+                    .vertical()
+                    .offset_lens(Second)
+            },
+            (0u32, Vec2::new(0.0, 0.0)),
+        );
+        let bottom = Vec2::new(0.0, std::f64::INFINITY);
+        harness.submit_command(Command::new(sys::SCROLL_TO, bottom));
+        // The offset is limited to the content, and reported on the next event.
+        harness.mouse_move(Point::new(10.0, 10.0));
+        assert_eq!(harness.data().1, Vec2::new(0.0, 600.0));
+    }
+
+    // Over the vertical bar of a 400x400 window, near the top.
+    const OVER_BAR: Point = Point::new(394.0, 50.0);
+    const AWAY: Point = Point::new(200.0, 50.0);
+
+    fn tall_scroll() -> Harness<u32> {
+        Harness::new(
+            || Scroll::new(SizedBox::empty().height(1000.0)).vertical(),
+            0,
+        )
+    }
+
+    fn bar_is_shown(harness: &mut Harness<u32>) -> bool {
+        let image = harness.render();
+        image.pixel(OVER_BAR.x as usize, OVER_BAR.y as usize)
+            != image.pixel(AWAY.x as usize, AWAY.y as usize)
+    }
+
+    #[test]
+    fn bars_show_on_hover_and_fade() {
+        let mut harness = tall_scroll();
+        assert!(!bar_is_shown(&mut harness));
+        harness.mouse_move(OVER_BAR);
+        assert!(bar_is_shown(&mut harness));
+        harness.mouse_move(AWAY);
+        harness.advance_time(Duration::from_millis(1000));
+        assert!(bar_is_shown(&mut harness));
+        harness.advance_time(Duration::from_millis(1000));
+        harness.animate(60);
+        assert!(!bar_is_shown(&mut harness));
+    }
+
+    #[test]
+    fn infinite_fade_delay_is_clamped() {
+        let mut harness = tall_scroll();
+        {
+            let mut state = harness.app().state().borrow_mut();
+            state.env = state
+                .env
+                .clone()
+                .adding(theme::SCROLL_BAR_FADE_DELAY, std::f64::INFINITY);
+        }
+        harness.mouse_move(OVER_BAR);
+        harness.mouse_move(AWAY);
+        harness.advance_time(Duration::from_secs(10));
+        assert!(bar_is_shown(&mut harness));
+    }
+
+    #[test]
+    fn shift_wheel_scrolls_horizontally() {
+        let mut harness = Harness::new(
+            || {
+                Scroll::new(SizedBox::empty().width(1000.0))
+                    .horizontal()
+                    .offset_lens(Second)
+            },
+            (0u32, Vec2::new(0.0, 0.0)),
+        );
+        harness.mouse_move(Point::new(10.0, 10.0));
+        let shift = KeyModifiers {
+            shift: true,
+            ..Default::default()
+        };
+        harness.wheel(Vec2::new(0.0, 50.0), shift);
+        // A plain wheel would scroll vertically, which this scroll can't.
+        harness.wheel(Vec2::new(0.0, 50.0), KeyModifiers::default());
+        assert_eq!(harness.data().1, Vec2::new(50.0, 0.0));
+    }
+
+    #[test]
+    fn shift_wheel_scrolls_vertical_only() {
+        let mut harness = Harness::new(
+            || {
+                Scroll::new(SizedBox::empty().height(1000.0))
+                    .vertical()
+                    .offset_lens(Second)
+            },
+            (0u32, Vec2::new(0.0, 0.0)),
+        );
+        harness.mouse_move(Point::new(10.0, 10.0));
+        let shift = KeyModifiers {
+            shift: true,
+            ..Default::default()
+        };
+        harness.wheel(Vec2::new(0.0, 50.0), shift);
+        assert_eq!(harness.data().1, Vec2::new(0.0, 50.0));
+    }
+
+    #[test]
+    fn scroll_snaps_to_pages() {
+        let mut harness = Harness::new(
+            || {
+                Scroll::new(SizedBox::empty().height(2000.0))
+                    .vertical()
+                    .snap_points(SnapPoints::Pages)
+                    .offset_lens(Second)
+            },
+            (0u32, Vec2::new(0.0, 0.0)),
+        );
+        harness.mouse_move(Point::new(10.0, 10.0));
+        harness.wheel(Vec2::new(0.0, 250.0), KeyModifiers::default());
+        assert_eq!(harness.data().1, Vec2::new(0.0, 250.0));
+        // Scrolling stopped nearer the second page than the first.
+        harness.advance_time(Duration::from_millis(500));
+        harness.animate(60);
+        assert_eq!(harness.data().1, Vec2::new(0.0, 400.0));
+    }
+
+    #[test]
+    fn scroll_snaps_to_children() {
+        let mut harness = Harness::new(
+            || {
+                let mut col = Column::new();
+                for height in &[300.0, 100.0, 700.0] {
+                    col.add_child(SizedBox::empty().height(*height), 0.0);
+                }
+                Scroll::new(col)
+                    .vertical()
+                    .snap_points(SnapPoints::Children)
+                    .offset_lens(Second)
+            },
+            (0u32, Vec2::new(0.0, 0.0)),
+        );
+        harness.mouse_move(Point::new(10.0, 10.0));
+        harness.wheel(Vec2::new(0.0, 250.0), KeyModifiers::default());
+        // Nearer the start of the second child than the first.
+        harness.advance_time(Duration::from_millis(500));
+        harness.animate(60);
+        assert_eq!(harness.data().1, Vec2::new(0.0, 300.0));
+    }
+
+    #[test]
+    fn scroll_asks_for_more_near_the_end() {
+        const LOAD_MORE: Selector = Selector::new("test.load-more");
+        let mut harness = Harness::new(
+            || {
+                Scroll::new(SizedBox::empty().height(1000.0))
+                    .vertical()
+                    .load_more(100.0, LOAD_MORE)
+            },
+            0u32,
+        );
+        harness.mouse_move(Point::new(10.0, 10.0));
+        assert!(harness.take_commands().is_empty());
+
+        // Asked once, until the content grows.
+        harness.wheel(Vec2::new(0.0, 550.0), KeyModifiers::default());
+        harness.wheel(Vec2::new(0.0, 10.0), KeyModifiers::default());
+        let commands = harness.take_commands();
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].selector, LOAD_MORE);
+    }
+}
@@ -0,0 +1,225 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A label whose text can be selected and copied.
+
+use crate::command::sys;
+use crate::kurbo::{Point, Rect, RoundedRect, Size};
+use crate::piet::{PietText, RenderContext, UnitPoint};
+use crate::text::{FontSet, LineLayout};
+use crate::theme;
+use crate::{
+    BaseState, BoxConstraints, Cursor, Data, Env, Event, EventCtx, HotKey, KeyCode, LayoutCtx,
+    PaintCtx, RawMods, SysMods, UpdateCtx, Widget,
+};
+
+use super::textbox::{next_grapheme, prev_grapheme, Selection};
+use super::LabelText;
+
+#[cfg(target_os = "linux")]
+use crate::shell::application::Application;
+
+/// A label whose text can be selected with the mouse or keyboard, and
+/// copied with the [`COPY`] command.
+///
+/// This is meant for read-only text that users may want to copy, such as
+/// error messages, paths, or log output. The text is selected by dragging
+/// over it; once the label has focus, shift and the arrow keys extend the
+/// selection, and select-all selects the whole text.
+///
+/// [`COPY`]: ../command/sys/constant.COPY.html
+pub struct SelectableLabel<T> {
+    text: LabelText<T>,
+    selection: Selection,
+}
+
+impl<T: Data> SelectableLabel<T> {
+    /// Create a new selectable label.
+    pub fn new(text: impl Into<LabelText<T>>) -> Self {
+        SelectableLabel {
+            text: text.into(),
+            selection: Selection::caret(0),
+        }
+    }
+
+    /// The currently selected text.
+    pub fn selected_text(&self) -> &str {
+        let text = self.text.display_text();
+        let selection = self.selection.constrain_to(text);
+        &text[selection.range()]
+    }
+
+    fn get_layout(&self, t: &mut PietText, env: &Env, text: &str) -> LineLayout {
+        // TODO: caching of both the format and the layout
+        FontSet::from_env(t, env).layout(t, text)
+    }
+
+    /// The width of the text up to the given offset.
+    fn width_to(&self, t: &mut PietText, env: &Env, offset: usize) -> f64 {
+        match self.text.display_text().get(..offset) {
+            Some(prefix) if !prefix.is_empty() => self.get_layout(t, env, prefix).width(),
+            _ => 0.0,
+        }
+    }
+
+    /// Find the grapheme boundary closest to a horizontal position.
+    // TODO: use the text layout's hit testing, once piet has it.
+    fn offset_for_x(&self, t: &mut PietText, env: &Env, x: f64) -> usize {
+        let text = self.text.display_text();
+        let mut offset = 0;
+        let mut prev_width = 0.0;
+        while offset < text.len() {
+            let next = next_grapheme(text, offset);
+            let width = self.width_to(t, env, next);
+            if x < (prev_width + width) / 2.0 {
+                return offset;
+            }
+            offset = next;
+            prev_width = width;
+        }
+        text.len()
+    }
+
+    fn text_origin(&self, size: Size, env: &Env) -> Point {
+        let font_size = env.get(theme::TEXT_SIZE_NORMAL);
+        let mut origin = UnitPoint::LEFT.resolve(Rect::from_origin_size(
+            Point::ORIGIN,
+            Size::new(size.width, size.height + (font_size * 1.2) / 2.),
+        ));
+        origin.y = origin.y.min(size.height);
+        origin
+    }
+
+    fn selection_changed(&self, ctx: &mut EventCtx) {
+        // On Linux, selected text becomes the primary selection.
+        #[cfg(target_os = "linux")]
+        {
+            let text = self.selected_text();
+            if !text.is_empty() {
+                Application::set_primary_selection_contents(text.into());
+            }
+        }
+        ctx.invalidate();
+    }
+}
+
+impl<T: Data> Widget<T> for SelectableLabel<T> {
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, _data: &T, env: &Env) {
+        let font_size = env.get(theme::TEXT_SIZE_NORMAL);
+        let origin = self.text_origin(base_state.size(), env);
+
+        let selection = self.selection.constrain_to(self.text.display_text());
+        if !selection.is_caret() {
+            let left = self.width_to(paint_ctx.text(), env, selection.min());
+            let right = self.width_to(paint_ctx.text(), env, selection.max());
+            let color = if base_state.has_focus() {
+                env.get(theme::SELECTION_COLOR)
+            } else {
+                env.get(theme::BORDER_LIGHT)
+            };
+            let rect = RoundedRect::from_origin_size(
+                Point::new(origin.x + left - 1., origin.y - font_size),
+                Size::new(right - left + 2., font_size + 4.).to_vec2(),
+                1.,
+            );
+            paint_ctx.fill(rect, &color);
+        }
+
+        let text_layout = self.get_layout(paint_ctx.text(), env, self.text.display_text());
+        text_layout.draw(&mut **paint_ctx, origin, &env.get(theme::LABEL_COLOR));
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        _data: &T,
+        env: &Env,
+    ) -> Size {
+        let font_size = env.get(theme::TEXT_SIZE_NORMAL);
+        let text_layout = self.get_layout(layout_ctx.text(), env, self.text.display_text());
+        bc.constrain((text_layout.width(), font_size * 1.2))
+    }
+
+    fn event(&mut self, event: &Event, ctx: &mut EventCtx, _data: &mut T, env: &Env) {
+        match event {
+            Event::MouseDown(mouse) if mouse.button.is_left() => {
+                ctx.request_focus();
+                ctx.set_active(true);
+                let offset = self.offset_for_x(ctx.text(), env, mouse.pos.x);
+                self.selection = if mouse.mods.shift {
+                    Selection::new(self.selection.start, offset)
+                } else {
+                    Selection::caret(offset)
+                };
+                ctx.invalidate();
+            }
+            Event::MouseMoved(mouse) => {
+                ctx.set_cursor(&Cursor::IBeam);
+                if ctx.is_active() {
+                    let offset = self.offset_for_x(ctx.text(), env, mouse.pos.x);
+                    if offset != self.selection.end {
+                        self.selection.end = offset;
+                        ctx.invalidate();
+                    }
+                }
+            }
+            Event::MouseUp(_) if ctx.is_active() => {
+                ctx.set_active(false);
+                self.selection_changed(ctx);
+            }
+            Event::FocusChanged(false) => {
+                self.selection = Selection::caret(self.selection.end);
+                ctx.invalidate();
+            }
+            Event::Command(cmd) if ctx.has_focus() && cmd.selector == sys::COPY => {
+                let text = self.selected_text();
+                if !text.is_empty() {
+                    ctx.win_ctx.set_clipboard_contents(text.into());
+                }
+                ctx.set_handled();
+            }
+            Event::Command(cmd) if ctx.has_focus() && cmd.selector == sys::SELECT_ALL => {
+                self.selection = Selection::new(0, self.text.display_text().len());
+                self.selection_changed(ctx);
+                ctx.set_handled();
+            }
+            Event::KeyDown(key_event) => {
+                let text = self.text.display_text();
+                match key_event {
+                    k_e if (HotKey::new(SysMods::Cmd, "a")).matches(k_e) => {
+                        self.selection = Selection::new(0, text.len());
+                    }
+                    k_e if (HotKey::new(RawMods::Shift, KeyCode::ArrowLeft)).matches(k_e) => {
+                        self.selection.end = prev_grapheme(text, self.selection.end);
+                    }
+                    k_e if (HotKey::new(RawMods::Shift, KeyCode::ArrowRight)).matches(k_e) => {
+                        self.selection.end = next_grapheme(text, self.selection.end);
+                    }
+                    _ => return,
+                }
+                self.selection_changed(ctx);
+                ctx.set_handled();
+            }
+            _ => (),
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: Option<&T>, data: &T, env: &Env) {
+        if self.text.resolve(data, env) {
+            self.selection = self.selection.constrain_to(self.text.display_text());
+            ctx.invalidate();
+        }
+    }
+}
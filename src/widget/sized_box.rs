@@ -119,6 +119,13 @@ impl<T: Data> Widget<T> for SizedBox<T> {
         }
     }
 
+    fn accepts_focus(&self) -> bool {
+        self.inner
+            .as_ref()
+            .map(|inner| inner.accepts_focus())
+            .unwrap_or(false)
+    }
+
     fn update(&mut self, ctx: &mut UpdateCtx, old_data: Option<&T>, data: &T, env: &Env) {
         if let Some(ref mut inner) = self.inner {
             inner.update(ctx, old_data, data, env);
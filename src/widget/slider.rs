@@ -19,9 +19,15 @@ use crate::piet::{LinearGradient, RenderContext, UnitPoint};
 use crate::theme;
 use crate::widget::Align;
 use crate::{
-    BaseState, BoxConstraints, Env, Event, EventCtx, LayoutCtx, PaintCtx, UpdateCtx, Widget,
+    BaseState, BoxConstraints, Env, Event, EventCtx, KeyCode, LayoutCtx, PaintCtx, UpdateCtx,
+    Widget,
 };
 
+/// The change in value for a press of an arrow key.
+const ARROW_STEP: f64 = 0.01;
+/// The change in value for a press of page up or page down.
+const PAGE_STEP: f64 = 0.1;
+
 /// A slider, allowing interactive update of a numeric value.
 #[derive(Debug, Clone)]
 pub struct Slider;
@@ -128,6 +134,7 @@ impl Widget<f64> for SliderRaw {
 
         //Actually paint the knob
         paint_ctx.fill(knob_circle, &knob_gradient);
+        paint_ctx.paint_focus_ring(base_state, knob_circle, env);
     }
 
     fn layout(
@@ -158,6 +165,7 @@ impl Widget<f64> for SliderRaw {
         match event {
             Event::MouseDown(mouse) => {
                 ctx.set_active(true);
+                ctx.request_focus();
                 if self.knob_hit_test(knob_size, mouse.pos) {
                     self.x_offset = self.knob_pos.x - mouse.pos.x
                 } else {
@@ -186,10 +194,28 @@ impl Widget<f64> for SliderRaw {
                 }
                 ctx.invalidate();
             }
+            Event::KeyDown(key) => {
+                let step = match key.key_code {
+                    KeyCode::ArrowLeft | KeyCode::ArrowDown => -ARROW_STEP,
+                    KeyCode::ArrowRight | KeyCode::ArrowUp => ARROW_STEP,
+                    KeyCode::PageDown => -PAGE_STEP,
+                    KeyCode::PageUp => PAGE_STEP,
+                    KeyCode::Home => -1.0,
+                    KeyCode::End => 1.0,
+                    _ => return,
+                };
+                *data = (data.max(0.0).min(1.0) + step).max(0.0).min(1.0);
+                ctx.invalidate();
+                ctx.set_handled();
+            }
             _ => (),
         }
     }
 
+    fn accepts_focus(&self) -> bool {
+        true
+    }
+
     fn update(&mut self, ctx: &mut UpdateCtx, _old_data: Option<&f64>, _data: &f64, _env: &Env) {
         ctx.invalidate();
     }
@@ -0,0 +1,157 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget that keeps data in sync with externally owned state.
+
+use std::time::{Duration, Instant};
+
+use crate::kurbo::{Point, Rect, Size};
+use crate::shared::SHARED_STATE_CHANGED;
+use crate::{
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx, SharedState,
+    TimerToken, UpdateCtx, Widget, WidgetPod,
+};
+
+/// A widget that copies between a [`SharedState`] and its child's data.
+///
+/// The `read` closure copies the relevant part of the shared state into
+/// the data. It runs when the widget first receives an event in its window,
+/// whenever the state is [notified] of a change, and, if a poll interval is
+/// set, on every tick of the interval. Polling suits state that changes
+/// continuously, such as the position of a simulation, where notifying
+/// on every change would be wasteful.
+///
+/// If a `write` closure is provided, changes the child makes to the data
+/// are copied back into the shared state, and other `Synced` widgets showing
+/// the same state, in any window, are notified.
+///
+/// [`SharedState`]: ../struct.SharedState.html
+/// [notified]: ../struct.SharedState.html#method.notify
+pub struct Synced<T: Data, S> {
+    child: WidgetPod<T, Box<dyn Widget<T>>>,
+    shared: SharedState<S>,
+    read: Box<dyn Fn(&S, &mut T)>,
+    write: Option<Box<dyn Fn(&T, &mut S)>>,
+    poll_interval: Option<Duration>,
+    timer: TimerToken,
+    /// The key of this widget's window sink, once it is connected.
+    sink: Option<usize>,
+}
+
+impl<T: Data, S: 'static> Synced<T, S> {
+    /// Create a new widget, reading the data from `shared` with `read`.
+    pub fn new(
+        child: impl Widget<T> + 'static,
+        shared: SharedState<S>,
+        read: impl Fn(&S, &mut T) + 'static,
+    ) -> Self {
+        Synced {
+            child: WidgetPod::new(child).boxed(),
+            shared,
+            read: Box::new(read),
+            write: None,
+            poll_interval: None,
+            timer: TimerToken::INVALID,
+            sink: None,
+        }
+    }
+
+    /// Builder-style method to copy changes to the data back into the
+    /// shared state.
+    pub fn write(mut self, write: impl Fn(&T, &mut S) + 'static) -> Self {
+        self.write = Some(Box::new(write));
+        self
+    }
+
+    /// Builder-style method to also read the state at a fixed interval.
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = Some(interval);
+        self
+    }
+
+    fn pull(&mut self, ctx: &mut EventCtx, data: &mut T) {
+        self.shared.clear_pending();
+        let old_data = data.clone();
+        (self.read)(&self.shared.lock(), data);
+        if !old_data.same(data) {
+            ctx.invalidate();
+        }
+    }
+
+    fn schedule_poll(&mut self, ctx: &mut EventCtx) {
+        if let Some(interval) = self.poll_interval {
+            self.timer = ctx.request_timer(Instant::now() + interval);
+        }
+    }
+}
+
+impl<T: Data, S: 'static> Widget<T> for Synced<T, S> {
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, _base_state: &BaseState, data: &T, env: &Env) {
+        self.child.paint(paint_ctx, data, env);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+        let size = self.child.layout(ctx, bc, data, env);
+        self.child
+            .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+        size
+    }
+
+    fn event(&mut self, event: &Event, ctx: &mut EventCtx, data: &mut T, env: &Env) {
+        if self.sink.is_none() {
+            self.sink = Some(self.shared.add_sink(ctx.command_sink()));
+            self.pull(ctx, data);
+            self.schedule_poll(ctx);
+        }
+        match event {
+            Event::Command(cmd)
+                if cmd.selector == SHARED_STATE_CHANGED
+                    && cmd.get_object::<usize>() == Some(&self.shared.id()) =>
+            {
+                self.pull(ctx, data);
+            }
+            Event::Timer(token) if *token == self.timer => {
+                self.pull(ctx, data);
+                self.schedule_poll(ctx);
+            }
+            _ => {
+                let old_data = data.clone();
+                self.child.event(event, ctx, data, env);
+                if !old_data.same(data) {
+                    if let Some(write) = self.write.as_ref() {
+                        write(data, &mut self.shared.lock());
+                        self.shared.notify();
+                    }
+                }
+            }
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: Option<&T>, data: &T, env: &Env) {
+        // Connect, and read the current state, on the next frame rather
+        // than on whatever input comes first.
+        if self.sink.is_none() {
+            ctx.request_anim_frame();
+        }
+        self.child.update(ctx, data, env);
+    }
+}
+
+impl<T: Data, S> Drop for Synced<T, S> {
+    fn drop(&mut self) {
+        if let Some(key) = self.sink.take() {
+            self.shared.remove_sink(key);
+        }
+    }
+}
@@ -14,24 +14,29 @@
 
 //! A textbox widget.
 
-use std::cmp::{max, min};
+use std::cmp::{max, min, Ordering};
 use std::ops::Range;
 use std::time::{Duration, Instant};
 
 use crate::{
-    BaseState, BoxConstraints, ClipboardItem, Cursor, Env, Event, EventCtx, HotKey, KeyCode,
-    LayoutCtx, PaintCtx, RawMods, SysMods, TimerToken, UpdateCtx, Widget,
+    BaseState, BoxConstraints, ClipboardItem, Cursor, Data, Env, Event, EventCtx, HotKey, ImeEvent,
+    KeyCode, KeyEvent, LayoutCtx, PaintCtx, RawMods, SysMods, TimerToken, UndoManager, UpdateCtx,
+    Widget,
 };
 
-use crate::kurbo::{Affine, Line, Point, RoundedRect, Size, Vec2};
-use crate::piet::{
-    FontBuilder, PietText, PietTextLayout, RenderContext, Text, TextLayout, TextLayoutBuilder,
-    UnitPoint,
-};
+use crate::bidi;
+use crate::command::sys;
+use crate::kurbo::{Affine, Line, Point, Rect, RoundedRect, Size, Vec2};
+use crate::piet::{PietText, RenderContext, UnitPoint};
+use crate::text::{FontSet, LineLayout};
 use crate::theme;
 use crate::widget::Align;
 
-use crate::unicode_segmentation::GraphemeCursor;
+use crate::unicode_segmentation::{GraphemeCursor, UnicodeSegmentation};
+
+use crate::shell::application::Application;
+#[cfg(target_os = "linux")]
+use crate::MouseButton;
 
 const BORDER_WIDTH: f64 = 1.;
 const PADDING_TOP: f64 = 5.;
@@ -82,11 +87,18 @@ impl Selection {
         self.min()..self.max()
     }
 
-    /// Constrain selection to be not greater than input string
+    /// Constrain selection to be not greater than input string, and to
+    /// fall on character boundaries.
     pub fn constrain_to(mut self, s: &str) -> Self {
         let s_len = s.len();
         self.start = min(self.start, s_len);
         self.end = min(self.end, s_len);
+        while !s.is_char_boundary(self.start) {
+            self.start -= 1;
+        }
+        while !s.is_char_boundary(self.end) {
+            self.end -= 1;
+        }
         self
     }
 }
@@ -99,6 +111,18 @@ impl TextBox {
     pub fn new() -> impl Widget<String> {
         Align::vertical(UnitPoint::CENTER, TextBoxRaw::new())
     }
+
+    /// A text box showing `placeholder` while it is empty and unfocused.
+    ///
+    /// See [`TextBoxRaw::placeholder`].
+    ///
+    /// [`TextBoxRaw::placeholder`]: struct.TextBoxRaw.html#method.placeholder
+    pub fn with_placeholder(placeholder: impl Into<String>) -> impl Widget<String> {
+        Align::vertical(
+            UnitPoint::CENTER,
+            TextBoxRaw::new().placeholder(placeholder),
+        )
+    }
 }
 
 /// A widget that allows user text input.
@@ -109,6 +133,40 @@ pub struct TextBoxRaw {
     selection: Selection,
     cursor_timer: TimerToken,
     cursor_on: bool,
+    /// The text being composed with the input method, shown in place of
+    /// the selection until it is committed.
+    preedit: String,
+    /// The cursor position within the preedit, as a byte offset.
+    preedit_cursor: Option<usize>,
+    placeholder: Option<String>,
+    history: UndoManager<Snapshot>,
+    /// The kind of the last edit, while it can be grouped with the next.
+    last_edit: Option<EditKind>,
+    /// Whether the widget has changed the text since the last update, to
+    /// tell its own edits from changes made elsewhere.
+    edited: bool,
+}
+
+/// The text and selection of a text box, as kept in its undo history.
+#[derive(Debug, Clone)]
+struct Snapshot {
+    text: String,
+    selection: Selection,
+}
+
+impl Data for Snapshot {
+    fn same(&self, other: &Self) -> bool {
+        self.text == other.text && self.selection.range() == other.selection.range()
+    }
+}
+
+/// Kinds of edits; consecutive edits of the same kind are undone together.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum EditKind {
+    Typing,
+    Deleting,
+    /// Edits such as pastes, which are always undone on their own.
+    Other,
 }
 
 impl TextBoxRaw {
@@ -120,19 +178,27 @@ impl TextBoxRaw {
             selection: Selection::caret(0),
             cursor_timer: TimerToken::INVALID,
             cursor_on: false,
+            preedit: String::new(),
+            preedit_cursor: None,
+            placeholder: None,
+            history: UndoManager::new(),
+            last_edit: None,
+            edited: false,
         }
     }
 
-    fn get_layout(&self, t: &mut PietText, env: &Env, data: &String) -> PietTextLayout {
-        let font_name = env.get(theme::FONT_NAME);
-        let font_size = env.get(theme::TEXT_SIZE_NORMAL);
+    /// Set a hint shown while the text box is empty and unfocused, in the
+    /// theme's [`PLACEHOLDER_COLOR`].
+    ///
+    /// [`PLACEHOLDER_COLOR`]: ../theme/constant.PLACEHOLDER_COLOR.html
+    pub fn placeholder(mut self, placeholder: impl Into<String>) -> Self {
+        self.placeholder = Some(placeholder.into());
+        self
+    }
+
+    fn get_layout(&self, t: &mut PietText, env: &Env, data: &String) -> LineLayout {
         // TODO: caching of both the format and the layout
-        let font = t
-            .new_font_by_name(font_name, font_size)
-            .unwrap()
-            .build()
-            .unwrap();
-        t.new_text_layout(&font, data).unwrap().build().unwrap()
+        FontSet::from_env(t, env).layout(t, data)
     }
 
     fn insert(&mut self, src: &mut String, new: &str) {
@@ -146,6 +212,19 @@ impl TextBoxRaw {
         self.selection = Selection::caret(selection.min() + new.len());
     }
 
+    /// The text to show, with any composition spliced in, and the position
+    /// of the caret in it.
+    fn display_text(&self, data: &str) -> (String, usize) {
+        let mut text = data.to_owned();
+        if self.preedit.is_empty() {
+            return (text, self.cursor());
+        }
+        let selection = self.selection.constrain_to(data);
+        text.replace_range(selection.range(), &self.preedit);
+        let cursor = self.preedit_cursor.unwrap_or_else(|| self.preedit.len());
+        (text, selection.min() + cursor)
+    }
+
     fn cursor_to(&mut self, to: usize) {
         self.selection = Selection::caret(to);
     }
@@ -156,7 +235,7 @@ impl TextBoxRaw {
 
     /// Calculate a stateful scroll offset
     fn update_hscroll(&mut self, rc_text: &mut PietText, env: &Env, data: &String) {
-        let cursor_x = self.substring_measurement_hack(rc_text, data, 0, self.cursor(), env);
+        let cursor_x = self.caret_x(rc_text, data, self.cursor(), env);
         let overall_text_width = self.substring_measurement_hack(rc_text, data, 0, data.len(), env);
 
         let padding = PADDING_LEFT * 2.;
@@ -215,6 +294,165 @@ impl TextBoxRaw {
         x
     }
 
+    /// The horizontal position of the caret at `offset`.
+    ///
+    /// For text containing right-to-left runs, this follows the visual
+    /// order of the text, rather than measuring its logical prefix.
+    fn caret_x(&self, piet_text: &mut PietText, text: &String, offset: usize, env: &Env) -> f64 {
+        let rtl = bidi::is_rtl(env);
+        if bidi::needs_bidi(text, rtl) {
+            let stops = bidi::caret_stops(text, rtl, |s| {
+                self.get_layout(piet_text, env, &s.to_owned()).width()
+            });
+            bidi::caret_x(&stops, offset)
+        } else {
+            self.substring_measurement_hack(piet_text, text, 0, offset, env)
+        }
+    }
+
+    /// The caret position next to the cursor on screen, to the right if
+    /// `right` is `true`.
+    ///
+    /// In right-to-left runs, this moves backwards through the text.
+    fn visual_move(&self, text: &str, right: bool, env: &Env) -> usize {
+        let rtl = bidi::is_rtl(env);
+        if bidi::needs_bidi(text, rtl) {
+            let stops = bidi::caret_stops(text, rtl, |_| 0.0);
+            bidi::visual_neighbor(&stops, self.cursor(), right)
+        } else if right {
+            next_grapheme(text, self.cursor())
+        } else {
+            prev_grapheme(text, self.cursor())
+        }
+    }
+
+    /// The caret position nearest to `x`, measured from the start of the
+    /// text as it is laid out, without scrolling.
+    fn offset_at(&self, piet_text: &mut PietText, text: &String, x: f64, env: &Env) -> usize {
+        let rtl = bidi::is_rtl(env);
+        if !bidi::needs_bidi(text, rtl) {
+            return self.prefix_offset_at(piet_text, text, x, env);
+        }
+        let stops = bidi::caret_stops(text, rtl, |s| {
+            self.get_layout(piet_text, env, &s.to_owned()).width()
+        });
+        stops
+            .iter()
+            .min_by(|(_, a), (_, b)| {
+                let (a, b) = ((a - x).abs(), (b - x).abs());
+                a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+            })
+            .map(|(offset, _)| *offset)
+            .unwrap_or(0)
+    }
+
+    /// The grapheme boundary nearest to `x`, in text without right-to-left
+    /// runs.
+    ///
+    /// Prefix widths only grow with the prefix, so this binary-searches the
+    /// boundaries, measuring a handful of prefixes rather than all of them.
+    fn prefix_offset_at(
+        &self,
+        piet_text: &mut PietText,
+        text: &String,
+        x: f64,
+        env: &Env,
+    ) -> usize {
+        let offsets: Vec<usize> = text
+            .grapheme_indices(true)
+            .map(|(offset, _)| offset)
+            .skip(1)
+            .chain(Some(text.len()))
+            .collect();
+        let mut width = |offset| self.substring_measurement_hack(piet_text, text, 0, offset, env);
+
+        // Find the first boundary at or past `x`; the nearest stop is that
+        // one or the one before it.
+        let (mut lo, mut hi) = (0, offsets.len());
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if width(offsets[mid]) < x {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        let (before, before_x) = match lo {
+            0 => (0, 0.0),
+            i => (offsets[i - 1], width(offsets[i - 1])),
+        };
+        match offsets.get(lo).map(|&after| (after, width(after))) {
+            Some((after, after_x)) if after_x - x < x - before_x => after,
+            _ => before,
+        }
+    }
+
+    /// Copy the selection to the clipboard, removing it if `cut` is `true`.
+    fn copy(&mut self, ctx: &mut EventCtx, data: &mut String, cut: bool) {
+        if let Some(text) = data.get(self.selection.range()) {
+            ctx.win_ctx.set_clipboard_contents(text.into());
+        }
+        if cut && !self.selection.is_caret() {
+            self.backspace(data);
+        }
+    }
+
+    /// Handle the undo and redo commands and shortcuts, returning `true` if
+    /// the event was one of them.
+    ///
+    /// When there is nothing to undo or redo, the command is not marked
+    /// handled, so that an [`Undoable`] further up can act on it.
+    ///
+    /// [`Undoable`]: struct.Undoable.html
+    fn undo_event(
+        &mut self,
+        event: &Event,
+        ctx: &mut EventCtx,
+        data: &mut String,
+        env: &Env,
+    ) -> bool {
+        let undo = match event {
+            Event::Command(cmd) if ctx.has_focus() && cmd.selector == sys::UNDO => true,
+            Event::Command(cmd) if ctx.has_focus() && cmd.selector == sys::REDO => false,
+            Event::KeyDown(k_e) if HotKey::new(SysMods::Cmd, KeyCode::KeyZ).matches(k_e) => true,
+            Event::KeyDown(k_e) if is_redo_key(k_e) => false,
+            _ => return false,
+        };
+        let current = Snapshot {
+            text: data.clone(),
+            selection: self.selection,
+        };
+        let restored = if undo {
+            self.history.undo(&current)
+        } else {
+            self.history.redo(&current)
+        };
+        if let Some(restored) = restored {
+            *data = restored.text;
+            self.selection = restored.selection.constrain_to(data);
+            self.last_edit = None;
+            self.edited = true;
+            self.update_hscroll(ctx.text(), env, data);
+            ctx.invalidate();
+            ctx.set_handled();
+        }
+        true
+    }
+
+    /// Record an edit in the undo history, given the state before it.
+    fn record_edit(&mut self, before: Snapshot, kind: EditKind) {
+        if kind == EditKind::Other || self.last_edit != Some(kind) {
+            self.history.break_group();
+        }
+        self.history.record(&before);
+        if kind == EditKind::Other {
+            self.history.break_group();
+            self.last_edit = None;
+        } else {
+            self.last_edit = Some(kind);
+        }
+    }
+
     fn reset_cursor_blink(&mut self, ctx: &mut EventCtx) {
         self.cursor_on = true;
         let deadline = Instant::now() + Duration::from_millis(500);
@@ -238,6 +476,8 @@ impl Widget<String> for TextBoxRaw {
         let cursor_color = env.get(theme::CURSOR_COLOR);
 
         let has_focus = base_state.has_focus();
+        let composing = !self.preedit.is_empty();
+        let (text, caret) = self.display_text(data);
 
         let border_color = if has_focus {
             env.get(theme::PRIMARY_LIGHT)
@@ -254,58 +494,77 @@ impl Widget<String> for TextBoxRaw {
 
         paint_ctx.fill(clip_rect, &background_color);
 
-        // Render text, selection, and cursor inside a clip
-        paint_ctx
-            .with_save(|rc| {
-                rc.clip(clip_rect);
-
-                // Shift everything inside the clip by the hscroll_offset
-                rc.transform(Affine::translate((-self.hscroll_offset, 0.)));
-
-                // Draw selection rect
-                if !self.selection.is_caret() {
-                    let (left, right) = (self.selection.min(), self.selection.max());
-
-                    let selection_width =
-                        self.substring_measurement_hack(rc.text(), data, left, right, env);
-
-                    let selection_pos = Point::new(
-                        self.substring_measurement_hack(rc.text(), data, 0, left, env)
-                            + PADDING_LEFT
-                            - 1.,
-                        PADDING_TOP - 2.,
-                    );
-                    let selection_rect = RoundedRect::from_origin_size(
-                        selection_pos,
-                        Size::new(selection_width + 2., font_size + 4.).to_vec2(),
-                        1.,
-                    );
-                    rc.fill(selection_rect, &selection_color);
-                }
+        let text_height = font_size * 0.8;
+        let text_pos = Point::new(0.0 + PADDING_LEFT, text_height + PADDING_TOP);
+        let cursor_x = self.caret_x(paint_ctx.text(), &text, caret, env);
 
-                // Layout, measure, and draw text
-                let text_layout = self.get_layout(rc.text(), env, data);
-                let text_height = font_size * 0.8;
-                let text_pos = Point::new(0.0 + PADDING_LEFT, text_height + PADDING_TOP);
+        // Render text, selection, and cursor inside a clip
+        paint_ctx.with_save(|rc| {
+            rc.clip(clip_rect);
+
+            // Shift everything inside the clip by the hscroll_offset
+            rc.transform(Affine::translate((-self.hscroll_offset, 0.)));
+
+            // Draw selection rect
+            if !self.selection.is_caret() && !composing {
+                let start_x = self.caret_x(rc.text(), data, self.selection.start, env);
+                let end_x = self.caret_x(rc.text(), data, self.selection.end, env);
+                let (left, right) = (start_x.min(end_x), start_x.max(end_x));
+
+                let selection_width = right - left;
+                let selection_pos = Point::new(left + PADDING_LEFT - 1., PADDING_TOP - 2.);
+                let selection_rect = RoundedRect::from_origin_size(
+                    selection_pos,
+                    Size::new(selection_width + 2., font_size + 4.).to_vec2(),
+                    1.,
+                );
+                rc.fill(selection_rect, &selection_color);
+            }
 
-                rc.draw_text(&text_layout, text_pos, &text_color);
+            // Layout, measure, and draw text, or the placeholder
+            let placeholder = self
+                .placeholder
+                .as_ref()
+                .filter(|_| text.is_empty() && !has_focus);
+            let (shown, shown_color) = match placeholder {
+                Some(placeholder) => (placeholder, env.get(theme::PLACEHOLDER_COLOR)),
+                None => (&text, text_color.clone()),
+            };
+            let visual_text = bidi::visual_text(shown, bidi::is_rtl(env));
+            let text_layout = self.get_layout(rc.text(), env, &visual_text);
+            text_layout.draw(rc, text_pos, &shown_color);
+
+            // Underline the composition
+            if composing {
+                let start = self.selection.constrain_to(data).min();
+                let end = start + self.preedit.len();
+                let x0 = self.caret_x(rc.text(), &text, start, env);
+                let x1 = self.caret_x(rc.text(), &text, end, env);
+                let y = text_pos.y + 2.;
+                let line = Line::new((text_pos.x + x0, y), (text_pos.x + x1, y));
+                rc.stroke(line, &text_color, 1.);
+            }
 
-                // Paint the cursor if focused and there's no selection
-                if has_focus && self.cursor_on && self.selection.is_caret() {
-                    let cursor_x =
-                        self.substring_measurement_hack(rc.text(), data, 0, self.cursor(), env);
-                    let xy = text_pos + Vec2::new(cursor_x, 2. - font_size);
-                    let x2y2 = xy + Vec2::new(0., font_size + 2.);
-                    let line = Line::new(xy, x2y2);
+            // Paint the cursor if focused and there's no selection
+            if has_focus && self.cursor_on && (self.selection.is_caret() || composing) {
+                let xy = text_pos + Vec2::new(cursor_x, 2. - font_size);
+                let x2y2 = xy + Vec2::new(0., font_size + 2.);
+                let line = Line::new(xy, x2y2);
 
-                    rc.stroke(line, &cursor_color, 1.);
-                }
-                Ok(())
-            })
-            .unwrap();
+                rc.stroke(line, &cursor_color, 1.);
+            }
+        });
 
         // Paint the border
         paint_ctx.stroke(clip_rect, &border_color, BORDER_WIDTH);
+        paint_ctx.paint_focus_ring(base_state, clip_rect, env);
+
+        if has_focus {
+            let x = text_pos.x + cursor_x - self.hscroll_offset;
+            let y = text_pos.y + 2. - font_size;
+            let rect = Rect::new(x, y, x + 1., y + font_size + 2.);
+            paint_ctx.set_ime_cursor_rect(rect);
+        }
     }
 
     fn layout(
@@ -327,16 +586,110 @@ impl Widget<String> for TextBoxRaw {
     }
 
     fn event(&mut self, event: &Event, ctx: &mut EventCtx, data: &mut String, env: &Env) {
+        if self.undo_event(event, ctx, data, env) {
+            return;
+        }
+        // Only events that can change the text pay for a snapshot of it.
+        let may_edit = match event {
+            Event::KeyDown(_) | Event::Paste(_) | Event::Ime(ImeEvent::Commit(_)) => true,
+            Event::Command(cmd) => cmd.selector == crate::command::sys::CUT,
+            #[cfg(target_os = "linux")]
+            Event::MouseDown(mouse) => mouse.button == MouseButton::Middle,
+            _ => false,
+        };
+        let selection = self.selection;
+        let before = if may_edit {
+            Some(Snapshot {
+                text: data.clone(),
+                selection,
+            })
+        } else {
+            None
+        };
+        let kind = match event {
+            Event::KeyDown(k_e)
+                if k_e.key_code == KeyCode::Backspace || k_e.key_code == KeyCode::Delete =>
+            {
+                EditKind::Deleting
+            }
+            Event::KeyDown(k_e)
+                if k_e.key_code.is_printable() && !k_e.mods.ctrl && !k_e.mods.meta =>
+            {
+                EditKind::Typing
+            }
+            Event::Ime(ImeEvent::Commit(_)) => EditKind::Typing,
+            _ => EditKind::Other,
+        };
+
         match event {
-            Event::MouseDown(_) => {
+            // Paste the primary selection (Middle click, on Linux)
+            #[cfg(target_os = "linux")]
+            Event::MouseDown(mouse) if mouse.button == MouseButton::Middle => {
+                ctx.request_focus();
+                if let Some(ClipboardItem::Text(string)) =
+                    Application::get_primary_selection_contents()
+                {
+                    self.insert(data, &string);
+                }
+                ctx.invalidate();
+                self.reset_cursor_blink(ctx);
+            }
+            Event::MouseDown(mouse) => {
                 ctx.request_focus();
-                // TODO: hit test and do this for real
-                self.cursor_to(self.selection.end);
+                ctx.set_active(true);
+                let x = mouse.pos.x - PADDING_LEFT + self.hscroll_offset;
+                let offset = self.offset_at(ctx.text(), data, x, env);
+                self.selection = match mouse.count {
+                    2 => word_at(data, offset),
+                    count if count > 2 => Selection::new(0, data.len()),
+                    _ if mouse.mods.shift => Selection::new(self.selection.start, offset),
+                    _ => Selection::caret(offset),
+                };
                 ctx.invalidate();
                 self.reset_cursor_blink(ctx);
             }
-            Event::MouseMoved(_) => {
+            Event::MouseMoved(mouse) => {
                 ctx.set_cursor(&Cursor::IBeam);
+                if ctx.is_active() {
+                    let x = mouse.pos.x - PADDING_LEFT + self.hscroll_offset;
+                    self.selection.end = self.offset_at(ctx.text(), data, x, env);
+                    self.update_hscroll(ctx.text(), env, data);
+                    ctx.invalidate();
+                }
+            }
+            Event::MouseUp(_) => {
+                if ctx.is_active() {
+                    ctx.set_active(false);
+                    #[cfg(target_os = "linux")]
+                    {
+                        if let Some(text) = data.get(self.selection.range()) {
+                            if !text.is_empty() {
+                                Application::set_primary_selection_contents(text.into());
+                            }
+                        }
+                    }
+                }
+            }
+            Event::FocusChanged(focused) => {
+                ctx.win_ctx.set_soft_keyboard_visible(*focused);
+                ctx.set_ime_enabled(*focused);
+                if !*focused && !self.preedit.is_empty() {
+                    self.preedit.clear();
+                    ctx.invalidate();
+                }
+            }
+            Event::Ime(ImeEvent::Preedit { text, cursor }) => {
+                self.preedit = text.clone();
+                self.preedit_cursor = *cursor;
+                self.reset_cursor_blink(ctx);
+                ctx.invalidate();
+            }
+            Event::Ime(ImeEvent::Commit(text)) => {
+                self.preedit.clear();
+                self.insert(data, text);
+                self.reset_cursor_blink(ctx);
+                self.update_hscroll(ctx.text(), env, data);
+                ctx.invalidate();
             }
             Event::Timer(id) => {
                 if *id == self.cursor_timer {
@@ -351,18 +704,24 @@ impl Widget<String> for TextBoxRaw {
                     && (cmd.selector == crate::command::sys::COPY
                         || cmd.selector == crate::command::sys::CUT) =>
             {
-                if let Some(text) = data.get(self.selection.range()) {
-                    ctx.win_ctx.set_clipboard_contents(text.into());
-                }
-                if !self.selection.is_caret() && cmd.selector == crate::command::sys::CUT {
-                    self.backspace(data);
-                }
+                self.copy(ctx, data, cmd.selector == crate::command::sys::CUT);
+                self.update_hscroll(ctx.text(), env, data);
+                ctx.invalidate();
+                ctx.set_handled();
+            }
+            Event::Command(ref cmd)
+                if ctx.has_focus() && cmd.selector == crate::command::sys::SELECT_ALL =>
+            {
+                self.selection = Selection::new(0, data.len());
+                ctx.invalidate();
                 ctx.set_handled();
             }
             Event::Paste(ref item) => {
                 if let ClipboardItem::Text(string) = item {
                     self.insert(data, string);
                     self.reset_cursor_blink(ctx);
+                    self.update_hscroll(ctx.text(), env, data);
+                    ctx.invalidate();
                 }
             }
             Event::KeyDown(key_event) => {
@@ -371,6 +730,24 @@ impl Widget<String> for TextBoxRaw {
                     k_e if (HotKey::new(SysMods::Cmd, "a")).matches(k_e) => {
                         self.selection = Selection::new(0, data.len());
                     }
+                    // Copy and cut (Ctrl+C, Ctrl+X || Cmd+C, Cmd+X), for
+                    // windows without an edit menu
+                    k_e if (HotKey::new(SysMods::Cmd, "c")).matches(k_e) => {
+                        self.copy(ctx, data, false);
+                    }
+                    k_e if (HotKey::new(SysMods::Cmd, "x")).matches(k_e) => {
+                        self.copy(ctx, data, true);
+                        self.reset_cursor_blink(ctx);
+                    }
+                    // Paste (Ctrl+V || Cmd+V)
+                    k_e if (HotKey::new(SysMods::Cmd, "v")).matches(k_e) => {
+                        if let Some(ClipboardItem::Text(string)) =
+                            Application::get_clipboard_contents()
+                        {
+                            self.insert(data, &string);
+                        }
+                        self.reset_cursor_blink(ctx);
+                    }
                     // Jump left (Ctrl+ArrowLeft || Cmd+ArrowLeft)
                     k_e if (HotKey::new(SysMods::Cmd, KeyCode::ArrowLeft)).matches(k_e) => {
                         self.cursor_to(0);
@@ -383,16 +760,16 @@ impl Widget<String> for TextBoxRaw {
                     }
                     // Select left (Shift+ArrowLeft)
                     k_e if (HotKey::new(RawMods::Shift, KeyCode::ArrowLeft)).matches(k_e) => {
-                        self.selection.end = prev_grapheme(data, self.cursor());
+                        self.selection.end = self.visual_move(data, false, env);
                     }
                     // Select right (Shift+ArrowRight)
                     k_e if (HotKey::new(RawMods::Shift, KeyCode::ArrowRight)).matches(k_e) => {
-                        self.selection.end = next_grapheme(data, self.cursor());
+                        self.selection.end = self.visual_move(data, true, env);
                     }
                     // Move left (ArrowLeft)
                     k_e if (HotKey::new(None, KeyCode::ArrowLeft)).matches(k_e) => {
                         if self.selection.is_caret() {
-                            self.cursor_to(prev_grapheme(data, self.cursor()));
+                            self.cursor_to(self.visual_move(data, false, env));
                         } else {
                             self.cursor_to(self.selection.min());
                         }
@@ -401,7 +778,7 @@ impl Widget<String> for TextBoxRaw {
                     // Move right (ArrowRight)
                     k_e if (HotKey::new(None, KeyCode::ArrowRight)).matches(k_e) => {
                         if self.selection.is_caret() {
-                            self.cursor_to(next_grapheme(data, self.cursor()));
+                            self.cursor_to(self.visual_move(data, true, env));
                         } else {
                             self.cursor_to(self.selection.max());
                         }
@@ -425,6 +802,8 @@ impl Widget<String> for TextBoxRaw {
                         }
                         self.reset_cursor_blink(ctx);
                     }
+                    // Tab moves focus to the next widget
+                    k_e if k_e.key_code == KeyCode::Tab => {}
                     // Actual typing
                     k_e if k_e.key_code.is_printable() => {
                         let incoming_text = k_e.text().unwrap_or("");
@@ -433,26 +812,53 @@ impl Widget<String> for TextBoxRaw {
                     }
                     _ => {}
                 }
+                // On Linux, selected text becomes the primary selection.
+                #[cfg(target_os = "linux")]
+                {
+                    if let Some(text) = data.get(self.selection.range()) {
+                        if !text.is_empty() {
+                            Application::set_primary_selection_contents(text.into());
+                        }
+                    }
+                }
                 self.update_hscroll(ctx.text(), env, data);
                 ctx.invalidate();
             }
             _ => (),
         }
+
+        if let Some(before) = before.filter(|before| *data != before.text) {
+            self.record_edit(before, kind);
+            self.edited = true;
+        } else if selection.range() != self.selection.range() {
+            // Moving the caret ends a run of typing.
+            self.last_edit = None;
+        }
+    }
+
+    fn accepts_focus(&self) -> bool {
+        true
     }
 
     fn update(
         &mut self,
         ctx: &mut UpdateCtx,
-        _old_data: Option<&String>,
-        _data: &String,
+        old_data: Option<&String>,
+        data: &String,
         _env: &Env,
     ) {
+        // The history doesn't apply to text that was changed elsewhere.
+        let changed = old_data.map_or(false, |old| !old.same(data));
+        if changed && !self.edited {
+            self.history.clear();
+        }
+        self.edited = false;
         ctx.invalidate();
     }
 }
 
 /// Gets the next character from the given index.
-fn next_grapheme(src: &str, from: usize) -> usize {
+pub(crate) fn next_grapheme(src: &str, from: usize) -> usize {
     let mut c = GraphemeCursor::new(from, src.len(), true);
     let next_boundary = c.next_boundary(src, 0).unwrap();
     if let Some(next) = next_boundary {
@@ -473,8 +879,34 @@ fn next_grapheme_exists(src: &str, from: usize) -> bool {
     }
 }
 
+/// Whether a key event is the redo shortcut: Ctrl+Y on Windows, and
+/// Cmd+Shift+Z or Ctrl+Shift+Z elsewhere.
+fn is_redo_key(key: &KeyEvent) -> bool {
+    if cfg!(target_os = "windows") {
+        HotKey::new(RawMods::Ctrl, KeyCode::KeyY).matches(key)
+    } else {
+        HotKey::new(SysMods::CmdShift, KeyCode::KeyZ).matches(key)
+    }
+}
+
+/// The selection of the word at `offset`, or of the run of spaces or
+/// punctuation there.
+fn word_at(src: &str, offset: usize) -> Selection {
+    src.split_word_bound_indices()
+        .map(|(start, word)| (start, start + word.len()))
+        .find(|&(start, end)| start <= offset && offset < end)
+        .or_else(|| {
+            // At the end of the text, the last word is selected.
+            src.split_word_bound_indices()
+                .last()
+                .map(|(start, word)| (start, start + word.len()))
+        })
+        .map(|(start, end)| Selection::new(start, end))
+        .unwrap_or_else(|| Selection::caret(offset))
+}
+
 /// Gets the previous character from the given index.
-fn prev_grapheme(src: &str, from: usize) -> usize {
+pub(crate) fn prev_grapheme(src: &str, from: usize) -> usize {
     let mut c = GraphemeCursor::new(from, src.len(), true);
     let prev_boundary = c.prev_boundary(src, 0).unwrap();
     if let Some(prev) = prev_boundary {
@@ -510,4 +942,76 @@ mod tests {
         // Insert again
         widget.insert(&mut data, "a");
     }
+
+    /// Test that a composition is shown in place of the selection.
+    #[test]
+    fn preedit_replaces_selection() {
+        let mut widget = TextBoxRaw::new();
+        widget.selection = Selection::new(1, 3);
+        widget.preedit = "日本".to_string();
+        widget.preedit_cursor = Some(3);
+
+        let (text, caret) = widget.display_text("abcd");
+        assert_eq!("a日本d", text);
+        assert_eq!(4, caret);
+    }
+
+    /// Test that double clicking selects the word under the mouse.
+    #[test]
+    fn word_at_offset() {
+        let text = "hello, big world";
+        assert_eq!(0..5, word_at(text, 2).range());
+        assert_eq!(7..10, word_at(text, 7).range());
+        assert_eq!(11..16, word_at(text, text.len()).range());
+        assert_eq!(0..0, word_at("", 0).range());
+    }
+
+    /// Test that a run of typing is undone in one step, apart from the
+    /// deletion that follows it.
+    #[test]
+    fn typing_is_undone_together() {
+        let mut widget = TextBoxRaw::new();
+        let mut data = String::new();
+        let snapshot = |widget: &TextBoxRaw, data: &String| Snapshot {
+            text: data.clone(),
+            selection: widget.selection,
+        };
+        for c in ["a", "b", "c"].iter() {
+            let before = snapshot(&widget, &data);
+            widget.insert(&mut data, c);
+            widget.record_edit(before, EditKind::Typing);
+        }
+        let before = snapshot(&widget, &data);
+        widget.backspace(&mut data);
+        widget.record_edit(before, EditKind::Deleting);
+        assert_eq!("ab", data);
+
+        let current = snapshot(&widget, &data);
+        let restored = widget.history.undo(&current).unwrap();
+        assert_eq!("abc", restored.text);
+        let restored = widget.history.undo(&restored).unwrap();
+        assert_eq!("", restored.text);
+        assert!(!widget.history.can_undo());
+    }
+
+    /// Test that emoji made of several code points are deleted whole.
+    #[test]
+    fn backspace_deletes_whole_emoji() {
+        let mut widget = TextBoxRaw::new();
+        let mut data = String::new();
+        // A flag, a waving hand with a skin tone, and a family joined
+        // with zero-width joiners.
+        let emoji = [
+            "\u{1f1eb}\u{1f1f7}",
+            "\u{1f44b}\u{1f3fd}",
+            "\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}",
+        ];
+        for e in emoji.iter() {
+            widget.insert(&mut data, e);
+        }
+        for (i, _) in emoji.iter().enumerate().rev() {
+            widget.backspace(&mut data);
+            assert_eq!(emoji[..i].concat(), data);
+        }
+    }
 }
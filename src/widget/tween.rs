@@ -0,0 +1,185 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget that animates a value derived from its data.
+
+use crate::kurbo::{Point, Rect, Size};
+use crate::theme;
+use crate::{
+    Animator, BaseState, BoxConstraints, Data, Env, Event, EventCtx, Interpolate, LayoutCtx,
+    PaintCtx, Transition, UpdateCtx, Widget, WidgetPod,
+};
+
+/// A widget that smoothly animates some property of its child when the
+/// data changes.
+///
+/// The property is a value computed from the data, such as a color or a
+/// height. When the data changes, the value moves to its new target
+/// according to the [`Transition`], rather than jumping there. The current
+/// value is either passed to the child through its environment, for paint
+/// properties and for widgets that read their layout from the environment,
+/// or used to adjust the constraints the child is laid out with.
+///
+/// Changes are immediate when [`theme::REDUCED_MOTION`] is set.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use druid::piet::Color;
+/// use druid::widget::{Label, Tween};
+/// use druid::{theme, Transition};
+///
+/// let status = Tween::new(
+///     Label::new("status"),
+///     |ok: &bool, _env| if *ok { Color::rgb8(0, 0xc0, 0) } else { Color::rgb8(0xc0, 0, 0) },
+///     |color, env| env.adding(theme::LABEL_COLOR, color.clone()),
+/// )
+/// .transition(Transition::new(Duration::from_millis(400)));
+/// ```
+///
+/// [`Transition`]: ../struct.Transition.html
+/// [`theme::REDUCED_MOTION`]: ../theme/constant.REDUCED_MOTION.html
+pub struct Tween<T: Data, V> {
+    child: WidgetPod<T, Box<dyn Widget<T>>>,
+    value: Box<dyn Fn(&T, &Env) -> V>,
+    apply: Apply<V>,
+    transition: Transition,
+    animator: Option<Animator<V>>,
+}
+
+/// How the animated value affects the child.
+enum Apply<V> {
+    Env(Box<dyn Fn(&V, Env) -> Env>),
+    Constraints(Box<dyn Fn(&V, &BoxConstraints) -> BoxConstraints>),
+}
+
+impl<T: Data, V: Interpolate> Tween<T, V> {
+    /// Animate a value that the child sees in its environment.
+    ///
+    /// The `apply` closure adds the current value to the environment,
+    /// usually with [`Env::adding`].
+    ///
+    /// [`Env::adding`]: ../struct.Env.html#method.adding
+    pub fn new(
+        child: impl Widget<T> + 'static,
+        value: impl Fn(&T, &Env) -> V + 'static,
+        apply: impl Fn(&V, Env) -> Env + 'static,
+    ) -> Self {
+        Tween::with_apply(child, value, Apply::Env(Box::new(apply)))
+    }
+
+    /// Animate the constraints that the child is laid out with.
+    ///
+    /// The `constrain` closure maps the current value and the incoming
+    /// constraints to the constraints passed to the child, for instance to
+    /// animate its height as a panel expands.
+    pub fn layout(
+        child: impl Widget<T> + 'static,
+        value: impl Fn(&T, &Env) -> V + 'static,
+        constrain: impl Fn(&V, &BoxConstraints) -> BoxConstraints + 'static,
+    ) -> Self {
+        Tween::with_apply(child, value, Apply::Constraints(Box::new(constrain)))
+    }
+
+    fn with_apply(
+        child: impl Widget<T> + 'static,
+        value: impl Fn(&T, &Env) -> V + 'static,
+        apply: Apply<V>,
+    ) -> Self {
+        Tween {
+            child: WidgetPod::new(child).boxed(),
+            value: Box::new(value),
+            apply,
+            transition: Transition::default(),
+            animator: None,
+        }
+    }
+
+    /// Set the transition used when the value changes.
+    pub fn transition(mut self, transition: Transition) -> Self {
+        self.transition = transition;
+        self
+    }
+
+    /// The current value, computing it from the data before the first update.
+    fn current(&mut self, data: &T, env: &Env) -> V {
+        let (value, transition) = (&self.value, self.transition);
+        self.animator
+            .get_or_insert_with(|| Animator::new(value(data, env), transition))
+            .value()
+    }
+
+    /// The environment the child sees.
+    fn child_env(&mut self, data: &T, env: &Env) -> Env {
+        let value = self.current(data, env);
+        match &self.apply {
+            Apply::Env(apply) => apply(&value, env.clone()),
+            Apply::Constraints(_) => env.clone(),
+        }
+    }
+}
+
+impl<T: Data, V: Interpolate> Widget<T> for Tween<T, V> {
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, _base_state: &BaseState, data: &T, env: &Env) {
+        let env = self.child_env(data, env);
+        self.child.paint(paint_ctx, data, &env);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+        let value = self.current(data, env);
+        let child_env = self.child_env(data, env);
+        let child_bc = match &self.apply {
+            Apply::Constraints(constrain) => constrain(&value, bc),
+            Apply::Env(_) => *bc,
+        };
+        let size = self.child.layout(ctx, &child_bc, data, &child_env);
+        self.child
+            .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+        bc.constrain(size)
+    }
+
+    fn event(&mut self, event: &Event, ctx: &mut EventCtx, data: &mut T, env: &Env) {
+        if let Event::AnimFrame(interval) = event {
+            if let Some(animator) = self.animator.as_mut() {
+                if animator.is_running() {
+                    if animator.advance(*interval) {
+                        ctx.request_anim_frame();
+                    }
+                    ctx.invalidate();
+                }
+            }
+        }
+        let env = self.child_env(data, env);
+        self.child.event(event, ctx, data, &env);
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: Option<&T>, data: &T, env: &Env) {
+        let target = (self.value)(data, env);
+        let data_changed = old_data.map(|old| !old.same(data)).unwrap_or(false);
+        match self.animator.as_mut() {
+            None => self.animator = Some(Animator::new(target, self.transition)),
+            Some(animator) if data_changed && !env.get(theme::REDUCED_MOTION) => {
+                animator.animate_to(target);
+                ctx.request_anim_frame();
+            }
+            // Other changes, such as to the theme, apply immediately.
+            Some(animator) if data_changed || !animator.is_running() => animator.jump_to(target),
+            Some(_) => (),
+        }
+        ctx.invalidate();
+        let env = self.child_env(data, env);
+        self.child.update(ctx, data, &env);
+    }
+}
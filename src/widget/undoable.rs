@@ -0,0 +1,97 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget that provides undo and redo for its child's data.
+
+use crate::command::sys;
+use crate::kurbo::{Point, Rect, Size};
+use crate::{
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx, UndoManager,
+    UpdateCtx, Widget, WidgetPod,
+};
+
+/// A widget that records changes its child makes to the data, and reverts
+/// them in response to the [`UNDO`] and [`REDO`] commands.
+///
+/// These are the commands sent by the standard Edit menu items. A
+/// descendant, such as a focused text box with its own history, can handle
+/// them first by marking the event as handled.
+///
+/// [`UNDO`]: ../command/sys/constant.UNDO.html
+/// [`REDO`]: ../command/sys/constant.REDO.html
+pub struct Undoable<T: Data> {
+    child: WidgetPod<T, Box<dyn Widget<T>>>,
+    history: UndoManager<T>,
+}
+
+impl<T: Data> Undoable<T> {
+    /// Create a new widget, with a default history.
+    pub fn new(child: impl Widget<T> + 'static) -> Self {
+        Undoable {
+            child: WidgetPod::new(child).boxed(),
+            history: UndoManager::new(),
+        }
+    }
+
+    /// Builder-style method to use a configured history.
+    pub fn history(mut self, history: UndoManager<T>) -> Self {
+        self.history = history;
+        self
+    }
+}
+
+impl<T: Data> Widget<T> for Undoable<T> {
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, _base_state: &BaseState, data: &T, env: &Env) {
+        self.child.paint(paint_ctx, data, env);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+        let size = self.child.layout(ctx, bc, data, env);
+        self.child
+            .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+        size
+    }
+
+    fn event(&mut self, event: &Event, ctx: &mut EventCtx, data: &mut T, env: &Env) {
+        match event {
+            Event::Command(cmd) if cmd.selector == sys::UNDO || cmd.selector == sys::REDO => {
+                self.child.event(event, ctx, data, env);
+                if ctx.is_handled() {
+                    return;
+                }
+                let restored = if cmd.selector == sys::UNDO {
+                    self.history.undo(data)
+                } else {
+                    self.history.redo(data)
+                };
+                if let Some(restored) = restored {
+                    *data = restored;
+                    ctx.invalidate();
+                }
+                ctx.set_handled();
+            }
+            _ => {
+                let old_data = data.clone();
+                self.child.event(event, ctx, data, env);
+                if !old_data.same(data) {
+                    self.history.record(&old_data);
+                }
+            }
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: Option<&T>, data: &T, env: &Env) {
+        self.child.update(ctx, data, env);
+    }
+}
@@ -0,0 +1,125 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget that validates its child's data.
+
+use crate::kurbo::{Point, Rect, Size};
+use crate::piet::RenderContext;
+use crate::theme;
+use crate::{
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx, TextLayout,
+    UpdateCtx, Validator, Widget, WidgetPod,
+};
+
+/// A widget that checks its child's data with a [`Validator`], and shows
+/// the error message below the child when the data is invalid.
+///
+/// To avoid flagging a form before anything has been entered, errors are
+/// only shown once the child has changed the data, unless
+/// [`show_initially`] is used. The message is drawn in the theme's
+/// [`ERROR_COLOR`], which is also used to outline the child.
+///
+/// [`Validator`]: ../struct.Validator.html
+/// [`show_initially`]: #method.show_initially
+/// [`ERROR_COLOR`]: ../theme/constant.ERROR_COLOR.html
+pub struct Validated<T: Data> {
+    child: WidgetPod<T, Box<dyn Widget<T>>>,
+    validator: Validator<T>,
+    error: Option<String>,
+    touched: bool,
+    message: TextLayout,
+}
+
+impl<T: Data> Validated<T> {
+    /// Create a new widget validating its child's data.
+    pub fn new(child: impl Widget<T> + 'static, validator: Validator<T>) -> Self {
+        Validated {
+            child: WidgetPod::new(child).boxed(),
+            validator,
+            error: None,
+            touched: false,
+            message: TextLayout::new(""),
+        }
+    }
+
+    /// Builder-style method to show errors before the data has been edited.
+    pub fn show_initially(mut self) -> Self {
+        self.touched = true;
+        self
+    }
+
+    fn shown_error(&self) -> Option<&str> {
+        if self.touched {
+            self.error.as_ref().map(String::as_str)
+        } else {
+            None
+        }
+    }
+}
+
+impl<T: Data + 'static> Widget<T> for Validated<T> {
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env) {
+        self.child.paint(paint_ctx, data, env);
+        if self.shown_error().is_some() {
+            let color = env.get(theme::ERROR_COLOR);
+            let child_rect = self.child.get_layout_rect();
+            let outline = Rect::from_origin_size(
+                Point::new(0.5, 0.5),
+                Size::new(child_rect.width() - 1.0, child_rect.height() - 1.0),
+            );
+            paint_ctx.stroke(outline, &color, 1.0);
+            self.message.set_text_color(Some(color));
+            let origin = Point::new(0.0, child_rect.height());
+            if origin.y < base_state.size().height {
+                self.message.draw(paint_ctx, origin, env);
+            }
+        }
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+        let child_size = self.child.layout(ctx, bc, data, env);
+        self.child
+            .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, child_size));
+        let mut height = child_size.height;
+        if let Some(error) = self.shown_error() {
+            let error = error.to_string();
+            self.message.set_text(error);
+            self.message.set_layout_width(child_size.width);
+            self.message.rebuild_if_needed(ctx.text(), env);
+            height += self.message.size().height;
+        }
+        bc.constrain(Size::new(child_size.width, height))
+    }
+
+    fn event(&mut self, event: &Event, ctx: &mut EventCtx, data: &mut T, env: &Env) {
+        let old_data = data.clone();
+        self.child.event(event, ctx, data, env);
+        if !old_data.same(data) && !self.touched {
+            self.touched = true;
+            ctx.invalidate();
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: Option<&T>, data: &T, env: &Env) {
+        self.child.update(ctx, data, env);
+        let changed = old_data.map(|old| !old.same(data)).unwrap_or(true);
+        if changed {
+            let error = self.validator.validate(data).err();
+            if error != self.error {
+                self.error = error;
+                ctx.invalidate();
+            }
+        }
+    }
+}
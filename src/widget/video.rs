@@ -0,0 +1,218 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget playing video.
+
+use std::path::Path;
+use std::time::Duration;
+
+use log::warn;
+
+use crate::command::sys;
+use crate::kurbo::{Point, Rect, Size};
+use crate::piet::{ImageFormat, InterpolationMode, RenderContext};
+use crate::shell::video::{VideoDecoder, VideoFrame};
+use crate::{
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, IdleToken, LayoutCtx, PaintCtx,
+    UpdateCtx, Widget,
+};
+
+/// The token used to start playback of autoplaying videos.
+const AUTOPLAY_TOKEN: IdleToken = IdleToken::new(0x7669_6465);
+
+/// A widget that plays a video file, using the platform's media framework.
+///
+/// Playback is controlled with the [`VIDEO_PLAY`], [`VIDEO_PAUSE`] and
+/// [`VIDEO_SEEK`] commands, which apply to every video in the window; when
+/// the end is reached, the widget submits [`VIDEO_ENDED`]. Frames are shown
+/// on animation frames, so playback follows the window's display rate.
+///
+/// The video is scaled to fit the widget, keeping its aspect ratio. Audio is
+/// not played. This widget is only available with the `video` feature.
+///
+/// [`VIDEO_PLAY`]: ../command/sys/constant.VIDEO_PLAY.html
+/// [`VIDEO_PAUSE`]: ../command/sys/constant.VIDEO_PAUSE.html
+/// [`VIDEO_SEEK`]: ../command/sys/constant.VIDEO_SEEK.html
+/// [`VIDEO_ENDED`]: ../command/sys/constant.VIDEO_ENDED.html
+pub struct Video {
+    decoder: Option<VideoDecoder>,
+    /// The frame being shown.
+    frame: Option<VideoFrame>,
+    /// A decoded frame that isn't due yet.
+    next: Option<VideoFrame>,
+    /// The playback position.
+    position: Duration,
+    playing: bool,
+    autoplay: bool,
+}
+
+impl Video {
+    /// Create a widget playing the given file, showing its first frame
+    /// until playback starts.
+    pub fn new(path: impl AsRef<Path>) -> Video {
+        let mut decoder = match VideoDecoder::open(path.as_ref()) {
+            Ok(decoder) => Some(decoder),
+            Err(e) => {
+                warn!("failed to open video {:?}: {:?}", path.as_ref(), e);
+                None
+            }
+        };
+        let frame = decoder.as_mut().and_then(VideoDecoder::next_frame);
+        Video {
+            decoder,
+            frame,
+            next: None,
+            position: Duration::from_secs(0),
+            playing: false,
+            autoplay: false,
+        }
+    }
+
+    /// Start playing as soon as the widget is shown.
+    pub fn autoplay(mut self) -> Self {
+        self.autoplay = true;
+        self
+    }
+
+    /// The length of the video, if known.
+    pub fn duration(&self) -> Option<Duration> {
+        self.decoder.as_ref().and_then(VideoDecoder::duration)
+    }
+
+    fn seek(&mut self, time: Duration) {
+        if let Some(decoder) = self.decoder.as_mut() {
+            decoder.seek(time);
+            self.position = time;
+            self.next = None;
+            if let Some(frame) = decoder.next_frame() {
+                self.frame = Some(frame);
+            }
+        }
+    }
+
+    /// Show the latest frame that is due, returning `false` at the end.
+    fn advance(&mut self, ctx: &mut EventCtx) -> bool {
+        let decoder = match self.decoder.as_mut() {
+            Some(decoder) => decoder,
+            None => return false,
+        };
+        loop {
+            if self.next.is_none() {
+                self.next = decoder.next_frame();
+            }
+            match self.next.take() {
+                Some(frame) if frame.time <= self.position => {
+                    self.frame = Some(frame);
+                    ctx.invalidate();
+                }
+                Some(frame) => {
+                    self.next = Some(frame);
+                    return true;
+                }
+                None => return false,
+            }
+        }
+    }
+
+    fn play(&mut self, ctx: &mut EventCtx) {
+        if self.decoder.is_some() && !self.playing {
+            self.playing = true;
+            ctx.request_anim_frame();
+        }
+    }
+}
+
+impl<T: Data> Widget<T> for Video {
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, _data: &T, _env: &Env) {
+        let frame = match self.frame.as_ref() {
+            Some(frame) => frame,
+            None => return,
+        };
+        let image = match paint_ctx.make_image(
+            frame.width,
+            frame.height,
+            &frame.pixels,
+            ImageFormat::RgbaSeparate,
+        ) {
+            Ok(image) => image,
+            Err(e) => {
+                warn!("failed to make video frame image: {:?}", e);
+                return;
+            }
+        };
+        // Fit the frame to our size, centered.
+        let size = base_state.size();
+        let scale = (size.width / frame.width as f64).min(size.height / frame.height as f64);
+        let fitted = Size::new(frame.width as f64 * scale, frame.height as f64 * scale);
+        let origin = Point::new(
+            (size.width - fitted.width) / 2.0,
+            (size.height - fitted.height) / 2.0,
+        );
+        let rect = Rect::from_origin_size(origin, fitted);
+        paint_ctx.draw_image(&image, rect, InterpolationMode::Bilinear);
+    }
+
+    fn layout(
+        &mut self,
+        _layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        _data: &T,
+        _env: &Env,
+    ) -> Size {
+        let natural = match self.frame.as_ref() {
+            Some(frame) => Size::new(frame.width as f64, frame.height as f64),
+            None => Size::new(100.0, 100.0),
+        };
+        bc.constrain(natural)
+    }
+
+    fn event(&mut self, event: &Event, ctx: &mut EventCtx, _data: &mut T, _env: &Env) {
+        match event {
+            Event::Idle(token) if *token == AUTOPLAY_TOKEN && self.autoplay => {
+                self.autoplay = false;
+                self.play(ctx);
+            }
+            Event::AnimFrame(interval) if self.playing => {
+                self.position += Duration::from_nanos(*interval);
+                if self.advance(ctx) {
+                    ctx.request_anim_frame();
+                } else {
+                    self.playing = false;
+                    ctx.submit_command(sys::VIDEO_ENDED.into(), None);
+                }
+            }
+            Event::Command(cmd) if cmd.selector == sys::VIDEO_PLAY => self.play(ctx),
+            Event::Command(cmd) if cmd.selector == sys::VIDEO_PAUSE => self.playing = false,
+            Event::Command(cmd) if cmd.selector == sys::VIDEO_SEEK => {
+                match cmd.get_object::<Duration>() {
+                    Some(time) => {
+                        self.seek(*time);
+                        ctx.invalidate();
+                    }
+                    None => warn!("VIDEO_SEEK expects a Duration argument"),
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: Option<&T>, _data: &T, _env: &Env) {
+        // Playback needs an event context; the first update asks for one.
+        if self.autoplay && old_data.is_none() {
+            if let Some(idle) = ctx.window().get_idle_handle() {
+                idle.schedule_idle(AUTOPLAY_TOKEN);
+            }
+        }
+    }
+}
@@ -0,0 +1,387 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Widgets that switch between children, with an optional transition.
+
+use log::error;
+
+use crate::kurbo::{Affine, Point, Rect, Size, Vec2};
+use crate::piet::RenderContext;
+use crate::theme;
+use crate::{
+    Animator, BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx,
+    Transition, UpdateCtx, Widget, WidgetPod,
+};
+
+/// How a [`ViewSwitcher`] or [`Either`] animates from one child to the
+/// next.
+///
+/// There is no crossfade, because the renderer can't yet paint a child
+/// with partial opacity.
+///
+/// [`ViewSwitcher`]: struct.ViewSwitcher.html
+/// [`Either`]: struct.Either.html
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SwitchTransition {
+    /// The new child replaces the old one immediately.
+    None,
+    /// The old child fades out and then the new child fades in.
+    ///
+    /// The children fade to and from the window background color, so this
+    /// looks right when the widget is painted over the window background.
+    Fade,
+    /// The new child slides in from the right, pushing the old one out to
+    /// the left.
+    SlideLeft,
+    /// The new child slides in from the left, pushing the old one out to
+    /// the right.
+    SlideRight,
+    /// The new child slides in from the bottom, pushing the old one up.
+    SlideUp,
+    /// The new child slides in from the top, pushing the old one down.
+    SlideDown,
+}
+
+/// A widget that builds its child from a key derived from the data,
+/// replacing the child whenever the key changes.
+///
+/// This is the usual way to show one of several pages: the key says which
+/// page is current, and the builder makes the page's widget.
+pub struct ViewSwitcher<T: Data, K: Data> {
+    key: Box<dyn Fn(&T, &Env) -> K>,
+    builder: Box<dyn Fn(&K, &T, &Env) -> Box<dyn Widget<T>>>,
+    child: Option<WidgetPod<T, Box<dyn Widget<T>>>>,
+    outgoing: Option<WidgetPod<T, Box<dyn Widget<T>>>>,
+    last_key: Option<K>,
+    switch: Switch<T>,
+}
+
+/// A widget that shows one of two children, depending on a condition.
+pub struct Either<T: Data> {
+    condition: Box<dyn Fn(&T, &Env) -> bool>,
+    true_branch: WidgetPod<T, Box<dyn Widget<T>>>,
+    false_branch: WidgetPod<T, Box<dyn Widget<T>>>,
+    current: Option<bool>,
+    switch: Switch<T>,
+}
+
+/// The state of a transition between two children.
+struct Switch<T> {
+    style: SwitchTransition,
+    transition: Transition,
+    /// While switching, the progress from 0 to 1, and the data the outgoing
+    /// child is painted with.
+    ///
+    /// The outgoing child is no longer updated, so it keeps the data it last
+    /// saw rather than data it may not be able to display.
+    running: Option<(Animator<f64>, T)>,
+}
+
+impl<T: Data, K: Data> ViewSwitcher<T, K> {
+    /// Create a new view switcher.
+    ///
+    /// The `key` closure picks the child to show, and `builder` makes it;
+    /// the builder is called again each time the key changes.
+    pub fn new<W: Widget<T> + 'static>(
+        key: impl Fn(&T, &Env) -> K + 'static,
+        builder: impl Fn(&K, &T, &Env) -> W + 'static,
+    ) -> Self {
+        ViewSwitcher {
+            key: Box::new(key),
+            builder: Box::new(move |key, data, env| Box::new(builder(key, data, env))),
+            child: None,
+            outgoing: None,
+            last_key: None,
+            switch: Switch::default(),
+        }
+    }
+
+    /// Animate the change from one child to the next.
+    pub fn transition(mut self, style: SwitchTransition, transition: Transition) -> Self {
+        self.switch.style = style;
+        self.switch.transition = transition;
+        self
+    }
+}
+
+impl<T: Data> Either<T> {
+    /// Create a widget that shows `true_branch` when the condition holds,
+    /// and `false_branch` otherwise.
+    pub fn new(
+        condition: impl Fn(&T, &Env) -> bool + 'static,
+        true_branch: impl Widget<T> + 'static,
+        false_branch: impl Widget<T> + 'static,
+    ) -> Self {
+        Either {
+            condition: Box::new(condition),
+            true_branch: WidgetPod::new(true_branch).boxed(),
+            false_branch: WidgetPod::new(false_branch).boxed(),
+            current: None,
+            switch: Switch::default(),
+        }
+    }
+
+    /// Animate the change from one child to the other.
+    pub fn transition(mut self, style: SwitchTransition, transition: Transition) -> Self {
+        self.switch.style = style;
+        self.switch.transition = transition;
+        self
+    }
+
+    /// The current child, and the other one.
+    fn branches(
+        &mut self,
+    ) -> (
+        &mut WidgetPod<T, Box<dyn Widget<T>>>,
+        &mut WidgetPod<T, Box<dyn Widget<T>>>,
+    ) {
+        if self.current.unwrap_or(true) {
+            (&mut self.true_branch, &mut self.false_branch)
+        } else {
+            (&mut self.false_branch, &mut self.true_branch)
+        }
+    }
+}
+
+impl<T: Data> Switch<T> {
+    fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+
+    /// Start switching away from a child last updated with `old_data`.
+    fn start(&mut self, ctx: &mut UpdateCtx, old_data: T, env: &Env) {
+        if self.style == SwitchTransition::None || env.get(theme::REDUCED_MOTION) {
+            self.running = None;
+            return;
+        }
+        let mut progress = Animator::new(0.0, self.transition);
+        progress.animate_to(1.0);
+        if progress.is_running() {
+            self.running = Some((progress, old_data));
+            ctx.request_anim_frame();
+        }
+    }
+
+    /// Advance the transition. Returns `true` when it has just finished.
+    fn advance(&mut self, ctx: &mut EventCtx, interval: u64) -> bool {
+        let finished = match self.running.as_mut() {
+            Some((progress, _)) => !progress.advance(interval),
+            None => return false,
+        };
+        if finished {
+            self.running = None;
+        } else {
+            ctx.request_anim_frame();
+        }
+        ctx.invalidate();
+        finished
+    }
+
+    /// Paint both children, as they are at this point in the transition.
+    fn paint(
+        &self,
+        paint_ctx: &mut PaintCtx,
+        size: Size,
+        env: &Env,
+        paint_old: impl FnOnce(&mut PaintCtx, &T),
+        paint_new: impl FnOnce(&mut PaintCtx),
+    ) {
+        let (t, old_data) = match self.running.as_ref() {
+            Some((progress, old_data)) => (progress.value(), old_data),
+            None => return paint_new(paint_ctx),
+        };
+        let bounds = Rect::from_origin_size(Point::ORIGIN, size);
+        if let Err(e) = paint_ctx.save() {
+            error!("saving render context failed: {:?}", e);
+            return;
+        }
+        paint_ctx.clip(bounds);
+        let direction = match self.style {
+            SwitchTransition::SlideLeft => Vec2::new(-size.width, 0.0),
+            SwitchTransition::SlideRight => Vec2::new(size.width, 0.0),
+            SwitchTransition::SlideUp => Vec2::new(0.0, -size.height),
+            SwitchTransition::SlideDown => Vec2::new(0.0, size.height),
+            SwitchTransition::Fade | SwitchTransition::None => Vec2::new(0.0, 0.0),
+        };
+        if self.style == SwitchTransition::Fade {
+            let background = env.get(theme::WINDOW_BACKGROUND_COLOR);
+            let cover = if t < 0.5 {
+                paint_old(paint_ctx, old_data);
+                t * 2.0
+            } else {
+                paint_new(paint_ctx);
+                2.0 - t * 2.0
+            };
+            paint_ctx.fill(bounds, &background.with_alpha(cover));
+        } else {
+            with_offset(paint_ctx, direction * t, |ctx| paint_old(ctx, old_data));
+            with_offset(paint_ctx, direction * (t - 1.0), paint_new);
+        }
+        if let Err(e) = paint_ctx.restore() {
+            error!("restoring render context failed: {:?}", e);
+        }
+    }
+}
+
+impl<T> Default for Switch<T> {
+    fn default() -> Self {
+        Switch {
+            style: SwitchTransition::None,
+            transition: Transition::default(),
+            running: None,
+        }
+    }
+}
+
+fn with_offset(paint_ctx: &mut PaintCtx, offset: Vec2, f: impl FnOnce(&mut PaintCtx)) {
+    if let Err(e) = paint_ctx.save() {
+        error!("saving render context failed: {:?}", e);
+        return;
+    }
+    paint_ctx.transform(Affine::translate(offset));
+    f(paint_ctx);
+    if let Err(e) = paint_ctx.restore() {
+        error!("restoring render context failed: {:?}", e);
+    }
+}
+
+fn layout_child<T: Data>(
+    child: &mut WidgetPod<T, Box<dyn Widget<T>>>,
+    ctx: &mut LayoutCtx,
+    bc: &BoxConstraints,
+    data: &T,
+    env: &Env,
+) -> Size {
+    let size = child.layout(ctx, bc, data, env);
+    child.set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+    size
+}
+
+impl<T: Data, K: Data> Widget<T> for ViewSwitcher<T, K> {
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env) {
+        let (child, outgoing) = (&mut self.child, &mut self.outgoing);
+        self.switch.paint(
+            paint_ctx,
+            base_state.size(),
+            env,
+            |paint_ctx, old_data| {
+                if let Some(outgoing) = outgoing.as_mut() {
+                    outgoing.paint(paint_ctx, old_data, env);
+                }
+            },
+            |paint_ctx| {
+                if let Some(child) = child.as_mut() {
+                    child.paint(paint_ctx, data, env);
+                }
+            },
+        );
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+        if let (Some(outgoing), Some((_, old_data))) =
+            (self.outgoing.as_mut(), self.switch.running.as_ref())
+        {
+            layout_child(outgoing, ctx, bc, old_data, env);
+        }
+        match self.child.as_mut() {
+            Some(child) => layout_child(child, ctx, bc, data, env),
+            None => bc.min(),
+        }
+    }
+
+    fn event(&mut self, event: &Event, ctx: &mut EventCtx, data: &mut T, env: &Env) {
+        if let Event::AnimFrame(interval) = event {
+            if self.switch.advance(ctx, *interval) {
+                self.outgoing = None;
+            }
+        }
+        if let Some(child) = self.child.as_mut() {
+            child.event(event, ctx, data, env);
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: Option<&T>, data: &T, env: &Env) {
+        let key = (self.key)(data, env);
+        let key_same = self
+            .last_key
+            .as_ref()
+            .map(|k| k.same(&key))
+            .unwrap_or(false);
+        if !key_same {
+            let child = WidgetPod::new((self.builder)(&key, data, env));
+            self.outgoing = self.child.replace(child);
+            match (self.outgoing.is_some(), old_data) {
+                (true, Some(old_data)) => self.switch.start(ctx, old_data.clone(), env),
+                _ => self.switch.running = None,
+            }
+            if !self.switch.is_running() {
+                self.outgoing = None;
+            }
+            self.last_key = Some(key);
+            ctx.invalidate();
+        }
+        if let Some(child) = self.child.as_mut() {
+            child.update(ctx, data, env);
+        }
+    }
+}
+
+impl<T: Data> Widget<T> for Either<T> {
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env) {
+        let (current, other) = if self.current.unwrap_or(true) {
+            (&mut self.true_branch, &mut self.false_branch)
+        } else {
+            (&mut self.false_branch, &mut self.true_branch)
+        };
+        self.switch.paint(
+            paint_ctx,
+            base_state.size(),
+            env,
+            |paint_ctx, old_data| other.paint(paint_ctx, old_data, env),
+            |paint_ctx| current.paint(paint_ctx, data, env),
+        );
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+        let (current, other) = if self.current.unwrap_or(true) {
+            (&mut self.true_branch, &mut self.false_branch)
+        } else {
+            (&mut self.false_branch, &mut self.true_branch)
+        };
+        if let Some((_, old_data)) = self.switch.running.as_ref() {
+            layout_child(other, ctx, bc, old_data, env);
+        }
+        layout_child(current, ctx, bc, data, env)
+    }
+
+    fn event(&mut self, event: &Event, ctx: &mut EventCtx, data: &mut T, env: &Env) {
+        if let Event::AnimFrame(interval) = event {
+            self.switch.advance(ctx, *interval);
+        }
+        self.branches().0.event(event, ctx, data, env);
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: Option<&T>, data: &T, env: &Env) {
+        let condition = (self.condition)(data, env);
+        if self.current != Some(condition) {
+            match (self.current, old_data) {
+                (Some(_), Some(old_data)) => self.switch.start(ctx, old_data.clone(), env),
+                _ => self.switch.running = None,
+            }
+            self.current = Some(condition);
+            ctx.invalidate();
+        }
+        self.branches().0.update(ctx, data, env);
+    }
+}
@@ -0,0 +1,192 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget showing web content.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use log::warn;
+
+use crate::command::sys;
+use crate::kurbo::Size;
+use crate::shell::platform::IdleHandle;
+use crate::shell::webview::WebView as PlatformWebView;
+use crate::{
+    BaseState, BoxConstraints, Command, Data, Env, Event, EventCtx, IdleToken, LayoutCtx, PaintCtx,
+    UpdateCtx, Widget,
+};
+
+use super::native_view::ViewHost;
+
+/// The token used to wake web views when their page has posted messages.
+const MESSAGE_TOKEN: IdleToken = IdleToken::new(0x7765_6276);
+
+/// A widget showing web pages, using the platform's web engine.
+///
+/// The view is controlled with the `WEBVIEW_*` commands in
+/// [`command::sys`], which apply to every web view in the window. Scripts in
+/// the page talk back by calling `window.druid.postMessage()`; each message
+/// is submitted as a [`WEBVIEW_MESSAGE`] command, carrying the message as a
+/// `String`.
+///
+/// Like a [`NativeView`], the web view is drawn above all druid content.
+/// This widget is only available with the `webview` feature, and on
+/// platforms without a supported web engine it shows nothing.
+///
+/// [`command::sys`]: ../command/sys/index.html
+/// [`WEBVIEW_MESSAGE`]: ../command/sys/constant.WEBVIEW_MESSAGE.html
+/// [`NativeView`]: struct.NativeView.html
+pub struct WebView {
+    web: Option<PlatformWebView>,
+    host: ViewHost,
+    messages: Rc<Messages>,
+}
+
+/// Messages posted by the page, waiting to be submitted as commands.
+#[derive(Default)]
+struct Messages {
+    pending: RefCell<VecDeque<String>>,
+    idle: RefCell<Option<IdleHandle>>,
+}
+
+impl Messages {
+    fn push(&self, message: String) {
+        self.pending.borrow_mut().push_back(message);
+        if let Some(idle) = self.idle.borrow().as_ref() {
+            idle.schedule_idle(MESSAGE_TOKEN);
+        }
+    }
+}
+
+impl WebView {
+    /// Create a new, empty web view.
+    pub fn new() -> WebView {
+        let messages = Rc::new(Messages::default());
+        let sink = messages.clone();
+        let web = match PlatformWebView::new(move |message| sink.push(message)) {
+            Ok(web) => Some(web),
+            Err(e) => {
+                warn!("failed to create web view: {:?}", e);
+                None
+            }
+        };
+        WebView {
+            web,
+            host: ViewHost::default(),
+            messages,
+        }
+    }
+
+    /// Start loading the given URL.
+    pub fn url(self, url: &str) -> Self {
+        if let Some(web) = self.web.as_ref() {
+            web.load_url(url);
+        }
+        self
+    }
+
+    /// Show the given HTML document.
+    pub fn html(self, html: &str) -> Self {
+        if let Some(web) = self.web.as_ref() {
+            web.load_html(html, None);
+        }
+        self
+    }
+
+    /// The platform web view, if one could be created.
+    pub fn platform_view(&self) -> Option<&PlatformWebView> {
+        self.web.as_ref()
+    }
+
+    fn handle_command(web: &PlatformWebView, cmd: &Command) {
+        match (&cmd.selector, cmd.get_object::<String>()) {
+            (&sys::WEBVIEW_LOAD_URL, Some(url)) => web.load_url(url),
+            (&sys::WEBVIEW_LOAD_HTML, Some(html)) => web.load_html(html, None),
+            (&sys::WEBVIEW_EVAL_SCRIPT, Some(script)) => web.eval_script(script),
+            (&sys::WEBVIEW_GO_BACK, _) => web.go_back(),
+            (&sys::WEBVIEW_GO_FORWARD, _) => web.go_forward(),
+            (&sys::WEBVIEW_RELOAD, _) => web.reload(),
+            (&sys::WEBVIEW_STOP, _) => web.stop(),
+            _ => (),
+        }
+    }
+}
+
+impl Default for WebView {
+    fn default() -> Self {
+        WebView::new()
+    }
+}
+
+impl<T: Data> Widget<T> for WebView {
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, _data: &T, _env: &Env) {
+        if let Some(web) = self.web.as_ref() {
+            self.host.paint(web.view(), paint_ctx, base_state.size());
+        }
+    }
+
+    fn layout(
+        &mut self,
+        _layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        _data: &T,
+        _env: &Env,
+    ) -> Size {
+        bc.constrain(Size::new(100.0, 100.0))
+    }
+
+    fn event(&mut self, event: &Event, ctx: &mut EventCtx, _data: &mut T, _env: &Env) {
+        let web = match self.web.as_ref() {
+            Some(web) => web,
+            None => return,
+        };
+        match event {
+            Event::Idle(token) if *token == MESSAGE_TOKEN => {
+                let pending =
+                    std::mem::replace(&mut *self.messages.pending.borrow_mut(), VecDeque::new());
+                for message in pending {
+                    ctx.submit_command(Command::new(sys::WEBVIEW_MESSAGE, message), None);
+                }
+            }
+            Event::Command(cmd) => WebView::handle_command(web, cmd),
+            _ => (),
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: Option<&T>, _data: &T, _env: &Env) {
+        if let Some(web) = self.web.as_ref() {
+            self.host.update(web.view(), ctx);
+        }
+        if self.messages.idle.borrow().is_none() {
+            let idle = ctx.window().get_idle_handle();
+            if let Some(idle) = idle.as_ref() {
+                // Messages may have been posted before we had a window.
+                if !self.messages.pending.borrow().is_empty() {
+                    idle.schedule_idle(MESSAGE_TOKEN);
+                }
+            }
+            *self.messages.idle.borrow_mut() = idle;
+        }
+    }
+}
+
+impl Drop for WebView {
+    fn drop(&mut self) {
+        if let Some(web) = self.web.as_ref() {
+            self.host.detach(web.view());
+        }
+    }
+}
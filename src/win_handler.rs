@@ -17,22 +17,37 @@
 use std::any::Any;
 use std::cell::RefCell;
 use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
 use std::rc::Rc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
-use log::{error, info, warn};
+use log::{debug, error, warn};
 
-use crate::kurbo::{Size, Vec2};
+use crate::kurbo::{Affine, Point, Rect, Size};
 use crate::piet::{Color, Piet, RenderContext};
 use crate::shell::application::Application;
 use crate::shell::dialog::FileDialogOptions;
-use crate::shell::window::{Cursor, WinCtx, WinHandler, WindowHandle};
-
+use crate::shell::dnd::{DragEvent as PlatformDragEvent, DropEffect};
+use crate::shell::window::{Cursor, FileInfo, WinCtx, WinHandler, WindowHandle};
+
+use crate::debug_paint::DebugPaint;
+use crate::dnd::DragState;
+use crate::ext_event::{ExtQueue, EXT_COMMAND_TOKEN};
+use crate::focus::{self, FocusState};
+use crate::idle::{IdleQueue, IDLE_TASK_TOKEN};
+use crate::inspector::{self, SharedTree, INSPECTOR_HIGHLIGHT};
 use crate::menu::ContextMenu;
-use crate::window::Window;
+#[cfg(feature = "persistence")]
+use crate::persist::Persistence;
+use crate::profiler::{Pass, Profiler};
+use crate::replay::{InputEvent, Recorder, Replayer};
+use crate::time_travel::{SharedHistory, Step, TIME_TRAVEL};
+use crate::trace;
+use crate::window::{FrameRate, Window};
 use crate::{
-    BaseState, Command, Data, Env, Event, EventCtx, KeyEvent, KeyModifiers, LayoutCtx, MenuDesc,
-    MouseEvent, PaintCtx, TimerToken, UpdateCtx, WheelEvent, WindowDesc, WindowId,
+    AppDelegate, BaseState, Command, Data, DelegateCtx, DragEvent, Env, Event, EventCtx, IdleToken,
+    ImeEvent, KeyCode, KeyEvent, LayoutCtx, MenuDesc, MouseEvent, PaintCtx, PenEvent, TimerToken,
+    UpdateCtx, WheelEvent, WindowDesc, WindowId, WindowInfo, WindowPlacement,
 };
 
 use crate::command::sys as sys_cmd;
@@ -40,6 +55,13 @@ use crate::command::sys as sys_cmd;
 // TODO: this should come from the theme.
 const BACKGROUND_COLOR: Color = Color::rgb8(0x27, 0x28, 0x22);
 
+/// How far off a capped frame must be to be delayed with a timer, rather
+/// than painted at the display's next refresh.
+const MIN_FRAME_DELAY: Duration = Duration::from_millis(8);
+
+/// The lowest frame rate a window can be capped to, in frames per second.
+const MIN_FRAME_RATE: f64 = 1.0;
+
 /// The struct implements the druid-shell `WinHandler` trait.
 ///
 /// One `DruidHandler` exists per window.
@@ -57,8 +79,29 @@ pub struct DruidHandler<T: Data> {
 pub(crate) struct AppState<T: Data> {
     command_queue: VecDeque<(WindowId, Command)>,
     windows: Windows<T>,
+    /// The menu shown on the application's dock icon. (mac only)
+    dock_menu: Option<MenuDesc<T>>,
+    /// Commands to be sent to the first window, once it is connected.
+    launch_commands: Vec<Command>,
     pub(crate) env: Env,
     pub(crate) data: T,
+    /// Saves parts of the data between runs.
+    #[cfg(feature = "persistence")]
+    pub(crate) persistence: Option<Persistence<T>>,
+    /// Snapshots of the data, if time travel is enabled.
+    pub(crate) history: Option<SharedHistory<T>>,
+    /// Every command processed, if they are being recorded for a test.
+    pub(crate) command_log: Option<Vec<Command>>,
+    /// Writes the input to a file, if it is being recorded.
+    pub(crate) recorder: Option<Recorder>,
+    /// Delivers recorded input, if it is being replayed.
+    pub(crate) replayer: Option<Replayer>,
+    /// Handles application-level behaviour, if the application has one.
+    pub(crate) delegate: Option<Box<dyn AppDelegate<T>>>,
+    /// The window that most recently had focus.
+    focused_window: Option<WindowId>,
+    /// Whether the application quits when its last window is closed.
+    pub(crate) quit_on_last_window: bool,
 }
 
 /// All active windows.
@@ -71,6 +114,37 @@ struct Windows<T: Data> {
 pub(crate) struct WindowState {
     pub(crate) handle: WindowHandle,
     prev_paint_time: Option<Instant>,
+    /// The time the last animation frame was expected to be shown, if known.
+    prev_present_time: Option<Instant>,
+    /// How often the window paints while animating.
+    frame_rate: FrameRate,
+    /// The timer that delays the next frame to keep to the frame rate.
+    frame_timer: Option<TimerToken>,
+    /// The state of any drag over this window.
+    drag: DragState,
+    /// The state of keyboard focus.
+    focus: FocusState,
+    /// Commands submitted to this window from other threads.
+    ext_queue: ExtQueue,
+    /// A fixed interval between animation frames, in nanoseconds, used
+    /// instead of the time between paints to make animation deterministic.
+    frame_interval: Option<u64>,
+    /// Whether the debug overlay is shown.
+    debug_paint: bool,
+    /// The tree shown by an inspector for this window, if one is open.
+    inspector: Option<SharedTree>,
+    /// Whether a description of the widgets has been requested, to be sent
+    /// after the next paint.
+    describe_widgets: bool,
+    /// The scale of an image of the window that has been requested, to be
+    /// rendered after the next paint.
+    render_request: Option<f64>,
+    /// Measures the time spent in each pass, if profiling is on.
+    profiler: Option<Profiler>,
+    /// Work to be done when the window is idle.
+    idle: IdleQueue,
+    /// The position of the most recent mouse event in this window.
+    last_mouse_pos: Option<Point>,
 }
 
 /// Everything required for a window to handle an event.
@@ -88,6 +162,20 @@ impl<T: Data> Windows<T> {
         let state = WindowState {
             handle,
             prev_paint_time: None,
+            prev_present_time: None,
+            frame_rate: FrameRate::Vsync,
+            frame_timer: None,
+            drag: DragState::default(),
+            focus: FocusState::default(),
+            ext_queue: ExtQueue::default(),
+            frame_interval: None,
+            debug_paint: DebugPaint::enabled_by_env(),
+            inspector: None,
+            describe_widgets: false,
+            render_request: None,
+            profiler: Profiler::from_env(),
+            idle: IdleQueue::default(),
+            last_mouse_pos: None,
         };
         self.state.insert(id, state);
     }
@@ -133,23 +221,87 @@ impl<T: Data> Windows<T> {
 
 impl<'a, T: Data + 'static> SingleWindowState<'a, T> {
     fn paint(&mut self, piet: &mut Piet, ctx: &mut dyn WinCtx) -> bool {
-        let request_anim = self.do_anim_frame(ctx);
-        self.do_layout(piet);
+        let start = Instant::now();
+        let request_anim = {
+            let span = trace::window_span(Pass::Event, self.window_id);
+            let _entered = span.enter();
+            self.do_anim_frame(ctx)
+        };
+        self.add_time(Pass::Event, start);
+        let start = Instant::now();
+        {
+            let span = trace::window_span(Pass::Layout, self.window_id);
+            let _entered = span.enter();
+            self.do_layout(piet);
+        }
+        self.add_time(Pass::Layout, start);
         piet.clear(BACKGROUND_COLOR);
-        self.do_paint(piet);
-        request_anim
+        let start = Instant::now();
+        {
+            let span = trace::window_span(Pass::Paint, self.window_id);
+            let _entered = span.enter();
+            self.do_paint(piet, ctx);
+        }
+        self.add_time(Pass::Paint, start);
+        if let Some(scale) = self.state.render_request.take() {
+            self.render_offscreen(scale);
+        }
+        if let Some(profiler) = self.state.profiler.as_mut() {
+            profiler.end_frame();
+            profiler.paint_overlay(piet, self.env);
+        }
+        request_anim && !self.delay_next_frame(ctx)
+    }
+
+    /// If the frame rate is capped and the next frame would come too soon,
+    /// schedule it for later, returning `true`.
+    fn delay_next_frame(&mut self, ctx: &mut dyn WinCtx) -> bool {
+        let fps = match self.state.frame_rate {
+            FrameRate::Capped(fps) if fps > 0.0 => fps,
+            _ => return false,
+        };
+        let last = match self.state.prev_paint_time {
+            Some(last) => last,
+            None => return false,
+        };
+        let next = last + Duration::from_secs_f64(1.0 / fps);
+        // A frame that is nearly due waits for the display's next refresh.
+        if next <= Instant::now() + MIN_FRAME_DELAY {
+            return false;
+        }
+        if self.state.frame_timer.is_none() {
+            self.state.frame_timer = Some(ctx.request_timer(next));
+        }
+        true
+    }
+
+    /// Add the time since `start` to the window's profile.
+    fn add_time(&mut self, pass: Pass, start: Instant) {
+        if let Some(profiler) = self.state.profiler.as_mut() {
+            profiler.add(pass, start.elapsed());
+        }
     }
 
     fn do_anim_frame(&mut self, ctx: &mut dyn WinCtx) -> bool {
-        // TODO: this calculation uses wall-clock time of the paint call, which
-        // potentially has jitter.
+        // Where the platform predicts when frames are shown, intervals are
+        // measured between those times, which don't have the jitter of the
+        // wall-clock time of the paint call.
         //
         // See https://github.com/xi-editor/druid/issues/85 for discussion.
         let this_paint_time = Instant::now();
+        let this_present_time = ctx.presentation_time();
         let prev_paint_time = self.state.prev_paint_time;
         let interval = if let Some(last) = prev_paint_time {
-            let duration = this_paint_time.duration_since(last);
-            1_000_000_000 * duration.as_secs() + (duration.subsec_nanos() as u64)
+            match self.state.frame_interval {
+                Some(interval) => interval,
+                None => {
+                    let duration = match (this_present_time, self.state.prev_present_time) {
+                        (Some(this), Some(prev)) if this > prev => this - prev,
+                        _ => this_paint_time.duration_since(last),
+                    };
+                    1_000_000_000 * duration.as_secs() + (duration.subsec_nanos() as u64)
+                }
+            }
         } else {
             0
         };
@@ -161,6 +313,7 @@ impl<'a, T: Data + 'static> SingleWindowState<'a, T> {
             None
         };
         self.state.prev_paint_time = prev;
+        self.state.prev_present_time = this_present_time.filter(|_| request_anim);
         request_anim
     }
 
@@ -172,12 +325,72 @@ impl<'a, T: Data + 'static> SingleWindowState<'a, T> {
         self.window.layout(&mut layout_ctx, self.data, self.env);
     }
 
-    fn do_paint(&mut self, piet: &mut Piet) {
+    fn do_paint(&mut self, piet: &mut Piet, ctx: &mut dyn WinCtx) {
+        let inspector = self.state.inspector.as_ref();
+        let record = inspector.is_some() || self.state.describe_widgets;
+        let highlight = inspector.and_then(|tree| tree.borrow().highlight);
         let mut paint_ctx = PaintCtx {
             render_ctx: piet,
             window_id: self.window_id,
+            ime_cursor_rect: None,
+            debug: DebugPaint::new(self.state.debug_paint, record, highlight),
+            focus_visible: self.state.focus.visible,
+            child_rects: None,
+            transform: Affine::default(),
+            saved_transforms: Vec::new(),
         };
         self.window.paint(&mut paint_ctx, self.data, self.env);
+        let tree = paint_ctx.debug.take().and_then(|debug| debug.finish());
+        if let Some(tree) = tree {
+            if std::mem::replace(&mut self.state.describe_widgets, false) {
+                let cmd = Command::new(sys_cmd::WIDGET_TREE, tree.clone());
+                self.command_queue.push_back((self.window_id, cmd));
+            }
+            if let Some(inspector) = self.state.inspector.as_ref() {
+                inspector.borrow_mut().set_tree(tree);
+            }
+        }
+        if let Some(rect) = paint_ctx.ime_cursor_rect {
+            ctx.set_ime_cursor_rect(rect);
+        }
+    }
+
+    /// Paint the window into an image, and send it to the window with a
+    /// `WINDOW_IMAGE` command.
+    #[cfg(not(any(target_os = "windows", target_arch = "wasm32")))]
+    fn render_offscreen(&mut self, scale: f64) {
+        let size = self.window.size();
+        let width = (size.width * scale).ceil().max(1.0) as u32;
+        let height = (size.height * scale).ceil().max(1.0) as u32;
+        let window_id = self.window_id;
+        let (window, data, env) = (&mut *self.window, &*self.data, self.env);
+        let result = crate::shell::headless::render_image(width, height, |piet| {
+            piet.clear(BACKGROUND_COLOR);
+            piet.transform(Affine::scale(scale));
+            let mut paint_ctx = PaintCtx {
+                render_ctx: piet,
+                window_id,
+                ime_cursor_rect: None,
+                debug: None,
+                focus_visible: false,
+                child_rects: None,
+                transform: Affine::scale(scale),
+                saved_transforms: Vec::new(),
+            };
+            window.paint(&mut paint_ctx, data, env);
+        });
+        match result {
+            Ok((image, ())) => {
+                let cmd = Command::new(sys_cmd::WINDOW_IMAGE, image);
+                self.command_queue.push_back((self.window_id, cmd));
+            }
+            Err(e) => error!("rendering the window failed: {:?}", e),
+        }
+    }
+
+    #[cfg(any(target_os = "windows", target_arch = "wasm32"))]
+    fn render_offscreen(&mut self, _scale: f64) {
+        warn!("rendering a window to an image is not supported on this platform");
     }
 
     /// Send an event to the widget hierarchy.
@@ -186,11 +399,29 @@ impl<'a, T: Data + 'static> SingleWindowState<'a, T> {
     /// second is true if invalidation is requested. The third is true if an
     /// animation frame is requested.
     fn do_event_inner(&mut self, event: Event, win_ctx: &mut dyn WinCtx) -> (bool, bool, bool) {
+        let event = self.map_internal_drag(event);
         // should there be a root base state persisting in the ui state instead?
         let mut cursor = match event {
             Event::MouseMoved(..) => Some(Cursor::Arrow),
             _ => None,
         };
+        let prev_drop_effect = self.state.drag.drop_effect;
+        self.state.drag.drop_effect = DropEffect::None;
+
+        match &event {
+            Event::MouseDown(e) | Event::MouseUp(e) | Event::MouseMoved(e) => {
+                self.state.last_mouse_pos = Some(e.pos)
+            }
+            _ => (),
+        }
+
+        // Focus is only shown until the mouse is used.
+        if let Event::MouseDown(_) = event {
+            if self.state.focus.visible {
+                self.state.focus.visible = false;
+                win_ctx.invalidate();
+            }
+        }
 
         let event = match event {
             Event::Size(size) => {
@@ -201,11 +432,16 @@ impl<'a, T: Data + 'static> SingleWindowState<'a, T> {
             other => other,
         };
 
+        let had_capture = self.wants_mouse_capture();
         let mut base_state = BaseState::default();
         let mut ctx = EventCtx {
             win_ctx,
             cursor: &mut cursor,
+            drag: &mut self.state.drag,
+            focus: &mut self.state.focus,
             command_queue: self.command_queue,
+            ext_queue: &self.state.ext_queue,
+            idle: &mut self.state.idle,
             base_state: &mut base_state,
             is_handled: false,
             is_root: true,
@@ -221,15 +457,111 @@ impl<'a, T: Data + 'static> SingleWindowState<'a, T> {
             self.window
                 .event(&focus_event, &mut ctx, self.data, self.env);
         }
-        let needs_inval = ctx.base_state.needs_inval;
-        let request_anim = ctx.base_state.request_anim;
+        let mut needs_inval = ctx.base_state.needs_inval;
+        let mut request_anim = ctx.base_state.request_anim;
+
+        let drag_finished = match event {
+            Event::DragOver(ref drag) | Event::DragEnter(ref drag) if drag.is_internal() => {
+                if self.state.drag.drop_effect == DropEffect::None {
+                    cursor = Some(Cursor::NotAllowed);
+                } else {
+                    cursor = Some(Cursor::Arrow);
+                }
+                None
+            }
+            Event::Drop(ref drag) if drag.is_internal() && is_handled => {
+                // The target may not set an effect on drop; fall back to the
+                // one it accepted the drag with.
+                let effect = match self.state.drag.drop_effect {
+                    DropEffect::None => prev_drop_effect,
+                    effect => effect,
+                };
+                Some(effect)
+            }
+            Event::Drop(ref drag) if drag.is_internal() => Some(DropEffect::None),
+            Event::DragLeave if self.state.drag.internal.is_some() => Some(DropEffect::None),
+            _ => None,
+        };
+
         if let Some(cursor) = cursor {
             win_ctx.set_cursor(&cursor);
         }
 
+        if let Some(effect) = drag_finished {
+            self.state.drag.internal = None;
+            let (_, inval, anim) = self.do_event_inner(Event::DragEnd(effect), win_ctx);
+            needs_inval |= inval;
+            request_anim |= anim;
+        }
+
+        let is_handled = match event {
+            Event::KeyDown(ref key) if !is_handled && key.key_code == KeyCode::Tab => {
+                let mods = key.mods;
+                if mods.ctrl || mods.alt || mods.meta {
+                    false
+                } else {
+                    let (inval, anim) = self.move_focus(!mods.shift, win_ctx);
+                    needs_inval |= inval;
+                    request_anim |= anim;
+                    true
+                }
+            }
+            _ => is_handled,
+        };
+
+        let wants_capture = self.wants_mouse_capture();
+        if wants_capture != had_capture {
+            win_ctx.set_mouse_capture(wants_capture);
+        }
+        self.state.idle.schedule(&self.state.handle);
+
         (is_handled, needs_inval, request_anim)
     }
 
+    /// Move focus to the next widget that accepts it, or the previous one if
+    /// `forward` is false.
+    ///
+    /// Returns two flags: whether invalidation, and whether an animation
+    /// frame, was requested.
+    fn move_focus(&mut self, forward: bool, win_ctx: &mut dyn WinCtx) -> (bool, bool) {
+        self.state.focus.chain.clear();
+        let collect = Event::Command(focus::COLLECT_FOCUS_CHAIN.into());
+        self.do_event_inner(collect, win_ctx);
+        let chain = std::mem::replace(&mut self.state.focus.chain, Vec::new());
+        let target = match focus::next_in_chain(&chain, forward) {
+            Some(target) => target,
+            None => return (false, false),
+        };
+        self.state.focus.target = Some(target);
+        self.state.focus.visible = true;
+        let take = Event::Command(focus::TAKE_FOCUS.into());
+        let (_, _, anim) = self.do_event_inner(take, win_ctx);
+        self.state.focus.target = None;
+        (true, anim)
+    }
+
+    /// The mouse is captured while a widget is active, so that it keeps
+    /// getting mouse events when the cursor leaves the window, and for the
+    /// duration of an in-app drag.
+    fn wants_mouse_capture(&self) -> bool {
+        self.window.root.state.has_active || self.state.drag.internal.is_some()
+    }
+
+    /// While an in-app drag is in progress, mouse events are delivered to
+    /// widgets as drag events.
+    fn map_internal_drag(&self, event: Event) -> Event {
+        let payload = match self.state.drag.internal {
+            Some(ref payload) => payload.clone(),
+            None => return event,
+        };
+        match event {
+            Event::MouseMoved(e) => Event::DragOver(DragEvent::internal(e.pos, e.mods, payload)),
+            Event::MouseUp(e) => Event::Drop(DragEvent::internal(e.pos, e.mods, payload)),
+            Event::KeyDown(e) if e.key_code == KeyCode::Escape => Event::DragLeave,
+            other => other,
+        }
+    }
+
     fn set_menu(&mut self, cmd: &Command) {
         let mut menu = match cmd.get_object::<MenuDesc<T>>() {
             Some(menu) => menu.to_owned(),
@@ -259,6 +591,29 @@ impl<'a, T: Data + 'static> SingleWindowState<'a, T> {
         self.window.context_menu = Some(menu);
     }
 
+    #[cfg(target_os = "windows")]
+    fn set_taskbar_progress(&mut self, cmd: &Command) {
+        match cmd.get_object::<crate::TaskbarProgress>() {
+            Some(progress) => self.state.handle.set_taskbar_progress(*progress),
+            None => warn!("set-taskbar-progress command is missing progress"),
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    fn set_taskbar_progress(&mut self, _cmd: &Command) {}
+
+    fn set_safe_area(&mut self, area: Rect, win_ctx: &mut dyn WinCtx) {
+        let scale = 96.0 / self.state.handle.get_dpi() as f64;
+        let area = Rect::new(
+            area.x0 * scale,
+            area.y0 * scale,
+            area.x1 * scale,
+            area.y1 * scale,
+        );
+        self.window.safe_area = Some(area);
+        win_ctx.invalidate();
+    }
+
     fn window_got_focus(&mut self) {
         #[cfg(target_os = "macos")]
         self.macos_update_app_menu()
@@ -292,6 +647,17 @@ impl<T: Data + 'static> AppState<T> {
             data,
             env,
             windows: Windows::default(),
+            dock_menu: None,
+            launch_commands: Vec::new(),
+            #[cfg(feature = "persistence")]
+            persistence: None,
+            history: None,
+            command_log: None,
+            recorder: None,
+            replayer: None,
+            delegate: None,
+            focused_window: None,
+            quit_on_last_window: false,
         }))
     }
 
@@ -300,18 +666,176 @@ impl<T: Data + 'static> AppState<T> {
             .windows
             .get(&window_id)
             .and_then(|w| w.get_menu_cmd(cmd_id))
+            .or_else(|| {
+                self.dock_menu
+                    .as_ref()
+                    .and_then(|m| m.command_for_id(cmd_id))
+            })
     }
 
     fn connect(&mut self, id: WindowId, handle: WindowHandle) {
         self.windows.connect(id, handle);
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.add_window(id);
+        }
+        if let Some(replayer) = self.replayer.as_mut() {
+            replayer.add_window(id);
+        }
+        for cmd in self.launch_commands.drain(..) {
+            self.command_queue.push_back((id, cmd));
+        }
+        self.update_window_lists(Some(id));
+        self.with_delegate(|delegate, data, env, ctx| delegate.window_added(id, data, env, ctx));
+    }
+
+    /// Start recording the widget tree of a window for an inspector, and
+    /// repaint the window so that the tree is filled in.
+    fn inspect(&mut self, id: WindowId) -> Option<SharedTree> {
+        let state = self.windows.state.get_mut(&id)?;
+        let tree = state.inspector.get_or_insert_with(Default::default).clone();
+        state.handle.invalidate();
+        Some(tree)
+    }
+
+    /// Queue a command to be sent to the first window, when it is created.
+    pub(crate) fn add_launch_command(&mut self, cmd: Command) {
+        self.launch_commands.push(cmd);
     }
 
     pub(crate) fn add_window(&mut self, id: WindowId, window: Window<T>) {
         self.windows.add(id, window);
     }
 
+    /// `id`, unless a window with that id is already open, in which case
+    /// a new id.
+    pub(crate) fn unused_window_id(&self, id: WindowId) -> WindowId {
+        if self.windows.windows.contains_key(&id) {
+            WindowId::new()
+        } else {
+            id
+        }
+    }
+
     fn remove_window(&mut self, id: WindowId) -> Option<WindowHandle> {
-        self.windows.remove(id)
+        let handle = self.windows.remove(id);
+        if self.focused_window == Some(id) {
+            self.focused_window = None;
+        }
+        self.update_window_lists(None);
+        if handle.is_some() {
+            self.with_delegate(|delegate, data, env, ctx| {
+                delegate.window_removed(id, data, env, ctx)
+            });
+        }
+        handle
+    }
+
+    /// Where to put a new window of the given size, opened from `parent`.
+    fn new_window_position(
+        &self,
+        parent: WindowId,
+        placement: WindowPlacement,
+        size: Size,
+    ) -> Option<Point> {
+        let window = self.windows.windows.get(&parent)?;
+        let state = self.windows.state.get(&parent)?;
+        let frame = state.handle.get_frame();
+        // mouse positions are relative to the content, below the title bar
+        let content_top = frame.y1 - window.size().height;
+        let pointer = state
+            .last_mouse_pos
+            .map(|pos| Point::new(frame.x0 + pos.x, content_top + pos.y));
+        placement.position(frame, pointer, size)
+    }
+
+    /// Whether the last window has been closed, and the application should
+    /// quit because of it.
+    fn should_quit(&self) -> bool {
+        self.quit_on_last_window && self.windows.state.is_empty()
+    }
+
+    /// Descriptions of the open windows, sorted by id.
+    fn window_info(&self) -> Vec<WindowInfo> {
+        let Windows { state, windows } = &self.windows;
+        let mut info: Vec<WindowInfo> = windows
+            .iter()
+            .filter(|(id, _)| state.contains_key(id))
+            .map(|(id, window)| WindowInfo {
+                id: *id,
+                title: window.title.localized_str().to_string(),
+                focused: self.focused_window == Some(*id),
+            })
+            .collect();
+        info.sort_by_key(|window| window.id);
+        info
+    }
+
+    /// Call a method of the delegate, if there is one.
+    fn with_delegate<R>(
+        &mut self,
+        f: impl FnOnce(&mut dyn AppDelegate<T>, &mut T, &Env, &mut DelegateCtx) -> R,
+    ) -> Option<R> {
+        let mut delegate = self.delegate.take()?;
+        let mut ctx = DelegateCtx {
+            windows: self.window_info(),
+            command_queue: &mut self.command_queue,
+        };
+        let result = f(delegate.as_mut(), &mut self.data, &self.env, &mut ctx);
+        self.delegate = Some(delegate);
+        Some(result)
+    }
+
+    /// Pass a command to the delegate, updating the windows if it changes
+    /// the data. Returns `false` if the command should go no further.
+    fn delegate_command(
+        &mut self,
+        target: WindowId,
+        cmd: &Command,
+        win_ctx: &mut dyn WinCtx,
+    ) -> bool {
+        let old_data = self.data.clone();
+        let proceed = self
+            .with_delegate(|delegate, data, env, ctx| delegate.command(target, cmd, data, env, ctx))
+            .unwrap_or(true);
+        if !old_data.same(&self.data) {
+            self.update_windows(target, false, win_ctx);
+        }
+        proceed
+    }
+
+    /// Update the lists of open windows in the windows' menus, rebuilding
+    /// the menus that have them.
+    ///
+    /// As in `rebuild_menus`, on macOS only the menu of `active_id` is
+    /// installed.
+    fn update_window_lists(&mut self, active_id: Option<WindowId>) {
+        let open: Vec<(WindowId, String)> = self
+            .window_info()
+            .into_iter()
+            .map(|window| (window.id, window.title))
+            .collect();
+        let AppState {
+            ref mut windows,
+            ref data,
+            ref env,
+            ..
+        } = self;
+        let Windows { state, windows } = windows;
+        for (id, window) in windows.iter_mut() {
+            let menu = match window.menu.as_mut() {
+                Some(menu) => menu,
+                None => continue,
+            };
+            if !menu.set_open_windows(&open, *id) {
+                continue;
+            }
+            if cfg!(target_os = "macos") && Some(*id) != active_id {
+                continue;
+            }
+            if let Some(state) = state.get(id) {
+                state.handle.set_menu(menu.build_window_menu(data, env));
+            }
+        }
     }
 
     fn assemble_window_state<'a>(
@@ -341,6 +865,8 @@ impl<T: Data + 'static> AppState<T> {
                 &sys_cmd::SET_MENU => {
                     self.assemble_window_state(source_id)
                         .map(|mut win| win.set_menu(cmd));
+                    // the new menu may list the open windows
+                    self.update_window_lists(Some(source_id));
                     return true;
                 }
                 &sys_cmd::SHOW_CONTEXT_MENU => {
@@ -348,15 +874,129 @@ impl<T: Data + 'static> AppState<T> {
                         .map(|mut win| win.show_context_menu(cmd));
                     return true;
                 }
+                &TIME_TRAVEL => self.step_history(cmd),
+                &sys_cmd::TOGGLE_DEBUG_PAINT => {
+                    if let Some(state) = self.windows.state.get_mut(&source_id) {
+                        state.debug_paint = !state.debug_paint;
+                        state.handle.invalidate();
+                    }
+                    return true;
+                }
+                &sys_cmd::SET_FRAME_RATE => {
+                    if let Some(state) = self.windows.state.get_mut(&source_id) {
+                        match cmd.get_object::<FrameRate>() {
+                            Some(rate) => {
+                                let rate = match *rate {
+                                    FrameRate::Capped(fps) if fps.is_nan() => {
+                                        warn!("ignoring a frame rate that is not a number");
+                                        FrameRate::Vsync
+                                    }
+                                    FrameRate::Capped(fps) => {
+                                        FrameRate::Capped(fps.max(MIN_FRAME_RATE))
+                                    }
+                                    rate => rate,
+                                };
+                                state.frame_rate = rate;
+                                state.frame_timer = None;
+                                state.handle.set_vsync(rate != FrameRate::Uncapped);
+                                state.handle.invalidate();
+                            }
+                            None => warn!("set-frame-rate command is missing frame rate"),
+                        }
+                    }
+                    return true;
+                }
+                &sys_cmd::TOGGLE_PROFILER => {
+                    if let Some(state) = self.windows.state.get_mut(&source_id) {
+                        state.profiler = match state.profiler {
+                            Some(_) => None,
+                            None => {
+                                let verbose = cmd.get_object::<bool>().cloned();
+                                Some(Profiler::new(verbose.unwrap_or(false)))
+                            }
+                        };
+                        state.handle.invalidate();
+                    }
+                    return true;
+                }
+                &sys_cmd::DESCRIBE_WIDGETS => {
+                    if let Some(state) = self.windows.state.get_mut(&source_id) {
+                        state.describe_widgets = true;
+                        state.handle.invalidate();
+                    }
+                    return true;
+                }
+                &sys_cmd::RENDER_WINDOW => {
+                    if let Some(state) = self.windows.state.get_mut(&source_id) {
+                        let scale = cmd.get_object::<f64>().cloned().unwrap_or(1.0);
+                        state.render_request = Some(scale);
+                        state.handle.invalidate();
+                    }
+                    return true;
+                }
+                &INSPECTOR_HIGHLIGHT => {
+                    if let Some(state) = self.windows.state.get(&source_id) {
+                        state.handle.invalidate();
+                    }
+                    return true;
+                }
+                &sys_cmd::SHOW_WINDOW => {
+                    let id = cmd.get_object::<WindowId>().unwrap_or(&source_id);
+                    if let Some(state) = self.windows.state.get(id) {
+                        state.handle.bring_to_front_and_focus();
+                    }
+                    return true;
+                }
+                &sys_cmd::MINIMIZE_WINDOW => {
+                    if let Some(state) = self.windows.state.get(&source_id) {
+                        state.handle.minimize();
+                    }
+                    return true;
+                }
+                &sys_cmd::ZOOM_WINDOW => {
+                    if let Some(state) = self.windows.state.get(&source_id) {
+                        state.handle.zoom();
+                    }
+                    return true;
+                }
+                &sys_cmd::SHOW_NEXT_TAB
+                | &sys_cmd::SHOW_PREVIOUS_TAB
+                | &sys_cmd::MERGE_ALL_WINDOWS => {
+                    self.macos_tab_command(source_id, cmd);
+                    return true;
+                }
+                &sys_cmd::SET_TASKBAR_PROGRESS => {
+                    self.assemble_window_state(source_id)
+                        .map(|mut win| win.set_taskbar_progress(cmd));
+                    return true;
+                }
                 _ => (),
             }
         }
 
+        let start = Instant::now();
         let (is_handled, dirty, anim) = self
             .assemble_window_state(source_id)
-            .map(|mut win| win.do_event_inner(event, win_ctx))
+            .map(|mut win| {
+                let span = trace::window_span(Pass::Event, source_id);
+                let _entered = span.enter();
+                let result = win.do_event_inner(event, win_ctx);
+                win.add_time(Pass::Event, start);
+                result
+            })
             .unwrap_or((false, false, false));
+        self.update_windows(source_id, anim || dirty, win_ctx);
+        is_handled
+    }
 
+    /// Send `update` to all windows, after an event in `source_id`, which is
+    /// repainted if `source_dirty` is set.
+    fn update_windows(
+        &mut self,
+        source_id: WindowId,
+        source_dirty: bool,
+        win_ctx: &mut dyn WinCtx,
+    ) {
         let AppState {
             ref mut windows,
             ref data,
@@ -367,25 +1007,189 @@ impl<T: Data + 'static> AppState<T> {
 
         // we send `update` to all windows, not just the active one:
         for (id, window) in windows {
-            if let Some(state) = state.get(id) {
+            if let Some(state) = state.get_mut(id) {
+                let span = trace::window_span(Pass::Update, *id);
+                let _entered = span.enter();
+                let start = Instant::now();
                 let mut update_ctx = UpdateCtx {
                     text_factory: win_ctx.text_factory(),
                     window: &state.handle,
                     needs_inval: false,
+                    needs_layout: false,
+                    request_anim: false,
                     window_id: *id,
                 };
                 window.update(&mut update_ctx, data, env);
-                if *id == source_id && (anim || dirty || update_ctx.needs_inval) {
-                    update_ctx.window.invalidate();
-                } else if update_ctx.needs_inval {
+                // On macOS the menu belongs to the application, so only the
+                // menu of the window the event went to is kept up to date;
+                // the others are rebuilt when their window gains focus.
+                if !cfg!(target_os = "macos") || *id == source_id {
+                    window.update_menu(&state.handle, data, env);
+                }
+                let needs_paint = update_ctx.needs_inval || update_ctx.needs_layout;
+                if *id == source_id && (source_dirty || needs_paint) {
+                    // The context reaches windows without a real handle,
+                    // such as headless ones.
+                    win_ctx.invalidate();
+                } else if needs_paint || update_ctx.request_anim {
                     update_ctx.window.invalidate();
                 }
+                if let Some(profiler) = state.profiler.as_mut() {
+                    profiler.add(Pass::Update, start.elapsed());
+                }
             }
         }
-        is_handled
+        if let Some(history) = self.history.as_ref() {
+            history.borrow_mut().record(&self.data);
+        }
+        #[cfg(feature = "persistence")]
+        {
+            if let Some(persistence) = self.persistence.as_mut() {
+                persistence.save_if_due(&self.data);
+            }
+        }
+    }
+
+    /// Display a snapshot from the history. The windows are updated along
+    /// with the rest of the command's handling.
+    fn step_history(&mut self, cmd: &Command) {
+        let step = match cmd.get_object::<Step>() {
+            Some(step) => *step,
+            None => {
+                warn!("time travel command has no step");
+                return;
+            }
+        };
+        let snapshot = self
+            .history
+            .as_ref()
+            .and_then(|history| history.borrow_mut().step(step));
+        if let Some(snapshot) = snapshot {
+            self.data = snapshot;
+        }
+    }
+
+    /// Handle a command for native window tabs. (mac only)
+    #[cfg(target_os = "macos")]
+    fn macos_tab_command(&self, window_id: WindowId, cmd: &Command) {
+        let handle = match self.windows.state.get(&window_id) {
+            Some(state) => &state.handle,
+            None => return,
+        };
+        match &cmd.selector {
+            &sys_cmd::SHOW_NEXT_TAB => handle.select_next_tab(),
+            &sys_cmd::SHOW_PREVIOUS_TAB => handle.select_previous_tab(),
+            _ => handle.merge_all_windows(),
+        }
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    fn macos_tab_command(&self, _window_id: WindowId, _cmd: &Command) {}
+
+    /// Use a fixed interval between animation frames in every window.
+    #[cfg(not(any(target_os = "windows", target_arch = "wasm32")))]
+    pub(crate) fn set_frame_interval(&mut self, interval: Option<u64>) {
+        for state in self.windows.state.values_mut() {
+            state.frame_interval = interval;
+        }
+    }
+
+    /// Save the persisted parts of the data, if they have changed.
+    #[cfg(feature = "persistence")]
+    pub(crate) fn save_state(&mut self) {
+        if let Some(persistence) = self.persistence.as_mut() {
+            persistence.save(&self.data);
+        }
+    }
+
+    /// Rebuild the menus of all windows, after a change that affects them,
+    /// such as a new locale.
+    ///
+    /// On macOS the menu belongs to the application, so only the menu of
+    /// `active_id` is installed; the others are rebuilt when their window
+    /// gains focus.
+    fn rebuild_menus(&mut self, active_id: WindowId) {
+        let AppState {
+            ref mut windows,
+            ref data,
+            ref env,
+            ..
+        } = self;
+        let Windows { state, windows } = windows;
+        for (id, window) in windows {
+            if cfg!(target_os = "macos") && *id != active_id {
+                continue;
+            }
+            if let (Some(state), Some(menu)) = (state.get(id), window.menu.as_mut()) {
+                state.handle.set_menu(menu.build_window_menu(data, env));
+            }
+        }
+        #[cfg(target_os = "macos")]
+        {
+            if let Some(menu) = self.dock_menu.as_mut() {
+                Application::set_dock_menu(Some(menu.build_window_menu(&self.data, &self.env)));
+            }
+        }
+    }
+
+    /// Move the commands submitted to a window from other threads into the
+    /// command queue.
+    fn take_ext_commands(&mut self, window_id: WindowId) {
+        let queue = match self.windows.state.get(&window_id) {
+            Some(state) => state.ext_queue.clone(),
+            None => return,
+        };
+        let mut queue = match queue.lock() {
+            Ok(queue) => queue,
+            Err(_) => return,
+        };
+        for ext in queue.drain(..) {
+            let target = ext.target.unwrap_or(window_id);
+            self.command_queue.push_back((target, ext.into_command()));
+        }
+    }
+
+    /// Returns `true` if `token` is the window's timer for a delayed frame.
+    fn take_frame_timer(&mut self, window_id: WindowId, token: TimerToken) -> bool {
+        match self.windows.state.get_mut(&window_id) {
+            Some(state) if state.frame_timer == Some(token) => {
+                state.frame_timer = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Run a window's idle tasks, until they finish or it is time to yield.
+    fn run_idle_tasks(&mut self, window_id: WindowId) {
+        let state = match self.windows.state.get_mut(&window_id) {
+            Some(state) => state,
+            None => return,
+        };
+        state
+            .idle
+            .run(state.prev_paint_time, &mut self.command_queue, window_id);
+        state.idle.schedule(&state.handle);
+    }
+
+    fn drop_effect(&self, window_id: WindowId) -> DropEffect {
+        self.windows
+            .state
+            .get(&window_id)
+            .map(|state| state.drag.drop_effect)
+            .unwrap_or(DropEffect::None)
+    }
+
+    fn set_safe_area(&mut self, window_id: WindowId, area: Rect, ctx: &mut dyn WinCtx) {
+        if let Some(mut win) = self.assemble_window_state(window_id) {
+            win.set_safe_area(area, ctx);
+        }
     }
 
     fn window_got_focus(&mut self, window_id: WindowId, _ctx: &mut dyn WinCtx) {
+        self.focused_window = Some(window_id);
+        self.command_queue
+            .push_back((window_id, sys_cmd::WINDOW_GOT_FOCUS.into()));
         self.assemble_window_state(window_id)
             .as_mut()
             .map(SingleWindowState::window_got_focus);
@@ -420,9 +1224,71 @@ impl<T: Data + 'static> DruidHandler<T> {
         result
     }
 
+    /// Write an input event to the recording, if one is being made.
+    fn record(&self, event: impl FnOnce() -> InputEvent) {
+        let mut state = self.app_state.borrow_mut();
+        if let Some(recorder) = state.recorder.as_mut() {
+            recorder.record(self.window_id, &event());
+        }
+    }
+
+    /// Request a timer for this window's next replayed event, if needed.
+    fn schedule_replay(&mut self, win_ctx: &mut dyn WinCtx) {
+        let mut state = self.app_state.borrow_mut();
+        if let Some(replayer) = state.replayer.as_mut() {
+            if let Some(deadline) = replayer.next_deadline(self.window_id) {
+                let token = win_ctx.request_timer(deadline);
+                replayer.set_timer(self.window_id, token);
+            }
+        }
+    }
+
+    /// Deliver the replayed events that are due, if `token` is the replay
+    /// timer. Returns `false` for other timers.
+    fn replay(&mut self, token: TimerToken, win_ctx: &mut dyn WinCtx) -> bool {
+        let due = match self.app_state.borrow_mut().replayer.as_mut() {
+            Some(replayer) => replayer.take_due(self.window_id, token),
+            None => None,
+        };
+        let due = match due {
+            Some(due) => due,
+            None => return false,
+        };
+        for event in due {
+            match event {
+                // The window itself stays the size it is.
+                InputEvent::Size(..) => (),
+                InputEvent::MouseDown(mouse) => self.mouse_down(&mouse, win_ctx),
+                InputEvent::MouseUp(mouse) => self.mouse_up(&mouse, win_ctx),
+                InputEvent::MouseMove(mouse) => self.mouse_move(&mouse, win_ctx),
+                InputEvent::Wheel(wheel) => self.wheel(&wheel, win_ctx),
+                InputEvent::KeyDown(key) => {
+                    self.key_down(key, win_ctx);
+                }
+                InputEvent::KeyUp(key) => self.key_up(key, win_ctx),
+                InputEvent::Command(id) => self.command(id, win_ctx),
+            }
+        }
+        self.schedule_replay(win_ctx);
+        true
+    }
+
+    /// Send a command to this window, as if a widget had submitted it.
+    #[cfg(not(any(target_os = "windows", target_arch = "wasm32")))]
+    pub(crate) fn submit_command(&mut self, cmd: Command, win_ctx: &mut dyn WinCtx) {
+        self.do_event(Event::Command(cmd), win_ctx);
+    }
+
     fn process_commands(&mut self, win_ctx: &mut dyn WinCtx) {
         loop {
-            let next_cmd = self.app_state.borrow_mut().command_queue.pop_front();
+            let next_cmd = {
+                let mut state = self.app_state.borrow_mut();
+                let next_cmd = state.command_queue.pop_front();
+                if let Some(log) = state.command_log.as_mut() {
+                    log.extend(next_cmd.iter().map(|(_, cmd)| cmd.clone()));
+                }
+                next_cmd
+            };
             match next_cmd {
                 Some((id, cmd)) => self.handle_cmd(id, cmd, win_ctx),
                 None => break,
@@ -447,16 +1313,31 @@ impl<T: Data + 'static> DruidHandler<T> {
     /// have their logic here; other commands are passed to the window.
     fn handle_cmd(&mut self, window_id: WindowId, cmd: Command, win_ctx: &mut dyn WinCtx) {
         //FIXME: we need some way of getting the correct `WinCtx` for this window.
+        if !self
+            .app_state
+            .borrow_mut()
+            .delegate_command(window_id, &cmd, win_ctx)
+        {
+            return;
+        }
         match &cmd.selector {
             &sys_cmd::OPEN_FILE => self.open_file(cmd, window_id, win_ctx),
-            &sys_cmd::NEW_WINDOW => self.new_window(cmd),
+            &sys_cmd::NEW_WINDOW => self.new_window(cmd, window_id),
+            &sys_cmd::SHOW_INSPECTOR => self.show_inspector(window_id),
             &sys_cmd::CLOSE_WINDOW => self.close_window(cmd, window_id),
             &sys_cmd::QUIT_APP => self.quit(),
             &sys_cmd::HIDE_APPLICATION => self.hide_app(),
             &sys_cmd::HIDE_OTHERS => self.hide_others(),
             &sys_cmd::PASTE => self.do_paste(window_id, win_ctx),
+            &sys_cmd::OPEN_URL => self.open_url(cmd),
+            &sys_cmd::SHOW_IN_FILE_MANAGER => self.show_in_file_manager(cmd),
+            &sys_cmd::SET_DOCK_MENU => self.set_dock_menu(cmd),
+            &sys_cmd::SET_BADGE_LABEL => self.set_badge_label(cmd),
+            &sys_cmd::REQUEST_ATTENTION => self.request_attention(cmd),
+            &sys_cmd::ADD_RECENT_DOCUMENT => self.add_recent_document(cmd),
+            &sys_cmd::SET_LOCALE => self.set_locale(cmd, window_id, win_ctx),
             sel => {
-                info!("handle_cmd {}", sel);
+                debug!("handle_cmd {}", sel);
                 let event = Event::Command(cmd);
                 self.app_state
                     .borrow_mut()
@@ -466,6 +1347,13 @@ impl<T: Data + 'static> DruidHandler<T> {
     }
 
     fn open_file(&mut self, cmd: Command, window_id: WindowId, win_ctx: &mut dyn WinCtx) {
+        if let Some(path) = cmd.get_object::<PathBuf>() {
+            let event = Event::OpenFile(FileInfo { path: path.clone() });
+            self.app_state
+                .borrow_mut()
+                .do_event(window_id, event, win_ctx);
+            return;
+        }
         let options = cmd
             .get_object::<FileDialogOptions>()
             .map(|opts| opts.to_owned())
@@ -479,7 +1367,7 @@ impl<T: Data + 'static> DruidHandler<T> {
         }
     }
 
-    fn new_window(&mut self, cmd: Command) {
+    fn new_window(&mut self, cmd: Command, window_id: WindowId) {
         let desc = match cmd.get_object::<WindowDesc<T>>() {
             Some(wd) => wd,
             None => {
@@ -495,14 +1383,42 @@ impl<T: Data + 'static> DruidHandler<T> {
                 return;
             }
         };
+        let size = window.get_frame().size();
+        let position = self
+            .app_state
+            .borrow()
+            .new_window_position(window_id, desc.placement, size);
+        if let Some(position) = position {
+            window.set_position(position);
+        }
         window.show();
     }
 
+    fn show_inspector(&mut self, window_id: WindowId) {
+        let tree = match self.app_state.borrow_mut().inspect(window_id) {
+            Some(tree) => tree,
+            None => return,
+        };
+        let desc = inspector::window(tree, window_id);
+        match desc.build_native(&self.app_state) {
+            Ok(win) => win.show(),
+            Err(e) => error!("failed to create inspector window: '{:?}'", e),
+        }
+    }
+
     fn close_window(&mut self, cmd: Command, window_id: WindowId) {
         let id = cmd.get_object().unwrap_or(&window_id);
         let handle = self.app_state.borrow_mut().remove_window(*id);
         if let Some(handle) = handle {
             handle.close();
+            self.quit_if_no_windows();
+        }
+    }
+
+    fn quit_if_no_windows(&self) {
+        let should_quit = self.app_state.borrow().should_quit();
+        if should_quit {
+            self.quit();
         }
     }
 
@@ -513,6 +1429,101 @@ impl<T: Data + 'static> DruidHandler<T> {
         }
     }
 
+    fn open_url(&self, cmd: Command) {
+        match cmd.get_object::<String>() {
+            Some(url) => Application::open_url(url),
+            None => warn!("open-url command is missing url"),
+        }
+    }
+
+    fn show_in_file_manager(&self, cmd: Command) {
+        match cmd.get_object::<PathBuf>() {
+            Some(path) => Application::reveal_in_file_manager(path),
+            None => warn!("show-in-file-manager command is missing path"),
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    fn set_dock_menu(&mut self, cmd: Command) {
+        let mut menu = match cmd.get_object::<MenuDesc<T>>() {
+            Some(menu) => menu.to_owned(),
+            None => {
+                warn!("set-dock-menu command is missing menu object");
+                return;
+            }
+        };
+        let mut state = self.app_state.borrow_mut();
+        let platform_menu = menu.build_window_menu(&state.data, &state.env);
+        Application::set_dock_menu(Some(platform_menu));
+        state.dock_menu = Some(menu);
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    fn set_dock_menu(&mut self, _cmd: Command) {
+        warn!("set-dock-menu is only supported on macOS");
+    }
+
+    fn set_badge_label(&self, cmd: Command) {
+        let label = match cmd.get_object::<String>() {
+            Some(label) => label,
+            None => {
+                warn!("set-badge-label command is missing label");
+                return;
+            }
+        };
+        #[cfg(target_os = "macos")]
+        Application::set_badge_label(Some(label.as_str()).filter(|s| !s.is_empty()));
+        #[cfg(not(target_os = "macos"))]
+        let _ = label;
+    }
+
+    fn request_attention(&self, cmd: Command) {
+        let critical = cmd.get_object::<bool>().cloned().unwrap_or(false);
+        #[cfg(target_os = "macos")]
+        Application::request_user_attention(critical);
+        #[cfg(not(target_os = "macos"))]
+        let _ = critical;
+    }
+
+    fn add_recent_document(&self, cmd: Command) {
+        match cmd.get_object::<PathBuf>() {
+            #[cfg(target_os = "windows")]
+            Some(path) => Application::add_recent_document(path),
+            #[cfg(not(target_os = "windows"))]
+            Some(_) => (),
+            None => warn!("add-recent-document command is missing path"),
+        }
+    }
+
+    fn set_locale(&mut self, cmd: Command, window_id: WindowId, win_ctx: &mut dyn WinCtx) {
+        let locale = match cmd.get_object::<String>().map(|s| s.parse()) {
+            Some(Ok(locale)) => locale,
+            Some(Err(_)) => {
+                warn!("set-locale command has an invalid language tag");
+                return;
+            }
+            None => {
+                warn!("set-locale command is missing language tag");
+                return;
+            }
+        };
+        let mut state = self.app_state.borrow_mut();
+        state.env.set_locale(locale);
+        state.rebuild_menus(window_id);
+        // Delivering the command also updates every window with the new env.
+        state.do_event(window_id, Event::Command(cmd), win_ctx);
+    }
+
+    fn update_accessibility(&mut self, win_ctx: &mut dyn WinCtx) {
+        let mut state = self.app_state.borrow_mut();
+        state.env.set_high_contrast(Application::is_high_contrast());
+        state
+            .env
+            .set_reduced_motion(Application::prefers_reduced_motion());
+        let cmd = Command::from(sys_cmd::ACCESSIBILITY_CHANGED);
+        state.do_event(self.window_id, Event::Command(cmd), win_ctx);
+    }
+
     fn quit(&self) {
         Application::quit()
     }
@@ -536,55 +1547,151 @@ impl<T: Data + 'static> WinHandler for DruidHandler<T> {
     }
 
     fn paint(&mut self, piet: &mut Piet, ctx: &mut dyn WinCtx) -> bool {
-        self.app_state.borrow_mut().paint(self.window_id, piet, ctx)
+        let result = self.app_state.borrow_mut().paint(self.window_id, piet, ctx);
+        self.schedule_replay(ctx);
+        // Deliver anything submitted while painting, such as a description
+        // of the widgets.
+        self.process_commands(ctx);
+        result
     }
 
     fn size(&mut self, width: u32, height: u32, ctx: &mut dyn WinCtx) {
+        self.record(|| InputEvent::Size(width, height));
         let event = Event::Size(Size::new(width as f64, height as f64));
         self.do_event(event, ctx);
     }
 
+    fn safe_area(&mut self, area: Rect, ctx: &mut dyn WinCtx) {
+        self.app_state
+            .borrow_mut()
+            .set_safe_area(self.window_id, area, ctx);
+    }
+
     fn command(&mut self, id: u32, ctx: &mut dyn WinCtx) {
+        self.record(|| InputEvent::Command(id));
         self.handle_system_cmd(id, ctx);
     }
 
     fn mouse_down(&mut self, event: &MouseEvent, ctx: &mut dyn WinCtx) {
-        // TODO: double-click detection (or is this done in druid-shell?)
+        self.record(|| InputEvent::MouseDown(event.clone()));
         let event = Event::MouseDown(event.clone());
         self.do_event(event, ctx);
     }
 
     fn mouse_up(&mut self, event: &MouseEvent, ctx: &mut dyn WinCtx) {
+        self.record(|| InputEvent::MouseUp(event.clone()));
         let event = Event::MouseUp(event.clone());
         self.do_event(event, ctx);
     }
 
     fn mouse_move(&mut self, event: &MouseEvent, ctx: &mut dyn WinCtx) {
+        self.record(|| InputEvent::MouseMove(event.clone()));
         let event = Event::MouseMoved(event.clone());
         self.do_event(event, ctx);
     }
 
+    fn pen(&mut self, event: &PenEvent, ctx: &mut dyn WinCtx) {
+        self.do_event(Event::Pen(event.clone()), ctx);
+    }
+
     fn key_down(&mut self, event: KeyEvent, ctx: &mut dyn WinCtx) -> bool {
+        self.record(|| InputEvent::KeyDown(event));
         self.do_event(Event::KeyDown(event), ctx)
     }
 
     fn key_up(&mut self, event: KeyEvent, ctx: &mut dyn WinCtx) {
+        self.record(|| InputEvent::KeyUp(event));
         self.do_event(Event::KeyUp(event), ctx);
     }
 
-    fn wheel(&mut self, delta: Vec2, mods: KeyModifiers, ctx: &mut dyn WinCtx) {
-        let event = Event::Wheel(WheelEvent { delta, mods });
-        self.do_event(event, ctx);
+    fn ime(&mut self, event: ImeEvent, ctx: &mut dyn WinCtx) {
+        self.do_event(Event::Ime(event), ctx);
+    }
+
+    fn wheel(&mut self, event: &WheelEvent, ctx: &mut dyn WinCtx) {
+        self.record(|| InputEvent::Wheel(event.clone()));
+        self.do_event(Event::Wheel(event.clone()), ctx);
+    }
+
+    fn drag_enter(&mut self, event: &PlatformDragEvent, ctx: &mut dyn WinCtx) -> DropEffect {
+        self.do_event(Event::DragEnter(event.clone().into()), ctx);
+        self.app_state.borrow().drop_effect(self.window_id)
+    }
+
+    fn drag_over(&mut self, event: &PlatformDragEvent, ctx: &mut dyn WinCtx) -> DropEffect {
+        self.do_event(Event::DragOver(event.clone().into()), ctx);
+        self.app_state.borrow().drop_effect(self.window_id)
+    }
+
+    fn drag_leave(&mut self, ctx: &mut dyn WinCtx) {
+        self.do_event(Event::DragLeave, ctx);
+    }
+
+    fn drag_drop(&mut self, event: &PlatformDragEvent, ctx: &mut dyn WinCtx) -> bool {
+        self.do_event(Event::Drop(event.clone().into()), ctx)
+    }
+
+    fn drag_end(&mut self, effect: DropEffect, ctx: &mut dyn WinCtx) {
+        self.do_event(Event::DragEnd(effect), ctx);
+    }
+
+    fn destroy(&mut self, ctx: &mut dyn WinCtx) {
+        // the window may have been closed by the user, rather than by a command
+        let handle = self.app_state.borrow_mut().remove_window(self.window_id);
+        self.process_commands(ctx);
+        if handle.is_some() {
+            self.quit_if_no_windows();
+        }
     }
 
     fn got_focus(&mut self, ctx: &mut dyn WinCtx) {
         self.app_state
             .borrow_mut()
             .window_got_focus(self.window_id, ctx);
+        self.process_commands(ctx);
+    }
+
+    fn accessibility_changed(&mut self, ctx: &mut dyn WinCtx) {
+        self.update_accessibility(ctx);
+        self.process_commands(ctx);
     }
 
     fn timer(&mut self, token: TimerToken, ctx: &mut dyn WinCtx) {
-        self.do_event(Event::Timer(token), ctx);
+        if self
+            .app_state
+            .borrow_mut()
+            .take_frame_timer(self.window_id, token)
+        {
+            ctx.invalidate();
+        } else if !self.replay(token, ctx) {
+            self.do_event(Event::Timer(token), ctx);
+        }
+    }
+
+    fn idle(&mut self, token: IdleToken, ctx: &mut dyn WinCtx) {
+        if token == EXT_COMMAND_TOKEN {
+            self.app_state
+                .borrow_mut()
+                .take_ext_commands(self.window_id);
+            self.process_commands(ctx);
+        } else if token == IDLE_TASK_TOKEN {
+            self.app_state.borrow_mut().run_idle_tasks(self.window_id);
+            self.process_commands(ctx);
+        } else {
+            self.do_event(Event::Idle(token), ctx);
+        }
+    }
+
+    fn suspended(&mut self, ctx: &mut dyn WinCtx) {
+        #[cfg(feature = "persistence")]
+        {
+            self.app_state.borrow_mut().save_state();
+        }
+        self.do_event(Event::Command(sys_cmd::APP_SUSPENDED.into()), ctx);
+    }
+
+    fn resumed(&mut self, ctx: &mut dyn WinCtx) {
+        self.do_event(Event::Command(sys_cmd::APP_RESUMED.into()), ctx);
     }
 
     fn as_any(&mut self) -> &mut dyn Any {
@@ -30,11 +30,36 @@ pub struct WindowId(u32);
 
 static WINDOW_ID_COUNTER: AtomicU32 = AtomicU32::new(1);
 
+/// How often a window paints while it is animating.
+///
+/// This is set with the [`SET_FRAME_RATE`] command.
+///
+/// [`SET_FRAME_RATE`]: command/sys/constant.SET_FRAME_RATE.html
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FrameRate {
+    /// Once per refresh of the display. This is the default.
+    Vsync,
+    /// At most the given number of frames per second, still in step with the
+    /// display. This saves power when smooth animation isn't needed.
+    ///
+    /// Rates below one frame per second are raised to one.
+    Capped(f64),
+    /// As often as frames can be painted, without waiting for the display.
+    ///
+    /// This is mostly useful for measuring performance. Some platforms
+    /// always wait for the display, in which case this is the same as
+    /// `Vsync`.
+    Uncapped,
+}
+
 /// Per-window state not owned by user code.
 pub struct Window<T: Data> {
     pub(crate) root: WidgetPod<T, Box<dyn Widget<T>>>,
     pub(crate) title: LocalizedString<T>,
     size: Size,
+    /// The part of the window not obscured by system UI, if the platform
+    /// reports one.
+    pub(crate) safe_area: Option<Rect>,
     pub(crate) menu: Option<MenuDesc<T>>,
     pub(crate) context_menu: Option<MenuDesc<T>>,
     // delegate?
@@ -49,6 +74,7 @@ impl<T: Data> Window<T> {
         Window {
             root: WidgetPod::new(Box::new(root)),
             size: Size::ZERO,
+            safe_area: None,
             title,
             menu,
             context_menu: None,
@@ -72,10 +98,18 @@ impl<T: Data> Window<T> {
     }
 
     pub fn layout(&mut self, layout_ctx: &mut LayoutCtx, data: &T, env: &Env) {
-        let bc = BoxConstraints::tight(self.size);
+        let area = self
+            .safe_area
+            .unwrap_or_else(|| Rect::from_origin_size(Point::ORIGIN, self.size));
+        let bc = BoxConstraints::tight(area.size());
         let size = self.root.layout(layout_ctx, &bc, data, env);
         self.root
-            .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+            .set_layout_rect(Rect::from_origin_size(area.origin(), size));
+    }
+
+    /// The size of the window's content area.
+    pub(crate) fn size(&self) -> Size {
+        self.size
     }
 
     pub fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &T, env: &Env) {
@@ -88,6 +122,16 @@ impl<T: Data> Window<T> {
         }
     }
 
+    /// Rebuild the platform menu if the state of any of its items depends
+    /// on the data, and has changed.
+    pub(crate) fn update_menu(&mut self, win_handle: &WindowHandle, data: &T, env: &Env) {
+        if let Some(menu) = self.menu.as_mut() {
+            if menu.needs_rebuild(data, env) {
+                win_handle.set_menu(menu.build_window_menu(data, env));
+            }
+        }
+    }
+
     pub(crate) fn get_menu_cmd(&self, cmd_id: u32) -> Option<Command> {
         self.context_menu
             .as_ref()